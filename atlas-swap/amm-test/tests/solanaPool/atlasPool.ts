@@ -25,10 +25,18 @@ export const setGlobalStateInstruction = (
   fee_base_lp: number,
   fee_base_owner: number,
   fee_deno: number,
+  dynamic_fee_min: number = 0,
+  dynamic_fee_max: number = 0,
+  fee_tiers: { threshold_bps: number; fixed_fee_numerator: number }[] = [],
+  minimum_fee: number = 0,
+  discount_mint: PublicKey = new PublicKey('11111111111111111111111111111111'),
+  discount_minimum_balance: number = 0,
+  discount_bps: number = 0,
+  quoter: PublicKey = new PublicKey('11111111111111111111111111111111'),
 ): TransactionInstruction => {
   const keys = [
     { pubkey: state_key, isSigner: false, isWritable: true }, // state info needs to be added
-    { pubkey: owner, isSigner: false, isWritable: false }, // current info
+    { pubkey: owner, isSigner: true, isWritable: false }, // current info
     { pubkey: new PublicKey('11111111111111111111111111111111'), isSigner: false, isWritable: false }, // system info
     { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: true }, // rent info
   ]
@@ -44,7 +52,20 @@ export const setGlobalStateInstruction = (
     BufferLayout.nu64('stable_return_fee_numerator'),
     BufferLayout.nu64('stable_fixed_fee_numerator'),
     BufferLayout.nu64('fee_denominator'),
+    BufferLayout.nu64('dynamic_fee_min_numerator'),
+    BufferLayout.nu64('dynamic_fee_max_numerator'),
+    BufferLayout.nu64('fee_tier_0_threshold_bps'),
+    BufferLayout.nu64('fee_tier_0_fixed_fee_numerator'),
+    BufferLayout.nu64('fee_tier_1_threshold_bps'),
+    BufferLayout.nu64('fee_tier_1_fixed_fee_numerator'),
+    BufferLayout.nu64('minimum_fee'),
+    BufferLayout.blob(32, 'discount_mint'),
+    BufferLayout.nu64('discount_minimum_balance'),
+    BufferLayout.nu64('discount_bps'),
+    BufferLayout.blob(32, 'quoter'),
   ])
+  // at most two tiers, the ones left out disabled
+  const tier = (i: number) => fee_tiers[i] || { threshold_bps: 0, fixed_fee_numerator: 0 }
 
   let data = Buffer.alloc(1024)
   {
@@ -60,6 +81,17 @@ export const setGlobalStateInstruction = (
         stable_return_fee_numerator: fee_base_lp,
         stable_fixed_fee_numerator: fee_base_owner,
         fee_denominator: fee_deno,
+        dynamic_fee_min_numerator: dynamic_fee_min,
+        dynamic_fee_max_numerator: dynamic_fee_max,
+        fee_tier_0_threshold_bps: tier(0).threshold_bps,
+        fee_tier_0_fixed_fee_numerator: tier(0).fixed_fee_numerator,
+        fee_tier_1_threshold_bps: tier(1).threshold_bps,
+        fee_tier_1_fixed_fee_numerator: tier(1).fixed_fee_numerator,
+        minimum_fee: minimum_fee,
+        discount_mint: discount_mint.toBuffer(),
+        discount_minimum_balance: discount_minimum_balance,
+        discount_bps: discount_bps,
+        quoter: quoter.toBuffer(),
       },
       data,
     )
@@ -0,0 +1,133 @@
+//! Decoding of fetched accounts and simulation results, shared by the
+//! blocking and async clients
+
+use crate::error::ClientError;
+use atlas_swap::{
+    state::{GlobalState, GlobalStateVersion, SwapState, SwapVersion},
+    token_2022::{self, is_token_program},
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionConfig},
+    rpc_filter::RpcFilterType,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::Account, instruction::Instruction, pubkey::Pubkey, transaction::Transaction,
+};
+use std::{convert::TryInto, str::FromStr};
+
+/// Outcome of a successful simulation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+    /// Program logs
+    pub logs: Vec<String>,
+    /// Compute units consumed, when the node reports them
+    pub units_consumed: Option<u64>,
+    /// Program that set the return data, and the data
+    pub return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+/// A Token-swap account and its address
+pub type Pool = (Pubkey, Box<dyn SwapState>);
+
+/// Unpack a Token-swap account
+pub fn unpack_swap(pubkey: &Pubkey, account: &Account) -> Result<Box<dyn SwapState>, ClientError> {
+    if account.owner != atlas_swap::id() {
+        return Err(ClientError::InvalidAccountData(*pubkey));
+    }
+    SwapVersion::unpack(&account.data).map_err(|_| ClientError::InvalidAccountData(*pubkey))
+}
+
+/// Swaps among program accounts matched by a filter, dropping other
+/// accounts that happened to match
+pub fn unpack_swaps(accounts: Vec<(Pubkey, Account)>) -> Vec<Pool> {
+    accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            let swap = unpack_swap(&pubkey, &account).ok()?;
+            Some((pubkey, swap)).filter(|(_, swap)| swap.is_initialized())
+        })
+        .collect()
+}
+
+/// getProgramAccounts config of `filters`, with the data base64 encoded
+pub fn program_accounts_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+/// Unpack the global state account
+pub fn unpack_global_state(pubkey: &Pubkey, account: &Account) -> Result<GlobalState, ClientError> {
+    if account.owner != atlas_swap::id() {
+        return Err(ClientError::InvalidAccountData(*pubkey));
+    }
+    GlobalStateVersion::unpack(&account.data).map_err(|_| ClientError::InvalidAccountData(*pubkey))
+}
+
+/// Unpack a token account of either token program
+pub fn unpack_token_account(
+    pubkey: &Pubkey,
+    account: &Account,
+) -> Result<spl_token::state::Account, ClientError> {
+    if !is_token_program(&account.owner) {
+        return Err(ClientError::InvalidAccountData(*pubkey));
+    }
+    token_2022::unpack_account(&account.data).map_err(|_| ClientError::InvalidAccountData(*pubkey))
+}
+
+/// Unsigned transaction of `instructions` to simulate, paid by `payer`
+pub fn preflight_transaction(instructions: &[Instruction], payer: &Pubkey) -> Transaction {
+    Transaction::new_with_payer(instructions, Some(payer))
+}
+
+/// Simulation config of a preflight transaction: signatures are not
+/// checked and the node fills in a recent blockhash
+pub fn preflight_config() -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        ..RpcSimulateTransactionConfig::default()
+    }
+}
+
+/// Outcome of a simulation, its error if it failed
+pub fn simulation(result: RpcSimulateTransactionResult) -> Result<Simulation, ClientError> {
+    let logs = result.logs.unwrap_or_default();
+    if let Some(error) = result.err {
+        return Err(ClientError::SimulationFailed { error, logs });
+    }
+    let return_data = match result.return_data {
+        Some(return_data) => {
+            let program_id = Pubkey::from_str(&return_data.program_id)
+                .map_err(|_| ClientError::InvalidReturnData)?;
+            // return data is always base64 encoded
+            let (data, _encoding) = return_data.data;
+            let data = base64::decode(data).map_err(|_| ClientError::InvalidReturnData)?;
+            Some((program_id, data))
+        }
+        None => None,
+    };
+    Ok(Simulation {
+        logs,
+        units_consumed: result.units_consumed,
+        return_data,
+    })
+}
+
+/// Token A and B amounts of a simulated QuoteWithdraw
+pub fn unpack_withdraw_quote(simulation: &Simulation) -> Result<(u64, u64), ClientError> {
+    match &simulation.return_data {
+        Some((program_id, data)) if *program_id == atlas_swap::id() && data.len() == 16 => Ok((
+            u64::from_le_bytes(data[..8].try_into().unwrap()),
+            u64::from_le_bytes(data[8..].try_into().unwrap()),
+        )),
+        _ => Err(ClientError::InvalidReturnData),
+    }
+}
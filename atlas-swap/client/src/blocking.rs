@@ -0,0 +1,139 @@
+//! Client over the blocking `RpcClient`
+
+use crate::{
+    accounts::{self, Pool, Simulation},
+    error::ClientError,
+    filters::{swap_mint, MintSide},
+    retry::{is_retryable, RetryConfig},
+};
+use atlas_swap::{
+    instruction::{quote_withdraw, QuoteWithdraw},
+    pda::find_global_state,
+    state::{GlobalState, SwapState},
+};
+use solana_client::{client_error::ClientError as RpcClientError, rpc_client::RpcClient};
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
+
+/// Blocking client of an atlas-swap deployment
+pub struct AtlasClient {
+    rpc: RpcClient,
+    retry: RetryConfig,
+}
+
+impl AtlasClient {
+    /// Client over `rpc`, retrying with the default config
+    pub fn new(rpc: RpcClient) -> Self {
+        Self::with_retry(rpc, RetryConfig::default())
+    }
+
+    /// Client over `rpc`, retrying as set by `retry`
+    pub fn with_retry(rpc: RpcClient, retry: RetryConfig) -> Self {
+        Self { rpc, retry }
+    }
+
+    /// The wrapped RPC client, for requests this client does not cover
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Run `request` until it succeeds, fails with an error that is not
+    /// transient, or runs out of attempts. The request boxes its error, as
+    /// the RPC error is too large to return by value.
+    fn with_retries<T>(
+        &self,
+        request: impl Fn() -> Result<T, Box<RpcClientError>>,
+    ) -> Result<T, ClientError> {
+        let mut attempt = 0;
+        loop {
+            match request() {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    attempt += 1;
+                    match self.retry.backoff(attempt) {
+                        Some(backoff) if is_retryable(&error) => std::thread::sleep(backoff),
+                        _ => return Err(ClientError::Rpc(error)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetch an account, retrying transport failures
+    pub fn get_account(&self, pubkey: &Pubkey) -> Result<Account, ClientError> {
+        self.with_retries(|| self.rpc.get_account(pubkey).map_err(Box::new))
+    }
+
+    /// Fetch and unpack a Token-swap account
+    pub fn get_swap(&self, pubkey: &Pubkey) -> Result<Box<dyn SwapState>, ClientError> {
+        accounts::unpack_swap(pubkey, &self.get_account(pubkey)?)
+    }
+
+    /// Fetch and unpack the global state account
+    pub fn get_global_state(&self, pubkey: &Pubkey) -> Result<GlobalState, ClientError> {
+        accounts::unpack_global_state(pubkey, &self.get_account(pubkey)?)
+    }
+
+    /// Fetch and unpack a token account of either token program
+    pub fn get_token_account(
+        &self,
+        pubkey: &Pubkey,
+    ) -> Result<spl_token::state::Account, ClientError> {
+        accounts::unpack_token_account(pubkey, &self.get_account(pubkey)?)
+    }
+
+    /// Pools of either version with `mint` on `side`, found with a single
+    /// filtered getProgramAccounts request
+    pub fn get_pools_with_mint(
+        &self,
+        mint: &Pubkey,
+        side: MintSide,
+    ) -> Result<Vec<Pool>, ClientError> {
+        let accounts = self
+            .with_retries(|| {
+                self.rpc.get_program_accounts_with_config(
+                    &atlas_swap::id(),
+                    accounts::program_accounts_config(vec![swap_mint(mint, side)]),
+                )
+                .map_err(Box::new)
+            })?;
+        Ok(accounts::unpack_swaps(accounts))
+    }
+
+    /// Simulate `instructions` paid by `payer` without signing them, to
+    /// catch failures before a transaction is sent
+    pub fn simulate(
+        &self,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+    ) -> Result<Simulation, ClientError> {
+        let transaction = accounts::preflight_transaction(instructions, payer);
+        let result = self.with_retries(|| {
+            self.rpc
+                .simulate_transaction_with_config(&transaction, accounts::preflight_config())
+                .map_err(Box::new)
+        })?;
+        accounts::simulation(result.value)
+    }
+
+    /// Token A and B amounts withdrawn by burning `pool_token_amount` from
+    /// the pool at `swap_pubkey`, quoted by simulation
+    pub fn quote_withdraw(
+        &self,
+        swap_pubkey: &Pubkey,
+        payer: &Pubkey,
+        pool_token_amount: u64,
+    ) -> Result<(u64, u64), ClientError> {
+        let swap = self.get_swap(swap_pubkey)?;
+        let instruction = quote_withdraw(
+            &atlas_swap::id(),
+            swap_pubkey,
+            &find_global_state(&atlas_swap::id()).0,
+            swap.pool_mint(),
+            swap.token_a_account(),
+            swap.token_b_account(),
+            QuoteWithdraw { pool_token_amount },
+        )
+        .map_err(|_| ClientError::InvalidAccountData(*swap_pubkey))?;
+        accounts::unpack_withdraw_quote(&self.simulate(&[instruction], payer)?)
+    }
+}
@@ -0,0 +1,54 @@
+//! Error types
+
+use atlas_swap::error::SwapError;
+use solana_client::client_error::ClientError as RpcClientError;
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, transaction::TransactionError,
+};
+use thiserror::Error;
+
+/// Errors that may be returned by the client.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The RPC request failed, after any retries. Boxed, as the RPC error
+    /// is many times the size of the other variants.
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<RpcClientError>),
+    /// The account is not owned by the expected program or does not hold
+    /// the expected state.
+    #[error("Invalid data in account {0}")]
+    InvalidAccountData(Pubkey),
+    /// The simulated transaction failed.
+    #[error("Simulation failed: {error}")]
+    SimulationFailed {
+        /// Error of the transaction
+        error: TransactionError,
+        /// Program logs up to the failure
+        logs: Vec<String>,
+    },
+    /// The simulated transaction returned no data, or data of another
+    /// program or layout.
+    #[error("Invalid return data")]
+    InvalidReturnData,
+}
+
+impl From<RpcClientError> for ClientError {
+    fn from(error: RpcClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
+
+impl ClientError {
+    /// Error of the swap program a simulation failed with. Token program
+    /// errors of the program's invocations share the instruction error, but
+    /// their codes are below the range of the program.
+    pub fn swap_error(&self) -> Option<SwapError> {
+        match self {
+            Self::SimulationFailed {
+                error: TransactionError::InstructionError(_, InstructionError::Custom(code)),
+                ..
+            } => SwapError::from_code(*code),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! getProgramAccounts filters over the account layouts of the program
+
+use atlas_swap::state::{
+    SWAP_V1_ACCOUNT_LEN, SWAP_V1_POOL_MINT_OFFSET, SWAP_V1_TOKEN_A_MINT_OFFSET,
+    SWAP_V1_TOKEN_B_MINT_OFFSET, SWAP_V1_VERSION, SWAP_V2_ACCOUNT_LEN, SWAP_V2_VERSION,
+    SWAP_VERSION_OFFSET,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+/// Side of a pool a mint is filtered on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MintSide {
+    /// Token A mint
+    TokenA,
+    /// Token B mint
+    TokenB,
+    /// Pool token mint
+    Pool,
+}
+
+impl MintSide {
+    /// Offset of the mint in a swap account of either version
+    pub fn offset(self) -> usize {
+        match self {
+            Self::TokenA => SWAP_V1_TOKEN_A_MINT_OFFSET,
+            Self::TokenB => SWAP_V1_TOKEN_B_MINT_OFFSET,
+            Self::Pool => SWAP_V1_POOL_MINT_OFFSET,
+        }
+    }
+}
+
+/// Filters matching every SwapV1 account
+pub fn swap_v1() -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(SWAP_V1_ACCOUNT_LEN as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            SWAP_VERSION_OFFSET,
+            vec![SWAP_V1_VERSION],
+        )),
+    ]
+}
+
+/// Filters matching every SwapV2 account
+pub fn swap_v2() -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::DataSize(SWAP_V2_ACCOUNT_LEN as u64),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+            SWAP_VERSION_OFFSET,
+            vec![SWAP_V2_VERSION],
+        )),
+    ]
+}
+
+/// Filter matching swap accounts of either version with `mint` on `side`.
+/// Other accounts of the program may hold the same bytes at that offset, so
+/// matches are unpacked as swaps before they are used.
+pub fn swap_mint(mint: &Pubkey, side: MintSide) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(side.offset(), mint.to_bytes().to_vec()))
+}
+
+/// Filters matching the swap accounts of one version, `swap_v1()` or
+/// `swap_v2()`, with `mint` on `side`
+pub fn with_swap_mint(
+    mut version: Vec<RpcFilterType>,
+    mint: &Pubkey,
+    side: MintSide,
+) -> Vec<RpcFilterType> {
+    version.push(swap_mint(mint, side));
+    version
+}
@@ -0,0 +1,18 @@
+#![deny(missing_docs)]
+
+//! RPC client for atlas-swap pools. The `blocking` feature, on by default,
+//! wraps the blocking `RpcClient` for CLIs; the `async` feature wraps the
+//! nonblocking one on tokio for bots. Both retry account fetches with
+//! backoff and simulate transactions before they are sent.
+
+pub mod accounts;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod error;
+pub mod filters;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod retry;
+
+// Export the swap program so downstream users share its types
+pub use atlas_swap;
@@ -0,0 +1,47 @@
+//! Retry with exponential backoff of RPC requests
+
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use std::time::Duration;
+
+/// How often and how long to retry a failed RPC request
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryConfig {
+    /// Retries after the first attempt, zero to never retry
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each following one
+    pub initial_backoff: Duration,
+    /// Longest wait between two attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Wait before the retry following `attempt` failed attempts, none once
+    /// the retries are used up
+    pub fn backoff(&self, attempt: u32) -> Option<Duration> {
+        if attempt == 0 || attempt > self.max_retries {
+            return None;
+        }
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.checked_pow(attempt - 1).unwrap_or(u32::MAX))
+            .unwrap_or(self.max_backoff);
+        Some(std::cmp::min(backoff, self.max_backoff))
+    }
+}
+
+/// Whether a request failing with `error` may succeed when sent again: the
+/// transport failed or the node rate limited it, rather than the node
+/// rejecting it
+pub fn is_retryable(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_))
+}
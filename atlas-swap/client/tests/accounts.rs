@@ -0,0 +1,108 @@
+//! Fetched accounts decode only when the program owns them in a layout it
+//! accepts, and RPC retries back off up to their limit.
+
+use atlas_swap::state::{GlobalState, GlobalStateVersion, SwapV2, SwapVersion};
+use atlas_swap_client::{
+    accounts::{unpack_global_state, unpack_swap, unpack_swaps, unpack_withdraw_quote, Simulation},
+    error::ClientError,
+    retry::RetryConfig,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::time::Duration;
+
+fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn swap_account(is_initialized: bool) -> Account {
+    let mut data = vec![0; SwapVersion::LATEST_LEN];
+    SwapVersion::pack(
+        SwapVersion::SwapV2(SwapV2 {
+            is_initialized,
+            ..SwapV2::default()
+        }),
+        &mut data,
+    )
+    .unwrap();
+    account(atlas_swap::id(), data)
+}
+
+#[test]
+fn swaps_decode_only_from_program_accounts() {
+    let pubkey = Pubkey::new_unique();
+    let swap = swap_account(true);
+    assert!(unpack_swap(&pubkey, &swap).unwrap().is_initialized());
+
+    let other_owner = account(Pubkey::new_unique(), swap.data.clone());
+    assert!(matches!(
+        unpack_swap(&pubkey, &other_owner),
+        Err(ClientError::InvalidAccountData(key)) if key == pubkey
+    ));
+    let short = account(atlas_swap::id(), vec![1; 10]);
+    assert!(unpack_swap(&pubkey, &short).is_err());
+
+    // matches of a filter that are not initialized swaps are dropped
+    let swaps = unpack_swaps(vec![
+        (pubkey, swap),
+        (Pubkey::new_unique(), swap_account(false)),
+        (Pubkey::new_unique(), other_owner),
+        (Pubkey::new_unique(), short),
+    ]);
+    assert_eq!(swaps.len(), 1);
+    assert_eq!(swaps[0].0, pubkey);
+}
+
+#[test]
+fn global_state_decodes_in_the_latest_layout() {
+    let pubkey = Pubkey::new_unique();
+    let state = GlobalState {
+        is_initialized: true,
+        owner: Pubkey::new_unique(),
+        ..GlobalState::default()
+    };
+    let mut data = vec![0; GlobalStateVersion::LATEST_LEN];
+    GlobalStateVersion::pack(&state, &mut data).unwrap();
+    assert_eq!(
+        unpack_global_state(&pubkey, &account(atlas_swap::id(), data.clone())).unwrap(),
+        state
+    );
+    assert!(unpack_global_state(&pubkey, &account(Pubkey::new_unique(), data)).is_err());
+}
+
+#[test]
+fn withdraw_quotes_decode_from_the_program_return_data() {
+    let mut data = 7u64.to_le_bytes().to_vec();
+    data.extend_from_slice(&9u64.to_le_bytes());
+    let simulation = |program_id, data| Simulation {
+        logs: vec![],
+        units_consumed: None,
+        return_data: Some((program_id, data)),
+    };
+    assert_eq!(
+        unpack_withdraw_quote(&simulation(atlas_swap::id(), data.clone())).unwrap(),
+        (7, 9)
+    );
+    assert!(unpack_withdraw_quote(&simulation(Pubkey::new_unique(), data.clone())).is_err());
+    assert!(unpack_withdraw_quote(&simulation(atlas_swap::id(), data[..8].to_vec())).is_err());
+}
+
+#[test]
+fn retries_back_off_up_to_the_limit() {
+    let config = RetryConfig {
+        max_retries: 4,
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(500),
+    };
+    assert_eq!(config.backoff(0), None);
+    assert_eq!(config.backoff(1), Some(Duration::from_millis(100)));
+    assert_eq!(config.backoff(2), Some(Duration::from_millis(200)));
+    assert_eq!(config.backoff(3), Some(Duration::from_millis(400)));
+    assert_eq!(config.backoff(4), Some(Duration::from_millis(500)));
+    assert_eq!(config.backoff(5), None);
+}
@@ -0,0 +1,31 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+use thiserror::Error;
+
+/// Errors that may be returned by the vault rebalancer program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum VaultError {
+    // 0.
+    /// Invalid instruction number passed in.
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    /// The vault authority is not the program address of the pool and
+    /// manager.
+    #[error("Invalid vault authority")]
+    InvalidVaultAuthority,
+    /// The manager of the vault did not sign.
+    #[error("Manager signature missing")]
+    MissingManagerSignature,
+}
+impl From<VaultError> for ProgramError {
+    fn from(e: VaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for VaultError {
+    fn type_of() -> &'static str {
+        "Vault Error"
+    }
+}
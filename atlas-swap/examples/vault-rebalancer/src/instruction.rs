@@ -0,0 +1,283 @@
+//! Instruction types
+
+use crate::error::VaultError;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+use std::mem::size_of;
+
+/// vault authority tag for seeds
+pub const VAULT_TAG: &str = "atlas-vault-rebalancer";
+
+/// Deposit instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deposit {
+    /// Pool token amount to mint into the vault
+    pub pool_token_amount: u64,
+    /// Maximum token A amount to deposit out of the vault
+    pub maximum_token_a_amount: u64,
+    /// Maximum token B amount to deposit out of the vault
+    pub maximum_token_b_amount: u64,
+}
+
+/// Rebalance instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rebalance {
+    /// SOURCE amount to swap out of the vault
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token credited to the vault
+    pub minimum_amount_out: u64,
+    /// Whether to swap token A for token B, rather than B for A
+    pub a_to_b: bool,
+}
+
+/// Withdraw instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Withdraw {
+    /// Pool token amount to burn out of the vault
+    pub pool_token_amount: u64,
+    /// Minimum token A amount credited to the vault
+    pub minimum_token_a_amount: u64,
+    /// Minimum token B amount credited to the vault
+    pub minimum_token_b_amount: u64,
+}
+
+/// Instructions supported by the vault rebalancer program. The vault
+/// authority of a pool and manager signs as the user transfer authority of
+/// the pool, which may not have an oracle, a gate, protocol-owned liquidity,
+/// a holding period or fee recipients.
+///
+/// Every instruction takes the same accounts:
+///
+///   0. `[signer]` Manager of the vault
+///   1. `[]` Vault authority, program address of VAULT_TAG, the token-swap and the manager
+///   2. `[]` atlas-swap program id
+///   3. `[writable]` Token-swap
+///   4. `[]` swap authority
+///   5. `[]` atlas-swap global state account
+///   6. `[writable]` token_a vault account, owned by the vault authority
+///   7. `[writable]` token_b vault account, owned by the vault authority
+///   8. `[writable]` Pool token vault account, owned by the vault authority
+///   9. `[writable]` token_a Base Account of the pool
+///   10. `[writable]` token_b Base Account of the pool
+///   11. `[writable]` Pool token mint
+///   12. `[]` Token program id
+///   13. `[writable]` Rebalance: fee account of the SOURCE token, when the pool charges an owner fee. Withdraw: pool token account of the fee owner, when the pool pays its withdrawal fee to it
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum VaultInstruction {
+    ///   Deposit both tokens of the vault into the pool.
+    Deposit(Deposit),
+    ///   Swap one token of the vault for the other through the pool.
+    Rebalance(Rebalance),
+    ///   Withdraw both tokens of the pool into the vault.
+    Withdraw(Withdraw),
+}
+
+impl VaultInstruction {
+    /// Unpacks a byte buffer into a [VaultInstruction](enum.VaultInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input.split_first().ok_or(VaultError::InvalidInstruction)?;
+        Ok(match tag {
+            0 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (maximum_token_a_amount, rest) = Self::unpack_u64(rest)?;
+                let (maximum_token_b_amount, _rest) = Self::unpack_u64(rest)?;
+                Self::Deposit(Deposit {
+                    pool_token_amount,
+                    maximum_token_a_amount,
+                    maximum_token_b_amount,
+                })
+            }
+            1 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                let (&a_to_b, _rest) = rest.split_first().ok_or(VaultError::InvalidInstruction)?;
+                Self::Rebalance(Rebalance {
+                    amount_in,
+                    minimum_amount_out,
+                    a_to_b: a_to_b != 0,
+                })
+            }
+            2 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_token_a_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_token_b_amount, _rest) = Self::unpack_u64(rest)?;
+                Self::Withdraw(Withdraw {
+                    pool_token_amount,
+                    minimum_token_a_amount,
+                    minimum_token_b_amount,
+                })
+            }
+            _ => return Err(VaultError::InvalidInstruction.into()),
+        })
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (amount, rest) = input.split_at(8);
+            let amount = amount
+                .get(..8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(VaultError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(VaultError::InvalidInstruction.into())
+        }
+    }
+
+    /// Packs a [VaultInstruction](enum.VaultInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::Deposit(Deposit {
+                pool_token_amount,
+                maximum_token_a_amount,
+                maximum_token_b_amount,
+            }) => {
+                buf.push(0);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&maximum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&maximum_token_b_amount.to_le_bytes());
+            }
+            Self::Rebalance(Rebalance {
+                amount_in,
+                minimum_amount_out,
+                a_to_b,
+            }) => {
+                buf.push(1);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.push(*a_to_b as u8);
+            }
+            Self::Withdraw(Withdraw {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+            }) => {
+                buf.push(2);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+            }
+        }
+        buf
+    }
+}
+
+/// Address of the vault authority of a manager in a pool
+pub fn find_vault_authority(program_id: &Pubkey, swap: &Pubkey, manager: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_TAG.as_bytes(), swap.as_ref(), manager.as_ref()],
+        program_id,
+    )
+}
+
+/// Pool and vault accounts shared by every instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vault {
+    /// Manager of the vault
+    pub manager_pubkey: Pubkey,
+    /// atlas-swap program id
+    pub swap_program_id: Pubkey,
+    /// Token-swap
+    pub swap_pubkey: Pubkey,
+    /// swap authority
+    pub authority_pubkey: Pubkey,
+    /// atlas-swap global state account
+    pub global_state_pubkey: Pubkey,
+    /// token_a vault account
+    pub token_a_pubkey: Pubkey,
+    /// token_b vault account
+    pub token_b_pubkey: Pubkey,
+    /// Pool token vault account
+    pub pool_token_pubkey: Pubkey,
+    /// token_a Base Account of the pool
+    pub swap_token_a_pubkey: Pubkey,
+    /// token_b Base Account of the pool
+    pub swap_token_b_pubkey: Pubkey,
+    /// Pool token mint
+    pub pool_mint_pubkey: Pubkey,
+    /// Token program id
+    pub token_program_id: Pubkey,
+}
+
+fn vault_instruction(
+    program_id: &Pubkey,
+    vault: &Vault,
+    fee_pubkey: Option<&Pubkey>,
+    instruction: VaultInstruction,
+) -> Instruction {
+    let data = instruction.pack();
+    let (vault_authority_pubkey, _) =
+        find_vault_authority(program_id, &vault.swap_pubkey, &vault.manager_pubkey);
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(vault.manager_pubkey, true),
+        AccountMeta::new_readonly(vault_authority_pubkey, false),
+        AccountMeta::new_readonly(vault.swap_program_id, false),
+        AccountMeta::new(vault.swap_pubkey, false),
+        AccountMeta::new_readonly(vault.authority_pubkey, false),
+        AccountMeta::new_readonly(vault.global_state_pubkey, false),
+        AccountMeta::new(vault.token_a_pubkey, false),
+        AccountMeta::new(vault.token_b_pubkey, false),
+        AccountMeta::new(vault.pool_token_pubkey, false),
+        AccountMeta::new(vault.swap_token_a_pubkey, false),
+        AccountMeta::new(vault.swap_token_b_pubkey, false),
+        AccountMeta::new(vault.pool_mint_pubkey, false),
+        AccountMeta::new_readonly(vault.token_program_id, false),
+    ];
+    if let Some(fee_pubkey) = fee_pubkey {
+        accounts.push(AccountMeta::new(*fee_pubkey, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates a 'deposit' instruction.
+pub fn deposit(program_id: &Pubkey, vault: &Vault, instruction: Deposit) -> Instruction {
+    vault_instruction(program_id, vault, None, VaultInstruction::Deposit(instruction))
+}
+
+/// Creates a 'rebalance' instruction. `pool_fee_pubkey` is the fee account
+/// of the SOURCE token, none when the pool charges no owner fee.
+pub fn rebalance(
+    program_id: &Pubkey,
+    vault: &Vault,
+    pool_fee_pubkey: Option<&Pubkey>,
+    instruction: Rebalance,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        vault,
+        pool_fee_pubkey,
+        VaultInstruction::Rebalance(instruction),
+    )
+}
+
+/// Creates a 'withdraw' instruction. `pool_fee_pubkey` is the pool token
+/// account of the fee owner, when the pool pays its withdrawal fee to it.
+pub fn withdraw(
+    program_id: &Pubkey,
+    vault: &Vault,
+    pool_fee_pubkey: Option<&Pubkey>,
+    instruction: Withdraw,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        vault,
+        pool_fee_pubkey,
+        VaultInstruction::Withdraw(instruction),
+    )
+}
@@ -0,0 +1,17 @@
+#![deny(missing_docs)]
+
+//! Example program managing a vault of liquidity in an atlas-swap pool: its
+//! program address deposits, swaps between the two tokens and withdraws
+//! through the cpi helpers of the swap program.
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
+
+// Export the swap program so downstream users share its types
+pub use atlas_swap;
+
+solana_program::declare_id!("3WoPYcn5q7yU7WXVwRwze6fpm49D8Mqx7F4Y41WZigdd");
@@ -0,0 +1,262 @@
+//! Program state processor
+
+use crate::{
+    error::VaultError,
+    instruction::{find_vault_authority, Deposit, Rebalance, VaultInstruction, Withdraw, VAULT_TAG},
+};
+use atlas_swap::{
+    cpi::{
+        self, CpiContext, DepositAllTokenTypesAccounts, SwapAccounts,
+        WithdrawAllTokenTypesAccounts,
+    },
+    instruction::{DepositAllTokenTypes, Swap, WithdrawAllTokenTypes},
+};
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    decode_error::DecodeError,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+    pubkey::Pubkey,
+};
+
+/// Accounts shared by every instruction, the vault authority checked
+struct VaultAccounts<'a> {
+    manager: AccountInfo<'a>,
+    vault_authority: AccountInfo<'a>,
+    bump: u8,
+    swap_program: AccountInfo<'a>,
+    swap: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    global_state: AccountInfo<'a>,
+    token_a: AccountInfo<'a>,
+    token_b: AccountInfo<'a>,
+    pool_token: AccountInfo<'a>,
+    swap_token_a: AccountInfo<'a>,
+    swap_token_b: AccountInfo<'a>,
+    pool_mint: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+    fee: Option<AccountInfo<'a>>,
+}
+
+/// Program state handler.
+pub struct Processor {}
+impl Processor {
+    fn vault_accounts<'a>(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo<'a>],
+    ) -> Result<VaultAccounts<'a>, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let manager_info = next_account_info(account_info_iter)?;
+        let vault_authority_info = next_account_info(account_info_iter)?;
+        let swap_program_info = next_account_info(account_info_iter)?;
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let global_state_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let pool_token_info = next_account_info(account_info_iter)?;
+        let swap_token_a_info = next_account_info(account_info_iter)?;
+        let swap_token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let fee_info = next_account_info(account_info_iter).ok();
+
+        if !manager_info.is_signer {
+            return Err(VaultError::MissingManagerSignature.into());
+        }
+        let (vault_authority, bump) =
+            find_vault_authority(program_id, swap_info.key, manager_info.key);
+        if *vault_authority_info.key != vault_authority {
+            return Err(VaultError::InvalidVaultAuthority.into());
+        }
+        if *swap_program_info.key != atlas_swap::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(VaultAccounts {
+            manager: manager_info.clone(),
+            vault_authority: vault_authority_info.clone(),
+            bump,
+            swap_program: swap_program_info.clone(),
+            swap: swap_info.clone(),
+            authority: authority_info.clone(),
+            global_state: global_state_info.clone(),
+            token_a: token_a_info.clone(),
+            token_b: token_b_info.clone(),
+            pool_token: pool_token_info.clone(),
+            swap_token_a: swap_token_a_info.clone(),
+            swap_token_b: swap_token_b_info.clone(),
+            pool_mint: pool_mint_info.clone(),
+            token_program: token_program_info.clone(),
+            fee: fee_info.cloned(),
+        })
+    }
+
+    /// Processes a [Deposit](enum.VaultInstruction.html).
+    pub fn process_deposit(
+        program_id: &Pubkey,
+        deposit: Deposit,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let vault = Self::vault_accounts(program_id, accounts)?;
+        let signer_seeds: &[&[u8]] = &[
+            VAULT_TAG.as_bytes(),
+            vault.swap.key.as_ref(),
+            vault.manager.key.as_ref(),
+            &[vault.bump],
+        ];
+        cpi::deposit_all_token_types(
+            CpiContext::with_signer(
+                vault.swap_program,
+                DepositAllTokenTypesAccounts {
+                    swap: vault.swap,
+                    authority: vault.authority,
+                    global_state: vault.global_state,
+                    user_transfer_authority: vault.vault_authority,
+                    deposit_token_a: vault.token_a,
+                    deposit_token_b: vault.token_b,
+                    swap_token_a: vault.swap_token_a,
+                    swap_token_b: vault.swap_token_b,
+                    pool_mint: vault.pool_mint,
+                    destination: vault.pool_token,
+                    token_program: vault.token_program,
+                    gate: None,
+                    deposit_record: None,
+                },
+                &[signer_seeds],
+            ),
+            DepositAllTokenTypes {
+                pool_token_amount: deposit.pool_token_amount,
+                maximum_token_a_amount: deposit.maximum_token_a_amount,
+                maximum_token_b_amount: deposit.maximum_token_b_amount,
+                max_ratio_deviation_bps: None,
+            },
+        )
+    }
+
+    /// Processes a [Rebalance](enum.VaultInstruction.html).
+    pub fn process_rebalance(
+        program_id: &Pubkey,
+        rebalance: Rebalance,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let vault = Self::vault_accounts(program_id, accounts)?;
+        let signer_seeds: &[&[u8]] = &[
+            VAULT_TAG.as_bytes(),
+            vault.swap.key.as_ref(),
+            vault.manager.key.as_ref(),
+            &[vault.bump],
+        ];
+        let (source, swap_source, swap_destination, destination) = if rebalance.a_to_b {
+            (vault.token_a, vault.swap_token_a, vault.swap_token_b, vault.token_b)
+        } else {
+            (vault.token_b, vault.swap_token_b, vault.swap_token_a, vault.token_a)
+        };
+        cpi::swap(
+            CpiContext::with_signer(
+                vault.swap_program,
+                SwapAccounts {
+                    swap: vault.swap,
+                    authority: vault.authority,
+                    user_transfer_authority: vault.vault_authority,
+                    global_state: vault.global_state,
+                    source,
+                    swap_source,
+                    swap_destination,
+                    destination,
+                    pool_mint: vault.pool_mint,
+                    pool_fee: vault.fee,
+                    token_program: vault.token_program,
+                    oracle: None,
+                    gate: None,
+                    protocol_liquidity: None,
+                    fee_recipients: vec![],
+                },
+                &[signer_seeds],
+            ),
+            Swap {
+                amount_in: rebalance.amount_in,
+                minimum_amount_out: rebalance.minimum_amount_out,
+                max_price_impact_bps: None,
+                tip: 0,
+                memo: vec![],
+                trade_direction: None,
+                fee_discount: false,
+            },
+        )
+    }
+
+    /// Processes a [Withdraw](enum.VaultInstruction.html).
+    pub fn process_withdraw(
+        program_id: &Pubkey,
+        withdraw: Withdraw,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let vault = Self::vault_accounts(program_id, accounts)?;
+        let signer_seeds: &[&[u8]] = &[
+            VAULT_TAG.as_bytes(),
+            vault.swap.key.as_ref(),
+            vault.manager.key.as_ref(),
+            &[vault.bump],
+        ];
+        cpi::withdraw_all_token_types(
+            CpiContext::with_signer(
+                vault.swap_program,
+                WithdrawAllTokenTypesAccounts {
+                    swap: vault.swap,
+                    authority: vault.authority,
+                    global_state: vault.global_state,
+                    user_transfer_authority: vault.vault_authority,
+                    pool_mint: vault.pool_mint,
+                    source: vault.pool_token,
+                    swap_token_a: vault.swap_token_a,
+                    swap_token_b: vault.swap_token_b,
+                    destination_token_a: vault.token_a,
+                    destination_token_b: vault.token_b,
+                    token_program: vault.token_program,
+                    pool_fee: vault.fee,
+                    deposit_record: None,
+                },
+                &[signer_seeds],
+            ),
+            WithdrawAllTokenTypes {
+                pool_token_amount: withdraw.pool_token_amount,
+                minimum_token_a_amount: withdraw.minimum_token_a_amount,
+                minimum_token_b_amount: withdraw.minimum_token_b_amount,
+            },
+        )
+    }
+
+    /// Processes an [Instruction](enum.Instruction.html).
+    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        match VaultInstruction::unpack(input)? {
+            VaultInstruction::Deposit(deposit) => {
+                msg!("Instruction: Deposit");
+                Self::process_deposit(program_id, deposit, accounts)
+            }
+            VaultInstruction::Rebalance(rebalance) => {
+                msg!("Instruction: Rebalance");
+                Self::process_rebalance(program_id, rebalance, accounts)
+            }
+            VaultInstruction::Withdraw(withdraw) => {
+                msg!("Instruction: Withdraw");
+                Self::process_withdraw(program_id, withdraw, accounts)
+            }
+        }
+    }
+}
+
+impl PrintProgramError for VaultError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        match self {
+            VaultError::InvalidInstruction => msg!("Error: InvalidInstruction"),
+            VaultError::InvalidVaultAuthority => msg!("Error: Invalid vault authority"),
+            VaultError::MissingManagerSignature => msg!("Error: Manager signature missing"),
+        }
+    }
+}
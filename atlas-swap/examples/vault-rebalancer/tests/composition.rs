@@ -0,0 +1,366 @@
+//! The vault program signs for its program address through the atlas-swap
+//! cpi helpers, which invoke the token program in turn. Both invocations run
+//! natively here, checking the account metas and signer seeds at each level.
+
+use atlas_swap::{
+    constraints::SWAP_TAG,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    state::{GlobalState, GlobalStateVersion, SwapV2, SwapVersion},
+};
+use atlas_swap_vault_rebalancer::{
+    error::VaultError,
+    instruction::{
+        deposit, find_vault_authority, rebalance, withdraw, Deposit, Rebalance, Vault, Withdraw,
+    },
+    processor::Processor,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs,
+    pubkey::Pubkey,
+};
+use spl_token::state::{Account, AccountState, Mint};
+use std::collections::HashMap;
+
+const RESERVE: u64 = 1_000_000;
+const POOL_SUPPLY: u64 = 1_000_000;
+
+struct TestSyscallStubs {}
+impl program_stubs::SyscallStubs for TestSyscallStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let signers = signers_seeds
+            .iter()
+            .flat_map(|seeds| {
+                [atlas_swap_vault_rebalancer::id(), atlas_swap::id()]
+                    .iter()
+                    .filter_map(|program_id| Pubkey::create_program_address(seeds, program_id).ok())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut new_account_infos = vec![];
+        for meta in instruction.accounts.iter() {
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| *account_info.key == meta.pubkey)
+                .expect("account meta missing from the account infos");
+            let mut new_account_info = account_info.clone();
+            if signers.contains(account_info.key) {
+                new_account_info.is_signer = true;
+            }
+            assert!(
+                !meta.is_signer || new_account_info.is_signer,
+                "{} did not sign",
+                meta.pubkey
+            );
+            new_account_infos.push(new_account_info);
+        }
+
+        if instruction.program_id == atlas_swap::id() {
+            atlas_swap::processor::Processor::process(
+                &instruction.program_id,
+                &new_account_infos,
+                &instruction.data,
+            )
+        } else {
+            spl_token::processor::Processor::process(
+                &instruction.program_id,
+                &new_account_infos,
+                &instruction.data,
+            )
+        }
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Clock) = Clock::default();
+        }
+        SUCCESS
+    }
+}
+
+fn test_syscall_stubs() {
+    use std::sync::Once;
+    static ONCE: Once = Once::new();
+
+    ONCE.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(TestSyscallStubs {}));
+    });
+}
+
+struct TestAccount {
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+/// A constant product pool without fees and the vault of a manager in it
+struct TestVault {
+    vault: Vault,
+    vault_authority: Pubkey,
+    accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl TestVault {
+    fn new() -> Self {
+        let program_id = atlas_swap::id();
+        let manager = Pubkey::new_unique();
+        let swap = Pubkey::new_unique();
+        let (authority, nonce) = Pubkey::find_program_address(&[swap.as_ref()], &program_id);
+        let (global_state, _) = Pubkey::find_program_address(
+            &[SWAP_TAG.as_bytes(), program_id.as_ref()],
+            &program_id,
+        );
+        let (vault_authority, _) =
+            find_vault_authority(&atlas_swap_vault_rebalancer::id(), &swap, &manager);
+        let token_a_mint = Pubkey::new_unique();
+        let token_b_mint = Pubkey::new_unique();
+        let vault = Vault {
+            manager_pubkey: manager,
+            swap_program_id: program_id,
+            swap_pubkey: swap,
+            authority_pubkey: authority,
+            global_state_pubkey: global_state,
+            token_a_pubkey: Pubkey::new_unique(),
+            token_b_pubkey: Pubkey::new_unique(),
+            pool_token_pubkey: Pubkey::new_unique(),
+            swap_token_a_pubkey: Pubkey::new_unique(),
+            swap_token_b_pubkey: Pubkey::new_unique(),
+            pool_mint_pubkey: Pubkey::new_unique(),
+            token_program_id: spl_token::id(),
+        };
+
+        let mut test_vault = Self {
+            vault: vault.clone(),
+            vault_authority,
+            accounts: HashMap::new(),
+        };
+        test_vault.add_token_account(vault.token_a_pubkey, vault_authority, token_a_mint, RESERVE);
+        test_vault.add_token_account(vault.token_b_pubkey, vault_authority, token_b_mint, RESERVE);
+        test_vault.add_token_account(vault.pool_token_pubkey, vault_authority, vault.pool_mint_pubkey, 0);
+        test_vault.add_token_account(vault.swap_token_a_pubkey, authority, token_a_mint, RESERVE);
+        test_vault.add_token_account(vault.swap_token_b_pubkey, authority, token_b_mint, RESERVE);
+
+        let mut pool_mint = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(authority),
+                supply: POOL_SUPPLY,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut pool_mint,
+        )
+        .unwrap();
+        test_vault.add(vault.pool_mint_pubkey, spl_token::id(), pool_mint);
+
+        let mut swap_data = vec![0; SwapVersion::LATEST_LEN];
+        SwapVersion::pack(
+            SwapVersion::SwapV2(SwapV2 {
+                is_initialized: true,
+                nonce,
+                token_program_id: spl_token::id(),
+                token_a: vault.swap_token_a_pubkey,
+                token_b: vault.swap_token_b_pubkey,
+                pool_mint: vault.pool_mint_pubkey,
+                token_a_mint,
+                token_b_mint,
+                swap_curve: SwapCurve {
+                    curve_type: CurveType::ConstantProduct,
+                    calculator: ConstantProductCurve.into(),
+                },
+                token_a_reserve: RESERVE,
+                token_b_reserve: RESERVE,
+                ..SwapV2::default()
+            }),
+            &mut swap_data,
+        )
+        .unwrap();
+        test_vault.add(swap, program_id, swap_data);
+
+        let mut state_data = vec![0; GlobalStateVersion::LATEST_LEN];
+        GlobalStateVersion::pack(
+            &GlobalState {
+                is_initialized: true,
+                fees: Fees {
+                    fee_denominator: 10_000,
+                    ..Fees::default()
+                },
+                ..GlobalState::default()
+            },
+            &mut state_data,
+        )
+        .unwrap();
+        test_vault.add(global_state, program_id, state_data);
+        test_vault
+    }
+
+    fn add(&mut self, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        self.accounts.insert(
+            key,
+            TestAccount {
+                owner,
+                lamports: 1,
+                data,
+            },
+        );
+    }
+
+    fn add_token_account(&mut self, key: Pubkey, owner: Pubkey, mint: Pubkey, amount: u64) {
+        let mut data = vec![0; Account::LEN];
+        Account::pack(
+            Account {
+                mint,
+                owner,
+                amount,
+                state: AccountState::Initialized,
+                ..Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add(key, spl_token::id(), data);
+    }
+
+    fn balance(&self, key: &Pubkey) -> u64 {
+        Account::unpack(&self.accounts[key].data).unwrap().amount
+    }
+
+    /// Runs a vault instruction, accounts missing from the tree being empty
+    /// program accounts such as the program ids
+    fn process(&mut self, instruction: Instruction) -> ProgramResult {
+        test_syscall_stubs();
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert(TestAccount {
+                owner: Pubkey::default(),
+                lamports: 1,
+                data: vec![],
+            });
+        }
+        let mut account_infos = vec![];
+        for (key, account) in self.accounts.iter_mut() {
+            if let Some(meta) = instruction.accounts.iter().find(|meta| meta.pubkey == *key) {
+                account_infos.push(AccountInfo::new(
+                    key,
+                    meta.is_signer,
+                    meta.is_writable,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    false,
+                    0,
+                ));
+            }
+        }
+        let account_infos = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .unwrap()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+}
+
+#[test]
+fn rebalance_swaps_vault_tokens_through_the_pool() {
+    let mut test_vault = TestVault::new();
+    let vault = test_vault.vault.clone();
+    test_vault
+        .process(rebalance(
+            &atlas_swap_vault_rebalancer::id(),
+            &vault,
+            None,
+            Rebalance {
+                amount_in: 10_000,
+                minimum_amount_out: 9_000,
+                a_to_b: true,
+            },
+        ))
+        .unwrap();
+    // the input the curve rounds away stays with the vault
+    let amount_swapped = RESERVE - test_vault.balance(&vault.token_a_pubkey);
+    assert!(amount_swapped <= 10_000);
+    assert!(test_vault.balance(&vault.token_b_pubkey) >= RESERVE + 9_000);
+    assert_eq!(
+        test_vault.balance(&vault.swap_token_a_pubkey),
+        RESERVE + amount_swapped
+    );
+}
+
+#[test]
+fn deposit_then_withdraw_returns_vault_liquidity() {
+    let mut test_vault = TestVault::new();
+    let vault = test_vault.vault.clone();
+    test_vault
+        .process(deposit(
+            &atlas_swap_vault_rebalancer::id(),
+            &vault,
+            Deposit {
+                pool_token_amount: POOL_SUPPLY / 10,
+                maximum_token_a_amount: RESERVE / 10,
+                maximum_token_b_amount: RESERVE / 10,
+            },
+        ))
+        .unwrap();
+    assert_eq!(test_vault.balance(&vault.pool_token_pubkey), POOL_SUPPLY / 10);
+    assert_eq!(test_vault.balance(&vault.token_a_pubkey), RESERVE - RESERVE / 10);
+    assert_eq!(test_vault.balance(&vault.token_b_pubkey), RESERVE - RESERVE / 10);
+
+    test_vault
+        .process(withdraw(
+            &atlas_swap_vault_rebalancer::id(),
+            &vault,
+            None,
+            Withdraw {
+                pool_token_amount: POOL_SUPPLY / 10,
+                minimum_token_a_amount: RESERVE / 10 - 1,
+                minimum_token_b_amount: RESERVE / 10 - 1,
+            },
+        ))
+        .unwrap();
+    assert_eq!(test_vault.balance(&vault.pool_token_pubkey), 0);
+    assert!(test_vault.balance(&vault.token_a_pubkey) >= RESERVE - 1);
+    assert!(test_vault.balance(&vault.token_b_pubkey) >= RESERVE - 1);
+}
+
+#[test]
+fn vault_of_another_manager_is_rejected() {
+    let mut test_vault = TestVault::new();
+    let mut vault = test_vault.vault.clone();
+    vault.manager_pubkey = Pubkey::new_unique();
+    let mut instruction = rebalance(
+        &atlas_swap_vault_rebalancer::id(),
+        &vault,
+        None,
+        Rebalance {
+            amount_in: 10_000,
+            minimum_amount_out: 0,
+            a_to_b: true,
+        },
+    );
+    instruction.accounts[1].pubkey = test_vault.vault_authority;
+    assert_eq!(
+        test_vault.process(instruction),
+        Err(VaultError::InvalidVaultAuthority.into())
+    );
+}
@@ -0,0 +1,26 @@
+//! Arbitrary bytes fed to the instruction and swap account parsers, which
+//! must never panic, must fail the same way on the same input, and must
+//! read back what they pack.
+
+use {
+    honggfuzz::fuzz,
+    spl_token_swap::{instruction::SwapInstruction, state::{SwapState, SwapVersion}},
+};
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| { run_fuzz(data) });
+    }
+}
+
+fn run_fuzz(data: &[u8]) {
+    let instruction = SwapInstruction::unpack(data);
+    assert_eq!(instruction, SwapInstruction::unpack(data));
+    if let Ok(instruction) = instruction {
+        assert_eq!(SwapInstruction::unpack(&instruction.pack().unwrap()), Ok(instruction));
+    }
+
+    let swap = SwapVersion::unpack_version(data).map(|swap| swap.is_initialized());
+    assert_eq!(swap, SwapVersion::unpack_version(data).map(|swap| swap.is_initialized()));
+    assert_eq!(swap, SwapVersion::unpack(data).map(|swap| swap.is_initialized()));
+}
@@ -0,0 +1,234 @@
+//! Client-side accounting of swap activity, folded from the events pools log
+//! into fee and PnL summaries per wallet and per pool
+
+use crate::{
+    curve::calculator::TradeDirection,
+    events::{Event, SwapEvent},
+};
+use solana_program::pubkey::Pubkey;
+use std::collections::BTreeMap;
+
+/// Prefix of the log line of an event logged with `sol_log_data`
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// Swap activity of a wallet in a pool, or of a whole pool
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeSummary {
+    /// Number of swaps
+    pub swaps: u64,
+    /// Token A sent into the pool, fees included
+    pub token_a_in: u128,
+    /// Token A taken out of the pool
+    pub token_a_out: u128,
+    /// Token B sent into the pool, fees included
+    pub token_b_in: u128,
+    /// Token B taken out of the pool
+    pub token_b_out: u128,
+    /// Trade fees in token A
+    pub trade_fee_a: u128,
+    /// Trade fees in token B
+    pub trade_fee_b: u128,
+    /// Owner fees in token A
+    pub owner_fee_a: u128,
+    /// Owner fees in token B
+    pub owner_fee_b: u128,
+}
+
+impl FeeSummary {
+    fn add(&mut self, event: &SwapEvent) {
+        self.swaps += 1;
+        let amount_in = u128::from(event.amount_in);
+        let amount_out = u128::from(event.amount_out);
+        let trade_fee = u128::from(event.trade_fee);
+        let owner_fee = u128::from(event.owner_fee);
+        match event.trade_direction {
+            TradeDirection::AtoB => {
+                self.token_a_in += amount_in;
+                self.token_b_out += amount_out;
+                self.trade_fee_a += trade_fee;
+                self.owner_fee_a += owner_fee;
+            }
+            TradeDirection::BtoA => {
+                self.token_b_in += amount_in;
+                self.token_a_out += amount_out;
+                self.trade_fee_b += trade_fee;
+                self.owner_fee_b += owner_fee;
+            }
+        }
+    }
+
+    /// Net token A of the wallet, what it took out of the pool less what it
+    /// put in. For a pool, the token A its traders took from it.
+    pub fn token_a_pnl(&self) -> i128 {
+        self.token_a_out as i128 - self.token_a_in as i128
+    }
+
+    /// Net token B of the wallet, what it took out of the pool less what it
+    /// put in. For a pool, the token B its traders took from it.
+    pub fn token_b_pnl(&self) -> i128 {
+        self.token_b_out as i128 - self.token_b_in as i128
+    }
+
+    fn csv_fields(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            self.swaps,
+            self.token_a_in,
+            self.token_a_out,
+            self.token_b_in,
+            self.token_b_out,
+            self.trade_fee_a,
+            self.trade_fee_b,
+            self.owner_fee_a,
+            self.owner_fee_b,
+            self.token_a_pnl(),
+            self.token_b_pnl(),
+        )
+    }
+
+    fn json_fields(&self) -> String {
+        format!(
+            "\"swaps\":{},\"token_a_in\":{},\"token_a_out\":{},\"token_b_in\":{},\"token_b_out\":{},\
+             \"trade_fee_a\":{},\"trade_fee_b\":{},\"owner_fee_a\":{},\"owner_fee_b\":{},\
+             \"token_a_pnl\":{},\"token_b_pnl\":{}",
+            self.swaps,
+            self.token_a_in,
+            self.token_a_out,
+            self.token_b_in,
+            self.token_b_out,
+            self.trade_fee_a,
+            self.trade_fee_b,
+            self.owner_fee_a,
+            self.owner_fee_b,
+            self.token_a_pnl(),
+            self.token_b_pnl(),
+        )
+    }
+}
+
+/// Column names of the summary fields of a CSV report
+const SUMMARY_CSV_HEADER: &str = "swaps,token_a_in,token_a_out,token_b_in,token_b_out,\
+    trade_fee_a,trade_fee_b,owner_fee_a,owner_fee_b,token_a_pnl,token_b_pnl";
+
+/// Swap activity folded per wallet and pool, and per pool. Amounts are in
+/// base units of each pool's tokens, so summaries of different pools are
+/// never added together.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeReport {
+    /// Activity of each wallet, by pool then wallet
+    pub wallets: BTreeMap<(Pubkey, Pubkey), FeeSummary>,
+    /// Activity of each pool
+    pub pools: BTreeMap<Pubkey, FeeSummary>,
+}
+
+impl FeeReport {
+    /// Fold a swap into the report
+    pub fn add_swap(&mut self, event: &SwapEvent) {
+        self.wallets
+            .entry((event.swap, event.owner))
+            .or_default()
+            .add(event);
+        self.pools.entry(event.swap).or_default().add(event);
+    }
+
+    /// Fold the swaps logged in the log lines of a transaction. Lines of
+    /// other programs or events are skipped, so the logs of a whole
+    /// transaction may be passed, its inner instructions included.
+    pub fn add_logs<S: AsRef<str>>(&mut self, logs: &[S]) {
+        for event in logs.iter().filter_map(|log| parse_swap_event(log.as_ref())) {
+            self.add_swap(&event);
+        }
+    }
+
+    /// Report of every wallet as CSV, one line per wallet and pool
+    pub fn wallets_csv(&self) -> String {
+        let mut csv = format!("pool,wallet,{}\n", SUMMARY_CSV_HEADER);
+        for ((swap, owner), summary) in self.wallets.iter() {
+            csv.push_str(&format!("{},{},{}\n", swap, owner, summary.csv_fields()));
+        }
+        csv
+    }
+
+    /// Report of every pool as CSV, one line per pool
+    pub fn pools_csv(&self) -> String {
+        let mut csv = format!("pool,{}\n", SUMMARY_CSV_HEADER);
+        for (swap, summary) in self.pools.iter() {
+            csv.push_str(&format!("{},{}\n", swap, summary.csv_fields()));
+        }
+        csv
+    }
+
+    /// Whole report as a JSON object with `wallets` and `pools` arrays
+    pub fn to_json(&self) -> String {
+        let wallets = self
+            .wallets
+            .iter()
+            .map(|((swap, owner), summary)| {
+                format!(
+                    "{{\"pool\":\"{}\",\"wallet\":\"{}\",{}}}",
+                    swap,
+                    owner,
+                    summary.json_fields()
+                )
+            })
+            .collect::<Vec<_>>();
+        let pools = self
+            .pools
+            .iter()
+            .map(|(swap, summary)| format!("{{\"pool\":\"{}\",{}}}", swap, summary.json_fields()))
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"wallets\":[{}],\"pools\":[{}]}}",
+            wallets.join(","),
+            pools.join(",")
+        )
+    }
+}
+
+/// Swap event of a `Program data:` log line, none for any other line
+pub fn parse_swap_event(log: &str) -> Option<SwapEvent> {
+    let mut fields = log.strip_prefix(PROGRAM_DATA_PREFIX)?.split(' ');
+    if decode_base64(fields.next()?)? != [SwapEvent::KIND] {
+        return None;
+    }
+    let event = SwapEvent::unpack(&decode_base64(fields.next()?)?)?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some(event)
+}
+
+/// Decode standard, padded base64, as `sol_log_data` logs each field
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some(u32::from(c - b'A')),
+            b'a'..=b'z' => Some(u32::from(c - b'a') + 26),
+            b'0'..=b'9' => Some(u32::from(c - b'0') + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let input = input.as_bytes();
+    if !input.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    for (i, chunk) in input.chunks(4).enumerate() {
+        let last = i == input.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut bits = 0u32;
+        for &c in &chunk[..4 - padding] {
+            bits = (bits << 6) | sextet(c)?;
+        }
+        bits <<= 6 * padding as u32;
+        let bytes = bits.to_be_bytes();
+        output.extend_from_slice(&bytes[1..4 - padding]);
+    }
+    Some(output)
+}
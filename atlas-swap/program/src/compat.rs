@@ -0,0 +1,132 @@
+//! Instructions in the layout of upstream spl-token-swap, mapped onto the
+//! layout of this program so integrations built against it keep working.
+//!
+//! An upstream instruction takes one change: the accounts this program needs
+//! beyond the upstream ones are appended to them, the global state account
+//! first. The global state holds the fees and owners every pool follows, and
+//! a program cannot read an account it is not passed, so it cannot be
+//! derived in place of being appended.
+//!
+//! * Initialize: the global state, then the token A and token B mints and
+//!   the pool token account of the swap authority locking MIN_LP_SUPPLY are
+//!   appended. The upstream fee account is left out, and so are the fees of
+//!   the upstream data: pools charge the fees of the global state.
+//! * Swap: the global state is appended, after the optional host fee
+//!   account, which has no counterpart. The upstream fee account is a pool
+//!   token account of the fee owner, so it is only passed on for a pool
+//!   minting the owner fee as pool tokens. A pool charging the owner fee in
+//!   the SOURCE token takes swaps in the layout of this program only.
+//! * DepositAllTokenTypes: the global state is appended.
+//! * WithdrawAllTokenTypes: the global state is appended. The upstream fee
+//!   account is only passed on for a pool paying its withdrawal fee out.
+//!
+//! Swap, DepositAllTokenTypes and WithdrawAllTokenTypes share their data
+//! layout with upstream.
+
+use crate::{
+    curve::base::SwapCurve,
+    instruction::{Initialize, SwapInstruction},
+    pda::find_global_state,
+    state::SwapVersion,
+};
+use solana_program::{account_info::AccountInfo, program_pack::Pack, pubkey::Pubkey};
+
+/// Tag of the upstream Initialize instruction
+const INITIALIZE: u8 = 0;
+/// Tag of the upstream Swap instruction
+const SWAP: u8 = 1;
+/// Tag of the upstream WithdrawAllTokenTypes instruction
+const WITHDRAW_ALL_TOKEN_TYPES: u8 = 3;
+
+/// Size of the fees of an upstream Initialize, eight numerators and
+/// denominators
+const UPSTREAM_FEES_LEN: usize = 64;
+
+/// Number of accounts of an upstream instruction, its optional ones left
+/// out, and the slot of the global state account in the layout of this
+/// program
+fn upstream_layout(tag: u8) -> Option<(usize, usize)> {
+    match tag {
+        0 => Some((8, 2)),
+        1 => Some((10, 3)),
+        2 => Some((10, 2)),
+        3 => Some((11, 2)),
+        _ => None,
+    }
+}
+
+/// Data and accounts of an instruction in the upstream layout, rewritten
+/// into the layout of this program, or None when the instruction is not in
+/// the upstream layout
+pub fn upstream_instruction<'a>(
+    program_id: &Pubkey,
+    input: &[u8],
+    accounts: &[AccountInfo<'a>],
+) -> Option<(Vec<u8>, Vec<AccountInfo<'a>>)> {
+    let tag = *input.first()?;
+    let (upstream_len, state_slot) = upstream_layout(tag)?;
+    let (global_state, _bump) = find_global_state(program_id);
+    if *accounts.get(state_slot)?.key == global_state {
+        return None;
+    }
+    if tag == INITIALIZE {
+        return upstream_initialize(input, accounts, &global_state);
+    }
+    let (state_info, upstream_accounts) = accounts.split_last()?;
+    if *state_info.key != global_state {
+        return None;
+    }
+
+    // the optional host fee account of an upstream swap has no counterpart
+    let mut accounts = upstream_accounts.get(..upstream_len)?.to_vec();
+    accounts.insert(state_slot, state_info.clone());
+    if tag == SWAP {
+        // the upstream fee account comes right after the pool mint
+        let token_swap = SwapVersion::unpack(&accounts[0].data.borrow()).ok()?;
+        if !token_swap.owner_fee_in_pool_tokens() {
+            accounts.remove(9);
+        }
+    }
+    if tag == WITHDRAW_ALL_TOKEN_TYPES {
+        // the upstream withdrawal fee account comes before the token
+        // program, and is only taken by a pool paying its withdrawal fee out
+        let pool_fee_info = accounts.remove(10);
+        let token_swap = SwapVersion::unpack(&accounts[0].data.borrow()).ok()?;
+        if let Some((_, false)) = token_swap.withdraw_fee() {
+            accounts.push(pool_fee_info);
+        }
+    }
+    Some((input.to_vec(), accounts))
+}
+
+/// An upstream Initialize, its fees then its swap curve, followed in its
+/// accounts by the global state, the token mints and the account locking
+/// MIN_LP_SUPPLY
+fn upstream_initialize<'a>(
+    input: &[u8],
+    accounts: &[AccountInfo<'a>],
+    global_state: &Pubkey,
+) -> Option<(Vec<u8>, Vec<AccountInfo<'a>>)> {
+    let swap_curve = input.get(1 + UPSTREAM_FEES_LEN..)?;
+    if swap_curve.len() != SwapCurve::LEN || accounts.len() != 12 {
+        return None;
+    }
+    let swap_curve = SwapCurve::unpack_unchecked(swap_curve).ok()?;
+    let (upstream_accounts, appended_accounts) = accounts.split_at(8);
+    if *appended_accounts[0].key != *global_state {
+        return None;
+    }
+
+    let mut accounts = upstream_accounts.to_vec();
+    // the upstream fee account comes after the pool mint
+    accounts.remove(5);
+    accounts.insert(2, appended_accounts[0].clone());
+    accounts.extend_from_slice(&appended_accounts[1..]);
+    let input = SwapInstruction::Initialize(Initialize {
+        swap_curve,
+        fee_owner: None,
+    })
+    .pack()
+    .ok()?;
+    Some((input, accounts))
+}
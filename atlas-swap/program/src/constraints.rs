@@ -3,12 +3,13 @@
 use crate::{
     curve::{
         base::{CurveType, SwapCurve},
-        fees::Fees,
+        fees::{FeeTier, Fees, FEE_TIER_COUNT},
     },
     error::SwapError,
 };
 
-use solana_program::program_error::ProgramError;
+use solana_program::{program_error::ProgramError, pubkey, pubkey::Pubkey};
+use std::str::FromStr;
 
 const MINIMUM_FEES: &Fees = &Fees {
     constant_product_return_fee_numerator: 0,
@@ -16,16 +17,28 @@ const MINIMUM_FEES: &Fees = &Fees {
     stable_return_fee_numerator: 0,
     stable_fixed_fee_numerator: 0,
     fee_denominator: 10000,
+    dynamic_fee_min_numerator: 0,
+    dynamic_fee_max_numerator: 0,
+    fee_tiers: [FeeTier {
+        threshold_bps: 0,
+        fixed_fee_numerator: 0,
+    }; FEE_TIER_COUNT],
+    minimum_fee: 0,
 };
-const VALID_CURVE_TYPES: &[CurveType] = &[CurveType::Stable, CurveType::ConstantProduct];
+const VALID_CURVE_TYPES: &[CurveType] = &[
+    CurveType::Stable,
+    CurveType::ConstantProduct,
+    CurveType::OraclePegged,
+    CurveType::RatedStable,
+    CurveType::MetaStable,
+];
 
 
 /// Encodes fee constraints, used in multihost environments where the program
 /// may be used by multiple frontends, to ensure that proper fees are being
 /// assessed.
-/// Since this struct needs to be created at compile-time, we only have access
-/// to const functions and constructors. Since SwapCurve contains a Box, it
-/// cannot be used, so we have to split the curves based on their types.
+/// Curves are constrained by type only, the parameters of each calculator
+/// being checked by its own `validate`.
 pub struct SwapConstraints<'a> {
     /// Valid curve types
     pub valid_curve_types: &'a [CurveType],
@@ -56,6 +69,15 @@ impl<'a> SwapConstraints<'a> {
             && fees.stable_return_fee_numerator >= self.fees.stable_return_fee_numerator
             && fees.stable_fixed_fee_numerator >= self.fees.stable_fixed_fee_numerator
             && fees.fee_denominator == self.fees.fee_denominator
+            && fees.minimum_fee >= self.fees.minimum_fee
+            && (!fees.is_dynamic()
+                || fees.dynamic_fee_min_numerator >= self.fees.dynamic_fee_min_numerator)
+            && fees
+                .fee_tiers
+                .iter()
+                .all(|tier| !tier.is_enabled()
+                    || (tier.fixed_fee_numerator >= self.fees.constant_product_fixed_fee_numerator
+                        && tier.fixed_fee_numerator >= self.fees.stable_fixed_fee_numerator))
         {
             Ok(())
         } else {
@@ -70,14 +92,62 @@ pub const SWAP_TAG:&str = "atlas-swap";
 /// swap router tag for seeds
 pub const SWAP_ROUTE_TAG:&str = "atlas-swap-router";
 
-/// rent sysvar program id
-pub const RENT_SYSVAR_ID:&str = "SysvarRent111111111111111111111111111111111";
+/// liquidity lock tag for seeds
+pub const LOCK_TAG:&str = "atlas-swap-lock";
 
-/// system program id
-pub const SYSTEM_PROGRAM_ID:&str = "11111111111111111111111111111111";
+/// staking tag for seeds
+pub const STAKE_TAG:&str = "atlas-swap-stake";
 
-/// initial program owner address
-pub const INITIAL_PROGRAM_OWNER: &str = "FABSYVqYSKogNUSRK6xBC3wRCTX6Gba9jMcHvLuEqC3G";
+/// limit order tag for seeds
+pub const ORDER_TAG:&str = "atlas-swap-order";
+
+/// recurring order tag for seeds
+pub const DCA_TAG:&str = "atlas-swap-dca";
+
+/// allowlist entry tag for seeds
+pub const ALLOWLIST_TAG:&str = "atlas-swap-allowlist";
+
+/// deposit record tag for seeds
+pub const DEPOSIT_RECORD_TAG:&str = "atlas-swap-deposit";
+
+/// quote nonce record tag for seeds
+pub const QUOTE_NONCE_TAG:&str = "atlas-swap-quote-nonce";
+
+/// protocol-owned liquidity authority tag for seeds
+pub const PROTOCOL_LIQUIDITY_TAG:&str = "atlas-swap-pol";
+
+/// swap commitment tag for seeds
+pub const COMMIT_TAG:&str = "atlas-swap-commit";
+
+/// batch auction tag for seeds
+pub const BATCH_TAG:&str = "atlas-swap-batch";
+
+/// LP position tag for seeds
+pub const POSITION_TAG:&str = "atlas-swap-position";
+
+/// fee governance tag for seeds
+pub const FEE_GOVERNANCE_TAG:&str = "atlas-swap-fee-governance";
+
+/// seed prefix of the governance program addresses of spl-governance
+pub const SPL_GOVERNANCE_SEED:&str = "account-governance";
+
+/// admin action scheduler tag for seeds
+pub const SCHEDULER_TAG:&str = "atlas-swap-scheduler";
+
+/// spl-token-swap program whose pools liquidity may be migrated from
+pub const SPL_TOKEN_SWAP_PROGRAM_ID: Pubkey = pubkey!("SwapsVeCiPHMUAtzQWZw7RjsKjgCjhwU55QGu4U1Szw");
+
+/// default initial program owner address
+pub const DEFAULT_INITIAL_PROGRAM_OWNER: Pubkey = pubkey!("FABSYVqYSKogNUSRK6xBC3wRCTX6Gba9jMcHvLuEqC3G");
+
+/// initial program owner address, overridable at build time through the
+/// `SWAP_PROGRAM_INITIAL_OWNER` environment variable
+pub fn initial_program_owner() -> Result<Pubkey, ProgramError> {
+    match option_env!("SWAP_PROGRAM_INITIAL_OWNER") {
+        Some(owner) => Pubkey::from_str(owner).map_err(|_| SwapError::InvalidProgramOwner.into()),
+        None => Ok(DEFAULT_INITIAL_PROGRAM_OWNER),
+    }
+}
 
 /// swap contraints
 pub const SWAP_CONSTRAINTS:SwapConstraints = SwapConstraints {
@@ -85,5 +155,41 @@ pub const SWAP_CONSTRAINTS:SwapConstraints = SwapConstraints {
     fees: MINIMUM_FEES,
 };
 
+/// Factor by which the amp of a stable pool may rise or fall in one update
+pub const MAX_AMP_CHANGE_FACTOR: u64 = 10;
+
+/// Most, in basis points, the output of a signed quote may exceed the
+/// output of the pool curve for the same input, the pool fees taken
+pub const MAX_QUOTE_DEVIATION_BPS: u64 = 50;
+
+/// Minimum time in seconds between scheduling an admin action and its ETA
+pub const MIN_PENDING_ACTION_DELAY: i64 = 86_400;
+
+/// Most decimals a pool mint may have
+pub const MAX_LP_DECIMALS: u8 = 9;
+
+/// Decimals of the pool mint of a new pool of tokens with the given
+/// decimals, unless its creator picks others
+pub fn default_lp_decimals(token_a_decimals: u8, token_b_decimals: u8) -> u8 {
+    std::cmp::min(std::cmp::max(token_a_decimals, token_b_decimals), MAX_LP_DECIMALS)
+}
+
 /// minimum lp supply
-pub const MIN_LP_SUPPLY:u128 = 100000;
\ No newline at end of file
+pub const MIN_LP_SUPPLY:u128 = 100000;
+
+/// Reserve, in base units, left by floor rounding that the withdrawal
+/// taking the pool supply down to MIN_LP_SUPPLY sweeps to the withdrawer,
+/// all but one unit
+pub const MAX_SWEEP_DUST: u64 = 2;
+
+/// Deviation, in basis points, a deposit of both tokens may have from the
+/// price of the pool reserves
+pub const MAX_DEPOSIT_RATIO_DEVIATION_BPS: u128 = 100;
+
+/// Share of the pool reserves, in basis points, the fee owner may rebalance
+/// per epoch
+pub const MAX_REBALANCE_BPS_PER_EPOCH: u128 = 1_000;
+
+/// Imbalance between the reserves of a stable pool, in basis points of the
+/// total reserves, from which the fee owner may rebalance it
+pub const MIN_REBALANCE_IMBALANCE_BPS: u128 = 500;
@@ -0,0 +1,275 @@
+//! Helpers for calling the swap program from other programs
+
+use crate::instruction::{self, DepositAllTokenTypes, Swap, SwapArgs, WithdrawAllTokenTypes};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, program::invoke_signed,
+};
+
+/// Swap program, accounts and signer seeds of a cross-program invocation.
+/// Programs whose PDA signs as the user transfer authority pass its seeds
+/// through `with_signer`.
+pub struct CpiContext<'a, 'b, T> {
+    /// The swap program
+    pub program: AccountInfo<'a>,
+    /// Accounts of the instruction
+    pub accounts: T,
+    /// Seeds of the program addresses signing the instruction
+    pub signer_seeds: &'b [&'b [&'b [u8]]],
+}
+
+impl<'a, 'b, T> CpiContext<'a, 'b, T> {
+    /// Context of an invocation signed by the transaction signers only
+    pub fn new(program: AccountInfo<'a>, accounts: T) -> Self {
+        Self {
+            program,
+            accounts,
+            signer_seeds: &[],
+        }
+    }
+
+    /// Context of an invocation also signed by program addresses
+    pub fn with_signer(program: AccountInfo<'a>, accounts: T, signer_seeds: &'b [&'b [&'b [u8]]]) -> Self {
+        Self {
+            program,
+            accounts,
+            signer_seeds,
+        }
+    }
+}
+
+/// Accounts of a [Swap](../instruction/enum.SwapInstruction.html) invocation
+pub struct SwapAccounts<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// swap authority
+    pub authority: AccountInfo<'a>,
+    /// user transfer authority
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// Global state account
+    pub global_state: AccountInfo<'a>,
+    /// token_(A|B) SOURCE Account
+    pub source: AccountInfo<'a>,
+    /// token_(A|B) Base Account to swap INTO
+    pub swap_source: AccountInfo<'a>,
+    /// token_(A|B) Base Account to swap FROM
+    pub swap_destination: AccountInfo<'a>,
+    /// token_(A|B) DESTINATION Account
+    pub destination: AccountInfo<'a>,
+    /// Pool token mint
+    pub pool_mint: AccountInfo<'a>,
+    /// Fee account of the SOURCE token, when the swap charges an owner fee
+    pub pool_fee: Option<AccountInfo<'a>>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Price oracle, when the pool has one
+    pub oracle: Option<AccountInfo<'a>>,
+    /// Gate token account or allowlist entry, when the pool is gated
+    pub gate: Option<AccountInfo<'a>>,
+    /// Protocol pool token account, when the pool accrues protocol-owned
+    /// liquidity
+    pub protocol_liquidity: Option<AccountInfo<'a>>,
+    /// SOURCE token account of each enabled fee recipient
+    pub fee_recipients: Vec<AccountInfo<'a>>,
+}
+
+/// Accounts of a [DepositAllTokenTypes](../instruction/enum.SwapInstruction.html) invocation
+pub struct DepositAllTokenTypesAccounts<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// swap authority
+    pub authority: AccountInfo<'a>,
+    /// Global state account
+    pub global_state: AccountInfo<'a>,
+    /// user transfer authority
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// token_a user transfer authority can transfer amount
+    pub deposit_token_a: AccountInfo<'a>,
+    /// token_b user transfer authority can transfer amount
+    pub deposit_token_b: AccountInfo<'a>,
+    /// token_a Base Account to deposit into
+    pub swap_token_a: AccountInfo<'a>,
+    /// token_b Base Account to deposit into
+    pub swap_token_b: AccountInfo<'a>,
+    /// Pool token mint
+    pub pool_mint: AccountInfo<'a>,
+    /// Pool token account to deposit the generated tokens
+    pub destination: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Gate token account or allowlist entry, when the pool is gated
+    pub gate: Option<AccountInfo<'a>>,
+    /// Deposit record of the destination owner, when the pool has a holding
+    /// period
+    pub deposit_record: Option<AccountInfo<'a>>,
+}
+
+/// Accounts of a [WithdrawAllTokenTypes](../instruction/enum.SwapInstruction.html) invocation
+pub struct WithdrawAllTokenTypesAccounts<'a> {
+    /// Token-swap
+    pub swap: AccountInfo<'a>,
+    /// swap authority
+    pub authority: AccountInfo<'a>,
+    /// Global state account
+    pub global_state: AccountInfo<'a>,
+    /// user transfer authority
+    pub user_transfer_authority: AccountInfo<'a>,
+    /// Pool token mint
+    pub pool_mint: AccountInfo<'a>,
+    /// SOURCE Pool account, amount is transferable by user transfer authority
+    pub source: AccountInfo<'a>,
+    /// token_a Swap Account to withdraw FROM
+    pub swap_token_a: AccountInfo<'a>,
+    /// token_b Swap Account to withdraw FROM
+    pub swap_token_b: AccountInfo<'a>,
+    /// token_a user Account to credit
+    pub destination_token_a: AccountInfo<'a>,
+    /// token_b user Account to credit
+    pub destination_token_b: AccountInfo<'a>,
+    /// Token program
+    pub token_program: AccountInfo<'a>,
+    /// Pool token account of the fee owner, when the pool pays its withdrawal
+    /// fee to it
+    pub pool_fee: Option<AccountInfo<'a>>,
+    /// Deposit record of the source owner, when the pool has a holding period
+    pub deposit_record: Option<AccountInfo<'a>>,
+}
+
+/// Invoke a [Swap](../instruction/enum.SwapInstruction.html).
+pub fn swap<'a>(ctx: CpiContext<'a, '_, SwapAccounts<'a>>, data: Swap) -> ProgramResult {
+    let accounts = ctx.accounts;
+    let ix = instruction::swap(
+        ctx.program.key,
+        SwapArgs {
+            token_program_id: *accounts.token_program.key,
+            swap_pubkey: *accounts.swap.key,
+            authority_pubkey: *accounts.authority.key,
+            user_transfer_authority_pubkey: *accounts.user_transfer_authority.key,
+            global_state_pubkey: *accounts.global_state.key,
+            source_pubkey: *accounts.source.key,
+            swap_source_pubkey: *accounts.swap_source.key,
+            swap_destination_pubkey: *accounts.swap_destination.key,
+            destination_pubkey: *accounts.destination.key,
+            pool_mint_pubkey: *accounts.pool_mint.key,
+            pool_fee_pubkey: accounts.pool_fee.as_ref().map(|pool_fee| *pool_fee.key),
+            oracle_pubkey: accounts.oracle.as_ref().map(|oracle| *oracle.key),
+            gate_pubkey: accounts.gate.as_ref().map(|gate| *gate.key),
+            protocol_liquidity_pubkey: accounts
+                .protocol_liquidity
+                .as_ref()
+                .map(|protocol_liquidity| *protocol_liquidity.key),
+            fee_recipient_pubkeys: accounts
+                .fee_recipients
+                .iter()
+                .map(|fee_recipient| *fee_recipient.key)
+                .collect(),
+            tip_pubkey: None,
+            discount_holder_pubkey: None,
+        },
+        data,
+    )?;
+    let mut account_infos = vec![
+        accounts.swap,
+        accounts.authority,
+        accounts.user_transfer_authority,
+        accounts.global_state,
+        accounts.source,
+        accounts.swap_source,
+        accounts.swap_destination,
+        accounts.destination,
+        accounts.pool_mint,
+        accounts.token_program,
+        ctx.program,
+    ];
+    account_infos.extend(
+        accounts
+            .pool_fee
+            .into_iter()
+            .chain(accounts.oracle)
+            .chain(accounts.gate)
+            .chain(accounts.protocol_liquidity)
+            .chain(accounts.fee_recipients),
+    );
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+}
+
+/// Invoke a [DepositAllTokenTypes](../instruction/enum.SwapInstruction.html).
+pub fn deposit_all_token_types<'a>(
+    ctx: CpiContext<'a, '_, DepositAllTokenTypesAccounts<'a>>,
+    data: DepositAllTokenTypes,
+) -> ProgramResult {
+    let accounts = ctx.accounts;
+    let ix = instruction::deposit_all_token_types(
+        ctx.program.key,
+        accounts.token_program.key,
+        accounts.swap.key,
+        accounts.authority.key,
+        accounts.global_state.key,
+        accounts.user_transfer_authority.key,
+        accounts.deposit_token_a.key,
+        accounts.deposit_token_b.key,
+        accounts.swap_token_a.key,
+        accounts.swap_token_b.key,
+        accounts.pool_mint.key,
+        accounts.destination.key,
+        accounts.gate.as_ref().map(|gate| gate.key),
+        accounts.deposit_record.as_ref().map(|deposit_record| deposit_record.key),
+        data,
+    )?;
+    let mut account_infos = vec![
+        accounts.swap,
+        accounts.authority,
+        accounts.global_state,
+        accounts.user_transfer_authority,
+        accounts.deposit_token_a,
+        accounts.deposit_token_b,
+        accounts.swap_token_a,
+        accounts.swap_token_b,
+        accounts.pool_mint,
+        accounts.destination,
+        accounts.token_program,
+        ctx.program,
+    ];
+    account_infos.extend(accounts.gate.into_iter().chain(accounts.deposit_record));
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+}
+
+/// Invoke a [WithdrawAllTokenTypes](../instruction/enum.SwapInstruction.html).
+pub fn withdraw_all_token_types<'a>(
+    ctx: CpiContext<'a, '_, WithdrawAllTokenTypesAccounts<'a>>,
+    data: WithdrawAllTokenTypes,
+) -> ProgramResult {
+    let accounts = ctx.accounts;
+    let ix = instruction::withdraw_all_token_types(
+        ctx.program.key,
+        accounts.token_program.key,
+        accounts.swap.key,
+        accounts.authority.key,
+        accounts.global_state.key,
+        accounts.user_transfer_authority.key,
+        accounts.pool_mint.key,
+        accounts.source.key,
+        accounts.swap_token_a.key,
+        accounts.swap_token_b.key,
+        accounts.destination_token_a.key,
+        accounts.destination_token_b.key,
+        accounts.pool_fee.as_ref().map(|pool_fee| pool_fee.key),
+        accounts.deposit_record.as_ref().map(|deposit_record| deposit_record.key),
+        data,
+    )?;
+    let mut account_infos = vec![
+        accounts.swap,
+        accounts.authority,
+        accounts.global_state,
+        accounts.user_transfer_authority,
+        accounts.pool_mint,
+        accounts.source,
+        accounts.swap_token_a,
+        accounts.swap_token_b,
+        accounts.destination_token_a,
+        accounts.destination_token_b,
+        accounts.token_program,
+        ctx.program,
+    ];
+    account_infos.extend(accounts.pool_fee.into_iter().chain(accounts.deposit_record));
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+}
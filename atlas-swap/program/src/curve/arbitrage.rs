@@ -0,0 +1,212 @@
+//! Client-side helpers for trading a pool back to an external price
+
+use crate::curve::{
+    base::{get_swap_quote, CurveType, SwapCurve, PRICE_IMPACT_PROBE_DIVISOR},
+    calculator::{CurveCalculator, TradeDirection},
+    fees::Fees,
+};
+use spl_math::{approximations::sqrt, uint::U256};
+use std::convert::TryFrom;
+
+/// Number of bisection steps of the numeric search, enough to cover every
+/// u64 input amount
+const SEARCH_ITERATIONS: usize = 64;
+
+/// Find the trade that moves the pool price of token A to the reference
+/// price of `price_numerator / price_denominator` token B per token A, both
+/// in raw token amounts.
+///
+/// Returns the direction and the amount of source token to swap, fees
+/// included, or `None` if the pool is already at the reference price or
+/// cannot reach it. The reserves and fees are the ones `get_swap_quote`
+/// takes.
+///
+/// The constant product curve is solved in closed form, using the base fee
+/// rates of the curve, and the share of the fee kept by the pool carries it
+/// slightly past the reference price. Other curves are solved numerically
+/// against `get_swap_quote`, so fee tiers and the fee kept by the pool are
+/// taken into account.
+pub fn get_arbitrage_amount(
+    reserves_a: u64,
+    reserves_b: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Option<(TradeDirection, u64)> {
+    if price_numerator == 0 || price_denominator == 0 {
+        return None;
+    }
+    match curve.curve_type {
+        // the price of a constant price curve never moves, and an
+        // oracle-pegged curve follows its oracle rather than its reserves
+        CurveType::ConstantPrice | CurveType::OraclePegged => None,
+        CurveType::ConstantProduct => constant_product_arbitrage_amount(
+            reserves_a,
+            reserves_b,
+            price_numerator,
+            price_denominator,
+            fees,
+        ),
+        _ => numeric_arbitrage_amount(
+            reserves_a,
+            reserves_b,
+            price_numerator,
+            price_denominator,
+            fees,
+            curve,
+        ),
+    }
+}
+
+/// Closed form of a constant product pool: the reserves at the reference
+/// price `p` keep the invariant `k`, so the source reserve becomes
+/// `sqrt(k / p)` when selling token A and `sqrt(k * p)` when selling token B.
+fn constant_product_arbitrage_amount(
+    reserves_a: u64,
+    reserves_b: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    fees: &Fees,
+) -> Option<(TradeDirection, u64)> {
+    let invariant = U256::from(reserves_a).checked_mul(U256::from(reserves_b))?;
+    let pool_price = u128::from(reserves_b).checked_mul(u128::from(price_denominator))?;
+    let reference_price = u128::from(price_numerator).checked_mul(u128::from(reserves_a))?;
+    let (trade_direction, swap_source_amount, new_swap_source_squared) =
+        match pool_price.cmp(&reference_price) {
+            std::cmp::Ordering::Greater => (
+                TradeDirection::AtoB,
+                reserves_a,
+                invariant
+                    .checked_mul(U256::from(price_denominator))?
+                    .checked_div(U256::from(price_numerator))?,
+            ),
+            std::cmp::Ordering::Less => (
+                TradeDirection::BtoA,
+                reserves_b,
+                invariant
+                    .checked_mul(U256::from(price_numerator))?
+                    .checked_div(U256::from(price_denominator))?,
+            ),
+            std::cmp::Ordering::Equal => return None,
+        };
+    let new_swap_source_amount = sqrt(u128::try_from(new_swap_source_squared).ok()?)?;
+    let amount_less_fees = new_swap_source_amount.checked_sub(u128::from(swap_source_amount))?;
+    if amount_less_fees == 0 {
+        return None;
+    }
+
+    // gross up by the fees taken out of the source amount before the trade
+    let fee_denominator = u128::from(fees.fee_denominator);
+    let amount_in = if fee_denominator == 0 {
+        amount_less_fees
+    } else {
+        let fee_numerator = u128::from(fees.constant_product_return_fee_numerator)
+            .checked_add(u128::from(fees.constant_product_fixed_fee_numerator))?;
+        let net_denominator = fee_denominator.checked_sub(fee_numerator)?;
+        amount_less_fees
+            .checked_mul(fee_denominator)?
+            .checked_add(net_denominator.checked_sub(1)?)?
+            .checked_div(net_denominator)?
+    };
+    Some((trade_direction, u64::try_from(amount_in).ok()?))
+}
+
+/// Bisect for the largest input amount that does not carry the pool past the
+/// reference price, quoting every step as the swap instruction would.
+fn numeric_arbitrage_amount(
+    reserves_a: u64,
+    reserves_b: u64,
+    price_numerator: u64,
+    price_denominator: u64,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Option<(TradeDirection, u64)> {
+    let trade_direction = match compare_spot_price(
+        curve,
+        u128::from(reserves_a),
+        u128::from(reserves_b),
+        price_numerator,
+        price_denominator,
+    )? {
+        std::cmp::Ordering::Greater => TradeDirection::AtoB,
+        std::cmp::Ordering::Less => TradeDirection::BtoA,
+        std::cmp::Ordering::Equal => return None,
+    };
+    // whether a trade of the amount leaves the pool price on the far side of
+    // the reference price, a trade the curve cannot quote counts as too large
+    let overshoots = |amount_in: u64| -> bool {
+        let result = match get_swap_quote(
+            reserves_a,
+            reserves_b,
+            amount_in,
+            trade_direction,
+            fees,
+            curve,
+        ) {
+            Some(result) => result,
+            None => return true,
+        };
+        let new_swap_source_amount = match result.new_swap_source_amount.checked_sub(result.owner_fee) {
+            Some(amount) => amount,
+            None => return true,
+        };
+        let (token_a_amount, token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (new_swap_source_amount, result.new_swap_destination_amount),
+            TradeDirection::BtoA => (result.new_swap_destination_amount, new_swap_source_amount),
+        };
+        match (
+            trade_direction,
+            compare_spot_price(curve, token_a_amount, token_b_amount, price_numerator, price_denominator),
+        ) {
+            (TradeDirection::AtoB, Some(ordering)) => ordering == std::cmp::Ordering::Less,
+            (TradeDirection::BtoA, Some(ordering)) => ordering == std::cmp::Ordering::Greater,
+            (_, None) => true,
+        }
+    };
+
+    let mut low = 0u64;
+    let mut high = u64::MAX;
+    for _ in 0..SEARCH_ITERATIONS {
+        if high - low <= 1 {
+            break;
+        }
+        let middle = low + (high - low) / 2;
+        if overshoots(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    if low == 0 {
+        None
+    } else {
+        Some((trade_direction, low))
+    }
+}
+
+/// Compare the spot price of token A in token B of the given reserves to the
+/// reference price. The spot price is measured with a trade of a
+/// ten-thousandth of the token A reserve, as `SwapCurve::price_impact` does.
+fn compare_spot_price(
+    curve: &SwapCurve,
+    token_a_amount: u128,
+    token_b_amount: u128,
+    price_numerator: u64,
+    price_denominator: u64,
+) -> Option<std::cmp::Ordering> {
+    let probe_amount = std::cmp::max(token_a_amount / PRICE_IMPACT_PROBE_DIVISOR, 1);
+    let probe = curve.calculator.swap_without_fees(
+        probe_amount,
+        token_a_amount,
+        token_b_amount,
+        TradeDirection::AtoB,
+    )?;
+    let spot_price = probe
+        .destination_amount_swapped
+        .checked_mul(u128::from(price_denominator))?;
+    let reference_price = probe
+        .source_amount_swapped
+        .checked_mul(u128::from(price_numerator))?;
+    Some(spot_price.cmp(&reference_price))
+}
@@ -6,17 +6,23 @@ use solana_program::{
 };
 
 use crate::curve::{
-    calculator::{CurveCalculator, SwapWithoutFeesResult, TradeDirection},
+    calculator::{
+        CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
+        TradingTokenResult,
+    },
     constant_price::ConstantPriceCurve,
     constant_product::ConstantProductCurve,
-    fees::Fees,
+    fees::{calculate_fee, Fees},
+    meta_stable::MetaStableCurve,
     offset::OffsetCurve,
+    oracle_pegged::OraclePeggedCurve,
+    rated_stable::RatedStableCurve,
     stable::StableCurve,
 };
 use crate::error::SwapError;
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use spl_math::precise_number::PreciseNumber;
 use std::convert::{TryFrom, TryInto};
-use std::fmt::Debug;
 
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
@@ -34,8 +40,21 @@ pub enum CurveType {
     Stable,
     /// Offset curve, like Uniswap, but the token B side has a faked offset
     Offset,
+    /// Quotes around the price of an external oracle with a fixed spread
+    OraclePegged,
+    /// Stable curve with token A valued at the exchange rate of a stake pool
+    RatedStable,
+    /// Stable curve with token B, the pool token of a base pool, valued at
+    /// the base pool's virtual price
+    MetaStable,
 }
 
+/// Basis point denominator of price impacts
+pub const PRICE_IMPACT_DENOMINATOR: u128 = 10_000;
+
+/// Share of the source reserve traded to measure the spot price
+pub const PRICE_IMPACT_PROBE_DIVISOR: u128 = 10_000;
+
 /// Encodes all results of swapping from a source token to a destination token
 #[derive(Debug, PartialEq)]
 pub struct SwapResult {
@@ -53,16 +72,223 @@ pub struct SwapResult {
     pub owner_fee: u128,
 }
 
-/// Concrete struct to wrap around the trait object which performs calculation.
+impl SwapResult {
+    /// Amount of source token transferred into the pool, excluding the owner fee
+    pub fn pool_source_amount(&self) -> Option<u128> {
+        self.source_amount_swapped.checked_sub(self.owner_fee)
+    }
+
+    /// Amount of source token actually traded against the curve, excluding fees
+    pub fn source_amount_less_fees(&self) -> Option<u128> {
+        self.source_amount_swapped
+            .checked_sub(self.trade_fee)?
+            .checked_sub(self.owner_fee)
+    }
+}
+
+/// Calculator of each curve type, dispatched statically
+#[derive(Clone, Debug, PartialEq)]
+pub enum Calculator {
+    /// Constant product calculator
+    ConstantProduct(ConstantProductCurve),
+    /// Constant price calculator
+    ConstantPrice(ConstantPriceCurve),
+    /// Stable calculator
+    Stable(StableCurve),
+    /// Offset calculator
+    Offset(OffsetCurve),
+    /// Oracle-pegged calculator
+    OraclePegged(OraclePeggedCurve),
+    /// Rated stable calculator
+    RatedStable(RatedStableCurve),
+    /// Meta stable calculator
+    MetaStable(MetaStableCurve),
+}
+
+/// Runs the given expression with the calculator of every curve type
+macro_rules! dispatch {
+    ($calculator:expr, $inner:ident => $body:expr) => {
+        match $calculator {
+            Calculator::ConstantProduct($inner) => $body,
+            Calculator::ConstantPrice($inner) => $body,
+            Calculator::Stable($inner) => $body,
+            Calculator::Offset($inner) => $body,
+            Calculator::OraclePegged($inner) => $body,
+            Calculator::RatedStable($inner) => $body,
+            Calculator::MetaStable($inner) => $body,
+        }
+    };
+}
+
+impl CurveCalculator for Calculator {
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        dispatch!(self, calculator => calculator.swap_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        ))
+    }
+
+    fn new_pool_supply(&self) -> u64 {
+        dispatch!(self, calculator => calculator.new_pool_supply())
+    }
+
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        dispatch!(self, calculator => calculator.pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        ))
+    }
+
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        dispatch!(self, calculator => calculator.deposit_single_token_type(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        ))
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        dispatch!(self, calculator => calculator.withdraw_single_token_type_exact_out(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        ))
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        dispatch!(self, calculator => calculator.validate())
+    }
+
+    fn get_curve_type(&self) -> CurveType {
+        dispatch!(self, calculator => calculator.get_curve_type())
+    }
+
+    fn validate_supply(&self, token_a_amount: u64, token_b_amount: u64) -> Result<(), SwapError> {
+        dispatch!(self, calculator => calculator.validate_supply(token_a_amount, token_b_amount))
+    }
+
+    fn allows_deposits(&self) -> bool {
+        dispatch!(self, calculator => calculator.allows_deposits())
+    }
+
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        dispatch!(self, calculator => calculator.normalized_value(
+            swap_token_a_amount,
+            swap_token_b_amount,
+        ))
+    }
+
+    fn virtual_price(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+    ) -> Option<u128> {
+        dispatch!(self, calculator => calculator.virtual_price(
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+        ))
+    }
+}
+
+impl DynPack for Calculator {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        dispatch!(self, calculator => DynPack::pack_into_slice(calculator, output))
+    }
+}
+
+impl From<ConstantProductCurve> for Calculator {
+    fn from(calculator: ConstantProductCurve) -> Self {
+        Self::ConstantProduct(calculator)
+    }
+}
+
+impl From<ConstantPriceCurve> for Calculator {
+    fn from(calculator: ConstantPriceCurve) -> Self {
+        Self::ConstantPrice(calculator)
+    }
+}
+
+impl From<StableCurve> for Calculator {
+    fn from(calculator: StableCurve) -> Self {
+        Self::Stable(calculator)
+    }
+}
+
+impl From<OffsetCurve> for Calculator {
+    fn from(calculator: OffsetCurve) -> Self {
+        Self::Offset(calculator)
+    }
+}
+
+impl From<OraclePeggedCurve> for Calculator {
+    fn from(calculator: OraclePeggedCurve) -> Self {
+        Self::OraclePegged(calculator)
+    }
+}
+
+impl From<RatedStableCurve> for Calculator {
+    fn from(calculator: RatedStableCurve) -> Self {
+        Self::RatedStable(calculator)
+    }
+}
+
+impl From<MetaStableCurve> for Calculator {
+    fn from(calculator: MetaStableCurve) -> Self {
+        Self::MetaStable(calculator)
+    }
+}
+
+/// Concrete struct to wrap around the calculator of its curve type.
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct SwapCurve {
     /// The type of curve contained in the calculator, helpful for outside
     /// queries
     pub curve_type: CurveType,
-    /// The actual calculator, represented as a trait object to allow for many
-    /// different types of curves
-    pub calculator: Box<dyn CurveCalculator>,
+    /// The actual calculator, one variant per curve type
+    pub calculator: Calculator,
 }
 
 impl SwapCurve {
@@ -78,7 +304,7 @@ impl SwapCurve {
     ) -> Option<SwapResult> {
         // debit the fee to calculate the amount swapped
         let trade_fee = fees.return_fee(source_amount, self)?;
-        let owner_fee = fees.fixed_fee(source_amount, self)?;
+        let owner_fee = fees.fixed_fee(source_amount, swap_source_amount, self)?;
 
         let total_fees = trade_fee.checked_add(owner_fee)?;
         let source_amount_less_fees = source_amount.checked_sub(total_fees)?;
@@ -105,6 +331,32 @@ impl SwapCurve {
         })
     }
 
+    /// Price impact of a trade, in basis points, comparing its execution
+    /// price without fees to the spot price of the pool. The spot price is
+    /// measured with a trade of a ten-thousandth of the source reserve.
+    pub fn price_impact(
+        &self,
+        source_amount: u128,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let probe_amount = std::cmp::max(swap_source_amount / PRICE_IMPACT_PROBE_DIVISOR, 1);
+        let probe = self.calculator.swap_without_fees(
+            probe_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        )?;
+        // execution price / spot price, in basis points
+        let price_ratio = destination_amount
+            .checked_mul(probe.source_amount_swapped)?
+            .checked_mul(PRICE_IMPACT_DENOMINATOR)?
+            .checked_div(source_amount.checked_mul(probe.destination_amount_swapped)?)?;
+        Some(PRICE_IMPACT_DENOMINATOR.saturating_sub(price_ratio))
+    }
+
     /// Get the amount of pool tokens for the deposited amount of token A or B
     pub fn deposit_single_token_type(
         &self,
@@ -162,40 +414,138 @@ impl SwapCurve {
     }
 }
 
-/// Default implementation for SwapCurve cannot be derived because of
-/// the contained Box.
-impl Default for SwapCurve {
-    fn default() -> Self {
-        let curve_type: CurveType = Default::default();
-        let calculator: ConstantProductCurve = Default::default();
-        Self {
-            curve_type,
-            calculator: Box::new(calculator),
-        }
-    }
+/// Encodes all results of withdrawing pool tokens for both trading tokens
+#[derive(Debug, PartialEq)]
+pub struct WithdrawQuote {
+    /// Amount of pool tokens taken as the withdrawal fee
+    pub withdraw_fee: u128,
+    /// Amount of token A withdrawn
+    pub token_a_amount: u128,
+    /// Amount of token B withdrawn
+    pub token_b_amount: u128,
 }
 
-/// Clone takes advantage of pack / unpack to get around the difficulty of
-/// cloning dynamic objects.
-/// Note that this is only to be used for testing.
-#[cfg(any(test, feature = "fuzz"))]
-impl Clone for SwapCurve {
-    fn clone(&self) -> Self {
-        let mut packed_self = [0u8; Self::LEN];
-        Self::pack_into_slice(self, &mut packed_self);
-        Self::unpack_from_slice(&packed_self).unwrap()
-    }
+/// Quote a swap of `amount_in` source tokens against the pool reserves,
+/// exactly as the swap instruction computes it.
+///
+/// The reserves are the tracked reserves of the pool, or the vault balances
+/// for pools that do not track them. The fees are the program fees with the
+/// pool's volatility and any fee discount applied, see `Fees::with_volatility`
+/// and `Fees::with_discount`.
+pub fn get_swap_quote(
+    reserves_a: u64,
+    reserves_b: u64,
+    amount_in: u64,
+    trade_direction: TradeDirection,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Option<SwapResult> {
+    let (swap_source_amount, swap_destination_amount) = match trade_direction {
+        TradeDirection::AtoB => (reserves_a, reserves_b),
+        TradeDirection::BtoA => (reserves_b, reserves_a),
+    };
+    curve.swap(
+        u128::from(amount_in),
+        u128::from(swap_source_amount),
+        u128::from(swap_destination_amount),
+        trade_direction,
+        fees,
+    )
 }
 
-/// Simple implementation for PartialEq which assumes that the output of
-/// `Pack` is enough to guarantee equality
-impl PartialEq for SwapCurve {
-    fn eq(&self, other: &Self) -> bool {
-        let mut packed_self = [0u8; Self::LEN];
-        Self::pack_into_slice(self, &mut packed_self);
-        let mut packed_other = [0u8; Self::LEN];
-        Self::pack_into_slice(other, &mut packed_other);
-        packed_self[..] == packed_other[..]
+/// `minimum_amount_out` of a swap of `amount_in` source tokens, the quote of
+/// `get_swap_quote` less `slippage_bps` basis points of it.
+///
+/// The quote already takes out the fees, their minimum included, and rounds
+/// the output down as the swap does, so a swap executing at the quoted
+/// reserves always passes. The tolerance is rounded in the trader's favour,
+/// and the minimum is at least one token whenever the quote is, so it never
+/// falls back to an unchecked zero. None when the pool cannot fill the swap
+/// or `slippage_bps` is above 10_000.
+pub fn get_minimum_amount_out(
+    reserves_a: u64,
+    reserves_b: u64,
+    amount_in: u64,
+    trade_direction: TradeDirection,
+    fees: &Fees,
+    curve: &SwapCurve,
+    slippage_bps: u64,
+) -> Option<u64> {
+    let remaining_bps = 10_000u128.checked_sub(u128::from(slippage_bps))?;
+    let quote = get_swap_quote(reserves_a, reserves_b, amount_in, trade_direction, fees, curve)?;
+    let amount_out = quote.destination_amount_swapped;
+    let minimum_amount_out = amount_out
+        .checked_mul(remaining_bps)?
+        .checked_add(9_999)?
+        .checked_div(10_000)?;
+    let minimum_amount_out = std::cmp::max(minimum_amount_out, std::cmp::min(amount_out, 1));
+    u64::try_from(minimum_amount_out).ok()
+}
+
+/// Quote the amounts of token A and B a deposit of `pool_token_amount` pool
+/// tokens requires, exactly as the deposit instruction computes them.
+///
+/// `pool_supply` is the supply of the pool mint, or the initial supply of the
+/// program for the first deposit into an empty pool.
+pub fn get_deposit_quote(
+    reserves_a: u64,
+    reserves_b: u64,
+    pool_supply: u64,
+    pool_token_amount: u64,
+    curve: &SwapCurve,
+) -> Option<TradingTokenResult> {
+    curve.calculator.pool_tokens_to_trading_tokens(
+        u128::from(pool_token_amount),
+        u128::from(pool_supply),
+        u128::from(reserves_a),
+        u128::from(reserves_b),
+        RoundDirection::Ceiling,
+    )
+}
+
+/// Quote the withdrawal fee and the amounts of token A and B a withdrawal of
+/// `pool_token_amount` pool tokens returns, exactly as the withdraw
+/// instruction computes them.
+///
+/// The program never redeems more than the pool mint supply less
+/// `MIN_LP_SUPPLY`, so `pool_token_amount` must already be capped to it.
+/// `withdraw_fee_numerator` is the pool's withdrawal fee over the fee
+/// denominator of `fees`, zero for pools without one.
+pub fn get_withdraw_quote(
+    reserves_a: u64,
+    reserves_b: u64,
+    pool_supply: u64,
+    pool_token_amount: u64,
+    withdraw_fee_numerator: u64,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Option<WithdrawQuote> {
+    let pool_token_amount = u128::from(pool_token_amount);
+    let withdraw_fee = calculate_fee(
+        pool_token_amount,
+        u128::from(withdraw_fee_numerator),
+        u128::from(fees.fee_denominator),
+        u128::from(fees.minimum_fee),
+    )?;
+    let results = curve.calculator.pool_tokens_to_trading_tokens(
+        pool_token_amount.checked_sub(withdraw_fee)?,
+        u128::from(pool_supply),
+        u128::from(reserves_a),
+        u128::from(reserves_b),
+        RoundDirection::Floor,
+    )?;
+    Some(WithdrawQuote {
+        withdraw_fee,
+        token_a_amount: std::cmp::min(u128::from(reserves_a), results.token_a_amount),
+        token_b_amount: std::cmp::min(u128::from(reserves_b), results.token_b_amount),
+    })
+}
+
+/// Sensible default of the calculator to the constant product curve, as for
+/// CurveType
+impl Default for Calculator {
+    fn default() -> Self {
+        Self::ConstantProduct(ConstantProductCurve)
     }
 }
 
@@ -220,13 +570,14 @@ impl Pack for SwapCurve {
             curve_type,
             calculator: match curve_type {
                 CurveType::ConstantProduct => {
-                    Box::new(ConstantProductCurve::unpack_from_slice(calculator)?)
-                }
-                CurveType::ConstantPrice => {
-                    Box::new(ConstantPriceCurve::unpack_from_slice(calculator)?)
+                    ConstantProductCurve::unpack_from_slice(calculator)?.into()
                 }
-                CurveType::Stable => Box::new(StableCurve::unpack_from_slice(calculator)?),
-                CurveType::Offset => Box::new(OffsetCurve::unpack_from_slice(calculator)?),
+                CurveType::ConstantPrice => ConstantPriceCurve::unpack_from_slice(calculator)?.into(),
+                CurveType::Stable => StableCurve::unpack_from_slice(calculator)?.into(),
+                CurveType::Offset => OffsetCurve::unpack_from_slice(calculator)?.into(),
+                CurveType::OraclePegged => OraclePeggedCurve::unpack_from_slice(calculator)?.into(),
+                CurveType::RatedStable => RatedStableCurve::unpack_from_slice(calculator)?.into(),
+                CurveType::MetaStable => MetaStableCurve::unpack_from_slice(calculator)?.into(),
             },
         })
     }
@@ -257,6 +608,9 @@ impl TryFrom<u8> for CurveType {
             1 => Ok(CurveType::ConstantPrice),
             2 => Ok(CurveType::Stable),
             3 => Ok(CurveType::Offset),
+            4 => Ok(CurveType::OraclePegged),
+            5 => Ok(CurveType::RatedStable),
+            6 => Ok(CurveType::MetaStable),
             _ => Err(ProgramError::InvalidAccountData),
         }
     }
@@ -0,0 +1,115 @@
+//! Constant product invariant generalized to baskets of up to
+//! MAX_BASKET_ASSETS equally-weighted assets.
+//!
+//! The invariant is the product of all reserves. A swap between two assets
+//! leaves the others untouched, so it keeps the product of the two traded
+//! reserves constant, which is the two-asset constant product swap.
+
+use {
+    crate::curve::{
+        base::{CurveType, SwapCurve, SwapResult},
+        calculator::{RoundDirection, TradeDirection},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    spl_math::uint::U256,
+};
+
+/// Most assets a basket pool may hold
+pub const MAX_BASKET_ASSETS: usize = 4;
+
+/// Fewest assets a basket pool may hold
+pub const MIN_BASKET_ASSETS: usize = 2;
+
+/// Subtract fees and calculate how much of the destination asset will be
+/// provided given an amount of the source asset, the fees being those of a
+/// constant product pool.
+pub fn swap(
+    source_amount: u128,
+    swap_source_amount: u128,
+    swap_destination_amount: u128,
+    fees: &Fees,
+) -> Option<SwapResult> {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+    .swap(
+        source_amount,
+        swap_source_amount,
+        swap_destination_amount,
+        TradeDirection::AtoB,
+        fees,
+    )
+}
+
+/// Get the amount of each asset for the given amount of pool tokens,
+/// provided the reserves and supply of pool tokens.
+///
+/// As for the two-asset constant product, amounts are proportional to the
+/// reserves. Rounding up does not take an asset a deposit is worth none of,
+/// for it to be rejected later in processing.
+pub fn pool_tokens_to_trading_tokens(
+    pool_tokens: u128,
+    pool_token_supply: u128,
+    reserves: &[u128],
+    round_direction: RoundDirection,
+) -> Option<Vec<u128>> {
+    reserves
+        .iter()
+        .map(|reserve| {
+            let value = pool_tokens.checked_mul(*reserve)?;
+            let amount = value.checked_div(pool_token_supply)?;
+            match round_direction {
+                RoundDirection::Floor => Some(amount),
+                RoundDirection::Ceiling => {
+                    if value.checked_rem(pool_token_supply)? > 0 && amount > 0 {
+                        amount.checked_add(1)
+                    } else {
+                        Some(amount)
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Geometric mean of the given amounts, rounded down: the n-th root of the
+/// invariant of a basket holding them.
+pub fn geometric_mean(amounts: &[u128]) -> Option<u128> {
+    if amounts.is_empty() || amounts.len() > MAX_BASKET_ASSETS {
+        return None;
+    }
+    let invariant = invariant(amounts)?;
+    // the mean is at most the largest amount, so it is found by bisection
+    // over that range
+    let mut low = 0u128;
+    let mut high = *amounts.iter().max()?;
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if checked_pow(mid, amounts.len()).is_some_and(|power| power <= invariant) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(low)
+}
+
+/// `base` to the power of `exponent`, or None if it overflows a U256
+fn checked_pow(base: u128, exponent: usize) -> Option<U256> {
+    let mut power = U256::one();
+    for _ in 0..exponent {
+        power = power.checked_mul(U256::from(base))?;
+    }
+    Some(power)
+}
+
+/// Invariant of a basket with the given reserves
+pub fn invariant(reserves: &[u128]) -> Option<U256> {
+    reserves
+        .iter()
+        .try_fold(U256::one(), |invariant, reserve| {
+            invariant.checked_mul(U256::from(*reserve))
+        })
+}
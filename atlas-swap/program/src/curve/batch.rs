@@ -0,0 +1,138 @@
+//! Uniform clearing price of a batch auction
+
+use crate::curve::{
+    base::{get_swap_quote, SwapCurve, PRICE_IMPACT_PROBE_DIVISOR},
+    calculator::TradeDirection,
+    fees::Fees,
+};
+use std::convert::TryFrom;
+
+/// Number of bisection steps of the search for the pool trade, enough to
+/// cover every u64 input amount
+const SEARCH_ITERATIONS: usize = 64;
+
+/// Settlement of a batch of swaps: the swaps in opposite directions are
+/// crossed with each other and only the imbalance is traded against the pool
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BatchClearing {
+    /// Direction and input amount of the trade against the pool, if any
+    pub pool_trade: Option<(TradeDirection, u64)>,
+    /// Output amount of the trade against the pool
+    pub pool_amount_out: u64,
+    /// Token B shared by the sellers of token A
+    pub token_b_out: u128,
+    /// Token A shared by the sellers of token B
+    pub token_a_out: u128,
+}
+
+impl BatchClearing {
+    /// Output of a swap of `amount_in` out of the batch, its pro rata share
+    /// of the output of its side at the clearing price
+    pub fn amount_out(
+        &self,
+        trade_direction: TradeDirection,
+        amount_in: u64,
+        token_a_in: u64,
+        token_b_in: u64,
+    ) -> Option<u128> {
+        let (side_in, side_out) = match trade_direction {
+            TradeDirection::AtoB => (token_a_in, self.token_b_out),
+            TradeDirection::BtoA => (token_b_in, self.token_a_out),
+        };
+        u128::from(amount_in)
+            .checked_mul(side_out)?
+            .checked_div(u128::from(side_in))
+    }
+}
+
+/// Clear a batch selling `token_a_in` token A and `token_b_in` token B
+/// against a pool with the given reserves, fees and curve, as `get_swap_quote`
+/// takes them.
+///
+/// Every swap of the batch trades at the same price, the average price of
+/// the pool trade: the surplus side sells to the pool only as much as keeps
+/// the pool's price at least as good as the price of crossing the rest with
+/// the other side. When the pool cannot improve on crossing, both sides
+/// simply swap their inputs.
+pub fn get_batch_clearing(
+    reserves_a: u64,
+    reserves_b: u64,
+    token_a_in: u64,
+    token_b_in: u64,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Option<BatchClearing> {
+    for trade_direction in [TradeDirection::AtoB, TradeDirection::BtoA] {
+        let (surplus_in, other_in) = match trade_direction {
+            TradeDirection::AtoB => (token_a_in, token_b_in),
+            TradeDirection::BtoA => (token_b_in, token_a_in),
+        };
+        // whether selling `amount_in` to the pool pays at least the price
+        // the other side pays for the remaining surplus
+        let pool_price_holds = |amount_in: u64| -> Option<(u64, u64)> {
+            let result = get_swap_quote(
+                reserves_a,
+                reserves_b,
+                amount_in,
+                trade_direction,
+                fees,
+                curve,
+            )?;
+            let amount_spent = u64::try_from(result.source_amount_swapped).ok()?;
+            let amount_out = u64::try_from(result.destination_amount_swapped).ok()?;
+            let pool_value = u128::from(amount_out)
+                .checked_mul(u128::from(surplus_in.checked_sub(amount_spent)?))?;
+            let crossing_value = u128::from(other_in).checked_mul(u128::from(amount_spent))?;
+            if amount_out > 0 && pool_value >= crossing_value {
+                Some((amount_spent, amount_out))
+            } else {
+                None
+            }
+        };
+        // a trade too small for the pool to output anything does not tell
+        // the direction, so probe with a ten-thousandth of the surplus
+        let probe_amount = std::cmp::max(surplus_in / PRICE_IMPACT_PROBE_DIVISOR as u64, 1);
+        if surplus_in == 0 || pool_price_holds(probe_amount).is_none() {
+            continue;
+        }
+
+        let mut low = probe_amount;
+        let mut high = surplus_in;
+        if pool_price_holds(high).is_none() {
+            for _ in 0..SEARCH_ITERATIONS {
+                if high - low <= 1 {
+                    break;
+                }
+                let middle = low + (high - low) / 2;
+                if pool_price_holds(middle).is_some() {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+        } else {
+            low = high;
+        }
+        let (amount_spent, amount_out) = pool_price_holds(low)?;
+        let surplus_out = u128::from(other_in).checked_add(u128::from(amount_out))?;
+        let other_out = u128::from(surplus_in.checked_sub(amount_spent)?);
+        let (token_b_out, token_a_out) = match trade_direction {
+            TradeDirection::AtoB => (surplus_out, other_out),
+            TradeDirection::BtoA => (other_out, surplus_out),
+        };
+        return Some(BatchClearing {
+            pool_trade: Some((trade_direction, low)),
+            pool_amount_out: amount_out,
+            token_b_out,
+            token_a_out,
+        });
+    }
+
+    // the pool does not beat crossing the two sides with each other
+    Some(BatchClearing {
+        pool_trade: None,
+        pool_amount_out: 0,
+        token_b_out: u128::from(token_b_in),
+        token_a_out: u128::from(token_a_in),
+    })
+}
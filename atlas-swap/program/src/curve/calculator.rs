@@ -16,6 +16,10 @@ pub const INITIAL_SWAP_POOL_AMOUNT: u64 = 1_000_000_000;
 /// equivalent pool tokens for the owner trading fee.
 pub const TOKENS_IN_POOL: u128 = 2;
 
+/// Precision of virtual prices, a virtual price of `VIRTUAL_PRICE_PRECISION`
+/// means one pool token is worth one unit of the curve invariant.
+pub const VIRTUAL_PRICE_PRECISION: u128 = 1_000_000_000_000;
+
 /// Helper function for mapping to SwapError::CalculationFailure
 pub fn map_zero_to_none(x: u128) -> Option<u128> {
     if x == 0 {
@@ -193,4 +197,19 @@ pub trait CurveCalculator: Debug + DynPack {
         swap_token_a_amount: u128,
         swap_token_b_amount: u128,
     ) -> Option<PreciseNumber>;
+
+    /// Calculates the value of one pool token in units of the curve
+    /// invariant, scaled by `VIRTUAL_PRICE_PRECISION`.
+    ///
+    /// Unlike spot reserves, the invariant cannot be moved by a trade, so the
+    /// virtual price is safe to value pool tokens with. Curves without such
+    /// an invariant return `None`.
+    fn virtual_price(
+        &self,
+        _swap_token_a_amount: u128,
+        _swap_token_b_amount: u128,
+        _pool_supply: u128,
+    ) -> Option<u128> {
+        None
+    }
 }
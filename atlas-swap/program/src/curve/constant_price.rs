@@ -14,6 +14,7 @@ use {
         program_pack::{IsInitialized, Pack, Sealed},
     },
     spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber, uint::U256},
+    std::convert::TryFrom,
 };
 
 /// Get the amount of pool tokens for the given amount of token A or B.
@@ -39,21 +40,19 @@ pub fn trading_tokens_to_pool_tokens(
         .checked_mul(token_b_price)?
         .checked_add(U256::from(swap_token_a_amount))?;
     let pool_supply = U256::from(pool_supply);
-    match round_direction {
-        RoundDirection::Floor => Some(
-            pool_supply
-                .checked_mul(given_value)?
-                .checked_div(total_value)?
-                .as_u128(),
-        ),
-        RoundDirection::Ceiling => Some(
+    let pool_tokens = match round_direction {
+        RoundDirection::Floor => pool_supply
+            .checked_mul(given_value)?
+            .checked_div(total_value)?,
+        RoundDirection::Ceiling => {
             pool_supply
                 .checked_mul(given_value)?
                 .checked_ceil_div(total_value)?
                 .0
-                .as_u128(),
-        ),
-    }
+        }
+    };
+    // a source worth many times the pool mints more than a u128 holds
+    u128::try_from(pool_tokens).ok()
 }
 
 /// ConstantPriceCurve struct implementing CurveCalculator
@@ -0,0 +1,74 @@
+//! Client-side helpers for quoting the depth of a pool
+
+use crate::curve::{base::get_swap_quote, base::SwapCurve, calculator::TradeDirection, fees::Fees};
+use std::convert::TryFrom;
+
+/// Number of input sizes of a depth chart
+pub const DEPTH_LADDER_POINTS: usize = 10;
+
+/// Output of a swap of one input size
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepthQuote {
+    /// Amount of source token swapped, fees included
+    pub amount_in: u64,
+    /// Amount of destination token the swap outputs
+    pub amount_out: u64,
+}
+
+/// Up to `points` input sizes from `min_amount` to `max_amount`, both
+/// included, each a constant ratio above the previous one. Sizes too close
+/// to round apart are only returned once.
+pub fn log_spaced_amounts(min_amount: u64, max_amount: u64, points: usize) -> Vec<u64> {
+    let min_amount = std::cmp::max(min_amount, 1);
+    if points == 0 || max_amount < min_amount {
+        return Vec::new();
+    }
+    if points == 1 || max_amount == min_amount {
+        return vec![max_amount];
+    }
+    let ratio = (max_amount as f64 / min_amount as f64).powf(1.0 / (points - 1) as f64);
+    let mut amounts: Vec<u64> = Vec::with_capacity(points);
+    for point in 0..points {
+        let amount = if point == points - 1 {
+            max_amount
+        } else {
+            let amount = (min_amount as f64 * ratio.powi(point as i32)).round() as u64;
+            amount.clamp(min_amount, max_amount)
+        };
+        if amounts.last().is_none_or(|last| amount > *last) {
+            amounts.push(amount);
+        }
+    }
+    amounts
+}
+
+/// Quote swaps of each of `amounts_in` source tokens against the pool
+/// reserves, as `get_swap_quote` does, so a depth chart or the marginal
+/// price between two sizes takes no simulation. Sizes the pool cannot fill
+/// are left out.
+pub fn get_depth_quotes(
+    reserves_a: u64,
+    reserves_b: u64,
+    amounts_in: &[u64],
+    trade_direction: TradeDirection,
+    fees: &Fees,
+    curve: &SwapCurve,
+) -> Vec<DepthQuote> {
+    amounts_in
+        .iter()
+        .filter_map(|&amount_in| {
+            let result = get_swap_quote(
+                reserves_a,
+                reserves_b,
+                amount_in,
+                trade_direction,
+                fees,
+                curve,
+            )?;
+            Some(DepthQuote {
+                amount_in,
+                amount_out: u64::try_from(result.destination_amount_swapped).ok()?,
+            })
+        })
+        .collect()
+}
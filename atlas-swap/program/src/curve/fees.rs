@@ -5,6 +5,7 @@ use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
 };
 use crate::{
     curve::{
@@ -30,14 +31,212 @@ pub struct Fees {
     pub stable_fixed_fee_numerator: u64,
 
     /// fee dominator 
-    pub fee_denominator: u64
+    pub fee_denominator: u64,
+
+    /// fixed fee numerator used in dynamic-fee mode when the pool is calm
+    pub dynamic_fee_min_numerator: u64,
+
+    /// fixed fee numerator used in dynamic-fee mode at or above
+    /// `DYNAMIC_FEE_VOLATILITY_CEILING`, zero disables dynamic fees
+    pub dynamic_fee_max_numerator: u64,
+
+    /// fixed fee schedule for large trades, ordered by ascending threshold
+    pub fee_tiers: [FeeTier; FEE_TIER_COUNT],
+
+    /// fee charged, in base units of the charged token, when a nonzero fee
+    /// rounds down to zero, zero charging nothing then
+    pub minimum_fee: u64,
+}
+
+/// Number of entries in the trade size fee schedule
+pub const FEE_TIER_COUNT: usize = 2;
+
+/// Basis point denominator of the fee tier thresholds
+pub const FEE_TIER_THRESHOLD_DENOMINATOR: u128 = 10_000;
+
+/// One entry of the trade size fee schedule
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeTier {
+    /// trade size, in basis points of the source reserve, from which this tier
+    /// applies, zero disables the tier
+    pub threshold_bps: u64,
+
+    /// fixed fee numerator charged to trades at or above the threshold
+    pub fixed_fee_numerator: u64,
+}
+
+impl FeeTier {
+    /// Size of a packed fee tier
+    pub const LEN: usize = 16;
+
+    /// Whether the tier takes part in the schedule
+    pub fn is_enabled(&self) -> bool {
+        self.threshold_bps > 0
+    }
+
+    /// Whether a trade of the given size against the given source reserve
+    /// falls in this tier
+    pub fn applies(&self, trading_tokens: u128, swap_source_amount: u128) -> Option<bool> {
+        if !self.is_enabled() {
+            return Some(false);
+        }
+        let threshold = swap_source_amount
+            .checked_mul(u128::from(self.threshold_bps))?
+            .checked_div(FEE_TIER_THRESHOLD_DENOMINATOR)?;
+        Some(trading_tokens >= threshold)
+    }
+}
+
+/// Realized volatility, in basis points, at which the dynamic fixed fee
+/// reaches `dynamic_fee_max_numerator`
+pub const DYNAMIC_FEE_VOLATILITY_CEILING: u64 = 1_000;
+
+/// Basis point denominator of the holder fee discount
+pub const FEE_DISCOUNT_DENOMINATOR: u64 = 10_000;
+
+/// Fixed fee discount granted to traders holding a designated token
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeeDiscount {
+    /// mint of the token that must be held
+    pub mint: Pubkey,
+
+    /// minimum balance of the holder token account
+    pub minimum_balance: u64,
+
+    /// discount on the fixed fee in basis points, zero disables the discount
+    pub discount_bps: u64,
+}
+
+impl FeeDiscount {
+    /// Whether holders are granted a discount
+    pub fn is_enabled(&self) -> bool {
+        self.discount_bps > 0
+    }
+
+    /// Validate that the discount is reasonable
+    pub fn validate(&self) -> Result<(), SwapError> {
+        if self.discount_bps > FEE_DISCOUNT_DENOMINATOR {
+            Err(SwapError::InvalidFee)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for FeeDiscount {
+    fn is_initialized(&self) -> bool {
+        true
+    }
 }
+impl Sealed for FeeDiscount {}
+impl Pack for FeeDiscount {
+    const LEN: usize = 48;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 48];
+        let (mint, minimum_balance, discount_bps) = mut_array_refs![output, 32, 8, 8];
+        mint.copy_from_slice(self.mint.as_ref());
+        *minimum_balance = self.minimum_balance.to_le_bytes();
+        *discount_bps = self.discount_bps.to_le_bytes();
+    }
 
-/// Helper function for calculating swap fee
+    fn unpack_from_slice(input: &[u8]) -> Result<FeeDiscount, ProgramError> {
+        if input.len() != Self::LEN {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        let input = array_ref![input, 0, 48];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (mint, minimum_balance, discount_bps) = array_refs![input, 32, 8, 8];
+        Ok(Self {
+            mint: Pubkey::new_from_array(*mint),
+            minimum_balance: u64::from_le_bytes(*minimum_balance),
+            discount_bps: u64::from_le_bytes(*discount_bps),
+        })
+    }
+}
+
+/// Number of recipients the owner fee can be split among
+pub const FEE_RECIPIENT_COUNT: usize = 3;
+
+/// Basis point denominator of the fee recipient weights
+pub const FEE_RECIPIENT_WEIGHT_DENOMINATOR: u64 = 10_000;
+
+/// Recipient of a share of the owner fee, the fee owner keeps the rest
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeRecipient {
+    /// owner of the token accounts receiving the share
+    pub owner: Pubkey,
+
+    /// share of the owner fee in basis points, zero disables the recipient
+    pub weight_bps: u64,
+}
+
+impl FeeRecipient {
+    /// Whether the recipient takes a share of the owner fee
+    pub fn is_enabled(&self) -> bool {
+        self.weight_bps > 0
+    }
+
+    /// Share of the given owner fee paid to the recipient
+    pub fn share(&self, owner_fee: u128) -> Option<u128> {
+        owner_fee
+            .checked_mul(u128::from(self.weight_bps))?
+            .checked_div(u128::from(FEE_RECIPIENT_WEIGHT_DENOMINATOR))
+    }
+
+    /// Validate that the weights of all recipients fit in the owner fee
+    pub fn validate_all(fee_recipients: &[FeeRecipient]) -> Result<(), SwapError> {
+        let mut total_weight_bps: u64 = 0;
+        for fee_recipient in fee_recipients.iter() {
+            total_weight_bps = total_weight_bps
+                .checked_add(fee_recipient.weight_bps)
+                .ok_or(SwapError::InvalidFee)?;
+        }
+        if total_weight_bps > FEE_RECIPIENT_WEIGHT_DENOMINATOR {
+            Err(SwapError::InvalidFee)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for FeeRecipient {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for FeeRecipient {}
+impl Pack for FeeRecipient {
+    const LEN: usize = 40;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 40];
+        let (owner, weight_bps) = mut_array_refs![output, 32, 8];
+        owner.copy_from_slice(self.owner.as_ref());
+        *weight_bps = self.weight_bps.to_le_bytes();
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<FeeRecipient, ProgramError> {
+        if input.len() != Self::LEN {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        let input = array_ref![input, 0, 40];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (owner, weight_bps) = array_refs![input, 32, 8];
+        Ok(Self {
+            owner: Pubkey::new_from_array(*owner),
+            weight_bps: u64::from_le_bytes(*weight_bps),
+        })
+    }
+}
+
+/// Helper function for calculating swap fee, a fee rounding down to zero
+/// being raised to the given minimum
 pub fn calculate_fee(
     token_amount: u128,
     fee_numerator: u128,
     fee_denominator: u128,
+    minimum_fee: u128,
 ) -> Option<u128> {
     if fee_numerator == 0 || token_amount == 0 {
         Some(0)
@@ -46,7 +245,7 @@ pub fn calculate_fee(
             .checked_mul(fee_numerator)?
             .checked_div(fee_denominator)?;
         if fee == 0 {
-            Some(1) // minimum fee of one token
+            Some(minimum_fee)
         } else {
             Some(fee)
         }
@@ -71,7 +270,7 @@ impl Fees {
             CurveType::ConstantProduct => {
                 return_fee_numerator = self.constant_product_return_fee_numerator;
             }
-            CurveType::Stable => {
+            CurveType::Stable | CurveType::RatedStable | CurveType::MetaStable => {
                 return_fee_numerator = self.stable_return_fee_numerator;
             }
             _ => {
@@ -82,30 +281,106 @@ impl Fees {
             trading_tokens,
             u128::try_from(return_fee_numerator).ok()?,
             u128::try_from(self.fee_denominator).ok()?,
+            u128::from(self.minimum_fee),
         )
     }
 
-    /// Calculate the trading fee in trading tokens
-    pub fn fixed_fee(&self, trading_tokens: u128,swap_curve: &SwapCurve) -> Option<u128> {
-        let fixed_fee_numerator;
+    /// Calculate the trading fee in trading tokens. Trades that are large
+    /// relative to the source reserve pay the numerator of the highest tier
+    /// they reach, if it is above the curve's base fixed fee.
+    pub fn fixed_fee(
+        &self,
+        trading_tokens: u128,
+        swap_source_amount: u128,
+        swap_curve: &SwapCurve,
+    ) -> Option<u128> {
+        let mut fixed_fee_numerator;
         match swap_curve.curve_type {
             CurveType::ConstantProduct => {
                 fixed_fee_numerator = self.constant_product_fixed_fee_numerator;
             }
-            CurveType::Stable => {
+            CurveType::Stable | CurveType::RatedStable | CurveType::MetaStable => {
                 fixed_fee_numerator = self.stable_fixed_fee_numerator;
             }
             _ => {
                 fixed_fee_numerator = self.constant_product_fixed_fee_numerator;
             }
         }
+        for tier in self.fee_tiers.iter() {
+            if tier.applies(trading_tokens, swap_source_amount)? {
+                fixed_fee_numerator = std::cmp::max(fixed_fee_numerator, tier.fixed_fee_numerator);
+            }
+        }
         calculate_fee(
             trading_tokens,
             u128::try_from(fixed_fee_numerator).ok()?,
             u128::try_from(self.fee_denominator).ok()?,
+            u128::from(self.minimum_fee),
         )
     }
     
+    /// Whether the fixed fee scales with the pool's realized volatility
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic_fee_max_numerator > 0
+    }
+
+    /// Fees in effect for a pool with the given realized volatility (in basis
+    /// points). In dynamic-fee mode the fixed fee numerator of every curve is
+    /// interpolated between the dynamic min and max numerators.
+    pub fn with_volatility(&self, volatility: u64) -> Option<Fees> {
+        let mut fees = self.clone();
+        if !self.is_dynamic() {
+            return Some(fees);
+        }
+        let range = self
+            .dynamic_fee_max_numerator
+            .checked_sub(self.dynamic_fee_min_numerator)?;
+        let volatility = std::cmp::min(volatility, DYNAMIC_FEE_VOLATILITY_CEILING);
+        let scaled = u128::from(range)
+            .checked_mul(u128::from(volatility))?
+            .checked_div(u128::from(DYNAMIC_FEE_VOLATILITY_CEILING))?;
+        let numerator = self
+            .dynamic_fee_min_numerator
+            .checked_add(u64::try_from(scaled).ok()?)?;
+        fees.constant_product_fixed_fee_numerator = numerator;
+        fees.stable_fixed_fee_numerator = numerator;
+        Some(fees)
+    }
+
+    /// Fees with every fixed fee numerator reduced by the given discount, in
+    /// basis points
+    pub fn with_discount(&self, discount_bps: u64) -> Option<Fees> {
+        let discount = |numerator: u64| -> Option<u64> {
+            let reduced = u128::from(numerator)
+                .checked_mul(u128::from(FEE_DISCOUNT_DENOMINATOR.checked_sub(discount_bps)?))?
+                .checked_div(u128::from(FEE_DISCOUNT_DENOMINATOR))?;
+            u64::try_from(reduced).ok()
+        };
+        let mut fees = self.clone();
+        fees.constant_product_fixed_fee_numerator = discount(self.constant_product_fixed_fee_numerator)?;
+        fees.stable_fixed_fee_numerator = discount(self.stable_fixed_fee_numerator)?;
+        for tier in fees.fee_tiers.iter_mut() {
+            tier.fixed_fee_numerator = discount(tier.fixed_fee_numerator)?;
+        }
+        Some(fees)
+    }
+
+    /// Enabled tiers must have ascending thresholds and numerators that leave
+    /// room for the return fee of every curve
+    fn fee_tiers_are_valid(&self) -> bool {
+        let mut previous_threshold = 0;
+        for tier in self.fee_tiers.iter().filter(|tier| tier.is_enabled()) {
+            if tier.threshold_bps <= previous_threshold
+                || tier.fixed_fee_numerator >= self.fee_denominator - self.constant_product_return_fee_numerator
+                || tier.fixed_fee_numerator >= self.fee_denominator - self.stable_return_fee_numerator
+            {
+                return false;
+            }
+            previous_threshold = tier.threshold_bps;
+        }
+        true
+    }
+
     /// Validate that the fees are reasonable
     pub fn validate(&self) -> Result<(), SwapError> {
 
@@ -113,7 +388,9 @@ impl Fees {
             self.constant_product_fixed_fee_numerator == 0  && 
             self.stable_fixed_fee_numerator == 0  && 
             self.constant_product_return_fee_numerator == 0  && 
-            self.stable_return_fee_numerator == 0
+            self.stable_return_fee_numerator == 0 &&
+            !self.is_dynamic() &&
+            !self.fee_tiers.iter().any(|tier| tier.is_enabled())
         {
             Ok(())
         } else if   self.constant_product_fixed_fee_numerator >= self.fee_denominator ||  
@@ -121,7 +398,13 @@ impl Fees {
                     self.constant_product_return_fee_numerator >= self.fee_denominator || 
                     self.stable_return_fee_numerator >= self.fee_denominator || 
                     self.constant_product_fixed_fee_numerator >= self.fee_denominator - self.constant_product_return_fee_numerator ||
-                    self.stable_fixed_fee_numerator >= self.fee_denominator - self.stable_return_fee_numerator
+                    self.stable_fixed_fee_numerator >= self.fee_denominator - self.stable_return_fee_numerator ||
+                    (self.is_dynamic() && (
+                        self.dynamic_fee_min_numerator > self.dynamic_fee_max_numerator ||
+                        self.dynamic_fee_max_numerator >= self.fee_denominator - self.constant_product_return_fee_numerator ||
+                        self.dynamic_fee_max_numerator >= self.fee_denominator - self.stable_return_fee_numerator
+                    )) ||
+                    !self.fee_tiers_are_valid()
         {
             Err(SwapError::InvalidFee)
         } else {
@@ -138,28 +421,39 @@ impl IsInitialized for Fees {
 }
 impl Sealed for Fees {}
 impl Pack for Fees {
-    const LEN: usize = 40;
+    const LEN: usize = 96;
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, 40];
+        let output = array_mut_ref![output, 0, 96];
         let (
             constant_product_return_fee_numerator,
             constant_product_fixed_fee_numerator,
             stable_return_fee_numerator,
             stable_fixed_fee_numerator,
             fee_denominator,
-        ) = mut_array_refs![output, 8, 8, 8, 8, 8];
+            dynamic_fee_min_numerator,
+            dynamic_fee_max_numerator,
+            fee_tiers,
+            minimum_fee,
+        ) = mut_array_refs![output, 8, 8, 8, 8, 8, 8, 8, 32, 8];
         *constant_product_return_fee_numerator = self.constant_product_return_fee_numerator.to_le_bytes();
         *constant_product_fixed_fee_numerator = self.constant_product_fixed_fee_numerator.to_le_bytes();
         *stable_return_fee_numerator = self.stable_return_fee_numerator.to_le_bytes();
         *stable_fixed_fee_numerator = self.stable_fixed_fee_numerator.to_le_bytes();
         *fee_denominator = self.fee_denominator.to_le_bytes();
+        *dynamic_fee_min_numerator = self.dynamic_fee_min_numerator.to_le_bytes();
+        *dynamic_fee_max_numerator = self.dynamic_fee_max_numerator.to_le_bytes();
+        for (tier, output) in self.fee_tiers.iter().zip(fee_tiers.chunks_exact_mut(FeeTier::LEN)) {
+            output[..8].copy_from_slice(&tier.threshold_bps.to_le_bytes());
+            output[8..].copy_from_slice(&tier.fixed_fee_numerator.to_le_bytes());
+        }
+        *minimum_fee = self.minimum_fee.to_le_bytes();
     }
 
     fn unpack_from_slice(input: &[u8]) -> Result<Fees, ProgramError> {
-        if input.len() < Self::LEN{
+        if input.len() != Self::LEN {
             return Err(SwapError::InvalidInstruction.into());    
         }
-        let input = array_ref![input, 0, 40];
+        let input = array_ref![input, 0, 96];
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             constant_product_return_fee_numerator,
@@ -167,13 +461,28 @@ impl Pack for Fees {
             stable_return_fee_numerator,
             stable_fixed_fee_numerator,
             fee_denominator,
-        ) = array_refs![input, 8, 8, 8, 8, 8];
+            dynamic_fee_min_numerator,
+            dynamic_fee_max_numerator,
+            fee_tiers_data,
+            minimum_fee,
+        ) = array_refs![input, 8, 8, 8, 8, 8, 8, 8, 32, 8];
+        let mut fee_tiers = [FeeTier::default(); FEE_TIER_COUNT];
+        for (tier, input) in fee_tiers.iter_mut().zip(fee_tiers_data.chunks_exact(FeeTier::LEN)) {
+            let input = array_ref![input, 0, FeeTier::LEN];
+            let (threshold_bps, fixed_fee_numerator) = array_refs![input, 8, 8];
+            tier.threshold_bps = u64::from_le_bytes(*threshold_bps);
+            tier.fixed_fee_numerator = u64::from_le_bytes(*fixed_fee_numerator);
+        }
         Ok(Self {
             constant_product_return_fee_numerator: u64::from_le_bytes(*constant_product_return_fee_numerator),
             constant_product_fixed_fee_numerator: u64::from_le_bytes(*constant_product_fixed_fee_numerator),
             stable_return_fee_numerator: u64::from_le_bytes(*stable_return_fee_numerator),
             stable_fixed_fee_numerator: u64::from_le_bytes(*stable_fixed_fee_numerator),
             fee_denominator: u64::from_le_bytes(*fee_denominator),
+            dynamic_fee_min_numerator: u64::from_le_bytes(*dynamic_fee_min_numerator),
+            dynamic_fee_max_numerator: u64::from_le_bytes(*dynamic_fee_max_numerator),
+            fee_tiers,
+            minimum_fee: u64::from_le_bytes(*minimum_fee),
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,254 @@
+//! Stable curve over token B valued at the virtual price of the base pool
+//! it is the pool token of, for metapools paired against another pool's
+//! liquidity
+use {
+    crate::{
+        curve::{
+            base::CurveType,
+            calculator::{
+                CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
+                TradingTokenResult, VIRTUAL_PRICE_PRECISION,
+            },
+            stable::StableCurve,
+        },
+        error::SwapError,
+    },
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    },
+    spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber},
+};
+
+/// MetaStableCurve struct implementing CurveCalculator
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaStableCurve {
+    /// Amplifier constant
+    pub amp: u64,
+    /// Base units of token A one base unit of token B, the base pool token,
+    /// is worth, scaled by VIRTUAL_PRICE_PRECISION: the virtual price of the
+    /// base pool, refreshed from the base pool's accounts
+    pub base_virtual_price: u128,
+}
+
+impl MetaStableCurve {
+    fn stable(&self) -> StableCurve {
+        StableCurve {
+            amp: self.amp,
+            ..StableCurve::default()
+        }
+    }
+
+    /// Token B amount valued in token A at the virtual price
+    fn scale(&self, token_b_amount: u128, round_direction: RoundDirection) -> Option<u128> {
+        let value = token_b_amount.checked_mul(self.base_virtual_price)?;
+        match round_direction {
+            RoundDirection::Floor => value.checked_div(VIRTUAL_PRICE_PRECISION),
+            RoundDirection::Ceiling => Some(value.checked_ceil_div(VIRTUAL_PRICE_PRECISION)?.0),
+        }
+    }
+
+    /// Token B amount worth the given token A value at the virtual price
+    fn unscale(&self, value: u128) -> Option<u128> {
+        value
+            .checked_mul(VIRTUAL_PRICE_PRECISION)?
+            .checked_div(self.base_virtual_price)
+    }
+
+    /// Source amount of a single-sided deposit or withdrawal, and the
+    /// reserves, valued in token A
+    fn scale_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Option<(u128, u128, u128)> {
+        let source_amount = match trade_direction {
+            TradeDirection::AtoB => source_amount,
+            TradeDirection::BtoA => self.scale(source_amount, round_direction)?,
+        };
+        Some((
+            source_amount,
+            swap_token_a_amount,
+            self.scale(swap_token_b_amount, RoundDirection::Floor)?,
+        ))
+    }
+}
+
+impl CurveCalculator for MetaStableCurve {
+    /// Stable curve over the token B reserve valued at the virtual price
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        let destination_amount_swapped = match trade_direction {
+            TradeDirection::AtoB => self.unscale(
+                self.stable()
+                    .swap_without_fees(
+                        source_amount,
+                        swap_source_amount,
+                        self.scale(swap_destination_amount, RoundDirection::Floor)?,
+                        trade_direction,
+                    )?
+                    .destination_amount_swapped,
+            )?,
+            TradeDirection::BtoA => {
+                self.stable()
+                    .swap_without_fees(
+                        self.scale(source_amount, RoundDirection::Floor)?,
+                        self.scale(swap_source_amount, RoundDirection::Floor)?,
+                        swap_destination_amount,
+                        trade_direction,
+                    )?
+                    .destination_amount_swapped
+            }
+        };
+        Some(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount,
+            destination_amount_swapped,
+        })
+    }
+
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        self.stable().pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        )
+    }
+
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .scale_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+                RoundDirection::Floor,
+            )?;
+        self.stable().deposit_single_token_type(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .scale_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+                RoundDirection::Ceiling,
+            )?;
+        self.stable().withdraw_single_token_type_exact_out(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )
+    }
+
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        self.stable().normalized_value(
+            swap_token_a_amount,
+            self.scale(swap_token_b_amount, RoundDirection::Floor)?,
+        )
+    }
+
+    /// D / pool supply, D being valued in token A
+    fn virtual_price(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+    ) -> Option<u128> {
+        self.stable().virtual_price(
+            swap_token_a_amount,
+            self.scale(swap_token_b_amount, RoundDirection::Floor)?,
+            pool_supply,
+        )
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        if self.base_virtual_price == 0 {
+            return Err(SwapError::InvalidCurve);
+        }
+        Ok(())
+    }
+    fn get_curve_type(&self) -> CurveType {
+        CurveType::MetaStable
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for MetaStableCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for MetaStableCurve {}
+impl Pack for MetaStableCurve {
+    const LEN: usize = 24;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<MetaStableCurve, ProgramError> {
+        if input.len() < Self::LEN {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        let input = array_ref![input, 0, 24];
+        let (amp, base_virtual_price) = array_refs![input, 8, 16];
+        Ok(Self {
+            amp: u64::from_le_bytes(*amp),
+            base_virtual_price: u128::from_le_bytes(*base_virtual_price),
+        })
+    }
+}
+
+impl DynPack for MetaStableCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 24];
+        let (amp, base_virtual_price) = mut_array_refs![output, 8, 16];
+        *amp = self.amp.to_le_bytes();
+        *base_virtual_price = self.base_virtual_price.to_le_bytes();
+    }
+}
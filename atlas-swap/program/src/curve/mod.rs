@@ -1,9 +1,16 @@
 //! Curve invariant implementations
 
+pub mod arbitrage;
 pub mod base;
+pub mod basket;
+pub mod batch;
 pub mod calculator;
 pub mod constant_price;
 pub mod constant_product;
+pub mod depth;
 pub mod fees;
+pub mod meta_stable;
 pub mod offset;
+pub mod oracle_pegged;
+pub mod rated_stable;
 pub mod stable;
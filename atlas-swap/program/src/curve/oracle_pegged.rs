@@ -0,0 +1,218 @@
+//! Curve quoting around an external oracle price instead of its inventory
+use {
+    crate::{
+        curve::{
+            base::CurveType,
+            calculator::{
+                map_zero_to_none, CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult,
+                TradeDirection, TradingTokenResult,
+            },
+            constant_product::pool_tokens_to_trading_tokens,
+        },
+        error::SwapError,
+        state::PRICE_PRECISION,
+    },
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    },
+    spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber, uint::U256},
+    std::convert::TryFrom,
+};
+
+/// Basis point denominator of the spread
+pub const SPREAD_DENOMINATOR: u64 = 10_000;
+
+/// Value of an amount of token A or B in token B at the given price
+fn value_in_token_b(amount: u128, price: u128, trade_direction: TradeDirection) -> Option<U256> {
+    match trade_direction {
+        TradeDirection::AtoB => U256::from(amount)
+            .checked_mul(U256::from(price))?
+            .checked_div(U256::from(PRICE_PRECISION)),
+        TradeDirection::BtoA => Some(U256::from(amount)),
+    }
+}
+
+/// Get the amount of pool tokens for the given amount of token A or B, the
+/// pool being valued in token B at the given price.
+pub fn trading_tokens_to_pool_tokens(
+    price: u128,
+    source_amount: u128,
+    swap_token_a_amount: u128,
+    swap_token_b_amount: u128,
+    pool_supply: u128,
+    trade_direction: TradeDirection,
+    round_direction: RoundDirection,
+) -> Option<u128> {
+    let given_value = value_in_token_b(source_amount, price, trade_direction)?;
+    let total_value = value_in_token_b(swap_token_a_amount, price, TradeDirection::AtoB)?
+        .checked_add(U256::from(swap_token_b_amount))?;
+    let pool_tokens = U256::from(pool_supply).checked_mul(given_value)?;
+    let pool_tokens = match round_direction {
+        RoundDirection::Floor => pool_tokens.checked_div(total_value)?,
+        RoundDirection::Ceiling => pool_tokens.checked_ceil_div(total_value)?.0,
+    };
+    u128::try_from(pool_tokens).ok()
+}
+
+/// OraclePeggedCurve struct implementing CurveCalculator
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OraclePeggedCurve {
+    /// Spread charged on each side of the oracle price, in basis points
+    pub spread_bps: u64,
+    /// Price of one base unit of token A in base units of token B, scaled by
+    /// PRICE_PRECISION. Set at init and replaced with the price of the
+    /// pool's price feed before every swap.
+    pub price: u128,
+}
+
+impl CurveCalculator for OraclePeggedCurve {
+    /// Token A is sold at the price less the spread and bought at the price
+    /// plus the spread, whatever the reserves
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        _swap_source_amount: u128,
+        _swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        let spread_bps = U256::from(self.spread_bps);
+        let denominator = U256::from(SPREAD_DENOMINATOR);
+        let destination_amount_swapped = match trade_direction {
+            TradeDirection::AtoB => U256::from(source_amount)
+                .checked_mul(U256::from(self.price))?
+                .checked_mul(denominator.checked_sub(spread_bps)?)?
+                .checked_div(U256::from(PRICE_PRECISION).checked_mul(denominator)?)?,
+            TradeDirection::BtoA => U256::from(source_amount)
+                .checked_mul(U256::from(PRICE_PRECISION))?
+                .checked_mul(denominator)?
+                .checked_div(U256::from(self.price).checked_mul(denominator.checked_add(spread_bps)?)?)?,
+        };
+        let source_amount_swapped = map_zero_to_none(source_amount)?;
+        let destination_amount_swapped =
+            map_zero_to_none(u128::try_from(destination_amount_swapped).ok()?)?;
+        Some(SwapWithoutFeesResult {
+            source_amount_swapped,
+            destination_amount_swapped,
+        })
+    }
+
+    /// Deposits and withdrawals of both tokens are proportional to the
+    /// reserves, as for the constant product curve
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        )
+    }
+
+    /// Single-sided deposits are valued at the price, without the spread
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        trading_tokens_to_pool_tokens(
+            self.price,
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+            RoundDirection::Floor,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        trading_tokens_to_pool_tokens(
+            self.price,
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+            RoundDirection::Ceiling,
+        )
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        if self.price == 0 || self.spread_bps >= SPREAD_DENOMINATOR {
+            Err(SwapError::InvalidCurve)
+        } else {
+            Ok(())
+        }
+    }
+    fn get_curve_type(&self) -> CurveType {
+        CurveType::OraclePegged
+    }
+
+    /// The total normalized value of the oracle-pegged curve is the value of
+    /// both reserves in token B at the price, halved to normalize it between
+    /// the two token types like the constant price curve.
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        let value = value_in_token_b(swap_token_a_amount, self.price, TradeDirection::AtoB)?
+            .checked_add(U256::from(swap_token_b_amount))?
+            .checked_div(U256::from(2))?;
+        PreciseNumber::new(u128::try_from(value).ok()?)
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for OraclePeggedCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for OraclePeggedCurve {}
+impl Pack for OraclePeggedCurve {
+    const LEN: usize = 24;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<OraclePeggedCurve, ProgramError> {
+        if input.len() < Self::LEN {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        let input = array_ref![input, 0, 24];
+        let (spread_bps, price) = array_refs![input, 8, 16];
+        Ok(Self {
+            spread_bps: u64::from_le_bytes(*spread_bps),
+            price: u128::from_le_bytes(*price),
+        })
+    }
+}
+
+impl DynPack for OraclePeggedCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 24];
+        let (spread_bps, price) = mut_array_refs![output, 8, 16];
+        *spread_bps = self.spread_bps.to_le_bytes();
+        *price = self.price.to_le_bytes();
+    }
+}
@@ -0,0 +1,251 @@
+//! Stable curve over token A valued at an exchange rate, for liquid staking
+//! tokens against their underlying
+use {
+    crate::{
+        curve::{
+            base::CurveType,
+            calculator::{
+                CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
+                TradingTokenResult,
+            },
+            stable::StableCurve,
+        },
+        error::SwapError,
+        state::PRICE_PRECISION,
+    },
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
+    solana_program::{
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack, Sealed},
+    },
+    spl_math::{checked_ceil_div::CheckedCeilDiv, precise_number::PreciseNumber},
+};
+
+/// RatedStableCurve struct implementing CurveCalculator
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RatedStableCurve {
+    /// Amplifier constant
+    pub amp: u64,
+    /// Base units of token B one base unit of token A is worth, scaled by
+    /// PRICE_PRECISION, refreshed from the pool's stake pool account
+    pub rate: u128,
+}
+
+impl RatedStableCurve {
+    fn stable(&self) -> StableCurve {
+        StableCurve {
+            amp: self.amp,
+            ..StableCurve::default()
+        }
+    }
+
+    /// Token A amount valued in token B at the rate
+    fn scale(&self, token_a_amount: u128, round_direction: RoundDirection) -> Option<u128> {
+        let value = token_a_amount.checked_mul(self.rate)?;
+        match round_direction {
+            RoundDirection::Floor => value.checked_div(PRICE_PRECISION),
+            RoundDirection::Ceiling => Some(value.checked_ceil_div(PRICE_PRECISION)?.0),
+        }
+    }
+
+    /// Token A amount worth the given token B value at the rate
+    fn unscale(&self, value: u128) -> Option<u128> {
+        value.checked_mul(PRICE_PRECISION)?.checked_div(self.rate)
+    }
+
+    /// Source amount of a single-sided deposit or withdrawal, and the
+    /// reserves, valued in token B
+    fn scale_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+        round_direction: RoundDirection,
+    ) -> Option<(u128, u128, u128)> {
+        let source_amount = match trade_direction {
+            TradeDirection::AtoB => self.scale(source_amount, round_direction)?,
+            TradeDirection::BtoA => source_amount,
+        };
+        Some((
+            source_amount,
+            self.scale(swap_token_a_amount, RoundDirection::Floor)?,
+            swap_token_b_amount,
+        ))
+    }
+}
+
+impl CurveCalculator for RatedStableCurve {
+    /// Stable curve over the token A reserve valued at the rate
+    fn swap_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<SwapWithoutFeesResult> {
+        let destination_amount_swapped = match trade_direction {
+            TradeDirection::AtoB => {
+                self.stable()
+                    .swap_without_fees(
+                        self.scale(source_amount, RoundDirection::Floor)?,
+                        self.scale(swap_source_amount, RoundDirection::Floor)?,
+                        swap_destination_amount,
+                        trade_direction,
+                    )?
+                    .destination_amount_swapped
+            }
+            TradeDirection::BtoA => self.unscale(
+                self.stable()
+                    .swap_without_fees(
+                        source_amount,
+                        swap_source_amount,
+                        self.scale(swap_destination_amount, RoundDirection::Floor)?,
+                        trade_direction,
+                    )?
+                    .destination_amount_swapped,
+            )?,
+        };
+        Some(SwapWithoutFeesResult {
+            source_amount_swapped: source_amount,
+            destination_amount_swapped,
+        })
+    }
+
+    fn pool_tokens_to_trading_tokens(
+        &self,
+        pool_tokens: u128,
+        pool_token_supply: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        self.stable().pool_tokens_to_trading_tokens(
+            pool_tokens,
+            pool_token_supply,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            round_direction,
+        )
+    }
+
+    fn deposit_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .scale_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+                RoundDirection::Floor,
+            )?;
+        self.stable().deposit_single_token_type(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )
+    }
+
+    fn withdraw_single_token_type_exact_out(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .scale_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+                RoundDirection::Ceiling,
+            )?;
+        self.stable().withdraw_single_token_type_exact_out(
+            source_amount,
+            swap_token_a_amount,
+            swap_token_b_amount,
+            pool_supply,
+            trade_direction,
+        )
+    }
+
+    fn normalized_value(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+    ) -> Option<PreciseNumber> {
+        self.stable().normalized_value(
+            self.scale(swap_token_a_amount, RoundDirection::Floor)?,
+            swap_token_b_amount,
+        )
+    }
+
+    /// D / pool supply, D being valued in token B
+    fn virtual_price(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+    ) -> Option<u128> {
+        self.stable().virtual_price(
+            self.scale(swap_token_a_amount, RoundDirection::Floor)?,
+            swap_token_b_amount,
+            pool_supply,
+        )
+    }
+
+    fn validate(&self) -> Result<(), SwapError> {
+        if self.rate == 0 {
+            return Err(SwapError::InvalidCurve);
+        }
+        Ok(())
+    }
+    fn get_curve_type(&self) -> CurveType {
+        CurveType::RatedStable
+    }
+}
+
+/// IsInitialized is required to use `Pack::pack` and `Pack::unpack`
+impl IsInitialized for RatedStableCurve {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+}
+impl Sealed for RatedStableCurve {}
+impl Pack for RatedStableCurve {
+    const LEN: usize = 24;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        (self as &dyn DynPack).pack_into_slice(output);
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<RatedStableCurve, ProgramError> {
+        if input.len() < Self::LEN {
+            return Err(SwapError::InvalidInstruction.into());
+        }
+        let input = array_ref![input, 0, 24];
+        let (amp, rate) = array_refs![input, 8, 16];
+        Ok(Self {
+            amp: u64::from_le_bytes(*amp),
+            rate: u128::from_le_bytes(*rate),
+        })
+    }
+}
+
+impl DynPack for RatedStableCurve {
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, 24];
+        let (amp, rate) = mut_array_refs![output, 8, 16];
+        *amp = self.amp.to_le_bytes();
+        *rate = self.rate.to_le_bytes();
+    }
+}
@@ -4,11 +4,11 @@ use {
         curve::base::CurveType,
         curve::calculator::{
             CurveCalculator, DynPack, RoundDirection, SwapWithoutFeesResult, TradeDirection,
-            TradingTokenResult,
+            TradingTokenResult, VIRTUAL_PRICE_PRECISION,
         },
         error::SwapError,
     },
-    arrayref::{array_mut_ref, array_ref},
+    arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs},
     solana_program::{
         program_error::ProgramError,
         program_pack::{IsInitialized, Pack, Sealed},
@@ -18,25 +18,35 @@ use {
 };
 
 const N_COINS: u8 = 2;
-const N_COINS_SQUARED: u8 = 4;
-const ITERATIONS: u8 = 32;
-
-/// Returns self to the power of b
-fn checked_u8_power(a: &U256, b: u8) -> Option<U256> {
-    let mut result = *a;
-    for _ in 1..b {
-        result = result.checked_mul(*a)?;
-    }
-    Some(result)
-}
+const N_COINS_U256: U256 = U256([2, 0, 0, 0]);
+const N_COINS_PLUS_ONE_U256: U256 = U256([3, 0, 0, 0]);
+const N_COINS_SQUARED_U256: U256 = U256([4, 0, 0, 0]);
+
+/// Most Newton iterations the invariant and swap amount solvers run, each
+/// costing a fixed handful of U256 multiplications and divisions, so a
+/// stable swap never costs more than twice this many iterations
+pub const MAX_NEWTON_ITERATIONS: u8 = 32;
 
-/// Returns self multiplied by b
-fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
-    let mut result = *a;
-    for _ in 1..b {
-        result = result.checked_add(*a)?;
+/// Newton iterates the solvers stop at, given the latest three: `Some` once
+/// they reach a fixed point or alternate between two values, in which case
+/// the result is the one the remaining iterations would have ended on
+fn newton_result(
+    current: U256,
+    previous: U256,
+    before_previous: Option<U256>,
+    iteration: u8,
+) -> Option<U256> {
+    if current == previous {
+        Some(current)
+    } else if before_previous == Some(current) {
+        if (MAX_NEWTON_ITERATIONS - iteration) % 2 == 1 {
+            Some(current)
+        } else {
+            Some(previous)
+        }
+    } else {
+        None
     }
-    Some(result)
 }
 
 /// StableCurve struct implementing CurveCalculator
@@ -44,17 +54,63 @@ fn checked_u8_mul(a: &U256, b: u8) -> Option<U256> {
 pub struct StableCurve {
     /// Amplifier constant
     pub amp: u64,
+    /// Decimals of token A, set from its mint at init
+    pub token_a_decimals: u8,
+    /// Decimals of token B, set from its mint at init
+    pub token_b_decimals: u8,
+}
+
+impl StableCurve {
+    /// Token A and token B amounts scaled to the decimals of the token with
+    /// more of them, the invariant assuming both tokens are worth the same
+    /// per base unit
+    fn normalize(&self, token_a_amount: u128, token_b_amount: u128) -> Option<(u128, u128)> {
+        let (token_a_multiplier, token_b_multiplier) = self.precision_multipliers()?;
+        Some((
+            token_a_amount.checked_mul(token_a_multiplier)?,
+            token_b_amount.checked_mul(token_b_multiplier)?,
+        ))
+    }
+
+    /// Source amount of a single-sided deposit or withdrawal, and the
+    /// reserves, scaled as by `normalize`
+    fn normalize_single_token_type(
+        &self,
+        source_amount: u128,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<(u128, u128, u128)> {
+        let (token_a_multiplier, token_b_multiplier) = self.precision_multipliers()?;
+        let source_multiplier = match trade_direction {
+            TradeDirection::AtoB => token_a_multiplier,
+            TradeDirection::BtoA => token_b_multiplier,
+        };
+        Some((
+            source_amount.checked_mul(source_multiplier)?,
+            swap_token_a_amount.checked_mul(token_a_multiplier)?,
+            swap_token_b_amount.checked_mul(token_b_multiplier)?,
+        ))
+    }
+
+    fn precision_multipliers(&self) -> Option<(u128, u128)> {
+        let decimals = std::cmp::max(self.token_a_decimals, self.token_b_decimals);
+        Some((
+            10u128.checked_pow(u32::from(decimals.checked_sub(self.token_a_decimals)?))?,
+            10u128.checked_pow(u32::from(decimals.checked_sub(self.token_b_decimals)?))?,
+        ))
+    }
 }
 
 /// d = (leverage * sum_x + d_product * n_coins) * initial_d / ((leverage - 1) * initial_d + (n_coins + 1) * d_product)
 fn calculate_step(initial_d: &U256, leverage: u64, sum_x: u128, d_product: &U256) -> Option<U256> {
     let leverage_mul = U256::from(leverage).checked_mul(sum_x.into())?;
-    let d_p_mul = checked_u8_mul(d_product, N_COINS)?;
+    let d_p_mul = d_product.checked_mul(N_COINS_U256)?;
 
     let l_val = leverage_mul.checked_add(d_p_mul)?.checked_mul(*initial_d)?;
 
     let leverage_sub = initial_d.checked_mul((leverage.checked_sub(1)?).into())?;
-    let n_coins_sum = checked_u8_mul(d_product, N_COINS.checked_add(1)?)?;
+    let n_coins_sum = d_product.checked_mul(N_COINS_PLUS_ONE_U256)?;
 
     let r_val = leverage_sub.checked_add(n_coins_sum)?;
 
@@ -65,19 +121,22 @@ fn calculate_step(initial_d: &U256, leverage: u64, sum_x: u128, d_product: &U256
 /// Equation:
 /// A * sum(x_i) * n**n + D = A * D * n**n + D**(n+1) / (n**n * prod(x_i))
 fn compute_d(leverage: u64, amount_a: u128, amount_b: u128) -> Option<u128> {
-    let amount_a_times_coins =
-        checked_u8_mul(&U256::from(amount_a), N_COINS)?.checked_add(U256::one())?;
-    let amount_b_times_coins =
-        checked_u8_mul(&U256::from(amount_b), N_COINS)?.checked_add(U256::one())?;
+    let amount_a_times_coins = U256::from(amount_a)
+        .checked_mul(N_COINS_U256)?
+        .checked_add(U256::one())?;
+    let amount_b_times_coins = U256::from(amount_b)
+        .checked_mul(N_COINS_U256)?
+        .checked_add(U256::one())?;
     let sum_x = amount_a.checked_add(amount_b)?; // sum(x_i), a.k.a S
     if sum_x == 0 {
         Some(0)
     } else {
+        let mut d_before_previous = None;
         let mut d_previous: U256;
         let mut d: U256 = sum_x.into();
 
         // Newton's method to approximate D
-        for _ in 0..ITERATIONS {
+        for iteration in 0..MAX_NEWTON_ITERATIONS {
             let mut d_product = d;
             d_product = d_product
                 .checked_mul(d)?
@@ -88,10 +147,10 @@ fn compute_d(leverage: u64, amount_a: u128, amount_b: u128) -> Option<u128> {
             d_previous = d;
             //d = (leverage * sum_x + d_p * n_coins) * d / ((leverage - 1) * d + (n_coins + 1) * d_p);
             d = calculate_step(&d, leverage, sum_x, &d_product)?;
-            // Equality with the precision of 1
-            if d == d_previous {
-                break;
+            if let Some(d) = newton_result(d, d_previous, d_before_previous, iteration) {
+                return u128::try_from(d).ok();
             }
+            d_before_previous = Some(d_previous);
         }
         u128::try_from(d).ok()
     }
@@ -113,22 +172,30 @@ fn compute_new_destination_amount(
 
     // sum' = prod' = x
     // c =  D ** (n + 1) / (n ** (2 * n) * prod' * A)
-    let c = checked_u8_power(&d_val, N_COINS.checked_add(1)?)?
-        .checked_div(checked_u8_mul(&new_source_amount, N_COINS_SQUARED)?.checked_mul(leverage)?)?;
+    let c = d_val
+        .checked_mul(d_val)?
+        .checked_mul(d_val)?
+        .checked_div(
+            new_source_amount
+                .checked_mul(N_COINS_SQUARED_U256)?
+                .checked_mul(leverage)?,
+        )?;
 
     // b = sum' - (A*n**n - 1) * D / (A * n**n)
     let b = new_source_amount.checked_add(d_val.checked_div(leverage)?)?;
 
     // Solve for y by approximating: y**2 + b*y = c
+    let mut y_before_prev = None;
     let mut y_prev: U256;
     let mut y = d_val;
-    for _ in 0..ITERATIONS {
+    for iteration in 0..MAX_NEWTON_ITERATIONS {
         y_prev = y;
-        y = (checked_u8_power(&y, 2)?.checked_add(c)?)
-            .checked_div(checked_u8_mul(&y, 2)?.checked_add(b)?.checked_sub(d_val)?)?;
-        if y == y_prev {
-            break;
+        y = (y.checked_mul(y)?.checked_add(c)?)
+            .checked_div(y.checked_add(y)?.checked_add(b)?.checked_sub(d_val)?)?;
+        if let Some(y) = newton_result(y, y_prev, y_before_prev, iteration) {
+            return u128::try_from(y).ok();
         }
+        y_before_prev = Some(y_prev);
     }
     u128::try_from(y).ok()
 }
@@ -140,18 +207,32 @@ impl CurveCalculator for StableCurve {
         source_amount: u128,
         swap_source_amount: u128,
         swap_destination_amount: u128,
-        _trade_direction: TradeDirection,
+        trade_direction: TradeDirection,
     ) -> Option<SwapWithoutFeesResult> {
         let leverage = self.amp.checked_mul(N_COINS as u64)?;
+        let (token_a_multiplier, token_b_multiplier) = self.precision_multipliers()?;
+        let (source_multiplier, destination_multiplier) = match trade_direction {
+            TradeDirection::AtoB => (token_a_multiplier, token_b_multiplier),
+            TradeDirection::BtoA => (token_b_multiplier, token_a_multiplier),
+        };
+        let swap_source_amount = swap_source_amount.checked_mul(source_multiplier)?;
+        let swap_destination_amount = swap_destination_amount.checked_mul(destination_multiplier)?;
 
-        let new_source_amount = swap_source_amount.checked_add(source_amount)?;
+        let new_source_amount =
+            swap_source_amount.checked_add(source_amount.checked_mul(source_multiplier)?)?;
         let new_destination_amount = compute_new_destination_amount(
             leverage,
             new_source_amount,
             compute_d(leverage, swap_source_amount, swap_destination_amount)?,
         )?;
 
-        let amount_swapped = swap_destination_amount.checked_sub(new_destination_amount)?;
+        // the new destination amount is rounded down, so a unit is kept back
+        // for the amount swapped to round in favor of the pool, as
+        // `get_dy` does
+        let amount_swapped = swap_destination_amount
+            .checked_sub(new_destination_amount)?
+            .saturating_sub(1)
+            .checked_div(destination_multiplier)?;
 
         Some(SwapWithoutFeesResult {
             source_amount_swapped: source_amount,
@@ -216,6 +297,13 @@ impl CurveCalculator for StableCurve {
         if source_amount == 0 {
             return Some(0);
         }
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .normalize_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+            )?;
         let leverage = self.amp.checked_mul(N_COINS as u64)?;
         let d0 = PreciseNumber::new(compute_d(
             leverage,
@@ -249,6 +337,13 @@ impl CurveCalculator for StableCurve {
         if source_amount == 0 {
             return Some(0);
         }
+        let (source_amount, swap_token_a_amount, swap_token_b_amount) = self
+            .normalize_single_token_type(
+                source_amount,
+                swap_token_a_amount,
+                swap_token_b_amount,
+                trade_direction,
+            )?;
         let leverage = self.amp.checked_mul(N_COINS as u64)?;
         let d0 = PreciseNumber::new(compute_d(
             leverage,
@@ -276,6 +371,8 @@ impl CurveCalculator for StableCurve {
         swap_token_a_amount: u128,
         swap_token_b_amount: u128,
     ) -> Option<PreciseNumber> {
+        let (swap_token_a_amount, swap_token_b_amount) =
+            self.normalize(swap_token_a_amount, swap_token_b_amount)?;
         #[cfg(not(any(test, feature = "fuzz")))]
         {
             let leverage = self.amp.checked_mul(N_COINS as u64)?;
@@ -310,6 +407,22 @@ impl CurveCalculator for StableCurve {
         }
     }
 
+    /// D / pool supply, D being at the precision of the token with more
+    /// decimals
+    fn virtual_price(
+        &self,
+        swap_token_a_amount: u128,
+        swap_token_b_amount: u128,
+        pool_supply: u128,
+    ) -> Option<u128> {
+        let (swap_token_a_amount, swap_token_b_amount) =
+            self.normalize(swap_token_a_amount, swap_token_b_amount)?;
+        let leverage = self.amp.checked_mul(N_COINS as u64)?;
+        compute_d(leverage, swap_token_a_amount, swap_token_b_amount)?
+            .checked_mul(VIRTUAL_PRICE_PRECISION)?
+            .checked_div(pool_supply)
+    }
+
     fn validate(&self) -> Result<(), SwapError> {
         // TODO are all amps valid?
         Ok(())
@@ -327,7 +440,7 @@ impl IsInitialized for StableCurve {
 }
 impl Sealed for StableCurve {}
 impl Pack for StableCurve {
-    const LEN: usize = 8;
+    const LEN: usize = 10;
     fn pack_into_slice(&self, output: &mut [u8]) {
         (self as &dyn DynPack).pack_into_slice(output);
     }
@@ -337,16 +450,22 @@ impl Pack for StableCurve {
             return Err(SwapError::InvalidInstruction.into());    
         }
 
-        let amp = array_ref![input, 0, 8];
+        let input = array_ref![input, 0, 10];
+        let (amp, token_a_decimals, token_b_decimals) = array_refs![input, 8, 1, 1];
         Ok(Self {
             amp: u64::from_le_bytes(*amp),
+            token_a_decimals: token_a_decimals[0],
+            token_b_decimals: token_b_decimals[0],
         })
     }
 }
 
 impl DynPack for StableCurve {
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let amp = array_mut_ref![output, 0, 8];
+        let output = array_mut_ref![output, 0, 10];
+        let (amp, token_a_decimals, token_b_decimals) = mut_array_refs![output, 8, 1, 1];
         *amp = self.amp.to_le_bytes();
+        token_a_decimals[0] = self.token_a_decimals;
+        token_b_decimals[0] = self.token_b_decimals;
     }
 }
\ No newline at end of file
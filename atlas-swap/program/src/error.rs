@@ -1,9 +1,17 @@
 //! Error types
 
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use solana_program::{decode_error::DecodeError, program_error::ProgramError};
 use thiserror::Error;
 
+/// Code of the first variant of SwapError, the start of the range Anchor
+/// gives programs for their own errors. Each variant is returned as this
+/// plus its position in the enum, the code an Anchor IDL listing the
+/// variants in order assigns it, so Anchor clients and explorers decode it
+/// by name. Variants are only ever appended, keeping codes stable.
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
 /// Errors that may be returned by the TokenSwap program.
 #[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
 pub enum SwapError {
@@ -103,8 +111,8 @@ pub enum SwapError {
     #[error("The operation cannot be performed on the given curve")]
     UnsupportedCurveOperation,
 
-    /// The decimal validation error.(Decimal must be 8)
-    #[error("The decimal validation error.(Decimal must be 8)")]
+    /// The pool mint has more than MAX_LP_DECIMALS decimals.
+    #[error("The decimal validation error.(Decimal must be at most 9)")]
     MismatchDecimalValidation,
 
     /// invalid program derived address
@@ -138,13 +146,335 @@ pub enum SwapError {
     /// Lamport balance below rent-exempt threshold.
     #[error("Lamport balance below rent-exempt threshold")]
     NotRentExempt,
+
+    /// The locked pool tokens cannot be released before the unlock timestamp.
+    #[error("Liquidity is still locked")]
+    LiquidityLocked,
+
+    /// The unlock timestamp is in the past or earlier than the current lock.
+    #[error("Invalid unlock timestamp")]
+    InvalidUnlockTimestamp,
+
+    /// The position does not hold enough staked pool tokens.
+    #[error("Insufficient staked pool tokens")]
+    InsufficientStake,
+
+    /// The holder account does not qualify for the fee discount.
+    #[error("Invalid fee discount holder account")]
+    InvalidFeeDiscountAccount,
+
+    /// The fixed fee account does not hold the source token of the trade.
+    #[error("Fee account mint does not match the source token")]
+    IncorrectFeeAccountMint,
+
+    /// The swap account predates tracked reserves.
+    #[error("Swap version does not track reserves")]
+    ReservesNotTracked,
+
+    /// The pool is not far enough off-peg, or the trade would overshoot it.
+    #[error("Rebalance does not restore the pool balance")]
+    InvalidRebalance,
+
+    /// The rebalance exceeds the amount allowed in the current epoch.
+    #[error("Rebalance limit for the epoch exceeded")]
+    RebalanceLimitExceeded,
+
+    /// The execution price deviates too far from the spot price.
+    #[error("Swap exceeds the maximum price impact")]
+    PriceImpactExceeded,
+
+    /// The price oracle account is invalid, stale or not trading.
+    #[error("Invalid or unavailable price oracle")]
+    InvalidOracle,
+
+    /// The execution price deviates too far from the oracle price.
+    #[error("Swap price deviates from the oracle price")]
+    OraclePriceDeviation,
+
+    /// The split swap pools or proportions are invalid.
+    #[error("Invalid split swap route")]
+    InvalidSplitRoute,
+
+    /// The swap quote or its signature is invalid.
+    #[error("Invalid swap quote")]
+    InvalidQuote,
+
+    /// The swap quote has expired.
+    #[error("Swap quote expired")]
+    QuoteExpired,
+
+    /// The pool price has not reached the limit price of the order.
+    #[error("Limit price not reached")]
+    LimitPriceNotReached,
+
+    /// The interval since the last recurring order tick has not elapsed.
+    #[error("Recurring order tick too early")]
+    DcaTickTooEarly,
+
+    /// Address of the provided token A vault is incorrect.
+    #[error("Address of the provided token A vault is incorrect")]
+    IncorrectTokenAVault,
+
+    /// Address of the provided token B vault is incorrect.
+    #[error("Address of the provided token B vault is incorrect")]
+    IncorrectTokenBVault,
+
+    /// The source token account is one of the pool vaults.
+    #[error("Source token account is one of the pool vaults")]
+    SourceEqualsVault,
+
+    /// The destination token account is one of the pool vaults.
+    #[error("Destination token account is one of the pool vaults")]
+    DestinationEqualsVault,
+
+    /// The amp change exceeds the allowed factor.
+    #[error("Amp change too large")]
+    AmpChangeTooLarge,
+
+    /// The pool is deprecated, only withdrawals are allowed.
+    #[error("Pool is deprecated")]
+    PoolDeprecated,
+
+    /// The deposit would take the pool token supply above the pool's cap.
+    #[error("Deposit cap exceeded")]
+    DepositCapExceeded,
+
+    /// The wallet holds neither the gate token nor an allowlist entry of the pool.
+    #[error("Wallet does not pass the pool gate")]
+    GateCheckFailed,
+
+    /// The pool tokens were deposited less than the pool's minimum holding period ago.
+    #[error("Minimum holding period not elapsed")]
+    HoldingPeriodNotElapsed,
+
+    /// The buyback would burn more than the epoch limit allows.
+    #[error("Buyback epoch limit exceeded")]
+    BuybackLimitExceeded,
+
+    /// The revealed swap does not match the commitment.
+    #[error("Swap does not match the commitment")]
+    InvalidCommitment,
+
+    /// The swap is revealed in the slot it was committed in.
+    #[error("Swap revealed before the slot after its commitment")]
+    RevealTooEarly,
+
+    /// The pool only trades through its batch auction.
+    #[error("Pool settles swaps in batch auctions")]
+    BatchAuctionEnabled,
+
+    /// The pool does not trade in batch auctions.
+    #[error("Pool does not settle swaps in batch auctions")]
+    BatchAuctionDisabled,
+
+    /// The window of the pending batch is over, it has to be settled first.
+    #[error("Batch auction window closed")]
+    BatchWindowClosed,
+
+    /// The batch is settled before the end of its window.
+    #[error("Batch auction window still open")]
+    BatchWindowOpen,
+
+    /// The batch holds the maximum number of swaps.
+    #[error("Batch auction is full")]
+    BatchAuctionFull,
+
+    /// The rate account is not the pool's, or not an up-to-date stake pool.
+    #[error("Invalid rate account")]
+    InvalidRateAccount,
+
+    /// The deposit is not priced like the vault balances.
+    #[error("Deposit ratio deviates from the vault balances")]
+    DepositRatioMismatch,
+
+    /// The vaults or the curve value after a swap do not match its quote.
+    #[error("Swap broke the curve invariant")]
+    InvariantViolated,
+
+    /// The pool compounds its trade fees instead of paying them out.
+    #[error("Pool does not pay out claimable fees")]
+    ClaimableFeesDisabled,
+
+    /// The LP position does not hold enough pool tokens.
+    #[error("Insufficient pool tokens in the LP position")]
+    InsufficientPositionTokens,
+
+    /// The position NFT mint is not a fresh mint of the swap authority.
+    #[error("Invalid position NFT mint")]
+    InvalidPositionMint,
+
+    /// Swaps and deposits are paused in the program or the pool.
+    #[error("Swaps and deposits are paused")]
+    Paused,
+
+    /// The swap would take the volume of the slot above the pool's cap.
+    #[error("Slot swap volume cap exceeded")]
+    SlotVolumeExceeded,
+
+    /// The price of the pool moved too far, swaps are halted until the
+    /// circuit breaker is reset.
+    #[error("Circuit breaker tripped")]
+    CircuitBreakerTripped,
+
+    /// Basket pools hold from MIN_BASKET_ASSETS to MAX_BASKET_ASSETS assets.
+    #[error("Invalid basket asset count")]
+    InvalidBasketAssetCount,
+
+    /// Rent of a closed account goes back to its payer, unless the payer
+    /// closes it.
+    #[error("Invalid rent recipient")]
+    InvalidRentRecipient,
+
+    /// The LP position still holds pool tokens or is owed fees.
+    #[error("LP position not empty")]
+    PositionNotEmpty,
+
+    /// Pools trade public balances only, their token accounts may not be
+    /// configured for confidential transfers.
+    #[error("Token account configured for confidential transfers")]
+    ConfidentialTransferAccount,
+
+    /// The mint has a Token-2022 extension pools cannot honour.
+    #[error("Unsupported mint extension")]
+    UnsupportedMintExtension,
+
+    /// The extensions of a Token-2022 account could not be read.
+    #[error("Invalid account extensions")]
+    InvalidAccountExtensions,
+
+    /// The fees are set by the fee governance, through an executed proposal.
+    #[error("Fees are governed")]
+    FeesGoverned,
+
+    /// The fee governance account or its governance does not match the
+    /// configured realm.
+    #[error("Invalid fee governance")]
+    InvalidFeeGovernance,
+
+    /// The ETA of a scheduled action is closer than MIN_PENDING_ACTION_DELAY.
+    #[error("Pending action delay too short")]
+    PendingActionDelayTooShort,
+
+    /// The scheduler already holds MAX_PENDING_ACTIONS actions.
+    #[error("Too many pending actions")]
+    TooManyPendingActions,
+
+    /// No pending action has the given id.
+    #[error("Pending action not found")]
+    PendingActionNotFound,
+
+    /// The ETA of the pending action has not been reached.
+    #[error("Pending action not ready")]
+    PendingActionNotReady,
+
+    /// The initial supply and lp decimals cannot change once pools exist.
+    #[error("Pool parameters are fixed once pools exist")]
+    PoolParametersFixed,
+
+    /// The fee owner of the program state is the default pubkey.
+    #[error("Invalid fee owner")]
+    InvalidFeeOwner,
+
+    /// The initial supply of the program state is not above MIN_LP_SUPPLY.
+    #[error("Initial supply too low")]
+    InitialSupplyTooLow,
+
+    /// The lp decimals of the program state are above MAX_LP_DECIMALS.
+    #[error("Invalid lp decimals")]
+    InvalidLpDecimals,
+
+    /// The trade direction of a swap does not match its pool accounts.
+    #[error("Trade direction does not match the swap accounts")]
+    TradeDirectionMismatch,
+
+    /// The trade would move the price of the pool past its circuit breaker.
+    #[error("Trade moves the price past the circuit breaker")]
+    CircuitBreakerPriceMove,
+
+    /// The pool count of the global state is only seeded while it is zero.
+    #[error("Pool count already set")]
+    PoolCountSet,
+
+    /// A deposit into a pool with a holding period mints to another owner.
+    #[error("Pool tokens under a holding period must go to the depositor")]
+    HoldingPeriodOwnerMismatch,
+
+    /// The owner has no tokens of a settled batch swap to claim.
+    #[error("No batch swap tokens to claim")]
+    NoBatchClaim,
+
+    /// The pool state is already of the latest version.
+    #[error("Pool state is already migrated")]
+    PoolStateMigrated,
+
+    /// The quote pays out more than MAX_QUOTE_DEVIATION_BPS above the
+    /// output of the pool curve.
+    #[error("Quote exceeds the curve price")]
+    QuoteAboveCurve,
+
+    /// The owner, fees and amp change only through a pending action of the
+    /// scheduler.
+    #[error("Change requires a pending action")]
+    PendingActionRequired,
+}
+/// Code, name and message of an error, as an IDL lists them
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ErrorDescription {
+    /// Custom program error code
+    pub code: u32,
+    /// Name of the SwapError variant
+    pub name: String,
+    /// Human-readable message, as the program logs it
+    pub message: String,
+}
+
+impl SwapError {
+    /// Code, name and message of the error
+    pub fn description(&self) -> ErrorDescription {
+        ErrorDescription {
+            code: self.code(),
+            name: format!("{:?}", self),
+            message: self.to_string(),
+        }
+    }
+
+    /// Custom program error code of the error
+    pub fn code(&self) -> u32 {
+        ERROR_CODE_OFFSET + self.clone() as u32
+    }
+
+    /// Error of a custom program error code, None for codes outside the
+    /// range of the program
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::from_u32(code.checked_sub(ERROR_CODE_OFFSET)?)
+    }
+}
+/// Descriptions of every error of the program, in code order
+pub fn error_catalog() -> Vec<ErrorDescription> {
+    (ERROR_CODE_OFFSET..)
+        .map_while(SwapError::from_code)
+        .map(|error| error.description())
+        .collect()
+}
+
+impl From<SwapError> for u32 {
+    fn from(e: SwapError) -> Self {
+        e.code()
+    }
 }
 impl From<SwapError> for ProgramError {
     fn from(e: SwapError) -> Self {
-        ProgramError::Custom(e as u32)
+        ProgramError::Custom(e.code())
     }
 }
 impl<T> DecodeError<T> for SwapError {
+    fn decode_custom_error_to_enum(custom: u32) -> Option<T>
+    where
+        T: FromPrimitive,
+    {
+        T::from_u32(custom.checked_sub(ERROR_CODE_OFFSET)?)
+    }
+
     fn type_of() -> &'static str {
         "Swap Error"
     }
@@ -0,0 +1,127 @@
+//! Program events, logged with `sol_log_data` so indexers can follow pool
+//! activity without replaying instructions
+
+use crate::curve::calculator::TradeDirection;
+use arrayref::{array_ref, array_refs};
+#[cfg(feature = "processor")]
+use solana_program::log::sol_log_data;
+use solana_program::pubkey::Pubkey;
+
+/// Event emitted by the program
+pub trait Event {
+    /// Discriminator logged ahead of the event data
+    const KIND: u8;
+
+    /// Serialize the event data
+    fn data(&self) -> Vec<u8>;
+
+    /// Log the event
+    #[cfg(feature = "processor")]
+    fn emit(&self) {
+        sol_log_data(&[&[Self::KIND], &self.data()]);
+    }
+}
+
+/// Zero-fee rebalance of a stable pool by the fee owner
+#[derive(Clone, Debug, PartialEq)]
+pub struct RebalanceEvent {
+    /// Token-swap that was rebalanced
+    pub swap: Pubkey,
+    /// Vault the tokens were sent to
+    pub swap_source: Pubkey,
+    /// Source tokens sent into the pool
+    pub amount_in: u64,
+    /// Destination tokens taken out of the pool
+    pub amount_out: u64,
+    /// Epoch of the rebalance
+    pub epoch: u64,
+    /// Source tokens rebalanced in the epoch, including this one
+    pub epoch_amount: u64,
+}
+
+impl Event for RebalanceEvent {
+    const KIND: u8 = 0;
+
+    fn data(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(96);
+        buf.extend_from_slice(self.swap.as_ref());
+        buf.extend_from_slice(self.swap_source.as_ref());
+        buf.extend_from_slice(&self.amount_in.to_le_bytes());
+        buf.extend_from_slice(&self.amount_out.to_le_bytes());
+        buf.extend_from_slice(&self.epoch.to_le_bytes());
+        buf.extend_from_slice(&self.epoch_amount.to_le_bytes());
+        buf
+    }
+}
+
+/// Swap through a pool, its fees charged in the source token
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapEvent {
+    /// Token-swap traded against
+    pub swap: Pubkey,
+    /// Owner of the source token account
+    pub owner: Pubkey,
+    /// Direction of the trade
+    pub trade_direction: TradeDirection,
+    /// Source tokens the pool received, fees included
+    pub amount_in: u64,
+    /// Destination tokens sent to the user
+    pub amount_out: u64,
+    /// Trade fee left to the liquidity providers
+    pub trade_fee: u64,
+    /// Owner fee, shared by the fee owner, the fee recipients and the
+    /// protocol
+    pub owner_fee: u64,
+    /// Slot of the swap
+    pub slot: u64,
+}
+
+impl SwapEvent {
+    /// Size of the event data
+    pub const LEN: usize = 105;
+
+    /// Deserialize the event data
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+        let data = array_ref![data, 0, SwapEvent::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (swap, owner, trade_direction, amount_in, amount_out, trade_fee, owner_fee, slot) =
+            array_refs![data, 32, 32, 1, 8, 8, 8, 8, 8];
+        Some(Self {
+            swap: Pubkey::new_from_array(*swap),
+            owner: Pubkey::new_from_array(*owner),
+            trade_direction: match trade_direction {
+                [0] => TradeDirection::AtoB,
+                [1] => TradeDirection::BtoA,
+                _ => return None,
+            },
+            amount_in: u64::from_le_bytes(*amount_in),
+            amount_out: u64::from_le_bytes(*amount_out),
+            trade_fee: u64::from_le_bytes(*trade_fee),
+            owner_fee: u64::from_le_bytes(*owner_fee),
+            slot: u64::from_le_bytes(*slot),
+        })
+    }
+}
+
+impl Event for SwapEvent {
+    const KIND: u8 = 1;
+
+    fn data(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::LEN);
+        buf.extend_from_slice(self.swap.as_ref());
+        buf.extend_from_slice(self.owner.as_ref());
+        buf.push(match self.trade_direction {
+            TradeDirection::AtoB => 0,
+            TradeDirection::BtoA => 1,
+        });
+        buf.extend_from_slice(&self.amount_in.to_le_bytes());
+        buf.extend_from_slice(&self.amount_out.to_le_bytes());
+        buf.extend_from_slice(&self.trade_fee.to_le_bytes());
+        buf.extend_from_slice(&self.owner_fee.to_le_bytes());
+        buf.extend_from_slice(&self.slot.to_le_bytes());
+        buf
+    }
+}
@@ -2,14 +2,14 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use crate::curve::{base::SwapCurve, fees::Fees};
+use crate::curve::{base::SwapCurve, basket::MAX_BASKET_ASSETS, calculator::TradeDirection, fees::{FeeDiscount, FeeRecipient, Fees, FEE_RECIPIENT_COUNT}};
 use crate::error::SwapError;
+use crate::state::AdminAction;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
-    msg,
     system_program,
     sysvar
 };
@@ -19,6 +19,18 @@ use std::mem::size_of;
 #[cfg(feature = "fuzz")]
 use arbitrary::Arbitrary;
 
+/// Leading byte of instruction data carrying a version header, which no
+/// instruction tag takes
+pub const INSTRUCTION_VERSION_MARKER: u8 = u8::MAX;
+
+/// Version of the instruction data layouts, bumped whenever instructions
+/// gain optional trailing fields. Data without a version header is of
+/// version 0, the layouts from before versioning.
+pub const INSTRUCTION_VERSION: u8 = 4;
+
+/// Longest memo a swap may carry, in bytes
+pub const MAX_MEMO_LEN: usize = 128;
+
 /// Initialize instruction data
 #[repr(C)]
 #[derive(Debug, PartialEq)]
@@ -26,9 +38,20 @@ pub struct Initialize {
     /// swap curve info for pool, including CurveType and anything
     /// else that may be required
     pub swap_curve: SwapCurve,
+    /// fee owner of the pool's swaps and withdrawal fees, in place of the
+    /// one of the program state
+    pub fee_owner: Option<Pubkey>,
 }
 
 
+/// UpdateCurveParameters instruction data
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub struct UpdateCurveParameters {
+    /// new swap curve of the pool, of the same CurveType as the current one
+    pub swap_curve: SwapCurve,
+}
+
 /// Set Global State data
 #[repr(C)]
 #[derive(Debug, PartialEq)]
@@ -49,6 +72,12 @@ pub struct SetGlobalState {
     ///Fee ratio
     pub fees: Fees,
 
+    /// fixed fee discount for holders of a designated token
+    pub fee_discount: FeeDiscount,
+
+    /// quoter allowed to sign swap quotes besides the fee owner
+    pub quoter: Pubkey,
+
 }
 
 
@@ -58,9 +87,23 @@ pub struct SetGlobalState {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Swap {
     /// SOURCE amount to transfer, output to DESTINATION is based on the exchange rate
+    /// of the amount the pool actually receives
     pub amount_in: u64,
     /// Minimum amount of DESTINATION token to output, prevents excessive slippage
     pub minimum_amount_out: u64,
+    /// Maximum execution price impact against the spot price, in basis points
+    pub max_price_impact_bps: Option<u64>,
+    /// SOURCE amount paid to the relayer on top of `amount_in`, zero for none
+    pub tip: u64,
+    /// Memo logged with the swap to tag it for reconciliation, at most
+    /// MAX_MEMO_LEN bytes, empty for none
+    pub memo: Vec<u8>,
+    /// Direction the swap is meant to trade in, checked against the pool
+    /// accounts passed, None to take it from them
+    pub trade_direction: Option<TradeDirection>,
+    /// Whether the swap claims the fee discount, passing the discount
+    /// token account of the user transfer authority
+    pub fee_discount: bool,
 }
 
 /// DepositAllTokenTypes instruction data
@@ -75,6 +118,9 @@ pub struct DepositAllTokenTypes {
     pub maximum_token_a_amount: u64,
     /// Maximum token B amount to deposit, prevents excessive slippage
     pub maximum_token_b_amount: u64,
+    /// Deviation, in basis points, of the price of the maximum amounts from
+    /// the price of the reserves
+    pub max_ratio_deviation_bps: Option<u64>,
 }
 
 /// WithdrawAllTokenTypes instruction data
@@ -115,297 +161,5104 @@ pub struct WithdrawSingleTokenTypeExactAmountOut {
     pub maximum_pool_token_amount: u64,
 }
 
-/// Instructions supported by the token swap program.
+/// LockLiquidity instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[repr(C)]
-#[derive(Debug, PartialEq)]
-pub enum SwapInstruction {
-    ///   Initializes a new swap
-    ///
-    ///   0. `[writable, signer]` New Token-swap to create.
-    ///   1. `[]` swap authority derived from `create_program_address(&[Token-swap account])`
-    ///   2. `[]` token_a Account. Must be non zero, owned by swap authority.
-    ///   3. `[]` token_b Account. Must be non zero, owned by swap authority.
-    ///   4. `[writable]` Pool Token Mint. Must be empty, owned by swap authority.
-    ///   5. `[]` Pool Token Account to deposit trading and withdraw fees.
-    ///   Must be empty, not owned by swap authority
-    ///   6. `[writable]` Pool Token Account to deposit the initial pool token
-    ///   supply.  Must be empty, not owned by swap authority.
-    ///   7. '[]` Token program id
-    Initialize(Initialize),
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockLiquidity {
+    /// Amount of pool tokens to move into the lock
+    pub amount: u64,
+    /// Unix timestamp before which the pool tokens cannot be unlocked
+    pub unlock_timestamp: i64,
+}
 
-    ///   Swap the tokens in the pool.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` swap authority
-    ///   2. `[]` user transfer authority
-    ///   3. `[writable]` token_(A|B) SOURCE Account, amount is transferable by user transfer authority,
-    ///   4. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
-    ///   5. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
-    ///   6. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
-    ///   7. `[writable]` Pool token mint, to generate trading fees
-    ///   8. `[writable]` Fee account, to receive trading fees
-    ///   9. '[]` Token program id
-    ///   10 `[optional, writable]` Host fee account to receive additional trading fees
-    Swap(Swap),
+/// InitializeStakePool instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct InitializeStakePool {
+    /// Reward tokens distributed per slot across all stakers
+    pub reward_per_slot: u64,
+}
 
-    ///   Deposit both types of tokens into the pool.  The output is a "pool"
-    ///   token representing ownership in the pool. Inputs are converted to
-    ///   the current ratio.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` swap authority
-    ///   2. `[]` user transfer authority
-    ///   3. `[writable]` token_a user transfer authority can transfer amount,
-    ///   4. `[writable]` token_b user transfer authority can transfer amount,
-    ///   5. `[writable]` token_a Base Account to deposit into.
-    ///   6. `[writable]` token_b Base Account to deposit into.
-    ///   7. `[writable]` Pool MINT account, swap authority is the owner.
-    ///   8. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
-    ///   9. '[]` Token program id
-    DepositAllTokenTypes(DepositAllTokenTypes),
+/// Stake instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stake {
+    /// Amount of pool tokens to stake
+    pub amount: u64,
+}
 
-    ///   Withdraw both types of tokens from the pool at the current ratio, given
-    ///   pool tokens.  The pool tokens are burned in exchange for an equivalent
-    ///   amount of token A and B.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` swap authority
-    ///   2. `[]` user transfer authority
-    ///   3. `[writable]` Pool mint account, swap authority is the owner
-    ///   4. `[writable]` SOURCE Pool account, amount is transferable by user transfer authority.
-    ///   5. `[writable]` token_a Swap Account to withdraw FROM.
-    ///   6. `[writable]` token_b Swap Account to withdraw FROM.
-    ///   7. `[writable]` token_a user Account to credit.
-    ///   8. `[writable]` token_b user Account to credit.
-    ///   9. `[writable]` Fee account, to receive withdrawal fees
-    ///   10 '[]` Token program id
-    WithdrawAllTokenTypes(WithdrawAllTokenTypes),
+/// Unstake instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unstake {
+    /// Amount of pool tokens to unstake
+    pub amount: u64,
+}
 
-    ///   Set global program state
-    ///
-    ///   0. `[writable]` program state account
-    ///   1. `[]` owner of  this contract
-    ///   2. `[]` owner address to update.
-    ///   3. `[]` fee owner address to update.
-    ///   4. `[]` initial supply
-    ///   5. `[]` fees
-    ///   6. `[]` swap curve.
-    SetGlobalStateInstruction(SetGlobalState),
+/// Rebalance instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rebalance {
+    /// SOURCE amount to transfer into the pool
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output, prevents excessive slippage
+    pub minimum_amount_out: u64,
+}
 
+/// SetPoolOracle instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPoolOracle {
+    /// Maximum deviation of swap prices from the oracle price, in basis
+    /// points, zero removes the oracle
+    pub max_deviation_bps: u64,
 }
 
-impl SwapInstruction {
-    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        msg!("unpack instruction");
-        let (&tag, rest) = input.split_first().ok_or(SwapError::InvalidInstruction)?;
-        msg!("unpack instruction tag {}", tag);
-        Ok(match tag {
-            0 => {
-                let swap_curve = SwapCurve::unpack_unchecked(rest)?;
-                msg!("unpack instruction rest.len() {}", rest.len());
-                // if rest.len() == 1 {
-                    Self::Initialize(Initialize {
-                        swap_curve,
-                    })
-                // } else {
-                //     return Err(SwapError::InvalidInstruction.into());
-                // }
-            }
-            1 => {
-                let (amount_in, rest) = Self::unpack_u64(rest)?;
-                let (minimum_amount_out, _rest) = Self::unpack_u64(rest)?;
-                Self::Swap(Swap {
-                    amount_in,
-                    minimum_amount_out,
-                })
-            }
-            2 => {
-                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
-                let (maximum_token_a_amount, rest) = Self::unpack_u64(rest)?;
-                let (maximum_token_b_amount, _rest) = Self::unpack_u64(rest)?;
-                Self::DepositAllTokenTypes(DepositAllTokenTypes {
-                    pool_token_amount,
-                    maximum_token_a_amount,
-                    maximum_token_b_amount,
-                })
-            }
-            3 => {
-                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
-                let (minimum_token_a_amount, rest) = Self::unpack_u64(rest)?;
-                let (minimum_token_b_amount, _rest) = Self::unpack_u64(rest)?;
-                Self::WithdrawAllTokenTypes(WithdrawAllTokenTypes {
-                    pool_token_amount,
-                    minimum_token_a_amount,
-                    minimum_token_b_amount,
-                })
-            }
-            4 => {// Upgrade Program State
-                let (owner_vec, rest) = rest.split_at(32);
-                let owner = Pubkey::new(owner_vec);
-                let (fee_owner_vec, rest) = rest.split_at(32);
-                let fee_owner = Pubkey::new(fee_owner_vec);
+/// SetDepositCap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetDepositCap {
+    /// Pool token supply deposits may not push the pool above, zero removes
+    /// the cap
+    pub max_lp_supply: u64,
+}
 
-                let (initial_supply, rest) = Self::unpack_u64(rest)?;
-                let (&lp_decimals, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
-                if rest.len() >= Fees::LEN {
-                    let (fees, _rest) = rest.split_at(Fees::LEN);
-                    let fees = Fees::unpack_unchecked(fees)?;
-                    Self::SetGlobalStateInstruction(SetGlobalState {
-                        owner,
-                        fee_owner,
-                        initial_supply,
-                        lp_decimals,
-                        fees,
-                    })
-                } else {
-                    return Err(SwapError::InvalidInstruction.into());
-                }
-            }
-            _ => return Err(SwapError::InvalidInstruction.into()),
-        })
-    }
+/// SetPoolGate instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPoolGate {
+    /// Whether swaps and deposits require passing the gate
+    pub gated: bool,
+    /// Token whose holders pass the gate, the default pubkey to only accept
+    /// allowlist entries
+    pub gate_mint: Pubkey,
+}
 
-    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
-        if input.len() >= 8 {
-            let (amount, rest) = input.split_at(8);
-            let amount = amount
-                .get(..8)
-                .and_then(|slice| slice.try_into().ok())
-                .map(u64::from_le_bytes)
-                .ok_or(SwapError::InvalidInstruction)?;
-            Ok((amount, rest))
-        } else {
-            Err(SwapError::InvalidInstruction.into())
-        }
-    }
+/// SetAllowlisted instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetAllowlisted {
+    /// Add the wallet to the allowlist, or remove it
+    pub allowed: bool,
+}
 
-    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match &*self {
-            Self::Initialize(Initialize {
-                swap_curve
-            }) => {
-                buf.push(0);
-                let mut swap_curve_slice = [0u8; SwapCurve::LEN];
-                Pack::pack_into_slice(swap_curve, &mut swap_curve_slice[..]);
-                buf.extend_from_slice(&swap_curve_slice);
-            }
-            Self::Swap(Swap {
-                amount_in,
-                minimum_amount_out,
-            }) => {
-                buf.push(1);
-                buf.extend_from_slice(&amount_in.to_le_bytes());
-                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
-            }
-            Self::DepositAllTokenTypes(DepositAllTokenTypes {
-                pool_token_amount,
-                maximum_token_a_amount,
-                maximum_token_b_amount,
-            }) => {
-                buf.push(2);
-                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
-                buf.extend_from_slice(&maximum_token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&maximum_token_b_amount.to_le_bytes());
-            }
-            Self::WithdrawAllTokenTypes(WithdrawAllTokenTypes {
-                pool_token_amount,
-                minimum_token_a_amount,
-                minimum_token_b_amount,
-            }) => {
-                buf.push(3);
-                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
-            }
-            Self::SetGlobalStateInstruction(SetGlobalState {
-                owner,
-                fee_owner,
-                initial_supply,
-                lp_decimals,
-                fees,
-            }) => {
-                buf.push(4);
-                buf.extend_from_slice(owner.as_ref());
-                buf.extend_from_slice(fee_owner.as_ref());
-                buf.extend_from_slice(&initial_supply.to_le_bytes());
-                buf.extend_from_slice(&lp_decimals.to_le_bytes());
-                let mut fees_slice = [0u8; Fees::LEN];
-                Pack::pack_into_slice(fees, &mut fees_slice[..]);
-                buf.extend_from_slice(&fees_slice);
-            }
-        }
-        buf
-    }
+/// SetWithdrawFee instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetWithdrawFee {
+    /// Fee charged in pool tokens on withdrawals, over the fee denominator
+    /// of the program state, zero removes the fee
+    pub withdraw_fee_numerator: u64,
+    /// Burn the fee instead of paying it to the fee owner
+    pub burn: bool,
 }
 
-/// Creates an 'initialize' instruction.
-pub fn initialize(
-    program_id: &Pubkey,
-    token_program_id: &Pubkey,
-    swap_pubkey: &Pubkey,
-    authority_pubkey: &Pubkey,
-    token_a_pubkey: &Pubkey,
-    token_b_pubkey: &Pubkey,
-    pool_pubkey: &Pubkey,
-    fee_pubkey: &Pubkey,
-    destination_pubkey: &Pubkey,
-    swap_curve: SwapCurve,
-) -> Result<Instruction, ProgramError> {
-    let init_data = SwapInstruction::Initialize(Initialize {
-        swap_curve
-    });
-    let data = init_data.pack();
+/// SetMinHoldPeriod instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetMinHoldPeriod {
+    /// Slots between a deposit and a withdrawal by the same owner, zero
+    /// removes the holding period
+    pub min_hold_slots: u64,
+}
 
-    let accounts = vec![
-        AccountMeta::new(*swap_pubkey, true),
-        AccountMeta::new_readonly(*authority_pubkey, false),
-        AccountMeta::new_readonly(*token_a_pubkey, false),
-        AccountMeta::new_readonly(*token_b_pubkey, false),
-        AccountMeta::new(*pool_pubkey, false),
-        AccountMeta::new_readonly(*fee_pubkey, false),
-        AccountMeta::new(*destination_pubkey, false),
-        AccountMeta::new_readonly(*token_program_id, false),
-    ];
+/// SetProtocolLiquidityShare instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetProtocolLiquidityShare {
+    /// Share of the owner fee, in basis points, converted into protocol-owned
+    /// liquidity, zero pays the whole fee to the fee owner
+    pub protocol_liquidity_bps: u64,
+}
 
-    Ok(Instruction {
-        program_id: *program_id,
-        accounts,
-        data,
-    })
+/// WithdrawProtocolLiquidity instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawProtocolLiquidity {
+    /// Pool tokens to move out of the protocol-owned account
+    pub pool_token_amount: u64,
 }
 
-/// Creates a 'deposit_all_token_types' instruction.
-pub fn deposit_all_token_types(
-    program_id: &Pubkey,
-    token_program_id: &Pubkey,
+/// SetBuybackConfig instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetBuybackConfig {
+    /// Token bought back with protocol fees and burned
+    pub buyback_mint: Pubkey,
+    /// Buyback tokens that may be burned per epoch, zero disables buybacks
+    pub buyback_epoch_limit: u64,
+}
+
+/// BuybackAndBurn instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuybackAndBurn {
+    /// Fee tokens to spend
+    pub amount_in: u64,
+    /// Minimum amount of buyback tokens to burn, prevents excessive slippage
+    pub minimum_amount_out: u64,
+}
+
+/// SetFeeRecipients instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetFeeRecipients {
+    /// Recipients splitting the owner fee with the fee owner, their weights
+    /// may not add up to more than the whole fee
+    pub fee_recipients: [FeeRecipient; FEE_RECIPIENT_COUNT],
+}
+
+/// CommitSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommitSwap {
+    /// Hash of the swap to reveal, see `SwapCommitment::hash`
+    pub hash: [u8; 32],
+}
+
+/// RevealSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RevealSwap {
+    /// SOURCE amount to transfer, as committed
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output, as committed
+    pub minimum_amount_out: u64,
+    /// Salt hashed into the commitment
+    pub salt: [u8; 32],
+}
+
+/// SetBatchAuction instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetBatchAuction {
+    /// Slots swaps are queued for before they can be settled, zero to go
+    /// back to regular swaps
+    pub batch_window_slots: u64,
+}
+
+/// SubmitBatchSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmitBatchSwap {
+    /// SOURCE amount to queue
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output at the clearing price,
+    /// the swap is refunded otherwise
+    pub minimum_amount_out: u64,
+}
+
+/// SetRebasing instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetRebasing {
+    /// The vault balances of the pool change without trades
+    pub rebasing: bool,
+}
+
+/// SetClaimableFees instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetClaimableFees {
+    /// Pay trade fees out to LP positions instead of compounding them
+    pub claimable_fees: bool,
+}
+
+/// SetNftPositions instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetNftPositions {
+    /// Deposits open a position owned by a new NFT instead of minting pool
+    /// tokens
+    pub nft_positions: bool,
+}
+
+/// SetGuardian instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetGuardian {
+    /// Guardian allowed to pause swaps and deposits, the default pubkey for
+    /// none
+    pub guardian: Pubkey,
+}
+
+/// SetPaused instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPaused {
+    /// Pause swaps and deposits, or resume them
+    pub paused: bool,
+}
+
+/// SetSlotVolumeCap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetSlotVolumeCap {
+    /// Token A volume the swaps of a single slot may not exceed, zero
+    /// removes the cap
+    pub max_slot_volume: u64,
+}
+
+/// SetCircuitBreaker instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetCircuitBreaker {
+    /// Price move, in basis points, within a window past which trades fail,
+    /// zero removes the circuit breaker
+    pub breaker_move_bps: u64,
+    /// Slots of a window
+    pub breaker_window_slots: u64,
+}
+
+/// ZapIn instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZapIn {
+    /// SOURCE amount to swap in part and deposit
+    pub amount_in: u64,
+    /// Minimum amount of pool tokens to mint
+    pub minimum_pool_token_amount: u64,
+}
+
+/// ZapOut instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZapOut {
+    /// Amount of pool tokens to burn
+    pub pool_token_amount: u64,
+    /// Minimum amount of DESTINATION token to receive, withdrawn and swapped
+    pub minimum_amount_out: u64,
+}
+
+/// SetPoolFeeOwner instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPoolFeeOwner {
+    /// Fee owner of the pool's swaps, the default pubkey to fall back to the
+    /// one of the program state
+    pub fee_owner: Pubkey,
+}
+
+/// SetOwnerFeeInPoolTokens instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetOwnerFeeInPoolTokens {
+    /// Mint the fee owner's cut of the owner fee as pool tokens, or transfer
+    /// it in the input token
+    pub owner_fee_in_pool_tokens: bool,
+}
+
+/// SetKeeperReward instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetKeeperReward {
+    /// Protocol-owned pool tokens paid to the keeper of a permissionless
+    /// crank of a pool, zero disables keeper rewards
+    pub keeper_reward: u64,
+    /// Slots between two rewarded cranks of the same pool
+    pub keeper_interval_slots: u64,
+}
+
+/// MigrateFromSplTokenSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrateFromSplTokenSwap {
+    /// Amount of pool tokens of the spl-token-swap pool to withdraw with
+    pub pool_token_amount: u64,
+    /// Minimum amount of pool tokens to mint
+    pub minimum_pool_token_amount: u64,
+}
+
+/// SwapBasket instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapBasket {
+    /// SOURCE amount to transfer, output to DESTINATION is based on the exchange rate
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output, prevents excessive slippage
+    pub minimum_amount_out: u64,
+}
+
+/// DepositBasket instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DepositBasket {
+    /// Pool token amount to transfer. Each asset amount is set by the
+    /// current exchange rate and size of the pool
+    pub pool_token_amount: u64,
+    /// Maximum amount of each asset to deposit, in the order of the basket,
+    /// prevents excessive slippage
+    pub maximum_token_amounts: [u64; MAX_BASKET_ASSETS],
+}
+
+/// WithdrawBasket instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithdrawBasket {
+    /// Amount of pool tokens to burn. User receives an output of each asset
+    /// based on the percentage of the pool tokens that are returned.
+    pub pool_token_amount: u64,
+    /// Minimum amount of each asset to receive, in the order of the basket,
+    /// prevents excessive slippage
+    pub minimum_token_amounts: [u64; MAX_BASKET_ASSETS],
+}
+
+/// IncreasePosition instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct IncreasePosition {
+    /// Amount of pool tokens to move into the position
+    pub pool_token_amount: u64,
+}
+
+/// DecreasePosition instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecreasePosition {
+    /// Amount of pool tokens to take out of the position
+    pub pool_token_amount: u64,
+}
+
+/// SplitSwap instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitSwap {
+    /// SOURCE amount to transfer across both pools
+    pub amount_in: u64,
+    /// Minimum combined amount of DESTINATION token to output
+    pub minimum_amount_out: u64,
+    /// Share of `amount_in` routed through the first pool, in basis points
+    pub first_pool_bps: u64,
+}
+
+/// SwapWithQuote instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapWithQuote {
+    /// SOURCE amount to transfer, as quoted
+    pub amount_in: u64,
+    /// DESTINATION amount to receive, as quoted
+    pub amount_out: u64,
+    /// Unix timestamp the quote expires at
+    pub expiry: i64,
+    /// Quote nonce
+    pub nonce: u64,
+}
+
+/// PlaceLimitOrder instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaceLimitOrder {
+    /// Order id, part of the order address
+    pub order_id: u64,
+    /// SOURCE amount to sell
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token for `amount_in`, the limit price
+    pub minimum_amount_out: u64,
+    /// SOURCE amount paid to the keeper filling the order
+    pub keeper_fee: u64,
+}
+
+/// CreateDca instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateDca {
+    /// Position id, part of the position address
+    pub dca_id: u64,
+    /// SOURCE amount to escrow for all ticks, keeper fees included
+    pub amount: u64,
+    /// SOURCE amount swapped per tick
+    pub amount_per_tick: u64,
+    /// Minimum amount of DESTINATION token per tick, prevents excessive slippage
+    pub minimum_amount_out_per_tick: u64,
+    /// SOURCE amount paid to the keeper executing each tick
+    pub keeper_fee: u64,
+    /// Minimum number of seconds between two ticks
+    pub interval: i64,
+}
+
+/// QuoteWithdraw instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteWithdraw {
+    /// Amount of pool tokens to quote the withdrawal of
+    pub pool_token_amount: u64,
+}
+
+/// MigrateLiquidity instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrateLiquidity {
+    /// Amount of pool tokens of the pool to withdraw from to burn
+    pub pool_token_amount: u64,
+    /// Minimum amount of pool tokens of the pool to deposit into to mint
+    pub minimum_pool_token_amount: u64,
+    /// Swap the share of the token withdrawn in excess of the reserves of
+    /// the pool to deposit into that balances the rest, before depositing
+    pub swap_imbalance: bool,
+}
+
+/// SetFeeGovernance instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetFeeGovernance {
+    /// spl-governance program of the realm, the default pubkey to hand the
+    /// fees back to the program owner
+    pub governance_program_id: Pubkey,
+    /// Realm of the governance
+    pub realm: Pubkey,
+    /// Seed of the governance program address in the realm, the account it
+    /// governs
+    pub governance_seed: Pubkey,
+}
+
+/// SetGovernedFees instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetGovernedFees {
+    /// Fee ratio
+    pub fees: Fees,
+    /// Fixed fee discount for holders of a designated token
+    pub fee_discount: FeeDiscount,
+}
+
+/// SchedulePendingAction instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchedulePendingAction {
+    /// Unix timestamp from which the action may be executed, at least
+    /// MIN_PENDING_ACTION_DELAY away
+    pub eta: i64,
+    /// Change to apply
+    pub action: AdminAction,
+}
+
+/// ExecutePendingAction instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutePendingAction {
+    /// Id of the pending action
+    pub action_id: u64,
+}
+
+/// CancelPendingAction instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CancelPendingAction {
+    /// Id of the pending action
+    pub action_id: u64,
+}
+
+/// SetPoolCount instruction data
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetPoolCount {
+    /// Pools and basket pools created before the global state counted them
+    pub pool_count: u64,
+}
+
+/// Instructions supported by the token swap program.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum SwapInstruction {
+    ///   Initializes a new swap
+    ///
+    ///   0. `[writable, signer]` New Token-swap to create.
+    ///   1. `[]` swap authority derived from `create_program_address(&[Token-swap account])`
+    ///   2. `[writable]` Global state account, counting the pool
+    ///   3. `[]` token_a Account. Must be non zero, owned by swap authority.
+    ///   4. `[]` token_b Account. Must be non zero, owned by swap authority.
+    ///   5. `[writable]` Pool Token Mint. Must be empty, owned by swap authority, with at most MAX_LP_DECIMALS decimals.
+    ///   6. `[writable]` Pool Token Account to deposit the initial pool token
+    ///      supply, less MIN_LP_SUPPLY.  Must be empty, not owned by swap authority.
+    ///   7. '[]` Token program id
+    ///   8. `[]` token_a Mint, whose decimals a stable curve is set with
+    ///   9. `[]` token_b Mint
+    ///   10. `[writable]` Pool Token Account owned by swap authority to lock MIN_LP_SUPPLY in
+    ///   11. `[signer]` Program owner, when the pool has its own fee owner
+    Initialize(Initialize),
+
+    ///   Swap the tokens in the pool. A relayer may pay the transaction fee
+    ///   and be compensated with a tip taken from the SOURCE account. A memo
+    ///   is logged as program data once the swap succeeds. A trade direction,
+    ///   when given, must match the pool accounts passed. Holders of the
+    ///   discount token claiming the fee discount trade at a reduced fee.
+    ///
+    ///   0. `[writable]` Token-swap, its price oracle is updated after the trade
+    ///   1. `[]` swap authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` token_(A|B) SOURCE Account, amount is transferable by user transfer authority,
+    ///   5. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   6. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   7. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
+    ///   8. `[writable]` Pool token mint, to generate trading fees
+    ///   9. `[writable]` Fee account, to receive trading fees, a pool token account when the pool mints the owner fee as pool tokens. Left out when the swap charges no owner fee, the accounts below then moving up by one
+    ///   10. '[]` Token program id
+    ///   11. `[]` Price oracle account, only when the pool has one
+    ///   12. `[]` Pool gate token account or allowlist entry of the user transfer authority, only when the pool is gated
+    ///   13. `[writable]` Protocol pool token account, only when the pool converts owner fees into protocol-owned liquidity
+    ///   14. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    ///   15. `[writable]` Relayer token_(A|B) SOURCE token account, only when the swap carries a tip
+    ///   16. `[]` Fee discount holder account, only when the swap claims the fee discount. Owned by the user transfer authority and holding at least the minimum balance of the discount, never empty
+    Swap(Swap),
+
+    ///   Deposit both types of tokens into the pool.  The output is a "pool"
+    ///   token representing ownership in the pool. Inputs are converted to
+    ///   the current ratio. The pool tokens may be minted to an account of
+    ///   another owner than the user transfer authority, unless the pool
+    ///   enforces a holding period, which the deposit restarts. Given a maximum ratio deviation,
+    ///   the deposit fails when the maximum amounts are priced further from
+    ///   the reserves.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[]` Global state account
+    ///   3. `[signer]` user transfer authority
+    ///   4. `[writable]` token_a user transfer authority can transfer amount,
+    ///   5. `[writable]` token_b user transfer authority can transfer amount,
+    ///   6. `[writable]` token_a Base Account to deposit into.
+    ///   7. `[writable]` token_b Base Account to deposit into.
+    ///   8. `[writable]` Pool MINT account, swap authority is the owner.
+    ///   9. `[writable]` Pool Account to deposit the generated tokens, of any owner, of the user transfer authority when the pool enforces a holding period.
+    ///   10. '[]` Token program id
+    ///   11. `[]` Pool gate token account or allowlist entry of the user transfer authority, only when the pool is gated
+    ///   12. `[writable]` Deposit record of the pool token account owner, only when the pool enforces a holding period
+    ///   13. `[writable]` Pool token account owned by the swap authority to lock MIN_LP_SUPPLY in, only when the pool mint supply is zero
+    ///
+    ///   A pool giving out position NFTs takes its position vault as the
+    ///   account 9 Pool Account, and then:
+    ///
+    ///   14. `[writable]` LP position, program address of the swap and NFT mint
+    ///   15. `[writable]` NFT mint, without supply or freeze authority, with the swap authority as mint authority
+    ///   16. `[writable]` NFT DESTINATION account
+    ///   17. `[writable, signer]` Payer of the LP position
+    ///   18. `[]` System program id
+    ///   19. `[]` Rent sysvar
+    DepositAllTokenTypes(DepositAllTokenTypes),
+
+    ///   Withdraw both types of tokens from the pool at the current ratio, given
+    ///   pool tokens.  The pool tokens are burned in exchange for an equivalent
+    ///   amount of token A and B, credited to accounts of any owner. The
+    ///   withdrawal leaving only MIN_LP_SUPPLY pool tokens also takes
+    ///   reserves of at most MAX_SWEEP_DUST left by rounding, but for one
+    ///   unit of each.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[]` Global state account
+    ///   3. `[signer]` user transfer authority
+    ///   4. `[writable]` Pool mint account, swap authority is the owner
+    ///   5. `[writable]` SOURCE Pool account, amount is transferable by user transfer authority.
+    ///   6. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   7. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   8. `[writable]` token_a Account to credit, of any owner.
+    ///   9. `[writable]` token_b Account to credit, of any owner.
+    ///   10. '[]` Token program id
+    ///   11. `[writable]` Pool token account of the fee owner of the pool, or of the program state when the pool has none, only when the pool charges a withdrawal fee that is not burned
+    ///   12. `[]` Deposit record of the SOURCE pool account owner, only when the pool enforces a holding period
+    WithdrawAllTokenTypes(WithdrawAllTokenTypes),
+
+    ///   Set global program state. Once the state is initialized, the owner,
+    ///   fees and fee discount change only through scheduled actions and must
+    ///   be passed unchanged.
+    ///
+    ///   0. `[writable]` program state account
+    ///   1. `[]` owner of  this contract
+    ///   2. `[]` owner address to update.
+    ///   3. `[]` fee owner address to update.
+    ///   4. `[]` initial supply
+    ///   5. `[]` fees
+    ///   6. `[]` swap curve.
+    ///   7. `[]` holder fee discount
+    ///   8. `[]` swap quoter
+    SetGlobalStateInstruction(SetGlobalState),
+
+    ///   Lock pool tokens in the owner's lock escrow until `unlock_timestamp`.
+    ///   Creates the lock on first use; an existing lock can be topped up and
+    ///   extended but never shortened.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Lock account, derived from `[LOCK_TAG, swap, owner]`
+    ///   2. `[writable]` Lock vault, pool token account owned by the lock account
+    ///   3. `[writable, signer]` Lock owner, also pays for the lock account
+    ///   4. `[writable]` SOURCE Pool token account, transferable by the owner
+    ///   5. `[]` Token program id
+    ///   6. `[]` System program id
+    ///   7. `[]` Rent sysvar
+    LockLiquidity(LockLiquidity),
+
+    ///   Release all locked pool tokens once the unlock timestamp has passed.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Lock account, derived from `[LOCK_TAG, swap, owner]`
+    ///   2. `[writable]` Lock vault, pool token account owned by the lock account
+    ///   3. `[signer]` Lock owner
+    ///   4. `[writable]` DESTINATION Pool token account
+    ///   5. `[]` Token program id
+    UnlockLiquidity,
+
+    ///   Create the stake pool of a swap, distributing `reward_per_slot` reward
+    ///   tokens pro-rata to staked pool tokens. Only the program owner can
+    ///   create it.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Global state
+    ///   2. `[writable]` Stake pool, derived from `[STAKE_TAG, swap]`
+    ///   3. `[]` Stake vault, pool token account owned by the stake pool
+    ///   4. `[]` Reward vault, reward token account owned by the stake pool
+    ///   5. `[writable, signer]` Program owner, also pays for the stake pool
+    ///   6. `[]` System program id
+    ///   7. `[]` Rent sysvar
+    InitializeStakePool(InitializeStakePool),
+
+    ///   Stake pool tokens.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Stake pool
+    ///   2. `[writable]` Stake position, derived from `[STAKE_TAG, stake pool, owner]`
+    ///   3. `[writable]` Stake vault
+    ///   4. `[writable, signer]` Position owner, also pays for the position
+    ///   5. `[writable]` SOURCE Pool token account, transferable by the owner
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[]` Rent sysvar
+    Stake(Stake),
+
+    ///   Unstake pool tokens, keeping the earned rewards claimable.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Stake pool
+    ///   2. `[writable]` Stake position
+    ///   3. `[writable]` Stake vault
+    ///   4. `[signer]` Position owner
+    ///   5. `[writable]` DESTINATION Pool token account
+    ///   6. `[]` Token program id
+    Unstake(Unstake),
+
+    ///   Claim the rewards earned by a stake position, bounded by the balance
+    ///   of the reward vault.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Stake pool
+    ///   2. `[writable]` Stake position
+    ///   3. `[writable]` Reward vault
+    ///   4. `[signer]` Position owner
+    ///   5. `[writable]` DESTINATION Reward token account
+    ///   6. `[]` Token program id
+    ClaimRewards,
+
+    ///   Set the tracked reserves of the pool to the vault balances, folding
+    ///   in any tokens transferred directly to the vaults.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` token_a Swap Account
+    ///   2. `[]` token_b Swap Account
+    ///   3. `[]` Global state account, only to be paid the keeper reward
+    ///   4. `[]` Protocol liquidity authority
+    ///   5. `[writable]` Protocol pool token account, paying the keeper reward
+    ///   6. `[writable]` Keeper pool token account
+    ///   7. `[]` Token program id
+    Sync,
+
+    ///   Send the vault balances above the tracked reserves to the given
+    ///   token accounts.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[writable]` token_a Swap Account
+    ///   3. `[writable]` token_b Swap Account
+    ///   4. `[writable]` token_a DESTINATION Account
+    ///   5. `[writable]` token_b DESTINATION Account
+    ///   6. `[]` Token program id
+    Skim,
+
+    ///   Swap at zero fee into the short side of an off-peg stable pool, by
+    ///   the fee owner and within the per-epoch rebalance limit.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[signer]` Fee owner
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` token_(A|B) SOURCE Account, owned by the fee owner
+    ///   5. `[writable]` token_(A|B) Base Account to swap INTO, the short side of the pool
+    ///   6. `[writable]` token_(A|B) Base Account to swap FROM
+    ///   7. `[writable]` token_(A|B) DESTINATION Account
+    ///   8. `[]` Token program id
+    Rebalance(Rebalance),
+
+    ///   Set or remove the price oracle swaps of a pool are checked against.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    ///   3. `[]` Pyth price account of token A priced in token B
+    ///   4. `[]` token_a Mint
+    ///   5. `[]` token_b Mint
+    SetPoolOracle(SetPoolOracle),
+
+    ///   Swap through two pools of the same pair, splitting the input between
+    ///   them and enforcing a combined minimum output. Each pool must also pay
+    ///   its share of the minimum, in proportion to its share of the input, so
+    ///   the minimum should leave room for the price impact of either pool.
+    ///
+    ///   0. `[signer]` user transfer authority
+    ///   1. `[]` Global state account
+    ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by user transfer authority
+    ///   3. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner
+    ///   4. `[]` Token program id
+    ///   5. `[writable]` First Token-swap
+    ///   6. `[]` First swap authority
+    ///   7. `[writable]` First pool Base Account to swap INTO
+    ///   8. `[writable]` First pool Base Account to swap FROM
+    ///   9. `[writable]` First pool token mint
+    ///   10. `[writable]` Fee account of the first pool's source token
+    ///   11. `[writable]` Second Token-swap
+    ///   12. `[]` Second swap authority
+    ///   13. `[writable]` Second pool Base Account to swap INTO
+    ///   14. `[writable]` Second pool Base Account to swap FROM
+    ///   15. `[writable]` Second pool token mint
+    ///   16. `[writable]` Fee account of the second pool's source token
+    ///   17. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    ///   18. `[]` Price oracle, gate and protocol pool token accounts of each
+    ///       pool that has them, first pool first. The gate account is the
+    ///       user transfer authority's gate token account or allowlist entry
+    SplitSwap(SplitSwap),
+
+    ///   Fill a quote signed off-chain by the fee owner or the designated
+    ///   quoter, at the quoted price. The fill follows the rules of a swap:
+    ///   the pool fees are charged, the owner fee shared with the fee
+    ///   recipients and the protocol, and the quoted output may exceed the
+    ///   output of the pool curve for what the pool receives by at most
+    ///   `MAX_QUOTE_DEVIATION_BPS`. The ed25519 program instruction
+    ///   verifying the quote signature must directly precede this
+    ///   instruction. Each taker's quotes of a pool must use rising nonces,
+    ///   kept in a quote nonce record the taker pays for on its first fill.
+    ///   Gated pools do not accept quotes.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[writable, signer]` user transfer authority, the quote taker
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` token_(A|B) SOURCE Account, amount is transferable by user transfer authority
+    ///   5. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   6. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   7. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
+    ///   8. `[writable]` Pool token mint, to generate trading fees
+    ///   9. `[writable]` Fee account, to receive trading fees, a pool token account when the pool mints the owner fee as pool tokens. Left out when the fill charges no owner fee, the accounts below then moving up by one
+    ///   10. `[]` Token program id
+    ///   11. `[]` Instructions sysvar
+    ///   12. `[writable]` Quote nonce record of the taker in the pool
+    ///   13. `[]` System program
+    ///   14. `[]` Rent sysvar
+    ///   15. `[]` Price oracle, required when the pool has one
+    ///   16. `[writable]` Protocol pool token account, required when the pool accrues protocol-owned liquidity
+    ///   17. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    SwapWithQuote(SwapWithQuote),
+
+    ///   Escrow SOURCE tokens in a limit order, filled by keepers once the
+    ///   pool pays at least `minimum_amount_out` for `amount_in`.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Order account, derived from `[ORDER_TAG, swap, owner, order_id]`
+    ///   2. `[]` Order vault, SOURCE token account owned by the order account
+    ///   3. `[writable, signer]` Order owner, also pays for the order account
+    ///   4. `[writable]` token_(A|B) SOURCE Account, amount is transferable by the owner
+    ///   5. `[]` token_(A|B) DESTINATION Account of the owner, credited on fill
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[]` Rent sysvar
+    PlaceLimitOrder(PlaceLimitOrder),
+
+    ///   Cancel a limit order, refunding the escrowed tokens and closing the
+    ///   order accounts.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Order account
+    ///   2. `[writable]` Order vault
+    ///   3. `[writable, signer]` Order owner
+    ///   4. `[writable]` token_(A|B) SOURCE Account to refund
+    ///   5. `[]` Token program id
+    ///   6. `[writable]` Rent recipient, the order owner if omitted
+    CancelLimitOrder,
+
+    ///   Fill a limit order against the pool once its price crosses the
+    ///   limit, with the fees of a regular swap. The keeper is paid the
+    ///   order's keeper fee, the input the swap rounds away is refunded to
+    ///   the owner and the order accounts are closed to the owner.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[]` Global state account
+    ///   3. `[writable]` Order account
+    ///   4. `[writable]` Order vault
+    ///   5. `[writable]` Order owner, receives the rent of the order accounts
+    ///   6. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   7. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   8. `[writable]` token_(A|B) DESTINATION Account of the order
+    ///   9. `[writable]` Pool token mint
+    ///   10. `[writable]` Fee account, to receive trading fees, a pool token account when the pool mints the owner fee as pool tokens
+    ///   11. `[writable]` Keeper token_(A|B) SOURCE Account, to receive the keeper fee
+    ///   12. `[writable]` token_(A|B) SOURCE Account of the owner, refunded the input the swap rounds away
+    ///   13. `[]` Token program id
+    ///   14. `[]` Price oracle account, only when the pool has one
+    ///   15. `[writable]` Protocol pool token account, only when the pool converts owner fees into protocol-owned liquidity
+    ///   16. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    FillLimitOrder,
+
+    ///   Escrow SOURCE tokens in a recurring order, swapped by keepers in
+    ///   fixed-size ticks at most once per interval.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Position account, derived from `[DCA_TAG, swap, owner, dca_id]`
+    ///   2. `[]` Position vault, SOURCE token account owned by the position account
+    ///   3. `[writable, signer]` Position owner, also pays for the position account
+    ///   4. `[writable]` token_(A|B) SOURCE Account, amount is transferable by the owner
+    ///   5. `[]` token_(A|B) DESTINATION Account of the owner, credited on every tick
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[]` Rent sysvar
+    CreateDca(CreateDca),
+
+    ///   Swap one tick of a recurring order once its interval has elapsed,
+    ///   with the fees of a regular swap. The keeper is paid the position's
+    ///   keeper fee, the input the swap rounds away stays in the position.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[]` Global state account
+    ///   3. `[writable]` Position account
+    ///   4. `[writable]` Position vault
+    ///   5. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   6. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   7. `[writable]` token_(A|B) DESTINATION Account of the position
+    ///   8. `[writable]` Pool token mint
+    ///   9. `[writable]` Fee account, to receive trading fees, a pool token account when the pool mints the owner fee as pool tokens
+    ///   10. `[writable]` Keeper token_(A|B) SOURCE Account, to receive the keeper fee
+    ///   11. `[]` Token program id
+    ///   12. `[]` Price oracle account, only when the pool has one
+    ///   13. `[writable]` Protocol pool token account, only when the pool converts owner fees into protocol-owned liquidity
+    ///   14. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    ExecuteDcaTick,
+
+    ///   Close a recurring order, refunding the escrowed tokens and closing
+    ///   the position accounts.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Position account
+    ///   2. `[writable]` Position vault
+    ///   3. `[writable, signer]` Position owner
+    ///   4. `[writable]` token_(A|B) SOURCE Account to refund
+    ///   5. `[]` Token program id
+    ///   6. `[writable]` Rent recipient, the position owner if omitted
+    CloseDca,
+
+    ///   Return the virtual price of a stable pool's pool token, the
+    ///   invariant D per pool token scaled by `VIRTUAL_PRICE_PRECISION`, as
+    ///   a little-endian u128 in the return data.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pool token mint
+    ///   2. `[]` token_a Swap Account
+    ///   3. `[]` token_b Swap Account
+    GetVirtualPrice,
+
+    ///   Return the fully-resolved state of a pool as a packed
+    ///   `PoolStateView` in the return data.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Global state account
+    ///   2. `[]` Pool token mint
+    ///   3. `[]` token_a Swap Account
+    ///   4. `[]` token_b Swap Account
+    GetPoolState,
+
+    ///   Grow the program state account in place from the legacy layout to
+    ///   the latest versioned layout, which leaves room for new fields. The
+    ///   account keeps its address.
+    ///
+    ///   0. `[writable]` Global state account, in the legacy layout
+    ///   1. `[writable, signer]` Program owner, topping up the rent
+    ///   2. `[]` System program
+    ///   3. `[]` Rent sysvar
+    MigrateGlobalState,
+
+    ///   Replace the parameters of a stable or constant price pool's curve,
+    ///   keeping its curve type. Rejected once the pool is initialized: the
+    ///   amp of a pool changes only through a scheduled `SetAmp` action.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    UpdateCurveParameters(UpdateCurveParameters),
+
+    ///   Deprecate a pool for good, blocking swaps and deposits while
+    ///   keeping withdrawals open so LPs can wind it down.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    DeprecatePool,
+
+    ///   Set or remove the cap on the pool token supply deposits may reach.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetDepositCap(SetDepositCap),
+
+    ///   Restrict swaps and deposits of a pool to wallets holding the gate
+    ///   token or an allowlist entry, or lift the restriction. Quotes,
+    ///   limit orders and recurring orders are not available on gated pools.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetPoolGate(SetPoolGate),
+
+    ///   Add a wallet to the allowlist of a pool, creating its entry, or
+    ///   remove it, closing the entry. The rent of a closed entry goes back
+    ///   to the account that paid it, unless that account is the signing
+    ///   program owner, who may send it elsewhere.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable, signer]` Program owner, paying for the entry
+    ///   2. `[]` Global state account
+    ///   3. `[writable]` Allowlist entry, program address of the swap and wallet
+    ///   4. `[]` Wallet
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    ///   7. `[writable]` Rent recipient on removal, the program owner if omitted
+    SetAllowlisted(SetAllowlisted),
+
+    ///   Set or remove the fee charged in pool tokens on withdrawals from a
+    ///   pool, paid to the fee owner or burned for the remaining liquidity
+    ///   providers.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetWithdrawFee(SetWithdrawFee),
+
+    ///   Set or remove the number of slots liquidity providers have to hold
+    ///   pool tokens between a deposit and a withdrawal. Deposits and
+    ///   withdrawals of the pool then require the deposit record of the
+    ///   pool token account owner.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetMinHoldPeriod(SetMinHoldPeriod),
+
+    ///   Create the deposit record of an owner for a pool. A new record
+    ///   counts as a deposit at the current slot.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable, signer]` Payer of the record
+    ///   2. `[writable]` Deposit record, program address of the swap and owner
+    ///   3. `[]` Owner of the pool token accounts
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    InitDepositRecord,
+
+    ///   Set the share of the owner fee of a pool that swaps deposit back
+    ///   into the pool, minting pool tokens to an account of the protocol
+    ///   liquidity authority.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetProtocolLiquidityShare(SetProtocolLiquidityShare),
+
+    ///   Move protocol-owned pool tokens to an account chosen by the program
+    ///   owner.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    ///   3. `[]` Protocol liquidity authority, program address of PROTOCOL_LIQUIDITY_TAG
+    ///   4. `[writable]` Protocol pool token account, owned by the protocol liquidity authority
+    ///   5. `[writable]` Pool token account to credit
+    ///   6. `[]` Token program id
+    WithdrawProtocolLiquidity(WithdrawProtocolLiquidity),
+
+    ///   Set the token bought back with protocol fees and the amount of it
+    ///   that may be burned per epoch.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner
+    SetBuybackConfig(SetBuybackConfig),
+
+    ///   Swap protocol fees held by the fee owner for the buyback token
+    ///   through a pool, and burn what was bought.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Fee owner
+    ///   2. `[writable]` Token-swap
+    ///   3. `[]` swap authority
+    ///   4. `[writable]` Fee owner token_(A|B) SOURCE Account holding the fees to spend
+    ///   5. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   6. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the buyback token.
+    ///   7. `[writable]` Fee owner buyback token Account, the bought tokens are burned from it
+    ///   8. `[writable]` Pool token mint
+    ///   9. `[writable]` Fee account, to receive trading fees
+    ///   10. `[]` Token program id
+    ///   11. `[writable]` Buyback token mint
+    ///   12. `[]` Price oracle, gate and protocol pool token accounts of the pool, as for a swap
+    BuybackAndBurn(BuybackAndBurn),
+
+    ///   Set the recipients splitting the owner fee of swaps with the fee
+    ///   owner. Swaps then pay each enabled recipient into its token account
+    ///   of the SOURCE token.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner
+    SetFeeRecipients(SetFeeRecipients),
+
+    ///   Sweep the whole balance of a token account owned by the swap
+    ///   authority whose mint is neither token A, token B nor the pool mint,
+    ///   such as tokens sent to the pool by mistake.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[signer]` Program owner
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` Foreign token account, owned by the swap authority
+    ///   5. `[writable]` Token account to credit
+    ///   6. `[]` Token program id
+    RecoverForeignTokens,
+
+    ///   Commit to a swap without revealing its size or direction. Committing
+    ///   again replaces the pending commitment.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable, signer]` User transfer authority, payer of the commitment
+    ///   2. `[writable]` Swap commitment, program address of the swap and user transfer authority
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    CommitSwap(CommitSwap),
+
+    ///   Execute a committed swap from the slot after its commitment, and
+    ///   close the commitment.
+    ///
+    ///   0. `[writable]` Swap commitment
+    ///   1. `[]` The accounts of Swap, in the same order, with the user transfer authority writable to receive the commitment rent. A revealed swap does not claim the fee discount
+    RevealSwap(RevealSwap),
+
+    ///   Switch a pool to batch auctions, or back to regular swaps. Swaps of
+    ///   a batch auction pool are queued for a window of slots and settled
+    ///   together at a single clearing price, and regular swaps are
+    ///   rejected. The batch auction account is created on first use.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[writable, signer]` Program owner, payer of the batch auction account
+    ///   2. `[]` Global state account
+    ///   3. `[writable]` Batch auction, program address of BATCH_TAG and the swap
+    ///   4. `[]` token_a vault, owned by the batch auction address
+    ///   5. `[]` token_b vault, owned by the batch auction address
+    ///   6. `[]` System program
+    ///   7. `[]` Rent sysvar
+    SetBatchAuction(SetBatchAuction),
+
+    ///   Queue a swap in the batch auction of the pool. The first swap of a
+    ///   batch opens its window.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Batch auction
+    ///   2. `[writable]` Batch auction vault of the SOURCE token
+    ///   3. `[signer]` User transfer authority
+    ///   4. `[writable]` token_(A|B) SOURCE Account, refunded if the clearing price misses the limit
+    ///   5. `[]` token_(A|B) DESTINATION Account
+    ///   6. `[]` Token program id
+    SubmitBatchSwap(SubmitBatchSwap),
+
+    ///   Settle the batch of a pool once its window is over. Opposite swaps
+    ///   are crossed with each other, the imbalance is swapped through the
+    ///   pool with the fees of a regular swap, and every swap receives its
+    ///   share at the same price. Swaps whose limit the price misses are
+    ///   refunded. Anyone may settle.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[]` Global state account
+    ///   3. `[writable]` Batch auction
+    ///   4. `[writable]` token_a vault of the batch auction
+    ///   5. `[writable]` token_b vault of the batch auction
+    ///   6. `[writable]` token_a Base Account of the swap
+    ///   7. `[writable]` token_b Base Account of the swap
+    ///   8. `[writable]` Pool token mint
+    ///   9. `[writable]` token_a Fee account, to receive trading fees, a pool token account when the pool mints the owner fee as pool tokens
+    ///   10. `[writable]` token_b Fee account, likewise
+    ///   11. `[]` Token program id
+    ///   12. `[]` Price oracle, if the pool has one
+    ///   13. `[writable]` Protocol pool token account, only when the pool converts owner fees into protocol-owned liquidity
+    ///   14. `[writable]` token_a account of each enabled fee recipient, in the order of the program state
+    ///   15. `[writable]` token_b account of each enabled fee recipient, likewise
+    ///   16. `[writable]` SOURCE and DESTINATION token accounts of each queued swap, in order
+    SettleBatch,
+
+    ///   Mark a pool as holding rebasing or interest-bearing tokens, or
+    ///   unmark it. The reserves of a rebasing pool follow its vault
+    ///   balances, so balance changes outside of trades accrue to the
+    ///   liquidity providers instead of being skimmed. The reserves are set
+    ///   to the vault balances either way.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    ///   3. `[]` token_a Base Account of the swap
+    ///   4. `[]` token_b Base Account of the swap
+    SetRebasing(SetRebasing),
+
+    ///   Set the stake pool a rated stable pool reads the exchange rate of
+    ///   token A from, or the base pool a metapool reads the virtual price
+    ///   of token B from, and read the rate.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    ///   3. `[]` Stake pool, updated in the current epoch, or base Token-swap
+    ///   4. `[]` Pool mint, token_a and token_b Base Accounts of the base Token-swap, for a metapool
+    SetRateAccount,
+
+    ///   Refresh the exchange rate of a rated stable pool from its stake
+    ///   pool, once the stake pool is updated for the epoch, or the virtual
+    ///   price of a metapool from its base pool. Anyone may refresh the rate.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` Stake pool of the swap, updated in the current epoch, or base Token-swap
+    ///   2. `[]` Pool mint, token_a and token_b Base Accounts of the base Token-swap, for a metapool
+    ///   3. `[]` Global state account, only to be paid the keeper reward
+    ///   4. `[]` Protocol liquidity authority
+    ///   5. `[writable]` Protocol pool token account, paying the keeper reward
+    ///   6. `[writable]` Keeper pool token account
+    ///   7. `[]` Token program id
+    UpdateRate,
+
+    ///   Pay the trade fees of a pool earned by its LP positions out to them
+    ///   instead of compounding them into the reserves, or go back to
+    ///   compounding. Positions earn the share of each fee of their pool
+    ///   tokens in the supply, the rest compounding for the other pool
+    ///   tokens.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    ///   3. `[]` Pool token account owned by the swap authority, holding the pool tokens of the positions
+    SetClaimableFees(SetClaimableFees),
+
+    ///   Move pool tokens into the LP position of their owner, settling the
+    ///   fees earned so far. The position is created on first use.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[writable]` LP position, program address of the swap and owner
+    ///   2. `[writable]` Position vault of the swap
+    ///   3. `[writable, signer]` Position owner, paying for the position
+    ///   4. `[writable]` Pool token SOURCE account of the owner
+    ///   5. `[]` Token program id
+    ///   6. `[]` System program id
+    ///   7. `[]` Rent sysvar
+    ///   8. `[]` Position NFT account of the owner, only for NFT positions
+    IncreasePosition(IncreasePosition),
+
+    ///   Take pool tokens out of an LP position, settling the fees earned so
+    ///   far.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[writable]` LP position
+    ///   3. `[writable]` Position vault of the swap
+    ///   4. `[signer]` Position owner
+    ///   5. `[writable]` Pool token DESTINATION account
+    ///   6. `[]` Token program id
+    ///   7. `[]` Position NFT account of the owner, only for NFT positions
+    DecreasePosition(DecreasePosition),
+
+    ///   Pay out the trade fees earned by an LP position.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[writable]` LP position
+    ///   3. `[writable]` token_a Base Account of the swap
+    ///   4. `[writable]` token_b Base Account of the swap
+    ///   5. `[signer]` Position owner
+    ///   6. `[writable]` token_a DESTINATION account
+    ///   7. `[writable]` token_b DESTINATION account
+    ///   8. `[]` Token program id
+    ///   9. `[]` Position NFT account of the owner, only for NFT positions
+    ClaimFees,
+
+    ///   Have the deposits into a pool with claimable fees open a position
+    ///   owned by a new NFT, instead of minting pool tokens to the
+    ///   depositor, or go back to pool tokens. Whoever holds the NFT owns
+    ///   the position.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetNftPositions(SetNftPositions),
+
+    ///   Set the guardian, a key that may pause swaps and deposits in every
+    ///   pool or in one of them but not unpause them, change fees or owners
+    ///   or move funds.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner
+    SetGuardian(SetGuardian),
+
+    ///   Pause swaps and deposits in every pool, or resume them. The
+    ///   guardian may pause, only the program owner may resume. Withdrawals
+    ///   stay open.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner or guardian
+    SetPaused(SetPaused),
+
+    ///   Pause swaps and deposits in a pool, or resume them. The guardian
+    ///   may pause, only the program owner may resume. Withdrawals stay
+    ///   open.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner or guardian
+    ///   2. `[]` Global state account
+    SetPoolPaused(SetPaused),
+
+    ///   Set or remove the cap on the token A volume the swaps of a single
+    ///   slot may trade through the pool.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetSlotVolumeCap(SetSlotVolumeCap),
+
+    ///   Set or remove the circuit breaker of a pool, failing any trade that
+    ///   would move its price more than the given basis points from the
+    ///   oracle price at the start of a window of slots.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetCircuitBreaker(SetCircuitBreaker),
+
+    ///   Resume the swaps of a pool halted by its circuit breaker, starting
+    ///   a new window at the next trade. Pools are halted only when their
+    ///   breaker tripped before trades moving the price too far failed.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner or guardian
+    ///   2. `[]` Global state account
+    ResetCircuitBreaker,
+
+    ///   Deposit a single token: swap the share of it that balances the
+    ///   rest against the reserves for the other token, then deposit both.
+    ///   What the deposit cannot use stays in the user's accounts, and only
+    ///   the pool tokens minted are checked against the slippage limit.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` SOURCE token account, of token A or B
+    ///   5. `[writable]` Token account of the other token, credited by the swap and debited by the deposit
+    ///   6. `[writable]` token_a Base Account of the swap
+    ///   7. `[writable]` token_b Base Account of the swap
+    ///   8. `[writable]` Pool MINT account
+    ///   9. `[writable]` Fee account of the SOURCE token
+    ///   10. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   11. `[]` Token program id
+    ///   12. `[]` Price oracle, gate and protocol pool token accounts, when the pool has them
+    ///   13. `[writable]` SOURCE token account of each enabled fee recipient, in the order of the program state
+    ///   14. `[writable]` Deposit record and NFT position accounts of the deposit, when the pool has them
+    ZapIn(ZapIn),
+
+    ///   Withdraw a single token: burn pool tokens for both tokens, then
+    ///   swap all of the other token withdrawn into the DESTINATION token.
+    ///   Only the total credited to DESTINATION is checked against the
+    ///   slippage limit.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` swap authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` Pool mint account, swap authority is the owner
+    ///   5. `[writable]` SOURCE Pool account, amount is transferable by user transfer authority.
+    ///   6. `[writable]` token_a Base Account of the swap
+    ///   7. `[writable]` token_b Base Account of the swap
+    ///   8. `[writable]` DESTINATION token account, of token A or B
+    ///   9. `[writable]` Token account of the other token, credited by the withdrawal and debited by the swap
+    ///   10. `[writable]` Fee account of the other token
+    ///   11. `[]` Token program id
+    ///   12. `[writable]` Pool token account of the fee owner and deposit record of the withdrawal, when the pool has them
+    ///   13. `[]` Price oracle, gate and protocol pool token accounts, when the pool has them
+    ///   14. `[writable]` Other token account of each enabled fee recipient, in the order of the program state
+    ZapOut(ZapOut),
+
+    ///   Set the fee owner of a pool's swaps, in place of the one of the
+    ///   program state.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetPoolFeeOwner(SetPoolFeeOwner),
+
+    ///   Set whether the swaps of a pool leave the fee owner's cut of the
+    ///   owner fee in the pool and mint it to the fee owner as pool tokens,
+    ///   instead of transferring it in the input token.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[signer]` Program owner
+    ///   2. `[]` Global state account
+    SetOwnerFeeInPoolTokens(SetOwnerFeeInPoolTokens),
+
+    ///   Set the reward paid out of protocol-owned liquidity to the keepers
+    ///   of permissionless cranks, and how often a pool's cranks may earn
+    ///   it.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner
+    SetKeeperReward(SetKeeperReward),
+
+    ///   Withdraw both tokens from an spl-token-swap pool and deposit them
+    ///   into a pool of the same tokens, for the most pool tokens they pay
+    ///   for. Tokens left over by the deposit stay in the token accounts.
+    ///
+    ///   0. `[]` spl-token-swap program id
+    ///   1. `[]` spl-token-swap Token-swap
+    ///   2. `[]` spl-token-swap swap authority
+    ///   3. `[writable]` spl-token-swap Pool mint account
+    ///   4. `[writable]` SOURCE spl-token-swap Pool account, amount is transferable by user transfer authority.
+    ///   5. `[writable]` spl-token-swap token_a Swap Account to withdraw FROM.
+    ///   6. `[writable]` spl-token-swap token_b Swap Account to withdraw FROM.
+    ///   7. `[writable]` spl-token-swap Fee account, to receive withdrawal fees
+    ///   8. `[writable]` Token-swap
+    ///   9. `[]` swap authority
+    ///   10. `[signer]` user transfer authority
+    ///   11. `[]` Global state account
+    ///   12. `[writable]` token_a user Account, credited by the withdrawal and debited by the deposit
+    ///   13. `[writable]` token_b user Account, credited by the withdrawal and debited by the deposit
+    ///   14. `[writable]` token_a Base Account to deposit into.
+    ///   15. `[writable]` token_b Base Account to deposit into.
+    ///   16. `[writable]` Pool MINT account, swap authority is the owner.
+    ///   17. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   18. `[]` Token program id
+    ///   19. `[]` Gate token account or allowlist entry and deposit record, as for DepositAllTokenTypes
+    MigrateFromSplTokenSwap(MigrateFromSplTokenSwap),
+
+    ///   Initializes a basket pool of two to MAX_BASKET_ASSETS assets under
+    ///   the generalized constant product invariant, the product of all
+    ///   reserves.
+    ///
+    ///   0. `[writable, signer]` New basket pool to create.
+    ///   1. `[]` basket authority derived from `create_program_address(&[basket account])`
+    ///   2. `[writable]` Global state account, counting the pool
+    ///   3. `[writable]` Pool Token Mint. Must be empty, owned by basket authority.
+    ///   4. `[writable]` Pool Token Account to deposit the initial pool token
+    ///      supply, less MIN_LP_SUPPLY.  Must be empty, not owned by basket authority.
+    ///   5. `[]` Token program id
+    ///   6. `[writable]` Pool Token Account owned by basket authority to lock MIN_LP_SUPPLY in
+    ///   7. `[]` Vault of each asset, in order. Must be non zero, owned by basket authority.
+    InitializeBasket,
+
+    ///   Swap one asset of a basket pool for another, at the fees of a
+    ///   constant product pool.
+    ///
+    ///   0. `[writable]` Basket pool
+    ///   1. `[]` basket authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` token_(A|B|C|D) SOURCE Account, amount is transferable by user transfer authority,
+    ///   5. `[writable]` token_(A|B|C|D) Vault to swap INTO.  Must be the SOURCE token.
+    ///   6. `[writable]` token_(A|B|C|D) Vault to swap FROM.  Must be the DESTINATION token.
+    ///   7. `[writable]` token_(A|B|C|D) DESTINATION Account assigned to USER as the owner.
+    ///   8. `[writable]` Fee account of the SOURCE token, owned by the fee owner
+    ///   9. `[]` Token program id
+    SwapBasket(SwapBasket),
+
+    ///   Deposit every asset of a basket pool, in proportion to its
+    ///   reserves, for pool tokens.
+    ///
+    ///   0. `[writable]` Basket pool
+    ///   1. `[]` basket authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` Pool MINT account, basket authority is the owner.
+    ///   5. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   6. `[]` Token program id
+    ///   7. `[writable]` For each asset, in order, the SOURCE account of the user, amount is transferable by user transfer authority,
+    ///      then the Vault to deposit into.
+    DepositBasket(DepositBasket),
+
+    ///   Withdraw every asset of a basket pool, in proportion to its
+    ///   reserves, by burning pool tokens.
+    ///
+    ///   0. `[writable]` Basket pool
+    ///   1. `[]` basket authority
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[writable]` Pool mint account, basket authority is the owner
+    ///   4. `[writable]` SOURCE Pool account, amount is transferable by user transfer authority.
+    ///   5. `[]` Token program id
+    ///   6. `[writable]` For each asset, in order, the Vault to withdraw FROM, then the
+    ///      DESTINATION account of the user.
+    WithdrawBasket(WithdrawBasket),
+
+    ///   Close an LP position holding no pool tokens and owing no fees. Its
+    ///   rent goes back to the account that paid it, unless that account
+    ///   is the signing owner, who may send it elsewhere.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` LP position
+    ///   2. `[signer]` Position owner
+    ///   3. `[writable]` Rent recipient
+    ///   4. `[]` Position NFT account of the owner, only for NFT positions
+    ClosePosition,
+
+    ///   Return the token A and B amounts a WithdrawAllTokenTypes of
+    ///   `pool_token_amount` would transfer out, each a little-endian u64 in
+    ///   the return data. The amounts are capped, rounded and swept of dust
+    ///   exactly as the withdrawal does, its fee taken out.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Global state account
+    ///   2. `[]` Pool token mint
+    ///   3. `[]` token_a Swap Account
+    ///   4. `[]` token_b Swap Account
+    QuoteWithdraw(QuoteWithdraw),
+
+    ///   Move liquidity between two pools of the same tokens: burn pool
+    ///   tokens of the first for both tokens, optionally swap the excess of
+    ///   one of them against the reserves of the second, then deposit into
+    ///   the second. Tokens left over by the deposit stay in the token
+    ///   accounts, and only the pool tokens minted are checked against the
+    ///   slippage limit.
+    ///
+    ///   0. `[writable]` Token-swap to withdraw from
+    ///   1. `[]` swap authority of the pool to withdraw from
+    ///   2. `[signer]` user transfer authority
+    ///   3. `[]` Global state account
+    ///   4. `[writable]` Pool mint account of the pool to withdraw from
+    ///   5. `[writable]` SOURCE Pool account, amount is transferable by user transfer authority.
+    ///   6. `[writable]` token_a Base Account to withdraw FROM.
+    ///   7. `[writable]` token_b Base Account to withdraw FROM.
+    ///   8. `[writable]` token_a user Account, credited by the withdrawal and debited by the deposit
+    ///   9. `[writable]` token_b user Account, credited by the withdrawal and debited by the deposit
+    ///   10. `[writable]` Token-swap to deposit into
+    ///   11. `[]` swap authority of the pool to deposit into
+    ///   12. `[writable]` token_a Base Account to deposit into.
+    ///   13. `[writable]` token_b Base Account to deposit into.
+    ///   14. `[writable]` Pool MINT account of the pool to deposit into
+    ///   15. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   16. `[]` Token program id
+    ///   17. `[writable]` Pool token account of the fee owner and deposit record of the withdrawal, when the pool to withdraw from has them
+    ///   18. `[writable]` Fee account of the token in excess, only to swap the imbalance
+    ///   19. `[]` Price oracle, gate and protocol pool token accounts of the pool to deposit into, when it has them, all but the gate only to swap the imbalance
+    ///   20. `[writable]` Token account of each enabled fee recipient, in the order of the program state, only to swap the imbalance
+    ///   21. `[writable]` Deposit record and NFT position accounts of the deposit, when the pool to deposit into has them
+    MigrateLiquidity(MigrateLiquidity),
+
+    ///   Hand the fees and fee discount of the program over to an
+    ///   spl-governance governance, after which only its executed proposals
+    ///   may change them through SetGovernedFees, or back to the program
+    ///   owner. The governance is the program address derived by the
+    ///   governance program from the realm and the governance seed.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner, or the current governance once the fees are governed
+    ///   2. `[writable]` Fee governance account, derived from `[FEE_GOVERNANCE_TAG, program_id]`
+    ///   3. `[]` Realm, owned by the governance program, unchecked when handing the fees back
+    ///   4. `[writable, signer]` Payer of the fee governance account
+    ///   5. `[]` System program
+    ///   6. `[]` Rent sysvar
+    SetFeeGovernance(SetFeeGovernance),
+
+    ///   Set the fees and fee discount of the program from an executed
+    ///   proposal of the fee governance.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[]` Fee governance account
+    ///   2. `[signer]` Governance of the fee governance account
+    SetGovernedFees(SetGovernedFees),
+
+    ///   Stage an admin action in the scheduler, to be executed by anyone
+    ///   from its ETA unless the owner cancels it first. Several actions
+    ///   may be pending at once, each with its own ETA.
+    ///
+    ///   0. `[]` Global state account
+    ///   1. `[writable, signer]` Program owner, paying for the scheduler account
+    ///   2. `[writable]` Scheduler account, derived from `[SCHEDULER_TAG, program_id]`
+    ///   3. `[]` System program
+    ///   4. `[]` Rent sysvar
+    SchedulePendingAction(SchedulePendingAction),
+
+    ///   Apply a pending action whose ETA has passed and remove it from the
+    ///   scheduler.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[writable]` Scheduler account
+    ///   2. `[writable]` Token-swap, only for an amp change
+    ExecutePendingAction(ExecutePendingAction),
+
+    ///   Remove a pending action from the scheduler without applying it.
+    ///
+    ///   0. `[]` Global state account
+    ///   1. `[signer]` Program owner
+    ///   2. `[writable]` Scheduler account
+    CancelPendingAction(CancelPendingAction),
+
+    ///   Seed the pool count of the global state with the pools created
+    ///   before it was counted, fixing the initial supply and lp decimals.
+    ///   Only allowed while the count is zero.
+    ///
+    ///   0. `[writable]` Global state account
+    ///   1. `[signer]` Program owner
+    SetPoolCount(SetPoolCount),
+
+    ///   Claim the tokens of the batch swaps of an owner that settlement
+    ///   could not pay to their accounts, closed or frozen since the swaps
+    ///   were queued, from one vault of the batch auction.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Batch auction
+    ///   2. `[writable]` token_(A|B) vault of the batch auction to claim from
+    ///   3. `[signer]` Owner of the SOURCE accounts of the swaps
+    ///   4. `[writable]` token_(A|B) Account credited with the claimed tokens
+    ///   5. `[]` Token program id
+    ClaimBatchOrders,
+
+    ///   Migrate a SwapV1 pool to the SwapV2 layout in place, growing its
+    ///   account and topping up its rent from the payer. The tracked
+    ///   reserves and the oracle start from the balances of the vaults.
+    ///
+    ///   0. `[writable]` Token-swap
+    ///   1. `[]` token_a Base Account of the swap
+    ///   2. `[]` token_b Base Account of the swap
+    ///   3. `[writable, signer]` Payer of the rent top-up
+    ///   4. `[]` System program
+    ///   5. `[]` Rent sysvar
+    MigratePoolState,
+}
+
+impl SwapInstruction {
+    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
+    ///
+    /// The buffer starts with the instruction tag, or with a version header
+    /// before it. Optional trailing fields may be left out in any version.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        debug_msg!("unpack instruction");
+        let (version, input) = Self::unpack_version(input)?;
+        match version {
+            // later versions only add optional trailing fields to the
+            // unversioned layouts, which read every field added so far
+            0..=INSTRUCTION_VERSION => {}
+            // newer versions may carry fields this program would ignore
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        }
+        let (&tag, rest) = input.split_first().ok_or(SwapError::InvalidInstruction)?;
+        debug_msg!("unpack instruction tag {}", tag);
+        Ok(match tag {
+            0 => {
+                debug_msg!("unpack instruction rest.len() {}", rest.len());
+                if rest.len() < SwapCurve::LEN {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let (swap_curve, rest) = rest.split_at(SwapCurve::LEN);
+                let swap_curve = SwapCurve::unpack_unchecked(swap_curve)?;
+                let fee_owner = if rest.is_empty() {
+                    None
+                } else {
+                    let (fee_owner, rest) = Self::unpack_pubkey(rest)?;
+                    Self::unpack_end(rest)?;
+                    Some(fee_owner)
+                };
+                // if rest.len() == 1 {
+                    Self::Initialize(Initialize {
+                        swap_curve,
+                        fee_owner,
+                    })
+                // } else {
+                //     return Err(SwapError::InvalidInstruction.into());
+                // }
+            }
+            1 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                // an unbounded price impact is written as u64::MAX when
+                // followed by a tip
+                let (max_price_impact_bps, rest) = Self::unpack_optional_u64(rest)?;
+                let (tip, rest) = Self::unpack_optional_u64(rest)?;
+                let (memo, rest) = Self::unpack_memo(rest)?;
+                // no trade direction is written as 2 when followed by the fee
+                // discount flag
+                let (trade_direction, fee_discount) = match rest {
+                    [] => (None, false),
+                    [0] => (Some(TradeDirection::AtoB), false),
+                    [1] => (Some(TradeDirection::BtoA), false),
+                    [0, 1] => (Some(TradeDirection::AtoB), true),
+                    [1, 1] => (Some(TradeDirection::BtoA), true),
+                    [2, 1] => (None, true),
+                    _ => return Err(SwapError::InvalidInstruction.into()),
+                };
+                Self::Swap(Swap {
+                    amount_in,
+                    minimum_amount_out,
+                    max_price_impact_bps: max_price_impact_bps.filter(|bps| *bps != u64::MAX),
+                    tip: tip.unwrap_or(0),
+                    memo,
+                    trade_direction,
+                    fee_discount,
+                })
+            }
+            2 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (maximum_token_a_amount, rest) = Self::unpack_u64(rest)?;
+                let (maximum_token_b_amount, rest) = Self::unpack_u64(rest)?;
+                let (max_ratio_deviation_bps, rest) = Self::unpack_optional_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::DepositAllTokenTypes(DepositAllTokenTypes {
+                    pool_token_amount,
+                    maximum_token_a_amount,
+                    maximum_token_b_amount,
+                    max_ratio_deviation_bps,
+                })
+            }
+            3 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_token_a_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_token_b_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::WithdrawAllTokenTypes(WithdrawAllTokenTypes {
+                    pool_token_amount,
+                    minimum_token_a_amount,
+                    minimum_token_b_amount,
+                })
+            }
+            4 => {// Upgrade Program State
+                let (owner, rest) = Self::unpack_pubkey(rest)?;
+                let (fee_owner, rest) = Self::unpack_pubkey(rest)?;
+
+                let (initial_supply, rest) = Self::unpack_u64(rest)?;
+                let (&lp_decimals, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                if rest.len() == Fees::LEN + FeeDiscount::LEN + 32 {
+                    let (fees, rest) = rest.split_at(Fees::LEN);
+                    let fees = Fees::unpack_unchecked(fees)?;
+                    let (fee_discount, rest) = rest.split_at(FeeDiscount::LEN);
+                    let fee_discount = FeeDiscount::unpack_unchecked(fee_discount)?;
+                    let (quoter, _rest) = rest.split_at(32);
+                    let quoter = Pubkey::new_from_array(
+                        quoter.try_into().map_err(|_| SwapError::InvalidInstruction)?,
+                    );
+                    Self::SetGlobalStateInstruction(SetGlobalState {
+                        owner,
+                        fee_owner,
+                        initial_supply,
+                        lp_decimals,
+                        fees,
+                        fee_discount,
+                        quoter,
+                    })
+                } else {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+            }
+            5 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (unlock_timestamp, rest) = Self::unpack_i64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::LockLiquidity(LockLiquidity {
+                    amount,
+                    unlock_timestamp,
+                })
+            }
+            6 => Self::unpack_unit(rest, Self::UnlockLiquidity)?,
+            7 => {
+                let (reward_per_slot, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::InitializeStakePool(InitializeStakePool { reward_per_slot })
+            }
+            8 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::Stake(Stake { amount })
+            }
+            9 => {
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::Unstake(Unstake { amount })
+            }
+            10 => Self::unpack_unit(rest, Self::ClaimRewards)?,
+            11 => Self::unpack_unit(rest, Self::Sync)?,
+            12 => Self::unpack_unit(rest, Self::Skim)?,
+            13 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::Rebalance(Rebalance {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            14 => {
+                let (max_deviation_bps, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetPoolOracle(SetPoolOracle { max_deviation_bps })
+            }
+            15 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                let (first_pool_bps, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SplitSwap(SplitSwap {
+                    amount_in,
+                    minimum_amount_out,
+                    first_pool_bps,
+                })
+            }
+            16 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (amount_out, rest) = Self::unpack_u64(rest)?;
+                let (expiry, rest) = Self::unpack_i64(rest)?;
+                let (nonce, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SwapWithQuote(SwapWithQuote {
+                    amount_in,
+                    amount_out,
+                    expiry,
+                    nonce,
+                })
+            }
+            17 => {
+                let (order_id, rest) = Self::unpack_u64(rest)?;
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                let (keeper_fee, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::PlaceLimitOrder(PlaceLimitOrder {
+                    order_id,
+                    amount_in,
+                    minimum_amount_out,
+                    keeper_fee,
+                })
+            }
+            18 => Self::unpack_unit(rest, Self::CancelLimitOrder)?,
+            19 => Self::unpack_unit(rest, Self::FillLimitOrder)?,
+            20 => {
+                let (dca_id, rest) = Self::unpack_u64(rest)?;
+                let (amount, rest) = Self::unpack_u64(rest)?;
+                let (amount_per_tick, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out_per_tick, rest) = Self::unpack_u64(rest)?;
+                let (keeper_fee, rest) = Self::unpack_u64(rest)?;
+                let (interval, rest) = Self::unpack_i64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::CreateDca(CreateDca {
+                    dca_id,
+                    amount,
+                    amount_per_tick,
+                    minimum_amount_out_per_tick,
+                    keeper_fee,
+                    interval,
+                })
+            }
+            21 => Self::unpack_unit(rest, Self::ExecuteDcaTick)?,
+            22 => Self::unpack_unit(rest, Self::CloseDca)?,
+            23 => Self::unpack_unit(rest, Self::GetVirtualPrice)?,
+            24 => Self::unpack_unit(rest, Self::GetPoolState)?,
+            25 => Self::unpack_unit(rest, Self::MigrateGlobalState)?,
+            26 => {
+                let swap_curve = SwapCurve::unpack_unchecked(rest)?;
+                Self::UpdateCurveParameters(UpdateCurveParameters { swap_curve })
+            }
+            27 => Self::unpack_unit(rest, Self::DeprecatePool)?,
+            28 => {
+                let (max_lp_supply, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetDepositCap(SetDepositCap { max_lp_supply })
+            }
+            29 => {
+                let (&gated, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                let (gate_mint, rest) = Self::unpack_pubkey(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetPoolGate(SetPoolGate {
+                    gated: gated != 0,
+                    gate_mint,
+                })
+            }
+            30 => {
+                let (&allowed, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetAllowlisted(SetAllowlisted {
+                    allowed: allowed != 0,
+                })
+            }
+            31 => {
+                let (withdraw_fee_numerator, rest) = Self::unpack_u64(rest)?;
+                let (&burn, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetWithdrawFee(SetWithdrawFee {
+                    withdraw_fee_numerator,
+                    burn: burn != 0,
+                })
+            }
+            32 => {
+                let (min_hold_slots, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetMinHoldPeriod(SetMinHoldPeriod { min_hold_slots })
+            }
+            33 => Self::unpack_unit(rest, Self::InitDepositRecord)?,
+            34 => {
+                let (protocol_liquidity_bps, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetProtocolLiquidityShare(SetProtocolLiquidityShare {
+                    protocol_liquidity_bps,
+                })
+            }
+            35 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::WithdrawProtocolLiquidity(WithdrawProtocolLiquidity { pool_token_amount })
+            }
+            36 => {
+                let (buyback_mint, rest) = Self::unpack_pubkey(rest)?;
+                let (buyback_epoch_limit, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetBuybackConfig(SetBuybackConfig {
+                    buyback_mint,
+                    buyback_epoch_limit,
+                })
+            }
+            37 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::BuybackAndBurn(BuybackAndBurn {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            38 => {
+                if rest.len() != FeeRecipient::LEN * FEE_RECIPIENT_COUNT {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let mut fee_recipients = [FeeRecipient::default(); FEE_RECIPIENT_COUNT];
+                for (fee_recipient, input) in fee_recipients
+                    .iter_mut()
+                    .zip(rest.chunks_exact(FeeRecipient::LEN))
+                {
+                    *fee_recipient = FeeRecipient::unpack_unchecked(input)?;
+                }
+                Self::SetFeeRecipients(SetFeeRecipients { fee_recipients })
+            }
+            39 => Self::unpack_unit(rest, Self::RecoverForeignTokens)?,
+            40 => {
+                let (hash, rest) = Self::unpack_hash(rest)?;
+                Self::unpack_end(rest)?;
+                Self::CommitSwap(CommitSwap { hash })
+            }
+            41 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                let (salt, rest) = Self::unpack_hash(rest)?;
+                Self::unpack_end(rest)?;
+                Self::RevealSwap(RevealSwap {
+                    amount_in,
+                    minimum_amount_out,
+                    salt,
+                })
+            }
+            42 => {
+                let (batch_window_slots, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetBatchAuction(SetBatchAuction { batch_window_slots })
+            }
+            43 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SubmitBatchSwap(SubmitBatchSwap {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            44 => Self::unpack_unit(rest, Self::SettleBatch)?,
+            45 => {
+                let (&rebasing, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetRebasing(SetRebasing {
+                    rebasing: rebasing != 0,
+                })
+            }
+            46 => Self::unpack_unit(rest, Self::SetRateAccount)?,
+            47 => Self::unpack_unit(rest, Self::UpdateRate)?,
+            48 => {
+                let (&claimable_fees, rest) =
+                    rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetClaimableFees(SetClaimableFees {
+                    claimable_fees: claimable_fees != 0,
+                })
+            }
+            49 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::IncreasePosition(IncreasePosition { pool_token_amount })
+            }
+            50 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::DecreasePosition(DecreasePosition { pool_token_amount })
+            }
+            51 => Self::unpack_unit(rest, Self::ClaimFees)?,
+            52 => {
+                let (&nft_positions, rest) =
+                    rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetNftPositions(SetNftPositions {
+                    nft_positions: nft_positions != 0,
+                })
+            }
+            53 => {
+                let (guardian, rest) = Self::unpack_pubkey(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetGuardian(SetGuardian { guardian })
+            }
+            54 => {
+                let (&paused, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetPaused(SetPaused {
+                    paused: paused != 0,
+                })
+            }
+            55 => {
+                let (&paused, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetPoolPaused(SetPaused {
+                    paused: paused != 0,
+                })
+            }
+            56 => {
+                let (max_slot_volume, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetSlotVolumeCap(SetSlotVolumeCap { max_slot_volume })
+            }
+            57 => {
+                let (breaker_move_bps, rest) = Self::unpack_u64(rest)?;
+                let (breaker_window_slots, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetCircuitBreaker(SetCircuitBreaker {
+                    breaker_move_bps,
+                    breaker_window_slots,
+                })
+            }
+            58 => Self::unpack_unit(rest, Self::ResetCircuitBreaker)?,
+            59 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::ZapIn(ZapIn {
+                    amount_in,
+                    minimum_pool_token_amount,
+                })
+            }
+            60 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::ZapOut(ZapOut {
+                    pool_token_amount,
+                    minimum_amount_out,
+                })
+            }
+            61 => {
+                let (fee_owner, rest) = Self::unpack_pubkey(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetPoolFeeOwner(SetPoolFeeOwner { fee_owner })
+            }
+            62 => {
+                let (&owner_fee_in_pool_tokens, rest) =
+                    rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::SetOwnerFeeInPoolTokens(SetOwnerFeeInPoolTokens {
+                    owner_fee_in_pool_tokens: owner_fee_in_pool_tokens != 0,
+                })
+            }
+            63 => {
+                let (keeper_reward, rest) = Self::unpack_u64(rest)?;
+                let (keeper_interval_slots, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetKeeperReward(SetKeeperReward {
+                    keeper_reward,
+                    keeper_interval_slots,
+                })
+            }
+            64 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::MigrateFromSplTokenSwap(MigrateFromSplTokenSwap {
+                    pool_token_amount,
+                    minimum_pool_token_amount,
+                })
+            }
+            65 => Self::unpack_unit(rest, Self::InitializeBasket)?,
+            66 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SwapBasket(SwapBasket {
+                    amount_in,
+                    minimum_amount_out,
+                })
+            }
+            67 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (maximum_token_amounts, rest) = Self::unpack_basket_amounts(rest)?;
+                Self::unpack_end(rest)?;
+                Self::DepositBasket(DepositBasket {
+                    pool_token_amount,
+                    maximum_token_amounts,
+                })
+            }
+            68 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_token_amounts, rest) = Self::unpack_basket_amounts(rest)?;
+                Self::unpack_end(rest)?;
+                Self::WithdrawBasket(WithdrawBasket {
+                    pool_token_amount,
+                    minimum_token_amounts,
+                })
+            }
+            69 => Self::unpack_unit(rest, Self::ClosePosition)?,
+            70 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::QuoteWithdraw(QuoteWithdraw { pool_token_amount })
+            }
+            71 => {
+                let (pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (minimum_pool_token_amount, rest) = Self::unpack_u64(rest)?;
+                let (&swap_imbalance, rest) =
+                    rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                Self::unpack_end(rest)?;
+                Self::MigrateLiquidity(MigrateLiquidity {
+                    pool_token_amount,
+                    minimum_pool_token_amount,
+                    swap_imbalance: swap_imbalance != 0,
+                })
+            }
+            72 => {
+                let (governance_program_id, rest) = Self::unpack_pubkey(rest)?;
+                let (realm, rest) = Self::unpack_pubkey(rest)?;
+                let (governance_seed, rest) = Self::unpack_pubkey(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetFeeGovernance(SetFeeGovernance {
+                    governance_program_id,
+                    realm,
+                    governance_seed,
+                })
+            }
+            73 => {
+                if rest.len() != Fees::LEN + FeeDiscount::LEN {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let (fees, rest) = rest.split_at(Fees::LEN);
+                let (fee_discount, _rest) = rest.split_at(FeeDiscount::LEN);
+                Self::SetGovernedFees(SetGovernedFees {
+                    fees: Fees::unpack_unchecked(fees)?,
+                    fee_discount: FeeDiscount::unpack_unchecked(fee_discount)?,
+                })
+            }
+            74 => {
+                let (eta, rest) = Self::unpack_i64(rest)?;
+                if rest.len() != AdminAction::LEN {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let action = AdminAction::unpack_from_slice(rest)
+                    .map_err(|_| SwapError::InvalidInstruction)?;
+                Self::SchedulePendingAction(SchedulePendingAction { eta, action })
+            }
+            75 => {
+                let (action_id, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::ExecutePendingAction(ExecutePendingAction { action_id })
+            }
+            76 => {
+                let (action_id, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::CancelPendingAction(CancelPendingAction { action_id })
+            }
+            77 => {
+                let (pool_count, rest) = Self::unpack_u64(rest)?;
+                Self::unpack_end(rest)?;
+                Self::SetPoolCount(SetPoolCount { pool_count })
+            }
+            78 => Self::unpack_unit(rest, Self::ClaimBatchOrders)?,
+            79 => Self::unpack_unit(rest, Self::MigratePoolState)?,
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        })
+    }
+
+    /// Version of instruction data and the data after its version header,
+    /// version 0 for data without one. A header always names a version
+    /// from 1 on.
+    pub fn unpack_version(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
+        match input.split_first() {
+            Some((&INSTRUCTION_VERSION_MARKER, rest)) => {
+                let (&version, rest) = rest.split_first().ok_or(SwapError::InvalidInstruction)?;
+                if version == 0 {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                Ok((version, rest))
+            }
+            _ => Ok((0, input)),
+        }
+    }
+
+    /// Optional trailing u64, None when the data ends before it
+    fn unpack_optional_u64(input: &[u8]) -> Result<(Option<u64>, &[u8]), ProgramError> {
+        if input.is_empty() {
+            Ok((None, input))
+        } else {
+            let (value, rest) = Self::unpack_u64(input)?;
+            Ok((Some(value), rest))
+        }
+    }
+
+    /// Optional trailing memo, a length byte followed by that many bytes,
+    /// empty when the data ends before it or its length is zero
+    fn unpack_memo(input: &[u8]) -> Result<(Vec<u8>, &[u8]), ProgramError> {
+        match input.split_first() {
+            None => Ok((Vec::new(), input)),
+            Some((&len, rest)) => {
+                let len = usize::from(len);
+                if len > MAX_MEMO_LEN || rest.len() < len {
+                    return Err(SwapError::InvalidInstruction.into());
+                }
+                let (memo, rest) = rest.split_at(len);
+                Ok((memo.to_vec(), rest))
+            }
+        }
+    }
+
+    /// Fails on data left over after the last field of an instruction
+    fn unpack_end(input: &[u8]) -> Result<(), ProgramError> {
+        if input.is_empty() {
+            Ok(())
+        } else {
+            Err(SwapError::InvalidInstruction.into())
+        }
+    }
+
+    /// An instruction without fields, failing on any data after its tag
+    fn unpack_unit(input: &[u8], instruction: Self) -> Result<Self, ProgramError> {
+        Self::unpack_end(input)?;
+        Ok(instruction)
+    }
+
+    fn unpack_hash(input: &[u8]) -> Result<([u8; 32], &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (hash, rest) = input.split_at(32);
+            let hash = hash.try_into().map_err(|_| SwapError::InvalidInstruction)?;
+            Ok((hash, rest))
+        } else {
+            Err(SwapError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+        if input.len() >= 32 {
+            let (key, rest) = input.split_at(32);
+            let key = key
+                .try_into()
+                .map(Pubkey::new_from_array)
+                .map_err(|_| SwapError::InvalidInstruction)?;
+            Ok((key, rest))
+        } else {
+            Err(SwapError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (amount, rest) = input.split_at(8);
+            let amount = amount
+                .get(..8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(SwapError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(SwapError::InvalidInstruction.into())
+        }
+    }
+
+    fn unpack_basket_amounts(input: &[u8]) -> Result<([u64; MAX_BASKET_ASSETS], &[u8]), ProgramError> {
+        let mut amounts = [0u64; MAX_BASKET_ASSETS];
+        let mut rest = input;
+        for amount in amounts.iter_mut() {
+            let (value, next) = Self::unpack_u64(rest)?;
+            *amount = value;
+            rest = next;
+        }
+        Ok((amounts, rest))
+    }
+
+    fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+        let (value, rest) = Self::unpack_u64(input)?;
+        Ok((value as i64, rest))
+    }
+
+    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte
+    /// buffer behind a version header. Programs from before versioning only
+    /// take the unversioned data of `pack`.
+    pub fn pack_versioned(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut buf = vec![INSTRUCTION_VERSION_MARKER, INSTRUCTION_VERSION];
+        buf.extend_from_slice(&self.pack()?);
+        Ok(buf)
+    }
+
+    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
+    /// Fails on a swap memo longer than MAX_MEMO_LEN, which the length byte
+    /// before it could not hold.
+    pub fn pack(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match &*self {
+            Self::Initialize(Initialize {
+                swap_curve,
+                fee_owner,
+            }) => {
+                buf.push(0);
+                let mut swap_curve_slice = [0u8; SwapCurve::LEN];
+                Pack::pack_into_slice(swap_curve, &mut swap_curve_slice[..]);
+                buf.extend_from_slice(&swap_curve_slice);
+                if let Some(fee_owner) = fee_owner {
+                    buf.extend_from_slice(fee_owner.as_ref());
+                }
+            }
+            Self::Swap(Swap {
+                amount_in,
+                minimum_amount_out,
+                max_price_impact_bps,
+                tip,
+                memo,
+                trade_direction,
+                fee_discount,
+            }) => {
+                buf.push(1);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                if *tip > 0 || !memo.is_empty() || trade_direction.is_some() || *fee_discount {
+                    buf.extend_from_slice(&max_price_impact_bps.unwrap_or(u64::MAX).to_le_bytes());
+                    buf.extend_from_slice(&tip.to_le_bytes());
+                } else if let Some(max_price_impact_bps) = max_price_impact_bps {
+                    buf.extend_from_slice(&max_price_impact_bps.to_le_bytes());
+                }
+                if !memo.is_empty() || trade_direction.is_some() || *fee_discount {
+                    if memo.len() > MAX_MEMO_LEN {
+                        return Err(SwapError::InvalidInstruction.into());
+                    }
+                    buf.push(memo.len() as u8);
+                    buf.extend_from_slice(memo);
+                }
+                match trade_direction {
+                    Some(TradeDirection::AtoB) => buf.push(0),
+                    Some(TradeDirection::BtoA) => buf.push(1),
+                    None if *fee_discount => buf.push(2),
+                    None => {}
+                }
+                if *fee_discount {
+                    buf.push(1);
+                }
+            }
+            Self::DepositAllTokenTypes(DepositAllTokenTypes {
+                pool_token_amount,
+                maximum_token_a_amount,
+                maximum_token_b_amount,
+                max_ratio_deviation_bps,
+            }) => {
+                buf.push(2);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&maximum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&maximum_token_b_amount.to_le_bytes());
+                if let Some(max_ratio_deviation_bps) = max_ratio_deviation_bps {
+                    buf.extend_from_slice(&max_ratio_deviation_bps.to_le_bytes());
+                }
+            }
+            Self::WithdrawAllTokenTypes(WithdrawAllTokenTypes {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+            }) => {
+                buf.push(3);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+            }
+            Self::SetGlobalStateInstruction(SetGlobalState {
+                owner,
+                fee_owner,
+                initial_supply,
+                lp_decimals,
+                fees,
+                fee_discount,
+                quoter,
+            }) => {
+                buf.push(4);
+                buf.extend_from_slice(owner.as_ref());
+                buf.extend_from_slice(fee_owner.as_ref());
+                buf.extend_from_slice(&initial_supply.to_le_bytes());
+                buf.extend_from_slice(&lp_decimals.to_le_bytes());
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+                let mut fee_discount_slice = [0u8; FeeDiscount::LEN];
+                Pack::pack_into_slice(fee_discount, &mut fee_discount_slice[..]);
+                buf.extend_from_slice(&fee_discount_slice);
+                buf.extend_from_slice(quoter.as_ref());
+            }
+            Self::LockLiquidity(LockLiquidity {
+                amount,
+                unlock_timestamp,
+            }) => {
+                buf.push(5);
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&unlock_timestamp.to_le_bytes());
+            }
+            Self::UnlockLiquidity => {
+                buf.push(6);
+            }
+            Self::InitializeStakePool(InitializeStakePool { reward_per_slot }) => {
+                buf.push(7);
+                buf.extend_from_slice(&reward_per_slot.to_le_bytes());
+            }
+            Self::Stake(Stake { amount }) => {
+                buf.push(8);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::Unstake(Unstake { amount }) => {
+                buf.push(9);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::ClaimRewards => {
+                buf.push(10);
+            }
+            Self::Sync => {
+                buf.push(11);
+            }
+            Self::Skim => {
+                buf.push(12);
+            }
+            Self::Rebalance(Rebalance {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(13);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SetPoolOracle(SetPoolOracle { max_deviation_bps }) => {
+                buf.push(14);
+                buf.extend_from_slice(&max_deviation_bps.to_le_bytes());
+            }
+            Self::SplitSwap(SplitSwap {
+                amount_in,
+                minimum_amount_out,
+                first_pool_bps,
+            }) => {
+                buf.push(15);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&first_pool_bps.to_le_bytes());
+            }
+            Self::SwapWithQuote(SwapWithQuote {
+                amount_in,
+                amount_out,
+                expiry,
+                nonce,
+            }) => {
+                buf.push(16);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&amount_out.to_le_bytes());
+                buf.extend_from_slice(&expiry.to_le_bytes());
+                buf.extend_from_slice(&nonce.to_le_bytes());
+            }
+            Self::PlaceLimitOrder(PlaceLimitOrder {
+                order_id,
+                amount_in,
+                minimum_amount_out,
+                keeper_fee,
+            }) => {
+                buf.push(17);
+                buf.extend_from_slice(&order_id.to_le_bytes());
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&keeper_fee.to_le_bytes());
+            }
+            Self::CancelLimitOrder => buf.push(18),
+            Self::FillLimitOrder => buf.push(19),
+            Self::CreateDca(CreateDca {
+                dca_id,
+                amount,
+                amount_per_tick,
+                minimum_amount_out_per_tick,
+                keeper_fee,
+                interval,
+            }) => {
+                buf.push(20);
+                buf.extend_from_slice(&dca_id.to_le_bytes());
+                buf.extend_from_slice(&amount.to_le_bytes());
+                buf.extend_from_slice(&amount_per_tick.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out_per_tick.to_le_bytes());
+                buf.extend_from_slice(&keeper_fee.to_le_bytes());
+                buf.extend_from_slice(&interval.to_le_bytes());
+            }
+            Self::ExecuteDcaTick => buf.push(21),
+            Self::CloseDca => buf.push(22),
+            Self::GetVirtualPrice => buf.push(23),
+            Self::GetPoolState => buf.push(24),
+            Self::MigrateGlobalState => buf.push(25),
+            Self::UpdateCurveParameters(UpdateCurveParameters { swap_curve }) => {
+                buf.push(26);
+                let mut swap_curve_slice = [0u8; SwapCurve::LEN];
+                Pack::pack_into_slice(swap_curve, &mut swap_curve_slice[..]);
+                buf.extend_from_slice(&swap_curve_slice);
+            }
+            Self::DeprecatePool => buf.push(27),
+            Self::SetDepositCap(SetDepositCap { max_lp_supply }) => {
+                buf.push(28);
+                buf.extend_from_slice(&max_lp_supply.to_le_bytes());
+            }
+            Self::SetPoolGate(SetPoolGate { gated, gate_mint }) => {
+                buf.push(29);
+                buf.push(*gated as u8);
+                buf.extend_from_slice(gate_mint.as_ref());
+            }
+            Self::SetAllowlisted(SetAllowlisted { allowed }) => {
+                buf.push(30);
+                buf.push(*allowed as u8);
+            }
+            Self::SetWithdrawFee(SetWithdrawFee {
+                withdraw_fee_numerator,
+                burn,
+            }) => {
+                buf.push(31);
+                buf.extend_from_slice(&withdraw_fee_numerator.to_le_bytes());
+                buf.push(*burn as u8);
+            }
+            Self::SetMinHoldPeriod(SetMinHoldPeriod { min_hold_slots }) => {
+                buf.push(32);
+                buf.extend_from_slice(&min_hold_slots.to_le_bytes());
+            }
+            Self::InitDepositRecord => buf.push(33),
+            Self::SetProtocolLiquidityShare(SetProtocolLiquidityShare {
+                protocol_liquidity_bps,
+            }) => {
+                buf.push(34);
+                buf.extend_from_slice(&protocol_liquidity_bps.to_le_bytes());
+            }
+            Self::WithdrawProtocolLiquidity(WithdrawProtocolLiquidity { pool_token_amount }) => {
+                buf.push(35);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::SetBuybackConfig(SetBuybackConfig {
+                buyback_mint,
+                buyback_epoch_limit,
+            }) => {
+                buf.push(36);
+                buf.extend_from_slice(buyback_mint.as_ref());
+                buf.extend_from_slice(&buyback_epoch_limit.to_le_bytes());
+            }
+            Self::BuybackAndBurn(BuybackAndBurn {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(37);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SetFeeRecipients(SetFeeRecipients { fee_recipients }) => {
+                buf.push(38);
+                for fee_recipient in fee_recipients.iter() {
+                    let mut fee_recipient_slice = [0u8; FeeRecipient::LEN];
+                    Pack::pack_into_slice(fee_recipient, &mut fee_recipient_slice[..]);
+                    buf.extend_from_slice(&fee_recipient_slice);
+                }
+            }
+            Self::RecoverForeignTokens => buf.push(39),
+            Self::CommitSwap(CommitSwap { hash }) => {
+                buf.push(40);
+                buf.extend_from_slice(hash);
+            }
+            Self::RevealSwap(RevealSwap {
+                amount_in,
+                minimum_amount_out,
+                salt,
+            }) => {
+                buf.push(41);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(salt);
+            }
+            Self::SetBatchAuction(SetBatchAuction { batch_window_slots }) => {
+                buf.push(42);
+                buf.extend_from_slice(&batch_window_slots.to_le_bytes());
+            }
+            Self::SubmitBatchSwap(SubmitBatchSwap {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(43);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SettleBatch => buf.push(44),
+            Self::SetRebasing(SetRebasing { rebasing }) => {
+                buf.push(45);
+                buf.push(*rebasing as u8);
+            }
+            Self::SetRateAccount => buf.push(46),
+            Self::UpdateRate => buf.push(47),
+            Self::SetClaimableFees(SetClaimableFees { claimable_fees }) => {
+                buf.push(48);
+                buf.push(*claimable_fees as u8);
+            }
+            Self::IncreasePosition(IncreasePosition { pool_token_amount }) => {
+                buf.push(49);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::DecreasePosition(DecreasePosition { pool_token_amount }) => {
+                buf.push(50);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::ClaimFees => buf.push(51),
+            Self::SetNftPositions(SetNftPositions { nft_positions }) => {
+                buf.push(52);
+                buf.push(*nft_positions as u8);
+            }
+            Self::SetGuardian(SetGuardian { guardian }) => {
+                buf.push(53);
+                buf.extend_from_slice(guardian.as_ref());
+            }
+            Self::SetPaused(SetPaused { paused }) => {
+                buf.push(54);
+                buf.push(*paused as u8);
+            }
+            Self::SetPoolPaused(SetPaused { paused }) => {
+                buf.push(55);
+                buf.push(*paused as u8);
+            }
+            Self::SetSlotVolumeCap(SetSlotVolumeCap { max_slot_volume }) => {
+                buf.push(56);
+                buf.extend_from_slice(&max_slot_volume.to_le_bytes());
+            }
+            Self::SetCircuitBreaker(SetCircuitBreaker {
+                breaker_move_bps,
+                breaker_window_slots,
+            }) => {
+                buf.push(57);
+                buf.extend_from_slice(&breaker_move_bps.to_le_bytes());
+                buf.extend_from_slice(&breaker_window_slots.to_le_bytes());
+            }
+            Self::ResetCircuitBreaker => buf.push(58),
+            Self::ZapIn(ZapIn {
+                amount_in,
+                minimum_pool_token_amount,
+            }) => {
+                buf.push(59);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_pool_token_amount.to_le_bytes());
+            }
+            Self::ZapOut(ZapOut {
+                pool_token_amount,
+                minimum_amount_out,
+            }) => {
+                buf.push(60);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::SetPoolFeeOwner(SetPoolFeeOwner { fee_owner }) => {
+                buf.push(61);
+                buf.extend_from_slice(fee_owner.as_ref());
+            }
+            Self::SetOwnerFeeInPoolTokens(SetOwnerFeeInPoolTokens {
+                owner_fee_in_pool_tokens,
+            }) => {
+                buf.push(62);
+                buf.push(*owner_fee_in_pool_tokens as u8);
+            }
+            Self::SetKeeperReward(SetKeeperReward {
+                keeper_reward,
+                keeper_interval_slots,
+            }) => {
+                buf.push(63);
+                buf.extend_from_slice(&keeper_reward.to_le_bytes());
+                buf.extend_from_slice(&keeper_interval_slots.to_le_bytes());
+            }
+            Self::MigrateFromSplTokenSwap(MigrateFromSplTokenSwap {
+                pool_token_amount,
+                minimum_pool_token_amount,
+            }) => {
+                buf.push(64);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_pool_token_amount.to_le_bytes());
+            }
+            Self::InitializeBasket => {
+                buf.push(65);
+            }
+            Self::SwapBasket(SwapBasket {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                buf.push(66);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+            }
+            Self::DepositBasket(DepositBasket {
+                pool_token_amount,
+                maximum_token_amounts,
+            }) => {
+                buf.push(67);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                for amount in maximum_token_amounts.iter() {
+                    buf.extend_from_slice(&amount.to_le_bytes());
+                }
+            }
+            Self::WithdrawBasket(WithdrawBasket {
+                pool_token_amount,
+                minimum_token_amounts,
+            }) => {
+                buf.push(68);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                for amount in minimum_token_amounts.iter() {
+                    buf.extend_from_slice(&amount.to_le_bytes());
+                }
+            }
+            Self::ClosePosition => {
+                buf.push(69);
+            }
+            Self::QuoteWithdraw(QuoteWithdraw { pool_token_amount }) => {
+                buf.push(70);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::MigrateLiquidity(MigrateLiquidity {
+                pool_token_amount,
+                minimum_pool_token_amount,
+                swap_imbalance,
+            }) => {
+                buf.push(71);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_pool_token_amount.to_le_bytes());
+                buf.push(*swap_imbalance as u8);
+            }
+            Self::SetFeeGovernance(SetFeeGovernance {
+                governance_program_id,
+                realm,
+                governance_seed,
+            }) => {
+                buf.push(72);
+                buf.extend_from_slice(governance_program_id.as_ref());
+                buf.extend_from_slice(realm.as_ref());
+                buf.extend_from_slice(governance_seed.as_ref());
+            }
+            Self::SetGovernedFees(SetGovernedFees { fees, fee_discount }) => {
+                buf.push(73);
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+                let mut fee_discount_slice = [0u8; FeeDiscount::LEN];
+                Pack::pack_into_slice(fee_discount, &mut fee_discount_slice[..]);
+                buf.extend_from_slice(&fee_discount_slice);
+            }
+            Self::SchedulePendingAction(SchedulePendingAction { eta, action }) => {
+                buf.push(74);
+                buf.extend_from_slice(&eta.to_le_bytes());
+                let mut action_slice = [0u8; AdminAction::LEN];
+                action.pack_into_slice(&mut action_slice);
+                buf.extend_from_slice(&action_slice);
+            }
+            Self::ExecutePendingAction(ExecutePendingAction { action_id }) => {
+                buf.push(75);
+                buf.extend_from_slice(&action_id.to_le_bytes());
+            }
+            Self::CancelPendingAction(CancelPendingAction { action_id }) => {
+                buf.push(76);
+                buf.extend_from_slice(&action_id.to_le_bytes());
+            }
+            Self::SetPoolCount(SetPoolCount { pool_count }) => {
+                buf.push(77);
+                buf.extend_from_slice(&pool_count.to_le_bytes());
+            }
+            Self::ClaimBatchOrders => buf.push(78),
+            Self::MigratePoolState => buf.push(79),
+        }
+        Ok(buf)
+    }
+}
+
+/// Creates an 'initialize' instruction.
+pub fn initialize(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    token_a_pubkey: &Pubkey,
+    token_b_pubkey: &Pubkey,
+    pool_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    dead_shares_pubkey: &Pubkey,
+    swap_curve: SwapCurve,
+    fee_owner_pubkey: Option<&Pubkey>,
+    program_owner_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let init_data = SwapInstruction::Initialize(Initialize {
+        swap_curve,
+        fee_owner: fee_owner_pubkey.copied(),
+    });
+    let data = init_data.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, true),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*token_a_pubkey, false),
+        AccountMeta::new_readonly(*token_b_pubkey, false),
+        AccountMeta::new(*pool_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*token_a_mint_pubkey, false),
+        AccountMeta::new_readonly(*token_b_mint_pubkey, false),
+        AccountMeta::new(*dead_shares_pubkey, false),
+    ];
+    if fee_owner_pubkey.is_some() {
+        let program_owner_pubkey = program_owner_pubkey.ok_or(SwapError::InvalidInstruction)?;
+        accounts.push(AccountMeta::new_readonly(*program_owner_pubkey, true));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deposit_all_token_types' instruction.
+pub fn deposit_all_token_types(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    deposit_token_a_pubkey: &Pubkey,
+    deposit_token_b_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    gate_pubkey: Option<&Pubkey>,
+    deposit_record_pubkey: Option<&Pubkey>,
+    instruction: DepositAllTokenTypes,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::DepositAllTokenTypes(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new(*deposit_token_a_pubkey, false),
+        AccountMeta::new(*deposit_token_b_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(gate_pubkey) = gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(*gate_pubkey, false));
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new(*deposit_record_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_all_token_types' instruction.
+pub fn withdraw_all_token_types(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    destination_token_a_pubkey: &Pubkey,
+    destination_token_b_pubkey: &Pubkey,
+    pool_fee_pubkey: Option<&Pubkey>,
+    deposit_record_pubkey: Option<&Pubkey>,
+    instruction: WithdrawAllTokenTypes,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::WithdrawAllTokenTypes(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*destination_token_a_pubkey, false),
+        AccountMeta::new(*destination_token_b_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(pool_fee_pubkey) = pool_fee_pubkey {
+        accounts.push(AccountMeta::new(*pool_fee_pubkey, false));
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new_readonly(*deposit_record_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts of a 'swap' instruction, the optional ones left out when `None`
+/// or empty
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapArgs {
+    /// Token program
+    pub token_program_id: Pubkey,
+    /// Token-swap
+    pub swap_pubkey: Pubkey,
+    /// Swap authority
+    pub authority_pubkey: Pubkey,
+    /// User transfer authority
+    pub user_transfer_authority_pubkey: Pubkey,
+    /// Global state account
+    pub global_state_pubkey: Pubkey,
+    /// token_(A|B) SOURCE Account
+    pub source_pubkey: Pubkey,
+    /// Pool account to swap INTO
+    pub swap_source_pubkey: Pubkey,
+    /// Pool account to swap FROM
+    pub swap_destination_pubkey: Pubkey,
+    /// token_(A|B) DESTINATION Account
+    pub destination_pubkey: Pubkey,
+    /// Pool token mint
+    pub pool_mint_pubkey: Pubkey,
+    /// Fee account, when the swap charges an owner fee
+    pub pool_fee_pubkey: Option<Pubkey>,
+    /// Price oracle, when the pool has one
+    pub oracle_pubkey: Option<Pubkey>,
+    /// Gate token account or allowlist entry, when the pool is gated
+    pub gate_pubkey: Option<Pubkey>,
+    /// Protocol pool token account, when the pool accrues protocol-owned
+    /// liquidity
+    pub protocol_liquidity_pubkey: Option<Pubkey>,
+    /// SOURCE token account of each enabled fee recipient
+    pub fee_recipient_pubkeys: Vec<Pubkey>,
+    /// Relayer SOURCE token account, when the swap carries a tip
+    pub tip_pubkey: Option<Pubkey>,
+    /// Fee discount holder account, when the swap claims the fee discount
+    pub discount_holder_pubkey: Option<Pubkey>,
+}
+
+/// Creates a 'swap' instruction.
+pub fn swap(
+    program_id: &Pubkey,
+    args: SwapArgs,
+    instruction: Swap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Swap(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(args.swap_pubkey, false),
+        AccountMeta::new_readonly(args.authority_pubkey, false),
+        AccountMeta::new_readonly(args.user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(args.global_state_pubkey, false),
+        AccountMeta::new(args.source_pubkey, false),
+        AccountMeta::new(args.swap_source_pubkey, false),
+        AccountMeta::new(args.swap_destination_pubkey, false),
+        AccountMeta::new(args.destination_pubkey, false),
+        AccountMeta::new(args.pool_mint_pubkey, false),
+    ];
+    if let Some(pool_fee_pubkey) = args.pool_fee_pubkey {
+        accounts.push(AccountMeta::new(pool_fee_pubkey, false));
+    }
+    accounts.push(AccountMeta::new_readonly(args.token_program_id, false));
+    if let Some(oracle_pubkey) = args.oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(oracle_pubkey, false));
+    }
+    if let Some(gate_pubkey) = args.gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(gate_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = args.protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in args.fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+    if let Some(tip_pubkey) = args.tip_pubkey {
+        accounts.push(AccountMeta::new(tip_pubkey, false));
+    }
+    if let Some(discount_holder_pubkey) = args.discount_holder_pubkey {
+        accounts.push(AccountMeta::new_readonly(discount_holder_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'SetGlobalStateInstruction' instruction.
+pub fn set_global_state(
+    program_id: &Pubkey,
+    state_account_pubkey: &Pubkey,
+    current_owner_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    fee_owner_pubkey: &Pubkey,
+    initial_supply: u64,
+    lp_decimals: u8,
+    fees: Fees,
+    fee_discount: FeeDiscount,
+    quoter_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let init_data = SwapInstruction::SetGlobalStateInstruction(SetGlobalState {
+        owner:*owner_pubkey,
+        fee_owner:*fee_owner_pubkey,
+        initial_supply,
+        lp_decimals,
+        fees,
+        fee_discount,
+        quoter:*quoter_pubkey,
+    });
+    let data = init_data.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*state_account_pubkey, false),
+        AccountMeta::new_readonly(*current_owner_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'lock_liquidity' instruction.
+pub fn lock_liquidity(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    lock_pubkey: &Pubkey,
+    lock_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    instruction: LockLiquidity,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::LockLiquidity(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*lock_pubkey, false),
+        AccountMeta::new(*lock_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'unlock_liquidity' instruction.
+pub fn unlock_liquidity(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    lock_pubkey: &Pubkey,
+    lock_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::UnlockLiquidity.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*lock_pubkey, false),
+        AccountMeta::new(*lock_vault_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'initialize_stake_pool' instruction.
+pub fn initialize_stake_pool(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    stake_pool_pubkey: &Pubkey,
+    stake_vault_pubkey: &Pubkey,
+    reward_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: InitializeStakePool,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeStakePool(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*stake_pool_pubkey, false),
+        AccountMeta::new_readonly(*stake_vault_pubkey, false),
+        AccountMeta::new_readonly(*reward_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'stake' instruction.
+pub fn stake(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    stake_pool_pubkey: &Pubkey,
+    stake_position_pubkey: &Pubkey,
+    stake_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    instruction: Stake,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Stake(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*stake_pool_pubkey, false),
+        AccountMeta::new(*stake_position_pubkey, false),
+        AccountMeta::new(*stake_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'unstake' instruction.
+pub fn unstake(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    stake_pool_pubkey: &Pubkey,
+    stake_position_pubkey: &Pubkey,
+    stake_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: Unstake,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Unstake(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*stake_pool_pubkey, false),
+        AccountMeta::new(*stake_position_pubkey, false),
+        AccountMeta::new(*stake_vault_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'claim_rewards' instruction.
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    stake_pool_pubkey: &Pubkey,
+    stake_position_pubkey: &Pubkey,
+    reward_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClaimRewards.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*stake_pool_pubkey, false),
+        AccountMeta::new(*stake_position_pubkey, false),
+        AccountMeta::new(*reward_vault_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts paying a keeper the reward of a permissionless crank
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeeperRewardAccounts {
+    /// Global state account
+    pub global_state_pubkey: Pubkey,
+    /// Protocol liquidity authority
+    pub protocol_liquidity_authority_pubkey: Pubkey,
+    /// Protocol pool token account of the pool
+    pub protocol_liquidity_pubkey: Pubkey,
+    /// Keeper pool token account
+    pub keeper_pubkey: Pubkey,
+    /// Token program id
+    pub token_program_id: Pubkey,
+}
+
+impl KeeperRewardAccounts {
+    fn account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.global_state_pubkey, false),
+            AccountMeta::new_readonly(self.protocol_liquidity_authority_pubkey, false),
+            AccountMeta::new(self.protocol_liquidity_pubkey, false),
+            AccountMeta::new(self.keeper_pubkey, false),
+            AccountMeta::new_readonly(self.token_program_id, false),
+        ]
+    }
+}
+
+/// Creates a 'sync' instruction.
+pub fn sync(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    keeper_reward_accounts: Option<&KeeperRewardAccounts>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Sync.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_b_pubkey, false),
+    ];
+    if let Some(keeper_reward_accounts) = keeper_reward_accounts {
+        accounts.extend(keeper_reward_accounts.account_metas());
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'skim' instruction.
+pub fn skim(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    destination_token_a_pubkey: &Pubkey,
+    destination_token_b_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Skim.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*destination_token_a_pubkey, false),
+        AccountMeta::new(*destination_token_b_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'rebalance' instruction.
+pub fn rebalance(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    fee_owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    swap_source_pubkey: &Pubkey,
+    swap_destination_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: Rebalance,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Rebalance(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*fee_owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_source_pubkey, false),
+        AccountMeta::new(*swap_destination_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_pool_oracle' instruction.
+pub fn set_pool_oracle(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    oracle_pubkey: &Pubkey,
+    token_a_mint_pubkey: &Pubkey,
+    token_b_mint_pubkey: &Pubkey,
+    instruction: SetPoolOracle,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPoolOracle(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*oracle_pubkey, false),
+        AccountMeta::new_readonly(*token_a_mint_pubkey, false),
+        AccountMeta::new_readonly(*token_b_mint_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts of one pool taking part in a split swap
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitSwapPool {
+    /// Token-swap
+    pub swap_pubkey: Pubkey,
+    /// Swap authority
+    pub authority_pubkey: Pubkey,
+    /// Pool account to swap INTO
+    pub swap_source_pubkey: Pubkey,
+    /// Pool account to swap FROM
+    pub swap_destination_pubkey: Pubkey,
+    /// Pool token mint
+    pub pool_mint_pubkey: Pubkey,
+    /// Fee account of the source token
+    pub pool_fee_pubkey: Pubkey,
+    /// Price oracle, when the pool has one
+    pub oracle_pubkey: Option<Pubkey>,
+    /// Gate token account or allowlist entry, when the pool is gated
+    pub gate_pubkey: Option<Pubkey>,
+    /// Protocol pool token account, when the pool accrues protocol-owned
+    /// liquidity
+    pub protocol_liquidity_pubkey: Option<Pubkey>,
+}
+
+/// Creates a 'split_swap' instruction.
+pub fn split_swap(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    first_pool: &SplitSwapPool,
+    second_pool: &SplitSwapPool,
+    fee_recipient_pubkeys: &[Pubkey],
+    instruction: SplitSwap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SplitSwap(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    for pool in [first_pool, second_pool].iter() {
+        accounts.push(AccountMeta::new(pool.swap_pubkey, false));
+        accounts.push(AccountMeta::new_readonly(pool.authority_pubkey, false));
+        accounts.push(AccountMeta::new(pool.swap_source_pubkey, false));
+        accounts.push(AccountMeta::new(pool.swap_destination_pubkey, false));
+        accounts.push(AccountMeta::new(pool.pool_mint_pubkey, false));
+        accounts.push(AccountMeta::new(pool.pool_fee_pubkey, false));
+    }
+    for fee_recipient_pubkey in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+    for pool in [first_pool, second_pool].iter() {
+        if let Some(oracle_pubkey) = pool.oracle_pubkey {
+            accounts.push(AccountMeta::new_readonly(oracle_pubkey, false));
+        }
+        if let Some(gate_pubkey) = pool.gate_pubkey {
+            accounts.push(AccountMeta::new_readonly(gate_pubkey, false));
+        }
+        if let Some(protocol_liquidity_pubkey) = pool.protocol_liquidity_pubkey {
+            accounts.push(AccountMeta::new(protocol_liquidity_pubkey, false));
+        }
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'swap_with_quote' instruction. Quotes do not fill against
+/// gated pools, carry no tip and get no fee discount, the gate, tip and
+/// discount holder accounts of `args` are left out.
+pub fn swap_with_quote(
+    program_id: &Pubkey,
+    args: SwapArgs,
+    quote_nonce_pubkey: &Pubkey,
+    instruction: SwapWithQuote,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SwapWithQuote(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(args.swap_pubkey, false),
+        AccountMeta::new_readonly(args.authority_pubkey, false),
+        AccountMeta::new(args.user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(args.global_state_pubkey, false),
+        AccountMeta::new(args.source_pubkey, false),
+        AccountMeta::new(args.swap_source_pubkey, false),
+        AccountMeta::new(args.swap_destination_pubkey, false),
+        AccountMeta::new(args.destination_pubkey, false),
+        AccountMeta::new(args.pool_mint_pubkey, false),
+    ];
+    if let Some(pool_fee_pubkey) = args.pool_fee_pubkey {
+        accounts.push(AccountMeta::new(pool_fee_pubkey, false));
+    }
+    accounts.push(AccountMeta::new_readonly(args.token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    accounts.push(AccountMeta::new(*quote_nonce_pubkey, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    if let Some(oracle_pubkey) = args.oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(oracle_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = args.protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in args.fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'place_limit_order' instruction.
+pub fn place_limit_order(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    order_pubkey: &Pubkey,
+    order_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: PlaceLimitOrder,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::PlaceLimitOrder(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*order_pubkey, false),
+        AccountMeta::new_readonly(*order_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'cancel_limit_order' instruction.
+pub fn cancel_limit_order(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    order_pubkey: &Pubkey,
+    order_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    refund_pubkey: &Pubkey,
+    rent_recipient_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CancelLimitOrder.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*order_pubkey, false),
+        AccountMeta::new(*order_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*refund_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(rent_recipient_pubkey) = rent_recipient_pubkey {
+        accounts.push(AccountMeta::new(*rent_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'fill_limit_order' instruction.
+pub fn fill_limit_order(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    order_pubkey: &Pubkey,
+    order_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    swap_source_pubkey: &Pubkey,
+    swap_destination_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    pool_fee_pubkey: &Pubkey,
+    keeper_pubkey: &Pubkey,
+    refund_pubkey: &Pubkey,
+    oracle_pubkey: Option<&Pubkey>,
+    protocol_liquidity_pubkey: Option<&Pubkey>,
+    fee_recipient_pubkeys: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::FillLimitOrder.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*order_pubkey, false),
+        AccountMeta::new(*order_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, false),
+        AccountMeta::new(*swap_source_pubkey, false),
+        AccountMeta::new(*swap_destination_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new(*keeper_pubkey, false),
+        AccountMeta::new(*refund_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(*protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'create_dca' instruction.
+pub fn create_dca(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: CreateDca,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CreateDca(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new_readonly(*position_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'execute_dca_tick' instruction.
+pub fn execute_dca_tick(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    swap_source_pubkey: &Pubkey,
+    swap_destination_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    pool_fee_pubkey: &Pubkey,
+    keeper_pubkey: &Pubkey,
+    oracle_pubkey: Option<&Pubkey>,
+    protocol_liquidity_pubkey: Option<&Pubkey>,
+    fee_recipient_pubkeys: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ExecuteDcaTick.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new(*position_vault_pubkey, false),
+        AccountMeta::new(*swap_source_pubkey, false),
+        AccountMeta::new(*swap_destination_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new(*keeper_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(*protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'close_dca' instruction.
+pub fn close_dca(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    refund_pubkey: &Pubkey,
+    rent_recipient_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CloseDca.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new(*position_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*refund_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(rent_recipient_pubkey) = rent_recipient_pubkey {
+        accounts.push(AccountMeta::new(*rent_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'get_virtual_price' instruction.
+pub fn get_virtual_price(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetVirtualPrice.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*pool_mint_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_b_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'get_pool_state' instruction.
+pub fn get_pool_state(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetPoolState.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*pool_mint_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_b_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'migrate_global_state' instruction.
+pub fn migrate_global_state(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::MigrateGlobalState.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update_curve_parameters' instruction.
+pub fn update_curve_parameters(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: UpdateCurveParameters,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::UpdateCurveParameters(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deprecate_pool' instruction.
+pub fn deprecate_pool(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::DeprecatePool.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_deposit_cap' instruction.
+pub fn set_deposit_cap(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetDepositCap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetDepositCap(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_pool_gate' instruction.
+pub fn set_pool_gate(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetPoolGate,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPoolGate(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_allowlisted' instruction.
+pub fn set_allowlisted(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    entry_pubkey: &Pubkey,
+    wallet_pubkey: &Pubkey,
+    rent_recipient_pubkey: Option<&Pubkey>,
+    instruction: SetAllowlisted,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetAllowlisted(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*entry_pubkey, false),
+        AccountMeta::new_readonly(*wallet_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+    if let Some(rent_recipient_pubkey) = rent_recipient_pubkey {
+        accounts.push(AccountMeta::new(*rent_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_withdraw_fee' instruction.
+pub fn set_withdraw_fee(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetWithdrawFee,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetWithdrawFee(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_min_hold_period' instruction.
+pub fn set_min_hold_period(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetMinHoldPeriod,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetMinHoldPeriod(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'init_deposit_record' instruction.
+pub fn init_deposit_record(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    deposit_record_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitDepositRecord.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new(*deposit_record_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_protocol_liquidity_share' instruction.
+pub fn set_protocol_liquidity_share(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetProtocolLiquidityShare,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetProtocolLiquidityShare(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_protocol_liquidity' instruction.
+pub fn withdraw_protocol_liquidity(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    protocol_liquidity_authority_pubkey: &Pubkey,
+    protocol_liquidity_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: WithdrawProtocolLiquidity,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::WithdrawProtocolLiquidity(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*protocol_liquidity_authority_pubkey, false),
+        AccountMeta::new(*protocol_liquidity_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_buyback_config' instruction.
+pub fn set_buyback_config(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: SetBuybackConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetBuybackConfig(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'buyback_and_burn' instruction.
+pub fn buyback_and_burn(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    fee_owner_pubkey: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    swap_source_pubkey: &Pubkey,
+    swap_destination_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    pool_fee_pubkey: &Pubkey,
+    buyback_mint_pubkey: &Pubkey,
+    pool_extra_pubkeys: &[AccountMeta],
+    instruction: BuybackAndBurn,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::BuybackAndBurn(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*fee_owner_pubkey, true),
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_source_pubkey, false),
+        AccountMeta::new(*swap_destination_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*buyback_mint_pubkey, false),
+    ];
+    accounts.extend_from_slice(pool_extra_pubkeys);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_fee_recipients' instruction.
+pub fn set_fee_recipients(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: SetFeeRecipients,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetFeeRecipients(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'recover_foreign_tokens' instruction.
+pub fn recover_foreign_tokens(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    foreign_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::RecoverForeignTokens.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*foreign_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'commit_swap' instruction.
+pub fn commit_swap(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    commitment_pubkey: &Pubkey,
+    instruction: CommitSwap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CommitSwap(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*user_transfer_authority_pubkey, true),
+        AccountMeta::new(*commitment_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'reveal_swap' instruction out of the matching 'swap'
+/// instruction.
+pub fn reveal_swap(
+    commitment_pubkey: &Pubkey,
+    swap_instruction: Instruction,
+    instruction: RevealSwap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::RevealSwap(instruction).pack()?;
+
+    let mut accounts = swap_instruction.accounts;
+    // the user transfer authority receives the commitment rent
+    accounts
+        .get_mut(2)
+        .ok_or(SwapError::InvalidInstruction)?
+        .is_writable = true;
+    accounts.insert(0, AccountMeta::new(*commitment_pubkey, false));
+
+    Ok(Instruction {
+        program_id: swap_instruction.program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_batch_auction' instruction.
+pub fn set_batch_auction(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    batch_auction_pubkey: &Pubkey,
+    token_a_vault_pubkey: &Pubkey,
+    token_b_vault_pubkey: &Pubkey,
+    instruction: SetBatchAuction,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetBatchAuction(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*batch_auction_pubkey, false),
+        AccountMeta::new_readonly(*token_a_vault_pubkey, false),
+        AccountMeta::new_readonly(*token_b_vault_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'submit_batch_swap' instruction.
+pub fn submit_batch_swap(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    batch_auction_pubkey: &Pubkey,
+    batch_vault_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: SubmitBatchSwap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SubmitBatchSwap(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*batch_auction_pubkey, false),
+        AccountMeta::new(*batch_vault_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'settle_batch' instruction. `fee_recipient_pubkeys` holds the
+/// token_a and token_b accounts of each enabled fee recipient, and
+/// `order_pubkeys` the SOURCE and DESTINATION accounts of each queued swap,
+/// in queue order.
+pub fn settle_batch(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    batch_auction_pubkey: &Pubkey,
+    token_a_vault_pubkey: &Pubkey,
+    token_b_vault_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    token_a_fee_pubkey: &Pubkey,
+    token_b_fee_pubkey: &Pubkey,
+    oracle_pubkey: Option<&Pubkey>,
+    protocol_liquidity_pubkey: Option<&Pubkey>,
+    fee_recipient_pubkeys: &[(Pubkey, Pubkey)],
+    order_pubkeys: &[(Pubkey, Pubkey)],
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SettleBatch.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*batch_auction_pubkey, false),
+        AccountMeta::new(*token_a_vault_pubkey, false),
+        AccountMeta::new(*token_b_vault_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*token_a_fee_pubkey, false),
+        AccountMeta::new(*token_b_fee_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(*protocol_liquidity_pubkey, false));
+    }
+    for (token_a_fee_recipient_pubkey, _) in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*token_a_fee_recipient_pubkey, false));
+    }
+    for (_, token_b_fee_recipient_pubkey) in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*token_b_fee_recipient_pubkey, false));
+    }
+    for (source_pubkey, destination_pubkey) in order_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*source_pubkey, false));
+        accounts.push(AccountMeta::new(*destination_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_rebasing' instruction.
+pub fn set_rebasing(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    instruction: SetRebasing,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetRebasing(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_b_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts of the base pool a metapool reads the virtual price of its
+/// token B from, besides the base Token-swap
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasePoolAccounts {
+    /// Pool token mint of the base pool, token B of the metapool
+    pub pool_mint_pubkey: Pubkey,
+    /// token_a Base Account of the base pool
+    pub token_a_pubkey: Pubkey,
+    /// token_b Base Account of the base pool
+    pub token_b_pubkey: Pubkey,
+}
+
+impl BasePoolAccounts {
+    fn account_metas(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(self.pool_mint_pubkey, false),
+            AccountMeta::new_readonly(self.token_a_pubkey, false),
+            AccountMeta::new_readonly(self.token_b_pubkey, false),
+        ]
+    }
+}
+
+/// Creates a 'set_rate_account' instruction.
+pub fn set_rate_account(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    rate_pubkey: &Pubkey,
+    base_pool_accounts: Option<&BasePoolAccounts>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetRateAccount.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*rate_pubkey, false),
+    ];
+    if let Some(base_pool_accounts) = base_pool_accounts {
+        accounts.extend(base_pool_accounts.account_metas());
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'update_rate' instruction.
+pub fn update_rate(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    rate_pubkey: &Pubkey,
+    base_pool_accounts: Option<&BasePoolAccounts>,
+    keeper_reward_accounts: Option<&KeeperRewardAccounts>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::UpdateRate.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*rate_pubkey, false),
+    ];
+    if let Some(base_pool_accounts) = base_pool_accounts {
+        accounts.extend(base_pool_accounts.account_metas());
+    }
+    if let Some(keeper_reward_accounts) = keeper_reward_accounts {
+        accounts.extend(keeper_reward_accounts.account_metas());
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_claimable_fees' instruction.
+pub fn set_claimable_fees(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    instruction: SetClaimableFees,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetClaimableFees(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*position_vault_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'increase_position' instruction.
+pub fn increase_position(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    instruction: IncreasePosition,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::IncreasePosition(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new(*position_vault_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'decrease_position' instruction.
+pub fn decrease_position(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    position_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    instruction: DecreasePosition,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::DecreasePosition(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new(*position_vault_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'claim_fees' instruction.
+pub fn claim_fees(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_a_pubkey: &Pubkey,
+    destination_b_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClaimFees.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_a_pubkey, false),
+        AccountMeta::new(*destination_b_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_nft_positions' instruction.
+pub fn set_nft_positions(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetNftPositions,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetNftPositions(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_guardian' instruction.
+pub fn set_guardian(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: SetGuardian,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetGuardian(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_paused' instruction.
+pub fn set_paused(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    instruction: SetPaused,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPaused(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_pool_paused' instruction.
+pub fn set_pool_paused(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetPaused,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPoolPaused(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_slot_volume_cap' instruction.
+pub fn set_slot_volume_cap(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetSlotVolumeCap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetSlotVolumeCap(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_circuit_breaker' instruction.
+pub fn set_circuit_breaker(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetCircuitBreaker,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetCircuitBreaker(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'reset_circuit_breaker' instruction.
+pub fn reset_circuit_breaker(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ResetCircuitBreaker.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'zap_in' instruction.
+pub fn zap_in(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    other_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    pool_fee_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    oracle_pubkey: Option<&Pubkey>,
+    gate_pubkey: Option<&Pubkey>,
+    protocol_liquidity_pubkey: Option<&Pubkey>,
+    fee_recipient_pubkeys: &[Pubkey],
+    deposit_record_pubkey: Option<&Pubkey>,
+    instruction: ZapIn,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ZapIn(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*other_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    if let Some(gate_pubkey) = gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(*gate_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(*protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new(*deposit_record_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'zap_out' instruction.
+pub fn zap_out(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    other_pubkey: &Pubkey,
+    pool_fee_pubkey: &Pubkey,
+    withdraw_fee_pubkey: Option<&Pubkey>,
+    deposit_record_pubkey: Option<&Pubkey>,
+    oracle_pubkey: Option<&Pubkey>,
+    gate_pubkey: Option<&Pubkey>,
+    protocol_liquidity_pubkey: Option<&Pubkey>,
+    fee_recipient_pubkeys: &[Pubkey],
+    instruction: ZapOut,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ZapOut(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(*other_pubkey, false),
+        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(withdraw_fee_pubkey) = withdraw_fee_pubkey {
+        accounts.push(AccountMeta::new(*withdraw_fee_pubkey, false));
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new(*deposit_record_pubkey, false));
+    }
+    if let Some(oracle_pubkey) = oracle_pubkey {
+        accounts.push(AccountMeta::new_readonly(*oracle_pubkey, false));
+    }
+    if let Some(gate_pubkey) = gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(*gate_pubkey, false));
+    }
+    if let Some(protocol_liquidity_pubkey) = protocol_liquidity_pubkey {
+        accounts.push(AccountMeta::new(*protocol_liquidity_pubkey, false));
+    }
+    for fee_recipient_pubkey in fee_recipient_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_pool_fee_owner' instruction.
+pub fn set_pool_fee_owner(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetPoolFeeOwner,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPoolFeeOwner(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_owner_fee_in_pool_tokens' instruction.
+pub fn set_owner_fee_in_pool_tokens(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    instruction: SetOwnerFeeInPoolTokens,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetOwnerFeeInPoolTokens(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_keeper_reward' instruction.
+pub fn set_keeper_reward(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: SetKeeperReward,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetKeeperReward(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts of the spl-token-swap pool liquidity is migrated from
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplTokenSwapPool {
+    /// spl-token-swap program id
+    pub program_id: Pubkey,
+    /// Token-swap
+    pub swap_pubkey: Pubkey,
+    /// Swap authority
+    pub authority_pubkey: Pubkey,
+    /// Pool token mint
+    pub pool_mint_pubkey: Pubkey,
+    /// token_a Swap Account
+    pub swap_token_a_pubkey: Pubkey,
+    /// token_b Swap Account
+    pub swap_token_b_pubkey: Pubkey,
+    /// Fee account, to receive withdrawal fees
+    pub pool_fee_pubkey: Pubkey,
+}
+
+/// Creates a 'migrate_from_spl_token_swap' instruction.
+pub fn migrate_from_spl_token_swap(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    spl_token_swap_pool: &SplTokenSwapPool,
+    source_pubkey: &Pubkey,
     swap_pubkey: &Pubkey,
     authority_pubkey: &Pubkey,
     user_transfer_authority_pubkey: &Pubkey,
-    deposit_token_a_pubkey: &Pubkey,
-    deposit_token_b_pubkey: &Pubkey,
-    swap_token_a_pubkey: &Pubkey,
-    swap_token_b_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    token_a_pubkey: &Pubkey,
+    token_b_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    gate_pubkey: Option<&Pubkey>,
+    deposit_record_pubkey: Option<&Pubkey>,
+    instruction: MigrateFromSplTokenSwap,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::MigrateFromSplTokenSwap(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(spl_token_swap_pool.program_id, false),
+        AccountMeta::new_readonly(spl_token_swap_pool.swap_pubkey, false),
+        AccountMeta::new_readonly(spl_token_swap_pool.authority_pubkey, false),
+        AccountMeta::new(spl_token_swap_pool.pool_mint_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(spl_token_swap_pool.swap_token_a_pubkey, false),
+        AccountMeta::new(spl_token_swap_pool.swap_token_b_pubkey, false),
+        AccountMeta::new(spl_token_swap_pool.pool_fee_pubkey, false),
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*token_a_pubkey, false),
+        AccountMeta::new(*token_b_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    if let Some(gate_pubkey) = gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(*gate_pubkey, false));
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new(*deposit_record_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'initialize_basket' instruction.
+pub fn initialize_basket(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    basket_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    dead_shares_pubkey: &Pubkey,
+    vault_pubkeys: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeBasket.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*basket_pubkey, true),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new(*pool_mint_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*dead_shares_pubkey, false),
+    ];
+    for vault_pubkey in vault_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(*vault_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'swap_basket' instruction.
+pub fn swap_basket(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    basket_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    swap_source_pubkey: &Pubkey,
+    swap_destination_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    fee_account_pubkey: &Pubkey,
+    instruction: SwapBasket,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SwapBasket(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*basket_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_source_pubkey, false),
+        AccountMeta::new(*swap_destination_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new(*fee_account_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deposit_basket' instruction, `asset_pubkeys` holding the
+/// source account of the user and the vault of each asset, in order.
+pub fn deposit_basket(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    basket_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
     pool_mint_pubkey: &Pubkey,
     destination_pubkey: &Pubkey,
-    instruction: DepositAllTokenTypes,
+    asset_pubkeys: &[(Pubkey, Pubkey)],
+    instruction: DepositBasket,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::DepositAllTokenTypes(instruction).pack();
+    let data = SwapInstruction::DepositBasket(instruction).pack()?;
 
-    let accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+    let mut accounts = vec![
+        AccountMeta::new(*basket_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
-        AccountMeta::new(*deposit_token_a_pubkey, false),
-        AccountMeta::new(*deposit_token_b_pubkey, false),
-        AccountMeta::new(*swap_token_a_pubkey, false),
-        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
         AccountMeta::new(*pool_mint_pubkey, false),
         AccountMeta::new(*destination_pubkey, false),
         AccountMeta::new_readonly(*token_program_id, false),
     ];
+    for (source_pubkey, vault_pubkey) in asset_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*source_pubkey, false));
+        accounts.push(AccountMeta::new(*vault_pubkey, false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
@@ -414,37 +5267,33 @@ pub fn deposit_all_token_types(
     })
 }
 
-/// Creates a 'withdraw_all_token_types' instruction.
-pub fn withdraw_all_token_types(
+/// Creates a 'withdraw_basket' instruction, `asset_pubkeys` holding the
+/// vault and the destination account of the user of each asset, in order.
+pub fn withdraw_basket(
     program_id: &Pubkey,
     token_program_id: &Pubkey,
-    swap_pubkey: &Pubkey,
+    basket_pubkey: &Pubkey,
     authority_pubkey: &Pubkey,
     user_transfer_authority_pubkey: &Pubkey,
     pool_mint_pubkey: &Pubkey,
-    fee_account_pubkey: &Pubkey,
     source_pubkey: &Pubkey,
-    swap_token_a_pubkey: &Pubkey,
-    swap_token_b_pubkey: &Pubkey,
-    destination_token_a_pubkey: &Pubkey,
-    destination_token_b_pubkey: &Pubkey,
-    instruction: WithdrawAllTokenTypes,
+    asset_pubkeys: &[(Pubkey, Pubkey)],
+    instruction: WithdrawBasket,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::WithdrawAllTokenTypes(instruction).pack();
+    let data = SwapInstruction::WithdrawBasket(instruction).pack()?;
 
-    let accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+    let mut accounts = vec![
+        AccountMeta::new(*basket_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
         AccountMeta::new(*pool_mint_pubkey, false),
         AccountMeta::new(*source_pubkey, false),
-        AccountMeta::new(*swap_token_a_pubkey, false),
-        AccountMeta::new(*swap_token_b_pubkey, false),
-        AccountMeta::new(*destination_token_a_pubkey, false),
-        AccountMeta::new(*destination_token_b_pubkey, false),
-        AccountMeta::new(*fee_account_pubkey, false),
         AccountMeta::new_readonly(*token_program_id, false),
     ];
+    for (vault_pubkey, destination_pubkey) in asset_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*vault_pubkey, false));
+        accounts.push(AccountMeta::new(*destination_pubkey, false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
@@ -453,38 +5302,268 @@ pub fn withdraw_all_token_types(
     })
 }
 
-/// Creates a 'swap' instruction.
-pub fn swap(
+/// Creates a 'close_position' instruction.
+pub fn close_position(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    position_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    rent_recipient_pubkey: &Pubkey,
+    nft_account_pubkey: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClosePosition.pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*position_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*rent_recipient_pubkey, false),
+    ];
+    if let Some(nft_account_pubkey) = nft_account_pubkey {
+        accounts.push(AccountMeta::new_readonly(*nft_account_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'quote_withdraw' instruction.
+pub fn quote_withdraw(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    pool_mint_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    instruction: QuoteWithdraw,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::QuoteWithdraw(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*pool_mint_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_a_pubkey, false),
+        AccountMeta::new_readonly(*swap_token_b_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts of the pool a MigrateLiquidity deposits into
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrateLiquidityDestination {
+    /// Token-swap to deposit into
+    pub swap_pubkey: Pubkey,
+    /// swap authority
+    pub authority_pubkey: Pubkey,
+    /// token_a Base Account
+    pub swap_token_a_pubkey: Pubkey,
+    /// token_b Base Account
+    pub swap_token_b_pubkey: Pubkey,
+    /// Pool mint account
+    pub pool_mint_pubkey: Pubkey,
+    /// Pool account of the user to deposit the generated tokens into
+    pub destination_pubkey: Pubkey,
+}
+
+/// Accounts of the swap of the imbalance by a MigrateLiquidity
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrateLiquiditySwap {
+    /// Fee account of the token in excess, or the program id when the pool
+    /// charges no owner fee
+    pub pool_fee_pubkey: Pubkey,
+    /// Price oracle, when the pool has one
+    pub oracle_pubkey: Option<Pubkey>,
+    /// Protocol pool token account, when the pool has one
+    pub protocol_liquidity_pubkey: Option<Pubkey>,
+    /// Token account of the token in excess of each enabled fee recipient
+    pub fee_recipient_pubkeys: Vec<Pubkey>,
+}
+
+/// Creates a 'migrate_liquidity' instruction. `swap` is required exactly
+/// when the instruction swaps the imbalance.
+pub fn migrate_liquidity(
     program_id: &Pubkey,
     token_program_id: &Pubkey,
     swap_pubkey: &Pubkey,
     authority_pubkey: &Pubkey,
     user_transfer_authority_pubkey: &Pubkey,
-    source_pubkey: &Pubkey,
-    swap_source_pubkey: &Pubkey,
-    swap_destination_pubkey: &Pubkey,
-    destination_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
     pool_mint_pubkey: &Pubkey,
-    pool_fee_pubkey: &Pubkey,
-    host_fee_pubkey: Option<&Pubkey>,
-    instruction: Swap,
+    source_pubkey: &Pubkey,
+    swap_token_a_pubkey: &Pubkey,
+    swap_token_b_pubkey: &Pubkey,
+    user_token_a_pubkey: &Pubkey,
+    user_token_b_pubkey: &Pubkey,
+    pool_fee_pubkey: Option<&Pubkey>,
+    withdraw_deposit_record_pubkey: Option<&Pubkey>,
+    destination: &MigrateLiquidityDestination,
+    swap: Option<&MigrateLiquiditySwap>,
+    gate_pubkey: Option<&Pubkey>,
+    deposit_record_pubkey: Option<&Pubkey>,
+    instruction: MigrateLiquidity,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Swap(instruction).pack();
+    if instruction.swap_imbalance != swap.is_some() {
+        return Err(SwapError::InvalidInput.into());
+    }
+    let data = SwapInstruction::MigrateLiquidity(instruction).pack()?;
 
     let mut accounts = vec![
-        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*swap_pubkey, false),
         AccountMeta::new_readonly(*authority_pubkey, false),
         AccountMeta::new_readonly(*user_transfer_authority_pubkey, true),
-        AccountMeta::new(*source_pubkey, false),
-        AccountMeta::new(*swap_source_pubkey, false),
-        AccountMeta::new(*swap_destination_pubkey, false),
-        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
         AccountMeta::new(*pool_mint_pubkey, false),
-        AccountMeta::new(*pool_fee_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*swap_token_a_pubkey, false),
+        AccountMeta::new(*swap_token_b_pubkey, false),
+        AccountMeta::new(*user_token_a_pubkey, false),
+        AccountMeta::new(*user_token_b_pubkey, false),
+        AccountMeta::new(destination.swap_pubkey, false),
+        AccountMeta::new_readonly(destination.authority_pubkey, false),
+        AccountMeta::new(destination.swap_token_a_pubkey, false),
+        AccountMeta::new(destination.swap_token_b_pubkey, false),
+        AccountMeta::new(destination.pool_mint_pubkey, false),
+        AccountMeta::new(destination.destination_pubkey, false),
         AccountMeta::new_readonly(*token_program_id, false),
     ];
-    if let Some(host_fee_pubkey) = host_fee_pubkey {
-        accounts.push(AccountMeta::new(*host_fee_pubkey, false));
+    if let Some(pool_fee_pubkey) = pool_fee_pubkey {
+        accounts.push(AccountMeta::new(*pool_fee_pubkey, false));
+    }
+    if let Some(withdraw_deposit_record_pubkey) = withdraw_deposit_record_pubkey {
+        accounts.push(AccountMeta::new_readonly(*withdraw_deposit_record_pubkey, false));
+    }
+    if let Some(swap) = swap {
+        accounts.push(AccountMeta::new(swap.pool_fee_pubkey, false));
+        if let Some(oracle_pubkey) = swap.oracle_pubkey {
+            accounts.push(AccountMeta::new_readonly(oracle_pubkey, false));
+        }
+    }
+    if let Some(gate_pubkey) = gate_pubkey {
+        accounts.push(AccountMeta::new_readonly(*gate_pubkey, false));
+    }
+    if let Some(swap) = swap {
+        if let Some(protocol_liquidity_pubkey) = swap.protocol_liquidity_pubkey {
+            accounts.push(AccountMeta::new(protocol_liquidity_pubkey, false));
+        }
+        for fee_recipient_pubkey in swap.fee_recipient_pubkeys.iter() {
+            accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+        }
+    }
+    if let Some(deposit_record_pubkey) = deposit_record_pubkey {
+        accounts.push(AccountMeta::new(*deposit_record_pubkey, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_fee_governance' instruction.
+pub fn set_fee_governance(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    fee_governance_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+    instruction: SetFeeGovernance,
+) -> Result<Instruction, ProgramError> {
+    let realm_pubkey = instruction.realm;
+    let data = SwapInstruction::SetFeeGovernance(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+        AccountMeta::new(*fee_governance_pubkey, false),
+        AccountMeta::new_readonly(realm_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_governed_fees' instruction, to be executed by a proposal
+/// of the fee governance.
+pub fn set_governed_fees(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    fee_governance_pubkey: &Pubkey,
+    governance_pubkey: &Pubkey,
+    instruction: SetGovernedFees,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetGovernedFees(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*fee_governance_pubkey, false),
+        AccountMeta::new_readonly(*governance_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'schedule_pending_action' instruction.
+pub fn schedule_pending_action(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    scheduler_pubkey: &Pubkey,
+    instruction: SchedulePendingAction,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SchedulePendingAction(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*owner_pubkey, true),
+        AccountMeta::new(*scheduler_pubkey, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an 'execute_pending_action' instruction. `swap_pubkey` is
+/// required exactly when the action changes the amp of a pool.
+pub fn execute_pending_action(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    scheduler_pubkey: &Pubkey,
+    swap_pubkey: Option<&Pubkey>,
+    instruction: ExecutePendingAction,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ExecutePendingAction(instruction).pack()?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new(*scheduler_pubkey, false),
+    ];
+    if let Some(swap_pubkey) = swap_pubkey {
+        accounts.push(AccountMeta::new(*swap_pubkey, false));
     }
 
     Ok(Instruction {
@@ -494,29 +5573,93 @@ pub fn swap(
     })
 }
 
-/// Creates an 'SetGlobalStateInstruction' instruction.
-pub fn set_global_state(
+/// Creates a 'cancel_pending_action' instruction.
+pub fn cancel_pending_action(
     program_id: &Pubkey,
-    state_account_pubkey: &Pubkey,
-    current_owner_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
     owner_pubkey: &Pubkey,
-    fee_owner_pubkey: &Pubkey,
-    initial_supply: u64,
-    lp_decimals: u8,
-    fees: Fees,
+    scheduler_pubkey: &Pubkey,
+    instruction: CancelPendingAction,
 ) -> Result<Instruction, ProgramError> {
-    let init_data = SwapInstruction::SetGlobalStateInstruction(SetGlobalState {
-        owner:*owner_pubkey,
-        fee_owner:*fee_owner_pubkey,
-        initial_supply,
-        lp_decimals,
-        fees,
-    });
-    let data = init_data.pack();
+    let data = SwapInstruction::CancelPendingAction(instruction).pack()?;
 
     let accounts = vec![
-        AccountMeta::new(*state_account_pubkey, false),
-        AccountMeta::new_readonly(*current_owner_pubkey, true),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*scheduler_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_pool_count' instruction.
+pub fn set_pool_count(
+    program_id: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    instruction: SetPoolCount,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::SetPoolCount(instruction).pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*global_state_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'claim_batch_orders' instruction.
+pub fn claim_batch_orders(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    batch_auction_pubkey: &Pubkey,
+    batch_vault_pubkey: &Pubkey,
+    owner_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClaimBatchOrders.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(*swap_pubkey, false),
+        AccountMeta::new(*batch_auction_pubkey, false),
+        AccountMeta::new(*batch_vault_pubkey, false),
+        AccountMeta::new_readonly(*owner_pubkey, true),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'migrate_pool_state' instruction.
+pub fn migrate_pool_state(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    token_a_pubkey: &Pubkey,
+    token_b_pubkey: &Pubkey,
+    payer_pubkey: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::MigratePoolState.pack()?;
+
+    let accounts = vec![
+        AccountMeta::new(*swap_pubkey, false),
+        AccountMeta::new_readonly(*token_a_pubkey, false),
+        AccountMeta::new_readonly(*token_b_pubkey, false),
+        AccountMeta::new(*payer_pubkey, true),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(sysvar::rent::id(), false),
     ];
@@ -2,14 +2,40 @@
 
 //! An Uniswap-like program for the Solana blockchain.
 
+/// Log a message only when built with the `debug-logs` feature, keeping
+/// release builds down to instruction names, errors and events
+macro_rules! debug_msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "debug-logs")]
+        solana_program::msg!($($arg)*);
+    };
+}
+
+// Built with the `client` feature and without the default `processor` one,
+// the crate leaves out the entrypoint, the processor, the cpi helpers and
+// the logging of events, so indexers and bots share the curve math, account
+// layouts, event parsing and instruction builders without the code calling
+// into the runtime. It is not a no_std build: every module still depends on
+// solana-program and std.
+pub mod accounting;
+#[cfg(feature = "spl-compat")]
+pub mod compat;
 pub mod constraints;
+#[cfg(feature = "processor")]
+pub mod cpi;
 pub mod curve;
 pub mod error;
+pub mod events;
 pub mod instruction;
+pub mod oracle;
+pub mod pda;
+#[cfg(feature = "processor")]
 pub mod processor;
+pub mod quote;
 pub mod state;
+pub mod token_2022;
 
-#[cfg(not(feature = "no-entrypoint"))]
+#[cfg(all(feature = "processor", not(feature = "no-entrypoint")))]
 mod entrypoint;
 
 // Export current sdk types for downstream users building with a different sdk version
@@ -0,0 +1,140 @@
+//! External price feeds used to sanity check pool prices
+
+use crate::{
+    error::SwapError,
+    state::{PRICE_PRECISION, PRICE_PRECISION_DECIMALS},
+};
+use arrayref::{array_ref, array_refs};
+use solana_program::program_error::ProgramError;
+use std::convert::TryFrom;
+
+/// Magic number at the start of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+
+/// Pyth account type of price accounts
+pub const PYTH_PRICE_ACCOUNT_TYPE: u32 = 3;
+
+/// Pyth aggregate status of a price that is currently trading
+pub const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Slots after which a published price is considered stale
+pub const MAX_ORACLE_STALENESS_SLOTS: u64 = 25;
+
+/// Size of the Pyth price account header and aggregate price read here
+const PYTH_PRICE_LEN: usize = 240;
+
+/// Aggregate price read from a Pyth price account
+#[derive(Clone, Debug, PartialEq)]
+pub struct PythPrice {
+    /// Price exponent
+    pub expo: i32,
+    /// Aggregate price
+    pub price: i64,
+    /// Aggregate confidence interval
+    pub conf: u64,
+    /// Aggregate status
+    pub status: u32,
+    /// Slot the aggregate was published at
+    pub pub_slot: u64,
+}
+
+impl PythPrice {
+    /// Reads the aggregate price of a Pyth v2 price account
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < PYTH_PRICE_LEN {
+            return Err(SwapError::InvalidOracle.into());
+        }
+        let input = array_ref![input, 0, PYTH_PRICE_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (magic, _version, account_type, _size, _price_type, expo, _rest, price, conf, status, _corp_act, pub_slot) =
+            array_refs![input, 4, 4, 4, 4, 4, 4, 184, 8, 8, 4, 4, 8];
+        if u32::from_le_bytes(*magic) != PYTH_MAGIC
+            || u32::from_le_bytes(*account_type) != PYTH_PRICE_ACCOUNT_TYPE
+        {
+            return Err(SwapError::InvalidOracle.into());
+        }
+        Ok(Self {
+            expo: i32::from_le_bytes(*expo),
+            price: i64::from_le_bytes(*price),
+            conf: u64::from_le_bytes(*conf),
+            status: u32::from_le_bytes(*status),
+            pub_slot: u64::from_le_bytes(*pub_slot),
+        })
+    }
+
+    /// Price of one base unit of token A in base units of token B, scaled by
+    /// PRICE_PRECISION, if the price is trading and fresh at the given slot
+    pub fn pool_price(
+        &self,
+        token_a_decimals: u8,
+        token_b_decimals: u8,
+        current_slot: u64,
+    ) -> Option<u128> {
+        if self.status != PYTH_STATUS_TRADING
+            || self.price <= 0
+            || current_slot.saturating_sub(self.pub_slot) > MAX_ORACLE_STALENESS_SLOTS
+        {
+            return None;
+        }
+        let price = u128::try_from(self.price).ok()?;
+        let exponent = self
+            .expo
+            .checked_add(i32::from(token_b_decimals))?
+            .checked_sub(i32::from(token_a_decimals))?
+            .checked_add(PRICE_PRECISION_DECIMALS)?;
+        if exponent >= 0 {
+            price.checked_mul(10u128.checked_pow(u32::try_from(exponent).ok()?)?)
+        } else {
+            price.checked_div(10u128.checked_pow(u32::try_from(-exponent).ok()?)?)
+        }
+    }
+}
+
+/// Account type of an initialized SPL stake pool
+pub const STAKE_POOL_ACCOUNT_TYPE: u8 = 1;
+
+/// Size of the stake pool fields read here
+const STAKE_POOL_LEN: usize = 282;
+
+/// Totals read from an SPL stake pool account
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakePoolRate {
+    /// Lamports managed by the stake pool
+    pub total_lamports: u64,
+    /// Supply of the stake pool token
+    pub pool_token_supply: u64,
+    /// Epoch the totals were last updated in
+    pub last_update_epoch: u64,
+}
+
+impl StakePoolRate {
+    /// Reads the totals of an SPL stake pool account
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < STAKE_POOL_LEN {
+            return Err(SwapError::InvalidRateAccount.into());
+        }
+        let input = array_ref![input, 0, STAKE_POOL_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (account_type, _rest, total_lamports, pool_token_supply, last_update_epoch) =
+            array_refs![input, 1, 257, 8, 8, 8];
+        if account_type[0] != STAKE_POOL_ACCOUNT_TYPE {
+            return Err(SwapError::InvalidRateAccount.into());
+        }
+        Ok(Self {
+            total_lamports: u64::from_le_bytes(*total_lamports),
+            pool_token_supply: u64::from_le_bytes(*pool_token_supply),
+            last_update_epoch: u64::from_le_bytes(*last_update_epoch),
+        })
+    }
+
+    /// Lamports one base unit of the stake pool token is worth, scaled by
+    /// PRICE_PRECISION, if the totals were updated in the given epoch
+    pub fn rate(&self, current_epoch: u64) -> Option<u128> {
+        if self.last_update_epoch != current_epoch || self.total_lamports == 0 {
+            return None;
+        }
+        u128::from(self.total_lamports)
+            .checked_mul(PRICE_PRECISION)?
+            .checked_div(u128::from(self.pool_token_supply))
+    }
+}
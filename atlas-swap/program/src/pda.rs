@@ -0,0 +1,208 @@
+//! Program addresses of the accounts of this program, and of the
+//! associated token accounts of a pool authority, derived with the same
+//! seeds as the processor checks them
+
+use crate::constraints::{
+    ALLOWLIST_TAG, BATCH_TAG, COMMIT_TAG, DCA_TAG, DEPOSIT_RECORD_TAG, FEE_GOVERNANCE_TAG,
+    LOCK_TAG, ORDER_TAG, POSITION_TAG, PROTOCOL_LIQUIDITY_TAG, QUOTE_NONCE_TAG, SCHEDULER_TAG,
+    SPL_GOVERNANCE_SEED, STAKE_TAG, SWAP_TAG,
+};
+use solana_program::{pubkey, pubkey::Pubkey};
+
+/// Associated token account program id
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Authority of a pool, owning its vaults and minting its pool tokens. The
+/// bump is the nonce the pool is initialized with.
+pub fn find_pool_authority(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[swap.as_ref()], program_id)
+}
+
+/// Global state account of the program
+pub fn find_global_state(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SWAP_TAG.as_bytes(), program_id.as_ref()], program_id)
+}
+
+/// Authority owning the protocol-owned liquidity
+pub fn find_protocol_liquidity_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref()],
+        program_id,
+    )
+}
+
+/// Allowlist entry of a wallet in a pool
+pub fn find_allowlist_entry(program_id: &Pubkey, swap: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ALLOWLIST_TAG.as_bytes(), swap.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// Deposit record of an owner in a pool
+pub fn find_deposit_record(program_id: &Pubkey, swap: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[DEPOSIT_RECORD_TAG.as_bytes(), swap.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// Quote nonce record of a taker in a pool
+pub fn find_quote_nonce(program_id: &Pubkey, swap: &Pubkey, taker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[QUOTE_NONCE_TAG.as_bytes(), swap.as_ref(), taker.as_ref()],
+        program_id,
+    )
+}
+
+/// Liquidity lock of an owner in a pool
+pub fn find_liquidity_lock(program_id: &Pubkey, swap: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOCK_TAG.as_bytes(), swap.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// Swap commitment of a user transfer authority in a pool
+pub fn find_swap_commitment(
+    program_id: &Pubkey,
+    swap: &Pubkey,
+    user_transfer_authority: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            COMMIT_TAG.as_bytes(),
+            swap.as_ref(),
+            user_transfer_authority.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Batch auction of a pool
+pub fn find_batch_auction(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BATCH_TAG.as_bytes(), swap.as_ref()], program_id)
+}
+
+/// Limit order of an owner in a pool
+pub fn find_limit_order(
+    program_id: &Pubkey,
+    swap: &Pubkey,
+    owner: &Pubkey,
+    order_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ORDER_TAG.as_bytes(),
+            swap.as_ref(),
+            owner.as_ref(),
+            &order_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Recurring order of an owner in a pool
+pub fn find_dca_order(
+    program_id: &Pubkey,
+    swap: &Pubkey,
+    owner: &Pubkey,
+    dca_id: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            DCA_TAG.as_bytes(),
+            swap.as_ref(),
+            owner.as_ref(),
+            &dca_id.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// Stake pool of a pool
+pub fn find_stake_pool(program_id: &Pubkey, swap: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_TAG.as_bytes(), swap.as_ref()], program_id)
+}
+
+/// Stake position of an owner in a stake pool
+pub fn find_stake_position(
+    program_id: &Pubkey,
+    stake_pool: &Pubkey,
+    owner: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[STAKE_TAG.as_bytes(), stake_pool.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// LP position of an NFT mint in a pool
+pub fn find_lp_position(program_id: &Pubkey, swap: &Pubkey, nft_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[POSITION_TAG.as_bytes(), swap.as_ref(), nft_mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Fee governance account of the program
+pub fn find_fee_governance(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FEE_GOVERNANCE_TAG.as_bytes(), program_id.as_ref()],
+        program_id,
+    )
+}
+
+/// Scheduler of the pending admin actions of the program
+pub fn find_scheduler(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEDULER_TAG.as_bytes(), program_id.as_ref()], program_id)
+}
+
+/// Governance of an spl-governance realm, the signer of the instructions of
+/// its executed proposals
+pub fn find_spl_governance(
+    governance_program_id: &Pubkey,
+    realm: &Pubkey,
+    governance_seed: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SPL_GOVERNANCE_SEED.as_bytes(),
+            realm.as_ref(),
+            governance_seed.as_ref(),
+        ],
+        governance_program_id,
+    )
+}
+
+/// Address of the associated token account of the wallet for the mint
+pub fn get_associated_token_address(
+    wallet: &Pubkey,
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program_id.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Associated token accounts of the authority of a pool for its token A and
+/// token B mints, each under the token program of its mint, usable as the
+/// vaults of a pool initialized at `swap`
+pub fn derive_vault_atas(
+    program_id: &Pubkey,
+    swap: &Pubkey,
+    token_a_mint: &Pubkey,
+    token_a_program_id: &Pubkey,
+    token_b_mint: &Pubkey,
+    token_b_program_id: &Pubkey,
+) -> (Pubkey, Pubkey) {
+    let (authority, _bump) = find_pool_authority(program_id, swap);
+    (
+        get_associated_token_address(&authority, token_a_program_id, token_a_mint),
+        get_associated_token_address(&authority, token_b_program_id, token_b_mint),
+    )
+}
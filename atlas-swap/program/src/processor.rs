@@ -3,34 +3,79 @@
 use crate::constraints::*;
 use crate::{
     curve::{
-        base::{SwapCurve},
-        calculator::{RoundDirection, TradeDirection},
-        fees::Fees,
+        base::{get_deposit_quote, get_swap_quote, get_withdraw_quote, CurveType, SwapCurve},
+        basket::{self, geometric_mean, MAX_BASKET_ASSETS, MIN_BASKET_ASSETS},
+        batch::get_batch_clearing,
+        calculator::{CurveCalculator, RoundDirection, TradeDirection},
+        fees::{FeeDiscount, FeeRecipient, Fees, FEE_RECIPIENT_COUNT},
+        oracle_pegged::OraclePeggedCurve,
+        meta_stable::MetaStableCurve,
+        rated_stable::RatedStableCurve,
+        stable::StableCurve,
     },
     error::SwapError,
+    events::{Event, RebalanceEvent, SwapEvent},
     instruction::{
         DepositAllTokenTypes, Initialize, Swap,
-        SwapInstruction, WithdrawAllTokenTypes, SetGlobalState
+        SwapInstruction, WithdrawAllTokenTypes, SetGlobalState, LockLiquidity,
+        InitializeStakePool, Stake, Unstake, Rebalance, SetPoolOracle, SplitSwap,
+        SwapWithQuote, PlaceLimitOrder, CreateDca, UpdateCurveParameters, SetDepositCap,
+        SetPoolGate, SetAllowlisted, SetWithdrawFee, SetMinHoldPeriod, SetProtocolLiquidityShare,
+        WithdrawProtocolLiquidity, SetBuybackConfig, BuybackAndBurn, SetFeeRecipients,
+        CommitSwap, RevealSwap, SetBatchAuction, SubmitBatchSwap, SetRebasing, SetClaimableFees,
+        IncreasePosition, DecreasePosition, SetNftPositions, SetGuardian, SetPaused,
+        SetSlotVolumeCap, SetCircuitBreaker, ZapIn, ZapOut, SetPoolFeeOwner, SetOwnerFeeInPoolTokens, SetKeeperReward, MigrateFromSplTokenSwap,
+        SwapBasket, DepositBasket, WithdrawBasket, QuoteWithdraw, MigrateLiquidity, SetFeeGovernance,
+        SetGovernedFees, SchedulePendingAction, ExecutePendingAction, CancelPendingAction, SetPoolCount,
     },
-    state::{SwapState, SwapV1, SwapVersion, GlobalState},
+    oracle::{PythPrice, StakePoolRate},
+    quote::Quote,
+    token_2022::{self, TOKEN_2022_PROGRAM_ID},
+    state::{SwapState, SwapV2, SwapVersion, GlobalState, GlobalStateVersion, PriceOracle, ExternalOracle, LiquidityLock, StakePool, StakePosition, LpPosition, LimitOrder, DcaPosition, AllowlistEntry, DepositRecord, QuoteNonce, SwapCommitment, FeeGovernance,
+        AdminAction, PendingAction, Scheduler, MAX_PENDING_ACTIONS,
+        BatchAuction, BatchOrder, MAX_BATCH_ORDERS, PoolStateView, POOL_STATE_VIEW_VERSION, BasketAsset, BasketPool},
 };
 use num_traits::FromPrimitive;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     decode_error::DecodeError,
     entrypoint::ProgramResult,
+    log::sol_log_data,
     msg,
     program::invoke_signed,
     program::invoke,
+    program::set_return_data,
+    instruction::{AccountMeta, Instruction},
     system_instruction,
+    system_program,
     program_error::{PrintProgramError, ProgramError},
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{instructions, rent::{self, Rent}, Sysvar},
 };
-use std::convert::TryInto;
-use std::str::FromStr;
+use spl_math::uint::U256;
+use std::convert::{TryFrom, TryInto};
+
+/// Accounts a swap through the pool moves tokens between, the SOURCE
+/// account being the user's for a regular swap and a program escrow for
+/// orders
+struct PoolSwapAccounts<'a, 'b> {
+    swap_info: &'b AccountInfo<'a>,
+    authority_info: &'b AccountInfo<'a>,
+    source_info: &'b AccountInfo<'a>,
+    source_authority_info: &'b AccountInfo<'a>,
+    swap_source_info: &'b AccountInfo<'a>,
+    swap_destination_info: &'b AccountInfo<'a>,
+    destination_info: &'b AccountInfo<'a>,
+    pool_mint_info: &'b AccountInfo<'a>,
+    fixed_fee_account_info: Option<&'b AccountInfo<'a>>,
+    protocol_liquidity_info: Option<&'b AccountInfo<'a>>,
+    fee_recipient_infos: Vec<(FeeRecipient, &'b AccountInfo<'a>)>,
+    oracle_info: Option<&'b AccountInfo<'a>>,
+    token_program_info: &'b AccountInfo<'a>,
+}
 
 /// Program state handler.
 pub struct Processor {}
@@ -42,6 +87,8 @@ impl Processor {
     ) -> Result<spl_token::state::Account, SwapError> {
         if account_info.owner != token_program_id {
             Err(SwapError::IncorrectTokenProgramId)
+        } else if *token_program_id == TOKEN_2022_PROGRAM_ID {
+            token_2022::unpack_account(&account_info.data.borrow())
         } else {
             spl_token::state::Account::unpack(&account_info.data.borrow())
                 .map_err(|_| SwapError::ExpectedAccount)
@@ -51,7 +98,7 @@ impl Processor {
     /// Assert `rent` exempt.
     pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramResult {
         if !rent.is_exempt(account_info.lamports(), account_info.data_len()) {
-            msg!(&rent.minimum_balance(account_info.data_len()).to_string());
+            debug_msg!("Rent exempt minimum {}", rent.minimum_balance(account_info.data_len()));
             Err(SwapError::NotRentExempt.into())
         } else {
             Ok(())
@@ -65,6 +112,8 @@ impl Processor {
     ) -> Result<spl_token::state::Mint, SwapError> {
         if account_info.owner != token_program_id {
             Err(SwapError::IncorrectTokenProgramId)
+        } else if *token_program_id == TOKEN_2022_PROGRAM_ID {
+            token_2022::unpack_mint(&account_info.data.borrow())
         } else {
             spl_token::state::Mint::unpack(&account_info.data.borrow())
                 .map_err(|_| SwapError::ExpectedMint)
@@ -96,13 +145,16 @@ impl Processor {
         let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
         let signers = &[&authority_signature_seeds[..]];
 
-        let ix = spl_token::instruction::burn(
+        let ix = token_2022::with_token_program(
             token_program.key,
-            burn_account.key,
-            mint.key,
-            authority.key,
-            &[],
-            amount,
+            spl_token::instruction::burn(
+                &spl_token::id(),
+                burn_account.key,
+                mint.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
         )?;
 
         invoke_signed(
@@ -125,13 +177,16 @@ impl Processor {
         let swap_bytes = swap.to_bytes();
         let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
         let signers = &[&authority_signature_seeds[..]];
-        let ix = spl_token::instruction::mint_to(
+        let ix = token_2022::with_token_program(
             token_program.key,
-            mint.key,
-            destination.key,
-            authority.key,
-            &[],
-            amount,
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
         )?;
 
         invoke_signed(&ix, &[mint, destination, authority, token_program], signers)
@@ -150,13 +205,16 @@ impl Processor {
         let swap_bytes = swap.to_bytes();
         let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
         let signers = &[&authority_signature_seeds[..]];
-        let ix = spl_token::instruction::transfer(
+        let ix = token_2022::with_token_program(
             token_program.key,
-            source.key,
-            destination.key,
-            authority.key,
-            &[],
-            amount,
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                source.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
         )?;
         invoke_signed(
             &ix,
@@ -165,7 +223,34 @@ impl Processor {
         )
     }
 
-    
+    /// Issue a spl_token `Transfer` instruction signed by an arbitrary
+    /// program address.
+    pub fn token_transfer_signed<'a>(
+        token_program: AccountInfo<'a>,
+        source: AccountInfo<'a>,
+        destination: AccountInfo<'a>,
+        authority: AccountInfo<'a>,
+        signer_seeds: &[&[u8]],
+        amount: u64,
+    ) -> Result<(), ProgramError> {
+        let ix = token_2022::with_token_program(
+            token_program.key,
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                source.key,
+                destination.key,
+                authority.key,
+                &[],
+                amount,
+            )?,
+        )?;
+        invoke_signed(
+            &ix,
+            &[source, destination, authority, token_program],
+            &[signer_seeds],
+        )
+    }
+
     /// create or allocate storage for new account
     pub fn create_or_allocate_account_raw<'a>(
         program_id: Pubkey,
@@ -183,7 +268,7 @@ impl Processor {
             .saturating_sub(new_account_info.lamports());
     
         if required_lamports > 0 {
-            msg!("Transfer {} lamports to the new account", required_lamports);
+            debug_msg!("Transfer {} lamports to the new account", required_lamports);
             invoke(
                 &system_instruction::transfer(&payer_info.key, new_account_info.key, required_lamports),
                 &[
@@ -194,20 +279,20 @@ impl Processor {
             )?;
         }
     
-        msg!("Allocate space for the account");
+        debug_msg!("Allocate space for the account");
         invoke_signed(
             &system_instruction::allocate(new_account_info.key, size.try_into().map_err(|_| SwapError::InvalidAllocateSpaceForAccount)?),
             &[new_account_info.clone(), system_program_info.clone()],
             &[&signer_seeds],
         )?;
     
-        msg!("Assign the account to the owning program");
+        debug_msg!("Assign the account to the owning program");
         invoke_signed(
             &system_instruction::assign(new_account_info.key, &program_id),
             &[new_account_info.clone(), system_program_info.clone()],
             &[&signer_seeds],
         )?;
-        msg!("Completed assignation!");
+        debug_msg!("Completed assignation!");
     
         Ok(())
     }
@@ -222,8 +307,6 @@ impl Processor {
         token_b_info: &AccountInfo,
         pool_mint_info: &AccountInfo,
         token_program_info: &AccountInfo,
-        user_token_a_info: Option<&AccountInfo>,
-        user_token_b_info: Option<&AccountInfo>,
     ) -> ProgramResult {
         if swap_account_info.owner != program_id {
             return Err(ProgramError::IncorrectProgramId);
@@ -231,10 +314,10 @@ impl Processor {
         Self::assert_pda(&[swap_account_info.key.as_ref()], program_id, authority_info.key)?;
         
         if *token_a_info.key != *token_swap.token_a_account() {
-            return Err(SwapError::IncorrectSwapAccount.into());
+            return Err(SwapError::IncorrectTokenAVault.into());
         }
         if *token_b_info.key != *token_swap.token_b_account() {
-            return Err(SwapError::IncorrectSwapAccount.into());
+            return Err(SwapError::IncorrectTokenBVault.into());
         }
         if *pool_mint_info.key != *token_swap.pool_mint() {
             return Err(SwapError::IncorrectPoolMint.into());
@@ -242,20 +325,11 @@ impl Processor {
         if *token_program_info.key != *token_swap.token_program_id() {
             return Err(SwapError::IncorrectTokenProgramId.into());
         }
-        if let Some(user_token_a_info) = user_token_a_info {
-            if token_a_info.key == user_token_a_info.key {
-                return Err(SwapError::InvalidInput.into());
-            }
-        }
-        if let Some(user_token_b_info) = user_token_b_info {
-            if token_b_info.key == user_token_b_info.key {
-                return Err(SwapError::InvalidInput.into());
-            }
-        }
         Ok(())
     }
     
     /// processor for Global State
+    #[allow(clippy::too_many_arguments)]
     pub fn process_set_global_state(
         program_id: &Pubkey,
         owner: &Pubkey,
@@ -263,6 +337,8 @@ impl Processor {
         initial_supply: u64,
         lp_decimals: u8,
         fees: Fees,
+        fee_discount: FeeDiscount,
+        quoter: &Pubkey,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
 
@@ -284,11 +360,11 @@ impl Processor {
             return Err(SwapError::InvalidSigner.into());
         }
 
-        if *system_info.key != Pubkey::from_str(SYSTEM_PROGRAM_ID).map_err(|_| SwapError::InvalidSystemProgramId)?{
+        if !system_program::check_id(system_info.key) {
             return Err(SwapError::InvalidSystemProgramId.into());
         }
 
-        if *rent_info.key != Pubkey::from_str(RENT_SYSVAR_ID).map_err(|_| SwapError::InvalidRentSysvarId)?{
+        if !rent::check_id(rent_info.key) {
             return Err(SwapError::InvalidRentSysvarId.into());
         }
 
@@ -300,7 +376,7 @@ impl Processor {
         let (_pda_key, bump) = Pubkey::find_program_address(&seeds, program_id);
         
         if global_state_info.data_is_empty(){
-            let size = GlobalState::get_packed_len();
+            let size = GlobalStateVersion::LATEST_LEN;
 
             Self::create_or_allocate_account_raw(
                 *program_id,
@@ -317,22 +393,57 @@ impl Processor {
             )?;
         }
 
-        let mut global_state = GlobalState::unpack_from_slice(&global_state_info.data.borrow())?;
+        if global_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut global_state = GlobalStateVersion::unpack(&global_state_info.data.borrow())?;
 
         if global_state.is_initialized == false
         {
-            global_state.owner = Pubkey::from_str(INITIAL_PROGRAM_OWNER).map_err(|_| SwapError::InvalidProgramOwner)?;
+            global_state.owner = initial_program_owner()?;
         }
         
         if global_state.owner != *current_owner_info.key
         {
             return Err(SwapError::InvalidProgramOwner.into());
         }
-        msg!("**************** validate_fees");
+        if global_state.fee_governed
+            && (fees != global_state.fees || fee_discount != global_state.fee_discount)
+        {
+            return Err(SwapError::FeesGoverned.into());
+        }
+        // the scheduler delays these, see process_execute_pending_action
+        if global_state.is_initialized
+            && (*owner != global_state.owner
+                || fees != global_state.fees
+                || fee_discount != global_state.fee_discount)
+        {
+            return Err(SwapError::PendingActionRequired.into());
+        }
+        // the deposits into empty pools and the initialization of pools
+        // already created rely on them
+        if global_state.pool_count > 0
+            && (initial_supply != global_state.initial_supply
+                || lp_decimals != global_state.lp_decimals)
+        {
+            return Err(SwapError::PoolParametersFixed.into());
+        }
+        if *fee_owner == Pubkey::default() {
+            return Err(SwapError::InvalidFeeOwner.into());
+        }
+        // validated only while they can still change, once pools exist they
+        // are the values those pools were created with
+        if global_state.pool_count == 0 {
+            if u128::from(initial_supply) <= MIN_LP_SUPPLY {
+                return Err(SwapError::InitialSupplyTooLow.into());
+            }
+            if lp_decimals > MAX_LP_DECIMALS {
+                return Err(SwapError::InvalidLpDecimals.into());
+            }
+        }
         SWAP_CONSTRAINTS.validate_fees(&fees)?;
-        msg!("**************** validate_fees1");
         fees.validate()?;
-        msg!("**************** validate_fees2");
+        fee_discount.validate()?;
         //Save the program state
         let obj = GlobalState{
             is_initialized:true,
@@ -341,540 +452,7842 @@ impl Processor {
             owner: *owner,
             fee_owner: *fee_owner,
             fees,
+            fee_discount,
+            quoter: *quoter,
+            ..global_state
         };
-        msg!("**************** validate_fees3");
-        obj.pack_into_slice(&mut &mut global_state_info.data.borrow_mut()[..]);
-        msg!("**************** validate_fees4");
+        GlobalStateVersion::pack(&obj, &mut global_state_info.data.borrow_mut())?;
         Ok(())
     }
 
-    /// Processes an [Initialize](enum.Instruction.html).
-    pub fn process_initialize(
+    /// Processes an [UpdateCurveParameters](enum.Instruction.html).
+    pub fn process_update_curve_parameters(
         program_id: &Pubkey,
-        swap_curve: SwapCurve,
+        mut swap_curve: SwapCurve,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let swap_info = next_account_info(account_info_iter)?;
-        let authority_info = next_account_info(account_info_iter)?;
-        let global_state_info = next_account_info(account_info_iter)?;
-        let token_a_info = next_account_info(account_info_iter)?;
-        let token_b_info = next_account_info(account_info_iter)?;
-        let pool_mint_info = next_account_info(account_info_iter)?;
-        let destination_info = next_account_info(account_info_iter)?;
-        let token_program_info = next_account_info(account_info_iter)?;
-        // let rent_info = next_account_info(account_info_iter)?;
-        // let rent = &Rent::from_account_info(rent_info)?;
-
-        let token_program_id = *token_program_info.key;
-        // Self::assert_rent_exempt(rent, swap_info)?;
-        if SwapVersion::is_initialized(&swap_info.data.borrow()) {
-            return Err(SwapError::AlreadyInUse.into());
-        }
-        let (_found_key, nonce) = Pubkey::find_program_address(&[swap_info.key.as_ref()], program_id);
-        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
 
-        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, global_state_info.key)?;
-        
-        let state = GlobalState::unpack_from_slice(&global_state_info.data.borrow())?;
-        if state.is_initialized() == false
-        {
-            return Err(SwapError::NotInitializedState.into());
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
 
-        let token_a = Self::unpack_token_account(token_a_info, &token_program_id)?;
-        let token_b = Self::unpack_token_account(token_b_info, &token_program_id)?;
-        let destination = Self::unpack_token_account(destination_info, &token_program_id)?;
-        let pool_mint = Self::unpack_mint(pool_mint_info, &token_program_id)?;
-        if *authority_info.key != token_a.owner {
-            return Err(SwapError::InvalidOwner.into());
-        }
-        if *authority_info.key != token_b.owner {
-            return Err(SwapError::InvalidOwner.into());
-        }
-        if *authority_info.key == destination.owner {
-            return Err(SwapError::InvalidOutputOwner.into());
+        // the amp of a live pool moves only through a scheduled SetAmp
+        if SwapVersion::is_initialized(&swap_info.data.borrow()) {
+            return Err(SwapError::PendingActionRequired.into());
         }
-        if COption::Some(*authority_info.key) != pool_mint.mint_authority {
-            return Err(SwapError::InvalidOwner.into());
+        let mut swap_version = SwapVersion::unpack_version(&swap_info.data.borrow())?;
+        let current_curve = match &mut swap_version {
+            SwapVersion::SwapV1(swap_v1) => &mut swap_v1.swap_curve,
+            SwapVersion::SwapV2(swap_v2) => &mut swap_v2.swap_curve,
+        };
+        // other curves price from parameters LPs deposited against
+        if swap_curve.curve_type != CurveType::Stable
+            && swap_curve.curve_type != CurveType::ConstantPrice
+        {
+            return Err(SwapError::UnsupportedCurveOperation.into());
         }
-
-        if token_a.mint == token_b.mint {
-            return Err(SwapError::RepeatedMint.into());
+        if swap_curve.curve_type != current_curve.curve_type {
+            return Err(SwapError::InvalidCurve.into());
         }
         SWAP_CONSTRAINTS.validate_curve(&swap_curve)?;
         swap_curve.calculator.validate()?;
-        swap_curve
-            .calculator
-            .validate_supply(token_a.amount, token_b.amount)?;
-
-        if token_a.delegate.is_some() {
-            return Err(SwapError::InvalidDelegate.into());
+        if swap_curve.curve_type == CurveType::Stable {
+            let current_stable_curve = Self::stable_curve(current_curve)?;
+            let mut stable_curve = Self::stable_curve(&swap_curve)?;
+            Self::check_amp_change(current_stable_curve.amp, stable_curve.amp)?;
+            // the decimals are those of the pool's mints
+            stable_curve.token_a_decimals = current_stable_curve.token_a_decimals;
+            stable_curve.token_b_decimals = current_stable_curve.token_b_decimals;
+            swap_curve.calculator = stable_curve.into();
         }
-        if token_b.delegate.is_some() {
-            return Err(SwapError::InvalidDelegate.into());
-        }
-        if token_a.close_authority.is_some() {
-            return Err(SwapError::InvalidCloseAuthority.into());
-        }
-        if token_b.close_authority.is_some() {
-            return Err(SwapError::InvalidCloseAuthority.into());
+        *current_curve = swap_curve;
+        SwapVersion::pack(swap_version, &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [DeprecatePool](enum.Instruction.html).
+    pub fn process_deprecate_pool(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
 
-        if token_a.is_frozen(){
-            return Err(SwapError::InvalidFreezeAuthority.into());
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.deprecated = true;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetDepositCap](enum.Instruction.html).
+    pub fn process_set_deposit_cap(
+        program_id: &Pubkey,
+        max_lp_supply: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        if token_b.is_frozen(){
-            return Err(SwapError::InvalidFreezeAuthority.into());
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.max_lp_supply = max_lp_supply;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetPoolGate](enum.Instruction.html).
+    pub fn process_set_pool_gate(
+        program_id: &Pubkey,
+        gated: bool,
+        gate_mint: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
 
-        if pool_mint.supply != 0 {
-            return Err(SwapError::InvalidSupply.into());
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.gated = gated;
+        swap_v2.gate_mint = if gated { *gate_mint } else { Pubkey::default() };
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetAllowlisted](enum.Instruction.html).
+    pub fn process_set_allowlisted(
+        program_id: &Pubkey,
+        allowed: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let entry_info = next_account_info(account_info_iter)?;
+        let wallet_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        if pool_mint.freeze_authority.is_some() {
-            return Err(SwapError::InvalidFreezeAuthority.into());
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
         }
-        // fixed
-        if pool_mint.decimals != state.lp_decimals() {
-            return Err(SwapError::MismatchDecimalValidation.into());
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
         }
 
-        let initial_amount = state.initial_supply();
-
-        Self::token_mint_to(
-            swap_info.key,
-            token_program_info.clone(),
-            pool_mint_info.clone(),
-            destination_info.clone(),
-            authority_info.clone(),
-            nonce,
-            initial_amount,
-        )?;
+        let seeds = [
+            ALLOWLIST_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            wallet_info.key.as_ref(),
+        ];
+        let (entry_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if entry_key != *entry_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
 
-        swap_curve.calculator.validate()?;
-        let obj = SwapVersion::SwapV1(SwapV1 {
-            is_initialized: true,
-            nonce,
-            token_program_id,
-            token_a: *token_a_info.key,
-            token_b: *token_b_info.key,
-            pool_mint: *pool_mint_info.key,
-            token_a_mint: token_a.mint,
-            token_b_mint: token_b.mint,
-            swap_curve,
-        });
-        SwapVersion::pack(obj, &mut swap_info.data.borrow_mut())?;
+        if allowed {
+            if !entry_info.data_is_empty() {
+                return Err(SwapError::AlreadyInUse.into());
+            }
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                entry_info,
+                rent_info,
+                system_info,
+                owner_info,
+                AllowlistEntry::get_packed_len(),
+                &[
+                    ALLOWLIST_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    wallet_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            let entry = AllowlistEntry {
+                is_initialized: true,
+                bump,
+                swap: *swap_info.key,
+                wallet: *wallet_info.key,
+                payer: *owner_info.key,
+            };
+            AllowlistEntry::pack(entry, &mut entry_info.data.borrow_mut())?;
+        } else {
+            if entry_info.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let entry = AllowlistEntry::unpack(&entry_info.data.borrow())?;
+            let rent_recipient_info = account_info_iter.next().unwrap_or(owner_info);
+            Self::check_rent_recipient(rent_recipient_info, &entry.payer, owner_info)?;
+            Self::close_account(entry_info, rent_recipient_info)?;
+        }
         Ok(())
     }
 
-    /// Processes an [Swap](enum.Instruction.html).
-    pub fn process_swap(
+    /// Processes a [SetWithdrawFee](enum.Instruction.html).
+    pub fn process_set_withdraw_fee(
         program_id: &Pubkey,
-        amount_in: u64,
-        minimum_amount_out: u64,
+        withdraw_fee_numerator: u64,
+        burn: bool,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
-        // get account info iterator
         let account_info_iter = &mut accounts.iter();
-        // get swap info
         let swap_info = next_account_info(account_info_iter)?;
-        // get authority info
-        let authority_info = next_account_info(account_info_iter)?;
-        // get user transfer autority info
-        let user_transfer_authority_info = next_account_info(account_info_iter)?;
-
+        let owner_info = next_account_info(account_info_iter)?;
         let state_info = next_account_info(account_info_iter)?;
-        // get source info
-        let source_info = next_account_info(account_info_iter)?;
-        // get swap source info
-        let swap_source_info = next_account_info(account_info_iter)?;
-        // get swap destination info
-        let swap_destination_info = next_account_info(account_info_iter)?;
-        // get destination info
-        let destination_info = next_account_info(account_info_iter)?;
-        // get pool mint info
-        let pool_mint_info = next_account_info(account_info_iter)?;
-        let fixed_fee_account_info = next_account_info(account_info_iter)?;
-        // get token program info
-        let token_program_info = next_account_info(account_info_iter)?;
-        // if swap owner is not program_id, then return incorrect program id error
+
         if swap_info.owner != program_id {
             return Err(ProgramError::IncorrectProgramId);
         }
-
-        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
-        
-        let state = GlobalState::unpack_from_slice(&state_info.data.borrow())?;
-        if state.is_initialized() == false
-        {
-            return Err(SwapError::NotInitializedState.into());
+        let state = Self::check_program_owner(program_id, state_info, owner_info)?;
+        if withdraw_fee_numerator >= state.fees().fee_denominator && withdraw_fee_numerator != 0 {
+            return Err(SwapError::InvalidFee.into());
         }
 
-        // get token_swap by swap_info.data
-        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
-        // if autority_info.key is not authority id then return invalid program address error
-        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.withdraw_fee_numerator = withdraw_fee_numerator;
+        swap_v2.withdraw_fee_burned = burn;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
 
-        // check if fee account is correct
-        let fee_token_account =
-            Self::unpack_token_account(&fixed_fee_account_info.clone(), token_swap.token_program_id())?;
-        if fee_token_account.owner != *state.fee_owner() {
-            return Err(SwapError::InvalidOwner.into());
+    /// Processes a [SetMinHoldPeriod](enum.Instruction.html).
+    pub fn process_set_min_hold_period(
+        program_id: &Pubkey,
+        min_hold_slots: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
 
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.min_hold_slots = min_hold_slots;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
 
-        // if swap_source_info.key is token a account of token_swap or 
-        // swap source info.key is token b account of token_swap then return incorrect swap account er
-        if !(*swap_source_info.key == *token_swap.token_a_account()
-            || *swap_source_info.key == *token_swap.token_b_account())
-        {
-            return Err(SwapError::IncorrectSwapAccount.into());
-        }
-        //if swap_destination_info.key is token a account of token_swap or 
-        //swap_destination_info.key is token b account of token_swap then return incorrect swap account er
-        if !(*swap_destination_info.key == *token_swap.token_a_account()
-            || *swap_destination_info.key == *token_swap.token_b_account())
-        {
-            return Err(SwapError::IncorrectSwapAccount.into());
-        }
-        // if swap source info.key is swap destination key then return invalid input error
-        if *swap_source_info.key == *swap_destination_info.key {
-            return Err(SwapError::InvalidInput.into());
+    /// Processes an [InitDepositRecord](enum.Instruction.html).
+    pub fn process_init_deposit_record(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let deposit_record_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if !payer_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
         }
-        // if swap source info key is source info key then return invalid input
-        if swap_source_info.key == source_info.key {
-            return Err(SwapError::InvalidInput.into());
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        // if swap destination info key is destination info key then return invalid input key
-        if swap_destination_info.key == destination_info.key {
-            return Err(SwapError::InvalidInput.into());
+        SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
         }
-        // if pool mint info key is not token swap pool mint
-        if *pool_mint_info.key != *token_swap.pool_mint() {
-            return Err(SwapError::IncorrectPoolMint.into());
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
         }
-        if *token_program_info.key != *token_swap.token_program_id() {
-            return Err(SwapError::IncorrectTokenProgramId.into());
+        if !deposit_record_info.data_is_empty() {
+            return Err(SwapError::AlreadyInUse.into());
         }
-        
-        let source_account =
-            Self::unpack_token_account(swap_source_info, token_swap.token_program_id())?;
-        let dest_account =
-            Self::unpack_token_account(swap_destination_info, token_swap.token_program_id())?;
-        // let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
 
-        let trade_direction = if *swap_source_info.key == *token_swap.token_a_account() {
-            TradeDirection::AtoB
-        } else {
-            TradeDirection::BtoA
-        };
-        let result = token_swap
-            .swap_curve()
-            .swap(
-                to_u128(amount_in)?,
-                to_u128(source_account.amount)?,
-                to_u128(dest_account.amount)?,
-                trade_direction,
-                state.fees()
-            )
-            .ok_or(SwapError::ZeroTradingTokens)?;
-        if result.destination_amount_swapped < to_u128(minimum_amount_out)? {
-            return Err(SwapError::ExceededSlippage.into());
+        let (deposit_record_key, bump) = Pubkey::find_program_address(
+            &[
+                DEPOSIT_RECORD_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        if deposit_record_key != *deposit_record_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
         }
-
-        Self::token_transfer(
-            swap_info.key,
-            token_program_info.clone(),
-            source_info.clone(),
-            swap_source_info.clone(),
-            user_transfer_authority_info.clone(),
-            token_swap.nonce(),
-            to_u64(result.source_amount_swapped-result.owner_fee)?,
+        Self::create_or_allocate_account_raw(
+            *program_id,
+            deposit_record_info,
+            rent_info,
+            system_info,
+            payer_info,
+            DepositRecord::get_packed_len(),
+            &[
+                DEPOSIT_RECORD_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[bump],
+            ],
         )?;
+        // a fresh record starts the holding period, so pool tokens moved to a
+        // new owner cannot skip it
+        let deposit_record = DepositRecord {
+            is_initialized: true,
+            bump,
+            swap: *swap_info.key,
+            owner: *owner_info.key,
+            last_deposit_slot: Clock::get()?.slot,
+        };
+        DepositRecord::pack(deposit_record, &mut deposit_record_info.data.borrow_mut())?;
+        Ok(())
+    }
 
-        //otherwise transfer SPL_Token
-        Self::token_transfer(
-            swap_info.key,
-            token_program_info.clone(),
-            source_info.clone(),
-            fixed_fee_account_info.clone(),
-            user_transfer_authority_info.clone(),
-            token_swap.nonce(),
-            to_u64(result.owner_fee)?,
-        )?;
+    /// Unpack the deposit record of `owner` for a pool, checking its address
+    fn unpack_deposit_record(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        owner: &Pubkey,
+        deposit_record_info: &AccountInfo,
+    ) -> Result<DepositRecord, ProgramError> {
+        if deposit_record_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let deposit_record = DepositRecord::unpack(&deposit_record_info.data.borrow())?;
+        let deposit_record_key = Pubkey::create_program_address(
+            &[
+                DEPOSIT_RECORD_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner.as_ref(),
+                &[deposit_record.bump],
+            ],
+            program_id,
+        )
+        .map_err(|_| SwapError::InvalidProgramAddress)?;
+        if deposit_record_key != *deposit_record_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        Ok(deposit_record)
+    }
 
-        //Transfer pc token from pool
-        Self::token_transfer(
-            swap_info.key,
-            token_program_info.clone(),
-            swap_destination_info.clone(),
-            destination_info.clone(),
-            authority_info.clone(),
-            token_swap.nonce(),
-            to_u64(result.destination_amount_swapped)?,
-        )?;
+    /// Processes a [SetProtocolLiquidityShare](enum.Instruction.html).
+    pub fn process_set_protocol_liquidity_share(
+        program_id: &Pubkey,
+        protocol_liquidity_bps: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
 
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+        if protocol_liquidity_bps > 10_000 {
+            return Err(SwapError::InvalidFee.into());
+        }
 
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if protocol_liquidity_bps != 0 && !swap_v2.swap_curve.calculator.allows_deposits() {
+            return Err(SwapError::UnsupportedCurveOperation.into());
+        }
+        swap_v2.protocol_liquidity_bps = protocol_liquidity_bps;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
         Ok(())
     }
-    /// Processes an [DepositAllTokenTypes](enum.Instruction.html).
-    pub fn process_deposit_all_token_types(
+
+    /// Processes a [WithdrawProtocolLiquidity](enum.Instruction.html).
+    pub fn process_withdraw_protocol_liquidity(
         program_id: &Pubkey,
         pool_token_amount: u64,
-        maximum_token_a_amount: u64,
-        maximum_token_b_amount: u64,
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let swap_info = next_account_info(account_info_iter)?;
-        let authority_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
         let state_info = next_account_info(account_info_iter)?;
-        let user_transfer_authority_info = next_account_info(account_info_iter)?;
-        let source_a_info = next_account_info(account_info_iter)?;
-        let source_b_info = next_account_info(account_info_iter)?;
-        let token_a_info = next_account_info(account_info_iter)?;
-        let token_b_info = next_account_info(account_info_iter)?;
-        let pool_mint_info = next_account_info(account_info_iter)?;
-        let dest_info = next_account_info(account_info_iter)?;
+        let protocol_liquidity_authority_info = next_account_info(account_info_iter)?;
+        let protocol_liquidity_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
 
-        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
-
-        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
-
-        let state = GlobalState::unpack_from_slice(&state_info.data.borrow())?;
-        if state.is_initialized() == false
-        {
-            return Err(SwapError::NotInitializedState.into());
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
 
-        let calculator = &token_swap.swap_curve().calculator;
-        if !calculator.allows_deposits() {
-            return Err(SwapError::UnsupportedCurveOperation.into());
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
         }
-        Self::check_accounts(
+        let bump = Self::check_protocol_liquidity_account(
+            program_id,
             token_swap.as_ref(),
+            protocol_liquidity_info,
+        )?;
+        Self::assert_pda(
+            &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref()],
             program_id,
-            swap_info,
-            authority_info,
-            token_a_info,
-            token_b_info,
-            pool_mint_info,
-            token_program_info,
-            Some(source_a_info),
-            Some(source_b_info),
+            protocol_liquidity_authority_info.key,
         )?;
-        let token_a = Self::unpack_token_account(token_a_info, token_swap.token_program_id())?;
-        let token_b = Self::unpack_token_account(token_b_info, token_swap.token_program_id())?;
-        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
-                
-        let current_pool_mint_supply = to_u128(pool_mint.supply)?;
-        let (pool_token_amount, pool_mint_supply) = if current_pool_mint_supply > 0 {
-            (to_u128(pool_token_amount)?, current_pool_mint_supply)
-        } else {
-            (to_u128(state.initial_supply())?, to_u128(state.initial_supply())?)
-        };
+        Self::token_transfer_signed(
+            token_program_info.clone(),
+            protocol_liquidity_info.clone(),
+            destination_info.clone(),
+            protocol_liquidity_authority_info.clone(),
+            &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref(), &[bump]],
+            pool_token_amount,
+        )
+    }
 
-        let results = calculator
-            .pool_tokens_to_trading_tokens(
-                pool_token_amount,
-                pool_mint_supply,
-                to_u128(token_a.amount)?,
-                to_u128(token_b.amount)?,
-                RoundDirection::Ceiling,
-            )
-            .ok_or(SwapError::ZeroTradingTokens)?;
-        let token_a_amount = to_u64(results.token_a_amount)?;
-        if token_a_amount > maximum_token_a_amount {
-            return Err(SwapError::ExceededSlippage.into());
+    /// Processes a [SetBuybackConfig](enum.Instruction.html).
+    pub fn process_set_buyback_config(
+        program_id: &Pubkey,
+        buyback_mint: &Pubkey,
+        buyback_epoch_limit: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        state.buyback_mint = *buyback_mint;
+        state.buyback_epoch_limit = buyback_epoch_limit;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetKeeperReward](enum.Instruction.html).
+    pub fn process_set_keeper_reward(
+        program_id: &Pubkey,
+        keeper_reward: u64,
+        keeper_interval_slots: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        state.keeper_reward = keeper_reward;
+        state.keeper_interval_slots = keeper_interval_slots;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetFeeRecipients](enum.Instruction.html).
+    pub fn process_set_fee_recipients(
+        program_id: &Pubkey,
+        fee_recipients: [FeeRecipient; FEE_RECIPIENT_COUNT],
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::check_program_owner(program_id, state_info, owner_info)?;
+        FeeRecipient::validate_all(&fee_recipients)?;
+
+        state.fee_recipients = fee_recipients;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [RecoverForeignTokens](enum.Instruction.html).
+    pub fn process_recover_foreign_tokens(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let foreign_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        if token_a_amount == 0 {
-            return Err(SwapError::ZeroTradingTokens.into());
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
         }
-        let token_b_amount = to_u64(results.token_b_amount)?;
-        if token_b_amount > maximum_token_b_amount {
-            return Err(SwapError::ExceededSlippage.into());
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        let foreign_account =
+            Self::unpack_token_account(foreign_info, token_swap.token_program_id())?;
+        if foreign_account.owner != *authority_info.key {
+            return Err(SwapError::InvalidOwner.into());
         }
-        if token_b_amount == 0 {
-            return Err(SwapError::ZeroTradingTokens.into());
+        // the pool's own tokens are accounted for by the vaults and the pool
+        // mint, only foreign tokens may be swept
+        if foreign_account.mint == *token_swap.token_a_mint()
+            || foreign_account.mint == *token_swap.token_b_mint()
+            || foreign_account.mint == *token_swap.pool_mint()
+        {
+            return Err(SwapError::InvalidInput.into());
         }
-
-        let pool_token_amount = to_u64(pool_token_amount)?;
-        //transfer token to pool
-        Self::token_transfer(
-            swap_info.key,
-            token_program_info.clone(),
-            source_a_info.clone(),
-            token_a_info.clone(),
-            user_transfer_authority_info.clone(),
-            token_swap.nonce(),
-            token_a_amount,
-        )?;
         Self::token_transfer(
             swap_info.key,
             token_program_info.clone(),
-            source_b_info.clone(),
-            token_b_info.clone(),
-            user_transfer_authority_info.clone(),
-            token_swap.nonce(),
-            token_b_amount,
-        )?;
-        //mint lp token to wallet
-        Self::token_mint_to(
-            swap_info.key,
-            token_program_info.clone(),
-            pool_mint_info.clone(),
-            dest_info.clone(),
+            foreign_info.clone(),
+            destination_info.clone(),
             authority_info.clone(),
             token_swap.nonce(),
-            pool_token_amount,
-        )?;
-
-        Ok(())
+            foreign_account.amount,
+        )
     }
 
-    /// Processes an [WithdrawAllTokenTypes](enum.Instruction.html).
-    pub fn process_withdraw_all_token_types(
+    /// Processes a [CommitSwap](enum.Instruction.html).
+    pub fn process_commit_swap(
         program_id: &Pubkey,
-        pool_token_amount: u64,
-        minimum_token_a_amount: u64,
-        minimum_token_b_amount: u64,
+        hash: [u8; 32],
         accounts: &[AccountInfo],
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let swap_info = next_account_info(account_info_iter)?;
-        let authority_info = next_account_info(account_info_iter)?;
-        let state_info = next_account_info(account_info_iter)?;
         let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let commitment_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if SwapVersion::unpack(&swap_info.data.borrow())?.is_deprecated() {
+            return Err(SwapError::PoolDeprecated.into());
+        }
+
+        let (commitment_key, bump) = Pubkey::find_program_address(
+            &[
+                COMMIT_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                user_transfer_authority_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        if commitment_key != *commitment_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        // a pending commitment is replaced, a new one is created
+        if commitment_info.data_is_empty() {
+            if !system_program::check_id(system_info.key) {
+                return Err(SwapError::InvalidSystemProgramId.into());
+            }
+            if !rent::check_id(rent_info.key) {
+                return Err(SwapError::InvalidRentSysvarId.into());
+            }
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                commitment_info,
+                rent_info,
+                system_info,
+                user_transfer_authority_info,
+                SwapCommitment::get_packed_len(),
+                &[
+                    COMMIT_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    user_transfer_authority_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+        } else if commitment_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let commitment = SwapCommitment {
+            is_initialized: true,
+            bump,
+            swap: *swap_info.key,
+            owner: *user_transfer_authority_info.key,
+            hash,
+            commit_slot: Clock::get()?.slot,
+        };
+        SwapCommitment::pack(commitment, &mut commitment_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [RevealSwap](enum.Instruction.html).
+    pub fn process_reveal_swap(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        salt: [u8; 32],
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let (commitment_info, swap_accounts) =
+            accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let account_info_iter = &mut swap_accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let _authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let _state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let _swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+
+        if commitment_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let commitment = SwapCommitment::unpack(&commitment_info.data.borrow())?;
+        if commitment.swap != *swap_info.key || commitment.owner != *user_transfer_authority_info.key {
+            return Err(SwapError::InvalidCommitment.into());
+        }
+        let hash = SwapCommitment::hash(
+            amount_in,
+            minimum_amount_out,
+            source_info.key,
+            swap_source_info.key,
+            destination_info.key,
+            &salt,
+        );
+        if hash != commitment.hash {
+            return Err(SwapError::InvalidCommitment.into());
+        }
+        if Clock::get()?.slot <= commitment.commit_slot {
+            return Err(SwapError::RevealTooEarly.into());
+        }
+
+        Self::process_swap(program_id, amount_in, minimum_amount_out, None, 0, None, false, swap_accounts)?;
+
+        Self::close_account(commitment_info, user_transfer_authority_info)
+    }
+
+    /// Check a vault of the batch auction, a token account of `mint` owned by
+    /// the batch auction address only
+    fn check_batch_vault(
+        batch_key: &Pubkey,
+        vault_info: &AccountInfo,
+        mint: &Pubkey,
+        token_program_id: &Pubkey,
+    ) -> ProgramResult {
+        let vault = Self::unpack_token_account(vault_info, token_program_id)?;
+        if vault.owner != *batch_key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if vault.mint != *mint {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if vault.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if vault.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+        Ok(())
+    }
+
+    /// Unpacks a batch auction, checking its owner and swap
+    fn unpack_batch_auction(
+        program_id: &Pubkey,
+        batch_info: &AccountInfo,
+        swap_info: &AccountInfo,
+    ) -> Result<BatchAuction, ProgramError> {
+        if batch_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let batch = BatchAuction::unpack_from_slice(&batch_info.data.borrow())?;
+        if !batch.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if batch.swap != *swap_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        Ok(batch)
+    }
+
+    /// Processes a [SetBatchAuction](enum.Instruction.html).
+    pub fn process_set_batch_auction(
+        program_id: &Pubkey,
+        batch_window_slots: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let token_a_vault_info = next_account_info(account_info_iter)?;
+        let token_b_vault_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        // the batch auction account and its vaults outlive the mode, so
+        // that a batch queued before switching back can still be settled
+        if batch_window_slots != 0 && batch_info.data_is_empty() {
+            if !system_program::check_id(system_info.key) {
+                return Err(SwapError::InvalidSystemProgramId.into());
+            }
+            if !rent::check_id(rent_info.key) {
+                return Err(SwapError::InvalidRentSysvarId.into());
+            }
+            let (batch_key, bump) = Pubkey::find_program_address(
+                &[BATCH_TAG.as_bytes(), swap_info.key.as_ref()],
+                program_id,
+            );
+            if batch_key != *batch_info.key {
+                return Err(SwapError::InvalidProgramAddress.into());
+            }
+            Self::check_batch_vault(
+                batch_info.key,
+                token_a_vault_info,
+                &swap_v2.token_a_mint,
+                &swap_v2.token_program_id,
+            )?;
+            Self::check_batch_vault(
+                batch_info.key,
+                token_b_vault_info,
+                &swap_v2.token_b_mint,
+                &swap_v2.token_program_id,
+            )?;
+
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                batch_info,
+                rent_info,
+                system_info,
+                owner_info,
+                BatchAuction::get_packed_len(),
+                &[BATCH_TAG.as_bytes(), swap_info.key.as_ref(), &[bump]],
+            )?;
+            let batch = BatchAuction {
+                is_initialized: true,
+                bump,
+                swap: *swap_info.key,
+                token_a_vault: *token_a_vault_info.key,
+                token_b_vault: *token_b_vault_info.key,
+                window_end_slot: 0,
+                orders: vec![],
+            };
+            batch.pack_into_slice(&mut batch_info.data.borrow_mut());
+        }
+
+        swap_v2.batch_window_slots = batch_window_slots;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SubmitBatchSwap](enum.Instruction.html).
+    pub fn process_submit_batch_swap(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let batch_vault_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.is_deprecated() {
+            return Err(SwapError::PoolDeprecated.into());
+        }
+        if token_swap.gate().is_some() {
+            return Err(SwapError::GateCheckFailed.into());
+        }
+        let batch_window_slots = token_swap
+            .batch_window_slots()
+            .ok_or(SwapError::BatchAuctionDisabled)?;
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if amount_in == 0 {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+
+        let mut batch = Self::unpack_batch_auction(program_id, batch_info, swap_info)?;
+        // the vault mint fixes the trade direction
+        let (trade_direction, destination_mint) = if *batch_vault_info.key == batch.token_a_vault {
+            (TradeDirection::AtoB, token_swap.token_b_mint())
+        } else if *batch_vault_info.key == batch.token_b_vault {
+            (TradeDirection::BtoA, token_swap.token_a_mint())
+        } else {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        };
+        let destination = Self::unpack_token_account(destination_info, token_swap.token_program_id())?;
+        if destination.mint != *destination_mint {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let source = Self::unpack_token_account(source_info, token_swap.token_program_id())?;
+
+        // the first swap of a batch opens its window
+        let current_slot = Clock::get()?.slot;
+        if !batch.orders.iter().any(BatchOrder::is_queued) {
+            batch.window_end_slot = current_slot
+                .checked_add(batch_window_slots)
+                .ok_or(SwapError::CalculationFailure)?;
+        } else if current_slot >= batch.window_end_slot {
+            return Err(SwapError::BatchWindowClosed.into());
+        }
+        if batch.orders.len() >= MAX_BATCH_ORDERS {
+            return Err(SwapError::BatchAuctionFull.into());
+        }
+        batch.orders.push(BatchOrder {
+            source: *source_info.key,
+            destination: *destination_info.key,
+            owner: source.owner,
+            trade_direction,
+            amount_in,
+            minimum_amount_out,
+            filled: false,
+            claimable: 0,
+        });
+        batch.pack_into_slice(&mut batch_info.data.borrow_mut());
+
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            batch_vault_info.clone(),
+            user_transfer_authority_info.clone(),
+            token_swap.nonce(),
+            amount_in,
+        )
+    }
+
+    /// Processes a [SettleBatch](enum.Instruction.html).
+    pub fn process_settle_batch(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let token_a_vault_info = next_account_info(account_info_iter)?;
+        let token_b_vault_info = next_account_info(account_info_iter)?;
+        let swap_token_a_info = next_account_info(account_info_iter)?;
+        let swap_token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_a_fee_info = next_account_info(account_info_iter)?;
+        let token_b_fee_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        let swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        Self::check_not_paused(&state, &swap_v2)?;
+        if swap_v2.breaker_tripped {
+            return Err(SwapError::CircuitBreakerTripped.into());
+        }
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let oracle_info = if swap_v2.external_oracle.is_enabled() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let (protocol_liquidity_info, token_a_fee_recipient_infos) =
+            Self::next_owner_fee_accounts(program_id, &swap_v2, &state, account_info_iter)?;
+        let token_b_fee_recipient_infos = state
+            .fee_recipients()
+            .map(|fee_recipient| Ok((*fee_recipient, next_account_info(account_info_iter)?)))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        let mut batch = Self::unpack_batch_auction(program_id, batch_info, swap_info)?;
+        if batch.token_a_vault != *token_a_vault_info.key || batch.token_b_vault != *token_b_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        // settled swaps left for their owners to claim take no part in the
+        // settlement
+        let (mut claims, orders): (Vec<_>, Vec<_>) = batch
+            .orders
+            .drain(..)
+            .partition(|order| !order.is_queued());
+        if orders.is_empty() {
+            return Err(SwapError::InvalidInput.into());
+        }
+        let current_slot = Clock::get()?.slot;
+        if current_slot < batch.window_end_slot {
+            return Err(SwapError::BatchWindowOpen.into());
+        }
+        let mut order_infos = Vec::with_capacity(orders.len());
+        for order in orders.iter() {
+            let source_info = next_account_info(account_info_iter)?;
+            let destination_info = next_account_info(account_info_iter)?;
+            if *source_info.key != order.source || *destination_info.key != order.destination {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            order_infos.push((source_info, destination_info));
+        }
+
+        // clear the batch, then again without the swaps whose limit the
+        // clearing price misses until every remaining swap is filled
+        let fees = state
+            .fees()
+            .with_volatility(swap_v2.oracle.current_volatility(current_slot))
+            .ok_or(SwapError::FeeCalculationFailure)?;
+        let pegged_curve = Self::pegged_curve(
+            &swap_v2.swap_curve,
+            Some(&swap_v2.external_oracle).filter(|oracle| oracle.is_enabled()),
+            oracle_info,
+            current_slot,
+        )?;
+        let swap_curve = pegged_curve.as_ref().unwrap_or(&swap_v2.swap_curve);
+        let (token_a_reserve, token_b_reserve) = Self::swap_reserves(
+            &swap_v2,
+            swap_token_a_info,
+            swap_token_b_info,
+            TradeDirection::AtoB,
+        )?;
+        let mut filled = vec![true; orders.len()];
+        let (clearing, token_a_in, token_b_in) = loop {
+            let mut token_a_in = 0u64;
+            let mut token_b_in = 0u64;
+            for (order, _) in orders.iter().zip(filled.iter()).filter(|(_, filled)| **filled) {
+                let side_in = match order.trade_direction {
+                    TradeDirection::AtoB => &mut token_a_in,
+                    TradeDirection::BtoA => &mut token_b_in,
+                };
+                *side_in = side_in
+                    .checked_add(order.amount_in)
+                    .ok_or(SwapError::CalculationFailure)?;
+            }
+            let clearing = get_batch_clearing(
+                token_a_reserve,
+                token_b_reserve,
+                token_a_in,
+                token_b_in,
+                &fees,
+                swap_curve,
+            )
+            .ok_or(SwapError::CalculationFailure)?;
+            let mut missed = false;
+            for (order, filled) in orders.iter().zip(filled.iter_mut()).filter(|(_, filled)| **filled) {
+                let amount_out = clearing
+                    .amount_out(order.trade_direction, order.amount_in, token_a_in, token_b_in)
+                    .ok_or(SwapError::CalculationFailure)?;
+                if amount_out < to_u128(order.minimum_amount_out)? {
+                    *filled = false;
+                    missed = true;
+                }
+            }
+            if !missed {
+                break (clearing, token_a_in, token_b_in);
+            }
+        };
+
+        let signer_seeds: &[&[u8]] = &[BATCH_TAG.as_bytes(), swap_info.key.as_ref(), &[batch.bump]];
+        if let Some((trade_direction, amount_in)) = clearing.pool_trade {
+            let (
+                vault_in_info,
+                vault_out_info,
+                swap_source_info,
+                swap_destination_info,
+                fee_info,
+                fee_recipient_infos,
+            ) = match trade_direction {
+                TradeDirection::AtoB => (
+                    token_a_vault_info,
+                    token_b_vault_info,
+                    swap_token_a_info,
+                    swap_token_b_info,
+                    token_a_fee_info,
+                    token_a_fee_recipient_infos,
+                ),
+                TradeDirection::BtoA => (
+                    token_b_vault_info,
+                    token_a_vault_info,
+                    swap_token_b_info,
+                    swap_token_a_info,
+                    token_b_fee_info,
+                    token_b_fee_recipient_infos,
+                ),
+            };
+            Self::escrow_swap(
+                program_id,
+                &PoolSwapAccounts {
+                    swap_info,
+                    authority_info,
+                    source_info: vault_in_info,
+                    source_authority_info: batch_info,
+                    swap_source_info,
+                    swap_destination_info,
+                    destination_info: vault_out_info,
+                    pool_mint_info,
+                    fixed_fee_account_info: Some(fee_info),
+                    protocol_liquidity_info,
+                    fee_recipient_infos,
+                    oracle_info,
+                    token_program_info,
+                },
+                &swap_v2,
+                &state,
+                signer_seeds,
+                amount_in,
+                clearing.pool_amount_out,
+                SwapError::ExceededSlippage,
+            )?;
+        }
+
+        for ((order, filled), (source_info, destination_info)) in
+            orders.iter().zip(filled.iter()).zip(order_infos.iter())
+        {
+            let (source_vault_info, source_mint, destination_vault_info, destination_mint) =
+                match order.trade_direction {
+                    TradeDirection::AtoB => (
+                        token_a_vault_info,
+                        &swap_v2.token_a_mint,
+                        token_b_vault_info,
+                        &swap_v2.token_b_mint,
+                    ),
+                    TradeDirection::BtoA => (
+                        token_b_vault_info,
+                        &swap_v2.token_b_mint,
+                        token_a_vault_info,
+                        &swap_v2.token_a_mint,
+                    ),
+                };
+            let (vault_info, account_info, mint, amount) = if *filled {
+                let amount_out = clearing
+                    .amount_out(order.trade_direction, order.amount_in, token_a_in, token_b_in)
+                    .ok_or(SwapError::CalculationFailure)?;
+                (destination_vault_info, destination_info, destination_mint, to_u64(amount_out)?)
+            } else {
+                (source_vault_info, source_info, source_mint, order.amount_in)
+            };
+            // an account closed or frozen since it queued its swap must not
+            // block the rest of the batch, its tokens stay in the vault for
+            // its owner to claim
+            match Self::unpack_token_account(account_info, &swap_v2.token_program_id) {
+                Ok(account) if account.mint == *mint && !account.is_frozen() => {}
+                _ => {
+                    if amount > 0 {
+                        claims.push(BatchOrder {
+                            filled: *filled,
+                            claimable: amount,
+                            ..order.clone()
+                        });
+                    }
+                    continue;
+                }
+            }
+            if amount > 0 {
+                Self::token_transfer_signed(
+                    token_program_info.clone(),
+                    vault_info.clone(),
+                    (*account_info).clone(),
+                    batch_info.clone(),
+                    signer_seeds,
+                    amount,
+                )?;
+            }
+        }
+
+        batch.orders = claims;
+        batch.window_end_slot = 0;
+        batch.pack_into_slice(&mut batch_info.data.borrow_mut());
+        Ok(())
+    }
+
+    /// Processes a [SetRebasing](enum.Instruction.html).
+    pub fn process_set_rebasing(
+        program_id: &Pubkey,
+        rebasing: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if *token_a_info.key != swap_v2.token_a {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != swap_v2.token_b {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+        // start from the vault balances, so the drift of a pool leaving the
+        // mode is not left to skim
+        swap_v2.token_a_reserve = Self::unpack_token_account(token_a_info, &swap_v2.token_program_id)?
+            .amount
+            .saturating_sub(swap_v2.unclaimed_fee_a);
+        swap_v2.token_b_reserve = Self::unpack_token_account(token_b_info, &swap_v2.token_program_id)?
+            .amount
+            .saturating_sub(swap_v2.unclaimed_fee_b);
+        swap_v2.rebasing = rebasing;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Reads the exchange rate of a rated stable pool from its stake pool,
+    /// or the virtual price of the base pool of a metapool, into the curve.
+    /// The other accounts of the base pool follow its Token-swap.
+    fn refresh_rate<'a>(
+        program_id: &Pubkey,
+        swap_v2: &mut SwapV2,
+        rate_info: &AccountInfo<'a>,
+        account_info_iter: &mut std::slice::Iter<AccountInfo<'a>>,
+    ) -> ProgramResult {
+        if *rate_info.key != swap_v2.rate_account {
+            return Err(SwapError::InvalidRateAccount.into());
+        }
+        let mut packed = [0u8; SwapCurve::LEN];
+        swap_v2.swap_curve.pack_into_slice(&mut packed);
+        match swap_v2.swap_curve.curve_type {
+            CurveType::RatedStable => {
+                let rate = StakePoolRate::unpack(&rate_info.data.borrow())?
+                    .rate(Clock::get()?.epoch)
+                    .ok_or(SwapError::InvalidRateAccount)?;
+                let mut calculator =
+                    RatedStableCurve::unpack_from_slice(&packed[1..1 + RatedStableCurve::LEN])?;
+                calculator.rate = rate;
+                swap_v2.swap_curve.calculator = calculator.into();
+            }
+            CurveType::MetaStable => {
+                let pool_mint_info = next_account_info(account_info_iter)?;
+                let token_a_info = next_account_info(account_info_iter)?;
+                let token_b_info = next_account_info(account_info_iter)?;
+                // token B of a metapool is the pool token of its base pool
+                if *pool_mint_info.key != swap_v2.token_b_mint {
+                    return Err(SwapError::InvalidRateAccount.into());
+                }
+                let mut calculator =
+                    MetaStableCurve::unpack_from_slice(&packed[1..1 + MetaStableCurve::LEN])?;
+                calculator.base_virtual_price = Self::virtual_price(
+                    program_id,
+                    rate_info,
+                    pool_mint_info,
+                    token_a_info,
+                    token_b_info,
+                )?;
+                swap_v2.swap_curve.calculator = calculator.into();
+            }
+            _ => return Err(SwapError::UnsupportedCurveOperation.into()),
+        }
+        Ok(())
+    }
+
+    /// Processes a [SetRateAccount](enum.Instruction.html).
+    pub fn process_set_rate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let rate_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.rate_account = *rate_info.key;
+        Self::refresh_rate(program_id, &mut swap_v2, rate_info, account_info_iter)?;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes an [UpdateRate](enum.Instruction.html).
+    pub fn process_update_rate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let rate_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        Self::refresh_rate(program_id, &mut swap_v2, rate_info, account_info_iter)?;
+        Self::pay_keeper_reward(program_id, &mut swap_v2, account_info_iter.as_slice())?;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Pay the keeper of a permissionless crank of a pool the keeper reward
+    /// out of the pool's protocol-owned liquidity, when it passes the
+    /// accounts to receive it and the pool's last rewarded crank is at least
+    /// the keeper interval old. A crank that earns nothing still goes
+    /// through.
+    fn pay_keeper_reward<'a>(
+        program_id: &Pubkey,
+        swap_v2: &mut SwapV2,
+        keeper_accounts: &[AccountInfo<'a>],
+    ) -> ProgramResult {
+        let account_info_iter = &mut keeper_accounts.iter();
+        let state_info = match account_info_iter.next() {
+            Some(state_info) => state_info,
+            None => return Ok(()),
+        };
+        let protocol_liquidity_authority_info = next_account_info(account_info_iter)?;
+        let protocol_liquidity_info = next_account_info(account_info_iter)?;
+        let keeper_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let bump = Self::check_protocol_liquidity_account(program_id, swap_v2, protocol_liquidity_info)?;
+        Self::assert_pda(
+            &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref()],
+            program_id,
+            protocol_liquidity_authority_info.key,
+        )?;
+
+        let current_slot = Clock::get()?.slot;
+        if state.keeper_reward == 0
+            || current_slot.saturating_sub(swap_v2.keeper_slot)
+                < std::cmp::max(state.keeper_interval_slots, 1)
+        {
+            return Ok(());
+        }
+        let keeper_reward = std::cmp::min(
+            state.keeper_reward,
+            Self::unpack_token_account(protocol_liquidity_info, &swap_v2.token_program_id)?.amount,
+        );
+        if keeper_reward == 0 {
+            return Ok(());
+        }
+        Self::token_transfer_signed(
+            token_program_info.clone(),
+            protocol_liquidity_info.clone(),
+            keeper_info.clone(),
+            protocol_liquidity_authority_info.clone(),
+            &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref(), &[bump]],
+            keeper_reward,
+        )?;
+        swap_v2.keeper_slot = current_slot;
+        Ok(())
+    }
+
+    /// Processes a [BuybackAndBurn](enum.Instruction.html).
+    pub fn process_buyback_and_burn(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let fee_owner_info = next_account_info(account_info_iter)?;
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let fee_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let buyback_mint_info = next_account_info(account_info_iter)?;
+
+        if !fee_owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut state = Self::unpack_global_state(program_id, state_info)?;
+        if *fee_owner_info.key != *state.fee_owner() {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if *buyback_mint_info.key != state.buyback_mint {
+            return Err(SwapError::InvalidInput.into());
+        }
+        let destination_before = Self::unpack_token_account(destination_info, token_program_info.key)?;
+        if destination_before.mint != state.buyback_mint {
+            return Err(SwapError::InvalidInput.into());
+        }
+
+        // swap the fees as the fee owner, the pool's oracle, gate and
+        // protocol liquidity accounts are passed through
+        let mut swap_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            fee_owner_info.clone(),
+            state_info.clone(),
+            source_info.clone(),
+            swap_source_info.clone(),
+            swap_destination_info.clone(),
+            destination_info.clone(),
+            pool_mint_info.clone(),
+            fee_account_info.clone(),
+            token_program_info.clone(),
+        ];
+        swap_accounts.extend(account_info_iter.cloned());
+        Self::process_swap(program_id, amount_in, minimum_amount_out, None, 0, None, false, &swap_accounts)?;
+
+        let destination_after = Self::unpack_token_account(destination_info, token_program_info.key)?;
+        let amount_out = destination_after
+            .amount
+            .checked_sub(destination_before.amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        state.record_buyback(Clock::get()?.epoch, amount_out)?;
+
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        Self::token_burn(
+            swap_info.key,
+            token_program_info.clone(),
+            destination_info.clone(),
+            buyback_mint_info.clone(),
+            fee_owner_info.clone(),
+            token_swap.nonce(),
+            amount_out,
+        )?;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Check that `protocol_liquidity_info` is a pool token account of the
+    /// pool owned by the protocol liquidity authority, returning the bump of
+    /// the authority
+    fn check_protocol_liquidity_account(
+        program_id: &Pubkey,
+        token_swap: &dyn SwapState,
+        protocol_liquidity_info: &AccountInfo,
+    ) -> Result<u8, ProgramError> {
+        let (protocol_liquidity_authority, bump) = Pubkey::find_program_address(
+            &[PROTOCOL_LIQUIDITY_TAG.as_bytes(), program_id.as_ref()],
+            program_id,
+        );
+        let protocol_liquidity_account =
+            Self::unpack_token_account(protocol_liquidity_info, token_swap.token_program_id())?;
+        if protocol_liquidity_account.owner != protocol_liquidity_authority {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if protocol_liquidity_account.mint != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        Ok(bump)
+    }
+
+    /// Check that swaps and deposits are paused neither in the program nor in
+    /// the pool
+    fn check_not_paused(state: &GlobalState, token_swap: &dyn SwapState) -> ProgramResult {
+        if state.paused || token_swap.is_paused() {
+            return Err(SwapError::Paused.into());
+        }
+        Ok(())
+    }
+
+    /// Check that `signer` may pause or resume swaps and deposits: the
+    /// program owner may do both, the guardian may only pause
+    fn check_pause_authority(state: &GlobalState, signer: &Pubkey, paused: bool) -> ProgramResult {
+        if *signer == *state.owner()
+            || (paused && *state.guardian() != Pubkey::default() && *signer == *state.guardian())
+        {
+            Ok(())
+        } else {
+            Err(SwapError::InvalidProgramOwner.into())
+        }
+    }
+
+    /// Check that `wallet` passes the gate of a gated pool, through either
+    /// its allowlist entry or a token account of the gate mint it owns
+    fn check_gate(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        token_swap: &dyn SwapState,
+        wallet: &Pubkey,
+        gate_info: &AccountInfo,
+    ) -> ProgramResult {
+        let gate_mint = match token_swap.gate() {
+            Some(gate_mint) => gate_mint,
+            None => return Ok(()),
+        };
+        if gate_info.owner == program_id {
+            let entry = AllowlistEntry::unpack(&gate_info.data.borrow())?;
+            if entry.swap == *swap_info.key && entry.wallet == *wallet {
+                return Ok(());
+            }
+        } else if *gate_mint != Pubkey::default() {
+            let holder_account =
+                Self::unpack_token_account(gate_info, token_swap.token_program_id())?;
+            if holder_account.mint == *gate_mint
+                && holder_account.owner == *wallet
+                && holder_account.amount > 0
+            {
+                return Ok(());
+            }
+        }
+        Err(SwapError::GateCheckFailed.into())
+    }
+
+    /// Source and destination reserves of a trade against a pool, the
+    /// balances of its vaults for a rebasing pool
+    fn swap_reserves<'a>(
+        swap_v2: &SwapV2,
+        swap_source_info: &AccountInfo<'a>,
+        swap_destination_info: &AccountInfo<'a>,
+        trade_direction: TradeDirection,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (token_a_reserve, token_b_reserve) = if swap_v2.rebasing {
+            let (token_a_info, token_b_info) = match trade_direction {
+                TradeDirection::AtoB => (swap_source_info, swap_destination_info),
+                TradeDirection::BtoA => (swap_destination_info, swap_source_info),
+            };
+            if *token_a_info.key != swap_v2.token_a {
+                return Err(SwapError::IncorrectTokenAVault.into());
+            }
+            if *token_b_info.key != swap_v2.token_b {
+                return Err(SwapError::IncorrectTokenBVault.into());
+            }
+            swap_v2.reserves_at(
+                Self::unpack_token_account(token_a_info, &swap_v2.token_program_id)?.amount,
+                Self::unpack_token_account(token_b_info, &swap_v2.token_program_id)?.amount,
+            )
+        } else {
+            (swap_v2.token_a_reserve, swap_v2.token_b_reserve)
+        };
+        Ok(match trade_direction {
+            TradeDirection::AtoB => (token_a_reserve, token_b_reserve),
+            TradeDirection::BtoA => (token_b_reserve, token_a_reserve),
+        })
+    }
+
+    /// Mints the `initial_amount` pool tokens of a new pool, locking
+    /// MIN_LP_SUPPLY of them in `dead_shares_info`, an account of the pool
+    /// authority, so the supply can never be burnt back down to a few shares
+    /// worth the whole pool. The rest goes to `destination_info`.
+    #[allow(clippy::too_many_arguments)]
+    fn mint_initial_pool_tokens<'a>(
+        pool: &Pubkey,
+        token_program_info: &AccountInfo<'a>,
+        pool_mint_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        dead_shares_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        nonce: u8,
+        initial_amount: u64,
+    ) -> ProgramResult {
+        let dead_shares = Self::unpack_token_account(dead_shares_info, token_program_info.key)?;
+        if dead_shares.mint != *pool_mint_info.key || dead_shares.owner != *authority_info.key {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        let locked_amount = to_u64(MIN_LP_SUPPLY)?;
+        let destination_amount = initial_amount
+            .checked_sub(locked_amount)
+            .filter(|amount| *amount > 0)
+            .ok_or(SwapError::ZeroTradingTokens)?;
+        Self::token_mint_to(
+            pool,
+            token_program_info.clone(),
+            pool_mint_info.clone(),
+            dead_shares_info.clone(),
+            authority_info.clone(),
+            nonce,
+            locked_amount,
+        )?;
+        Self::token_mint_to(
+            pool,
+            token_program_info.clone(),
+            pool_mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            nonce,
+            destination_amount,
+        )
+    }
+
+    /// Pool tokens minted for the first deposit of a pool or basket pool,
+    /// the geometric mean of the deposited `reserves`, of which
+    /// MIN_LP_SUPPLY is locked with the pool authority. Deposits whose mean
+    /// is not above MIN_LP_SUPPLY mint the legacy initial supply of the
+    /// program state instead.
+    fn initial_pool_tokens(
+        reserves: &[u128],
+        legacy_initial_supply: u64,
+    ) -> Result<u64, ProgramError> {
+        let mean = geometric_mean(reserves).ok_or(SwapError::CalculationFailure)?;
+        if mean <= MIN_LP_SUPPLY {
+            Ok(legacy_initial_supply)
+        } else {
+            Ok(to_u64(mean)?)
+        }
+    }
+
+    /// Checks both tokens of a deposit are priced like the given balances,
+    /// within `max_deviation_bps`
+    fn check_deposit_ratio(
+        token_a_amount: u64,
+        token_b_amount: u64,
+        token_a_balance: u64,
+        token_b_balance: u64,
+        max_deviation_bps: u128,
+    ) -> ProgramResult {
+        let deposit_price = to_u128(token_a_amount)?
+            .checked_mul(to_u128(token_b_balance)?)
+            .ok_or(SwapError::CalculationFailure)?;
+        let balance_price = to_u128(token_b_amount)?
+            .checked_mul(to_u128(token_a_balance)?)
+            .ok_or(SwapError::CalculationFailure)?;
+        let deviation = std::cmp::max(deposit_price, balance_price)
+            - std::cmp::min(deposit_price, balance_price);
+        if deviation
+            .checked_mul(10_000)
+            .ok_or(SwapError::CalculationFailure)?
+            > balance_price
+                .checked_mul(max_deviation_bps)
+                .ok_or(SwapError::CalculationFailure)?
+        {
+            return Err(SwapError::DepositRatioMismatch.into());
+        }
+        Ok(())
+    }
+
+    /// Parameters of a stable curve, read back from their packed form
+    fn stable_curve(swap_curve: &SwapCurve) -> Result<StableCurve, ProgramError> {
+        let mut packed = [0u8; SwapCurve::LEN];
+        swap_curve.pack_into_slice(&mut packed);
+        StableCurve::unpack_from_slice(&packed[1..1 + StableCurve::LEN])
+    }
+
+    /// Record the price a trade leaves a pool at with its oracle, failing
+    /// the trade when it moves the price past the circuit breaker
+    fn record_price(swap_v2: &mut SwapV2, current_slot: u64, price: u128) -> ProgramResult {
+        if swap_v2
+            .check_circuit_breaker(current_slot, price)
+            .ok_or(SwapError::CalculationFailure)?
+        {
+            return Err(SwapError::CircuitBreakerPriceMove.into());
+        }
+        swap_v2
+            .oracle
+            .update(current_slot, price)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Check a stable pool may move from `current_amp` to `amp` in one update
+    fn check_amp_change(current_amp: u64, amp: u64) -> ProgramResult {
+        if amp == 0 {
+            return Err(SwapError::InvalidCurve.into());
+        }
+        if amp > current_amp.saturating_mul(MAX_AMP_CHANGE_FACTOR)
+            || amp.saturating_mul(MAX_AMP_CHANGE_FACTOR) < current_amp
+        {
+            return Err(SwapError::AmpChangeTooLarge.into());
+        }
+        Ok(())
+    }
+
+    /// Processes a [MigrateGlobalState](enum.Instruction.html).
+    pub fn process_migrate_global_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let global_state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, global_state_info.key)?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+        if global_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = match GlobalStateVersion::unpack_version(&global_state_info.data.borrow())? {
+            GlobalStateVersion::GlobalStateV1(state) => state,
+            GlobalStateVersion::GlobalStateV2(_) => return Err(ProgramError::InvalidAccountData),
+        };
+        if !state.is_initialized() {
+            return Err(SwapError::NotInitializedState.into());
+        }
+        if *owner_info.key != *state.owner() {
+            return Err(SwapError::InvalidProgramOwner.into());
+        }
+
+        let rent = &Rent::from_account_info(rent_info)?;
+        let required_lamports = rent
+            .minimum_balance(GlobalStateVersion::LATEST_LEN)
+            .saturating_sub(global_state_info.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(owner_info.key, global_state_info.key, required_lamports),
+                &[owner_info.clone(), global_state_info.clone(), system_info.clone()],
+            )?;
+        }
+        global_state_info.realloc(GlobalStateVersion::LATEST_LEN, true)?;
+        GlobalStateVersion::pack(&state, &mut global_state_info.data.borrow_mut())
+    }
+
+    /// Processes an [Initialize](enum.Instruction.html).
+    pub fn process_initialize(
+        program_id: &Pubkey,
+        mut swap_curve: SwapCurve,
+        fee_owner: Option<Pubkey>,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let global_state_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let token_a_mint_info = next_account_info(account_info_iter)?;
+        let token_b_mint_info = next_account_info(account_info_iter)?;
+        let dead_shares_info = next_account_info(account_info_iter)?;
+        // let rent_info = next_account_info(account_info_iter)?;
+        // let rent = &Rent::from_account_info(rent_info)?;
+
+        let token_program_id = *token_program_info.key;
+        // Self::assert_rent_exempt(rent, swap_info)?;
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if SwapVersion::is_initialized(&swap_info.data.borrow()) {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        let (_found_key, nonce) = Pubkey::find_program_address(&[swap_info.key.as_ref()], program_id);
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, global_state_info.key)?;
+        
+        if global_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut state = GlobalStateVersion::unpack(&global_state_info.data.borrow())?;
+        if state.is_initialized() == false
+        {
+            return Err(SwapError::NotInitializedState.into());
+        }
+        // only the program owner may route the fees of a pool elsewhere
+        if let Some(fee_owner) = fee_owner {
+            let program_owner_info = next_account_info(account_info_iter)?;
+            if !program_owner_info.is_signer || *program_owner_info.key != *state.owner() {
+                return Err(SwapError::InvalidProgramOwner.into());
+            }
+            if fee_owner == Pubkey::default() || fee_owner == *authority_info.key {
+                return Err(SwapError::InvalidOwner.into());
+            }
+        }
+
+        let token_a = Self::unpack_token_account(token_a_info, &token_program_id)?;
+        let token_b = Self::unpack_token_account(token_b_info, &token_program_id)?;
+        let destination = Self::unpack_token_account(destination_info, &token_program_id)?;
+        let pool_mint = Self::unpack_mint(pool_mint_info, &token_program_id)?;
+        if *authority_info.key != token_a.owner {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if *authority_info.key != token_b.owner {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if *authority_info.key == destination.owner {
+            return Err(SwapError::InvalidOutputOwner.into());
+        }
+        if COption::Some(*authority_info.key) != pool_mint.mint_authority {
+            return Err(SwapError::InvalidOwner.into());
+        }
+
+        if token_a.mint == token_b.mint {
+            return Err(SwapError::RepeatedMint.into());
+        }
+        if *token_a_mint_info.key != token_a.mint || *token_b_mint_info.key != token_b.mint {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        // Token-2022 mints may carry extensions pools cannot honour
+        if token_program_id == TOKEN_2022_PROGRAM_ID {
+            Self::unpack_mint(token_a_mint_info, &token_program_id)?;
+            Self::unpack_mint(token_b_mint_info, &token_program_id)?;
+        }
+        // the stable invariant compares the reserves at a common precision
+        if swap_curve.curve_type == CurveType::Stable {
+            let mut stable_curve = Self::stable_curve(&swap_curve)?;
+            stable_curve.token_a_decimals =
+                Self::unpack_mint(token_a_mint_info, &token_program_id)?.decimals;
+            stable_curve.token_b_decimals =
+                Self::unpack_mint(token_b_mint_info, &token_program_id)?.decimals;
+            swap_curve.calculator = stable_curve.into();
+        }
+        SWAP_CONSTRAINTS.validate_curve(&swap_curve)?;
+        swap_curve.calculator.validate()?;
+        swap_curve
+            .calculator
+            .validate_supply(token_a.amount, token_b.amount)?;
+
+        // a delegate could drain the vaults, user accounts may have one and
+        // fund trades through it as the user transfer authority
+        if token_a.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if token_b.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if token_a.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+        if token_b.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+
+        // token mints with a freeze authority, as regulated tokens require,
+        // are accepted in any pool; only vaults frozen already, which could
+        // not trade, are rejected
+        if token_a.is_frozen(){
+            return Err(SwapError::InvalidFreezeAuthority.into());
+        }
+        if token_b.is_frozen(){
+            return Err(SwapError::InvalidFreezeAuthority.into());
+        }
+
+        if pool_mint.supply != 0 {
+            return Err(SwapError::InvalidSupply.into());
+        }
+        if pool_mint.freeze_authority.is_some() {
+            return Err(SwapError::InvalidFreezeAuthority.into());
+        }
+        if pool_mint.decimals > MAX_LP_DECIMALS {
+            return Err(SwapError::MismatchDecimalValidation.into());
+        }
+
+        let initial_amount = Self::initial_pool_tokens(
+            &[to_u128(token_a.amount)?, to_u128(token_b.amount)?],
+            state.initial_supply(),
+        )?;
+        Self::mint_initial_pool_tokens(
+            swap_info.key,
+            token_program_info,
+            pool_mint_info,
+            destination_info,
+            dead_shares_info,
+            authority_info,
+            nonce,
+            initial_amount,
+        )?;
+
+        swap_curve.calculator.validate()?;
+        let initial_price = PriceOracle::spot_price(to_u128(token_a.amount)?, to_u128(token_b.amount)?)
+            .ok_or(SwapError::CalculationFailure)?;
+        let obj = SwapVersion::SwapV2(SwapV2 {
+            is_initialized: true,
+            nonce,
+            token_program_id,
+            token_a: *token_a_info.key,
+            token_b: *token_b_info.key,
+            pool_mint: *pool_mint_info.key,
+            token_a_mint: token_a.mint,
+            token_b_mint: token_b.mint,
+            swap_curve,
+            oracle: PriceOracle::new(Clock::get()?.slot, initial_price),
+            token_a_reserve: token_a.amount,
+            token_b_reserve: token_b.amount,
+            rebalance_epoch: 0,
+            rebalance_amount: 0,
+            external_oracle: ExternalOracle::default(),
+            quote_nonce: 0,
+            last_swap_slot: 0,
+            last_price: 0,
+            deprecated: false,
+            max_lp_supply: 0,
+            gated: false,
+            gate_mint: Pubkey::default(),
+            withdraw_fee_numerator: 0,
+            withdraw_fee_burned: false,
+            min_hold_slots: 0,
+            protocol_liquidity_bps: 0,
+            batch_window_slots: 0,
+            rebasing: false,
+            rate_account: Pubkey::default(),
+            position_vault: Pubkey::default(),
+            claimable_fees: false,
+            position_pool_tokens: 0,
+            fee_growth_a: 0,
+            fee_growth_b: 0,
+            unclaimed_fee_a: 0,
+            unclaimed_fee_b: 0,
+            nft_positions: false,
+            paused: false,
+            max_slot_volume: 0,
+            volume_slot: 0,
+            slot_volume: 0,
+            breaker_move_bps: 0,
+            breaker_window_slots: 0,
+            breaker_slot: 0,
+            breaker_price: 0,
+            breaker_tripped: false,
+            fee_owner: fee_owner.unwrap_or_default(),
+            owner_fee_in_pool_tokens: false,
+            keeper_slot: 0,
+        });
+        SwapVersion::pack(obj, &mut swap_info.data.borrow_mut())?;
+        state.record_pool()?;
+        GlobalStateVersion::pack(&state, &mut global_state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes an [Swap](enum.Instruction.html).
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_swap(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        max_price_impact_bps: Option<u64>,
+        tip: u64,
+        expected_trade_direction: Option<TradeDirection>,
+        fee_discount: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        // get account info iterator
+        let account_info_iter = &mut accounts.iter();
+        // get swap info
+        let swap_info = next_account_info(account_info_iter)?;
+        // get authority info
+        let authority_info = next_account_info(account_info_iter)?;
+        // get user transfer autority info
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+
+        let state_info = next_account_info(account_info_iter)?;
+        // get source info
+        let source_info = next_account_info(account_info_iter)?;
+        // get swap source info
+        let swap_source_info = next_account_info(account_info_iter)?;
+        // get swap destination info
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        // get destination info
+        let destination_info = next_account_info(account_info_iter)?;
+        // get pool mint info
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        // the fee account is left out when the swap charges no owner fee,
+        // the token program then following the pool mint
+        let fee_or_token_program_info = next_account_info(account_info_iter)?;
+        let (fixed_fee_account_info, token_program_info) =
+            if token_2022::is_token_program(fee_or_token_program_info.key) {
+                (None, fee_or_token_program_info)
+            } else {
+                (Some(fee_or_token_program_info), next_account_info(account_info_iter)?)
+            };
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        // if swap owner is not program_id, then return incorrect program id error
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
+        
+        if state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = GlobalStateVersion::unpack(&state_info.data.borrow())?;
+        if state.is_initialized() == false
+        {
+            return Err(SwapError::NotInitializedState.into());
+        }
+
+        // get token_swap by swap_info.data
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.batch_window_slots().is_some() {
+            return Err(SwapError::BatchAuctionEnabled.into());
+        }
+        // get oracle info, required when the pool has one
+        let oracle_info = if token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        // get gate info, required when the pool is gated
+        if token_swap.gate().is_some() {
+            let gate_info = next_account_info(account_info_iter)?;
+            Self::check_gate(
+                program_id,
+                swap_info,
+                token_swap.as_ref(),
+                user_transfer_authority_info.key,
+                gate_info,
+            )?;
+        }
+        let (protocol_liquidity_info, fee_recipient_infos) = Self::next_owner_fee_accounts(
+            program_id,
+            token_swap.as_ref(),
+            &state,
+            account_info_iter,
+        )?;
+        // get relayer tip account info, required when the swap carries a tip
+        let tip_info = if tip > 0 {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        // get fee discount holder info, required when the swap claims the
+        // discount
+        let discount_holder_info = if fee_discount {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+
+        // holders of the discount token trade at a reduced fixed fee
+        let fee_discount_bps = match discount_holder_info {
+            Some(discount_holder_info) => {
+                let fee_discount = state.fee_discount();
+                let holder_account =
+                    Self::unpack_token_account(discount_holder_info, token_swap.token_program_id())?;
+                if !fee_discount.is_enabled()
+                    || holder_account.mint != fee_discount.mint
+                    || holder_account.owner != *user_transfer_authority_info.key
+                    || holder_account.amount == 0
+                    || holder_account.amount < fee_discount.minimum_balance
+                {
+                    return Err(SwapError::InvalidFeeDiscountAccount.into());
+                }
+                Some(fee_discount.discount_bps)
+            }
+            None => None,
+        };
+
+        Self::swap_through_pool(
+            program_id,
+            &PoolSwapAccounts {
+                swap_info,
+                authority_info,
+                source_info,
+                source_authority_info: user_transfer_authority_info,
+                swap_source_info,
+                swap_destination_info,
+                destination_info,
+                pool_mint_info,
+                fixed_fee_account_info,
+                protocol_liquidity_info,
+                fee_recipient_infos,
+                oracle_info,
+                token_program_info,
+            },
+            token_swap.as_ref(),
+            &state,
+            None,
+            amount_in,
+            minimum_amount_out,
+            max_price_impact_bps,
+            expected_trade_direction,
+            fee_discount_bps,
+            None,
+            SwapError::ExceededSlippage,
+        )?;
+
+        // pay the relayer that submitted the transaction
+        if let Some(tip_info) = tip_info {
+            if tip_info.key == swap_source_info.key
+                || fixed_fee_account_info.map(|info| info.key) == Some(tip_info.key)
+            {
+                return Err(SwapError::InvalidInput.into());
+            }
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                tip_info.clone(),
+                user_transfer_authority_info.clone(),
+                token_swap.nonce(),
+                tip,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the accounts sharing the owner fee of a swap with the fee
+    /// account: the protocol liquidity account, required when the pool
+    /// converts owner fees into protocol-owned liquidity, then the token
+    /// account of each fee recipient
+    #[allow(clippy::type_complexity)]
+    fn next_owner_fee_accounts<'a, 'b, I: Iterator<Item = &'b AccountInfo<'a>>>(
+        program_id: &Pubkey,
+        token_swap: &dyn SwapState,
+        state: &GlobalState,
+        account_info_iter: &mut I,
+    ) -> Result<(Option<&'b AccountInfo<'a>>, Vec<(FeeRecipient, &'b AccountInfo<'a>)>), ProgramError>
+    {
+        let protocol_liquidity_info = if token_swap.protocol_liquidity_bps().is_some() {
+            let protocol_liquidity_info = next_account_info(account_info_iter)?;
+            Self::check_protocol_liquidity_account(program_id, token_swap, protocol_liquidity_info)?;
+            Some(protocol_liquidity_info)
+        } else {
+            None
+        };
+        let fee_recipient_infos = state
+            .fee_recipients()
+            .map(|fee_recipient| Ok((*fee_recipient, next_account_info(account_info_iter)?)))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+        Ok((protocol_liquidity_info, fee_recipient_infos))
+    }
+
+    /// Swap `amount_in` of the SOURCE account through the pool, under the
+    /// rules every swap of the program follows, returning the output. The
+    /// SOURCE authority signs with `source_signer_seeds` when it is a program
+    /// address, and the input the curve rounds away goes back to the SOURCE
+    /// account. A quoted fill pays out `quoted_amount_out` instead of the
+    /// output of the curve.
+    #[allow(clippy::too_many_arguments)]
+    fn swap_through_pool(
+        program_id: &Pubkey,
+        accounts: &PoolSwapAccounts,
+        token_swap: &dyn SwapState,
+        state: &GlobalState,
+        source_signer_seeds: Option<&[&[u8]]>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        max_price_impact_bps: Option<u64>,
+        expected_trade_direction: Option<TradeDirection>,
+        fee_discount_bps: Option<u64>,
+        quoted_amount_out: Option<u64>,
+        slippage_error: SwapError,
+    ) -> Result<u64, ProgramError> {
+        let PoolSwapAccounts {
+            swap_info,
+            authority_info,
+            source_info,
+            source_authority_info,
+            swap_source_info,
+            swap_destination_info,
+            destination_info,
+            pool_mint_info,
+            fixed_fee_account_info,
+            protocol_liquidity_info,
+            ref fee_recipient_infos,
+            oracle_info,
+            token_program_info,
+        } = *accounts;
+        if token_swap.is_deprecated() {
+            return Err(SwapError::PoolDeprecated.into());
+        }
+        Self::check_not_paused(state, token_swap)?;
+        if token_swap.circuit_breaker_tripped() {
+            return Err(SwapError::CircuitBreakerTripped.into());
+        }
+        // if autority_info.key is not authority id then return invalid program address error
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+
+        let fee_token_account = match fixed_fee_account_info {
+            Some(fixed_fee_account_info) => {
+                let fee_token_account = Self::unpack_token_account(
+                    fixed_fee_account_info,
+                    token_swap.token_program_id(),
+                )?;
+                if fee_token_account.owner != *token_swap.fee_owner().unwrap_or(state.fee_owner()) {
+                    return Err(SwapError::InvalidOwner.into());
+                }
+                Some(fee_token_account)
+            }
+            None => None,
+        };
+
+
+        // if swap_source_info.key is token a account of token_swap or 
+        // swap source info.key is token b account of token_swap then return incorrect swap account er
+        if !(*swap_source_info.key == *token_swap.token_a_account()
+            || *swap_source_info.key == *token_swap.token_b_account())
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        //if swap_destination_info.key is token a account of token_swap or 
+        //swap_destination_info.key is token b account of token_swap then return incorrect swap account er
+        if !(*swap_destination_info.key == *token_swap.token_a_account()
+            || *swap_destination_info.key == *token_swap.token_b_account())
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        // if swap source info.key is swap destination key then return invalid input error
+        if *swap_source_info.key == *swap_destination_info.key {
+            return Err(SwapError::InvalidInput.into());
+        }
+        // if swap source info key is source info key then return source equals vault
+        if swap_source_info.key == source_info.key {
+            return Err(SwapError::SourceEqualsVault.into());
+        }
+        // if swap destination info key is destination info key then return destination equals vault
+        if swap_destination_info.key == destination_info.key {
+            return Err(SwapError::DestinationEqualsVault.into());
+        }
+        // if pool mint info key is not token swap pool mint
+        if *pool_mint_info.key != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        
+        let source_account =
+            Self::unpack_token_account(swap_source_info, token_swap.token_program_id())?;
+        let dest_account =
+            Self::unpack_token_account(swap_destination_info, token_swap.token_program_id())?;
+        // let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+
+        let trade_direction = if *swap_source_info.key == *token_swap.token_a_account() {
+            TradeDirection::AtoB
+        } else {
+            TradeDirection::BtoA
+        };
+        if let Some(expected_trade_direction) = expected_trade_direction {
+            if expected_trade_direction != trade_direction {
+                return Err(SwapError::TradeDirectionMismatch.into());
+            }
+        }
+        // the fixed fee is charged in the source token
+        let source_mint = match trade_direction {
+            TradeDirection::AtoB => token_swap.token_a_mint(),
+            TradeDirection::BtoA => token_swap.token_b_mint(),
+        };
+        // or in pool tokens when the pool mints the owner fee
+        let fee_mint = if token_swap.owner_fee_in_pool_tokens() {
+            token_swap.pool_mint()
+        } else {
+            source_mint
+        };
+        if let Some(fee_token_account) = fee_token_account {
+            if fee_token_account.mint != *fee_mint {
+                return Err(SwapError::IncorrectFeeAccountMint.into());
+            }
+        }
+        for (fee_recipient, fee_recipient_info) in fee_recipient_infos.iter() {
+            let fee_recipient_account =
+                Self::unpack_token_account(fee_recipient_info, token_swap.token_program_id())?;
+            if fee_recipient_account.owner != fee_recipient.owner {
+                return Err(SwapError::InvalidOwner.into());
+            }
+            if fee_recipient_account.mint != *source_mint {
+                return Err(SwapError::IncorrectFeeAccountMint.into());
+            }
+        }
+        // price against the tracked reserves, so tokens transferred directly
+        // to the vaults do not move the price until they are synced
+        let (token_a_reserve, token_b_reserve) = match trade_direction {
+            TradeDirection::AtoB => token_swap.reserves_at(source_account.amount, dest_account.amount),
+            TradeDirection::BtoA => token_swap.reserves_at(dest_account.amount, source_account.amount),
+        };
+        let (swap_source_amount, swap_destination_amount) = match trade_direction {
+            TradeDirection::AtoB => (token_a_reserve, token_b_reserve),
+            TradeDirection::BtoA => (token_b_reserve, token_a_reserve),
+        };
+        // scale the fixed fee with the realized volatility tracked by the pool
+        let current_slot = Clock::get()?.slot;
+        let volatility = token_swap
+            .oracle()
+            .map_or(0, |oracle| oracle.current_volatility(current_slot));
+        let mut fees = state
+            .fees()
+            .with_volatility(volatility)
+            .ok_or(SwapError::FeeCalculationFailure)?;
+        // holders of the discount token trade at a reduced fixed fee
+        if let Some(discount_bps) = fee_discount_bps {
+            fees = fees
+                .with_discount(discount_bps)
+                .ok_or(SwapError::FeeCalculationFailure)?;
+        }
+        // an oracle-pegged pool quotes around the current price of its oracle
+        let pegged_curve = Self::pegged_curve(
+            token_swap.swap_curve(),
+            token_swap.external_oracle(),
+            oracle_info,
+            current_slot,
+        )?;
+        let swap_curve = pegged_curve.as_ref().unwrap_or_else(|| token_swap.swap_curve());
+
+        // take the whole input into the pool and quote what the vault actually
+        // received, so tokens taking a cut on transfer are priced as such
+        match source_signer_seeds {
+            Some(source_signer_seeds) => Self::token_transfer_signed(
+                token_program_info.clone(),
+                source_info.clone(),
+                swap_source_info.clone(),
+                source_authority_info.clone(),
+                source_signer_seeds,
+                amount_in,
+            )?,
+            None => Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                swap_source_info.clone(),
+                source_authority_info.clone(),
+                token_swap.nonce(),
+                amount_in,
+            )?,
+        }
+        let amount_received = Self::unpack_token_account(swap_source_info, token_swap.token_program_id())?
+            .amount
+            .checked_sub(source_account.amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        let mut result = get_swap_quote(
+            token_a_reserve,
+            token_b_reserve,
+            amount_received,
+            trade_direction,
+            &fees,
+            swap_curve,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        // the quote may beat the output of the curve by at most
+        // MAX_QUOTE_DEVIATION_BPS, the fees being charged as on any swap
+        if let Some(quoted_amount_out) = quoted_amount_out {
+            let max_amount_out = result
+                .destination_amount_swapped
+                .checked_mul(to_u128(MAX_QUOTE_DEVIATION_BPS.saturating_add(10_000))?)
+                .and_then(|amount| amount.checked_div(10_000))
+                .ok_or(SwapError::CalculationFailure)?;
+            if to_u128(quoted_amount_out)? > max_amount_out {
+                return Err(SwapError::QuoteAboveCurve.into());
+            }
+            result.new_swap_destination_amount = to_u128(swap_destination_amount)?
+                .checked_sub(to_u128(quoted_amount_out)?)
+                .filter(|amount| *amount > 0)
+                .ok_or(SwapError::ZeroTradingTokens)?;
+            result.destination_amount_swapped = to_u128(quoted_amount_out)?;
+        }
+        if result.destination_amount_swapped < to_u128(minimum_amount_out)? {
+            return Err(slippage_error.into());
+        }
+        // the curve may round the input it takes down, the rest of what the
+        // vault received going back to the SOURCE account
+        let source_surplus = to_u128(amount_received)?
+            .checked_sub(result.source_amount_swapped)
+            .ok_or(SwapError::CalculationFailure)?;
+        if source_surplus > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                swap_source_info.clone(),
+                source_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                to_u64(source_surplus)?,
+            )?;
+        }
+        if let Some(max_price_impact_bps) = max_price_impact_bps {
+            let price_impact = swap_curve
+                .price_impact(
+                    result.source_amount_less_fees().ok_or(SwapError::FeeCalculationFailure)?,
+                    result.destination_amount_swapped,
+                    to_u128(swap_source_amount)?,
+                    to_u128(swap_destination_amount)?,
+                    trade_direction,
+                )
+                .ok_or(SwapError::CalculationFailure)?;
+            if price_impact > to_u128(max_price_impact_bps)? {
+                return Err(SwapError::PriceImpactExceeded.into());
+            }
+        }
+        if let (Some(external_oracle), Some(oracle_info)) = (token_swap.external_oracle(), oracle_info) {
+            Self::check_oracle_price(
+                external_oracle,
+                oracle_info,
+                result.source_amount_less_fees().ok_or(SwapError::FeeCalculationFailure)?,
+                result.destination_amount_swapped,
+                trade_direction,
+                current_slot,
+            )?;
+        }
+        // the protocol's share of the owner fee stays in the pool
+        let protocol_fee = match token_swap.protocol_liquidity_bps() {
+            Some(protocol_liquidity_bps) => result
+                .owner_fee
+                .checked_mul(to_u128(protocol_liquidity_bps)?)
+                .and_then(|fee| fee.checked_div(10_000))
+                .ok_or(SwapError::FeeCalculationFailure)?,
+            None => 0,
+        };
+        let owner_fee = result
+            .owner_fee
+            .checked_sub(protocol_fee)
+            .ok_or(SwapError::FeeCalculationFailure)?;
+
+        // pay each fee recipient its share out of the pool, the fee owner
+        // keeps the rest
+        let mut fee_owner_amount = owner_fee;
+        for (fee_recipient, fee_recipient_info) in fee_recipient_infos.iter() {
+            let fee_recipient_amount = fee_recipient
+                .share(owner_fee)
+                .ok_or(SwapError::FeeCalculationFailure)?;
+            fee_owner_amount = fee_owner_amount
+                .checked_sub(fee_recipient_amount)
+                .ok_or(SwapError::FeeCalculationFailure)?;
+            if fee_recipient_amount > 0 {
+                Self::token_transfer(
+                    swap_info.key,
+                    token_program_info.clone(),
+                    swap_source_info.clone(),
+                    (*fee_recipient_info).clone(),
+                    authority_info.clone(),
+                    token_swap.nonce(),
+                    to_u64(fee_recipient_amount)?,
+                )?;
+            }
+        }
+
+        // the fee owner's cut stays in the pool when it is paid in pool
+        // tokens
+        let retained_owner_fee = if token_swap.owner_fee_in_pool_tokens() {
+            fee_owner_amount
+        } else {
+            0
+        };
+
+        // no transfer when the fee owner is owed nothing, the fee account
+        // being required otherwise
+        if fee_owner_amount > 0 && retained_owner_fee == 0 {
+            let fixed_fee_account_info =
+                fixed_fee_account_info.ok_or(SwapError::IncorrectFeeAccount)?;
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                swap_source_info.clone(),
+                fixed_fee_account_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                to_u64(fee_owner_amount)?,
+            )?;
+        }
+
+        // mint the protocol the pool tokens of a single-sided deposit of its
+        // share, priced against the post-trade reserves
+        if let Some(protocol_liquidity_info) = protocol_liquidity_info {
+            let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+            let new_source_amount = result
+                .new_swap_source_amount
+                .checked_sub(result.owner_fee)
+                .ok_or(SwapError::CalculationFailure)?;
+            let (token_a_amount, token_b_amount) = match trade_direction {
+                TradeDirection::AtoB => (new_source_amount, result.new_swap_destination_amount),
+                TradeDirection::BtoA => (result.new_swap_destination_amount, new_source_amount),
+            };
+            let pool_token_amount = swap_curve
+                .deposit_single_token_type(
+                    protocol_fee,
+                    token_a_amount,
+                    token_b_amount,
+                    to_u128(pool_mint.supply)?,
+                    trade_direction,
+                    &fees,
+                )
+                .ok_or(SwapError::CalculationFailure)?;
+            if pool_token_amount > 0 {
+                Self::token_mint_to(
+                    swap_info.key,
+                    token_program_info.clone(),
+                    pool_mint_info.clone(),
+                    protocol_liquidity_info.clone(),
+                    authority_info.clone(),
+                    token_swap.nonce(),
+                    to_u64(pool_token_amount)?,
+                )?;
+            }
+        }
+
+        // the supply the trade fee earned by LP positions is shared over,
+        // before any pool tokens are minted for the owner fee
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => Some(swap_v2),
+            _ => None,
+        };
+        let positions_open = matches!(
+            swap_v2,
+            Some(SwapV2 { claimable_fees: true, position_pool_tokens, .. }) if position_pool_tokens > 0
+        );
+        let pool_mint_supply = if retained_owner_fee > 0 || positions_open {
+            Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?.supply
+        } else {
+            0
+        };
+
+        // mint the fee owner the pool tokens of a single-sided deposit of
+        // its cut, without the trade fee of such a deposit
+        if retained_owner_fee > 0 {
+            let fixed_fee_account_info =
+                fixed_fee_account_info.ok_or(SwapError::IncorrectFeeAccount)?;
+            let new_source_amount = result
+                .new_swap_source_amount
+                .checked_sub(owner_fee)
+                .ok_or(SwapError::CalculationFailure)?;
+            let (token_a_amount, token_b_amount) = match trade_direction {
+                TradeDirection::AtoB => (new_source_amount, result.new_swap_destination_amount),
+                TradeDirection::BtoA => (result.new_swap_destination_amount, new_source_amount),
+            };
+            let pool_token_amount = swap_curve
+                .calculator
+                .deposit_single_token_type(
+                    retained_owner_fee,
+                    token_a_amount,
+                    token_b_amount,
+                    to_u128(pool_mint_supply)?,
+                    trade_direction,
+                )
+                .ok_or(SwapError::CalculationFailure)?;
+            if pool_token_amount > 0 {
+                Self::token_mint_to(
+                    swap_info.key,
+                    token_program_info.clone(),
+                    pool_mint_info.clone(),
+                    fixed_fee_account_info.clone(),
+                    authority_info.clone(),
+                    token_swap.nonce(),
+                    to_u64(pool_token_amount)?,
+                )?;
+            }
+        }
+
+        //Transfer pc token from pool
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            swap_destination_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_swap.nonce(),
+            to_u64(result.destination_amount_swapped)?,
+        )?;
+
+        // the trade fee earned by LP positions stays in the vault, outside
+        // the reserves
+        let position_fee = match swap_v2.as_mut() {
+            Some(swap_v2) => swap_v2
+                .accrue_trade_fee(trade_direction, result.trade_fee, pool_mint_supply)
+                .ok_or(SwapError::CalculationFailure)?,
+            None => 0,
+        };
+        // the owner fee is sent to the fee account, not the pool, except for
+        // the protocol's share and a cut paid in pool tokens
+        let new_source_amount = result
+            .new_swap_source_amount
+            .checked_sub(owner_fee)
+            .and_then(|amount| amount.checked_add(retained_owner_fee))
+            .and_then(|amount| amount.checked_sub(position_fee))
+            .ok_or(SwapError::CalculationFailure)?;
+        let (token_a_amount, token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (new_source_amount, result.new_swap_destination_amount),
+            TradeDirection::BtoA => (result.new_swap_destination_amount, new_source_amount),
+        };
+        // the vaults must have moved by exactly the reserve changes of the
+        // quote, and the value of the reserves under the curve must not have
+        // dropped through the swap
+        let swap_source_balance =
+            Self::unpack_token_account(swap_source_info, token_swap.token_program_id())?.amount;
+        let swap_destination_balance =
+            Self::unpack_token_account(swap_destination_info, token_swap.token_program_id())?.amount;
+        if swap_source_balance.checked_sub(source_account.amount).map(u128::from)
+            != new_source_amount
+                .checked_sub(to_u128(swap_source_amount)?)
+                .and_then(|amount| amount.checked_add(position_fee))
+            || dest_account.amount.checked_sub(swap_destination_balance).map(u128::from)
+                != to_u128(swap_destination_amount)?.checked_sub(result.new_swap_destination_amount)
+        {
+            return Err(SwapError::InvariantViolated.into());
+        }
+        let value_before = swap_curve
+            .calculator
+            .normalized_value(to_u128(token_a_reserve)?, to_u128(token_b_reserve)?)
+            .ok_or(SwapError::CalculationFailure)?;
+        let value_after = swap_curve
+            .calculator
+            .normalized_value(token_a_amount, token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        if !value_after.greater_than_or_equal(&value_before) {
+            return Err(SwapError::InvariantViolated.into());
+        }
+
+        // record the post-trade reserves and price in the pool state
+        if let Some(mut swap_v2) = swap_v2 {
+            swap_v2.token_a_reserve = to_u64(token_a_amount)?;
+            swap_v2.token_b_reserve = to_u64(token_b_amount)?;
+            let price = PriceOracle::spot_price(token_a_amount, token_b_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            Self::record_price(&mut swap_v2, current_slot, price)?;
+            swap_v2.record_slot_volume(
+                current_slot,
+                trade_direction,
+                result.source_amount_swapped,
+                result.destination_amount_swapped,
+            )?;
+            swap_v2
+                .record_swap(
+                    current_slot,
+                    trade_direction,
+                    result.source_amount_swapped,
+                    result.destination_amount_swapped,
+                )
+                .ok_or(SwapError::CalculationFailure)?;
+            SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        }
+
+        SwapEvent {
+            swap: *swap_info.key,
+            owner: Self::unpack_token_account(source_info, token_swap.token_program_id())?.owner,
+            trade_direction,
+            amount_in: to_u64(result.source_amount_swapped)?,
+            amount_out: to_u64(result.destination_amount_swapped)?,
+            trade_fee: to_u64(result.trade_fee)?,
+            owner_fee: to_u64(result.owner_fee)?,
+            slot: current_slot,
+        }
+        .emit();
+        Ok(to_u64(result.destination_amount_swapped)?)
+    }
+    /// Processes an [DepositAllTokenTypes](enum.Instruction.html).
+    pub fn process_deposit_all_token_types(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        maximum_token_a_amount: u64,
+        maximum_token_b_amount: u64,
+        max_ratio_deviation_bps: Option<u64>,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let source_a_info = next_account_info(account_info_iter)?;
+        let source_b_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let dest_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.is_deprecated() {
+            return Err(SwapError::PoolDeprecated.into());
+        }
+        if token_swap.gate().is_some() {
+            let gate_info = next_account_info(account_info_iter)?;
+            Self::check_gate(
+                program_id,
+                swap_info,
+                token_swap.as_ref(),
+                user_transfer_authority_info.key,
+                gate_info,
+            )?;
+        }
+        let deposit_record_info = if token_swap.min_hold_slots().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
+
+        if state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = GlobalStateVersion::unpack(&state_info.data.borrow())?;
+        if state.is_initialized() == false
+        {
+            return Err(SwapError::NotInitializedState.into());
+        }
+        Self::check_not_paused(&state, token_swap.as_ref())?;
+
+        let calculator = &token_swap.swap_curve().calculator;
+        if !calculator.allows_deposits() {
+            return Err(SwapError::UnsupportedCurveOperation.into());
+        }
+        Self::check_accounts(
+            token_swap.as_ref(),
+            program_id,
+            swap_info,
+            authority_info,
+            token_a_info,
+            token_b_info,
+            pool_mint_info,
+            token_program_info,
+        )?;
+        if token_a_info.key == source_a_info.key || token_b_info.key == source_b_info.key {
+            return Err(SwapError::SourceEqualsVault.into());
+        }
+        let token_a = Self::unpack_token_account(token_a_info, token_swap.token_program_id())?;
+        let token_b = Self::unpack_token_account(token_b_info, token_swap.token_program_id())?;
+        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+        let (token_a_reserve, token_b_reserve) =
+            token_swap.reserves_at(token_a.amount, token_b.amount);
+                
+        // pools initialized before Initialize locked MIN_LP_SUPPLY kept it
+        // with the first provider, who may burn it through the token program
+        // and empty the pool. The first deposit into an emptied pool locks
+        // MIN_LP_SUPPLY of its pool tokens in an account of the swap
+        // authority, as Initialize now does
+        let (pool_token_amount, pool_mint_supply, dead_shares_info) = if pool_mint.supply > 0 {
+            (pool_token_amount, pool_mint.supply, None)
+        } else {
+            let dead_shares_info = next_account_info(account_info_iter)?;
+            let dead_shares = Self::unpack_token_account(dead_shares_info, token_swap.token_program_id())?;
+            if dead_shares.mint != *pool_mint_info.key || dead_shares.owner != *authority_info.key {
+                return Err(SwapError::IncorrectPoolMint.into());
+            }
+            let initial_amount = Self::initial_pool_tokens(
+                &[to_u128(token_a_reserve)?, to_u128(token_b_reserve)?],
+                state.initial_supply(),
+            )?;
+            (initial_amount, initial_amount, Some(dead_shares_info))
+        };
+        // a pool giving out position NFTs mints the pool tokens of deposits
+        // into its position vault, under a new position owned by the NFT
+        let nft_position_infos = if let Some(position_vault) = token_swap.nft_position_vault() {
+            if *dest_info.key != *position_vault {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            Some((
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            ))
+        } else {
+            None
+        };
+
+        let results = get_deposit_quote(
+            token_a_reserve,
+            token_b_reserve,
+            pool_mint_supply,
+            pool_token_amount,
+            token_swap.swap_curve(),
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        let token_a_amount = to_u64(results.token_a_amount)?;
+        if token_a_amount > maximum_token_a_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        if token_a_amount == 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        let token_b_amount = to_u64(results.token_b_amount)?;
+        if token_b_amount > maximum_token_b_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        if token_b_amount == 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        // the rounding of the quote on small reserves may price the tokens
+        // of the deposit unlike the reserves it is quoted on, which tokens
+        // donated to the vaults leave as they are
+        Self::check_deposit_ratio(
+            token_a_amount,
+            token_b_amount,
+            token_a_reserve,
+            token_b_reserve,
+            MAX_DEPOSIT_RATIO_DEVIATION_BPS,
+        )?;
+        // the maximums the depositor signed for price the pool as they
+        // expected it, which a price move since leaves behind
+        if let Some(max_ratio_deviation_bps) = max_ratio_deviation_bps {
+            Self::check_deposit_ratio(
+                maximum_token_a_amount,
+                maximum_token_b_amount,
+                token_a_reserve,
+                token_b_reserve,
+                to_u128(max_ratio_deviation_bps)?,
+            )?;
+        }
+
+        if let Some(max_lp_supply) = token_swap.max_lp_supply() {
+            let lp_supply = pool_mint
+                .supply
+                .checked_add(pool_token_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            if lp_supply > max_lp_supply {
+                return Err(SwapError::DepositCapExceeded.into());
+            }
+        }
+        //transfer token to pool
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_a_info.clone(),
+            token_a_info.clone(),
+            user_transfer_authority_info.clone(),
+            token_swap.nonce(),
+            token_a_amount,
+        )?;
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_b_info.clone(),
+            token_b_info.clone(),
+            user_transfer_authority_info.clone(),
+            token_swap.nonce(),
+            token_b_amount,
+        )?;
+        let pool_token_amount = if let Some(dead_shares_info) = dead_shares_info {
+            let dead_shares = to_u64(MIN_LP_SUPPLY)?;
+            let pool_token_amount = pool_token_amount
+                .checked_sub(dead_shares)
+                .filter(|amount| *amount > 0)
+                .ok_or(SwapError::ZeroTradingTokens)?;
+            Self::token_mint_to(
+                swap_info.key,
+                token_program_info.clone(),
+                pool_mint_info.clone(),
+                dead_shares_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                dead_shares,
+            )?;
+            pool_token_amount
+        } else {
+            pool_token_amount
+        };
+        //mint lp token to wallet
+        Self::token_mint_to(
+            swap_info.key,
+            token_program_info.clone(),
+            pool_mint_info.clone(),
+            dest_info.clone(),
+            authority_info.clone(),
+            token_swap.nonce(),
+            pool_token_amount,
+        )?;
+        // restart the holding period of the owner of the minted pool tokens,
+        // or of the position NFT, who must be the depositor so that dust
+        // minted to another owner cannot hold back its withdrawals
+        if let Some(deposit_record_info) = deposit_record_info {
+            let holder_info = nft_position_infos
+                .map_or(dest_info, |(_, _, nft_destination_info, _, _, _)| nft_destination_info);
+            let dest_account = Self::unpack_token_account(holder_info, token_swap.token_program_id())?;
+            if dest_account.owner != *user_transfer_authority_info.key {
+                return Err(SwapError::HoldingPeriodOwnerMismatch.into());
+            }
+            let mut deposit_record =
+                Self::unpack_deposit_record(program_id, swap_info, &dest_account.owner, deposit_record_info)?;
+            deposit_record.last_deposit_slot = Clock::get()?.slot;
+            DepositRecord::pack(deposit_record, &mut deposit_record_info.data.borrow_mut())?;
+        }
+
+        let swap_version = SwapVersion::unpack_version(&swap_info.data.borrow())?;
+        if let SwapVersion::SwapV2(mut swap_v2) = swap_version {
+            swap_v2.token_a_reserve = token_a_reserve
+                .checked_add(token_a_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            swap_v2.token_b_reserve = token_b_reserve
+                .checked_add(token_b_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            if let Some((
+                position_info,
+                nft_mint_info,
+                nft_destination_info,
+                payer_info,
+                system_info,
+                rent_info,
+            )) = nft_position_infos
+            {
+                Self::open_nft_position(
+                    program_id,
+                    swap_info,
+                    authority_info,
+                    &mut swap_v2,
+                    position_info,
+                    nft_mint_info,
+                    nft_destination_info,
+                    payer_info,
+                    token_program_info,
+                    system_info,
+                    rent_info,
+                    pool_token_amount,
+                )?;
+            }
+            SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+
+    /// Processes an [WithdrawAllTokenTypes](enum.Instruction.html).
+    pub fn process_withdraw_all_token_types(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        minimum_token_a_amount: u64,
+        minimum_token_b_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let dest_token_a_info = next_account_info(account_info_iter)?;
+        let dest_token_b_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
+
+        if state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = GlobalStateVersion::unpack(&state_info.data.borrow())?;
+        if state.is_initialized() == false
+        {
+            return Err(SwapError::NotInitializedState.into());
+        }
+
+        Self::check_accounts(
+            token_swap.as_ref(),
+            program_id,
+            swap_info,
+            authority_info,
+            token_a_info,
+            token_b_info,
+            pool_mint_info,
+            token_program_info,
+        )?;
+        if token_a_info.key == dest_token_a_info.key || token_b_info.key == dest_token_b_info.key {
+            return Err(SwapError::DestinationEqualsVault.into());
+        }
+
+        // the fee owner's pool token account, required when the withdrawal
+        // fee is paid out rather than burned
+        let pool_fee_info = match token_swap.withdraw_fee() {
+            Some((_, false)) => {
+                let pool_fee_info = next_account_info(account_info_iter)?;
+                let pool_fee_account =
+                    Self::unpack_token_account(pool_fee_info, token_swap.token_program_id())?;
+                if pool_fee_account.owner != *token_swap.fee_owner().unwrap_or(state.fee_owner()) {
+                    return Err(SwapError::InvalidOwner.into());
+                }
+                if pool_fee_account.mint != *token_swap.pool_mint() {
+                    return Err(SwapError::IncorrectFeeAccountMint.into());
+                }
+                Some(pool_fee_info)
+            }
+            _ => None,
+        };
+        if let Some(min_hold_slots) = token_swap.min_hold_slots() {
+            let deposit_record_info = next_account_info(account_info_iter)?;
+            let source_account = Self::unpack_token_account(source_info, token_swap.token_program_id())?;
+            let deposit_record =
+                Self::unpack_deposit_record(program_id, swap_info, &source_account.owner, deposit_record_info)?;
+            let unlock_slot = deposit_record
+                .last_deposit_slot
+                .checked_add(min_hold_slots)
+                .ok_or(SwapError::CalculationFailure)?;
+            if Clock::get()?.slot < unlock_slot {
+                return Err(SwapError::HoldingPeriodNotElapsed.into());
+            }
+        }
+
+        let token_a = Self::unpack_token_account(token_a_info, token_swap.token_program_id())?;
+        let token_b = Self::unpack_token_account(token_b_info, token_swap.token_program_id())?;
+        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+        let (token_a_reserve, token_b_reserve) =
+            token_swap.reserves_at(token_a.amount, token_b.amount);
+
+        let mut pool_token_amount = to_u128(pool_token_amount)?;
+
+        //Check the minimum lp token amount
+        let max_pool_token_amount = to_u128(pool_mint.supply)?.checked_sub(MIN_LP_SUPPLY).ok_or(SwapError::CalculationFailure)?;
+        pool_token_amount = std::cmp::min(pool_token_amount, max_pool_token_amount);
+
+        // the withdrawal fee is taken out of the pool tokens before the rest
+        // are redeemed for the reserves
+        let withdraw_fee_numerator = token_swap
+            .withdraw_fee()
+            .map_or(0, |(withdraw_fee_numerator, _)| withdraw_fee_numerator);
+        let quote = get_withdraw_quote(
+            token_a_reserve,
+            token_b_reserve,
+            pool_mint.supply,
+            to_u64(pool_token_amount)?,
+            withdraw_fee_numerator,
+            state.fees(),
+            token_swap.swap_curve(),
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        let withdraw_fee = quote.withdraw_fee;
+        let redeemed_pool_token_amount = pool_token_amount
+            .checked_sub(withdraw_fee)
+            .ok_or(SwapError::FeeCalculationFailure)?;
+
+        let mut token_a_amount = to_u64(quote.token_a_amount)?;
+        if token_a_amount < minimum_token_a_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        if token_a_amount == 0 && token_a_reserve != 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        let mut token_b_amount = to_u64(quote.token_b_amount)?;
+        if token_b_amount < minimum_token_b_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        if token_b_amount == 0 && token_b_reserve != 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+
+        // a fee paid to the fee owner is moved before the burn, a burned fee
+        // is burned along with the redeemed pool tokens
+        let burn_amount = match pool_fee_info {
+            Some(pool_fee_info) if withdraw_fee > 0 => {
+                Self::token_transfer(
+                    swap_info.key,
+                    token_program_info.clone(),
+                    source_info.clone(),
+                    pool_fee_info.clone(),
+                    user_transfer_authority_info.clone(),
+                    token_swap.nonce(),
+                    to_u64(withdraw_fee)?,
+                )?;
+                redeemed_pool_token_amount
+            }
+            _ => pool_token_amount,
+        };
+        // only the locked MIN_LP_SUPPLY is left after this withdrawal, so
+        // the crumbs of rounding it leaves in the vaults could never be
+        // withdrawn
+        if to_u128(pool_mint.supply)?.checked_sub(burn_amount) == Some(MIN_LP_SUPPLY) {
+            token_a_amount = Self::sweep_dust(token_a_reserve, token_a_amount)?;
+            token_b_amount = Self::sweep_dust(token_b_reserve, token_b_amount)?;
+        }
+        Self::token_burn(
+            swap_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            pool_mint_info.clone(),
+            user_transfer_authority_info.clone(),
+            token_swap.nonce(),
+            to_u64(burn_amount)?,
+        )?;
+
+        if token_a_amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_a_info.clone(),
+                dest_token_a_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                token_a_amount,
+            )?;
+        }
+        if token_b_amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_b_info.clone(),
+                dest_token_b_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                token_b_amount,
+            )?;
+        }
+
+        let swap_version = SwapVersion::unpack_version(&swap_info.data.borrow())?;
+        if let SwapVersion::SwapV2(mut swap_v2) = swap_version {
+            swap_v2.token_a_reserve = token_a_reserve
+                .checked_sub(token_a_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            swap_v2.token_b_reserve = token_b_reserve
+                .checked_sub(token_b_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+            SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        }
+        Ok(())
+    }
+
+    /// Amount withdrawn from a reserve, plus what it leaves behind when that
+    /// is at most MAX_SWEEP_DUST, but for one unit: an empty reserve would
+    /// leave the locked supply backed by nothing and fail every deposit
+    fn sweep_dust(reserve: u64, amount: u64) -> Result<u64, ProgramError> {
+        let dust = reserve
+            .checked_sub(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        if dust > MAX_SWEEP_DUST {
+            return Ok(amount);
+        }
+        Ok(amount.max(reserve.saturating_sub(1)))
+    }
+
+    /// Check the execution price of a trade against the pool's price oracle
+    fn check_oracle_price(
+        external_oracle: &ExternalOracle,
+        oracle_info: &AccountInfo,
+        source_amount: u128,
+        destination_amount: u128,
+        trade_direction: TradeDirection,
+        current_slot: u64,
+    ) -> ProgramResult {
+        let oracle_price = Self::oracle_price(external_oracle, oracle_info, current_slot)?;
+        let (token_a_amount, token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_amount, destination_amount),
+            TradeDirection::BtoA => (destination_amount, source_amount),
+        };
+        let execution_price = PriceOracle::spot_price(token_a_amount, token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        let deviation = std::cmp::max(execution_price, oracle_price)
+            - std::cmp::min(execution_price, oracle_price);
+        let deviation_bps = deviation
+            .checked_mul(10_000)
+            .and_then(|deviation| deviation.checked_div(oracle_price))
+            .ok_or(SwapError::CalculationFailure)?;
+        if deviation_bps > to_u128(external_oracle.max_deviation_bps)? {
+            return Err(SwapError::OraclePriceDeviation.into());
+        }
+        Ok(())
+    }
+
+    /// Price of the pool's external oracle, in token B base units per token A
+    /// base unit scaled by PRICE_PRECISION
+    fn oracle_price(
+        external_oracle: &ExternalOracle,
+        oracle_info: &AccountInfo,
+        current_slot: u64,
+    ) -> Result<u128, ProgramError> {
+        if *oracle_info.key != external_oracle.price_account {
+            return Err(SwapError::InvalidOracle.into());
+        }
+        let oracle_price = PythPrice::unpack(&oracle_info.data.borrow())?
+            .pool_price(
+                external_oracle.token_a_decimals,
+                external_oracle.token_b_decimals,
+                current_slot,
+            )
+            .ok_or(SwapError::InvalidOracle)?;
+        Ok(oracle_price)
+    }
+
+    /// Curve of an oracle-pegged pool pegged to the current oracle price, or
+    /// None for the other curves. An oracle-pegged pool cannot swap until its
+    /// external oracle is set, with a maximum deviation above its spread.
+    fn pegged_curve(
+        swap_curve: &SwapCurve,
+        external_oracle: Option<&ExternalOracle>,
+        oracle_info: Option<&AccountInfo>,
+        current_slot: u64,
+    ) -> Result<Option<SwapCurve>, ProgramError> {
+        if swap_curve.curve_type != CurveType::OraclePegged {
+            return Ok(None);
+        }
+        let (external_oracle, oracle_info) = match (external_oracle, oracle_info) {
+            (Some(external_oracle), Some(oracle_info)) => (external_oracle, oracle_info),
+            _ => return Err(SwapError::InvalidOracle.into()),
+        };
+        let mut packed = [0u8; SwapCurve::LEN];
+        swap_curve.pack_into_slice(&mut packed);
+        let mut calculator =
+            OraclePeggedCurve::unpack_from_slice(&packed[1..1 + OraclePeggedCurve::LEN])?;
+        calculator.price = Self::oracle_price(external_oracle, oracle_info, current_slot)?;
+        Ok(Some(SwapCurve {
+            curve_type: CurveType::OraclePegged,
+            calculator: calculator.into(),
+        }))
+    }
+
+    /// Processes a [SetPoolOracle](enum.Instruction.html).
+    pub fn process_set_pool_oracle(
+        program_id: &Pubkey,
+        max_deviation_bps: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let oracle_info = next_account_info(account_info_iter)?;
+        let token_a_mint_info = next_account_info(account_info_iter)?;
+        let token_b_mint_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.external_oracle = if max_deviation_bps == 0 {
+            ExternalOracle::default()
+        } else {
+            if *token_a_mint_info.key != swap_v2.token_a_mint
+                || *token_b_mint_info.key != swap_v2.token_b_mint
+            {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            let token_a_mint = Self::unpack_mint(token_a_mint_info, &swap_v2.token_program_id)?;
+            let token_b_mint = Self::unpack_mint(token_b_mint_info, &swap_v2.token_program_id)?;
+            let external_oracle = ExternalOracle {
+                price_account: *oracle_info.key,
+                max_deviation_bps,
+                token_a_decimals: token_a_mint.decimals,
+                token_b_decimals: token_b_mint.decimals,
+            };
+            // the feed must be readable and trading when it is set
+            PythPrice::unpack(&oracle_info.data.borrow())?
+                .pool_price(
+                    external_oracle.token_a_decimals,
+                    external_oracle.token_b_decimals,
+                    Clock::get()?.slot,
+                )
+                .ok_or(SwapError::InvalidOracle)?;
+            external_oracle
+        };
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SplitSwap](enum.Instruction.html).
+    pub fn process_split_swap(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        first_pool_bps: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let first_pool_infos = [
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        ];
+        let second_pool_infos = [
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+            next_account_info(account_info_iter)?,
+        ];
+        // the fee recipient accounts are shared by both pools
+        let fee_recipient_count = Self::unpack_global_state(program_id, state_info)?
+            .fee_recipients()
+            .count();
+        let fee_recipient_infos = (0..fee_recipient_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        if first_pool_bps > 10_000 || first_pool_infos[0].key == second_pool_infos[0].key {
+            return Err(SwapError::InvalidSplitRoute.into());
+        }
+        // both pools trade the same pair the same way; each swap checks its
+        // vaults are those of its pool
+        for (first_info, second_info) in first_pool_infos[2..4].iter().zip(&second_pool_infos[2..4]) {
+            let first_mint = Self::unpack_token_account(first_info, token_program_info.key)?.mint;
+            let second_mint = Self::unpack_token_account(second_info, token_program_info.key)?.mint;
+            if first_mint != second_mint {
+                return Err(SwapError::InvalidSplitRoute.into());
+            }
+        }
+        let first_amount_in = to_u64(
+            to_u128(amount_in)?
+                .checked_mul(to_u128(first_pool_bps)?)
+                .and_then(|amount| amount.checked_div(10_000))
+                .ok_or(SwapError::CalculationFailure)?,
+        )?;
+        let second_amount_in = amount_in
+            .checked_sub(first_amount_in)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        let destination_before =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        for (pool_infos, pool_amount_in) in [
+            (first_pool_infos, first_amount_in),
+            (second_pool_infos, second_amount_in),
+        ]
+        .iter()
+        {
+            let [
+                swap_info,
+                authority_info,
+                swap_source_info,
+                swap_destination_info,
+                pool_mint_info,
+                fee_account_info,
+            ] = pool_infos;
+            if swap_info.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            // the pool's oracle and gate accounts, if any, follow the pool accounts
+            let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+            let oracle_info = if token_swap.external_oracle().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            let gate_info = if token_swap.gate().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            let protocol_liquidity_info = if token_swap.protocol_liquidity_bps().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            if *pool_amount_in == 0 {
+                continue;
+            }
+            // each pool pays at least its share of the minimum, so neither
+            // leg can be moved against the trader while the other covers it
+            let pool_minimum_amount_out = to_u64(
+                to_u128(minimum_amount_out)?
+                    .checked_mul(to_u128(*pool_amount_in)?)
+                    .and_then(|amount| amount.checked_div(u128::from(amount_in)))
+                    .ok_or(SwapError::CalculationFailure)?,
+            )?;
+            let mut swap_accounts = vec![
+                (*swap_info).clone(),
+                (*authority_info).clone(),
+                user_transfer_authority_info.clone(),
+                state_info.clone(),
+                source_info.clone(),
+                (*swap_source_info).clone(),
+                (*swap_destination_info).clone(),
+                destination_info.clone(),
+                (*pool_mint_info).clone(),
+                (*fee_account_info).clone(),
+                token_program_info.clone(),
+            ];
+            if let Some(oracle_info) = oracle_info {
+                swap_accounts.push(oracle_info.clone());
+            }
+            if let Some(gate_info) = gate_info {
+                swap_accounts.push(gate_info.clone());
+            }
+            if let Some(protocol_liquidity_info) = protocol_liquidity_info {
+                swap_accounts.push(protocol_liquidity_info.clone());
+            }
+            for fee_recipient_info in fee_recipient_infos.iter() {
+                swap_accounts.push((*fee_recipient_info).clone());
+            }
+            Self::process_swap(
+                program_id,
+                *pool_amount_in,
+                pool_minimum_amount_out,
+                None,
+                0,
+                None,
+                false,
+                &swap_accounts,
+            )?;
+        }
+
+        let destination_after =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        let amount_out = destination_after
+            .checked_sub(destination_before)
+            .ok_or(SwapError::CalculationFailure)?;
+        if amount_out < minimum_amount_out {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        Ok(())
+    }
+
+    /// Processes a [SwapWithQuote](enum.Instruction.html).
+    pub fn process_swap_with_quote(
+        program_id: &Pubkey,
+        amount_in: u64,
+        amount_out: u64,
+        expiry: i64,
+        nonce: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        // the fee account is left out when the fill charges no owner fee,
+        // the token program then following the pool mint
+        let fee_or_token_program_info = next_account_info(account_info_iter)?;
+        let (fixed_fee_account_info, token_program_info) =
+            if token_2022::is_token_program(fee_or_token_program_info.key) {
+                (None, fee_or_token_program_info)
+            } else {
+                (Some(fee_or_token_program_info), next_account_info(account_info_iter)?)
+            };
+        let instructions_info = next_account_info(account_info_iter)?;
+        let quote_nonce_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+
+        let swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if swap_v2.gated {
+            return Err(SwapError::GateCheckFailed.into());
+        }
+        if swap_v2.batch_window_slots != 0 {
+            return Err(SwapError::BatchAuctionEnabled.into());
+        }
+        // get oracle info, required when the pool has one
+        let oracle_info = if swap_v2.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let (protocol_liquidity_info, fee_recipient_infos) =
+            Self::next_owner_fee_accounts(program_id, &swap_v2, &state, account_info_iter)?;
+
+        // the quote signature is verified by the ed25519 program instruction
+        // right before this one
+        let quote = Quote {
+            swap: *swap_info.key,
+            taker: *user_transfer_authority_info.key,
+            swap_source: *swap_source_info.key,
+            amount_in,
+            amount_out,
+            expiry,
+            nonce,
+        };
+        let current_index = instructions::load_current_index_checked(instructions_info)?;
+        let verify_index = current_index
+            .checked_sub(1)
+            .ok_or(SwapError::InvalidQuote)?;
+        let verify_instruction =
+            instructions::load_instruction_at_checked(usize::from(verify_index), instructions_info)?;
+        let signer = quote.verified_signer(&verify_instruction, verify_index)?;
+        if signer != *state.fee_owner()
+            && (*state.quoter() == Pubkey::default() || signer != *state.quoter())
+        {
+            return Err(SwapError::InvalidQuote.into());
+        }
+        if Clock::get()?.unix_timestamp > expiry {
+            return Err(SwapError::QuoteExpired.into());
+        }
+        Self::use_quote_nonce(
+            program_id,
+            swap_info,
+            user_transfer_authority_info,
+            quote_nonce_info,
+            system_info,
+            rent_info,
+            nonce,
+        )?;
+
+        if amount_in == 0 || amount_out == 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        Self::swap_through_pool(
+            program_id,
+            &PoolSwapAccounts {
+                swap_info,
+                authority_info,
+                source_info,
+                source_authority_info: user_transfer_authority_info,
+                swap_source_info,
+                swap_destination_info,
+                destination_info,
+                pool_mint_info,
+                fixed_fee_account_info,
+                protocol_liquidity_info,
+                fee_recipient_infos,
+                oracle_info,
+                token_program_info,
+            },
+            &swap_v2,
+            &state,
+            None,
+            amount_in,
+            amount_out,
+            None,
+            None,
+            None,
+            Some(amount_out),
+            SwapError::ExceededSlippage,
+        )?;
+        Ok(())
+    }
+
+    /// Record `nonce` as the last quote nonce the taker filled in a pool,
+    /// creating the record on the first fill, so every quote of a taker
+    /// fills at most once without touching the quotes of other takers
+    fn use_quote_nonce<'a>(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo<'a>,
+        taker_info: &AccountInfo<'a>,
+        quote_nonce_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        nonce: u64,
+    ) -> ProgramResult {
+        let mut quote_nonce = if quote_nonce_info.data_is_empty() {
+            if !system_program::check_id(system_info.key) {
+                return Err(SwapError::InvalidSystemProgramId.into());
+            }
+            if !rent::check_id(rent_info.key) {
+                return Err(SwapError::InvalidRentSysvarId.into());
+            }
+            let (quote_nonce_key, bump) = Pubkey::find_program_address(
+                &[
+                    QUOTE_NONCE_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    taker_info.key.as_ref(),
+                ],
+                program_id,
+            );
+            if quote_nonce_key != *quote_nonce_info.key {
+                return Err(SwapError::InvalidProgramAddress.into());
+            }
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                quote_nonce_info,
+                rent_info,
+                system_info,
+                taker_info,
+                QuoteNonce::get_packed_len(),
+                &[
+                    QUOTE_NONCE_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    taker_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            QuoteNonce {
+                is_initialized: true,
+                bump,
+                swap: *swap_info.key,
+                taker: *taker_info.key,
+                nonce: 0,
+            }
+        } else {
+            if quote_nonce_info.owner != program_id {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let quote_nonce = QuoteNonce::unpack(&quote_nonce_info.data.borrow())?;
+            let quote_nonce_key = Pubkey::create_program_address(
+                &[
+                    QUOTE_NONCE_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    taker_info.key.as_ref(),
+                    &[quote_nonce.bump],
+                ],
+                program_id,
+            )
+            .map_err(|_| SwapError::InvalidProgramAddress)?;
+            if quote_nonce_key != *quote_nonce_info.key {
+                return Err(SwapError::InvalidProgramAddress.into());
+            }
+            quote_nonce
+        };
+        if nonce <= quote_nonce.nonce {
+            return Err(SwapError::InvalidQuote.into());
+        }
+        quote_nonce.nonce = nonce;
+        QuoteNonce::pack(quote_nonce, &mut quote_nonce_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [GetVirtualPrice](enum.Instruction.html).
+    pub fn process_get_virtual_price(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+
+        let virtual_price =
+            Self::virtual_price(program_id, swap_info, pool_mint_info, token_a_info, token_b_info)?;
+        set_return_data(&virtual_price.to_le_bytes());
+        Ok(())
+    }
+
+    /// Virtual price of a stable pool, read from its accounts
+    fn virtual_price(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        pool_mint_info: &AccountInfo,
+        token_a_info: &AccountInfo,
+        token_b_info: &AccountInfo,
+    ) -> Result<u128, ProgramError> {
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.swap_curve().curve_type != CurveType::Stable
+            && token_swap.swap_curve().curve_type != CurveType::RatedStable
+            && token_swap.swap_curve().curve_type != CurveType::MetaStable
+        {
+            return Err(SwapError::UnsupportedCurveOperation.into());
+        }
+        if *pool_mint_info.key != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if *token_a_info.key != *token_swap.token_a_account() {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != *token_swap.token_b_account() {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+
+        let (token_a_amount, token_b_amount) = token_swap.reserves_at(
+            Self::unpack_token_account(token_a_info, token_swap.token_program_id())?.amount,
+            Self::unpack_token_account(token_b_info, token_swap.token_program_id())?.amount,
+        );
+        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+        let virtual_price = token_swap
+            .swap_curve()
+            .calculator
+            .virtual_price(
+                to_u128(token_a_amount)?,
+                to_u128(token_b_amount)?,
+                to_u128(pool_mint.supply)?,
+            )
+            .ok_or(SwapError::ZeroTradingTokens)?;
+        Ok(virtual_price)
+    }
+
+    /// Processes a [GetPoolState](enum.Instruction.html).
+    pub fn process_get_pool_state(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        let swap_version = *swap_info
+            .data
+            .borrow()
+            .first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if *pool_mint_info.key != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if *token_a_info.key != *token_swap.token_a_account() {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != *token_swap.token_b_account() {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+
+        let (token_a_reserve, token_b_reserve) = token_swap.reserves_at(
+            Self::unpack_token_account(token_a_info, token_swap.token_program_id())?.amount,
+            Self::unpack_token_account(token_b_info, token_swap.token_program_id())?.amount,
+        );
+        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+        let current_slot = Clock::get()?.slot;
+        let volatility = token_swap
+            .oracle()
+            .map_or(0, |oracle| oracle.current_volatility(current_slot));
+        let fees = state
+            .fees()
+            .with_volatility(volatility)
+            .ok_or(SwapError::FeeCalculationFailure)?;
+        let (last_swap_slot, last_price) = token_swap.last_swap().unwrap_or((0, 0));
+        let mut swap_curve = [0u8; SwapCurve::LEN];
+        token_swap.swap_curve().pack_into_slice(&mut swap_curve);
+        let view = PoolStateView {
+            schema_version: POOL_STATE_VIEW_VERSION,
+            swap_version,
+            token_a_reserve,
+            token_b_reserve,
+            pool_supply: pool_mint.supply,
+            swap_curve: SwapCurve::unpack_from_slice(&swap_curve)?,
+            fees,
+            spot_price: PriceOracle::spot_price(to_u128(token_a_reserve)?, to_u128(token_b_reserve)?)
+                .unwrap_or(0),
+            volatility,
+            last_swap_slot,
+            last_price,
+        };
+        let mut data = [0u8; PoolStateView::LEN];
+        view.pack_into_slice(&mut data);
+        set_return_data(&data);
+        Ok(())
+    }
+
+    /// Processes a [Sync](enum.Instruction.html).
+    pub fn process_sync(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if *token_a_info.key != swap_v2.token_a {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != swap_v2.token_b {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+        let token_a = Self::unpack_token_account(token_a_info, &swap_v2.token_program_id)?;
+        let token_b = Self::unpack_token_account(token_b_info, &swap_v2.token_program_id)?;
+
+        // fold the tokens transferred directly to the vaults into the reserves
+        swap_v2.token_a_reserve = token_a.amount.saturating_sub(swap_v2.unclaimed_fee_a);
+        swap_v2.token_b_reserve = token_b.amount.saturating_sub(swap_v2.unclaimed_fee_b);
+        let price = PriceOracle::spot_price(
+            to_u128(swap_v2.token_a_reserve)?,
+            to_u128(swap_v2.token_b_reserve)?,
+        )
+        .ok_or(SwapError::CalculationFailure)?;
+        swap_v2
+            .oracle
+            .update(Clock::get()?.slot, price)
+            .ok_or(SwapError::CalculationFailure)?;
+        Self::pay_keeper_reward(program_id, &mut swap_v2, account_info_iter.as_slice())?;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [Skim](enum.Instruction.html).
+    pub fn process_skim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let dest_token_a_info = next_account_info(account_info_iter)?;
+        let dest_token_b_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.reserves().is_none() {
+            return Err(SwapError::ReservesNotTracked.into());
+        }
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        if *token_a_info.key != *token_swap.token_a_account() {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != *token_swap.token_b_account() {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let token_a = Self::unpack_token_account(token_a_info, token_swap.token_program_id())?;
+        let token_b = Self::unpack_token_account(token_b_info, token_swap.token_program_id())?;
+
+        // send everything above the tracked reserves and the fees owed to LP
+        // positions to the recipients, the whole balances of a rebasing pool
+        // are reserves
+        let (token_a_reserve, token_b_reserve) =
+            token_swap.reserves_at(token_a.amount, token_b.amount);
+        let (unclaimed_fee_a, unclaimed_fee_b) = token_swap.unclaimed_fees();
+        let excess_a = token_a
+            .amount
+            .saturating_sub(token_a_reserve)
+            .saturating_sub(unclaimed_fee_a);
+        let excess_b = token_b
+            .amount
+            .saturating_sub(token_b_reserve)
+            .saturating_sub(unclaimed_fee_b);
+        if excess_a > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_a_info.clone(),
+                dest_token_a_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                excess_a,
+            )?;
+        }
+        if excess_b > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_b_info.clone(),
+                dest_token_b_info.clone(),
+                authority_info.clone(),
+                token_swap.nonce(),
+                excess_b,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [Rebalance](enum.Instruction.html).
+    pub fn process_rebalance(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let fee_owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !fee_owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if *fee_owner_info.key != *state.fee_owner() {
+            return Err(SwapError::InvalidOwner.into());
+        }
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if swap_v2.deprecated {
+            return Err(SwapError::PoolDeprecated.into());
+        }
+        if swap_v2.swap_curve.curve_type != CurveType::Stable {
+            return Err(SwapError::UnsupportedCurveOperation.into());
+        }
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let trade_direction = if *swap_source_info.key == swap_v2.token_a
+            && *swap_destination_info.key == swap_v2.token_b
+        {
+            TradeDirection::AtoB
+        } else if *swap_source_info.key == swap_v2.token_b
+            && *swap_destination_info.key == swap_v2.token_a
+        {
+            TradeDirection::BtoA
+        } else {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        };
+        if swap_source_info.key == source_info.key {
+            return Err(SwapError::SourceEqualsVault.into());
+        }
+        if swap_destination_info.key == destination_info.key {
+            return Err(SwapError::DestinationEqualsVault.into());
+        }
+
+        let (swap_source_amount, swap_destination_amount) = Self::swap_reserves(
+            &swap_v2,
+            swap_source_info,
+            swap_destination_info,
+            trade_direction,
+        )?;
+        let swap_source_amount = to_u128(swap_source_amount)?;
+        let swap_destination_amount = to_u128(swap_destination_amount)?;
+        let total_reserves = swap_source_amount
+            .checked_add(swap_destination_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        // only the short side of a pool far enough off-peg can be topped up
+        let imbalance = swap_destination_amount
+            .checked_sub(swap_source_amount)
+            .ok_or(SwapError::InvalidRebalance)?;
+        let min_imbalance = total_reserves
+            .checked_mul(MIN_REBALANCE_IMBALANCE_BPS)
+            .and_then(|amount| amount.checked_div(10_000))
+            .ok_or(SwapError::CalculationFailure)?;
+        if imbalance < min_imbalance {
+            return Err(SwapError::InvalidRebalance.into());
+        }
+
+        // bound the amount rebalanced per epoch
+        let epoch = Clock::get()?.epoch;
+        let epoch_amount = if swap_v2.rebalance_epoch == epoch {
+            swap_v2.rebalance_amount
+        } else {
+            0
+        }
+        .checked_add(amount_in)
+        .ok_or(SwapError::CalculationFailure)?;
+        let max_epoch_amount = total_reserves
+            .checked_mul(MAX_REBALANCE_BPS_PER_EPOCH)
+            .and_then(|amount| amount.checked_div(10_000))
+            .ok_or(SwapError::CalculationFailure)?;
+        if to_u128(epoch_amount)? > max_epoch_amount {
+            return Err(SwapError::RebalanceLimitExceeded.into());
+        }
+
+        let result = swap_v2
+            .swap_curve
+            .swap(
+                to_u128(amount_in)?,
+                swap_source_amount,
+                swap_destination_amount,
+                trade_direction,
+                &Fees::default(),
+            )
+            .ok_or(SwapError::ZeroTradingTokens)?;
+        if result.destination_amount_swapped < to_u128(minimum_amount_out)? {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        // the rebalance must not push the pool off-peg the other way
+        if result.new_swap_source_amount > result.new_swap_destination_amount {
+            return Err(SwapError::InvalidRebalance.into());
+        }
+
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            swap_source_info.clone(),
+            fee_owner_info.clone(),
+            swap_v2.nonce,
+            to_u64(result.source_amount_swapped)?,
+        )?;
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            swap_destination_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            swap_v2.nonce,
+            to_u64(result.destination_amount_swapped)?,
+        )?;
+
+        let (token_a_amount, token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (result.new_swap_source_amount, result.new_swap_destination_amount),
+            TradeDirection::BtoA => (result.new_swap_destination_amount, result.new_swap_source_amount),
+        };
+        swap_v2.token_a_reserve = to_u64(token_a_amount)?;
+        swap_v2.token_b_reserve = to_u64(token_b_amount)?;
+        swap_v2.rebalance_epoch = epoch;
+        swap_v2.rebalance_amount = epoch_amount;
+        let price = PriceOracle::spot_price(token_a_amount, token_b_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2
+            .oracle
+            .update(Clock::get()?.slot, price)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2
+            .record_swap(
+                Clock::get()?.slot,
+                trade_direction,
+                result.source_amount_swapped,
+                result.destination_amount_swapped,
+            )
+            .ok_or(SwapError::CalculationFailure)?;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+
+        RebalanceEvent {
+            swap: *swap_info.key,
+            swap_source: *swap_source_info.key,
+            amount_in: to_u64(result.source_amount_swapped)?,
+            amount_out: to_u64(result.destination_amount_swapped)?,
+            epoch,
+            epoch_amount,
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Processes a [LockLiquidity](enum.Instruction.html).
+    pub fn process_lock_liquidity(
+        program_id: &Pubkey,
+        amount: u64,
+        unlock_timestamp: i64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let lock_info = next_account_info(account_info_iter)?;
+        let lock_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let seeds = [
+            LOCK_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+        ];
+        let (lock_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if lock_key != *lock_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+
+        let lock_vault = Self::unpack_token_account(lock_vault_info, token_swap.token_program_id())?;
+        if lock_vault.owner != *lock_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if lock_vault.mint != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if lock_vault.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if lock_vault.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+
+        if lock_info.data_is_empty() {
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                lock_info,
+                rent_info,
+                system_info,
+                owner_info,
+                LiquidityLock::get_packed_len(),
+                &[
+                    LOCK_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    owner_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+        }
+
+        let mut lock = LiquidityLock::unpack_from_slice(&lock_info.data.borrow())?;
+        if lock.is_initialized {
+            if lock.vault != *lock_vault_info.key {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            // a lock can only ever be extended
+            if unlock_timestamp < lock.unlock_timestamp {
+                return Err(SwapError::InvalidUnlockTimestamp.into());
+            }
+        } else {
+            lock = LiquidityLock {
+                is_initialized: true,
+                bump,
+                owner: *owner_info.key,
+                swap: *swap_info.key,
+                vault: *lock_vault_info.key,
+                amount: 0,
+                unlock_timestamp,
+            };
+        }
+        if unlock_timestamp <= Clock::get()?.unix_timestamp {
+            return Err(SwapError::InvalidUnlockTimestamp.into());
+        }
+
+        lock.amount = lock
+            .amount
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        lock.unlock_timestamp = unlock_timestamp;
+        lock.pack_into_slice(&mut lock_info.data.borrow_mut());
+
+        if amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                lock_vault_info.clone(),
+                owner_info.clone(),
+                token_swap.nonce(),
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes an [UnlockLiquidity](enum.Instruction.html).
+    pub fn process_unlock_liquidity(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let lock_info = next_account_info(account_info_iter)?;
+        let lock_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if lock_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+
+        let mut lock = LiquidityLock::unpack_from_slice(&lock_info.data.borrow())?;
+        if !lock.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if lock.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if lock.swap != *swap_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if lock.vault != *lock_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if Clock::get()?.unix_timestamp < lock.unlock_timestamp {
+            return Err(SwapError::LiquidityLocked.into());
+        }
+
+        let amount = lock.amount;
+        lock.amount = 0;
+        lock.pack_into_slice(&mut lock_info.data.borrow_mut());
+
+        if amount > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                lock_vault_info.clone(),
+                destination_info.clone(),
+                lock_info.clone(),
+                &[
+                    LOCK_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    owner_info.key.as_ref(),
+                    &[lock.bump],
+                ],
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Close an escrow account and its vault, sending their rent to the
+    /// rent recipient
+    fn close_escrow<'a>(
+        order_info: &AccountInfo<'a>,
+        order_vault_info: &AccountInfo<'a>,
+        rent_recipient_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let ix = token_2022::with_token_program(
+            token_program_info.key,
+            spl_token::instruction::close_account(
+                &spl_token::id(),
+                order_vault_info.key,
+                rent_recipient_info.key,
+                order_info.key,
+                &[],
+            )?,
+        )?;
+        invoke_signed(
+            &ix,
+            &[
+                order_vault_info.clone(),
+                rent_recipient_info.clone(),
+                order_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+        Self::close_account(order_info, rent_recipient_info)
+    }
+
+    /// Close an account of the program, zeroing its data and sending its
+    /// rent to the rent recipient
+    fn close_account(account_info: &AccountInfo, rent_recipient_info: &AccountInfo) -> ProgramResult {
+        let lamports = account_info.lamports();
+        **rent_recipient_info.lamports.borrow_mut() = rent_recipient_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(SwapError::CalculationFailure)?;
+        **account_info.lamports.borrow_mut() = 0;
+        account_info.data.borrow_mut().fill(0);
+        Ok(())
+    }
+
+    /// Check the rent of a closed account goes back to the account that
+    /// paid it, unless that account signed for it to go elsewhere
+    fn check_rent_recipient(
+        rent_recipient_info: &AccountInfo,
+        payer: &Pubkey,
+        signer_info: &AccountInfo,
+    ) -> ProgramResult {
+        if rent_recipient_info.key != payer && !(signer_info.is_signer && signer_info.key == payer) {
+            return Err(SwapError::InvalidRentRecipient.into());
+        }
+        Ok(())
+    }
+
+    /// Unpacks a limit order, checking its owner, swap and vault
+    fn unpack_limit_order(
+        program_id: &Pubkey,
+        order_info: &AccountInfo,
+        swap_info: &AccountInfo,
+        order_vault_info: &AccountInfo,
+    ) -> Result<LimitOrder, ProgramError> {
+        if order_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let order = LimitOrder::unpack_from_slice(&order_info.data.borrow())?;
+        if !order.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if order.swap != *swap_info.key || order.vault != *order_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        Ok(order)
+    }
+
+    /// Checks the vault of an escrow account and the destination of its
+    /// output, returning the pool vault the escrowed tokens are swapped INTO
+    fn check_escrow_vault(
+        token_swap: &dyn SwapState,
+        escrow_key: &Pubkey,
+        escrow_vault_info: &AccountInfo,
+        destination_info: &AccountInfo,
+    ) -> Result<Pubkey, ProgramError> {
+        let escrow_vault = Self::unpack_token_account(escrow_vault_info, token_swap.token_program_id())?;
+        if escrow_vault.owner != *escrow_key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if escrow_vault.delegate.is_some() {
+            return Err(SwapError::InvalidDelegate.into());
+        }
+        if escrow_vault.close_authority.is_some() {
+            return Err(SwapError::InvalidCloseAuthority.into());
+        }
+        // the vault mint fixes the trade direction
+        let (swap_source, destination_mint) = if escrow_vault.mint == *token_swap.token_a_mint() {
+            (*token_swap.token_a_account(), token_swap.token_b_mint())
+        } else if escrow_vault.mint == *token_swap.token_b_mint() {
+            (*token_swap.token_b_account(), token_swap.token_a_mint())
+        } else {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        };
+        let destination = Self::unpack_token_account(destination_info, token_swap.token_program_id())?;
+        if destination.mint != *destination_mint {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        Ok(swap_source)
+    }
+
+    /// Processes a [PlaceLimitOrder](enum.Instruction.html).
+    pub fn process_place_limit_order(
+        program_id: &Pubkey,
+        order_id: u64,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        keeper_fee: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let order_info = next_account_info(account_info_iter)?;
+        let order_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.reserves().is_none() {
+            return Err(SwapError::ReservesNotTracked.into());
+        }
+        if token_swap.gate().is_some() {
+            return Err(SwapError::GateCheckFailed.into());
+        }
+
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if amount_in == 0 || minimum_amount_out == 0 {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let order_id_bytes = order_id.to_le_bytes();
+        let seeds = [
+            ORDER_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &order_id_bytes,
+        ];
+        let (order_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if order_key != *order_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if !order_info.data_is_empty() {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+
+        let swap_source = Self::check_escrow_vault(
+            token_swap.as_ref(),
+            order_info.key,
+            order_vault_info,
+            destination_info,
+        )?;
+
+        Self::create_or_allocate_account_raw(
+            *program_id,
+            order_info,
+            rent_info,
+            system_info,
+            owner_info,
+            LimitOrder::get_packed_len(),
+            &[
+                ORDER_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &order_id_bytes,
+                &[bump],
+            ],
+        )?;
+        let order = LimitOrder {
+            is_initialized: true,
+            bump,
+            owner: *owner_info.key,
+            swap: *swap_info.key,
+            vault: *order_vault_info.key,
+            swap_source,
+            destination: *destination_info.key,
+            order_id,
+            amount_in,
+            minimum_amount_out,
+            keeper_fee,
+        };
+        order.pack_into_slice(&mut order_info.data.borrow_mut());
+
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            order_vault_info.clone(),
+            owner_info.clone(),
+            token_swap.nonce(),
+            amount_in
+                .checked_add(keeper_fee)
+                .ok_or(SwapError::CalculationFailure)?,
+        )?;
+        Ok(())
+    }
+
+    /// Processes a [CancelLimitOrder](enum.Instruction.html).
+    pub fn process_cancel_limit_order(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let order_info = next_account_info(account_info_iter)?;
+        let order_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let refund_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let order = Self::unpack_limit_order(program_id, order_info, swap_info, order_vault_info)?;
+        if order.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+
+        let order_id_bytes = order.order_id.to_le_bytes();
+        let signer_seeds: &[&[u8]] = &[
+            ORDER_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &order_id_bytes,
+            &[order.bump],
+        ];
+        let escrowed = Self::unpack_token_account(order_vault_info, token_swap.token_program_id())?.amount;
+        if escrowed > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                order_vault_info.clone(),
+                refund_info.clone(),
+                order_info.clone(),
+                signer_seeds,
+                escrowed,
+            )?;
+        }
+        // the owner paid for the order accounts
+        let rent_recipient_info = account_info_iter.next().unwrap_or(owner_info);
+        Self::close_escrow(
+            order_info,
+            order_vault_info,
+            rent_recipient_info,
+            token_program_info,
+            signer_seeds,
+        )
+    }
+
+    /// Unpacks the pool and the program state an order is swapped against
+    fn unpack_order_pool(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        state_info: &AccountInfo,
+    ) -> Result<(Box<dyn SwapState>, GlobalState), ProgramError> {
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        Ok((SwapVersion::unpack(&swap_info.data.borrow())?, state))
+    }
+
+    /// Swap tokens escrowed by a program address through the pool under the
+    /// rules of a regular swap, and return the output amount
+    #[allow(clippy::too_many_arguments)]
+    fn escrow_swap(
+        program_id: &Pubkey,
+        accounts: &PoolSwapAccounts,
+        token_swap: &dyn SwapState,
+        state: &GlobalState,
+        signer_seeds: &[&[u8]],
+        amount_in: u64,
+        minimum_amount_out: u64,
+        slippage_error: SwapError,
+    ) -> Result<u64, ProgramError> {
+        if token_swap.reserves().is_none() {
+            return Err(SwapError::ReservesNotTracked.into());
+        }
+        if token_swap.gate().is_some() {
+            return Err(SwapError::GateCheckFailed.into());
+        }
+        Self::swap_through_pool(
+            program_id,
+            accounts,
+            token_swap,
+            state,
+            Some(signer_seeds),
+            amount_in,
+            minimum_amount_out,
+            None,
+            None,
+            None,
+            None,
+            slippage_error,
+        )
+    }
+
+    /// Processes a [FillLimitOrder](enum.Instruction.html).
+    pub fn process_fill_limit_order(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let order_info = next_account_info(account_info_iter)?;
+        let order_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let fixed_fee_account_info = next_account_info(account_info_iter)?;
+        let keeper_info = next_account_info(account_info_iter)?;
+        let refund_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let (token_swap, state) = Self::unpack_order_pool(program_id, swap_info, state_info)?;
+        let oracle_info = if token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let (protocol_liquidity_info, fee_recipient_infos) = Self::next_owner_fee_accounts(
+            program_id,
+            token_swap.as_ref(),
+            &state,
+            account_info_iter,
+        )?;
+
+        let order = Self::unpack_limit_order(program_id, order_info, swap_info, order_vault_info)?;
+        if order.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if order.swap_source != *swap_source_info.key || order.destination != *destination_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if Self::unpack_token_account(refund_info, token_swap.token_program_id())?.owner != order.owner {
+            return Err(SwapError::InvalidOwner.into());
+        }
+
+        let order_id_bytes = order.order_id.to_le_bytes();
+        let signer_seeds: &[&[u8]] = &[
+            ORDER_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &order_id_bytes,
+            &[order.bump],
+        ];
+        let escrowed = Self::unpack_token_account(order_vault_info, token_program_info.key)?.amount;
+        Self::escrow_swap(
+            program_id,
+            &PoolSwapAccounts {
+                swap_info,
+                authority_info,
+                source_info: order_vault_info,
+                source_authority_info: order_info,
+                swap_source_info,
+                swap_destination_info,
+                destination_info,
+                pool_mint_info,
+                fixed_fee_account_info: Some(fixed_fee_account_info),
+                protocol_liquidity_info,
+                fee_recipient_infos,
+                oracle_info,
+                token_program_info,
+            },
+            token_swap.as_ref(),
+            &state,
+            signer_seeds,
+            order.amount_in,
+            order.minimum_amount_out,
+            SwapError::LimitPriceNotReached,
+        )?;
+        // the input the swap rounds away goes back to the owner
+        let remaining = Self::unpack_token_account(order_vault_info, token_program_info.key)?.amount;
+        let surplus = remaining
+            .checked_add(order.amount_in)
+            .and_then(|amount| amount.checked_sub(escrowed))
+            .ok_or(SwapError::CalculationFailure)?;
+        if surplus > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                order_vault_info.clone(),
+                refund_info.clone(),
+                order_info.clone(),
+                signer_seeds,
+                surplus,
+            )?;
+        }
+        // the keeper fee and anything sent to the vault on top of the order
+        let remaining = remaining
+            .checked_sub(surplus)
+            .ok_or(SwapError::CalculationFailure)?;
+        if remaining > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                order_vault_info.clone(),
+                keeper_info.clone(),
+                order_info.clone(),
+                signer_seeds,
+                remaining,
+            )?;
+        }
+
+        Self::close_escrow(
+            order_info,
+            order_vault_info,
+            owner_info,
+            token_program_info,
+            signer_seeds,
+        )
+    }
+
+    /// Unpacks a recurring order, checking its owner, swap and vault
+    fn unpack_dca_position(
+        program_id: &Pubkey,
+        position_info: &AccountInfo,
+        swap_info: &AccountInfo,
+        position_vault_info: &AccountInfo,
+    ) -> Result<DcaPosition, ProgramError> {
+        if position_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let position = DcaPosition::unpack_from_slice(&position_info.data.borrow())?;
+        if !position.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if position.swap != *swap_info.key || position.vault != *position_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        Ok(position)
+    }
+
+    /// Processes a [CreateDca](enum.Instruction.html).
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_create_dca(
+        program_id: &Pubkey,
+        dca_id: u64,
+        amount: u64,
+        amount_per_tick: u64,
+        minimum_amount_out_per_tick: u64,
+        keeper_fee: u64,
+        interval: i64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if token_swap.reserves().is_none() {
+            return Err(SwapError::ReservesNotTracked.into());
+        }
+        if token_swap.gate().is_some() {
+            return Err(SwapError::GateCheckFailed.into());
+        }
+
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let tick_amount = amount_per_tick
+            .checked_add(keeper_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        if amount_per_tick == 0 || interval <= 0 || amount < tick_amount {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let dca_id_bytes = dca_id.to_le_bytes();
+        let seeds = [
+            DCA_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &dca_id_bytes,
+        ];
+        let (position_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if position_key != *position_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if !position_info.data_is_empty() {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        let swap_source = Self::check_escrow_vault(
+            token_swap.as_ref(),
+            position_info.key,
+            position_vault_info,
+            destination_info,
+        )?;
+
+        Self::create_or_allocate_account_raw(
+            *program_id,
+            position_info,
+            rent_info,
+            system_info,
+            owner_info,
+            DcaPosition::get_packed_len(),
+            &[
+                DCA_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &dca_id_bytes,
+                &[bump],
+            ],
+        )?;
+        let position = DcaPosition {
+            is_initialized: true,
+            bump,
+            owner: *owner_info.key,
+            swap: *swap_info.key,
+            vault: *position_vault_info.key,
+            swap_source,
+            destination: *destination_info.key,
+            dca_id,
+            amount_per_tick,
+            minimum_amount_out_per_tick,
+            keeper_fee,
+            interval,
+            last_tick_timestamp: 0,
+        };
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+
+        Self::token_transfer(
+            swap_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            position_vault_info.clone(),
+            owner_info.clone(),
+            token_swap.nonce(),
+            amount,
+        )?;
+        Ok(())
+    }
+
+    /// Processes an [ExecuteDcaTick](enum.Instruction.html).
+    pub fn process_execute_dca_tick(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let fixed_fee_account_info = next_account_info(account_info_iter)?;
+        let keeper_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let (token_swap, state) = Self::unpack_order_pool(program_id, swap_info, state_info)?;
+        let oracle_info = if token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let (protocol_liquidity_info, fee_recipient_infos) = Self::next_owner_fee_accounts(
+            program_id,
+            token_swap.as_ref(),
+            &state,
+            account_info_iter,
+        )?;
+
+        let mut position =
+            Self::unpack_dca_position(program_id, position_info, swap_info, position_vault_info)?;
+        if position.swap_source != *swap_source_info.key || position.destination != *destination_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let now = Clock::get()?.unix_timestamp;
+        let next_tick_timestamp = position
+            .last_tick_timestamp
+            .checked_add(position.interval)
+            .ok_or(SwapError::CalculationFailure)?;
+        if now < next_tick_timestamp {
+            return Err(SwapError::DcaTickTooEarly.into());
+        }
+        position.last_tick_timestamp = now;
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+
+        let dca_id_bytes = position.dca_id.to_le_bytes();
+        let signer_seeds: &[&[u8]] = &[
+            DCA_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            position.owner.as_ref(),
+            &dca_id_bytes,
+            &[position.bump],
+        ];
+        // the input the swap rounds away stays in the position vault
+        Self::escrow_swap(
+            program_id,
+            &PoolSwapAccounts {
+                swap_info,
+                authority_info,
+                source_info: position_vault_info,
+                source_authority_info: position_info,
+                swap_source_info,
+                swap_destination_info,
+                destination_info,
+                pool_mint_info,
+                fixed_fee_account_info: Some(fixed_fee_account_info),
+                protocol_liquidity_info,
+                fee_recipient_infos,
+                oracle_info,
+                token_program_info,
+            },
+            token_swap.as_ref(),
+            &state,
+            signer_seeds,
+            position.amount_per_tick,
+            position.minimum_amount_out_per_tick,
+            SwapError::ExceededSlippage,
+        )?;
+        if position.keeper_fee > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                position_vault_info.clone(),
+                keeper_info.clone(),
+                position_info.clone(),
+                signer_seeds,
+                position.keeper_fee,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [CloseDca](enum.Instruction.html).
+    pub fn process_close_dca(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let refund_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let position =
+            Self::unpack_dca_position(program_id, position_info, swap_info, position_vault_info)?;
+        if position.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+
+        let dca_id_bytes = position.dca_id.to_le_bytes();
+        let signer_seeds: &[&[u8]] = &[
+            DCA_TAG.as_bytes(),
+            swap_info.key.as_ref(),
+            owner_info.key.as_ref(),
+            &dca_id_bytes,
+            &[position.bump],
+        ];
+        let escrowed = Self::unpack_token_account(position_vault_info, token_swap.token_program_id())?.amount;
+        if escrowed > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                position_vault_info.clone(),
+                refund_info.clone(),
+                position_info.clone(),
+                signer_seeds,
+                escrowed,
+            )?;
+        }
+        // the owner paid for the position accounts
+        let rent_recipient_info = account_info_iter.next().unwrap_or(owner_info);
+        Self::close_escrow(
+            position_info,
+            position_vault_info,
+            rent_recipient_info,
+            token_program_info,
+            signer_seeds,
+        )
+    }
+
+    /// Unpacks the stake pool of a swap, checking its owner and swap
+    fn unpack_stake_pool(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        stake_pool_info: &AccountInfo,
+    ) -> Result<StakePool, ProgramError> {
+        if stake_pool_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let stake_pool = StakePool::unpack_from_slice(&stake_pool_info.data.borrow())?;
+        if !stake_pool.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if stake_pool.swap != *swap_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        Ok(stake_pool)
+    }
+
+    /// Unpacks an existing stake position, checking its owner and stake pool
+    fn unpack_stake_position(
+        program_id: &Pubkey,
+        stake_pool_info: &AccountInfo,
+        position_info: &AccountInfo,
+        owner_info: &AccountInfo,
+    ) -> Result<StakePosition, ProgramError> {
+        if position_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let position = StakePosition::unpack_from_slice(&position_info.data.borrow())?;
+        if !position.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if position.stake_pool != *stake_pool_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if position.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        Ok(position)
+    }
+
+    /// Processes an [InitializeStakePool](enum.Instruction.html).
+    pub fn process_initialize_stake_pool(
+        program_id: &Pubkey,
+        reward_per_slot: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let stake_vault_info = next_account_info(account_info_iter)?;
+        let reward_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *state.owner() != *owner_info.key {
+            return Err(SwapError::InvalidProgramOwner.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let (stake_pool_key, bump) = Pubkey::find_program_address(
+            &[STAKE_TAG.as_bytes(), swap_info.key.as_ref()],
+            program_id,
+        );
+        if stake_pool_key != *stake_pool_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if !stake_pool_info.data_is_empty() {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+
+        let stake_vault = Self::unpack_token_account(stake_vault_info, token_swap.token_program_id())?;
+        let reward_vault = Self::unpack_token_account(reward_vault_info, token_swap.token_program_id())?;
+        for vault in [&stake_vault, &reward_vault].iter() {
+            if vault.owner != stake_pool_key {
+                return Err(SwapError::InvalidOwner.into());
+            }
+            if vault.delegate.is_some() {
+                return Err(SwapError::InvalidDelegate.into());
+            }
+            if vault.close_authority.is_some() {
+                return Err(SwapError::InvalidCloseAuthority.into());
+            }
+        }
+        if stake_vault.mint != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+
+        Self::create_or_allocate_account_raw(
+            *program_id,
+            stake_pool_info,
+            rent_info,
+            system_info,
+            owner_info,
+            StakePool::get_packed_len(),
+            &[STAKE_TAG.as_bytes(), swap_info.key.as_ref(), &[bump]],
+        )?;
+
+        let stake_pool = StakePool {
+            is_initialized: true,
+            bump,
+            swap: *swap_info.key,
+            stake_vault: *stake_vault_info.key,
+            reward_mint: reward_vault.mint,
+            reward_vault: *reward_vault_info.key,
+            reward_per_slot,
+            last_update_slot: Clock::get()?.slot,
+            acc_reward_per_share: 0,
+            total_staked: 0,
+        };
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut());
+        Ok(())
+    }
+
+    /// Processes a [Stake](enum.Instruction.html).
+    pub fn process_stake(
+        program_id: &Pubkey,
+        amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let stake_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let mut stake_pool = Self::unpack_stake_pool(program_id, swap_info, stake_pool_info)?;
+        if stake_pool.stake_vault != *stake_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+
+        let (position_key, bump) = Pubkey::find_program_address(
+            &[
+                STAKE_TAG.as_bytes(),
+                stake_pool_info.key.as_ref(),
+                owner_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        if position_key != *position_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        let mut position = if position_info.data_is_empty() {
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                position_info,
+                rent_info,
+                system_info,
+                owner_info,
+                StakePosition::get_packed_len(),
+                &[
+                    STAKE_TAG.as_bytes(),
+                    stake_pool_info.key.as_ref(),
+                    owner_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            StakePosition {
+                is_initialized: true,
+                bump,
+                owner: *owner_info.key,
+                stake_pool: *stake_pool_info.key,
+                amount: 0,
+                reward_debt: 0,
+                pending_rewards: 0,
+            }
+        } else {
+            Self::unpack_stake_position(program_id, stake_pool_info, position_info, owner_info)?
+        };
+
+        stake_pool
+            .update(Clock::get()?.slot)
+            .ok_or(SwapError::CalculationFailure)?;
+        position
+            .settle(&stake_pool)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.amount = position
+            .amount
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        stake_pool.total_staked = stake_pool
+            .total_staked
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        position
+            .checkpoint(&stake_pool)
+            .ok_or(SwapError::CalculationFailure)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut());
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+
+        if amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                stake_vault_info.clone(),
+                owner_info.clone(),
+                token_swap.nonce(),
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes an [Unstake](enum.Instruction.html).
+    pub fn process_unstake(
+        program_id: &Pubkey,
+        amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let stake_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+
+        let mut stake_pool = Self::unpack_stake_pool(program_id, swap_info, stake_pool_info)?;
+        if stake_pool.stake_vault != *stake_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let mut position =
+            Self::unpack_stake_position(program_id, stake_pool_info, position_info, owner_info)?;
+        if position.amount < amount {
+            return Err(SwapError::InsufficientStake.into());
+        }
+
+        stake_pool
+            .update(Clock::get()?.slot)
+            .ok_or(SwapError::CalculationFailure)?;
+        position
+            .settle(&stake_pool)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.amount = position
+            .amount
+            .checked_sub(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        stake_pool.total_staked = stake_pool
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        position
+            .checkpoint(&stake_pool)
+            .ok_or(SwapError::CalculationFailure)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut());
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+
+        if amount > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                stake_vault_info.clone(),
+                destination_info.clone(),
+                stake_pool_info.clone(),
+                &[STAKE_TAG.as_bytes(), swap_info.key.as_ref(), &[stake_pool.bump]],
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [ClaimRewards](enum.Instruction.html).
+    pub fn process_claim_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let stake_pool_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let reward_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+
+        let mut stake_pool = Self::unpack_stake_pool(program_id, swap_info, stake_pool_info)?;
+        if stake_pool.reward_vault != *reward_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let mut position =
+            Self::unpack_stake_position(program_id, stake_pool_info, position_info, owner_info)?;
+
+        stake_pool
+            .update(Clock::get()?.slot)
+            .ok_or(SwapError::CalculationFailure)?;
+        position
+            .settle(&stake_pool)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        // rewards are only paid out as far as the vault is funded, the rest
+        // stays claimable
+        let reward_vault = Self::unpack_token_account(reward_vault_info, token_swap.token_program_id())?;
+        let amount = std::cmp::min(position.pending_rewards, reward_vault.amount);
+        position.pending_rewards = position
+            .pending_rewards
+            .checked_sub(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        stake_pool.pack_into_slice(&mut stake_pool_info.data.borrow_mut());
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+
+        if amount > 0 {
+            Self::token_transfer_signed(
+                token_program_info.clone(),
+                reward_vault_info.clone(),
+                destination_info.clone(),
+                stake_pool_info.clone(),
+                &[STAKE_TAG.as_bytes(), swap_info.key.as_ref(), &[stake_pool.bump]],
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Unpacks the LP position of an owner in a swap. The owner of an NFT
+    /// position is the holder of its NFT, whose token account is taken from
+    /// the remaining accounts.
+    fn unpack_lp_position<'a, 'b>(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo,
+        position_info: &AccountInfo,
+        owner_info: &AccountInfo,
+        token_program_id: &Pubkey,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<LpPosition, ProgramError> {
+        if position_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let position = LpPosition::unpack_from_slice(&position_info.data.borrow())?;
+        if !position.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if position.swap != *swap_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if position.nft {
+            let nft_info = next_account_info(account_info_iter)?;
+            let nft_account = Self::unpack_token_account(nft_info, token_program_id)?;
+            if nft_account.mint != position.owner
+                || nft_account.owner != *owner_info.key
+                || nft_account.amount != 1
+            {
+                return Err(SwapError::InvalidOwner.into());
+            }
+        } else if position.owner != *owner_info.key {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        Ok(position)
+    }
+
+    /// Opens the position of a deposit into a pool giving out position NFTs:
+    /// the position, keyed on a fresh NFT mint, holds the pool tokens of the
+    /// deposit, and the only token of the mint is minted to the depositor
+    #[allow(clippy::too_many_arguments)]
+    fn open_nft_position<'a>(
+        program_id: &Pubkey,
+        swap_info: &AccountInfo<'a>,
+        authority_info: &AccountInfo<'a>,
+        swap_v2: &mut SwapV2,
+        position_info: &AccountInfo<'a>,
+        nft_mint_info: &AccountInfo<'a>,
+        nft_destination_info: &AccountInfo<'a>,
+        payer_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        system_info: &AccountInfo<'a>,
+        rent_info: &AccountInfo<'a>,
+        pool_token_amount: u64,
+    ) -> ProgramResult {
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+        // only the swap authority may mint the NFT, and only once
+        let nft_mint = Self::unpack_mint(nft_mint_info, &swap_v2.token_program_id)?;
+        if nft_mint.decimals != 0
+            || nft_mint.supply != 0
+            || nft_mint.mint_authority != COption::Some(*authority_info.key)
+            || nft_mint.freeze_authority.is_some()
+        {
+            return Err(SwapError::InvalidPositionMint.into());
+        }
+        let (position_key, bump) = Pubkey::find_program_address(
+            &[
+                POSITION_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                nft_mint_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        if position_key != *position_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        Self::create_or_allocate_account_raw(
+            *program_id,
+            position_info,
+            rent_info,
+            system_info,
+            payer_info,
+            LpPosition::get_packed_len(),
+            &[
+                POSITION_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                nft_mint_info.key.as_ref(),
+                &[bump],
+            ],
+        )?;
+        LpPosition {
+            is_initialized: true,
+            bump,
+            owner: *nft_mint_info.key,
+            swap: *swap_info.key,
+            pool_tokens: pool_token_amount,
+            fee_growth_checkpoint_a: swap_v2.fee_growth_a,
+            fee_growth_checkpoint_b: swap_v2.fee_growth_b,
+            fees_owed_a: 0,
+            fees_owed_b: 0,
+            nft: true,
+            payer: *payer_info.key,
+        }
+        .pack_into_slice(&mut position_info.data.borrow_mut());
+        swap_v2.position_pool_tokens = swap_v2
+            .position_pool_tokens
+            .checked_add(pool_token_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        Self::token_mint_to(
+            swap_info.key,
+            token_program_info.clone(),
+            nft_mint_info.clone(),
+            nft_destination_info.clone(),
+            authority_info.clone(),
+            swap_v2.nonce,
+            1,
+        )
+    }
+
+    /// Processes a [SetNftPositions](enum.Instruction.html).
+    pub fn process_set_nft_positions(
+        program_id: &Pubkey,
+        nft_positions: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        // deposits are minted into the position vault
+        if nft_positions && !swap_v2.claimable_fees {
+            return Err(SwapError::ClaimableFeesDisabled.into());
+        }
+        swap_v2.nft_positions = nft_positions;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetClaimableFees](enum.Instruction.html).
+    pub fn process_set_claimable_fees(
+        program_id: &Pubkey,
+        claimable_fees: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if claimable_fees {
+            let position_vault =
+                Self::unpack_token_account(position_vault_info, &swap_v2.token_program_id)?;
+            if position_vault.mint != swap_v2.pool_mint {
+                return Err(SwapError::IncorrectPoolMint.into());
+            }
+            let (authority, _bump) =
+                Pubkey::find_program_address(&[swap_info.key.as_ref()], program_id);
+            if position_vault.owner != authority {
+                return Err(SwapError::InvalidOwner.into());
+            }
+            // the pool tokens of open positions stay in the vault they were
+            // moved into
+            if swap_v2.position_pool_tokens > 0 && *position_vault_info.key != swap_v2.position_vault {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            swap_v2.position_vault = *position_vault_info.key;
+        }
+        swap_v2.claimable_fees = claimable_fees;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes an [IncreasePosition](enum.Instruction.html).
+    pub fn process_increase_position(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if !swap_v2.claimable_fees {
+            return Err(SwapError::ClaimableFeesDisabled.into());
+        }
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+        if swap_v2.position_vault != *position_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+
+        let (position_key, bump) = Pubkey::find_program_address(
+            &[
+                POSITION_TAG.as_bytes(),
+                swap_info.key.as_ref(),
+                owner_info.key.as_ref(),
+            ],
+            program_id,
+        );
+        if position_key != *position_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        let mut position = if position_info.data_is_empty() {
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                position_info,
+                rent_info,
+                system_info,
+                owner_info,
+                LpPosition::get_packed_len(),
+                &[
+                    POSITION_TAG.as_bytes(),
+                    swap_info.key.as_ref(),
+                    owner_info.key.as_ref(),
+                    &[bump],
+                ],
+            )?;
+            LpPosition {
+                is_initialized: true,
+                bump,
+                owner: *owner_info.key,
+                swap: *swap_info.key,
+                pool_tokens: 0,
+                fee_growth_checkpoint_a: swap_v2.fee_growth_a,
+                fee_growth_checkpoint_b: swap_v2.fee_growth_b,
+                fees_owed_a: 0,
+                fees_owed_b: 0,
+                nft: false,
+                payer: *owner_info.key,
+            }
+        } else {
+            Self::unpack_lp_position(
+                program_id,
+                swap_info,
+                position_info,
+                owner_info,
+                &swap_v2.token_program_id,
+                account_info_iter,
+            )?
+        };
+
+        position
+            .settle(&swap_v2)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.pool_tokens = position
+            .pool_tokens
+            .checked_add(pool_token_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2.position_pool_tokens = swap_v2
+            .position_pool_tokens
+            .checked_add(pool_token_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+        let nonce = swap_v2.nonce;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+
+        if pool_token_amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                position_vault_info.clone(),
+                owner_info.clone(),
+                nonce,
+                pool_token_amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [DecreasePosition](enum.Instruction.html).
+    pub fn process_decrease_position(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let position_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        if swap_v2.position_vault != *position_vault_info.key {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let mut position = Self::unpack_lp_position(
+            program_id,
+            swap_info,
+            position_info,
+            owner_info,
+            &swap_v2.token_program_id,
+            account_info_iter,
+        )?;
+        if position.pool_tokens < pool_token_amount {
+            return Err(SwapError::InsufficientPositionTokens.into());
+        }
+
+        position
+            .settle(&swap_v2)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.pool_tokens = position
+            .pool_tokens
+            .checked_sub(pool_token_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2.position_pool_tokens = swap_v2
+            .position_pool_tokens
+            .checked_sub(pool_token_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+        let nonce = swap_v2.nonce;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+
+        if pool_token_amount > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                position_vault_info.clone(),
+                destination_info.clone(),
+                authority_info.clone(),
+                nonce,
+                pool_token_amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [ClaimFees](enum.Instruction.html).
+    pub fn process_claim_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_a_info = next_account_info(account_info_iter)?;
+        let destination_b_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        Self::assert_pda(&[swap_info.key.as_ref()], program_id, authority_info.key)?;
+        if *token_a_info.key != swap_v2.token_a {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != swap_v2.token_b {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+        if *token_program_info.key != swap_v2.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        let mut position = Self::unpack_lp_position(
+            program_id,
+            swap_info,
+            position_info,
+            owner_info,
+            &swap_v2.token_program_id,
+            account_info_iter,
+        )?;
+
+        position
+            .settle(&swap_v2)
+            .ok_or(SwapError::CalculationFailure)?;
+        // the fee growth rounds down per swap, the settled fees may exceed
+        // the fees set aside by a few tokens
+        let amount_a = std::cmp::min(position.fees_owed_a, swap_v2.unclaimed_fee_a);
+        let amount_b = std::cmp::min(position.fees_owed_b, swap_v2.unclaimed_fee_b);
+        position.fees_owed_a = position
+            .fees_owed_a
+            .checked_sub(amount_a)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.fees_owed_b = position
+            .fees_owed_b
+            .checked_sub(amount_b)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2.unclaimed_fee_a = swap_v2
+            .unclaimed_fee_a
+            .checked_sub(amount_a)
+            .ok_or(SwapError::CalculationFailure)?;
+        swap_v2.unclaimed_fee_b = swap_v2
+            .unclaimed_fee_b
+            .checked_sub(amount_b)
+            .ok_or(SwapError::CalculationFailure)?;
+        position.pack_into_slice(&mut position_info.data.borrow_mut());
+        let nonce = swap_v2.nonce;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+
+        if amount_a > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_a_info.clone(),
+                destination_a_info.clone(),
+                authority_info.clone(),
+                nonce,
+                amount_a,
+            )?;
+        }
+        if amount_b > 0 {
+            Self::token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                token_b_info.clone(),
+                destination_b_info.clone(),
+                authority_info.clone(),
+                nonce,
+                amount_b,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes a [SetGuardian](enum.Instruction.html).
+    pub fn process_set_guardian(
+        program_id: &Pubkey,
+        guardian: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        state.guardian = *guardian;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetPaused](enum.Instruction.html).
+    pub fn process_set_paused(
+        program_id: &Pubkey,
+        paused: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+
+        if !authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut state = Self::unpack_global_state(program_id, state_info)?;
+        Self::check_pause_authority(&state, authority_info.key, paused)?;
+
+        state.paused = paused;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetPoolPaused](enum.Instruction.html).
+    pub fn process_set_pool_paused(
+        program_id: &Pubkey,
+        paused: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if !authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        Self::check_pause_authority(&state, authority_info.key, paused)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.paused = paused;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetSlotVolumeCap](enum.Instruction.html).
+    pub fn process_set_slot_volume_cap(
+        program_id: &Pubkey,
+        max_slot_volume: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.max_slot_volume = max_slot_volume;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetCircuitBreaker](enum.Instruction.html).
+    pub fn process_set_circuit_breaker(
+        program_id: &Pubkey,
+        breaker_move_bps: u64,
+        breaker_window_slots: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+        if breaker_move_bps != 0 && breaker_window_slots == 0 {
+            return Err(SwapError::InvalidInput.into());
+        }
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.breaker_move_bps = breaker_move_bps;
+        swap_v2.breaker_window_slots = breaker_window_slots;
+        swap_v2.breaker_price = 0;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [ResetCircuitBreaker](enum.Instruction.html).
+    pub fn process_reset_circuit_breaker(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if !authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        // the guardian may reset the breaker as it may pause the pool
+        Self::check_pause_authority(&state, authority_info.key, true)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        swap_v2.breaker_tripped = false;
+        swap_v2.breaker_price = 0;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Share of a single token zapped into a pool to swap for the other
+    /// token, so that what is left and what the swap returns are in the
+    /// proportion of the reserves after the swap. The curve is probed with
+    /// half the amount and the proportion solved around the probe, fees
+    /// aside.
+    fn zap_swap_amount(
+        swap_curve: &SwapCurve,
+        amount_in: u64,
+        source_reserve: u64,
+        destination_reserve: u64,
+        trade_direction: TradeDirection,
+    ) -> Result<u64, ProgramError> {
+        let probe = swap_curve
+            .calculator
+            .swap_without_fees(
+                to_u128(amount_in / 2)?,
+                to_u128(source_reserve)?,
+                to_u128(destination_reserve)?,
+                trade_direction,
+            )
+            .ok_or(SwapError::ZeroTradingTokens)?;
+        let probe_in = U256::from(probe.source_amount_swapped);
+        let probe_out = U256::from(probe.destination_amount_swapped);
+        // amount_in * in * (R_out - out) / (in * (R_out - out) + out * (R_in + in))
+        let swap_amount = U256::from(destination_reserve)
+            .checked_sub(probe_out)
+            .and_then(|destination_left| probe_in.checked_mul(destination_left))
+            .and_then(|kept| {
+                let swapped = U256::from(source_reserve)
+                    .checked_add(probe_in)?
+                    .checked_mul(probe_out)?;
+                U256::from(amount_in)
+                    .checked_mul(kept)?
+                    .checked_div(kept.checked_add(swapped)?)
+            })
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(to_u64(u128::try_from(swap_amount).map_err(|_| SwapError::ConversionFailure)?)?)
+    }
+
+    /// Share of `source_amount` to swap so that the rest of it and
+    /// `destination_amount`, plus the output of the swap, are in the ratio
+    /// of the reserves after the swap. The swap is priced, as for a zap, at
+    /// the rate of a swap of half the excess.
+    fn imbalance_swap_amount(
+        swap_curve: &SwapCurve,
+        source_amount: u64,
+        destination_amount: u64,
+        source_reserve: u64,
+        destination_reserve: u64,
+        trade_direction: TradeDirection,
+    ) -> Result<u64, ProgramError> {
+        let source_amount = U256::from(source_amount);
+        let destination_amount = U256::from(destination_amount);
+        let source_reserve = U256::from(source_reserve);
+        let destination_reserve = U256::from(destination_reserve);
+        // source_amount * R_out - destination_amount * R_in
+        let imbalance = source_amount
+            .checked_mul(destination_reserve)
+            .and_then(|amount| {
+                amount.checked_sub(destination_amount.checked_mul(source_reserve)?)
+            })
+            .ok_or(SwapError::CalculationFailure)?;
+        let excess = imbalance
+            .checked_div(destination_reserve)
+            .ok_or(SwapError::CalculationFailure)?;
+        let probe = swap_curve
+            .calculator
+            .swap_without_fees(
+                u128::try_from(excess / 2).map_err(|_| SwapError::ConversionFailure)?,
+                u128::try_from(source_reserve).map_err(|_| SwapError::ConversionFailure)?,
+                u128::try_from(destination_reserve).map_err(|_| SwapError::ConversionFailure)?,
+                trade_direction,
+            )
+            .ok_or(SwapError::ZeroTradingTokens)?;
+        let probe_in = U256::from(probe.source_amount_swapped);
+        let probe_out = U256::from(probe.destination_amount_swapped);
+        // imbalance * in / ((R_out + destination_amount) * in + out * (R_in + source_amount))
+        let swap_amount = destination_reserve
+            .checked_add(destination_amount)
+            .and_then(|amount| amount.checked_mul(probe_in))
+            .and_then(|kept| {
+                let swapped = source_reserve
+                    .checked_add(source_amount)?
+                    .checked_mul(probe_out)?;
+                imbalance
+                    .checked_mul(probe_in)?
+                    .checked_div(kept.checked_add(swapped)?)
+            })
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(to_u64(u128::try_from(swap_amount).map_err(|_| SwapError::ConversionFailure)?)?)
+    }
+
+    /// Processes a [ZapIn](enum.Instruction.html).
+    pub fn process_zap_in(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_pool_token_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let other_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let pool_fee_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // the pool's oracle, gate and protocol liquidity accounts, if any,
+        // follow the pool accounts
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        let oracle_info = if token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let gate_info = if token_swap.gate().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let protocol_liquidity_info = if token_swap.protocol_liquidity_bps().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let fee_recipient_count = GlobalStateVersion::unpack(&state_info.data.borrow())?
+            .fee_recipients()
+            .count();
+        let fee_recipient_infos = (0..fee_recipient_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        if *token_a_info.key != *token_swap.token_a_account()
+            || *token_b_info.key != *token_swap.token_b_account()
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let source_mint = Self::unpack_token_account(source_info, token_program_info.key)?.mint;
+        let (trade_direction, swap_source_info, swap_destination_info) =
+            if source_mint == *token_swap.token_a_mint() {
+                (TradeDirection::AtoB, token_a_info, token_b_info)
+            } else if source_mint == *token_swap.token_b_mint() {
+                (TradeDirection::BtoA, token_b_info, token_a_info)
+            } else {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            };
+        let (token_a_reserve, token_b_reserve) = token_swap.reserves_at(
+            Self::unpack_token_account(token_a_info, token_program_info.key)?.amount,
+            Self::unpack_token_account(token_b_info, token_program_info.key)?.amount,
+        );
+        let (source_reserve, destination_reserve) = match trade_direction {
+            TradeDirection::AtoB => (token_a_reserve, token_b_reserve),
+            TradeDirection::BtoA => (token_b_reserve, token_a_reserve),
+        };
+        let swap_amount = Self::zap_swap_amount(
+            token_swap.swap_curve(),
+            amount_in,
+            source_reserve,
+            destination_reserve,
+            trade_direction,
+        )?;
+
+        let other_before = Self::unpack_token_account(other_info, token_program_info.key)?.amount;
+        let mut swap_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            user_transfer_authority_info.clone(),
+            state_info.clone(),
+            source_info.clone(),
+            swap_source_info.clone(),
+            swap_destination_info.clone(),
+            other_info.clone(),
+            pool_mint_info.clone(),
+            pool_fee_info.clone(),
+            token_program_info.clone(),
+        ];
+        if let Some(oracle_info) = oracle_info {
+            swap_accounts.push(oracle_info.clone());
+        }
+        if let Some(gate_info) = gate_info {
+            swap_accounts.push(gate_info.clone());
+        }
+        if let Some(protocol_liquidity_info) = protocol_liquidity_info {
+            swap_accounts.push(protocol_liquidity_info.clone());
+        }
+        for fee_recipient_info in fee_recipient_infos.iter() {
+            swap_accounts.push((*fee_recipient_info).clone());
+        }
+        Self::process_swap(program_id, swap_amount, 0, None, 0, None, false, &swap_accounts)?;
+        let other_amount = Self::unpack_token_account(other_info, token_program_info.key)?
+            .amount
+            .checked_sub(other_before)
+            .ok_or(SwapError::CalculationFailure)?;
+        let source_amount = amount_in
+            .checked_sub(swap_amount)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        // the most pool tokens both amounts pay for at the reserves after
+        // the swap
+        let (token_a_amount, token_b_amount, source_a_info, source_b_info) = match trade_direction {
+            TradeDirection::AtoB => (source_amount, other_amount, source_info, other_info),
+            TradeDirection::BtoA => (other_amount, source_amount, other_info, source_info),
+        };
+        let pool_token_amount = Self::pool_tokens_for_amounts(
+            swap_info,
+            token_a_info,
+            token_b_info,
+            pool_mint_info,
+            token_program_info,
+            token_a_amount,
+            token_b_amount,
+        )?;
+        if pool_token_amount < minimum_pool_token_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+
+        let mut deposit_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            state_info.clone(),
+            user_transfer_authority_info.clone(),
+            source_a_info.clone(),
+            source_b_info.clone(),
+            token_a_info.clone(),
+            token_b_info.clone(),
+            pool_mint_info.clone(),
+            destination_info.clone(),
+            token_program_info.clone(),
+        ];
+        if let Some(gate_info) = gate_info {
+            deposit_accounts.push(gate_info.clone());
+        }
+        deposit_accounts.extend(account_info_iter.cloned());
+        Self::process_deposit_all_token_types(
+            program_id,
+            pool_token_amount,
+            token_a_amount,
+            token_b_amount,
+            None,
+            &deposit_accounts,
+        )
+    }
+
+    /// The most pool tokens the given amounts of both tokens pay for at the
+    /// current reserves of the pool
+    fn pool_tokens_for_amounts(
+        swap_info: &AccountInfo,
+        token_a_info: &AccountInfo,
+        token_b_info: &AccountInfo,
+        pool_mint_info: &AccountInfo,
+        token_program_info: &AccountInfo,
+        token_a_amount: u64,
+        token_b_amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        let pool_mint_supply = Self::unpack_mint(pool_mint_info, token_program_info.key)?.supply;
+        let (token_a_reserve, token_b_reserve) = token_swap.reserves_at(
+            Self::unpack_token_account(token_a_info, token_program_info.key)?.amount,
+            Self::unpack_token_account(token_b_info, token_program_info.key)?.amount,
+        );
+        let pool_tokens_for = |amount: u64, reserve: u64| {
+            to_u128(amount)?
+                .checked_mul(to_u128(pool_mint_supply)?)
+                .and_then(|pool_tokens| pool_tokens.checked_div(to_u128(reserve).ok()?))
+                .ok_or_else(|| ProgramError::from(SwapError::CalculationFailure))
+        };
+        Ok(to_u64(std::cmp::min(
+            pool_tokens_for(token_a_amount, token_a_reserve)?,
+            pool_tokens_for(token_b_amount, token_b_reserve)?,
+        ))?)
+    }
+
+    /// Processes a [MigrateFromSplTokenSwap](enum.Instruction.html).
+    pub fn process_migrate_from_spl_token_swap(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        minimum_pool_token_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let spl_token_swap_program_info = next_account_info(account_info_iter)?;
+        let spl_swap_info = next_account_info(account_info_iter)?;
+        let spl_authority_info = next_account_info(account_info_iter)?;
+        let spl_pool_mint_info = next_account_info(account_info_iter)?;
+        let spl_source_info = next_account_info(account_info_iter)?;
+        let spl_token_a_info = next_account_info(account_info_iter)?;
+        let spl_token_b_info = next_account_info(account_info_iter)?;
+        let spl_pool_fee_info = next_account_info(account_info_iter)?;
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_a_info = next_account_info(account_info_iter)?;
+        let source_b_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if *spl_token_swap_program_info.key != SPL_TOKEN_SWAP_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // withdraw both tokens from the spl-token-swap pool, in its own
+        // instruction layout, the deposit bounding the value received
+        let source_a_before = Self::unpack_token_account(source_a_info, token_program_info.key)?.amount;
+        let source_b_before = Self::unpack_token_account(source_b_info, token_program_info.key)?.amount;
+        let mut data = vec![3];
+        data.extend_from_slice(&pool_token_amount.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        let withdraw_ix = Instruction {
+            program_id: *spl_token_swap_program_info.key,
+            accounts: vec![
+                AccountMeta::new_readonly(*spl_swap_info.key, false),
+                AccountMeta::new_readonly(*spl_authority_info.key, false),
+                AccountMeta::new_readonly(*user_transfer_authority_info.key, true),
+                AccountMeta::new(*spl_pool_mint_info.key, false),
+                AccountMeta::new(*spl_source_info.key, false),
+                AccountMeta::new(*spl_token_a_info.key, false),
+                AccountMeta::new(*spl_token_b_info.key, false),
+                AccountMeta::new(*source_a_info.key, false),
+                AccountMeta::new(*source_b_info.key, false),
+                AccountMeta::new(*spl_pool_fee_info.key, false),
+                AccountMeta::new_readonly(*token_program_info.key, false),
+            ],
+            data,
+        };
+        invoke(
+            &withdraw_ix,
+            &[
+                spl_swap_info.clone(),
+                spl_authority_info.clone(),
+                user_transfer_authority_info.clone(),
+                spl_pool_mint_info.clone(),
+                spl_source_info.clone(),
+                spl_token_a_info.clone(),
+                spl_token_b_info.clone(),
+                source_a_info.clone(),
+                source_b_info.clone(),
+                spl_pool_fee_info.clone(),
+                token_program_info.clone(),
+                spl_token_swap_program_info.clone(),
+            ],
+        )?;
+        let token_a_amount = Self::unpack_token_account(source_a_info, token_program_info.key)?
+            .amount
+            .checked_sub(source_a_before)
+            .ok_or(SwapError::CalculationFailure)?;
+        let token_b_amount = Self::unpack_token_account(source_b_info, token_program_info.key)?
+            .amount
+            .checked_sub(source_b_before)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        let deposit_pool_token_amount = Self::pool_tokens_for_amounts(
+            swap_info,
+            token_a_info,
+            token_b_info,
+            pool_mint_info,
+            token_program_info,
+            token_a_amount,
+            token_b_amount,
+        )?;
+        if deposit_pool_token_amount < minimum_pool_token_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+
+        let mut deposit_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            state_info.clone(),
+            user_transfer_authority_info.clone(),
+            source_a_info.clone(),
+            source_b_info.clone(),
+            token_a_info.clone(),
+            token_b_info.clone(),
+            pool_mint_info.clone(),
+            destination_info.clone(),
+            token_program_info.clone(),
+        ];
+        deposit_accounts.extend(account_info_iter.cloned());
+        Self::process_deposit_all_token_types(
+            program_id,
+            deposit_pool_token_amount,
+            token_a_amount,
+            token_b_amount,
+            None,
+            &deposit_accounts,
+        )
+    }
+
+    /// Processes a [ZapOut](enum.Instruction.html).
+    pub fn process_zap_out(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let other_info = next_account_info(account_info_iter)?;
+        let pool_fee_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // the withdrawal's accounts, then the pool's oracle, gate and
+        // protocol liquidity accounts, if any, follow the pool accounts
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        let withdraw_fee_info = match token_swap.withdraw_fee() {
+            Some((_, false)) => Some(next_account_info(account_info_iter)?),
+            _ => None,
+        };
+        let deposit_record_info = if token_swap.min_hold_slots().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let oracle_info = if token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let gate_info = if token_swap.gate().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let protocol_liquidity_info = if token_swap.protocol_liquidity_bps().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let fee_recipient_count = GlobalStateVersion::unpack(&state_info.data.borrow())?
+            .fee_recipients()
+            .count();
+        let fee_recipient_infos = (0..fee_recipient_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+
+        if *token_a_info.key != *token_swap.token_a_account()
+            || *token_b_info.key != *token_swap.token_b_account()
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        let destination_mint =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.mint;
+        let (dest_token_a_info, dest_token_b_info, swap_source_info, swap_destination_info) =
+            if destination_mint == *token_swap.token_a_mint() {
+                (destination_info, other_info, token_b_info, token_a_info)
+            } else if destination_mint == *token_swap.token_b_mint() {
+                (other_info, destination_info, token_a_info, token_b_info)
+            } else {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            };
+
+        let destination_before =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        let other_before = Self::unpack_token_account(other_info, token_program_info.key)?.amount;
+        let mut withdraw_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            state_info.clone(),
+            user_transfer_authority_info.clone(),
+            pool_mint_info.clone(),
+            source_info.clone(),
+            token_a_info.clone(),
+            token_b_info.clone(),
+            dest_token_a_info.clone(),
+            dest_token_b_info.clone(),
+            token_program_info.clone(),
+        ];
+        if let Some(withdraw_fee_info) = withdraw_fee_info {
+            withdraw_accounts.push(withdraw_fee_info.clone());
+        }
+        if let Some(deposit_record_info) = deposit_record_info {
+            withdraw_accounts.push(deposit_record_info.clone());
+        }
+        Self::process_withdraw_all_token_types(program_id, pool_token_amount, 0, 0, &withdraw_accounts)?;
+        let other_amount = Self::unpack_token_account(other_info, token_program_info.key)?
+            .amount
+            .checked_sub(other_before)
+            .ok_or(SwapError::CalculationFailure)?;
+
+        if other_amount > 0 {
+            let mut swap_accounts = vec![
+                swap_info.clone(),
+                authority_info.clone(),
+                user_transfer_authority_info.clone(),
+                state_info.clone(),
+                other_info.clone(),
+                swap_source_info.clone(),
+                swap_destination_info.clone(),
+                destination_info.clone(),
+                pool_mint_info.clone(),
+                pool_fee_info.clone(),
+                token_program_info.clone(),
+            ];
+            if let Some(oracle_info) = oracle_info {
+                swap_accounts.push(oracle_info.clone());
+            }
+            if let Some(gate_info) = gate_info {
+                swap_accounts.push(gate_info.clone());
+            }
+            if let Some(protocol_liquidity_info) = protocol_liquidity_info {
+                swap_accounts.push(protocol_liquidity_info.clone());
+            }
+            for fee_recipient_info in fee_recipient_infos.iter() {
+                swap_accounts.push((*fee_recipient_info).clone());
+            }
+            Self::process_swap(program_id, other_amount, 0, None, 0, None, false, &swap_accounts)?;
+        }
+
+        let destination_after =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        let amount_out = destination_after
+            .checked_sub(destination_before)
+            .ok_or(SwapError::CalculationFailure)?;
+        if amount_out < minimum_amount_out {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+        Ok(())
+    }
+
+    /// Processes a [SetPoolFeeOwner](enum.Instruction.html).
+    pub fn process_set_pool_fee_owner(
+        program_id: &Pubkey,
+        fee_owner: Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        // the pool authority could never spend the fees
+        let authority = Pubkey::create_program_address(
+            &[swap_info.key.as_ref(), &[swap_v2.nonce]],
+            program_id,
+        )
+        .map_err(|_| SwapError::InvalidProgramAddress)?;
+        if fee_owner == authority {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        swap_v2.fee_owner = fee_owner;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetOwnerFeeInPoolTokens](enum.Instruction.html).
+    pub fn process_set_owner_fee_in_pool_tokens(
+        program_id: &Pubkey,
+        owner_fee_in_pool_tokens: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let mut swap_v2 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => return Err(SwapError::ReservesNotTracked.into()),
+        };
+        if owner_fee_in_pool_tokens && !swap_v2.swap_curve.calculator.allows_deposits() {
+            return Err(SwapError::UnsupportedCurveOperation.into());
+        }
+        swap_v2.owner_fee_in_pool_tokens = owner_fee_in_pool_tokens;
+        SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut swap_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Unpacks the basket pool of `basket_info`, checking its authority and
+    /// token program
+    fn unpack_basket(
+        program_id: &Pubkey,
+        basket_info: &AccountInfo,
+        authority_info: &AccountInfo,
+        token_program_info: &AccountInfo,
+    ) -> Result<BasketPool, ProgramError> {
+        if basket_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let basket = BasketPool::unpack(&basket_info.data.borrow())?;
+        Self::assert_pda(&[basket_info.key.as_ref()], program_id, authority_info.key)?;
+        if *token_program_info.key != basket.token_program_id {
+            return Err(SwapError::IncorrectTokenProgramId.into());
+        }
+        Ok(basket)
+    }
+
+    /// Unpacks the initialized global state of `state_info`
+    fn unpack_global_state(program_id: &Pubkey, state_info: &AccountInfo) -> Result<GlobalState, ProgramError> {
+        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
+        if state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = GlobalStateVersion::unpack(&state_info.data.borrow())?;
+        if !state.is_initialized() {
+            return Err(SwapError::NotInitializedState.into());
+        }
+        Ok(state)
+    }
+
+    /// Unpacks the initialized global state of `state_info`, checking that
+    /// `owner_info` is its owner and signed
+    fn check_program_owner(
+        program_id: &Pubkey,
+        state_info: &AccountInfo,
+        owner_info: &AccountInfo,
+    ) -> Result<GlobalState, ProgramError> {
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if *owner_info.key != *state.owner() {
+            return Err(SwapError::InvalidProgramOwner.into());
+        }
+        Ok(state)
+    }
+
+    /// Processes an [InitializeBasket](enum.Instruction.html).
+    pub fn process_initialize_basket(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let basket_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let global_state_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let dead_shares_info = next_account_info(account_info_iter)?;
+        let vault_infos = account_info_iter.as_slice();
+
+        let token_program_id = *token_program_info.key;
+        if basket_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if BasketPool::unpack_unchecked(&basket_info.data.borrow())?.is_initialized {
+            return Err(SwapError::AlreadyInUse.into());
+        }
+        let (_found_key, nonce) = Pubkey::find_program_address(&[basket_info.key.as_ref()], program_id);
+        Self::assert_pda(&[basket_info.key.as_ref()], program_id, authority_info.key)?;
+        let mut state = Self::unpack_global_state(program_id, global_state_info)?;
+        if vault_infos.len() < MIN_BASKET_ASSETS || vault_infos.len() > MAX_BASKET_ASSETS {
+            return Err(SwapError::InvalidBasketAssetCount.into());
+        }
+
+        let destination = Self::unpack_token_account(destination_info, &token_program_id)?;
+        let pool_mint = Self::unpack_mint(pool_mint_info, &token_program_id)?;
+        if *authority_info.key == destination.owner {
+            return Err(SwapError::InvalidOutputOwner.into());
+        }
+        if COption::Some(*authority_info.key) != pool_mint.mint_authority {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if pool_mint.supply != 0 {
+            return Err(SwapError::InvalidSupply.into());
+        }
+        if pool_mint.freeze_authority.is_some() {
+            return Err(SwapError::InvalidFreezeAuthority.into());
+        }
+        if pool_mint.decimals > MAX_LP_DECIMALS {
+            return Err(SwapError::MismatchDecimalValidation.into());
+        }
+
+        let mut assets: Vec<BasketAsset> = Vec::with_capacity(vault_infos.len());
+        for vault_info in vault_infos.iter() {
+            let vault = Self::unpack_token_account(vault_info, &token_program_id)?;
+            if *authority_info.key != vault.owner {
+                return Err(SwapError::InvalidOwner.into());
+            }
+            if vault.delegate.is_some() {
+                return Err(SwapError::InvalidDelegate.into());
+            }
+            if vault.close_authority.is_some() {
+                return Err(SwapError::InvalidCloseAuthority.into());
+            }
+            if vault.is_frozen() {
+                return Err(SwapError::InvalidFreezeAuthority.into());
+            }
+            if vault.amount == 0 {
+                return Err(SwapError::EmptySupply.into());
+            }
+            if assets.iter().any(|asset| asset.mint == vault.mint) {
+                return Err(SwapError::RepeatedMint.into());
+            }
+            assets.push(BasketAsset {
+                vault: *vault_info.key,
+                mint: vault.mint,
+                reserve: vault.amount,
+            });
+        }
+
+        let basket = BasketPool {
+            is_initialized: true,
+            nonce,
+            token_program_id,
+            pool_mint: *pool_mint_info.key,
+            assets,
+        };
+        // as for pools of two tokens
+        let initial_amount = Self::initial_pool_tokens(&basket.reserves(), state.initial_supply())?;
+        Self::mint_initial_pool_tokens(
+            basket_info.key,
+            token_program_info,
+            pool_mint_info,
+            destination_info,
+            dead_shares_info,
+            authority_info,
+            nonce,
+            initial_amount,
+        )?;
+
+        BasketPool::pack(basket, &mut basket_info.data.borrow_mut())?;
+        state.record_pool()?;
+        GlobalStateVersion::pack(&state, &mut global_state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SwapBasket](enum.Instruction.html).
+    pub fn process_swap_basket(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let basket_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let swap_source_info = next_account_info(account_info_iter)?;
+        let swap_destination_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let fee_account_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut basket =
+            Self::unpack_basket(program_id, basket_info, authority_info, token_program_info)?;
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if state.paused {
+            return Err(SwapError::Paused.into());
+        }
+
+        let source_index = basket
+            .asset_index(swap_source_info.key)
+            .ok_or(SwapError::IncorrectSwapAccount)?;
+        let destination_index = basket
+            .asset_index(swap_destination_info.key)
+            .ok_or(SwapError::IncorrectSwapAccount)?;
+        if source_index == destination_index {
+            return Err(SwapError::InvalidInput.into());
+        }
+        if swap_source_info.key == source_info.key {
+            return Err(SwapError::SourceEqualsVault.into());
+        }
+        if swap_destination_info.key == destination_info.key {
+            return Err(SwapError::DestinationEqualsVault.into());
+        }
+        let fee_account = Self::unpack_token_account(fee_account_info, &basket.token_program_id)?;
+        if fee_account.owner != *state.fee_owner() {
+            return Err(SwapError::InvalidOwner.into());
+        }
+        if fee_account.mint != basket.assets[source_index].mint {
+            return Err(SwapError::IncorrectFeeAccountMint.into());
+        }
+
+        // quote what the vault actually received, so tokens taking a cut on
+        // transfer are priced as such
+        let source_balance =
+            Self::unpack_token_account(swap_source_info, &basket.token_program_id)?.amount;
+        Self::token_transfer(
+            basket_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            swap_source_info.clone(),
+            user_transfer_authority_info.clone(),
+            basket.nonce,
+            amount_in,
+        )?;
+        let amount_received = Self::unpack_token_account(swap_source_info, &basket.token_program_id)?
+            .amount
+            .checked_sub(source_balance)
+            .ok_or(SwapError::CalculationFailure)?;
+        let result = basket::swap(
+            to_u128(amount_received)?,
+            to_u128(basket.assets[source_index].reserve)?,
+            to_u128(basket.assets[destination_index].reserve)?,
+            state.fees(),
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        if result.destination_amount_swapped < to_u128(minimum_amount_out)? {
+            return Err(SwapError::ExceededSlippage.into());
+        }
+
+        Self::token_transfer(
+            basket_info.key,
+            token_program_info.clone(),
+            swap_destination_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            basket.nonce,
+            to_u64(result.destination_amount_swapped)?,
+        )?;
+        if result.owner_fee > 0 {
+            Self::token_transfer(
+                basket_info.key,
+                token_program_info.clone(),
+                swap_source_info.clone(),
+                fee_account_info.clone(),
+                authority_info.clone(),
+                basket.nonce,
+                to_u64(result.owner_fee)?,
+            )?;
+        }
+
+        basket.assets[source_index].reserve = to_u64(
+            result
+                .new_swap_source_amount
+                .checked_sub(result.owner_fee)
+                .ok_or(SwapError::CalculationFailure)?,
+        )?;
+        basket.assets[destination_index].reserve = to_u64(result.new_swap_destination_amount)?;
+        BasketPool::pack(basket, &mut basket_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [DepositBasket](enum.Instruction.html).
+    pub fn process_deposit_basket(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        maximum_token_amounts: [u64; MAX_BASKET_ASSETS],
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let basket_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let asset_infos = account_info_iter.as_slice();
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut basket =
+            Self::unpack_basket(program_id, basket_info, authority_info, token_program_info)?;
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        if state.paused {
+            return Err(SwapError::Paused.into());
+        }
+        if *pool_mint_info.key != basket.pool_mint {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if asset_infos.len() != basket.assets.len() * 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let pool_mint = Self::unpack_mint(pool_mint_info, &basket.token_program_id)?;
+        let token_amounts = basket::pool_tokens_to_trading_tokens(
+            to_u128(pool_token_amount)?,
+            to_u128(pool_mint.supply)?,
+            &basket.reserves(),
+            RoundDirection::Ceiling,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+
+        let nonce = basket.nonce;
+        for (index, (asset, infos)) in basket
+            .assets
+            .iter_mut()
+            .zip(asset_infos.chunks_exact(2))
+            .enumerate()
+        {
+            let (source_info, vault_info) = (&infos[0], &infos[1]);
+            if *vault_info.key != asset.vault {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            if source_info.key == vault_info.key {
+                return Err(SwapError::SourceEqualsVault.into());
+            }
+            let token_amount = to_u64(token_amounts[index])?;
+            if token_amount == 0 {
+                return Err(SwapError::ZeroTradingTokens.into());
+            }
+            if token_amount > maximum_token_amounts[index] {
+                return Err(SwapError::ExceededSlippage.into());
+            }
+            Self::token_transfer(
+                basket_info.key,
+                token_program_info.clone(),
+                source_info.clone(),
+                vault_info.clone(),
+                user_transfer_authority_info.clone(),
+                nonce,
+                token_amount,
+            )?;
+            asset.reserve = asset
+                .reserve
+                .checked_add(token_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+        }
+
+        Self::token_mint_to(
+            basket_info.key,
+            token_program_info.clone(),
+            pool_mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            nonce,
+            pool_token_amount,
+        )?;
+        BasketPool::pack(basket, &mut basket_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [WithdrawBasket](enum.Instruction.html).
+    pub fn process_withdraw_basket(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        minimum_token_amounts: [u64; MAX_BASKET_ASSETS],
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let basket_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let asset_infos = account_info_iter.as_slice();
+
+        if !user_transfer_authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut basket =
+            Self::unpack_basket(program_id, basket_info, authority_info, token_program_info)?;
+        if *pool_mint_info.key != basket.pool_mint {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if asset_infos.len() != basket.assets.len() * 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let pool_mint = Self::unpack_mint(pool_mint_info, &basket.token_program_id)?;
+        let token_amounts = basket::pool_tokens_to_trading_tokens(
+            to_u128(pool_token_amount)?,
+            to_u128(pool_mint.supply)?,
+            &basket.reserves(),
+            RoundDirection::Floor,
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        if token_amounts.iter().all(|token_amount| *token_amount == 0) {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+
+        let nonce = basket.nonce;
+        Self::token_burn(
+            basket_info.key,
+            token_program_info.clone(),
+            source_info.clone(),
+            pool_mint_info.clone(),
+            user_transfer_authority_info.clone(),
+            nonce,
+            pool_token_amount,
+        )?;
+        for (index, (asset, infos)) in basket
+            .assets
+            .iter_mut()
+            .zip(asset_infos.chunks_exact(2))
+            .enumerate()
+        {
+            let (vault_info, destination_info) = (&infos[0], &infos[1]);
+            if *vault_info.key != asset.vault {
+                return Err(SwapError::IncorrectSwapAccount.into());
+            }
+            if vault_info.key == destination_info.key {
+                return Err(SwapError::DestinationEqualsVault.into());
+            }
+            let token_amount = to_u64(token_amounts[index])?;
+            if token_amount < minimum_token_amounts[index] {
+                return Err(SwapError::ExceededSlippage.into());
+            }
+            if token_amount > 0 {
+                Self::token_transfer(
+                    basket_info.key,
+                    token_program_info.clone(),
+                    vault_info.clone(),
+                    destination_info.clone(),
+                    authority_info.clone(),
+                    nonce,
+                    token_amount,
+                )?;
+            }
+            asset.reserve = asset
+                .reserve
+                .checked_sub(token_amount)
+                .ok_or(SwapError::CalculationFailure)?;
+        }
+
+        BasketPool::pack(basket, &mut basket_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [ClosePosition](enum.Instruction.html).
+    pub fn process_close_position(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let position_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let rent_recipient_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let position = Self::unpack_lp_position(
+            program_id,
+            swap_info,
+            position_info,
+            owner_info,
+            token_swap.token_program_id(),
+            account_info_iter,
+        )?;
+        // pool tokens leave a position through DecreasePosition, which
+        // settles its fees, so an empty position earns nothing more
+        if position.pool_tokens != 0 || position.fees_owed_a != 0 || position.fees_owed_b != 0 {
+            return Err(SwapError::PositionNotEmpty.into());
+        }
+        Self::check_rent_recipient(rent_recipient_info, &position.payer, owner_info)?;
+        Self::close_account(position_info, rent_recipient_info)
+    }
+
+    /// Processes a [QuoteWithdraw](enum.Instruction.html).
+    pub fn process_quote_withdraw(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
         let pool_mint_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let state = Self::unpack_global_state(program_id, state_info)?;
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if *pool_mint_info.key != *token_swap.pool_mint() {
+            return Err(SwapError::IncorrectPoolMint.into());
+        }
+        if *token_a_info.key != *token_swap.token_a_account() {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != *token_swap.token_b_account() {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+
+        let (token_a_reserve, token_b_reserve) = token_swap.reserves_at(
+            Self::unpack_token_account(token_a_info, token_swap.token_program_id())?.amount,
+            Self::unpack_token_account(token_b_info, token_swap.token_program_id())?.amount,
+        );
+        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+
+        // the same steps as process_withdraw_all_token_types, without its
+        // slippage checks
+        let max_pool_token_amount = to_u128(pool_mint.supply)?
+            .checked_sub(MIN_LP_SUPPLY)
+            .ok_or(SwapError::CalculationFailure)?;
+        let pool_token_amount = std::cmp::min(to_u128(pool_token_amount)?, max_pool_token_amount);
+        let quote = get_withdraw_quote(
+            token_a_reserve,
+            token_b_reserve,
+            pool_mint.supply,
+            to_u64(pool_token_amount)?,
+            token_swap
+                .withdraw_fee()
+                .map_or(0, |(withdraw_fee_numerator, _)| withdraw_fee_numerator),
+            state.fees(),
+            token_swap.swap_curve(),
+        )
+        .ok_or(SwapError::ZeroTradingTokens)?;
+        let mut token_a_amount = to_u64(quote.token_a_amount)?;
+        if token_a_amount == 0 && token_a_reserve != 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        let mut token_b_amount = to_u64(quote.token_b_amount)?;
+        if token_b_amount == 0 && token_b_reserve != 0 {
+            return Err(SwapError::ZeroTradingTokens.into());
+        }
+        let burn_amount = match token_swap.withdraw_fee() {
+            Some((_, false)) if quote.withdraw_fee > 0 => pool_token_amount
+                .checked_sub(quote.withdraw_fee)
+                .ok_or(SwapError::FeeCalculationFailure)?,
+            _ => pool_token_amount,
+        };
+        if to_u128(pool_mint.supply)?.checked_sub(burn_amount) == Some(MIN_LP_SUPPLY) {
+            token_a_amount = Self::sweep_dust(token_a_reserve, token_a_amount)?;
+            token_b_amount = Self::sweep_dust(token_b_reserve, token_b_amount)?;
+        }
+
+        let mut data = [0u8; 16];
+        data[..8].copy_from_slice(&token_a_amount.to_le_bytes());
+        data[8..].copy_from_slice(&token_b_amount.to_le_bytes());
+        set_return_data(&data);
+        Ok(())
+    }
+
+    /// Processes a [MigrateLiquidity](enum.Instruction.html).
+    pub fn process_migrate_liquidity(
+        program_id: &Pubkey,
+        pool_token_amount: u64,
+        minimum_pool_token_amount: u64,
+        swap_imbalance: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let from_swap_info = next_account_info(account_info_iter)?;
+        let from_authority_info = next_account_info(account_info_iter)?;
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let from_pool_mint_info = next_account_info(account_info_iter)?;
         let source_info = next_account_info(account_info_iter)?;
+        let from_token_a_info = next_account_info(account_info_iter)?;
+        let from_token_b_info = next_account_info(account_info_iter)?;
+        let user_token_a_info = next_account_info(account_info_iter)?;
+        let user_token_b_info = next_account_info(account_info_iter)?;
+        let swap_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
         let token_a_info = next_account_info(account_info_iter)?;
         let token_b_info = next_account_info(account_info_iter)?;
-        let dest_token_a_info = next_account_info(account_info_iter)?;
-        let dest_token_b_info = next_account_info(account_info_iter)?;
+        let pool_mint_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
 
+        if from_swap_info.owner != program_id || swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if from_swap_info.key == swap_info.key {
+            return Err(SwapError::InvalidInput.into());
+        }
+        let from_token_swap = SwapVersion::unpack(&from_swap_info.data.borrow())?;
         let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if *from_token_swap.token_a_mint() != *token_swap.token_a_mint()
+            || *from_token_swap.token_b_mint() != *token_swap.token_b_mint()
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        if *token_a_info.key != *token_swap.token_a_account()
+            || *token_b_info.key != *token_swap.token_b_account()
+        {
+            return Err(SwapError::IncorrectSwapAccount.into());
+        }
+        // the withdrawal's accounts, then the swap's, then the deposit's
+        let withdraw_fee_info = match from_token_swap.withdraw_fee() {
+            Some((_, false)) => Some(next_account_info(account_info_iter)?),
+            _ => None,
+        };
+        let withdraw_record_info = if from_token_swap.min_hold_slots().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let pool_fee_info = if swap_imbalance {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let oracle_info = if swap_imbalance && token_swap.external_oracle().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let gate_info = if token_swap.gate().is_some() {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let protocol_liquidity_info =
+            if swap_imbalance && token_swap.protocol_liquidity_bps().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+        let fee_recipient_count = if swap_imbalance {
+            GlobalStateVersion::unpack(&state_info.data.borrow())?
+                .fee_recipients()
+                .count()
+        } else {
+            0
+        };
+        let fee_recipient_infos = (0..fee_recipient_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
 
-        Self::assert_pda(&[SWAP_TAG.as_bytes(),program_id.as_ref()], program_id, state_info.key)?;
+        let user_token_a_before =
+            Self::unpack_token_account(user_token_a_info, token_program_info.key)?.amount;
+        let user_token_b_before =
+            Self::unpack_token_account(user_token_b_info, token_program_info.key)?.amount;
+        let mut withdraw_accounts = vec![
+            from_swap_info.clone(),
+            from_authority_info.clone(),
+            state_info.clone(),
+            user_transfer_authority_info.clone(),
+            from_pool_mint_info.clone(),
+            source_info.clone(),
+            from_token_a_info.clone(),
+            from_token_b_info.clone(),
+            user_token_a_info.clone(),
+            user_token_b_info.clone(),
+            token_program_info.clone(),
+        ];
+        if let Some(withdraw_fee_info) = withdraw_fee_info {
+            withdraw_accounts.push(withdraw_fee_info.clone());
+        }
+        if let Some(withdraw_record_info) = withdraw_record_info {
+            withdraw_accounts.push(withdraw_record_info.clone());
+        }
+        Self::process_withdraw_all_token_types(program_id, pool_token_amount, 0, 0, &withdraw_accounts)?;
+        let user_token_amounts = || -> Result<(u64, u64), ProgramError> {
+            Ok((
+                Self::unpack_token_account(user_token_a_info, token_program_info.key)?
+                    .amount
+                    .checked_sub(user_token_a_before)
+                    .ok_or(SwapError::CalculationFailure)?,
+                Self::unpack_token_account(user_token_b_info, token_program_info.key)?
+                    .amount
+                    .checked_sub(user_token_b_before)
+                    .ok_or(SwapError::CalculationFailure)?,
+            ))
+        };
+        let (mut token_a_amount, mut token_b_amount) = user_token_amounts()?;
 
-        let state = GlobalState::unpack_from_slice(&state_info.data.borrow())?;
-        if state.is_initialized() == false
-        {
-            return Err(SwapError::NotInitializedState.into());
+        if let Some(pool_fee_info) = pool_fee_info {
+            // swap the share of the token in excess of the reserves' ratio
+            // that balances the rest
+            let (token_a_reserve, token_b_reserve) = token_swap.reserves_at(
+                Self::unpack_token_account(token_a_info, token_program_info.key)?.amount,
+                Self::unpack_token_account(token_b_info, token_program_info.key)?.amount,
+            );
+            let a_in_excess = U256::from(token_a_amount)
+                .checked_mul(U256::from(token_b_reserve))
+                .ok_or(SwapError::CalculationFailure)?
+                > U256::from(token_b_amount)
+                    .checked_mul(U256::from(token_a_reserve))
+                    .ok_or(SwapError::CalculationFailure)?;
+            let (trade_direction, source_amount, destination_amount, source_reserve, destination_reserve) =
+                if a_in_excess {
+                    (TradeDirection::AtoB, token_a_amount, token_b_amount, token_a_reserve, token_b_reserve)
+                } else {
+                    (TradeDirection::BtoA, token_b_amount, token_a_amount, token_b_reserve, token_a_reserve)
+                };
+            // an excess too small to swap for any of the other token is
+            // left over
+            let swap_amount = match Self::imbalance_swap_amount(
+                token_swap.swap_curve(),
+                source_amount,
+                destination_amount,
+                source_reserve,
+                destination_reserve,
+                trade_direction,
+            ) {
+                Err(error) if error == SwapError::ZeroTradingTokens.into() => 0,
+                swap_amount => swap_amount?,
+            };
+            if swap_amount > 0 {
+                let (source_info, swap_source_info, swap_destination_info, destination_info) =
+                    match trade_direction {
+                        TradeDirection::AtoB => {
+                            (user_token_a_info, token_a_info, token_b_info, user_token_b_info)
+                        }
+                        TradeDirection::BtoA => {
+                            (user_token_b_info, token_b_info, token_a_info, user_token_a_info)
+                        }
+                    };
+                let mut swap_accounts = vec![
+                    swap_info.clone(),
+                    authority_info.clone(),
+                    user_transfer_authority_info.clone(),
+                    state_info.clone(),
+                    source_info.clone(),
+                    swap_source_info.clone(),
+                    swap_destination_info.clone(),
+                    destination_info.clone(),
+                    pool_mint_info.clone(),
+                    pool_fee_info.clone(),
+                    token_program_info.clone(),
+                ];
+                if let Some(oracle_info) = oracle_info {
+                    swap_accounts.push(oracle_info.clone());
+                }
+                if let Some(gate_info) = gate_info {
+                    swap_accounts.push(gate_info.clone());
+                }
+                if let Some(protocol_liquidity_info) = protocol_liquidity_info {
+                    swap_accounts.push(protocol_liquidity_info.clone());
+                }
+                for fee_recipient_info in fee_recipient_infos.iter() {
+                    swap_accounts.push((*fee_recipient_info).clone());
+                }
+                Self::process_swap(program_id, swap_amount, 0, None, 0, None, false, &swap_accounts)?;
+                let (new_token_a_amount, new_token_b_amount) = user_token_amounts()?;
+                token_a_amount = new_token_a_amount;
+                token_b_amount = new_token_b_amount;
+            }
         }
 
-        Self::check_accounts(
-            token_swap.as_ref(),
-            program_id,
+        // the most pool tokens both amounts pay for, the one slippage check
+        // of the whole migration
+        let deposit_pool_token_amount = Self::pool_tokens_for_amounts(
             swap_info,
-            authority_info,
             token_a_info,
             token_b_info,
             pool_mint_info,
             token_program_info,
-            Some(dest_token_a_info),
-            Some(dest_token_b_info),
+            token_a_amount,
+            token_b_amount,
         )?;
+        if deposit_pool_token_amount < minimum_pool_token_amount {
+            return Err(SwapError::ExceededSlippage.into());
+        }
 
-        let token_a = Self::unpack_token_account(token_a_info, token_swap.token_program_id())?;
-        let token_b = Self::unpack_token_account(token_b_info, token_swap.token_program_id())?;
-        let pool_mint = Self::unpack_mint(pool_mint_info, token_swap.token_program_id())?;
+        let mut deposit_accounts = vec![
+            swap_info.clone(),
+            authority_info.clone(),
+            state_info.clone(),
+            user_transfer_authority_info.clone(),
+            user_token_a_info.clone(),
+            user_token_b_info.clone(),
+            token_a_info.clone(),
+            token_b_info.clone(),
+            pool_mint_info.clone(),
+            destination_info.clone(),
+            token_program_info.clone(),
+        ];
+        if let Some(gate_info) = gate_info {
+            deposit_accounts.push(gate_info.clone());
+        }
+        deposit_accounts.extend(account_info_iter.cloned());
+        Self::process_deposit_all_token_types(
+            program_id,
+            deposit_pool_token_amount,
+            token_a_amount,
+            token_b_amount,
+            None,
+            &deposit_accounts,
+        )
+    }
 
-        let calculator = &token_swap.swap_curve().calculator;
+    /// Processes a [SetFeeGovernance](enum.Instruction.html).
+    pub fn process_set_fee_governance(
+        program_id: &Pubkey,
+        governance_program_id: &Pubkey,
+        realm: &Pubkey,
+        governance_seed: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let fee_governance_info = next_account_info(account_info_iter)?;
+        let realm_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
 
-        let mut pool_token_amount = to_u128(pool_token_amount)?;
+        if !authority_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut state = Self::unpack_global_state(program_id, state_info)?;
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
 
-        //Check the minimum lp token amount
-        let max_pool_token_amount = to_u128(pool_mint.supply)?.checked_sub(MIN_LP_SUPPLY).ok_or(SwapError::CalculationFailure)?;
-        pool_token_amount = std::cmp::min(pool_token_amount, max_pool_token_amount);
+        let seeds = [FEE_GOVERNANCE_TAG.as_bytes(), program_id.as_ref()];
+        let (fee_governance_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if fee_governance_key != *fee_governance_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if fee_governance_info.data_is_empty() {
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                fee_governance_info,
+                rent_info,
+                system_info,
+                payer_info,
+                FeeGovernance::get_packed_len(),
+                &[FEE_GOVERNANCE_TAG.as_bytes(), program_id.as_ref(), &[bump]],
+            )?;
+        }
+        if fee_governance_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let fee_governance = FeeGovernance::unpack_unchecked(&fee_governance_info.data.borrow())?;
+
+        // once governed, only the governance hands the fees over
+        if state.fee_governed {
+            if *authority_info.key != fee_governance.governance {
+                return Err(SwapError::InvalidFeeGovernance.into());
+            }
+        } else if *authority_info.key != *state.owner() {
+            return Err(SwapError::InvalidProgramOwner.into());
+        }
+
+        let fee_governance = if *governance_program_id == Pubkey::default() {
+            FeeGovernance {
+                bump,
+                ..FeeGovernance::default()
+            }
+        } else {
+            if *realm_info.key != *realm || realm_info.owner != governance_program_id {
+                return Err(SwapError::InvalidFeeGovernance.into());
+            }
+            let (governance, _bump) = Pubkey::find_program_address(
+                &[
+                    SPL_GOVERNANCE_SEED.as_bytes(),
+                    realm.as_ref(),
+                    governance_seed.as_ref(),
+                ],
+                governance_program_id,
+            );
+            FeeGovernance {
+                is_initialized: true,
+                bump,
+                governance_program_id: *governance_program_id,
+                realm: *realm,
+                governance,
+            }
+        };
+        state.fee_governed = fee_governance.is_initialized;
+        FeeGovernance::pack(fee_governance, &mut fee_governance_info.data.borrow_mut())?;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetGovernedFees](enum.Instruction.html).
+    pub fn process_set_governed_fees(
+        program_id: &Pubkey,
+        fees: Fees,
+        fee_discount: FeeDiscount,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let fee_governance_info = next_account_info(account_info_iter)?;
+        let governance_info = next_account_info(account_info_iter)?;
+
+        if !governance_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        let mut state = Self::unpack_global_state(program_id, state_info)?;
+        Self::assert_pda(&[FEE_GOVERNANCE_TAG.as_bytes(),program_id.as_ref()], program_id, fee_governance_info.key)?;
+        if fee_governance_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let fee_governance = FeeGovernance::unpack(&fee_governance_info.data.borrow())?;
+        if !state.fee_governed || *governance_info.key != fee_governance.governance {
+            return Err(SwapError::InvalidFeeGovernance.into());
+        }
+
+        SWAP_CONSTRAINTS.validate_fees(&fees)?;
+        fees.validate()?;
+        fee_discount.validate()?;
+        state.fees = fees;
+        state.fee_discount = fee_discount;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SchedulePendingAction](enum.Instruction.html).
+    pub fn process_schedule_pending_action(
+        program_id: &Pubkey,
+        eta: i64,
+        action: AdminAction,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let scheduler_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        let state = Self::check_program_owner(program_id, state_info, owner_info)?;
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+
+        let minimum_eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(MIN_PENDING_ACTION_DELAY)
+            .ok_or(SwapError::CalculationFailure)?;
+        if eta < minimum_eta {
+            return Err(SwapError::PendingActionDelayTooShort.into());
+        }
+        match &action {
+            AdminAction::SetFees { fees, fee_discount } => {
+                if state.fee_governed {
+                    return Err(SwapError::FeesGoverned.into());
+                }
+                SWAP_CONSTRAINTS.validate_fees(fees)?;
+                fees.validate()?;
+                fee_discount.validate()?;
+            }
+            AdminAction::SetAmp { amp, .. } => {
+                if *amp == 0 {
+                    return Err(SwapError::InvalidCurve.into());
+                }
+            }
+            AdminAction::SetOwner { .. } => {}
+        }
+
+        let seeds = [SCHEDULER_TAG.as_bytes(), program_id.as_ref()];
+        let (scheduler_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+        if scheduler_key != *scheduler_info.key {
+            return Err(SwapError::InvalidProgramAddress.into());
+        }
+        if scheduler_info.data_is_empty() {
+            Self::create_or_allocate_account_raw(
+                *program_id,
+                scheduler_info,
+                rent_info,
+                system_info,
+                owner_info,
+                Scheduler::get_packed_len(),
+                &[SCHEDULER_TAG.as_bytes(), program_id.as_ref(), &[bump]],
+            )?;
+        }
+        if scheduler_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut scheduler = Scheduler::unpack_unchecked(&scheduler_info.data.borrow())?;
+        if scheduler.actions.len() >= MAX_PENDING_ACTIONS {
+            return Err(SwapError::TooManyPendingActions.into());
+        }
+        scheduler.actions.push(PendingAction {
+            action_id: scheduler.next_action_id,
+            eta,
+            action,
+        });
+        scheduler.next_action_id = scheduler
+            .next_action_id
+            .checked_add(1)
+            .ok_or(SwapError::CalculationFailure)?;
+        scheduler.is_initialized = true;
+        scheduler.bump = bump;
+        Scheduler::pack(scheduler, &mut scheduler_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Unpack the scheduler of the program and the position of one of its
+    /// pending actions
+    fn pending_action(
+        program_id: &Pubkey,
+        scheduler_info: &AccountInfo,
+        action_id: u64,
+    ) -> Result<(Scheduler, usize), ProgramError> {
+        Self::assert_pda(&[SCHEDULER_TAG.as_bytes(),program_id.as_ref()], program_id, scheduler_info.key)?;
+        if scheduler_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let scheduler = Scheduler::unpack(&scheduler_info.data.borrow())?;
+        let position = scheduler
+            .actions
+            .iter()
+            .position(|pending_action| pending_action.action_id == action_id)
+            .ok_or(SwapError::PendingActionNotFound)?;
+        Ok((scheduler, position))
+    }
+
+    /// Processes an [ExecutePendingAction](enum.Instruction.html).
+    pub fn process_execute_pending_action(
+        program_id: &Pubkey,
+        action_id: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let scheduler_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::unpack_global_state(program_id, state_info)?;
+        let (mut scheduler, position) = Self::pending_action(program_id, scheduler_info, action_id)?;
+        if Clock::get()?.unix_timestamp < scheduler.actions[position].eta {
+            return Err(SwapError::PendingActionNotReady.into());
+        }
+
+        match scheduler.actions.remove(position).action {
+            AdminAction::SetFees { fees, fee_discount } => {
+                if state.fee_governed {
+                    return Err(SwapError::FeesGoverned.into());
+                }
+                state.fees = fees;
+                state.fee_discount = fee_discount;
+                GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+            }
+            AdminAction::SetAmp { swap, amp } => {
+                let swap_info = next_account_info(account_info_iter)?;
+                if *swap_info.key != swap {
+                    return Err(SwapError::IncorrectSwapAccount.into());
+                }
+                if swap_info.owner != program_id {
+                    return Err(ProgramError::IncorrectProgramId);
+                }
+                let mut swap_version = SwapVersion::unpack_version(&swap_info.data.borrow())?;
+                let swap_curve = match &mut swap_version {
+                    SwapVersion::SwapV1(swap_v1) => &mut swap_v1.swap_curve,
+                    SwapVersion::SwapV2(swap_v2) => &mut swap_v2.swap_curve,
+                };
+                if swap_curve.curve_type != CurveType::Stable {
+                    return Err(SwapError::InvalidCurve.into());
+                }
+                let mut stable_curve = Self::stable_curve(swap_curve)?;
+                Self::check_amp_change(stable_curve.amp, amp)?;
+                stable_curve.amp = amp;
+                swap_curve.calculator = stable_curve.into();
+                swap_curve.calculator.validate()?;
+                SwapVersion::pack(swap_version, &mut swap_info.data.borrow_mut())?;
+            }
+            AdminAction::SetOwner { owner } => {
+                state.owner = owner;
+                GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+            }
+        }
+        Scheduler::pack(scheduler, &mut scheduler_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [CancelPendingAction](enum.Instruction.html).
+    pub fn process_cancel_pending_action(
+        program_id: &Pubkey,
+        action_id: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let scheduler_info = next_account_info(account_info_iter)?;
+
+        Self::check_program_owner(program_id, state_info, owner_info)?;
+
+        let (mut scheduler, position) = Self::pending_action(program_id, scheduler_info, action_id)?;
+        scheduler.actions.remove(position);
+        Scheduler::pack(scheduler, &mut scheduler_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [SetPoolCount](enum.Instruction.html).
+    pub fn process_set_pool_count(
+        program_id: &Pubkey,
+        pool_count: u64,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let state_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+
+        let mut state = Self::check_program_owner(program_id, state_info, owner_info)?;
+        // seeded once, before or instead of the first pool counted
+        if state.pool_count > 0 || pool_count == 0 {
+            return Err(SwapError::PoolCountSet.into());
+        }
+
+        state.pool_count = pool_count;
+        GlobalStateVersion::pack(&state, &mut state_info.data.borrow_mut())?;
+        Ok(())
+    }
+
+    /// Processes a [ClaimBatchOrders](enum.Instruction.html).
+    pub fn process_claim_batch_orders(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let batch_vault_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
 
-        let results = calculator
-            .pool_tokens_to_trading_tokens(
-                pool_token_amount,
-                to_u128(pool_mint.supply)?,
-                to_u128(token_a.amount)?,
-                to_u128(token_b.amount)?,
-                RoundDirection::Floor,
-            )
-            .ok_or(SwapError::ZeroTradingTokens)?;
-        let token_a_amount = to_u64(results.token_a_amount)?;
-        let token_a_amount = std::cmp::min(token_a.amount, token_a_amount);
-        if token_a_amount < minimum_token_a_amount {
-            return Err(SwapError::ExceededSlippage.into());
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        if token_a_amount == 0 && token_a.amount != 0 {
-            return Err(SwapError::ZeroTradingTokens.into());
+        let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+        if !owner_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
         }
-        let token_b_amount = to_u64(results.token_b_amount)?;
-        let token_b_amount = std::cmp::min(token_b.amount, token_b_amount);
-        if token_b_amount < minimum_token_b_amount {
-            return Err(SwapError::ExceededSlippage.into());
+        if *token_program_info.key != *token_swap.token_program_id() {
+            return Err(SwapError::IncorrectTokenProgramId.into());
         }
-        if token_b_amount == 0 && token_b.amount != 0 {
-            return Err(SwapError::ZeroTradingTokens.into());
+        let mut batch = Self::unpack_batch_auction(program_id, batch_info, swap_info)?;
+        if *batch_vault_info.key != batch.token_a_vault && *batch_vault_info.key != batch.token_b_vault {
+            return Err(SwapError::IncorrectSwapAccount.into());
         }
 
-        Self::token_burn(
-            swap_info.key,
+        // a filled swap is paid from the vault of its DESTINATION token, a
+        // refunded one from the vault of its SOURCE token
+        let (token_a_vault, token_b_vault) = (batch.token_a_vault, batch.token_b_vault);
+        let claim_vault = |order: &BatchOrder| match (order.trade_direction, order.filled) {
+            (TradeDirection::AtoB, false) | (TradeDirection::BtoA, true) => token_a_vault,
+            (TradeDirection::BtoA, false) | (TradeDirection::AtoB, true) => token_b_vault,
+        };
+        let mut amount = 0u64;
+        let mut orders = Vec::with_capacity(batch.orders.len());
+        for order in batch.orders.drain(..) {
+            if !order.is_queued()
+                && order.owner == *owner_info.key
+                && claim_vault(&order) == *batch_vault_info.key
+            {
+                amount = amount
+                    .checked_add(order.claimable)
+                    .ok_or(SwapError::CalculationFailure)?;
+            } else {
+                orders.push(order);
+            }
+        }
+        if amount == 0 {
+            return Err(SwapError::NoBatchClaim.into());
+        }
+        batch.orders = orders;
+        batch.pack_into_slice(&mut batch_info.data.borrow_mut());
+
+        Self::token_transfer_signed(
             token_program_info.clone(),
-            source_info.clone(),
-            pool_mint_info.clone(),
-            user_transfer_authority_info.clone(),
-            token_swap.nonce(),
-            to_u64(pool_token_amount)?,
-        )?;
+            batch_vault_info.clone(),
+            destination_info.clone(),
+            batch_info.clone(),
+            &[BATCH_TAG.as_bytes(), swap_info.key.as_ref(), &[batch.bump]],
+            amount,
+        )
+    }
 
-        if token_a_amount > 0 {
-            Self::token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                token_a_info.clone(),
-                dest_token_a_info.clone(),
-                authority_info.clone(),
-                token_swap.nonce(),
-                token_a_amount,
-            )?;
+    /// Processes a [MigratePoolState](enum.Instruction.html).
+    pub fn process_migrate_pool_state(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let swap_info = next_account_info(account_info_iter)?;
+        let token_a_info = next_account_info(account_info_iter)?;
+        let token_b_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_info = next_account_info(account_info_iter)?;
+        let rent_info = next_account_info(account_info_iter)?;
+
+        if swap_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        if token_b_amount > 0 {
-            Self::token_transfer(
-                swap_info.key,
-                token_program_info.clone(),
-                token_b_info.clone(),
-                dest_token_b_info.clone(),
-                authority_info.clone(),
-                token_swap.nonce(),
-                token_b_amount,
+        let swap_v1 = match SwapVersion::unpack_version(&swap_info.data.borrow())? {
+            SwapVersion::SwapV1(swap_v1) => swap_v1,
+            SwapVersion::SwapV2(_) => return Err(SwapError::PoolStateMigrated.into()),
+        };
+        if !swap_v1.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if *token_a_info.key != swap_v1.token_a {
+            return Err(SwapError::IncorrectTokenAVault.into());
+        }
+        if *token_b_info.key != swap_v1.token_b {
+            return Err(SwapError::IncorrectTokenBVault.into());
+        }
+        if !payer_info.is_signer {
+            return Err(SwapError::InvalidSigner.into());
+        }
+        if !system_program::check_id(system_info.key) {
+            return Err(SwapError::InvalidSystemProgramId.into());
+        }
+        if !rent::check_id(rent_info.key) {
+            return Err(SwapError::InvalidRentSysvarId.into());
+        }
+        let token_a = Self::unpack_token_account(token_a_info, &swap_v1.token_program_id)?;
+        let token_b = Self::unpack_token_account(token_b_info, &swap_v1.token_program_id)?;
+
+        let rent = &Rent::from_account_info(rent_info)?;
+        let required_lamports = rent
+            .minimum_balance(SwapVersion::LATEST_LEN)
+            .saturating_sub(swap_info.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, swap_info.key, required_lamports),
+                &[payer_info.clone(), swap_info.clone(), system_info.clone()],
             )?;
         }
+        swap_info.realloc(SwapVersion::LATEST_LEN, true)?;
+
+        // the authority, vaults and curve carry over, the tracked state
+        // starts as for a new pool holding the balances of the vaults
+        let price = PriceOracle::spot_price(to_u128(token_a.amount)?, to_u128(token_b.amount)?)
+            .ok_or(SwapError::CalculationFailure)?;
+        let obj = SwapVersion::SwapV2(SwapV2 {
+            is_initialized: true,
+            nonce: swap_v1.nonce,
+            token_program_id: swap_v1.token_program_id,
+            token_a: swap_v1.token_a,
+            token_b: swap_v1.token_b,
+            pool_mint: swap_v1.pool_mint,
+            token_a_mint: swap_v1.token_a_mint,
+            token_b_mint: swap_v1.token_b_mint,
+            swap_curve: swap_v1.swap_curve,
+            oracle: PriceOracle::new(Clock::get()?.slot, price),
+            token_a_reserve: token_a.amount,
+            token_b_reserve: token_b.amount,
+            rebalance_epoch: 0,
+            rebalance_amount: 0,
+            external_oracle: ExternalOracle::default(),
+            quote_nonce: 0,
+            last_swap_slot: 0,
+            last_price: 0,
+            deprecated: false,
+            max_lp_supply: 0,
+            gated: false,
+            gate_mint: Pubkey::default(),
+            withdraw_fee_numerator: 0,
+            withdraw_fee_burned: false,
+            min_hold_slots: 0,
+            protocol_liquidity_bps: 0,
+            batch_window_slots: 0,
+            rebasing: false,
+            rate_account: Pubkey::default(),
+            position_vault: Pubkey::default(),
+            claimable_fees: false,
+            position_pool_tokens: 0,
+            fee_growth_a: 0,
+            fee_growth_b: 0,
+            unclaimed_fee_a: 0,
+            unclaimed_fee_b: 0,
+            nft_positions: false,
+            paused: false,
+            max_slot_volume: 0,
+            volume_slot: 0,
+            slot_volume: 0,
+            breaker_move_bps: 0,
+            breaker_window_slots: 0,
+            breaker_slot: 0,
+            breaker_price: 0,
+            breaker_tripped: false,
+            fee_owner: Pubkey::default(),
+            owner_fee_in_pool_tokens: false,
+            keeper_slot: 0,
+        });
+        SwapVersion::pack(obj, &mut swap_info.data.borrow_mut())?;
         Ok(())
     }
 
     /// Processes an [Instruction](enum.Instruction.html).
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        #[cfg(feature = "spl-compat")]
+        if let Some((input, accounts)) =
+            crate::compat::upstream_instruction(program_id, input, accounts)
+        {
+            return Self::process(program_id, &accounts, &input);
+        }
         let instruction = SwapInstruction::unpack(input)?;
         match instruction {
             SwapInstruction::Initialize(Initialize {
-                swap_curve
+                swap_curve,
+                fee_owner,
             }) => {
                 msg!("Instruction: Init");
                 Self::process_initialize(
                     program_id,
                     swap_curve,
+                    fee_owner,
                     accounts,
                 )
             }
             SwapInstruction::Swap(Swap {
                 amount_in,
                 minimum_amount_out,
+                max_price_impact_bps,
+                tip,
+                memo,
+                trade_direction,
+                fee_discount,
             }) => {
                 msg!("Instruction: Swap");
-                Self::process_swap(program_id, amount_in, minimum_amount_out, accounts)
+                Self::process_swap(
+                    program_id,
+                    amount_in,
+                    minimum_amount_out,
+                    max_price_impact_bps,
+                    tip,
+                    trade_direction,
+                    fee_discount,
+                    accounts,
+                )?;
+                if !memo.is_empty() {
+                    sol_log_data(&[&memo]);
+                }
+                Ok(())
             }
             SwapInstruction::DepositAllTokenTypes(DepositAllTokenTypes {
                 pool_token_amount,
                 maximum_token_a_amount,
                 maximum_token_b_amount,
+                max_ratio_deviation_bps,
             }) => {
                 msg!("Instruction: DepositAllTokenTypes");
                 Self::process_deposit_all_token_types(
@@ -882,6 +8295,7 @@ impl Processor {
                     pool_token_amount,
                     maximum_token_a_amount,
                     maximum_token_b_amount,
+                    max_ratio_deviation_bps,
                     accounts,
                 )
             }
@@ -905,6 +8319,8 @@ impl Processor {
                 initial_supply,
                 lp_decimals,
                 fees,
+                fee_discount,
+                quoter,
             }) => {
                 msg!("Instruction: SetGlobalStateInstruction");
                 Self::process_set_global_state(
@@ -914,107 +8330,475 @@ impl Processor {
                     initial_supply,
                     lp_decimals,
                     fees,
+                    fee_discount,
+                    &quoter,
                     accounts,
                 )
             }
-        }
-    }
-}
-
-impl PrintProgramError for SwapError {
-    fn print<E>(&self)
-    where
-        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
-    {
-        match self {
-            SwapError::NotRentExempt => msg!("Error: Not Rent Exempt"),
-            SwapError::AlreadyInUse => msg!("Error: Swap account already in use"),
-            SwapError::InvalidProgramAddress => {
-                msg!("Error: Invalid program address generated from nonce and key")
+            SwapInstruction::LockLiquidity(LockLiquidity {
+                amount,
+                unlock_timestamp,
+            }) => {
+                msg!("Instruction: LockLiquidity");
+                Self::process_lock_liquidity(program_id, amount, unlock_timestamp, accounts)
+            }
+            SwapInstruction::UnlockLiquidity => {
+                msg!("Instruction: UnlockLiquidity");
+                Self::process_unlock_liquidity(program_id, accounts)
+            }
+            SwapInstruction::InitializeStakePool(InitializeStakePool { reward_per_slot }) => {
+                msg!("Instruction: InitializeStakePool");
+                Self::process_initialize_stake_pool(program_id, reward_per_slot, accounts)
+            }
+            SwapInstruction::Stake(Stake { amount }) => {
+                msg!("Instruction: Stake");
+                Self::process_stake(program_id, amount, accounts)
+            }
+            SwapInstruction::Unstake(Unstake { amount }) => {
+                msg!("Instruction: Unstake");
+                Self::process_unstake(program_id, amount, accounts)
+            }
+            SwapInstruction::ClaimRewards => {
+                msg!("Instruction: ClaimRewards");
+                Self::process_claim_rewards(program_id, accounts)
+            }
+            SwapInstruction::Sync => {
+                msg!("Instruction: Sync");
+                Self::process_sync(program_id, accounts)
+            }
+            SwapInstruction::Skim => {
+                msg!("Instruction: Skim");
+                Self::process_skim(program_id, accounts)
+            }
+            SwapInstruction::Rebalance(Rebalance {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: Rebalance");
+                Self::process_rebalance(program_id, amount_in, minimum_amount_out, accounts)
+            }
+            SwapInstruction::SetPoolOracle(SetPoolOracle { max_deviation_bps }) => {
+                msg!("Instruction: SetPoolOracle");
+                Self::process_set_pool_oracle(program_id, max_deviation_bps, accounts)
+            }
+            SwapInstruction::SplitSwap(SplitSwap {
+                amount_in,
+                minimum_amount_out,
+                first_pool_bps,
+            }) => {
+                msg!("Instruction: SplitSwap");
+                Self::process_split_swap(
+                    program_id,
+                    amount_in,
+                    minimum_amount_out,
+                    first_pool_bps,
+                    accounts,
+                )
+            }
+            SwapInstruction::SwapWithQuote(SwapWithQuote {
+                amount_in,
+                amount_out,
+                expiry,
+                nonce,
+            }) => {
+                msg!("Instruction: SwapWithQuote");
+                Self::process_swap_with_quote(
+                    program_id,
+                    amount_in,
+                    amount_out,
+                    expiry,
+                    nonce,
+                    accounts,
+                )
             }
-            SwapError::InvalidOwner => {
-                msg!("Error: The input account owner is not the program address")
+            SwapInstruction::PlaceLimitOrder(PlaceLimitOrder {
+                order_id,
+                amount_in,
+                minimum_amount_out,
+                keeper_fee,
+            }) => {
+                msg!("Instruction: PlaceLimitOrder");
+                Self::process_place_limit_order(
+                    program_id,
+                    order_id,
+                    amount_in,
+                    minimum_amount_out,
+                    keeper_fee,
+                    accounts,
+                )
             }
-            SwapError::InvalidOutputOwner => {
-                msg!("Error: Output pool account owner cannot be the program address")
+            SwapInstruction::CancelLimitOrder => {
+                msg!("Instruction: CancelLimitOrder");
+                Self::process_cancel_limit_order(program_id, accounts)
             }
-            SwapError::ExpectedMint => msg!("Error: Deserialized account is not an SPL Token mint"),
-            SwapError::ExpectedAccount => {
-                msg!("Error: Deserialized account is not an SPL Token account")
+            SwapInstruction::FillLimitOrder => {
+                msg!("Instruction: FillLimitOrder");
+                Self::process_fill_limit_order(program_id, accounts)
             }
-            SwapError::EmptySupply => msg!("Error: Input token account empty"),
-            SwapError::InvalidSupply => msg!("Error: Pool token mint has a non-zero supply"),
-            SwapError::RepeatedMint => msg!("Error: Swap input token accounts have the same mint"),
-            SwapError::InvalidDelegate => msg!("Error: Token account has a delegate"),
-            SwapError::InvalidInput => msg!("Error: InvalidInput"),
-            SwapError::IncorrectSwapAccount => {
-                msg!("Error: Address of the provided swap token account is incorrect")
+            SwapInstruction::CreateDca(CreateDca {
+                dca_id,
+                amount,
+                amount_per_tick,
+                minimum_amount_out_per_tick,
+                keeper_fee,
+                interval,
+            }) => {
+                msg!("Instruction: CreateDca");
+                Self::process_create_dca(
+                    program_id,
+                    dca_id,
+                    amount,
+                    amount_per_tick,
+                    minimum_amount_out_per_tick,
+                    keeper_fee,
+                    interval,
+                    accounts,
+                )
             }
-            SwapError::IncorrectPoolMint => {
-                msg!("Error: Address of the provided pool token mint is incorrect")
+            SwapInstruction::ExecuteDcaTick => {
+                msg!("Instruction: ExecuteDcaTick");
+                Self::process_execute_dca_tick(program_id, accounts)
             }
-            SwapError::InvalidOutput => msg!("Error: InvalidOutput"),
-            SwapError::CalculationFailure => msg!("Error: CalculationFailure"),
-            SwapError::InvalidInstruction => msg!("Error: InvalidInstruction"),
-            SwapError::ExceededSlippage => {
-                msg!("Error: Swap instruction exceeds desired slippage limit")
+            SwapInstruction::CloseDca => {
+                msg!("Instruction: CloseDca");
+                Self::process_close_dca(program_id, accounts)
             }
-            SwapError::InvalidCloseAuthority => msg!("Error: Token account has a close authority"),
-            SwapError::InvalidFreezeAuthority => {
-                msg!("Error: Pool token mint has a freeze authority")
+            SwapInstruction::GetVirtualPrice => {
+                msg!("Instruction: GetVirtualPrice");
+                Self::process_get_virtual_price(program_id, accounts)
             }
-            SwapError::IncorrectFeeAccount => msg!("Error: Pool fee token account incorrect"),
-            SwapError::ZeroTradingTokens => {
-                msg!("Error: Given pool token amount results in zero trading tokens")
+            SwapInstruction::GetPoolState => {
+                msg!("Instruction: GetPoolState");
+                Self::process_get_pool_state(program_id, accounts)
             }
-            SwapError::FeeCalculationFailure => msg!(
-                "Error: The fee calculation failed due to overflow, underflow, or unexpected 0"
-            ),
-            SwapError::ConversionFailure => msg!("Error: Conversion to or from u64 failed."),
-            SwapError::InvalidFee => {
-                msg!("Error: The provided fee does not match the program owner's constraints")
+            SwapInstruction::MigrateGlobalState => {
+                msg!("Instruction: MigrateGlobalState");
+                Self::process_migrate_global_state(program_id, accounts)
             }
-            SwapError::IncorrectTokenProgramId => {
-                msg!("Error: The provided token program does not match the token program expected by the swap")
+            SwapInstruction::UpdateCurveParameters(UpdateCurveParameters { swap_curve }) => {
+                msg!("Instruction: UpdateCurveParameters");
+                Self::process_update_curve_parameters(program_id, swap_curve, accounts)
             }
-            SwapError::UnsupportedCurveType => {
-                msg!("Error: The provided curve type is not supported by the program owner")
+            SwapInstruction::DeprecatePool => {
+                msg!("Instruction: DeprecatePool");
+                Self::process_deprecate_pool(program_id, accounts)
             }
-            SwapError::InvalidCurve => {
-                msg!("Error: The provided curve parameters are invalid")
+            SwapInstruction::SetDepositCap(SetDepositCap { max_lp_supply }) => {
+                msg!("Instruction: SetDepositCap");
+                Self::process_set_deposit_cap(program_id, max_lp_supply, accounts)
             }
-            SwapError::UnsupportedCurveOperation => {
-                msg!("Error: The operation cannot be performed on the given curve")
+            SwapInstruction::SetPoolGate(SetPoolGate { gated, gate_mint }) => {
+                msg!("Instruction: SetPoolGate");
+                Self::process_set_pool_gate(program_id, gated, &gate_mint, accounts)
             }
-            SwapError::MismatchDecimalValidation => {
-                msg!("The decimal validation error.")
+            SwapInstruction::SetAllowlisted(SetAllowlisted { allowed }) => {
+                msg!("Instruction: SetAllowlisted");
+                Self::process_set_allowlisted(program_id, allowed, accounts)
             }
-
-            SwapError::InvalidPdaAddress => {
-                msg!("invalid program derived address")
+            SwapInstruction::SetWithdrawFee(SetWithdrawFee {
+                withdraw_fee_numerator,
+                burn,
+            }) => {
+                msg!("Instruction: SetWithdrawFee");
+                Self::process_set_withdraw_fee(program_id, withdraw_fee_numerator, burn, accounts)
+            }
+            SwapInstruction::SetMinHoldPeriod(SetMinHoldPeriod { min_hold_slots }) => {
+                msg!("Instruction: SetMinHoldPeriod");
+                Self::process_set_min_hold_period(program_id, min_hold_slots, accounts)
+            }
+            SwapInstruction::InitDepositRecord => {
+                msg!("Instruction: InitDepositRecord");
+                Self::process_init_deposit_record(program_id, accounts)
+            }
+            SwapInstruction::SetProtocolLiquidityShare(SetProtocolLiquidityShare {
+                protocol_liquidity_bps,
+            }) => {
+                msg!("Instruction: SetProtocolLiquidityShare");
+                Self::process_set_protocol_liquidity_share(program_id, protocol_liquidity_bps, accounts)
+            }
+            SwapInstruction::WithdrawProtocolLiquidity(WithdrawProtocolLiquidity {
+                pool_token_amount,
+            }) => {
+                msg!("Instruction: WithdrawProtocolLiquidity");
+                Self::process_withdraw_protocol_liquidity(program_id, pool_token_amount, accounts)
+            }
+            SwapInstruction::SetBuybackConfig(SetBuybackConfig {
+                buyback_mint,
+                buyback_epoch_limit,
+            }) => {
+                msg!("Instruction: SetBuybackConfig");
+                Self::process_set_buyback_config(program_id, &buyback_mint, buyback_epoch_limit, accounts)
+            }
+            SwapInstruction::BuybackAndBurn(BuybackAndBurn {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: BuybackAndBurn");
+                Self::process_buyback_and_burn(program_id, amount_in, minimum_amount_out, accounts)
+            }
+            SwapInstruction::SetFeeRecipients(SetFeeRecipients { fee_recipients }) => {
+                msg!("Instruction: SetFeeRecipients");
+                Self::process_set_fee_recipients(program_id, fee_recipients, accounts)
+            }
+            SwapInstruction::RecoverForeignTokens => {
+                msg!("Instruction: RecoverForeignTokens");
+                Self::process_recover_foreign_tokens(program_id, accounts)
+            }
+            SwapInstruction::CommitSwap(CommitSwap { hash }) => {
+                msg!("Instruction: CommitSwap");
+                Self::process_commit_swap(program_id, hash, accounts)
+            }
+            SwapInstruction::RevealSwap(RevealSwap {
+                amount_in,
+                minimum_amount_out,
+                salt,
+            }) => {
+                msg!("Instruction: RevealSwap");
+                Self::process_reveal_swap(program_id, amount_in, minimum_amount_out, salt, accounts)
+            }
+            SwapInstruction::SetBatchAuction(SetBatchAuction { batch_window_slots }) => {
+                msg!("Instruction: SetBatchAuction");
+                Self::process_set_batch_auction(program_id, batch_window_slots, accounts)
+            }
+            SwapInstruction::SubmitBatchSwap(SubmitBatchSwap {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: SubmitBatchSwap");
+                Self::process_submit_batch_swap(program_id, amount_in, minimum_amount_out, accounts)
+            }
+            SwapInstruction::SettleBatch => {
+                msg!("Instruction: SettleBatch");
+                Self::process_settle_batch(program_id, accounts)
+            }
+            SwapInstruction::SetRebasing(SetRebasing { rebasing }) => {
+                msg!("Instruction: SetRebasing");
+                Self::process_set_rebasing(program_id, rebasing, accounts)
+            }
+            SwapInstruction::SetRateAccount => {
+                msg!("Instruction: SetRateAccount");
+                Self::process_set_rate_account(program_id, accounts)
+            }
+            SwapInstruction::UpdateRate => {
+                msg!("Instruction: UpdateRate");
+                Self::process_update_rate(program_id, accounts)
+            }
+            SwapInstruction::SetClaimableFees(SetClaimableFees { claimable_fees }) => {
+                msg!("Instruction: SetClaimableFees");
+                Self::process_set_claimable_fees(program_id, claimable_fees, accounts)
+            }
+            SwapInstruction::IncreasePosition(IncreasePosition { pool_token_amount }) => {
+                msg!("Instruction: IncreasePosition");
+                Self::process_increase_position(program_id, pool_token_amount, accounts)
+            }
+            SwapInstruction::DecreasePosition(DecreasePosition { pool_token_amount }) => {
+                msg!("Instruction: DecreasePosition");
+                Self::process_decrease_position(program_id, pool_token_amount, accounts)
+            }
+            SwapInstruction::ClaimFees => {
+                msg!("Instruction: ClaimFees");
+                Self::process_claim_fees(program_id, accounts)
+            }
+            SwapInstruction::SetNftPositions(SetNftPositions { nft_positions }) => {
+                msg!("Instruction: SetNftPositions");
+                Self::process_set_nft_positions(program_id, nft_positions, accounts)
+            }
+            SwapInstruction::SetGuardian(SetGuardian { guardian }) => {
+                msg!("Instruction: SetGuardian");
+                Self::process_set_guardian(program_id, &guardian, accounts)
+            }
+            SwapInstruction::SetPaused(SetPaused { paused }) => {
+                msg!("Instruction: SetPaused");
+                Self::process_set_paused(program_id, paused, accounts)
+            }
+            SwapInstruction::SetPoolPaused(SetPaused { paused }) => {
+                msg!("Instruction: SetPoolPaused");
+                Self::process_set_pool_paused(program_id, paused, accounts)
+            }
+            SwapInstruction::SetSlotVolumeCap(SetSlotVolumeCap { max_slot_volume }) => {
+                msg!("Instruction: SetSlotVolumeCap");
+                Self::process_set_slot_volume_cap(program_id, max_slot_volume, accounts)
+            }
+            SwapInstruction::SetCircuitBreaker(SetCircuitBreaker {
+                breaker_move_bps,
+                breaker_window_slots,
+            }) => {
+                msg!("Instruction: SetCircuitBreaker");
+                Self::process_set_circuit_breaker(
+                    program_id,
+                    breaker_move_bps,
+                    breaker_window_slots,
+                    accounts,
+                )
+            }
+            SwapInstruction::ResetCircuitBreaker => {
+                msg!("Instruction: ResetCircuitBreaker");
+                Self::process_reset_circuit_breaker(program_id, accounts)
+            }
+            SwapInstruction::ZapIn(ZapIn {
+                amount_in,
+                minimum_pool_token_amount,
+            }) => {
+                msg!("Instruction: ZapIn");
+                Self::process_zap_in(program_id, amount_in, minimum_pool_token_amount, accounts)
             }
-            SwapError::InvalidAllocateSpaceForAccount => {
-                msg!("Can't allocate space for the account")
+            SwapInstruction::ZapOut(ZapOut {
+                pool_token_amount,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: ZapOut");
+                Self::process_zap_out(program_id, pool_token_amount, minimum_amount_out, accounts)
+            }
+            SwapInstruction::SetPoolFeeOwner(SetPoolFeeOwner { fee_owner }) => {
+                msg!("Instruction: SetPoolFeeOwner");
+                Self::process_set_pool_fee_owner(program_id, fee_owner, accounts)
+            }
+            SwapInstruction::SetOwnerFeeInPoolTokens(SetOwnerFeeInPoolTokens {
+                owner_fee_in_pool_tokens,
+            }) => {
+                msg!("Instruction: SetOwnerFeeInPoolTokens");
+                Self::process_set_owner_fee_in_pool_tokens(
+                    program_id,
+                    owner_fee_in_pool_tokens,
+                    accounts,
+                )
+            }
+            SwapInstruction::SetKeeperReward(SetKeeperReward {
+                keeper_reward,
+                keeper_interval_slots,
+            }) => {
+                msg!("Instruction: SetKeeperReward");
+                Self::process_set_keeper_reward(
+                    program_id,
+                    keeper_reward,
+                    keeper_interval_slots,
+                    accounts,
+                )
+            }
+            SwapInstruction::MigrateFromSplTokenSwap(MigrateFromSplTokenSwap {
+                pool_token_amount,
+                minimum_pool_token_amount,
+            }) => {
+                msg!("Instruction: MigrateFromSplTokenSwap");
+                Self::process_migrate_from_spl_token_swap(
+                    program_id,
+                    pool_token_amount,
+                    minimum_pool_token_amount,
+                    accounts,
+                )
+            }
+            SwapInstruction::InitializeBasket => {
+                msg!("Instruction: InitializeBasket");
+                Self::process_initialize_basket(program_id, accounts)
+            }
+            SwapInstruction::SwapBasket(SwapBasket {
+                amount_in,
+                minimum_amount_out,
+            }) => {
+                msg!("Instruction: SwapBasket");
+                Self::process_swap_basket(program_id, amount_in, minimum_amount_out, accounts)
+            }
+            SwapInstruction::DepositBasket(DepositBasket {
+                pool_token_amount,
+                maximum_token_amounts,
+            }) => {
+                msg!("Instruction: DepositBasket");
+                Self::process_deposit_basket(
+                    program_id,
+                    pool_token_amount,
+                    maximum_token_amounts,
+                    accounts,
+                )
+            }
+            SwapInstruction::WithdrawBasket(WithdrawBasket {
+                pool_token_amount,
+                minimum_token_amounts,
+            }) => {
+                msg!("Instruction: WithdrawBasket");
+                Self::process_withdraw_basket(
+                    program_id,
+                    pool_token_amount,
+                    minimum_token_amounts,
+                    accounts,
+                )
+            }
+            SwapInstruction::ClosePosition => {
+                msg!("Instruction: ClosePosition");
+                Self::process_close_position(program_id, accounts)
+            }
+            SwapInstruction::QuoteWithdraw(QuoteWithdraw { pool_token_amount }) => {
+                msg!("Instruction: QuoteWithdraw");
+                Self::process_quote_withdraw(program_id, pool_token_amount, accounts)
+            }
+            SwapInstruction::MigrateLiquidity(MigrateLiquidity {
+                pool_token_amount,
+                minimum_pool_token_amount,
+                swap_imbalance,
+            }) => {
+                msg!("Instruction: MigrateLiquidity");
+                Self::process_migrate_liquidity(
+                    program_id,
+                    pool_token_amount,
+                    minimum_pool_token_amount,
+                    swap_imbalance,
+                    accounts,
+                )
+            }
+            SwapInstruction::SetFeeGovernance(SetFeeGovernance {
+                governance_program_id,
+                realm,
+                governance_seed,
+            }) => {
+                msg!("Instruction: SetFeeGovernance");
+                Self::process_set_fee_governance(
+                    program_id,
+                    &governance_program_id,
+                    &realm,
+                    &governance_seed,
+                    accounts,
+                )
+            }
+            SwapInstruction::SetGovernedFees(SetGovernedFees { fees, fee_discount }) => {
+                msg!("Instruction: SetGovernedFees");
+                Self::process_set_governed_fees(program_id, fees, fee_discount, accounts)
+            }
+            SwapInstruction::SchedulePendingAction(SchedulePendingAction { eta, action }) => {
+                msg!("Instruction: SchedulePendingAction");
+                Self::process_schedule_pending_action(program_id, eta, action, accounts)
             }
-            SwapError::InvalidSigner => {
-                msg!("owner should be the signer")
+            SwapInstruction::ExecutePendingAction(ExecutePendingAction { action_id }) => {
+                msg!("Instruction: ExecutePendingAction");
+                Self::process_execute_pending_action(program_id, action_id, accounts)
             }
-            SwapError::InvalidSystemProgramId => {
-                msg!("Invalid SystemProgram Id")
+            SwapInstruction::CancelPendingAction(CancelPendingAction { action_id }) => {
+                msg!("Instruction: CancelPendingAction");
+                Self::process_cancel_pending_action(program_id, action_id, accounts)
             }
-            SwapError::InvalidRentSysvarId => {
-                msg!("Invalid Rent Sysvar Id")
+            SwapInstruction::SetPoolCount(SetPoolCount { pool_count }) => {
+                msg!("Instruction: SetPoolCount");
+                Self::process_set_pool_count(program_id, pool_count, accounts)
             }
-            SwapError::InvalidProgramOwner => {
-                msg!("Invalid owner of the contract")
+            SwapInstruction::ClaimBatchOrders => {
+                msg!("Instruction: ClaimBatchOrders");
+                Self::process_claim_batch_orders(program_id, accounts)
             }
-            SwapError::NotInitializedState => {
-                msg!("Program State should be initialized before creating pool")
+            SwapInstruction::MigratePoolState => {
+                msg!("Instruction: MigratePoolState");
+                Self::process_migrate_pool_state(program_id, accounts)
             }
         }
     }
 }
 
+impl PrintProgramError for SwapError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("Error: {}", self)
+    }
+}
+
 fn to_u128(val: u64) -> Result<u128, SwapError> {
     val.try_into().map_err(|_| SwapError::ConversionFailure)
 }
@@ -0,0 +1,116 @@
+//! Signed off-chain swap quotes, verified through the ed25519 program
+
+use crate::error::SwapError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    ed25519_program, instruction::Instruction, program_error::ProgramError, pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+/// Offset of the signature offsets in ed25519 program instruction data
+const ED25519_OFFSETS_START: usize = 2;
+
+/// Size of one set of signature offsets
+const ED25519_OFFSETS_LEN: usize = 14;
+
+/// Size of an ed25519 public key
+const ED25519_PUBKEY_LEN: usize = 32;
+
+/// Instruction index the ed25519 program reads as "this instruction"
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Quote locking a price for one fill through a pool's vaults
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Quote {
+    /// Token-swap the quote is for
+    pub swap: Pubkey,
+    /// User transfer authority allowed to fill the quote
+    pub taker: Pubkey,
+    /// Vault the taker swaps INTO, fixing the trade direction
+    pub swap_source: Pubkey,
+    /// SOURCE amount the taker sends
+    pub amount_in: u64,
+    /// DESTINATION amount the taker receives
+    pub amount_out: u64,
+    /// Unix timestamp after which the quote can no longer be filled
+    pub expiry: i64,
+    /// Nonce of the quote, must be above the pool's last filled quote nonce
+    pub nonce: u64,
+}
+
+impl Quote {
+    /// Size of the signed quote message
+    pub const LEN: usize = 128;
+
+    /// Serialize the quote into the message signed by the quoter
+    pub fn message(&self) -> [u8; Quote::LEN] {
+        let mut message = [0u8; Quote::LEN];
+        let output = array_mut_ref![message, 0, Quote::LEN];
+        let (swap, taker, swap_source, amount_in, amount_out, expiry, nonce) =
+            mut_array_refs![output, 32, 32, 32, 8, 8, 8, 8];
+        swap.copy_from_slice(self.swap.as_ref());
+        taker.copy_from_slice(self.taker.as_ref());
+        swap_source.copy_from_slice(self.swap_source.as_ref());
+        *amount_in = self.amount_in.to_le_bytes();
+        *amount_out = self.amount_out.to_le_bytes();
+        *expiry = self.expiry.to_le_bytes();
+        *nonce = self.nonce.to_le_bytes();
+        message
+    }
+
+    /// Check that `instruction`, found at `instruction_index` in the
+    /// transaction, is an ed25519 program instruction verifying a signature
+    /// of this quote, and return the signer
+    pub fn verified_signer(
+        &self,
+        instruction: &Instruction,
+        instruction_index: u16,
+    ) -> Result<Pubkey, ProgramError> {
+        if instruction.program_id != ed25519_program::id() {
+            return Err(SwapError::InvalidQuote.into());
+        }
+        let data = &instruction.data;
+        // exactly one signature, so the quote cannot ride along another one
+        if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN || data[0] != 1 {
+            return Err(SwapError::InvalidQuote.into());
+        }
+        let offsets = array_ref![data, ED25519_OFFSETS_START, ED25519_OFFSETS_LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            _signature_offset,
+            signature_instruction_index,
+            public_key_offset,
+            public_key_instruction_index,
+            message_data_offset,
+            message_data_size,
+            message_instruction_index,
+        ) = array_refs![offsets, 2, 2, 2, 2, 2, 2, 2];
+        // the signed data must live in the ed25519 instruction itself
+        for index in [
+            signature_instruction_index,
+            public_key_instruction_index,
+            message_instruction_index,
+        ]
+        .iter()
+        {
+            let index = u16::from_le_bytes(**index);
+            if index != ED25519_CURRENT_INSTRUCTION && index != instruction_index {
+                return Err(SwapError::InvalidQuote.into());
+            }
+        }
+        let public_key_offset = usize::from(u16::from_le_bytes(*public_key_offset));
+        let message_data_offset = usize::from(u16::from_le_bytes(*message_data_offset));
+        let message_data_size = usize::from(u16::from_le_bytes(*message_data_size));
+        let public_key: [u8; ED25519_PUBKEY_LEN] = data
+            .get(public_key_offset..public_key_offset + ED25519_PUBKEY_LEN)
+            .and_then(|public_key| public_key.try_into().ok())
+            .ok_or(SwapError::InvalidQuote)?;
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(SwapError::InvalidQuote)?;
+        if message != &self.message()[..] {
+            return Err(SwapError::InvalidQuote.into());
+        }
+        Ok(Pubkey::new_from_array(public_key))
+    }
+}
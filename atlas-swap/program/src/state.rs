@@ -1,15 +1,17 @@
 //! State transition types
 
-use crate::curve::{base::{SwapCurve}, fees::Fees};
+use crate::curve::{base::{SwapCurve}, basket::MAX_BASKET_ASSETS, calculator::TradeDirection, fees::{FeeDiscount, FeeRecipient, Fees, FEE_RECIPIENT_COUNT}};
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use enum_dispatch::enum_dispatch;
 use solana_program::{
+    hash::hashv,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 
 };
 use crate::error::SwapError;
+use std::convert::TryFrom;
 
 /// Trait representing access to program state across all versions
 #[enum_dispatch]
@@ -33,22 +35,98 @@ pub trait SwapState {
     fn token_b_mint(&self) -> &Pubkey;
     ///
     fn swap_curve(&self) -> &SwapCurve;
+
+    /// Price accumulator of the pool, if the version tracks one
+    fn oracle(&self) -> Option<&PriceOracle>;
+
+    /// Token A and token B reserves of the pool, if the version tracks them
+    fn reserves(&self) -> Option<(u64, u64)>;
+
+    /// External price feed of the pool, if one is configured
+    fn external_oracle(&self) -> Option<&ExternalOracle>;
+
+    /// Slot and execution price of the last trade, if the version tracks them
+    fn last_swap(&self) -> Option<(u64, u128)>;
+
+    /// Is the pool deprecated, open to withdrawals only
+    fn is_deprecated(&self) -> bool;
+
+    /// Pool token supply deposits may not push the pool above, if capped
+    fn max_lp_supply(&self) -> Option<u64>;
+
+    /// Gate token mint of a gated pool, the default pubkey when only the
+    /// allowlist grants access
+    fn gate(&self) -> Option<&Pubkey>;
+
+    /// Withdrawal fee numerator, over the fee denominator of the program
+    /// state, and whether the fee is burned rather than paid to the fee
+    /// owner, if the pool charges one
+    fn withdraw_fee(&self) -> Option<(u64, bool)>;
+
+    /// Slots a depositor has to wait before withdrawing, if the pool
+    /// enforces a holding period
+    fn min_hold_slots(&self) -> Option<u64>;
+
+    /// Share of the owner fee, in basis points, converted into pool tokens
+    /// held by the protocol, if the pool accrues protocol-owned liquidity
+    fn protocol_liquidity_bps(&self) -> Option<u64>;
+
+    /// Slots swaps are accumulated for before being settled together, if
+    /// the pool trades in batch auctions
+    fn batch_window_slots(&self) -> Option<u64>;
+
+    /// Token A and token B reserves the pool trades against, given the
+    /// balances of its vaults: the tracked reserves, or the vault balances
+    /// for pools that do not track them and for rebasing pools
+    fn reserves_at(&self, token_a_balance: u64, token_b_balance: u64) -> (u64, u64);
+
+    /// Token A and token B trade fees held in the vaults for LP positions to
+    /// claim, outside the reserves
+    fn unclaimed_fees(&self) -> (u64, u64);
+
+    /// Vault the pool tokens of deposits are minted into, if the pool gives
+    /// out position NFTs instead of pool tokens
+    fn nft_position_vault(&self) -> Option<&Pubkey>;
+
+    /// Are swaps and deposits into the pool paused, withdrawals staying open
+    fn is_paused(&self) -> bool;
+
+    /// Token A volume the swaps of a single slot may not exceed, if capped
+    fn max_slot_volume(&self) -> Option<u64>;
+
+    /// Are swaps halted by the circuit breaker until it is reset
+    fn circuit_breaker_tripped(&self) -> bool;
+
+    /// Fee owner of the pool's swaps and withdrawal fees, in place of the
+    /// one of the program state, if the pool has its own
+    fn fee_owner(&self) -> Option<&Pubkey>;
+
+    /// Is the fee owner's cut of the owner fee left in the pool and minted
+    /// to it as pool tokens, rather than transferred in the input token
+    fn owner_fee_in_pool_tokens(&self) -> bool;
 }
 
 
-/// All versions of SwapState
+/// All versions of SwapState, a SwapV2 being far larger than a SwapV1.
+/// Versions are unpacked for the length of an instruction, never stored.
+#[allow(clippy::large_enum_variant)]
 #[enum_dispatch(SwapState)]
 pub enum SwapVersion {
-    /// Latest version, used for all new swaps
+    /// Original version, without any tracked pool state
     SwapV1,
+    /// Latest version, used for all new swaps
+    SwapV2,
 }
 
 /// SwapVersion does not implement program_pack::Pack because there are size
 /// checks on pack and unpack that would break backwards compatibility, so
 /// special implementations are provided here
+///
+/// SwapV1 pools are migrated to SwapV2 in place by MigratePoolState, which
+/// grows their accounts to `LATEST_LEN`
 impl SwapVersion {
     /// Size of the latest version of the SwapState
-    pub const LATEST_LEN: usize = 1 + SwapV1::LEN; // add one for the version enum
+    pub const LATEST_LEN: usize = 1 + SwapV2::LEN; // add one for the version enum
 
     /// Pack a swap into a byte array, based on its version
     pub fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
@@ -57,6 +135,10 @@ impl SwapVersion {
                 dst[0] = 1;
                 SwapV1::pack(swap_info, &mut dst[1..])
             }
+            Self::SwapV2(swap_info) => {
+                dst[0] = 2;
+                SwapV2::pack(swap_info, &mut dst[1..])
+            }
         }
     }
 
@@ -68,6 +150,20 @@ impl SwapVersion {
             .ok_or(ProgramError::InvalidAccountData)?;
         match version {
             1 => Ok(Box::new(SwapV1::unpack(rest)?)),
+            2 => Ok(Box::new(SwapV2::unpack(rest)?)),
+            _ => Err(ProgramError::UninitializedAccount),
+        }
+    }
+
+    /// Unpack the swap account into its concrete version, for instructions
+    /// that update the tracked pool state and pack it back
+    pub fn unpack_version(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&version, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        match version {
+            1 => Ok(Self::SwapV1(SwapV1::unpack(rest)?)),
+            2 => Ok(Self::SwapV2(SwapV2::unpack(rest)?)),
             _ => Err(ProgramError::UninitializedAccount),
         }
     }
@@ -82,6 +178,44 @@ impl SwapVersion {
     }
 }
 
+// Byte offsets of fields in a swap account, version byte included, for
+// getProgramAccounts filters. SwapV2 extends the layout of SwapV1, so the
+// SwapV1 offsets hold for both; the version byte and the account size tell
+// them apart from each other and from the other accounts of the program.
+
+/// Offset of the version byte of a swap account
+pub const SWAP_VERSION_OFFSET: usize = 0;
+/// Version byte of a SwapV1 account
+pub const SWAP_V1_VERSION: u8 = 1;
+/// Version byte of a SwapV2 account
+pub const SWAP_V2_VERSION: u8 = 2;
+/// Size of a SwapV1 account
+pub const SWAP_V1_ACCOUNT_LEN: usize = 1 + SwapV1::LEN;
+/// Size of a SwapV2 account
+pub const SWAP_V2_ACCOUNT_LEN: usize = 1 + SwapV2::LEN;
+/// Offset of the token program id of a swap account
+pub const SWAP_V1_TOKEN_PROGRAM_ID_OFFSET: usize = 3;
+/// Offset of the token A vault of a swap account
+pub const SWAP_V1_TOKEN_A_OFFSET: usize = 35;
+/// Offset of the token B vault of a swap account
+pub const SWAP_V1_TOKEN_B_OFFSET: usize = 67;
+/// Offset of the pool mint of a swap account
+pub const SWAP_V1_POOL_MINT_OFFSET: usize = 99;
+/// Offset of the token A mint of a swap account
+pub const SWAP_V1_TOKEN_A_MINT_OFFSET: usize = 131;
+/// Offset of the token B mint of a swap account
+pub const SWAP_V1_TOKEN_B_MINT_OFFSET: usize = 163;
+/// Offset of the curve type of a swap account
+pub const SWAP_V1_CURVE_TYPE_OFFSET: usize = 195;
+/// Offset of the deprecated flag of a SwapV2 account
+pub const SWAP_V2_DEPRECATED_OFFSET: usize = 382;
+/// Offset of the gate mint of a SwapV2 account
+pub const SWAP_V2_GATE_MINT_OFFSET: usize = 392;
+/// Offset of the paused flag of a SwapV2 account
+pub const SWAP_V2_PAUSED_OFFSET: usize = 580;
+/// Offset of the fee owner of a SwapV2 account
+pub const SWAP_V2_FEE_OWNER_OFFSET: usize = 646;
+
 /// Program states.
 #[repr(C)]
 #[derive(Debug, Default, PartialEq)]
@@ -154,6 +288,81 @@ impl SwapState for SwapV1 {
         &self.swap_curve
     }
 
+    fn oracle(&self) -> Option<&PriceOracle> {
+        None
+    }
+
+    fn reserves(&self) -> Option<(u64, u64)> {
+        None
+    }
+
+    fn external_oracle(&self) -> Option<&ExternalOracle> {
+        None
+    }
+
+    fn last_swap(&self) -> Option<(u64, u128)> {
+        None
+    }
+
+    fn is_deprecated(&self) -> bool {
+        false
+    }
+
+    fn max_lp_supply(&self) -> Option<u64> {
+        None
+    }
+
+    fn gate(&self) -> Option<&Pubkey> {
+        None
+    }
+
+    fn withdraw_fee(&self) -> Option<(u64, bool)> {
+        None
+    }
+
+    fn min_hold_slots(&self) -> Option<u64> {
+        None
+    }
+
+    fn protocol_liquidity_bps(&self) -> Option<u64> {
+        None
+    }
+
+    fn batch_window_slots(&self) -> Option<u64> {
+        None
+    }
+
+    fn reserves_at(&self, token_a_balance: u64, token_b_balance: u64) -> (u64, u64) {
+        (token_a_balance, token_b_balance)
+    }
+
+    fn unclaimed_fees(&self) -> (u64, u64) {
+        (0, 0)
+    }
+
+    fn nft_position_vault(&self) -> Option<&Pubkey> {
+        None
+    }
+
+    fn is_paused(&self) -> bool {
+        false
+    }
+
+    fn max_slot_volume(&self) -> Option<u64> {
+        None
+    }
+
+    fn circuit_breaker_tripped(&self) -> bool {
+        false
+    }
+
+    fn fee_owner(&self) -> Option<&Pubkey> {
+        None
+    }
+
+    fn owner_fee_in_pool_tokens(&self) -> bool {
+        false
+    }
 }
 
 impl Sealed for SwapV1 {}
@@ -226,109 +435,2848 @@ impl Pack for SwapV1 {
     }
 }
 
-///Program State
+/// Precision of the prices tracked by the oracle accumulator
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000;
+
+/// Number of decimals of PRICE_PRECISION
+pub const PRICE_PRECISION_DECIMALS: i32 = 12;
+
+/// Basis points of realized volatility forgotten per elapsed slot
+pub const VOLATILITY_DECAY_PER_SLOT: u64 = 1;
+
+/// Running price accumulator of a pool, updated on every swap
 #[repr(C)]
-#[derive(Debug, Default, PartialEq)]
-pub struct GlobalState {
-    /// Initialized state.
-    pub is_initialized:bool,
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PriceOracle {
+    /// Slot of the last update
+    pub last_update_slot: u64,
+    /// Spot price of token A in token B after the last update, scaled by
+    /// PRICE_PRECISION
+    pub last_price: u128,
+    /// Sum of price times elapsed slots, wrapping, for time-weighted averages
+    pub price_cumulative: u128,
+    /// Decaying sum of the absolute price moves, in basis points
+    pub volatility: u64,
+}
 
-    /// program owner address to update all
-    pub owner: Pubkey,
+impl PriceOracle {
+    /// Size of the packed oracle
+    pub const LEN: usize = 48;
 
-    /// Fee owner address
-    pub fee_owner: Pubkey,
+    /// Spot price of token A in token B given the pool reserves
+    pub fn spot_price(token_a_amount: u128, token_b_amount: u128) -> Option<u128> {
+        token_b_amount
+            .checked_mul(PRICE_PRECISION)?
+            .checked_div(token_a_amount)
+    }
 
-    /// initial lp supply
-    pub initial_supply: u64,
+    /// Create an oracle starting at the given slot and price
+    pub fn new(current_slot: u64, price: u128) -> Self {
+        Self {
+            last_update_slot: current_slot,
+            last_price: price,
+            price_cumulative: 0,
+            volatility: 0,
+        }
+    }
 
-    /// lp token's decimals
-    pub lp_decimals: u8,
+    /// Realized volatility decayed to the given slot
+    pub fn current_volatility(&self, current_slot: u64) -> u64 {
+        let elapsed = current_slot.saturating_sub(self.last_update_slot);
+        self.volatility
+            .saturating_sub(elapsed.saturating_mul(VOLATILITY_DECAY_PER_SLOT))
+    }
 
-    ///Fee ratio
-    pub fees: Fees,
-}
-impl Sealed for GlobalState {}
-impl Pack for GlobalState{
-    /// Size of the Program State
-    const LEN:usize = 114; // add one for the version enum
+    /// Accumulate the previous price up to the given slot and record the new one
+    pub fn update(&mut self, current_slot: u64, price: u128) -> Option<()> {
+        let elapsed = current_slot.saturating_sub(self.last_update_slot);
+        self.price_cumulative = self
+            .price_cumulative
+            .wrapping_add(self.last_price.saturating_mul(u128::from(elapsed)));
+        let price_move = if self.last_price == 0 {
+            0
+        } else {
+            let difference = if price > self.last_price {
+                price.checked_sub(self.last_price)?
+            } else {
+                self.last_price.checked_sub(price)?
+            };
+            difference.checked_mul(10_000)?.checked_div(self.last_price)?
+        };
+        self.volatility = self
+            .current_volatility(current_slot)
+            .saturating_add(u64::try_from(price_move).unwrap_or(u64::MAX));
+        self.last_price = price;
+        self.last_update_slot = current_slot;
+        Some(())
+    }
 
-    /// Pack a swap into a byte array, based on its version
     fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, GlobalState::LEN];
-        let (
-            is_initialized,
-            state_owner,
-            fee_owner,
-            initial_supply,
-            lp_decimals,
-            fees,
-        ) = mut_array_refs![output, 1, 32, 32, 8, 1, 40];
-        is_initialized[0] = self.is_initialized as u8;
-        state_owner.copy_from_slice(self.owner.as_ref());
-        fee_owner.copy_from_slice(self.fee_owner.as_ref());
-        *initial_supply = self.initial_supply.to_le_bytes();
-        lp_decimals[0] = self.lp_decimals as u8;
-        self.fees.pack_into_slice(&mut fees[..]);
+        let output = array_mut_ref![output, 0, PriceOracle::LEN];
+        let (last_update_slot, last_price, price_cumulative, volatility) =
+            mut_array_refs![output, 8, 16, 16, 8];
+        *last_update_slot = self.last_update_slot.to_le_bytes();
+        *last_price = self.last_price.to_le_bytes();
+        *price_cumulative = self.price_cumulative.to_le_bytes();
+        *volatility = self.volatility.to_le_bytes();
     }
 
-    /// Unpacks a byte buffer into a [SwapV1](struct.SwapV1.html).
-    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
-        if input.len() != GlobalState::LEN{
-            return Err(SwapError::InvalidInstruction.into());    
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        let input = array_ref![input, 0, PriceOracle::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (last_update_slot, last_price, price_cumulative, volatility) =
+            array_refs![input, 8, 16, 16, 8];
+        Self {
+            last_update_slot: u64::from_le_bytes(*last_update_slot),
+            last_price: u128::from_le_bytes(*last_price),
+            price_cumulative: u128::from_le_bytes(*price_cumulative),
+            volatility: u64::from_le_bytes(*volatility),
         }
-        let input = array_ref![input, 0, GlobalState::LEN];
+    }
+}
+
+/// External price feed a pool checks its execution prices against
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExternalOracle {
+    /// Pyth price account of token A priced in token B, default when unset
+    pub price_account: Pubkey,
+    /// Maximum deviation of the execution price from the oracle price, in
+    /// basis points
+    pub max_deviation_bps: u64,
+    /// Decimals of the token A mint
+    pub token_a_decimals: u8,
+    /// Decimals of the token B mint
+    pub token_b_decimals: u8,
+}
+
+impl ExternalOracle {
+    /// Size of the packed oracle configuration
+    pub const LEN: usize = 42;
+
+    /// Whether the pool checks its prices against the oracle
+    pub fn is_enabled(&self) -> bool {
+        self.price_account != Pubkey::default()
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, ExternalOracle::LEN];
+        let (price_account, max_deviation_bps, token_a_decimals, token_b_decimals) =
+            mut_array_refs![output, 32, 8, 1, 1];
+        price_account.copy_from_slice(self.price_account.as_ref());
+        *max_deviation_bps = self.max_deviation_bps.to_le_bytes();
+        token_a_decimals[0] = self.token_a_decimals;
+        token_b_decimals[0] = self.token_b_decimals;
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Self {
+        let input = array_ref![input, 0, ExternalOracle::LEN];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (
-            is_initialized,
-            state_owner,
-            fee_owner,
-            initial_supply,
-            lp_decimals,
-            fees,
-        ) = array_refs![input, 1, 32, 32, 8, 1, 40];
-        Ok(Self {
-            is_initialized: match is_initialized {
-                [0] => false,
-                [1] => true,
-                _ => return Err(ProgramError::InvalidAccountData),
-            },
-            owner: Pubkey::new_from_array(*state_owner),
-            fee_owner: Pubkey::new_from_array(*fee_owner),
-            initial_supply:u64::from_le_bytes(*initial_supply),
-            lp_decimals:lp_decimals[0],
-            fees: Fees::unpack_from_slice(fees)?,
-        })
+        let (price_account, max_deviation_bps, token_a_decimals, token_b_decimals) =
+            array_refs![input, 32, 8, 1, 1];
+        Self {
+            price_account: Pubkey::new_from_array(*price_account),
+            max_deviation_bps: u64::from_le_bytes(*max_deviation_bps),
+            token_a_decimals: token_a_decimals[0],
+            token_b_decimals: token_b_decimals[0],
+        }
     }
 }
 
-impl GlobalState{
-    /// is program account initialized
-    pub fn is_initialized(&self) -> bool {
-        return self.is_initialized
+/// Program states, tracking pool state that changes on every trade.
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct SwapV2 {
+    /// Initialized state.
+    pub is_initialized: bool,
+    /// Nonce used in program address.
+    pub nonce: u8,
+
+    /// Program ID of the tokens being exchanged.
+    pub token_program_id: Pubkey,
+
+    /// Token A
+    pub token_a: Pubkey,
+    /// Token B
+    pub token_b: Pubkey,
+
+    /// Pool tokens are issued when A or B tokens are deposited.
+    pub pool_mint: Pubkey,
+
+    /// Mint information for token A
+    pub token_a_mint: Pubkey,
+    /// Mint information for token B
+    pub token_b_mint: Pubkey,
+
+    ///Curve Type to swap
+    pub swap_curve: SwapCurve,
+
+    /// Price accumulator, updated on every swap
+    pub oracle: PriceOracle,
+
+    /// Token A held by the pool, tokens transferred directly to the vault
+    /// are not counted until a Sync
+    pub token_a_reserve: u64,
+    /// Token B held by the pool
+    pub token_b_reserve: u64,
+
+    /// Epoch of the last rebalance
+    pub rebalance_epoch: u64,
+    /// Source tokens rebalanced during `rebalance_epoch`
+    pub rebalance_amount: u64,
+
+    /// External price feed swaps are checked against
+    pub external_oracle: ExternalOracle,
+
+    /// Unused, the nonces of filled quotes are kept per taker in
+    /// [QuoteNonce](struct.QuoteNonce.html) records
+    pub quote_nonce: u64,
+
+    /// Slot of the last trade
+    pub last_swap_slot: u64,
+    /// Execution price of token A in token B of the last trade, scaled by
+    /// PRICE_PRECISION
+    pub last_price: u128,
+
+    /// Set once the pool is wound down, only withdrawals stay open
+    pub deprecated: bool,
+
+    /// Pool token supply deposits may not push the pool above, zero for
+    /// no cap
+    pub max_lp_supply: u64,
+
+    /// Swaps and deposits require holding the gate token or an allowlist
+    /// entry
+    pub gated: bool,
+    /// Token whose holders may trade on a gated pool, the default pubkey
+    /// for an allowlist-only pool
+    pub gate_mint: Pubkey,
+
+    /// Fee charged in pool tokens on withdrawals, over the fee denominator
+    /// of the program state
+    pub withdraw_fee_numerator: u64,
+    /// Burn the withdrawal fee, leaving its share of the reserves to the
+    /// remaining liquidity providers, instead of paying it to the fee owner
+    pub withdraw_fee_burned: bool,
+
+    /// Slots between a deposit and a withdrawal by the same owner, zero for
+    /// no holding period
+    pub min_hold_slots: u64,
+
+    /// Share of the owner fee, in basis points, deposited back into the pool
+    /// on behalf of the protocol, zero to pay the whole fee to the fee owner
+    pub protocol_liquidity_bps: u64,
+
+    /// Slots swaps are queued for before a batch auction settles them at a
+    /// single price, zero for regular swaps
+    pub batch_window_slots: u64,
+
+    /// The vault balances change without trades, as for rebasing or
+    /// interest-bearing tokens: the reserves follow the balances, making
+    /// pool tokens shares of whatever the vaults hold, and nothing is left
+    /// to skim
+    pub rebasing: bool,
+
+    /// Stake pool the rate of a rated stable curve is refreshed from, the
+    /// default pubkey until one is set
+    pub rate_account: Pubkey,
+
+    /// Pool token account of the swap authority holding the pool tokens of
+    /// the LP positions, the default pubkey until fees are made claimable
+    pub position_vault: Pubkey,
+    /// Trade fees are paid out to LP positions instead of compounding into
+    /// the reserves
+    pub claimable_fees: bool,
+    /// Pool tokens held in LP positions
+    pub position_pool_tokens: u64,
+    /// Token A trade fees earned per position pool token, scaled by
+    /// REWARD_PRECISION
+    pub fee_growth_a: u128,
+    /// Token B trade fees earned per position pool token, scaled by
+    /// REWARD_PRECISION
+    pub fee_growth_b: u128,
+    /// Token A trade fees earned by LP positions and not claimed yet
+    pub unclaimed_fee_a: u64,
+    /// Token B trade fees earned by LP positions and not claimed yet
+    pub unclaimed_fee_b: u64,
+    /// Deposits open a position owned by a new NFT instead of minting pool
+    /// tokens to the depositor
+    pub nft_positions: bool,
+    /// Swaps and deposits are paused by the program owner or the guardian
+    pub paused: bool,
+    /// Token A volume the swaps of a single slot may not exceed, zero for
+    /// no cap
+    pub max_slot_volume: u64,
+    /// Slot of `slot_volume`
+    pub volume_slot: u64,
+    /// Token A volume swapped during `volume_slot`
+    pub slot_volume: u64,
+    /// Price move, in basis points, within a circuit breaker window past
+    /// which trades fail, zero when the pool has no circuit breaker
+    pub breaker_move_bps: u64,
+    /// Slots of a circuit breaker window
+    pub breaker_window_slots: u64,
+    /// Slot the current circuit breaker window started at
+    pub breaker_slot: u64,
+    /// Oracle price at the start of the current circuit breaker window,
+    /// scaled by PRICE_PRECISION
+    pub breaker_price: u128,
+    /// Swaps are halted by the circuit breaker until the program owner or
+    /// the guardian resets it, as pools tripped before trades moving the
+    /// price too far failed are
+    pub breaker_tripped: bool,
+    /// Fee owner of the pool's swaps and withdrawal fees, overriding the one
+    /// of the program state, the default pubkey for none
+    pub fee_owner: Pubkey,
+    /// The fee owner's cut of the owner fee stays in the pool and is minted
+    /// to it as pool tokens
+    pub owner_fee_in_pool_tokens: bool,
+    /// Slot of the last crank of the pool a keeper was rewarded for
+    pub keeper_slot: u64,
+}
+
+impl SwapState for SwapV2 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
     }
-    /// state owner to change current program state
-    pub fn owner(&self) -> &Pubkey {
-        &self.owner
+
+    fn nonce(&self) -> u8 {
+        self.nonce
     }
 
-    /// fee owner to recevie when swap
-    pub fn fee_owner(&self) -> &Pubkey {
-        &self.fee_owner
+    fn token_program_id(&self) -> &Pubkey {
+        &self.token_program_id
     }
 
-    /// initial supply to create pool
-    pub fn initial_supply(&self) -> u64 {
-        self.initial_supply
+    fn token_a_account(&self) -> &Pubkey {
+        &self.token_a
     }
 
-    /// lp decimals
-    pub fn lp_decimals(&self) -> u8 {
-        self.lp_decimals
+    fn token_b_account(&self) -> &Pubkey {
+        &self.token_b
     }
-    
-    /// fees redistributed
-    pub fn fees(&self) -> &Fees {
-        &self.fees
+
+    fn pool_mint(&self) -> &Pubkey {
+        &self.pool_mint
+    }
+
+    fn token_a_mint(&self) -> &Pubkey {
+        &self.token_a_mint
+    }
+
+    fn token_b_mint(&self) -> &Pubkey {
+        &self.token_b_mint
+    }
+
+    fn swap_curve(&self) -> &SwapCurve {
+        &self.swap_curve
+    }
+
+    fn oracle(&self) -> Option<&PriceOracle> {
+        Some(&self.oracle)
+    }
+
+    fn reserves(&self) -> Option<(u64, u64)> {
+        Some((self.token_a_reserve, self.token_b_reserve))
+    }
+
+    fn external_oracle(&self) -> Option<&ExternalOracle> {
+        Some(&self.external_oracle).filter(|external_oracle| external_oracle.is_enabled())
+    }
+
+    fn last_swap(&self) -> Option<(u64, u128)> {
+        Some((self.last_swap_slot, self.last_price))
+    }
+
+    fn is_deprecated(&self) -> bool {
+        self.deprecated
+    }
+
+    fn max_lp_supply(&self) -> Option<u64> {
+        Some(self.max_lp_supply).filter(|max_lp_supply| *max_lp_supply != 0)
+    }
+
+    fn gate(&self) -> Option<&Pubkey> {
+        if self.gated {
+            Some(&self.gate_mint)
+        } else {
+            None
+        }
+    }
+
+    fn withdraw_fee(&self) -> Option<(u64, bool)> {
+        Some((self.withdraw_fee_numerator, self.withdraw_fee_burned))
+            .filter(|(withdraw_fee_numerator, _)| *withdraw_fee_numerator != 0)
+    }
+
+    fn min_hold_slots(&self) -> Option<u64> {
+        Some(self.min_hold_slots).filter(|min_hold_slots| *min_hold_slots != 0)
+    }
+
+    fn protocol_liquidity_bps(&self) -> Option<u64> {
+        Some(self.protocol_liquidity_bps)
+            .filter(|protocol_liquidity_bps| *protocol_liquidity_bps != 0)
+    }
+
+    fn batch_window_slots(&self) -> Option<u64> {
+        Some(self.batch_window_slots).filter(|batch_window_slots| *batch_window_slots != 0)
+    }
+
+    fn reserves_at(&self, token_a_balance: u64, token_b_balance: u64) -> (u64, u64) {
+        if self.rebasing {
+            (
+                token_a_balance.saturating_sub(self.unclaimed_fee_a),
+                token_b_balance.saturating_sub(self.unclaimed_fee_b),
+            )
+        } else {
+            (self.token_a_reserve, self.token_b_reserve)
+        }
+    }
+
+    fn unclaimed_fees(&self) -> (u64, u64) {
+        (self.unclaimed_fee_a, self.unclaimed_fee_b)
+    }
+
+    fn nft_position_vault(&self) -> Option<&Pubkey> {
+        Some(&self.position_vault).filter(|_| self.nft_positions)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn max_slot_volume(&self) -> Option<u64> {
+        Some(self.max_slot_volume).filter(|max_slot_volume| *max_slot_volume != 0)
+    }
+
+    fn circuit_breaker_tripped(&self) -> bool {
+        self.breaker_tripped
+    }
+
+    fn fee_owner(&self) -> Option<&Pubkey> {
+        Some(&self.fee_owner).filter(|fee_owner| **fee_owner != Pubkey::default())
+    }
+
+    fn owner_fee_in_pool_tokens(&self) -> bool {
+        self.owner_fee_in_pool_tokens
+    }
+}
+
+impl SwapV2 {
+    /// Record the slot and execution price of a trade
+    pub fn record_swap(
+        &mut self,
+        current_slot: u64,
+        trade_direction: TradeDirection,
+        source_amount: u128,
+        destination_amount: u128,
+    ) -> Option<()> {
+        let (token_a_amount, token_b_amount) = match trade_direction {
+            TradeDirection::AtoB => (source_amount, destination_amount),
+            TradeDirection::BtoA => (destination_amount, source_amount),
+        };
+        self.last_price = PriceOracle::spot_price(token_a_amount, token_b_amount)?;
+        self.last_swap_slot = current_slot;
+        Some(())
+    }
+
+    /// Add the token A volume of a trade to the volume of the current slot,
+    /// failing past the pool's cap
+    pub fn record_slot_volume(
+        &mut self,
+        current_slot: u64,
+        trade_direction: TradeDirection,
+        source_amount: u128,
+        destination_amount: u128,
+    ) -> Result<(), SwapError> {
+        let token_a_amount = match trade_direction {
+            TradeDirection::AtoB => source_amount,
+            TradeDirection::BtoA => destination_amount,
+        };
+        if current_slot != self.volume_slot {
+            self.volume_slot = current_slot;
+            self.slot_volume = 0;
+        }
+        let slot_volume = u64::try_from(token_a_amount)
+            .ok()
+            .and_then(|token_a_amount| self.slot_volume.checked_add(token_a_amount))
+            .ok_or(SwapError::CalculationFailure)?;
+        if self.max_slot_volume != 0 && slot_volume > self.max_slot_volume {
+            return Err(SwapError::SlotVolumeExceeded);
+        }
+        self.slot_volume = slot_volume;
+        Ok(())
+    }
+
+    /// Check the price after a trade against the oracle price at the start
+    /// of the circuit breaker window, a window starting at the first trade
+    /// after the previous one ended. Must run before the oracle records the
+    /// new price. Returns whether the trade moves the price past the
+    /// breaker, and so must fail.
+    pub fn check_circuit_breaker(&mut self, current_slot: u64, price: u128) -> Option<bool> {
+        if self.breaker_move_bps == 0 || self.breaker_tripped {
+            return Some(false);
+        }
+        if self.breaker_price == 0
+            || current_slot >= self.breaker_slot.saturating_add(self.breaker_window_slots)
+        {
+            self.breaker_slot = current_slot;
+            self.breaker_price = self.oracle.last_price;
+        }
+        if self.breaker_price == 0 {
+            return Some(false);
+        }
+        let difference = if price > self.breaker_price {
+            price.checked_sub(self.breaker_price)?
+        } else {
+            self.breaker_price.checked_sub(price)?
+        };
+        let price_move = difference.checked_mul(10_000)?.checked_div(self.breaker_price)?;
+        Some(price_move > u128::from(self.breaker_move_bps))
+    }
+
+    /// Move the share of the trade fee of a swap earned by the open LP
+    /// positions, their pool tokens out of the `pool_mint_supply`, out of
+    /// the reserves into their fee growth, returning the amount moved. The
+    /// rest of the fee, and all of it without claimable fees or open
+    /// positions, compounds into the reserves for the other pool tokens.
+    pub fn accrue_trade_fee(
+        &mut self,
+        trade_direction: TradeDirection,
+        trade_fee: u128,
+        pool_mint_supply: u64,
+    ) -> Option<u128> {
+        if !self.claimable_fees || self.position_pool_tokens == 0 || pool_mint_supply == 0 {
+            return Some(0);
+        }
+        let position_pool_tokens = u128::from(self.position_pool_tokens);
+        let growth = trade_fee
+            .checked_mul(REWARD_PRECISION)?
+            .checked_div(u128::from(pool_mint_supply))?;
+        // the rounding of the growth compounds into the reserves
+        let accrued = growth
+            .checked_mul(position_pool_tokens)?
+            .checked_div(REWARD_PRECISION)?;
+        let (fee_growth, unclaimed_fee) = match trade_direction {
+            TradeDirection::AtoB => (&mut self.fee_growth_a, &mut self.unclaimed_fee_a),
+            TradeDirection::BtoA => (&mut self.fee_growth_b, &mut self.unclaimed_fee_b),
+        };
+        *fee_growth = fee_growth.checked_add(growth)?;
+        *unclaimed_fee = unclaimed_fee.checked_add(u64::try_from(accrued).ok()?)?;
+        Some(accrued)
+    }
+}
+
+impl Sealed for SwapV2 {}
+impl IsInitialized for SwapV2 {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for SwapV2 {
+    const LEN: usize = 686;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SwapV2::LEN];
+        let (
+            is_initialized,
+            nonce,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            swap_curve,
+            oracle,
+            token_a_reserve,
+            token_b_reserve,
+            rebalance_epoch,
+            rebalance_amount,
+            external_oracle,
+            quote_nonce,
+            last_swap_slot,
+            last_price,
+            deprecated,
+            max_lp_supply,
+            gated,
+            gate_mint,
+            withdraw_fee_numerator,
+            withdraw_fee_burned,
+            min_hold_slots,
+            protocol_liquidity_bps,
+            batch_window_slots,
+            rebasing,
+            rate_account,
+            position_vault,
+            claimable_fees,
+            position_pool_tokens,
+            fee_growth_a,
+            fee_growth_b,
+            unclaimed_fee_a,
+            unclaimed_fee_b,
+            nft_positions,
+            paused,
+            max_slot_volume,
+            volume_slot,
+            slot_volume,
+            breaker_move_bps,
+            breaker_window_slots,
+            breaker_slot,
+            breaker_price,
+            breaker_tripped,
+            fee_owner,
+            owner_fee_in_pool_tokens,
+            keeper_slot,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 32, 32, 32, 33, 48, 8, 8, 8, 8, 42, 8, 8, 16, 1, 8, 1, 32, 8, 1, 8, 8, 8, 1, 32, 32, 1, 8, 16, 16, 8, 8, 1, 1, 8, 8, 8, 8, 8, 8, 16, 1, 32, 1, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        nonce[0] = self.nonce;
+        token_program_id.copy_from_slice(self.token_program_id.as_ref());
+        token_a.copy_from_slice(self.token_a.as_ref());
+        token_b.copy_from_slice(self.token_b.as_ref());
+        pool_mint.copy_from_slice(self.pool_mint.as_ref());
+        token_a_mint.copy_from_slice(self.token_a_mint.as_ref());
+        token_b_mint.copy_from_slice(self.token_b_mint.as_ref());
+        self.swap_curve.pack_into_slice(&mut swap_curve[..]);
+        self.oracle.pack_into_slice(&mut oracle[..]);
+        *token_a_reserve = self.token_a_reserve.to_le_bytes();
+        *token_b_reserve = self.token_b_reserve.to_le_bytes();
+        *rebalance_epoch = self.rebalance_epoch.to_le_bytes();
+        *rebalance_amount = self.rebalance_amount.to_le_bytes();
+        self.external_oracle.pack_into_slice(&mut external_oracle[..]);
+        *quote_nonce = self.quote_nonce.to_le_bytes();
+        *last_swap_slot = self.last_swap_slot.to_le_bytes();
+        *last_price = self.last_price.to_le_bytes();
+        deprecated[0] = self.deprecated as u8;
+        *max_lp_supply = self.max_lp_supply.to_le_bytes();
+        gated[0] = self.gated as u8;
+        gate_mint.copy_from_slice(self.gate_mint.as_ref());
+        *withdraw_fee_numerator = self.withdraw_fee_numerator.to_le_bytes();
+        withdraw_fee_burned[0] = self.withdraw_fee_burned as u8;
+        *min_hold_slots = self.min_hold_slots.to_le_bytes();
+        *protocol_liquidity_bps = self.protocol_liquidity_bps.to_le_bytes();
+        *batch_window_slots = self.batch_window_slots.to_le_bytes();
+        rebasing[0] = self.rebasing as u8;
+        rate_account.copy_from_slice(self.rate_account.as_ref());
+        position_vault.copy_from_slice(self.position_vault.as_ref());
+        claimable_fees[0] = self.claimable_fees as u8;
+        *position_pool_tokens = self.position_pool_tokens.to_le_bytes();
+        *fee_growth_a = self.fee_growth_a.to_le_bytes();
+        *fee_growth_b = self.fee_growth_b.to_le_bytes();
+        *unclaimed_fee_a = self.unclaimed_fee_a.to_le_bytes();
+        *unclaimed_fee_b = self.unclaimed_fee_b.to_le_bytes();
+        nft_positions[0] = self.nft_positions as u8;
+        paused[0] = self.paused as u8;
+        *max_slot_volume = self.max_slot_volume.to_le_bytes();
+        *volume_slot = self.volume_slot.to_le_bytes();
+        *slot_volume = self.slot_volume.to_le_bytes();
+        *breaker_move_bps = self.breaker_move_bps.to_le_bytes();
+        *breaker_window_slots = self.breaker_window_slots.to_le_bytes();
+        *breaker_slot = self.breaker_slot.to_le_bytes();
+        *breaker_price = self.breaker_price.to_le_bytes();
+        breaker_tripped[0] = self.breaker_tripped as u8;
+        fee_owner.copy_from_slice(self.fee_owner.as_ref());
+        owner_fee_in_pool_tokens[0] = self.owner_fee_in_pool_tokens as u8;
+        *keeper_slot = self.keeper_slot.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [SwapV2](struct.SwapV2.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, SwapV2::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            nonce,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            swap_curve,
+            oracle,
+            token_a_reserve,
+            token_b_reserve,
+            rebalance_epoch,
+            rebalance_amount,
+            external_oracle,
+            quote_nonce,
+            last_swap_slot,
+            last_price,
+            deprecated,
+            max_lp_supply,
+            gated,
+            gate_mint,
+            withdraw_fee_numerator,
+            withdraw_fee_burned,
+            min_hold_slots,
+            protocol_liquidity_bps,
+            batch_window_slots,
+            rebasing,
+            rate_account,
+            position_vault,
+            claimable_fees,
+            position_pool_tokens,
+            fee_growth_a,
+            fee_growth_b,
+            unclaimed_fee_a,
+            unclaimed_fee_b,
+            nft_positions,
+            paused,
+            max_slot_volume,
+            volume_slot,
+            slot_volume,
+            breaker_move_bps,
+            breaker_window_slots,
+            breaker_slot,
+            breaker_price,
+            breaker_tripped,
+            fee_owner,
+            owner_fee_in_pool_tokens,
+            keeper_slot,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 32, 32, 32, 33, 48, 8, 8, 8, 8, 42, 8, 8, 16, 1, 8, 1, 32, 8, 1, 8, 8, 8, 1, 32, 32, 1, 8, 16, 16, 8, 8, 1, 1, 8, 8, 8, 8, 8, 8, 16, 1, 32, 1, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            nonce: nonce[0],
+            token_program_id: Pubkey::new_from_array(*token_program_id),
+            token_a: Pubkey::new_from_array(*token_a),
+            token_b: Pubkey::new_from_array(*token_b),
+            pool_mint: Pubkey::new_from_array(*pool_mint),
+            token_a_mint: Pubkey::new_from_array(*token_a_mint),
+            token_b_mint: Pubkey::new_from_array(*token_b_mint),
+            swap_curve: SwapCurve::unpack_from_slice(swap_curve)?,
+            oracle: PriceOracle::unpack_from_slice(oracle),
+            token_a_reserve: u64::from_le_bytes(*token_a_reserve),
+            token_b_reserve: u64::from_le_bytes(*token_b_reserve),
+            rebalance_epoch: u64::from_le_bytes(*rebalance_epoch),
+            rebalance_amount: u64::from_le_bytes(*rebalance_amount),
+            external_oracle: ExternalOracle::unpack_from_slice(external_oracle),
+            quote_nonce: u64::from_le_bytes(*quote_nonce),
+            last_swap_slot: u64::from_le_bytes(*last_swap_slot),
+            last_price: u128::from_le_bytes(*last_price),
+            deprecated: match deprecated {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            max_lp_supply: u64::from_le_bytes(*max_lp_supply),
+            gated: match gated {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            gate_mint: Pubkey::new_from_array(*gate_mint),
+            withdraw_fee_numerator: u64::from_le_bytes(*withdraw_fee_numerator),
+            withdraw_fee_burned: match withdraw_fee_burned {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            min_hold_slots: u64::from_le_bytes(*min_hold_slots),
+            protocol_liquidity_bps: u64::from_le_bytes(*protocol_liquidity_bps),
+            batch_window_slots: u64::from_le_bytes(*batch_window_slots),
+            rebasing: match rebasing {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            rate_account: Pubkey::new_from_array(*rate_account),
+            position_vault: Pubkey::new_from_array(*position_vault),
+            claimable_fees: match claimable_fees {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            position_pool_tokens: u64::from_le_bytes(*position_pool_tokens),
+            fee_growth_a: u128::from_le_bytes(*fee_growth_a),
+            fee_growth_b: u128::from_le_bytes(*fee_growth_b),
+            unclaimed_fee_a: u64::from_le_bytes(*unclaimed_fee_a),
+            unclaimed_fee_b: u64::from_le_bytes(*unclaimed_fee_b),
+            nft_positions: match nft_positions {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            paused: match paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            max_slot_volume: u64::from_le_bytes(*max_slot_volume),
+            volume_slot: u64::from_le_bytes(*volume_slot),
+            slot_volume: u64::from_le_bytes(*slot_volume),
+            breaker_move_bps: u64::from_le_bytes(*breaker_move_bps),
+            breaker_window_slots: u64::from_le_bytes(*breaker_window_slots),
+            breaker_slot: u64::from_le_bytes(*breaker_slot),
+            breaker_price: u128::from_le_bytes(*breaker_price),
+            breaker_tripped: match breaker_tripped {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            fee_owner: Pubkey::new_from_array(*fee_owner),
+            owner_fee_in_pool_tokens: match owner_fee_in_pool_tokens {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            keeper_slot: u64::from_le_bytes(*keeper_slot),
+        })
+    }
+}
+
+///Program State
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct GlobalState {
+    /// Initialized state.
+    pub is_initialized:bool,
+
+    /// program owner address to update all
+    pub owner: Pubkey,
+
+    /// Fee owner address
+    pub fee_owner: Pubkey,
+
+    /// initial lp supply of pools whose first deposit is too small for the
+    /// geometric mean of its amounts
+    pub initial_supply: u64,
+
+    /// lp token's decimals, no longer enforced: each pool mint has its own,
+    /// up to MAX_LP_DECIMALS
+    pub lp_decimals: u8,
+
+    ///Fee ratio
+    pub fees: Fees,
+
+    /// fixed fee discount for holders of a designated token
+    pub fee_discount: FeeDiscount,
+
+    /// Quoter allowed to sign swap quotes besides the fee owner
+    pub quoter: Pubkey,
+
+    /// Token bought back with protocol fees and burned
+    pub buyback_mint: Pubkey,
+
+    /// Buyback tokens that may be burned per epoch, zero disables buybacks
+    pub buyback_epoch_limit: u64,
+
+    /// Epoch of the last buyback
+    pub buyback_epoch: u64,
+
+    /// Buyback tokens burned during `buyback_epoch`
+    pub buyback_epoch_amount: u64,
+
+    /// Recipients splitting the owner fee with the fee owner
+    pub fee_recipients: [FeeRecipient; FEE_RECIPIENT_COUNT],
+
+    /// Guardian allowed to pause swaps and deposits, but not to unpause them
+    /// or change anything else, the default pubkey when there is none
+    pub guardian: Pubkey,
+
+    /// Swaps and deposits into every pool are paused
+    pub paused: bool,
+
+    /// Protocol-owned pool tokens paid to the keeper of a permissionless
+    /// crank of a pool, zero disables keeper rewards
+    pub keeper_reward: u64,
+
+    /// Slots between two rewarded cranks of the same pool
+    pub keeper_interval_slots: u64,
+
+    /// Fees and fee discount may only be changed by the governance of the
+    /// fee governance account, through SetGovernedFees
+    pub fee_governed: bool,
+
+    /// Pools and basket pools initialized since the counter was added, plus
+    /// those created before as seeded by SetPoolCount. The initial supply
+    /// and lp decimals are fixed once it is above zero
+    pub pool_count: u64,
+}
+impl Sealed for GlobalState {}
+impl Pack for GlobalState{
+    /// Size of the Program State
+    const LEN:usize = 484; // add one for the version enum
+
+    /// Pack a swap into a byte array, based on its version
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, GlobalState::LEN];
+        let (
+            is_initialized,
+            state_owner,
+            fee_owner,
+            initial_supply,
+            lp_decimals,
+            fees,
+            fee_discount,
+            quoter,
+            buyback_mint,
+            buyback_epoch_limit,
+            buyback_epoch,
+            buyback_epoch_amount,
+            fee_recipients,
+            guardian,
+            paused,
+            minimum_fee,
+            keeper_reward,
+            keeper_interval_slots,
+            fee_governed,
+            pool_count,
+        ) = mut_array_refs![output, 1, 32, 32, 8, 1, 88, 48, 32, 32, 8, 8, 8, 120, 32, 1, 8, 8, 8, 1, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        state_owner.copy_from_slice(self.owner.as_ref());
+        fee_owner.copy_from_slice(self.fee_owner.as_ref());
+        *initial_supply = self.initial_supply.to_le_bytes();
+        lp_decimals[0] = self.lp_decimals as u8;
+        // the minimum fee was added to the fees after the layout of the state
+        // was fixed, so it is stored after the other fields
+        let mut fees_data = [0u8; Fees::LEN];
+        self.fees.pack_into_slice(&mut fees_data);
+        let (fees_data, minimum_fee_data) = array_refs![&fees_data, 88, 8];
+        fees.copy_from_slice(fees_data);
+        minimum_fee.copy_from_slice(minimum_fee_data);
+        self.fee_discount.pack_into_slice(&mut fee_discount[..]);
+        quoter.copy_from_slice(self.quoter.as_ref());
+        buyback_mint.copy_from_slice(self.buyback_mint.as_ref());
+        *buyback_epoch_limit = self.buyback_epoch_limit.to_le_bytes();
+        *buyback_epoch = self.buyback_epoch.to_le_bytes();
+        *buyback_epoch_amount = self.buyback_epoch_amount.to_le_bytes();
+        for (fee_recipient, output) in self
+            .fee_recipients
+            .iter()
+            .zip(fee_recipients.chunks_exact_mut(FeeRecipient::LEN))
+        {
+            fee_recipient.pack_into_slice(output);
+        }
+        guardian.copy_from_slice(self.guardian.as_ref());
+        paused[0] = self.paused as u8;
+        *keeper_reward = self.keeper_reward.to_le_bytes();
+        *keeper_interval_slots = self.keeper_interval_slots.to_le_bytes();
+        fee_governed[0] = self.fee_governed as u8;
+        *pool_count = self.pool_count.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [SwapV1](struct.SwapV1.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != GlobalState::LEN{
+            return Err(SwapError::InvalidInstruction.into());    
+        }
+        let input = array_ref![input, 0, GlobalState::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            state_owner,
+            fee_owner,
+            initial_supply,
+            lp_decimals,
+            fees,
+            fee_discount,
+            quoter,
+            buyback_mint,
+            buyback_epoch_limit,
+            buyback_epoch,
+            buyback_epoch_amount,
+            fee_recipients_data,
+            guardian,
+            paused,
+            minimum_fee,
+            keeper_reward,
+            keeper_interval_slots,
+            fee_governed,
+            pool_count,
+        ) = array_refs![input, 1, 32, 32, 8, 1, 88, 48, 32, 32, 8, 8, 8, 120, 32, 1, 8, 8, 8, 1, 8];
+        let mut fees_data = [0u8; Fees::LEN];
+        fees_data[..88].copy_from_slice(fees);
+        fees_data[88..].copy_from_slice(minimum_fee);
+        let mut fee_recipients = [FeeRecipient::default(); FEE_RECIPIENT_COUNT];
+        for (fee_recipient, input) in fee_recipients
+            .iter_mut()
+            .zip(fee_recipients_data.chunks_exact(FeeRecipient::LEN))
+        {
+            *fee_recipient = FeeRecipient::unpack_from_slice(input)?;
+        }
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            owner: Pubkey::new_from_array(*state_owner),
+            fee_owner: Pubkey::new_from_array(*fee_owner),
+            initial_supply:u64::from_le_bytes(*initial_supply),
+            lp_decimals:lp_decimals[0],
+            fees: Fees::unpack_from_slice(&fees_data)?,
+            fee_discount: FeeDiscount::unpack_from_slice(fee_discount)?,
+            quoter: Pubkey::new_from_array(*quoter),
+            buyback_mint: Pubkey::new_from_array(*buyback_mint),
+            buyback_epoch_limit: u64::from_le_bytes(*buyback_epoch_limit),
+            buyback_epoch: u64::from_le_bytes(*buyback_epoch),
+            buyback_epoch_amount: u64::from_le_bytes(*buyback_epoch_amount),
+            fee_recipients,
+            guardian: Pubkey::new_from_array(*guardian),
+            paused: match paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            keeper_reward: u64::from_le_bytes(*keeper_reward),
+            keeper_interval_slots: u64::from_le_bytes(*keeper_interval_slots),
+            fee_governed: match fee_governed {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            pool_count: u64::from_le_bytes(*pool_count),
+        })
+    }
+}
+
+impl GlobalState{
+    /// is program account initialized
+    pub fn is_initialized(&self) -> bool {
+        return self.is_initialized
+    }
+    /// state owner to change current program state
+    pub fn owner(&self) -> &Pubkey {
+        &self.owner
+    }
+
+    /// fee owner to recevie when swap
+    pub fn fee_owner(&self) -> &Pubkey {
+        &self.fee_owner
+    }
+
+    /// initial supply to create pool
+    pub fn initial_supply(&self) -> u64 {
+        self.initial_supply
+    }
+
+    /// lp decimals
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+    
+    /// fees redistributed
+    pub fn fees(&self) -> &Fees {
+        &self.fees
+    }
+
+    /// fixed fee discount for token holders
+    pub fn fee_discount(&self) -> &FeeDiscount {
+        &self.fee_discount
+    }
+
+    /// designated swap quote signer
+    pub fn quoter(&self) -> &Pubkey {
+        &self.quoter
+    }
+
+    /// pause-only guardian
+    pub fn guardian(&self) -> &Pubkey {
+        &self.guardian
+    }
+
+    /// Count one more pool initialized
+    pub fn record_pool(&mut self) -> Result<(), SwapError> {
+        self.pool_count = self
+            .pool_count
+            .checked_add(1)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Recipients taking a share of the owner fee
+    pub fn fee_recipients(&self) -> impl Iterator<Item = &FeeRecipient> {
+        self.fee_recipients
+            .iter()
+            .filter(|fee_recipient| fee_recipient.is_enabled())
+    }
+
+    /// Record a buyback burning `amount` tokens during `epoch`, failing past
+    /// the epoch limit
+    pub fn record_buyback(&mut self, epoch: u64, amount: u64) -> Result<(), SwapError> {
+        if epoch != self.buyback_epoch {
+            self.buyback_epoch = epoch;
+            self.buyback_epoch_amount = 0;
+        }
+        let buyback_epoch_amount = self
+            .buyback_epoch_amount
+            .checked_add(amount)
+            .ok_or(SwapError::CalculationFailure)?;
+        if buyback_epoch_amount > self.buyback_epoch_limit {
+            return Err(SwapError::BuybackLimitExceeded);
+        }
+        self.buyback_epoch_amount = buyback_epoch_amount;
+        Ok(())
+    }
+}
+
+/// Bytes left free after the program state for fields added later, shrunk
+/// by every field added so the account size stays the same
+pub const GLOBAL_STATE_RESERVED_LEN: usize = 14;
+
+/// Size of the legacy program state, a prefix of the current layout
+pub const GLOBAL_STATE_V1_LEN: usize = 242;
+
+/// Version byte of a program state account in the latest layout, at offset
+/// 0; the legacy layout has none and its offsets are one less
+pub const GLOBAL_STATE_V2_VERSION: u8 = 2;
+/// Offset of the owner of a program state account
+pub const GLOBAL_STATE_OWNER_OFFSET: usize = 2;
+/// Offset of the fee owner of a program state account
+pub const GLOBAL_STATE_FEE_OWNER_OFFSET: usize = 34;
+/// Offset of the quoter of a program state account
+pub const GLOBAL_STATE_QUOTER_OFFSET: usize = 211;
+/// Offset of the buyback mint of a program state account
+pub const GLOBAL_STATE_BUYBACK_MINT_OFFSET: usize = 243;
+
+/// All layouts of the program state account
+#[derive(Debug, PartialEq)]
+pub enum GlobalStateVersion {
+    /// Original layout, the bare state without a version byte or reserved
+    /// space, until the account is migrated in place
+    GlobalStateV1(GlobalState),
+    /// Latest layout, prefixed with the version and followed by reserved space
+    GlobalStateV2(GlobalState),
+}
+
+/// GlobalStateVersion does not implement program_pack::Pack because the
+/// legacy layout has no version byte, so special implementations are
+/// provided here
+impl GlobalStateVersion {
+    /// Size of the latest version of the program state
+    pub const LATEST_LEN: usize = 1 + GlobalState::LEN + GLOBAL_STATE_RESERVED_LEN; // add one for the version enum
+
+    /// Pack the program state into a byte array, in the latest layout
+    pub fn pack(src: &GlobalState, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LATEST_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (version, rest) = dst.split_at_mut(1);
+        let (state, reserved) = rest.split_at_mut(GlobalState::LEN);
+        version[0] = 2;
+        src.pack_into_slice(state);
+        reserved.iter_mut().for_each(|byte| *byte = 0);
+        Ok(())
+    }
+
+    /// Unpack the program state account based on its layout
+    pub fn unpack_version(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() == GLOBAL_STATE_V1_LEN {
+            // fields added since the legacy layout unpack as zero
+            let mut state = [0u8; GlobalState::LEN];
+            state[..GLOBAL_STATE_V1_LEN].copy_from_slice(input);
+            return Ok(Self::GlobalStateV1(GlobalState::unpack_from_slice(&state)?));
+        }
+        if input.len() != Self::LATEST_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (&version, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidAccountData)?;
+        match version {
+            // a freshly allocated account, not initialized yet
+            0 | 2 => Ok(Self::GlobalStateV2(GlobalState::unpack_from_slice(
+                &rest[..GlobalState::LEN],
+            )?)),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Unpack a program state in the latest layout, the only one accepted
+    /// outside of the migration
+    pub fn unpack(input: &[u8]) -> Result<GlobalState, ProgramError> {
+        match Self::unpack_version(input)? {
+            Self::GlobalStateV2(state) => Ok(state),
+            Self::GlobalStateV1(_) => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+///Liquidity lock escrow, one per (swap, owner) pair
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct LiquidityLock {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the lock program address
+    pub bump: u8,
+
+    /// Owner allowed to unlock the pool tokens
+    pub owner: Pubkey,
+
+    /// Swap the locked pool tokens belong to
+    pub swap: Pubkey,
+
+    /// Pool token account holding the locked tokens, owned by the lock address
+    pub vault: Pubkey,
+
+    /// Amount of pool tokens currently locked
+    pub amount: u64,
+
+    /// Unix timestamp after which the pool tokens can be unlocked
+    pub unlock_timestamp: i64,
+}
+impl Sealed for LiquidityLock {}
+impl IsInitialized for LiquidityLock {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for LiquidityLock {
+    /// Size of the liquidity lock
+    const LEN: usize = 114;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, LiquidityLock::LEN];
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            amount,
+            unlock_timestamp,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 8, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        owner.copy_from_slice(self.owner.as_ref());
+        swap.copy_from_slice(self.swap.as_ref());
+        vault.copy_from_slice(self.vault.as_ref());
+        *amount = self.amount.to_le_bytes();
+        *unlock_timestamp = self.unlock_timestamp.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [LiquidityLock](struct.LiquidityLock.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != LiquidityLock::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, LiquidityLock::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            amount,
+            unlock_timestamp,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 8, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            owner: Pubkey::new_from_array(*owner),
+            swap: Pubkey::new_from_array(*swap),
+            vault: Pubkey::new_from_array(*vault),
+            amount: u64::from_le_bytes(*amount),
+            unlock_timestamp: i64::from_le_bytes(*unlock_timestamp),
+        })
+    }
+}
+
+/// Precision of the accumulated reward per staked pool token
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+///Staking pool distributing a reward token to staked pool tokens of a swap
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct StakePool {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the stake pool program address
+    pub bump: u8,
+
+    /// Swap whose pool tokens are staked
+    pub swap: Pubkey,
+
+    /// Pool token account holding the staked tokens, owned by the stake pool
+    pub stake_vault: Pubkey,
+
+    /// Mint of the reward token
+    pub reward_mint: Pubkey,
+
+    /// Reward token account funding the rewards, owned by the stake pool
+    pub reward_vault: Pubkey,
+
+    /// Reward tokens distributed per slot across all stakers
+    pub reward_per_slot: u64,
+
+    /// Slot of the last accumulator update
+    pub last_update_slot: u64,
+
+    /// Accumulated rewards per staked pool token, scaled by REWARD_PRECISION
+    pub acc_reward_per_share: u128,
+
+    /// Total pool tokens staked
+    pub total_staked: u64,
+}
+impl Sealed for StakePool {}
+impl IsInitialized for StakePool {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for StakePool {
+    /// Size of the stake pool
+    const LEN: usize = 170;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, StakePool::LEN];
+        let (
+            is_initialized,
+            bump,
+            swap,
+            stake_vault,
+            reward_mint,
+            reward_vault,
+            reward_per_slot,
+            last_update_slot,
+            acc_reward_per_share,
+            total_staked,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 32, 8, 8, 16, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        stake_vault.copy_from_slice(self.stake_vault.as_ref());
+        reward_mint.copy_from_slice(self.reward_mint.as_ref());
+        reward_vault.copy_from_slice(self.reward_vault.as_ref());
+        *reward_per_slot = self.reward_per_slot.to_le_bytes();
+        *last_update_slot = self.last_update_slot.to_le_bytes();
+        *acc_reward_per_share = self.acc_reward_per_share.to_le_bytes();
+        *total_staked = self.total_staked.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [StakePool](struct.StakePool.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != StakePool::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, StakePool::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            swap,
+            stake_vault,
+            reward_mint,
+            reward_vault,
+            reward_per_slot,
+            last_update_slot,
+            acc_reward_per_share,
+            total_staked,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 32, 8, 8, 16, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            stake_vault: Pubkey::new_from_array(*stake_vault),
+            reward_mint: Pubkey::new_from_array(*reward_mint),
+            reward_vault: Pubkey::new_from_array(*reward_vault),
+            reward_per_slot: u64::from_le_bytes(*reward_per_slot),
+            last_update_slot: u64::from_le_bytes(*last_update_slot),
+            acc_reward_per_share: u128::from_le_bytes(*acc_reward_per_share),
+            total_staked: u64::from_le_bytes(*total_staked),
+        })
+    }
+}
+
+impl StakePool {
+    /// Accrue the rewards emitted since the last update into the accumulator
+    pub fn update(&mut self, current_slot: u64) -> Option<()> {
+        if current_slot <= self.last_update_slot {
+            return Some(());
+        }
+        if self.total_staked > 0 {
+            let slots = current_slot.checked_sub(self.last_update_slot)?;
+            let reward = u128::from(slots).checked_mul(u128::from(self.reward_per_slot))?;
+            self.acc_reward_per_share = self.acc_reward_per_share.checked_add(
+                reward
+                    .checked_mul(REWARD_PRECISION)?
+                    .checked_div(u128::from(self.total_staked))?,
+            )?;
+        }
+        self.last_update_slot = current_slot;
+        Some(())
+    }
+}
+
+///Staked position of a single owner in a stake pool
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct StakePosition {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the position program address
+    pub bump: u8,
+
+    /// Owner of the staked pool tokens
+    pub owner: Pubkey,
+
+    /// Stake pool of the position
+    pub stake_pool: Pubkey,
+
+    /// Amount of pool tokens staked
+    pub amount: u64,
+
+    /// Rewards already accounted for, scaled by REWARD_PRECISION
+    pub reward_debt: u128,
+
+    /// Rewards settled but not yet claimed
+    pub pending_rewards: u64,
+}
+impl Sealed for StakePosition {}
+impl IsInitialized for StakePosition {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for StakePosition {
+    /// Size of the stake position
+    const LEN: usize = 98;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, StakePosition::LEN];
+        let (
+            is_initialized,
+            bump,
+            owner,
+            stake_pool,
+            amount,
+            reward_debt,
+            pending_rewards,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 8, 16, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        owner.copy_from_slice(self.owner.as_ref());
+        stake_pool.copy_from_slice(self.stake_pool.as_ref());
+        *amount = self.amount.to_le_bytes();
+        *reward_debt = self.reward_debt.to_le_bytes();
+        *pending_rewards = self.pending_rewards.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [StakePosition](struct.StakePosition.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != StakePosition::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, StakePosition::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            owner,
+            stake_pool,
+            amount,
+            reward_debt,
+            pending_rewards,
+        ) = array_refs![input, 1, 1, 32, 32, 8, 16, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            owner: Pubkey::new_from_array(*owner),
+            stake_pool: Pubkey::new_from_array(*stake_pool),
+            amount: u64::from_le_bytes(*amount),
+            reward_debt: u128::from_le_bytes(*reward_debt),
+            pending_rewards: u64::from_le_bytes(*pending_rewards),
+        })
+    }
+}
+
+impl StakePosition {
+    /// Move the rewards earned since the last settlement into `pending_rewards`
+    pub fn settle(&mut self, stake_pool: &StakePool) -> Option<()> {
+        let accrued = u128::from(self.amount)
+            .checked_mul(stake_pool.acc_reward_per_share)?
+            .checked_div(REWARD_PRECISION)?;
+        let earned = accrued.checked_sub(self.reward_debt)?;
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(u64::try_from(earned).ok()?)?;
+        self.reward_debt = accrued;
+        Some(())
+    }
+
+    /// Reset the reward debt after the staked amount changed
+    pub fn checkpoint(&mut self, stake_pool: &StakePool) -> Option<()> {
+        self.reward_debt = u128::from(self.amount)
+            .checked_mul(stake_pool.acc_reward_per_share)?
+            .checked_div(REWARD_PRECISION)?;
+        Some(())
+    }
+}
+
+///LP position of a single owner in a pool with claimable fees
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct LpPosition {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the position program address
+    pub bump: u8,
+
+    /// Owner of the position, or the mint of the NFT of an NFT position
+    pub owner: Pubkey,
+
+    /// Swap of the position
+    pub swap: Pubkey,
+
+    /// Pool tokens held in the position
+    pub pool_tokens: u64,
+
+    /// Token A fee growth of the swap at the last settlement
+    pub fee_growth_checkpoint_a: u128,
+
+    /// Token B fee growth of the swap at the last settlement
+    pub fee_growth_checkpoint_b: u128,
+
+    /// Token A fees settled but not yet claimed
+    pub fees_owed_a: u64,
+
+    /// Token B fees settled but not yet claimed
+    pub fees_owed_b: u64,
+
+    /// The position belongs to the holder of its NFT
+    pub nft: bool,
+
+    /// Account that paid the rent of the position, refunded when it is
+    /// closed
+    pub payer: Pubkey,
+}
+impl Sealed for LpPosition {}
+impl IsInitialized for LpPosition {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for LpPosition {
+    /// Size of the LP position
+    const LEN: usize = 155;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, LpPosition::LEN];
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            pool_tokens,
+            fee_growth_checkpoint_a,
+            fee_growth_checkpoint_b,
+            fees_owed_a,
+            fees_owed_b,
+            nft,
+            payer,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 8, 16, 16, 8, 8, 1, 32];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        owner.copy_from_slice(self.owner.as_ref());
+        swap.copy_from_slice(self.swap.as_ref());
+        *pool_tokens = self.pool_tokens.to_le_bytes();
+        *fee_growth_checkpoint_a = self.fee_growth_checkpoint_a.to_le_bytes();
+        *fee_growth_checkpoint_b = self.fee_growth_checkpoint_b.to_le_bytes();
+        *fees_owed_a = self.fees_owed_a.to_le_bytes();
+        *fees_owed_b = self.fees_owed_b.to_le_bytes();
+        nft[0] = self.nft as u8;
+        payer.copy_from_slice(self.payer.as_ref());
+    }
+
+    /// Unpacks a byte buffer into a [LpPosition](struct.LpPosition.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != LpPosition::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, LpPosition::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            pool_tokens,
+            fee_growth_checkpoint_a,
+            fee_growth_checkpoint_b,
+            fees_owed_a,
+            fees_owed_b,
+            nft,
+            payer,
+        ) = array_refs![input, 1, 1, 32, 32, 8, 16, 16, 8, 8, 1, 32];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            owner: Pubkey::new_from_array(*owner),
+            swap: Pubkey::new_from_array(*swap),
+            pool_tokens: u64::from_le_bytes(*pool_tokens),
+            fee_growth_checkpoint_a: u128::from_le_bytes(*fee_growth_checkpoint_a),
+            fee_growth_checkpoint_b: u128::from_le_bytes(*fee_growth_checkpoint_b),
+            fees_owed_a: u64::from_le_bytes(*fees_owed_a),
+            fees_owed_b: u64::from_le_bytes(*fees_owed_b),
+            nft: match nft {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            payer: Pubkey::new_from_array(*payer),
+        })
+    }
+}
+
+impl LpPosition {
+    /// Move the fees earned since the last settlement into the fees owed and
+    /// checkpoint the fee growth of the swap
+    pub fn settle(&mut self, swap_v2: &SwapV2) -> Option<()> {
+        let pool_tokens = u128::from(self.pool_tokens);
+        let earned_a = swap_v2
+            .fee_growth_a
+            .checked_sub(self.fee_growth_checkpoint_a)?
+            .checked_mul(pool_tokens)?
+            .checked_div(REWARD_PRECISION)?;
+        let earned_b = swap_v2
+            .fee_growth_b
+            .checked_sub(self.fee_growth_checkpoint_b)?
+            .checked_mul(pool_tokens)?
+            .checked_div(REWARD_PRECISION)?;
+        self.fees_owed_a = self
+            .fees_owed_a
+            .checked_add(u64::try_from(earned_a).ok()?)?;
+        self.fees_owed_b = self
+            .fees_owed_b
+            .checked_add(u64::try_from(earned_b).ok()?)?;
+        self.fee_growth_checkpoint_a = swap_v2.fee_growth_a;
+        self.fee_growth_checkpoint_b = swap_v2.fee_growth_b;
+        Some(())
+    }
+}
+
+///Limit order escrow, one per (swap, owner, order id)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct LimitOrder {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the order program address
+    pub bump: u8,
+
+    /// Owner allowed to cancel the order, receives the rent back
+    pub owner: Pubkey,
+
+    /// Swap the order is filled against
+    pub swap: Pubkey,
+
+    /// Token account escrowing the input tokens, owned by the order address
+    pub vault: Pubkey,
+
+    /// Pool vault the input tokens are swapped INTO, fixing the direction
+    pub swap_source: Pubkey,
+
+    /// Owner token account credited with the output tokens
+    pub destination: Pubkey,
+
+    /// Order id chosen by the owner
+    pub order_id: u64,
+
+    /// Input tokens sold when the order is filled
+    pub amount_in: u64,
+
+    /// Minimum output for `amount_in`, the limit price of the order
+    pub minimum_amount_out: u64,
+
+    /// Input tokens paid to the keeper filling the order
+    pub keeper_fee: u64,
+}
+impl Sealed for LimitOrder {}
+impl IsInitialized for LimitOrder {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for LimitOrder {
+    /// Size of the limit order
+    const LEN: usize = 194;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, LimitOrder::LEN];
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            swap_source,
+            destination,
+            order_id,
+            amount_in,
+            minimum_amount_out,
+            keeper_fee,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        owner.copy_from_slice(self.owner.as_ref());
+        swap.copy_from_slice(self.swap.as_ref());
+        vault.copy_from_slice(self.vault.as_ref());
+        swap_source.copy_from_slice(self.swap_source.as_ref());
+        destination.copy_from_slice(self.destination.as_ref());
+        *order_id = self.order_id.to_le_bytes();
+        *amount_in = self.amount_in.to_le_bytes();
+        *minimum_amount_out = self.minimum_amount_out.to_le_bytes();
+        *keeper_fee = self.keeper_fee.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [LimitOrder](struct.LimitOrder.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != LimitOrder::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, LimitOrder::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            swap_source,
+            destination,
+            order_id,
+            amount_in,
+            minimum_amount_out,
+            keeper_fee,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            owner: Pubkey::new_from_array(*owner),
+            swap: Pubkey::new_from_array(*swap),
+            vault: Pubkey::new_from_array(*vault),
+            swap_source: Pubkey::new_from_array(*swap_source),
+            destination: Pubkey::new_from_array(*destination),
+            order_id: u64::from_le_bytes(*order_id),
+            amount_in: u64::from_le_bytes(*amount_in),
+            minimum_amount_out: u64::from_le_bytes(*minimum_amount_out),
+            keeper_fee: u64::from_le_bytes(*keeper_fee),
+        })
+    }
+}
+
+///Recurring order escrow, one per (swap, owner, position id)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct DcaPosition {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the position program address
+    pub bump: u8,
+
+    /// Owner allowed to close the position, receives the rent back
+    pub owner: Pubkey,
+
+    /// Swap the position trades against
+    pub swap: Pubkey,
+
+    /// Token account escrowing the input tokens, owned by the position address
+    pub vault: Pubkey,
+
+    /// Pool vault the input tokens are swapped INTO, fixing the direction
+    pub swap_source: Pubkey,
+
+    /// Owner token account credited with the output tokens
+    pub destination: Pubkey,
+
+    /// Position id chosen by the owner
+    pub dca_id: u64,
+
+    /// Input tokens swapped per tick
+    pub amount_per_tick: u64,
+
+    /// Minimum output of each tick
+    pub minimum_amount_out_per_tick: u64,
+
+    /// Input tokens paid to the keeper executing each tick
+    pub keeper_fee: u64,
+
+    /// Minimum number of seconds between two ticks
+    pub interval: i64,
+
+    /// Unix timestamp of the last executed tick
+    pub last_tick_timestamp: i64,
+}
+impl Sealed for DcaPosition {}
+impl IsInitialized for DcaPosition {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for DcaPosition {
+    /// Size of the recurring order
+    const LEN: usize = 210;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, DcaPosition::LEN];
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            swap_source,
+            destination,
+            dca_id,
+            amount_per_tick,
+            minimum_amount_out_per_tick,
+            keeper_fee,
+            interval,
+            last_tick_timestamp,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        owner.copy_from_slice(self.owner.as_ref());
+        swap.copy_from_slice(self.swap.as_ref());
+        vault.copy_from_slice(self.vault.as_ref());
+        swap_source.copy_from_slice(self.swap_source.as_ref());
+        destination.copy_from_slice(self.destination.as_ref());
+        *dca_id = self.dca_id.to_le_bytes();
+        *amount_per_tick = self.amount_per_tick.to_le_bytes();
+        *minimum_amount_out_per_tick = self.minimum_amount_out_per_tick.to_le_bytes();
+        *keeper_fee = self.keeper_fee.to_le_bytes();
+        *interval = self.interval.to_le_bytes();
+        *last_tick_timestamp = self.last_tick_timestamp.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [DcaPosition](struct.DcaPosition.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != DcaPosition::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, DcaPosition::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            owner,
+            swap,
+            vault,
+            swap_source,
+            destination,
+            dca_id,
+            amount_per_tick,
+            minimum_amount_out_per_tick,
+            keeper_fee,
+            interval,
+            last_tick_timestamp,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 32, 32, 8, 8, 8, 8, 8, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            owner: Pubkey::new_from_array(*owner),
+            swap: Pubkey::new_from_array(*swap),
+            vault: Pubkey::new_from_array(*vault),
+            swap_source: Pubkey::new_from_array(*swap_source),
+            destination: Pubkey::new_from_array(*destination),
+            dca_id: u64::from_le_bytes(*dca_id),
+            amount_per_tick: u64::from_le_bytes(*amount_per_tick),
+            minimum_amount_out_per_tick: u64::from_le_bytes(*minimum_amount_out_per_tick),
+            keeper_fee: u64::from_le_bytes(*keeper_fee),
+            interval: i64::from_le_bytes(*interval),
+            last_tick_timestamp: i64::from_le_bytes(*last_tick_timestamp),
+        })
+    }
+}
+
+///Allowlist entry letting a wallet trade on a gated pool, one per (swap, wallet)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct AllowlistEntry {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the entry program address
+    pub bump: u8,
+
+    /// Gated swap the entry grants access to
+    pub swap: Pubkey,
+
+    /// Wallet allowed to swap and deposit
+    pub wallet: Pubkey,
+
+    /// Account that paid the rent of the entry, refunded when it is removed
+    pub payer: Pubkey,
+}
+impl Sealed for AllowlistEntry {}
+impl IsInitialized for AllowlistEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for AllowlistEntry {
+    /// Size of the allowlist entry
+    const LEN: usize = 98;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, AllowlistEntry::LEN];
+        let (is_initialized, bump, swap, wallet, payer) = mut_array_refs![output, 1, 1, 32, 32, 32];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        wallet.copy_from_slice(self.wallet.as_ref());
+        payer.copy_from_slice(self.payer.as_ref());
+    }
+
+    /// Unpacks a byte buffer into an [AllowlistEntry](struct.AllowlistEntry.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != AllowlistEntry::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, AllowlistEntry::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, swap, wallet, payer) = array_refs![input, 1, 1, 32, 32, 32];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            wallet: Pubkey::new_from_array(*wallet),
+            payer: Pubkey::new_from_array(*payer),
+        })
+    }
+}
+
+///spl-governance governance whose executed proposals set the fees, one per
+///program
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct FeeGovernance {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the fee governance program address
+    pub bump: u8,
+
+    /// spl-governance program the realm belongs to
+    pub governance_program_id: Pubkey,
+
+    /// Realm of the governance
+    pub realm: Pubkey,
+
+    /// Governance program address signing the fee updates of executed
+    /// proposals
+    pub governance: Pubkey,
+}
+impl Sealed for FeeGovernance {}
+impl IsInitialized for FeeGovernance {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for FeeGovernance {
+    /// Size of the fee governance
+    const LEN: usize = 98;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, FeeGovernance::LEN];
+        let (is_initialized, bump, governance_program_id, realm, governance) =
+            mut_array_refs![output, 1, 1, 32, 32, 32];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        governance_program_id.copy_from_slice(self.governance_program_id.as_ref());
+        realm.copy_from_slice(self.realm.as_ref());
+        governance.copy_from_slice(self.governance.as_ref());
+    }
+
+    /// Unpacks a byte buffer into a [FeeGovernance](struct.FeeGovernance.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != FeeGovernance::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, FeeGovernance::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, governance_program_id, realm, governance) =
+            array_refs![input, 1, 1, 32, 32, 32];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            governance_program_id: Pubkey::new_from_array(*governance_program_id),
+            realm: Pubkey::new_from_array(*realm),
+            governance: Pubkey::new_from_array(*governance),
+        })
+    }
+}
+
+///Last deposit of an owner into a pool, one per (swap, owner)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct DepositRecord {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the record program address
+    pub bump: u8,
+
+    /// Swap the deposits were made into
+    pub swap: Pubkey,
+
+    /// Owner of the pool token accounts deposits are minted to
+    pub owner: Pubkey,
+
+    /// Slot of the last deposit, or of the record creation
+    pub last_deposit_slot: u64,
+}
+impl Sealed for DepositRecord {}
+impl IsInitialized for DepositRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for DepositRecord {
+    /// Size of the deposit record
+    const LEN: usize = 74;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, DepositRecord::LEN];
+        let (is_initialized, bump, swap, owner, last_deposit_slot) =
+            mut_array_refs![output, 1, 1, 32, 32, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        *last_deposit_slot = self.last_deposit_slot.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [DepositRecord](struct.DepositRecord.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != DepositRecord::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, DepositRecord::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, swap, owner, last_deposit_slot) =
+            array_refs![input, 1, 1, 32, 32, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            owner: Pubkey::new_from_array(*owner),
+            last_deposit_slot: u64::from_le_bytes(*last_deposit_slot),
+        })
+    }
+}
+
+///Last quote nonce a taker filled in a pool, one per (swap, taker)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct QuoteNonce {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the record program address
+    pub bump: u8,
+
+    /// Swap the quotes were filled against
+    pub swap: Pubkey,
+
+    /// Taker of the quotes
+    pub taker: Pubkey,
+
+    /// Nonce of the last filled quote, the next quote of the taker must use
+    /// a higher one
+    pub nonce: u64,
+}
+impl Sealed for QuoteNonce {}
+impl IsInitialized for QuoteNonce {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for QuoteNonce {
+    /// Size of the quote nonce record
+    const LEN: usize = 74;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, QuoteNonce::LEN];
+        let (is_initialized, bump, swap, taker, nonce) = mut_array_refs![output, 1, 1, 32, 32, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        taker.copy_from_slice(self.taker.as_ref());
+        *nonce = self.nonce.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [QuoteNonce](struct.QuoteNonce.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != QuoteNonce::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, QuoteNonce::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, swap, taker, nonce) = array_refs![input, 1, 1, 32, 32, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            taker: Pubkey::new_from_array(*taker),
+            nonce: u64::from_le_bytes(*nonce),
+        })
+    }
+}
+
+///Hidden swap of a commit-reveal swap, one per (swap, owner)
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct SwapCommitment {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the commitment program address
+    pub bump: u8,
+
+    /// Swap the committed trade executes against
+    pub swap: Pubkey,
+
+    /// User transfer authority that must reveal the trade, receives the rent
+    /// back
+    pub owner: Pubkey,
+
+    /// Hash of the trade parameters and salt, see `SwapCommitment::hash`
+    pub hash: [u8; 32],
+
+    /// Slot of the commitment, the trade can be revealed from the next one
+    pub commit_slot: u64,
+}
+impl SwapCommitment {
+    /// Hash committing to a swap of `amount_in` from `source` into the pool
+    /// vault `swap_source`, paying at least `minimum_amount_out` to
+    /// `destination`
+    pub fn hash(
+        amount_in: u64,
+        minimum_amount_out: u64,
+        source: &Pubkey,
+        swap_source: &Pubkey,
+        destination: &Pubkey,
+        salt: &[u8; 32],
+    ) -> [u8; 32] {
+        hashv(&[
+            &amount_in.to_le_bytes(),
+            &minimum_amount_out.to_le_bytes(),
+            source.as_ref(),
+            swap_source.as_ref(),
+            destination.as_ref(),
+            salt,
+        ])
+        .to_bytes()
+    }
+}
+impl Sealed for SwapCommitment {}
+impl IsInitialized for SwapCommitment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for SwapCommitment {
+    /// Size of the swap commitment
+    const LEN: usize = 106;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SwapCommitment::LEN];
+        let (is_initialized, bump, swap, owner, hash, commit_slot) =
+            mut_array_refs![output, 1, 1, 32, 32, 32, 8];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        hash.copy_from_slice(&self.hash);
+        *commit_slot = self.commit_slot.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [SwapCommitment](struct.SwapCommitment.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != SwapCommitment::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, SwapCommitment::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, swap, owner, hash, commit_slot) =
+            array_refs![input, 1, 1, 32, 32, 32, 8];
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            owner: Pubkey::new_from_array(*owner),
+            hash: *hash,
+            commit_slot: u64::from_le_bytes(*commit_slot),
+        })
+    }
+}
+
+/// Maximum number of swaps a batch auction holds
+pub const MAX_BATCH_ORDERS: usize = 16;
+
+///Swap queued in a batch auction
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchOrder {
+    /// Token account the input tokens came from, refunded if the clearing
+    /// price misses the limit of the order
+    pub source: Pubkey,
+
+    /// Token account credited with the output tokens
+    pub destination: Pubkey,
+
+    /// Owner of the SOURCE account when the swap was queued, who claims the
+    /// tokens of the swap if its accounts cannot receive them at settlement
+    pub owner: Pubkey,
+
+    /// Direction of the swap
+    pub trade_direction: TradeDirection,
+
+    /// Input tokens sold
+    pub amount_in: u64,
+
+    /// Minimum output for `amount_in`
+    pub minimum_amount_out: u64,
+
+    /// Whether the settlement filled the swap, its claim then being paid in
+    /// the DESTINATION token rather than refunded in the SOURCE token
+    pub filled: bool,
+
+    /// Tokens the settlement owes the owner, zero while the swap is queued
+    pub claimable: u64,
+}
+impl BatchOrder {
+    /// Whether the swap still waits for a settlement, rather than for its
+    /// owner to claim its tokens
+    pub fn is_queued(&self) -> bool {
+        self.claimable == 0
+    }
+}
+impl Sealed for BatchOrder {}
+impl Pack for BatchOrder {
+    /// Size of a batch order
+    const LEN: usize = 122;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, BatchOrder::LEN];
+        let (source, destination, owner, trade_direction, amount_in, minimum_amount_out, filled, claimable) =
+            mut_array_refs![output, 32, 32, 32, 1, 8, 8, 1, 8];
+        source.copy_from_slice(self.source.as_ref());
+        destination.copy_from_slice(self.destination.as_ref());
+        owner.copy_from_slice(self.owner.as_ref());
+        trade_direction[0] = match self.trade_direction {
+            TradeDirection::AtoB => 0,
+            TradeDirection::BtoA => 1,
+        };
+        *amount_in = self.amount_in.to_le_bytes();
+        *minimum_amount_out = self.minimum_amount_out.to_le_bytes();
+        filled[0] = self.filled as u8;
+        *claimable = self.claimable.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [BatchOrder](struct.BatchOrder.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, BatchOrder::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (source, destination, owner, trade_direction, amount_in, minimum_amount_out, filled, claimable) =
+            array_refs![input, 32, 32, 32, 1, 8, 8, 1, 8];
+        Ok(Self {
+            source: Pubkey::new_from_array(*source),
+            destination: Pubkey::new_from_array(*destination),
+            owner: Pubkey::new_from_array(*owner),
+            trade_direction: match trade_direction {
+                [0] => TradeDirection::AtoB,
+                [1] => TradeDirection::BtoA,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            amount_in: u64::from_le_bytes(*amount_in),
+            minimum_amount_out: u64::from_le_bytes(*minimum_amount_out),
+            filled: match filled {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            claimable: u64::from_le_bytes(*claimable),
+        })
+    }
+}
+
+///Queue of the swaps of a batch auction pool, one per swap
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct BatchAuction {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the batch auction program address
+    pub bump: u8,
+
+    /// Swap the batch is settled against
+    pub swap: Pubkey,
+
+    /// Token account escrowing the queued token A, owned by the batch
+    /// auction address
+    pub token_a_vault: Pubkey,
+
+    /// Token account escrowing the queued token B, owned by the batch
+    /// auction address
+    pub token_b_vault: Pubkey,
+
+    /// Slot from which the queued swaps can be settled and no more swaps
+    /// are accepted
+    pub window_end_slot: u64,
+
+    /// Queued swaps, and settled swaps left for their owners to claim, at
+    /// most MAX_BATCH_ORDERS
+    pub orders: Vec<BatchOrder>,
+}
+impl Sealed for BatchAuction {}
+impl IsInitialized for BatchAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for BatchAuction {
+    /// Size of the batch auction
+    const LEN: usize = 107 + BatchOrder::LEN * MAX_BATCH_ORDERS;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, BatchAuction::LEN];
+        let (
+            is_initialized,
+            bump,
+            swap,
+            token_a_vault,
+            token_b_vault,
+            window_end_slot,
+            order_count,
+            orders,
+        ) = mut_array_refs![output, 1, 1, 32, 32, 32, 8, 1, BatchOrder::LEN * MAX_BATCH_ORDERS];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        swap.copy_from_slice(self.swap.as_ref());
+        token_a_vault.copy_from_slice(self.token_a_vault.as_ref());
+        token_b_vault.copy_from_slice(self.token_b_vault.as_ref());
+        *window_end_slot = self.window_end_slot.to_le_bytes();
+        order_count[0] = self.orders.len() as u8;
+        orders.fill(0);
+        for (order, output) in self.orders.iter().zip(orders.chunks_exact_mut(BatchOrder::LEN)) {
+            order.pack_into_slice(output);
+        }
+    }
+
+    /// Unpacks a byte buffer into a [BatchAuction](struct.BatchAuction.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != BatchAuction::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, BatchAuction::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            is_initialized,
+            bump,
+            swap,
+            token_a_vault,
+            token_b_vault,
+            window_end_slot,
+            order_count,
+            orders,
+        ) = array_refs![input, 1, 1, 32, 32, 32, 8, 1, BatchOrder::LEN * MAX_BATCH_ORDERS];
+        let order_count = usize::from(order_count[0]);
+        if order_count > MAX_BATCH_ORDERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            swap: Pubkey::new_from_array(*swap),
+            token_a_vault: Pubkey::new_from_array(*token_a_vault),
+            token_b_vault: Pubkey::new_from_array(*token_b_vault),
+            window_end_slot: u64::from_le_bytes(*window_end_slot),
+            orders: orders
+                .chunks_exact(BatchOrder::LEN)
+                .take(order_count)
+                .map(BatchOrder::unpack_from_slice)
+                .collect::<Result<Vec<_>, ProgramError>>()?,
+        })
+    }
+}
+
+/// Maximum number of admin actions pending in the scheduler at once
+pub const MAX_PENDING_ACTIONS: usize = 8;
+
+///Admin change staged in the scheduler
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminAction {
+    /// Set the fees and fee discount of the program
+    SetFees {
+        /// Fee ratio
+        fees: Fees,
+        /// Fixed fee discount for holders of a designated token
+        fee_discount: FeeDiscount,
+    },
+    /// Move the amp of a stable pool, within MAX_AMP_CHANGE_FACTOR of its
+    /// amp when the action is executed
+    SetAmp {
+        /// Stable pool to change the amp of
+        swap: Pubkey,
+        /// New amp
+        amp: u64,
+    },
+    /// Hand the program over to a new owner
+    SetOwner {
+        /// New program owner
+        owner: Pubkey,
+    },
+}
+impl Sealed for AdminAction {}
+impl Pack for AdminAction {
+    /// Size of an admin action, its tag and the largest of its payloads
+    const LEN: usize = 1 + Fees::LEN + FeeDiscount::LEN;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, AdminAction::LEN];
+        let (tag, payload) = mut_array_refs![output, 1, Fees::LEN + FeeDiscount::LEN];
+        payload.fill(0);
+        match self {
+            Self::SetFees { fees, fee_discount } => {
+                tag[0] = 0;
+                let (fees_data, fee_discount_data) = payload.split_at_mut(Fees::LEN);
+                fees.pack_into_slice(fees_data);
+                fee_discount.pack_into_slice(fee_discount_data);
+            }
+            Self::SetAmp { swap, amp } => {
+                tag[0] = 1;
+                payload[..32].copy_from_slice(swap.as_ref());
+                payload[32..40].copy_from_slice(&amp.to_le_bytes());
+            }
+            Self::SetOwner { owner } => {
+                tag[0] = 2;
+                payload[..32].copy_from_slice(owner.as_ref());
+            }
+        }
+    }
+
+    /// Unpacks a byte buffer into an [AdminAction](enum.AdminAction.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, AdminAction::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (tag, payload) = array_refs![input, 1, Fees::LEN + FeeDiscount::LEN];
+        let pubkey = array_ref![payload, 0, 32];
+        Ok(match tag[0] {
+            0 => Self::SetFees {
+                fees: Fees::unpack_from_slice(&payload[..Fees::LEN])?,
+                fee_discount: FeeDiscount::unpack_from_slice(&payload[Fees::LEN..])?,
+            },
+            1 => Self::SetAmp {
+                swap: Pubkey::new_from_array(*pubkey),
+                amp: u64::from_le_bytes(*array_ref![payload, 32, 8]),
+            },
+            2 => Self::SetOwner {
+                owner: Pubkey::new_from_array(*pubkey),
+            },
+            _ => return Err(ProgramError::InvalidAccountData),
+        })
+    }
+}
+
+///Admin action waiting in the scheduler for its ETA
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingAction {
+    /// Id of the action in the scheduler, never reused
+    pub action_id: u64,
+
+    /// Unix timestamp from which the action may be executed
+    pub eta: i64,
+
+    /// Change to apply
+    pub action: AdminAction,
+}
+impl Sealed for PendingAction {}
+impl Pack for PendingAction {
+    /// Size of a pending action
+    const LEN: usize = 16 + AdminAction::LEN;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, PendingAction::LEN];
+        let (action_id, eta, action) = mut_array_refs![output, 8, 8, AdminAction::LEN];
+        *action_id = self.action_id.to_le_bytes();
+        *eta = self.eta.to_le_bytes();
+        self.action.pack_into_slice(action);
+    }
+
+    /// Unpacks a byte buffer into a [PendingAction](struct.PendingAction.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, PendingAction::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (action_id, eta, action) = array_refs![input, 8, 8, AdminAction::LEN];
+        Ok(Self {
+            action_id: u64::from_le_bytes(*action_id),
+            eta: i64::from_le_bytes(*eta),
+            action: AdminAction::unpack_from_slice(action)?,
+        })
+    }
+}
+
+///Queue of the admin actions of the program waiting for their ETA, one per
+///program
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct Scheduler {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed of the scheduler program address
+    pub bump: u8,
+
+    /// Id of the next action scheduled
+    pub next_action_id: u64,
+
+    /// Pending actions, at most MAX_PENDING_ACTIONS
+    pub actions: Vec<PendingAction>,
+}
+impl Sealed for Scheduler {}
+impl IsInitialized for Scheduler {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for Scheduler {
+    /// Size of the scheduler
+    const LEN: usize = 11 + PendingAction::LEN * MAX_PENDING_ACTIONS;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, Scheduler::LEN];
+        let (is_initialized, bump, next_action_id, action_count, actions) =
+            mut_array_refs![output, 1, 1, 8, 1, PendingAction::LEN * MAX_PENDING_ACTIONS];
+        is_initialized[0] = self.is_initialized as u8;
+        bump[0] = self.bump;
+        *next_action_id = self.next_action_id.to_le_bytes();
+        action_count[0] = self.actions.len() as u8;
+        actions.fill(0);
+        for (action, output) in self
+            .actions
+            .iter()
+            .zip(actions.chunks_exact_mut(PendingAction::LEN))
+        {
+            action.pack_into_slice(output);
+        }
+    }
+
+    /// Unpacks a byte buffer into a [Scheduler](struct.Scheduler.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != Scheduler::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, Scheduler::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump, next_action_id, action_count, actions) =
+            array_refs![input, 1, 1, 8, 1, PendingAction::LEN * MAX_PENDING_ACTIONS];
+        let action_count = usize::from(action_count[0]);
+        if action_count > MAX_PENDING_ACTIONS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bump: bump[0],
+            next_action_id: u64::from_le_bytes(*next_action_id),
+            actions: actions
+                .chunks_exact(PendingAction::LEN)
+                .take(action_count)
+                .map(PendingAction::unpack_from_slice)
+                .collect::<Result<Vec<_>, ProgramError>>()?,
+        })
+    }
+}
+
+///Asset held by a basket pool
+#[repr(C)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BasketAsset {
+    /// Token account holding the asset, owned by the basket authority
+    pub vault: Pubkey,
+
+    /// Mint of the asset
+    pub mint: Pubkey,
+
+    /// Tracked reserve of the asset, which tokens transferred directly to
+    /// the vault do not change
+    pub reserve: u64,
+}
+impl Sealed for BasketAsset {}
+impl Pack for BasketAsset {
+    /// Size of a basket asset
+    const LEN: usize = 72;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, BasketAsset::LEN];
+        let (vault, mint, reserve) = mut_array_refs![output, 32, 32, 8];
+        vault.copy_from_slice(self.vault.as_ref());
+        mint.copy_from_slice(self.mint.as_ref());
+        *reserve = self.reserve.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [BasketAsset](struct.BasketAsset.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, BasketAsset::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (vault, mint, reserve) = array_refs![input, 32, 32, 8];
+        Ok(Self {
+            vault: Pubkey::new_from_array(*vault),
+            mint: Pubkey::new_from_array(*mint),
+            reserve: u64::from_le_bytes(*reserve),
+        })
+    }
+}
+
+///Pool of up to MAX_BASKET_ASSETS assets under the generalized constant
+///product invariant
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct BasketPool {
+    /// Initialized state.
+    pub is_initialized: bool,
+
+    /// Bump seed used to generate the program address / authority
+    pub nonce: u8,
+
+    /// Program ID of the tokens being exchanged.
+    pub token_program_id: Pubkey,
+
+    /// Pool tokens are issued when assets are deposited.
+    /// Pool tokens can be withdrawn back to the original assets.
+    pub pool_mint: Pubkey,
+
+    /// Assets of the basket, from MIN_BASKET_ASSETS to MAX_BASKET_ASSETS
+    pub assets: Vec<BasketAsset>,
+}
+impl BasketPool {
+    /// Index of the asset held in the given vault
+    pub fn asset_index(&self, vault: &Pubkey) -> Option<usize> {
+        self.assets.iter().position(|asset| asset.vault == *vault)
+    }
+
+    /// Tracked reserves of the assets, in order
+    pub fn reserves(&self) -> Vec<u128> {
+        self.assets.iter().map(|asset| u128::from(asset.reserve)).collect()
+    }
+}
+impl Sealed for BasketPool {}
+impl IsInitialized for BasketPool {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+impl Pack for BasketPool {
+    /// Size of the basket pool
+    const LEN: usize = 67 + BasketAsset::LEN * MAX_BASKET_ASSETS;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, BasketPool::LEN];
+        let (is_initialized, nonce, token_program_id, pool_mint, asset_count, assets) =
+            mut_array_refs![output, 1, 1, 32, 32, 1, BasketAsset::LEN * MAX_BASKET_ASSETS];
+        is_initialized[0] = self.is_initialized as u8;
+        nonce[0] = self.nonce;
+        token_program_id.copy_from_slice(self.token_program_id.as_ref());
+        pool_mint.copy_from_slice(self.pool_mint.as_ref());
+        asset_count[0] = self.assets.len() as u8;
+        assets.fill(0);
+        for (asset, output) in self.assets.iter().zip(assets.chunks_exact_mut(BasketAsset::LEN)) {
+            asset.pack_into_slice(output);
+        }
+    }
+
+    /// Unpacks a byte buffer into a [BasketPool](struct.BasketPool.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != BasketPool::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, BasketPool::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, nonce, token_program_id, pool_mint, asset_count, assets) =
+            array_refs![input, 1, 1, 32, 32, 1, BasketAsset::LEN * MAX_BASKET_ASSETS];
+        let asset_count = usize::from(asset_count[0]);
+        if asset_count > MAX_BASKET_ASSETS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            is_initialized: match is_initialized {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            nonce: nonce[0],
+            token_program_id: Pubkey::new_from_array(*token_program_id),
+            pool_mint: Pubkey::new_from_array(*pool_mint),
+            assets: assets
+                .chunks_exact(BasketAsset::LEN)
+                .take(asset_count)
+                .map(BasketAsset::unpack_from_slice)
+                .collect::<Result<Vec<_>, ProgramError>>()?,
+        })
+    }
+}
+
+/// Version of the [PoolStateView](struct.PoolStateView.html) schema
+pub const POOL_STATE_VIEW_VERSION: u8 = 2;
+
+/// Fully-resolved pool state returned by the GetPoolState view
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct PoolStateView {
+    /// Schema version, bumped whenever the layout changes
+    pub schema_version: u8,
+    /// Version of the swap account
+    pub swap_version: u8,
+    /// Token A held by the pool
+    pub token_a_reserve: u64,
+    /// Token B held by the pool
+    pub token_b_reserve: u64,
+    /// Pool token supply
+    pub pool_supply: u64,
+    /// Curve type and parameters
+    pub swap_curve: SwapCurve,
+    /// Fees in effect for a swap at the current slot
+    pub fees: Fees,
+    /// Spot price of token A in token B, scaled by PRICE_PRECISION
+    pub spot_price: u128,
+    /// Realized volatility at the current slot, in basis points
+    pub volatility: u64,
+    /// Slot of the last trade, zero if not tracked
+    pub last_swap_slot: u64,
+    /// Execution price of the last trade, zero if not tracked
+    pub last_price: u128,
+}
+impl Sealed for PoolStateView {}
+impl Pack for PoolStateView {
+    /// Size of the view
+    const LEN: usize = 203;
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, PoolStateView::LEN];
+        let (
+            schema_version,
+            swap_version,
+            token_a_reserve,
+            token_b_reserve,
+            pool_supply,
+            swap_curve,
+            fees,
+            spot_price,
+            volatility,
+            last_swap_slot,
+            last_price,
+        ) = mut_array_refs![output, 1, 1, 8, 8, 8, 33, 96, 16, 8, 8, 16];
+        schema_version[0] = self.schema_version;
+        swap_version[0] = self.swap_version;
+        *token_a_reserve = self.token_a_reserve.to_le_bytes();
+        *token_b_reserve = self.token_b_reserve.to_le_bytes();
+        *pool_supply = self.pool_supply.to_le_bytes();
+        self.swap_curve.pack_into_slice(&mut swap_curve[..]);
+        self.fees.pack_into_slice(&mut fees[..]);
+        *spot_price = self.spot_price.to_le_bytes();
+        *volatility = self.volatility.to_le_bytes();
+        *last_swap_slot = self.last_swap_slot.to_le_bytes();
+        *last_price = self.last_price.to_le_bytes();
+    }
+
+    /// Unpacks a byte buffer into a [PoolStateView](struct.PoolStateView.html).
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() != PoolStateView::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let input = array_ref![input, 0, PoolStateView::LEN];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            schema_version,
+            swap_version,
+            token_a_reserve,
+            token_b_reserve,
+            pool_supply,
+            swap_curve,
+            fees,
+            spot_price,
+            volatility,
+            last_swap_slot,
+            last_price,
+        ) = array_refs![input, 1, 1, 8, 8, 8, 33, 96, 16, 8, 8, 16];
+        if schema_version[0] != POOL_STATE_VIEW_VERSION {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            schema_version: schema_version[0],
+            swap_version: swap_version[0],
+            token_a_reserve: u64::from_le_bytes(*token_a_reserve),
+            token_b_reserve: u64::from_le_bytes(*token_b_reserve),
+            pool_supply: u64::from_le_bytes(*pool_supply),
+            swap_curve: SwapCurve::unpack_from_slice(swap_curve)?,
+            fees: Fees::unpack_from_slice(fees)?,
+            spot_price: u128::from_le_bytes(*spot_price),
+            volatility: u64::from_le_bytes(*volatility),
+            last_swap_slot: u64::from_le_bytes(*last_swap_slot),
+            last_price: u128::from_le_bytes(*last_price),
+        })
     }
 }
@@ -0,0 +1,106 @@
+//! Token-2022 accounts, read without the extension crate. Pools of
+//! Token-2022 mints trade public balances only: accounts configured for
+//! confidential transfers and mint extensions changing how transfers settle
+//! are rejected.
+
+use crate::error::SwapError;
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey,
+    pubkey::Pubkey,
+};
+use spl_token::state::{Account, Mint};
+
+/// Token-2022 program id
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Account type byte of mints with extensions
+const ACCOUNT_TYPE_MINT: u8 = 1;
+
+/// Account type byte of token accounts with extensions
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Extensions charging a fee or running a hook on transfers, which pools
+/// price as if the whole amount arrived, or letting someone other than the
+/// pool authority move or freeze vault balances
+const UNSUPPORTED_MINT_EXTENSIONS: &[u16] = &[
+    1,  // TransferFeeConfig
+    9,  // NonTransferable
+    12, // PermanentDelegate
+    14, // TransferHook
+    16, // ConfidentialTransferFeeConfig
+];
+
+/// Extension of token accounts configured for confidential transfers
+const CONFIDENTIAL_TRANSFER_ACCOUNT: u16 = 5;
+
+/// Is the id that of a token program pools may use
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::id() || *program_id == TOKEN_2022_PROGRAM_ID
+}
+
+/// Points an instruction built by `spl_token` at the given token program,
+/// the instruction layouts used by pools being shared by both programs
+pub fn with_token_program(
+    token_program_id: &Pubkey,
+    mut instruction: Instruction,
+) -> Result<Instruction, ProgramError> {
+    if !is_token_program(token_program_id) {
+        return Err(SwapError::IncorrectTokenProgramId.into());
+    }
+    instruction.program_id = *token_program_id;
+    Ok(instruction)
+}
+
+/// Types of the extensions in the TLV data following the account type byte
+fn extension_types(data: &[u8], account_type: u8) -> Result<Vec<u16>, SwapError> {
+    if data.len() <= Account::LEN {
+        return Ok(Vec::new());
+    }
+    if data[Account::LEN] != account_type {
+        return Err(SwapError::InvalidAccountExtensions);
+    }
+    let mut tlv = &data[Account::LEN + 1..];
+    let mut types = Vec::new();
+    while tlv.len() >= 4 {
+        let extension_type = u16::from_le_bytes([tlv[0], tlv[1]]);
+        // uninitialized space ends the extensions
+        if extension_type == 0 {
+            break;
+        }
+        let length = usize::from(u16::from_le_bytes([tlv[2], tlv[3]]));
+        tlv = tlv
+            .get(4 + length..)
+            .ok_or(SwapError::InvalidAccountExtensions)?;
+        types.push(extension_type);
+    }
+    Ok(types)
+}
+
+/// Unpacks a token account of either token program, rejecting accounts
+/// configured for confidential transfers
+pub fn unpack_account(data: &[u8]) -> Result<Account, SwapError> {
+    let base = data.get(..Account::LEN).ok_or(SwapError::ExpectedAccount)?;
+    let account = Account::unpack(base).map_err(|_| SwapError::ExpectedAccount)?;
+    if extension_types(data, ACCOUNT_TYPE_ACCOUNT)?.contains(&CONFIDENTIAL_TRANSFER_ACCOUNT) {
+        return Err(SwapError::ConfidentialTransferAccount);
+    }
+    Ok(account)
+}
+
+/// Unpacks a mint of either token program, rejecting extensions pools
+/// cannot honour. A confidential transfer mint is accepted, pools trading
+/// its public balances.
+pub fn unpack_mint(data: &[u8]) -> Result<Mint, SwapError> {
+    let base = data.get(..Mint::LEN).ok_or(SwapError::ExpectedMint)?;
+    let mint = Mint::unpack(base).map_err(|_| SwapError::ExpectedMint)?;
+    if data.len() > Mint::LEN && data.len() <= Account::LEN {
+        return Err(SwapError::ExpectedMint);
+    }
+    if extension_types(data, ACCOUNT_TYPE_MINT)?
+        .iter()
+        .any(|extension_type| UNSUPPORTED_MINT_EXTENSIONS.contains(extension_type))
+    {
+        return Err(SwapError::UnsupportedMintExtension);
+    }
+    Ok(mint)
+}
@@ -0,0 +1,92 @@
+//! Deposits and withdrawals refuse a swap or global state account another
+//! program owns before reading it, whatever data it holds.
+
+mod common;
+
+use atlas_swap::instruction::{withdraw_all_token_types, WithdrawAllTokenTypes};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+
+/// A withdrawal of a thousandth of the provider's pool tokens to `user`
+fn withdraw(test: &ProgramTest, pool: &TestPool, user: &TestUser) -> Instruction {
+    withdraw_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        &pool.provider,
+        &pool.pool_mint,
+        &pool.provider_pool_tokens,
+        &pool.token_a,
+        &pool.token_b,
+        &user.token_a,
+        &user.token_b,
+        None,
+        None,
+        WithdrawAllTokenTypes {
+            pool_token_amount: test.token_amount(&pool.provider_pool_tokens) / 1_000,
+            minimum_token_a_amount: 0,
+            minimum_token_b_amount: 0,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn deposits_need_accounts_of_the_program() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+    let deposit = test.deposit_instruction(&pool, &user, RESERVE / 1_000, RESERVE, RESERVE);
+    let other_program = Pubkey::new_unique();
+
+    // an account of another program, which no swap unpacks from
+    let swap_account = test.accounts[&pool.swap].data.clone();
+    test.add(pool.swap, other_program, vec![1; 32]);
+    assert_eq!(
+        test.process(&deposit),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    test.add(pool.swap, atlas_swap::id(), swap_account);
+    let state = test.state;
+    test.accounts.get_mut(&state).unwrap().owner = other_program;
+    assert_eq!(
+        test.process(&deposit),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert_eq!(test.token_amount(&user.token_a), RESERVE);
+
+    test.accounts.get_mut(&state).unwrap().owner = atlas_swap::id();
+    test.process(&deposit).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), RESERVE / 1_000);
+}
+
+#[test]
+fn withdrawals_need_accounts_of_the_program() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let user = test.add_user(&pool, 0, 0);
+    let withdraw = withdraw(&test, &pool, &user);
+    let other_program = Pubkey::new_unique();
+
+    // an account of another program, which no swap unpacks from
+    let swap_account = test.accounts[&pool.swap].data.clone();
+    test.add(pool.swap, other_program, vec![1; 32]);
+    assert_eq!(
+        test.process(&withdraw),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    test.add(pool.swap, atlas_swap::id(), swap_account);
+    let state = test.state;
+    test.accounts.get_mut(&state).unwrap().owner = other_program;
+    assert_eq!(
+        test.process(&withdraw),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert_eq!(test.token_amount(&user.token_a), 0);
+
+    test.accounts.get_mut(&state).unwrap().owner = atlas_swap::id();
+    test.process(&withdraw).unwrap();
+    assert!(test.token_amount(&user.token_a) > 0);
+}
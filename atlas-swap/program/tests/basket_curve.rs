@@ -0,0 +1,46 @@
+//! Generalized constant product of basket pools: the initial supply is the
+//! geometric mean of the reserves and proportional deposits and withdrawals
+//! round in favor of the pool.
+
+use atlas_swap::curve::{
+    basket::{geometric_mean, invariant, pool_tokens_to_trading_tokens},
+    calculator::RoundDirection,
+};
+
+#[test]
+fn geometric_mean_is_the_floor_of_the_nth_root() {
+    assert_eq!(geometric_mean(&[4, 9]), Some(6));
+    assert_eq!(geometric_mean(&[2, 4, 8]), Some(4));
+    assert_eq!(geometric_mean(&[1, 1, 1, 17]), Some(2));
+    let max = u128::from(u64::MAX);
+    assert_eq!(geometric_mean(&[max, max, max, max]), Some(max));
+    assert_eq!(geometric_mean(&[]), None);
+    assert_eq!(geometric_mean(&[1, 1, 1, 1, 1]), None);
+}
+
+#[test]
+fn deposits_round_up_and_withdrawals_round_down() {
+    let reserves = [1_000, 3_000, 7, 10_001];
+    let deposit =
+        pool_tokens_to_trading_tokens(3, 10, &reserves, RoundDirection::Ceiling).unwrap();
+    let withdrawal =
+        pool_tokens_to_trading_tokens(3, 10, &reserves, RoundDirection::Floor).unwrap();
+    assert_eq!(deposit, vec![300, 900, 3, 3_001]);
+    assert_eq!(withdrawal, vec![300, 900, 2, 3_000]);
+}
+
+#[test]
+fn proportional_deposit_does_not_lower_the_invariant_per_pool_token() {
+    let reserves = [1_000_000, 2_500_000, 40_000];
+    let supply = 1_000_000u128;
+    let pool_tokens = 333_333;
+    let deposit =
+        pool_tokens_to_trading_tokens(pool_tokens, supply, &reserves, RoundDirection::Ceiling)
+            .unwrap();
+    let new_reserves: Vec<u128> = reserves.iter().zip(deposit).map(|(r, d)| r + d).collect();
+    let new_supply = supply + pool_tokens;
+    // the invariant per pool token, to the power of the asset count
+    let before = invariant(&reserves).unwrap() * invariant(&[new_supply; 3]).unwrap();
+    let after = invariant(&new_reserves).unwrap() * invariant(&[supply; 3]).unwrap();
+    assert!(after >= before);
+}
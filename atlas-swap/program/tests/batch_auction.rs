@@ -0,0 +1,315 @@
+//! Batch auction pools settle their queued swaps at one clearing price,
+//! refund the swaps whose limit it misses, and keep the tokens of swaps
+//! whose accounts were closed for their owners to claim. The window opened
+//! by the first swap of a batch takes swaps until its last slot, and the
+//! batch is only settled after it.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        claim_batch_orders, set_batch_auction, settle_batch, submit_batch_swap, SetBatchAuction,
+        SubmitBatchSwap,
+    },
+    pda::find_batch_auction,
+    state::BatchAuction,
+};
+use common::{set_clock, ProgramTest, TestAccount, TestPool, TestUser};
+use solana_program::{
+    instruction::Instruction, program_pack::Pack, pubkey::Pubkey, system_program,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const WINDOW_SLOTS: u64 = 10;
+
+struct Batch {
+    pool: TestPool,
+    batch: Pubkey,
+    token_a_vault: Pubkey,
+    token_b_vault: Pubkey,
+    token_a_fee: Pubkey,
+    token_b_fee: Pubkey,
+    orders: Vec<(Pubkey, Pubkey)>,
+}
+
+fn batch_pool(test: &mut ProgramTest) -> Batch {
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let batch = find_batch_auction(&atlas_swap::id(), &pool.swap).0;
+    let token_a_vault = test.add_token_account(&pool.token_a_mint, &batch, 0);
+    let token_b_vault = test.add_token_account(&pool.token_b_mint, &batch, 0);
+    test.accounts.insert(
+        test.owner,
+        TestAccount {
+            owner: system_program::id(),
+            lamports: 1_000_000_000,
+            data: vec![],
+        },
+    );
+    test.process(
+        &set_batch_auction(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            &batch,
+            &token_a_vault,
+            &token_b_vault,
+            SetBatchAuction {
+                batch_window_slots: WINDOW_SLOTS,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let fee_owner = test.fee_owner;
+    let token_a_fee = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let token_b_fee = test.add_token_account(&pool.token_b_mint, &fee_owner, 0);
+    Batch {
+        pool,
+        batch,
+        token_a_vault,
+        token_b_vault,
+        token_a_fee,
+        token_b_fee,
+        orders: vec![],
+    }
+}
+
+fn submit_instruction(
+    batch: &Batch,
+    user: &TestUser,
+    a_to_b: bool,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Instruction {
+    let (vault, source, destination) = if a_to_b {
+        (batch.token_a_vault, user.token_a, user.token_b)
+    } else {
+        (batch.token_b_vault, user.token_b, user.token_a)
+    };
+    submit_batch_swap(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &batch.pool.swap,
+        &batch.batch,
+        &vault,
+        &user.wallet,
+        &source,
+        &destination,
+        SubmitBatchSwap {
+            amount_in,
+            minimum_amount_out,
+        },
+    )
+    .unwrap()
+}
+
+fn submit(
+    test: &mut ProgramTest,
+    batch: &mut Batch,
+    user: &TestUser,
+    a_to_b: bool,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) {
+    test.process(&submit_instruction(
+        batch,
+        user,
+        a_to_b,
+        amount_in,
+        minimum_amount_out,
+    ))
+    .unwrap();
+    batch.orders.push(if a_to_b {
+        (user.token_a, user.token_b)
+    } else {
+        (user.token_b, user.token_a)
+    });
+}
+
+fn settle_instruction(test: &ProgramTest, batch: &Batch) -> Instruction {
+    settle_batch(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &batch.pool.swap,
+        &batch.pool.authority,
+        &test.state,
+        &batch.batch,
+        &batch.token_a_vault,
+        &batch.token_b_vault,
+        &batch.pool.token_a,
+        &batch.pool.token_b,
+        &batch.pool.pool_mint,
+        &batch.token_a_fee,
+        &batch.token_b_fee,
+        None,
+        None,
+        &[],
+        &batch.orders,
+    )
+    .unwrap()
+}
+
+fn settle(test: &mut ProgramTest, batch: &Batch) {
+    set_clock(WINDOW_SLOTS, 0);
+    test.process(&settle_instruction(test, batch)).unwrap();
+}
+
+fn no_fees() -> Fees {
+    Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+#[test]
+fn swaps_of_a_batch_share_one_clearing_price() {
+    set_clock(0, 0);
+    let mut test = ProgramTest::new(no_fees());
+    let mut batch = batch_pool(&mut test);
+    let large = test.add_user(&batch.pool, 1_000_000, 0);
+    let small = test.add_user(&batch.pool, 500_000, 0);
+    let other_side = test.add_user(&batch.pool, 0, 400_000);
+    submit(&mut test, &mut batch, &large, true, 1_000_000, 1);
+    submit(&mut test, &mut batch, &small, true, 500_000, 1);
+    submit(&mut test, &mut batch, &other_side, false, 400_000, 1);
+    settle(&mut test, &batch);
+
+    let large_out = test.token_amount(&large.token_b);
+    let small_out = test.token_amount(&small.token_b);
+    let other_side_out = test.token_amount(&other_side.token_a);
+    // both sellers of token A receive the same price, within rounding
+    assert!((large_out as i128 - 2 * small_out as i128).abs() <= 2);
+    // and the seller of token B the inverse price
+    let a_price = 1_500_000f64 / (large_out + small_out) as f64;
+    let b_price = 400_000f64 / other_side_out as f64;
+    assert!((a_price * b_price - 1.0).abs() < 1e-3);
+    // only the imbalance went through the pool
+    let pool_a_in = test.token_amount(&batch.pool.token_a) - RESERVE;
+    assert!(pool_a_in > 0 && pool_a_in < 1_100_000);
+    let auction = BatchAuction::unpack(&test.accounts[&batch.batch].data).unwrap();
+    assert!(auction.orders.is_empty());
+}
+
+#[test]
+fn balanced_swaps_cross_without_the_pool() {
+    set_clock(0, 0);
+    let mut test = ProgramTest::new(no_fees());
+    let mut batch = batch_pool(&mut test);
+    let seller_a = test.add_user(&batch.pool, 500_000, 0);
+    let seller_b = test.add_user(&batch.pool, 0, 500_000);
+    submit(&mut test, &mut batch, &seller_a, true, 500_000, 500_000);
+    submit(&mut test, &mut batch, &seller_b, false, 500_000, 500_000);
+    settle(&mut test, &batch);
+
+    assert_eq!(test.token_amount(&seller_a.token_b), 500_000);
+    assert_eq!(test.token_amount(&seller_b.token_a), 500_000);
+    assert_eq!(test.token_amount(&batch.pool.token_a), RESERVE);
+    assert_eq!(test.token_amount(&batch.pool.token_b), RESERVE);
+}
+
+#[test]
+fn missed_limits_are_refunded_and_closed_accounts_claimed() {
+    set_clock(0, 0);
+    let mut test = ProgramTest::new(no_fees());
+    let mut batch = batch_pool(&mut test);
+    let greedy = test.add_user(&batch.pool, 100_000, 0);
+    let closed = test.add_user(&batch.pool, 100_000, 0);
+    submit(&mut test, &mut batch, &greedy, true, 100_000, 200_000);
+    submit(&mut test, &mut batch, &closed, true, 100_000, 1);
+    // the destination of the second swap is closed before settlement
+    test.accounts.insert(
+        closed.token_b,
+        TestAccount {
+            owner: system_program::id(),
+            lamports: 0,
+            data: vec![],
+        },
+    );
+    settle(&mut test, &batch);
+
+    // the limit of the first swap is missed, so it is refunded
+    assert_eq!(test.token_amount(&greedy.token_a), 100_000);
+    assert_eq!(test.token_amount(&greedy.token_b), 0);
+    // the output of the second is kept for its owner
+    let owed = test.token_amount(&batch.token_b_vault);
+    assert!(owed > 0);
+    let claim = |test: &mut ProgramTest, owner: &Pubkey, destination: &Pubkey| {
+        test.process(
+            &claim_batch_orders(
+                &atlas_swap::id(),
+                &spl_token::id(),
+                &batch.pool.swap,
+                &batch.batch,
+                &batch.token_b_vault,
+                owner,
+                destination,
+            )
+            .unwrap(),
+        )
+    };
+    let new_account = test.add_token_account(&batch.pool.token_b_mint, &closed.wallet, 0);
+    assert_eq!(
+        claim(&mut test, &greedy.wallet, &greedy.token_b),
+        Err(SwapError::NoBatchClaim.into())
+    );
+    claim(&mut test, &closed.wallet, &new_account).unwrap();
+    assert_eq!(test.token_amount(&new_account), owed);
+    assert_eq!(test.token_amount(&batch.token_b_vault), 0);
+    assert_eq!(
+        claim(&mut test, &closed.wallet, &new_account),
+        Err(SwapError::NoBatchClaim.into())
+    );
+}
+
+#[test]
+fn window_takes_swaps_until_it_closes_and_settles_after() {
+    set_clock(5, 0);
+    let mut test = ProgramTest::new(no_fees());
+    let mut batch = batch_pool(&mut test);
+    let first = test.add_user(&batch.pool, 500_000, 0);
+    let last = test.add_user(&batch.pool, 0, 500_000);
+    let late = test.add_user(&batch.pool, 500_000, 0);
+    // the first swap opens the window, for slots 5 to 14
+    submit(&mut test, &mut batch, &first, true, 500_000, 1);
+    set_clock(5 + WINDOW_SLOTS - 1, 0);
+    submit(&mut test, &mut batch, &last, false, 500_000, 1);
+    assert_eq!(
+        test.process(&settle_instruction(&test, &batch)),
+        Err(SwapError::BatchWindowOpen.into())
+    );
+
+    set_clock(5 + WINDOW_SLOTS, 0);
+    assert_eq!(
+        test.process(&submit_instruction(&batch, &late, true, 500_000, 1)),
+        Err(SwapError::BatchWindowClosed.into())
+    );
+    // regular swaps are not taken while the pool runs batches
+    let swap = test.swap_instruction(&batch.pool, &late, true, 500_000, 1, None);
+    assert_eq!(
+        test.process(&swap),
+        Err(SwapError::BatchAuctionEnabled.into())
+    );
+    test.process(&settle_instruction(&test, &batch)).unwrap();
+    assert_eq!(test.token_amount(&first.token_b), 500_000);
+    assert_eq!(test.token_amount(&last.token_a), 500_000);
+
+    // the next swap opens a new window
+    batch.orders.clear();
+    submit(&mut test, &mut batch, &late, true, 500_000, 1);
+    let auction = BatchAuction::unpack(&test.accounts[&batch.batch].data).unwrap();
+    assert_eq!(auction.window_end_slot, 5 + 2 * WINDOW_SLOTS);
+}
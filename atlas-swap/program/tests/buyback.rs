@@ -0,0 +1,196 @@
+//! The fee owner may swap fees held in one token of a pool for the buyback
+//! token and burn what was bought, up to the epoch limit the program owner
+//! sets.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{buyback_and_burn, set_buyback_config, BuybackAndBurn, SetBuybackConfig},
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const FEES_HELD: u64 = 10_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+/// A pool buying back its token B, limited to `epoch_limit` per epoch, and
+/// the fee owner's accounts: fees held in token A, the fee account of the
+/// pool and the account bought tokens are burned from
+struct BuybackTest {
+    test: ProgramTest,
+    pool: TestPool,
+    fees_held: Pubkey,
+    fee_account: Pubkey,
+    bought: Pubkey,
+}
+
+impl BuybackTest {
+    fn new(epoch_limit: u64) -> Self {
+        let mut test = ProgramTest::new(Fees {
+            constant_product_return_fee_numerator: 25,
+            constant_product_fixed_fee_numerator: 5,
+            fee_denominator: 10_000,
+            ..Fees::default()
+        });
+        let pool = test.create_pool(
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            RESERVE,
+            RESERVE,
+        );
+        let fee_owner = test.fee_owner;
+        let fees_held = test.add_token_account(&pool.token_a_mint, &fee_owner, FEES_HELD);
+        let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+        let bought = test.add_token_account(&pool.token_b_mint, &fee_owner, 0);
+        let owner = test.owner;
+        let set_config = config(&test, &owner, &pool.token_b_mint, epoch_limit);
+        test.process(&set_config).unwrap();
+        Self {
+            test,
+            pool,
+            fees_held,
+            fee_account,
+            bought,
+        }
+    }
+
+    fn buyback(&self, fee_owner: &Pubkey, amount_in: u64) -> Instruction {
+        buyback_and_burn(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &self.test.state,
+            fee_owner,
+            &self.pool.swap,
+            &self.pool.authority,
+            &self.fees_held,
+            &self.pool.token_a,
+            &self.pool.token_b,
+            &self.bought,
+            &self.pool.pool_mint,
+            &self.fee_account,
+            &self.pool.token_b_mint,
+            &[],
+            BuybackAndBurn {
+                amount_in,
+                minimum_amount_out: 1,
+            },
+        )
+        .unwrap()
+    }
+}
+
+fn config(
+    test: &ProgramTest,
+    owner: &Pubkey,
+    buyback_mint: &Pubkey,
+    epoch_limit: u64,
+) -> Instruction {
+    set_buyback_config(
+        &atlas_swap::id(),
+        &test.state,
+        owner,
+        SetBuybackConfig {
+            buyback_mint: *buyback_mint,
+            buyback_epoch_limit: epoch_limit,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn bought_tokens_are_burned() {
+    let mut buyback_test = BuybackTest::new(FEES_HELD);
+    let fee_owner = buyback_test.test.fee_owner;
+    let buyback = buyback_test.buyback(&fee_owner, AMOUNT_IN);
+    let BuybackTest {
+        test,
+        pool,
+        fees_held,
+        bought,
+        ..
+    } = &mut buyback_test;
+    let supply = test.mint(&pool.token_b_mint).supply;
+    test.process(&buyback).unwrap();
+
+    assert_eq!(test.token_amount(fees_held), FEES_HELD - AMOUNT_IN);
+    assert_eq!(test.token_amount(bought), 0);
+    let amount_out = RESERVE - test.token_amount(&pool.token_b);
+    assert!(amount_out > 0);
+    assert_eq!(test.mint(&pool.token_b_mint).supply, supply - amount_out);
+    assert_eq!(test.global_state().buyback_epoch_amount, amount_out);
+}
+
+#[test]
+fn buybacks_stop_at_the_epoch_limit() {
+    let mut buyback_test = BuybackTest::new(AMOUNT_IN);
+    let fee_owner = buyback_test.test.fee_owner;
+    let buyback = buyback_test.buyback(&fee_owner, AMOUNT_IN / 2);
+    buyback_test.test.process(&buyback).unwrap();
+    let buyback = buyback_test.buyback(&fee_owner, AMOUNT_IN / 2 + 10_000);
+    assert_eq!(
+        buyback_test.test.process(&buyback),
+        Err(SwapError::BuybackLimitExceeded.into())
+    );
+
+    // a zero limit disables buybacks
+    let owner = buyback_test.test.owner;
+    let token_b_mint = buyback_test.pool.token_b_mint;
+    let disable = config(&buyback_test.test, &owner, &token_b_mint, 0);
+    buyback_test.test.process(&disable).unwrap();
+    let buyback = buyback_test.buyback(&fee_owner, 1_000);
+    assert_eq!(
+        buyback_test.test.process(&buyback),
+        Err(SwapError::BuybackLimitExceeded.into())
+    );
+}
+
+#[test]
+fn only_the_fee_owner_buys_back_the_configured_token() {
+    let mut buyback_test = BuybackTest::new(FEES_HELD);
+    let other = buyback_test.buyback(&Pubkey::new_unique(), AMOUNT_IN);
+    assert_eq!(
+        buyback_test.test.process(&other),
+        Err(SwapError::InvalidOwner.into())
+    );
+    let fee_owner = buyback_test.test.fee_owner;
+    let mut unsigned = buyback_test.buyback(&fee_owner, AMOUNT_IN);
+    unsigned.accounts[1].is_signer = false;
+    assert_eq!(
+        buyback_test.test.process(&unsigned),
+        Err(SwapError::InvalidSigner.into())
+    );
+    // a buyback token other than the one bought
+    let owner = buyback_test.test.owner;
+    let token_a_mint = buyback_test.pool.token_a_mint;
+    let config_a = config(&buyback_test.test, &owner, &token_a_mint, FEES_HELD);
+    buyback_test.test.process(&config_a).unwrap();
+    let buyback = buyback_test.buyback(&fee_owner, AMOUNT_IN);
+    assert_eq!(
+        buyback_test.test.process(&buyback),
+        Err(SwapError::InvalidInput.into())
+    );
+    assert_eq!(
+        buyback_test.test.token_amount(&buyback_test.fees_held),
+        FEES_HELD
+    );
+
+    let other_config = config(
+        &buyback_test.test,
+        &Pubkey::new_unique(),
+        &token_a_mint,
+        FEES_HELD,
+    );
+    assert_eq!(
+        buyback_test.test.process(&other_config),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+}
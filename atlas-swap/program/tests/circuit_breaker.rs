@@ -0,0 +1,71 @@
+//! The circuit breaker of a pool fails the trade that would move its price
+//! too far within a window, rather than letting it through and halting the
+//! trades after it.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{set_circuit_breaker, SetCircuitBreaker},
+};
+use common::{set_clock, ProgramTest};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn trade_moving_the_price_past_the_breaker_fails() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+    test.process(
+        &set_circuit_breaker(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            SetCircuitBreaker {
+                breaker_move_bps: 500,
+                breaker_window_slots: 100,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    set_clock(1, 0);
+
+    // a 2% move goes through
+    let small_swap = test.swap_instruction(&pool, &user, true, RESERVE / 100, 1, None);
+    test.process(&small_swap).unwrap();
+    let swap_v2 = test.swap_v2(&pool.swap);
+    let source_amount = test.token_amount(&user.token_a);
+
+    // a further 8% within the window does not, and leaves the pool as it was
+    let large_swap = test.swap_instruction(&pool, &user, true, RESERVE / 25, 1, None);
+    assert_eq!(
+        test.process(&large_swap),
+        Err(SwapError::CircuitBreakerPriceMove.into())
+    );
+    assert_eq!(test.token_amount(&user.token_a), source_amount);
+    assert_eq!(test.swap_v2(&pool.swap), swap_v2);
+    assert!(!swap_v2.breaker_tripped);
+
+    // trades are measured from the price a window starts at
+    set_clock(101, 0);
+    test.process(&test.swap_instruction(&pool, &user, false, RESERVE / 50, 1, None))
+        .unwrap();
+}
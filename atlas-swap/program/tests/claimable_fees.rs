@@ -0,0 +1,277 @@
+//! Pools paying out their trade fees pay each LP position the share of a fee
+//! of the pool tokens it holds at the time of the swap, and owners claim
+//! them without withdrawing their liquidity. The share of the pool tokens
+//! outside positions compounds into the reserves.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        claim_fees, decrease_position, increase_position, set_claimable_fees, DecreasePosition,
+        IncreasePosition, SetClaimableFees,
+    },
+    pda::find_lp_position,
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const POSITION: u64 = RESERVE / 4;
+const AMOUNT_IN: u64 = 1_000_000;
+
+/// An owner of pool tokens and of empty token accounts to claim fees into
+struct Owner {
+    wallet: Pubkey,
+    pool_tokens: Pubkey,
+    token_a: Pubkey,
+    token_b: Pubkey,
+    position: Pubkey,
+}
+
+/// An owner of `pool_tokens` of the pool tokens of the provider
+fn add_owner(test: &mut ProgramTest, pool: &TestPool, pool_tokens: u64) -> Owner {
+    let wallet = test.add_payer(1_000_000_000);
+    let owner_pool_tokens = test.add_token_account(&pool.pool_mint, &wallet, 0);
+    test.set_token_account(&pool.provider_pool_tokens, |account| {
+        account.amount -= pool_tokens
+    });
+    test.set_token_account(&owner_pool_tokens, |account| account.amount = pool_tokens);
+    Owner {
+        wallet,
+        pool_tokens: owner_pool_tokens,
+        token_a: test.add_token_account(&pool.token_a_mint, &wallet, 0),
+        token_b: test.add_token_account(&pool.token_b_mint, &wallet, 0),
+        position: find_lp_position(&atlas_swap::id(), &pool.swap, &wallet).0,
+    }
+}
+
+fn increase(pool: &TestPool, position_vault: &Pubkey, owner: &Owner, amount: u64) -> Instruction {
+    increase_position(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &owner.position,
+        position_vault,
+        &owner.wallet,
+        &owner.pool_tokens,
+        IncreasePosition {
+            pool_token_amount: amount,
+        },
+    )
+    .unwrap()
+}
+
+fn claim(pool: &TestPool, owner: &Owner) -> Instruction {
+    claim_fees(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &owner.position,
+        &pool.token_a,
+        &pool.token_b,
+        &owner.wallet,
+        &owner.token_a,
+        &owner.token_b,
+    )
+    .unwrap()
+}
+
+/// Swaps `AMOUNT_IN` and returns the trade fee of the swap
+fn swap(test: &mut ProgramTest, pool: &TestPool, fees: &Fees, a_to_b: bool) -> u64 {
+    let swap_v2 = test.swap_v2(&pool.swap);
+    let (reserve_in, reserve_out, trade_direction) = if a_to_b {
+        (
+            swap_v2.token_a_reserve,
+            swap_v2.token_b_reserve,
+            TradeDirection::AtoB,
+        )
+    } else {
+        (
+            swap_v2.token_b_reserve,
+            swap_v2.token_a_reserve,
+            TradeDirection::BtoA,
+        )
+    };
+    let quote = get_swap_quote(
+        reserve_in,
+        reserve_out,
+        AMOUNT_IN,
+        trade_direction,
+        fees,
+        &swap_v2.swap_curve,
+    )
+    .unwrap();
+    let user = if a_to_b {
+        test.add_user(pool, AMOUNT_IN, 0)
+    } else {
+        test.add_user(pool, 0, AMOUNT_IN)
+    };
+    let swap = test.swap_instruction(pool, &user, a_to_b, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+    quote.trade_fee as u64
+}
+
+fn fees() -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 30,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+/// A pool paying out the trade fees of its positions, and its position
+/// vault
+fn claimable_pool(test: &mut ProgramTest) -> (TestPool, Pubkey) {
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let position_vault = test.add_token_account(&pool.pool_mint, &pool.authority, 0);
+    test.process(
+        &set_claimable_fees(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            &position_vault,
+            SetClaimableFees {
+                claimable_fees: true,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    (pool, position_vault)
+}
+
+#[test]
+fn positions_share_the_fees_of_the_swaps_they_were_open_for() {
+    let fees = fees();
+    let mut test = ProgramTest::new(fees.clone());
+    let (pool, position_vault) = claimable_pool(&mut test);
+    // the two owners each hold a quarter of the supply
+    let first = add_owner(&mut test, &pool, POSITION);
+    let second = add_owner(&mut test, &pool, POSITION);
+    assert_eq!(test.mint(&pool.pool_mint).supply, 4 * POSITION);
+
+    // the first position alone earns its quarter of the fee of the first
+    // swap, both positions earn their quarter of the fee of the second
+    test.process(&increase(&pool, &position_vault, &first, POSITION))
+        .unwrap();
+    let fee_a = swap(&mut test, &pool, &fees, true);
+    test.process(&increase(&pool, &position_vault, &second, POSITION))
+        .unwrap();
+    let fee_b = swap(&mut test, &pool, &fees, false);
+    assert!(fee_a > 0 && fee_b > 0);
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.unclaimed_fee_a, fee_a / 4);
+    assert_eq!(swap_v2.unclaimed_fee_b, fee_b / 2);
+    assert_eq!(swap_v2.position_pool_tokens, 2 * POSITION);
+
+    test.process(&claim(&pool, &first)).unwrap();
+    test.process(&claim(&pool, &second)).unwrap();
+    assert_eq!(test.token_amount(&first.token_a), fee_a / 4);
+    assert_eq!(test.token_amount(&first.token_b), fee_b / 4);
+    assert_eq!(test.token_amount(&second.token_a), 0);
+    assert_eq!(test.token_amount(&second.token_b), fee_b / 4);
+    // the fees paid out leave the reserves as they were
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(
+        swap_v2.token_b_reserve + swap_v2.unclaimed_fee_b,
+        test.token_amount(&pool.token_b)
+    );
+
+    // pool tokens taken out of a position stop earning fees
+    let first_a = test.token_amount(&first.token_a);
+    let decrease = |amount| {
+        decrease_position(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &first.position,
+            &position_vault,
+            &first.wallet,
+            &first.pool_tokens,
+            DecreasePosition {
+                pool_token_amount: amount,
+            },
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        test.process(&decrease(POSITION + 1)),
+        Err(SwapError::InsufficientPositionTokens.into())
+    );
+    test.process(&decrease(POSITION)).unwrap();
+    assert_eq!(test.token_amount(&first.pool_tokens), POSITION);
+    let fee_a = swap(&mut test, &pool, &fees, true);
+    test.process(&claim(&pool, &first)).unwrap();
+    test.process(&claim(&pool, &second)).unwrap();
+    assert_eq!(test.token_amount(&first.token_a), first_a);
+    assert_eq!(test.token_amount(&second.token_a), fee_a / 4);
+}
+
+#[test]
+fn positions_need_claimable_fees() {
+    let mut test = ProgramTest::new(fees());
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let position_vault = test.add_token_account(&pool.pool_mint, &pool.authority, 0);
+    let owner = add_owner(&mut test, &pool, POSITION);
+    assert_eq!(
+        test.process(&increase(&pool, &position_vault, &owner, POSITION)),
+        Err(SwapError::ClaimableFeesDisabled.into())
+    );
+}
+
+#[test]
+fn pool_tokens_outside_positions_keep_their_share_of_the_fees() {
+    let fees = fees();
+    let mut test = ProgramTest::new(fees.clone());
+    let (pool, position_vault) = claimable_pool(&mut test);
+    let owner = add_owner(&mut test, &pool, RESERVE / 4);
+    test.process(&increase(&pool, &position_vault, &owner, RESERVE / 4))
+        .unwrap();
+
+    let quote = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &test.swap_v2(&pool.swap).swap_curve,
+    )
+    .unwrap();
+    let fee_a = swap(&mut test, &pool, &fees, true);
+    // the position earns its quarter of the fee, the three quarters of the
+    // provider compound into the reserves
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.unclaimed_fee_a, fee_a / 4);
+    assert_eq!(
+        swap_v2.token_a_reserve as u128,
+        quote.new_swap_source_amount - (fee_a / 4) as u128
+    );
+    test.process(&claim(&pool, &owner)).unwrap();
+    assert_eq!(test.token_amount(&owner.token_a), fee_a / 4);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+}
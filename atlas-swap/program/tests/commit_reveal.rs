@@ -0,0 +1,124 @@
+//! A committed swap is revealed in a later slot, only with the terms it was
+//! committed to, and the commitment rent goes back to the trader.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{commit_swap, reveal_swap, CommitSwap, RevealSwap},
+    pda::find_swap_commitment,
+    state::SwapCommitment,
+};
+use common::{set_clock, ProgramTest, TestAccount};
+use solana_program::{program_error::ProgramError, program_pack::Pack, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+const MINIMUM_AMOUNT_OUT: u64 = 900_000;
+const SALT: [u8; 32] = [7; 32];
+
+#[test]
+fn reveal_swaps_the_committed_terms_in_a_later_slot() {
+    let fees = Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(swap_curve.clone(), RESERVE, RESERVE);
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    test.accounts.insert(
+        user.wallet,
+        TestAccount {
+            owner: system_program::id(),
+            lamports: 1_000_000_000,
+            data: vec![],
+        },
+    );
+    let commitment = find_swap_commitment(&atlas_swap::id(), &pool.swap, &user.wallet).0;
+    let hash = SwapCommitment::hash(
+        AMOUNT_IN,
+        MINIMUM_AMOUNT_OUT,
+        &user.token_a,
+        &pool.token_a,
+        &user.token_b,
+        &SALT,
+    );
+    set_clock(10, 0);
+    test.process(
+        &commit_swap(
+            &atlas_swap::id(),
+            &pool.swap,
+            &user.wallet,
+            &commitment,
+            CommitSwap { hash },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let state = SwapCommitment::unpack(&test.accounts[&commitment].data).unwrap();
+    assert_eq!(state.owner, user.wallet);
+    assert_eq!(state.commit_slot, 10);
+
+    let reveal = |minimum_amount_out| {
+        reveal_swap(
+            &commitment,
+            test.swap_instruction(&pool, &user, true, AMOUNT_IN, minimum_amount_out, None),
+            RevealSwap {
+                amount_in: AMOUNT_IN,
+                minimum_amount_out,
+                salt: SALT,
+            },
+        )
+        .unwrap()
+    };
+    let committed = reveal(MINIMUM_AMOUNT_OUT);
+    let loosened = reveal(1);
+    assert_eq!(
+        test.process(&committed),
+        Err(SwapError::RevealTooEarly.into())
+    );
+
+    set_clock(11, 0);
+    assert_eq!(
+        test.process(&loosened),
+        Err(SwapError::InvalidCommitment.into())
+    );
+    let lamports = test.accounts[&user.wallet].lamports;
+    let rent = test.accounts[&commitment].lamports;
+    test.process(&committed).unwrap();
+    let quote = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    assert_eq!(
+        test.token_amount(&user.token_a),
+        AMOUNT_IN - quote.source_amount_swapped as u64
+    );
+    assert_eq!(
+        test.token_amount(&user.token_b),
+        quote.destination_amount_swapped as u64
+    );
+    assert_eq!(test.accounts[&commitment].lamports, 0);
+    assert_eq!(test.accounts[&user.wallet].lamports, lamports + rent);
+
+    // the commitment is spent
+    assert_eq!(
+        test.process(&committed),
+        Err(ProgramError::UninitializedAccount)
+    );
+}
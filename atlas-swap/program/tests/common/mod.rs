@@ -0,0 +1,729 @@
+//! Harness of the processor tests: accounts kept by key and handed to the
+//! program laid out as the runtime serializes them, so reallocations and
+//! assignments behave as on chain, with the token and system programs
+//! served through the invoke syscall, and the withdrawals of spl-token-swap
+//! pools by a stand-in.
+
+#![allow(dead_code)]
+
+use atlas_swap::{
+    constraints::SPL_TOKEN_SWAP_PROGRAM_ID,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    instruction::{
+        deposit_all_token_types, initialize, swap, DepositAllTokenTypes, Swap, SwapArgs,
+    },
+    pda::find_global_state,
+    processor::Processor,
+    state::{GlobalState, GlobalStateVersion, SwapV2, SwapVersion},
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{self, ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program, sysvar,
+};
+use spl_token::state::{Account, AccountState, Mint};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    convert::TryInto,
+    mem::size_of,
+};
+
+thread_local! {
+    static CLOCK: Cell<(u64, i64)> = const { Cell::new((0, 0)) };
+    static RETURN_DATA: RefCell<Option<(Pubkey, Vec<u8>)>> = const { RefCell::new(None) };
+    static TRANSFER_FEES: RefCell<HashMap<Pubkey, u64>> = RefCell::new(HashMap::new());
+}
+
+/// Sets the slot and unix timestamp of the clock the program reads
+pub fn set_clock(slot: u64, unix_timestamp: i64) {
+    CLOCK.with(|clock| clock.set((slot, unix_timestamp)));
+}
+
+/// Makes the token program take a cut of `fee_bps` of each transfer of
+/// `mint` out of the amount received, like a fee-on-transfer token
+pub fn set_transfer_fee(mint: &Pubkey, fee_bps: u64) {
+    TRANSFER_FEES.with(|fees| fees.borrow_mut().insert(*mint, fee_bps));
+}
+
+/// Data the last instruction set as its return data
+pub fn return_data() -> Option<Vec<u8>> {
+    RETURN_DATA.with(|return_data| return_data.borrow().as_ref().map(|(_, data)| data.clone()))
+}
+
+struct TestSyscallStubs {}
+impl program_stubs::SyscallStubs for TestSyscallStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &atlas_swap::id()).unwrap())
+            .collect::<Vec<_>>();
+        let mut new_account_infos = vec![];
+        for meta in instruction.accounts.iter() {
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| *account_info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let mut new_account_info = account_info.clone();
+            if signers.contains(account_info.key) {
+                new_account_info.is_signer = true;
+            }
+            if meta.is_signer && !new_account_info.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            new_account_infos.push(new_account_info);
+        }
+        if instruction.program_id == spl_token::id() {
+            spl_token::processor::Processor::process(
+                &instruction.program_id,
+                &new_account_infos,
+                &instruction.data,
+            )?;
+            take_transfer_fee(&new_account_infos, &instruction.data)
+        } else if instruction.program_id == system_program::id() {
+            process_system_instruction(&new_account_infos, &instruction.data)
+        } else if instruction.program_id == SPL_TOKEN_SWAP_PROGRAM_ID {
+            process_spl_token_swap_withdrawal(&new_account_infos, &instruction.data)
+        } else {
+            Err(ProgramError::IncorrectProgramId)
+        }
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let (slot, unix_timestamp) = CLOCK.with(Cell::get);
+        unsafe {
+            *(var_addr as *mut Clock) = Clock {
+                slot,
+                unix_timestamp,
+                ..Clock::default()
+            };
+        }
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        SUCCESS
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        RETURN_DATA.with(|return_data| {
+            *return_data.borrow_mut() = Some((atlas_swap::id(), data.to_vec()));
+        });
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        RETURN_DATA.with(|return_data| return_data.borrow().clone())
+    }
+}
+
+/// Takes the cut of a fee-on-transfer mint out of the destination of a
+/// token transfer
+fn take_transfer_fee(account_infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let destination_info = match data.first() {
+        // Transfer
+        Some(3) => &account_infos[1],
+        // TransferChecked
+        Some(12) => &account_infos[2],
+        _ => return Ok(()),
+    };
+    let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let mut destination = Account::unpack(&destination_info.data.borrow())?;
+    let fee_bps = TRANSFER_FEES.with(|fees| fees.borrow().get(&destination.mint).copied());
+    if let Some(fee_bps) = fee_bps {
+        destination.amount -= amount * fee_bps / 10_000;
+        Account::pack(destination, &mut destination_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// The transfers, allocations and assignments the program asks of the
+/// system program
+fn process_system_instruction(account_infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (tag, rest) = data.split_at(4);
+    match u32::from_le_bytes(tag.try_into().unwrap()) {
+        // Assign
+        1 => {
+            account_infos[0].assign(&Pubkey::new_from_array(rest[..32].try_into().unwrap()));
+            Ok(())
+        }
+        // Transfer
+        2 => {
+            let lamports = u64::from_le_bytes(rest[..8].try_into().unwrap());
+            let mut from = account_infos[0].lamports.borrow_mut();
+            **from = from
+                .checked_sub(lamports)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            **account_infos[1].lamports.borrow_mut() += lamports;
+            Ok(())
+        }
+        // Allocate
+        8 => {
+            let space = u64::from_le_bytes(rest[..8].try_into().unwrap());
+            account_infos[0].realloc(space as usize, true)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// A WithdrawAllTokenTypes of an spl-token-swap pool without withdrawal
+/// fees: the pool tokens are burnt and both vaults pay out their share of
+/// the supply
+fn process_spl_token_swap_withdrawal(account_infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.first() != Some(&3) {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let pool_token_amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let (authority_info, user_transfer_authority_info, pool_mint_info, source_info) = (
+        &account_infos[1],
+        &account_infos[2],
+        &account_infos[3],
+        &account_infos[4],
+    );
+    let supply = Mint::unpack(&pool_mint_info.data.borrow())?.supply;
+    let burn = spl_token::instruction::burn(
+        &spl_token::id(),
+        source_info.key,
+        pool_mint_info.key,
+        user_transfer_authority_info.key,
+        &[],
+        pool_token_amount,
+    )?;
+    spl_token::processor::Processor::process(
+        &spl_token::id(),
+        &[
+            source_info.clone(),
+            pool_mint_info.clone(),
+            user_transfer_authority_info.clone(),
+        ],
+        &burn.data,
+    )?;
+
+    let mut authority_info = authority_info.clone();
+    authority_info.is_signer = true;
+    for (vault_info, destination_info) in [
+        (&account_infos[5], &account_infos[7]),
+        (&account_infos[6], &account_infos[8]),
+    ] {
+        let reserve = Account::unpack(&vault_info.data.borrow())?.amount;
+        let amount = (reserve as u128 * pool_token_amount as u128 / supply as u128) as u64;
+        let transfer = spl_token::instruction::transfer(
+            &spl_token::id(),
+            vault_info.key,
+            destination_info.key,
+            authority_info.key,
+            &[],
+            amount,
+        )?;
+        spl_token::processor::Processor::process(
+            &spl_token::id(),
+            &[
+                vault_info.clone(),
+                destination_info.clone(),
+                authority_info.clone(),
+            ],
+            &transfer.data,
+        )?;
+    }
+    Ok(())
+}
+
+fn test_syscall_stubs() {
+    use std::sync::Once;
+    static ONCE: Once = Once::new();
+
+    ONCE.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(TestSyscallStubs {}));
+    });
+}
+
+pub struct TestAccount {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+/// Processes instructions against a set of accounts, a global state owned
+/// by `owner` already created and `owner` holding lamports to pay for
+/// new accounts
+pub struct ProgramTest {
+    pub owner: Pubkey,
+    pub fee_owner: Pubkey,
+    pub state: Pubkey,
+    pub accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl ProgramTest {
+    /// A program whose global state charges `fees`
+    pub fn new(fees: Fees) -> Self {
+        let mut test = Self {
+            owner: Pubkey::new_unique(),
+            fee_owner: Pubkey::new_unique(),
+            state: find_global_state(&atlas_swap::id()).0,
+            accounts: HashMap::new(),
+        };
+        let mut state_data = vec![0; GlobalStateVersion::LATEST_LEN];
+        GlobalStateVersion::pack(
+            &GlobalState {
+                is_initialized: true,
+                owner: test.owner,
+                fee_owner: test.fee_owner,
+                initial_supply: 1_000_000_000,
+                lp_decimals: 6,
+                fees,
+                ..GlobalState::default()
+            },
+            &mut state_data,
+        )
+        .unwrap();
+        test.add(test.state, atlas_swap::id(), state_data);
+        let mut rent_data = vec![0; 17];
+        let rent = Rent::default();
+        rent_data[..8].copy_from_slice(&rent.lamports_per_byte_year.to_le_bytes());
+        rent_data[8..16].copy_from_slice(&rent.exemption_threshold.to_le_bytes());
+        rent_data[16] = rent.burn_percent;
+        test.add(sysvar::rent::id(), sysvar::id(), rent_data);
+        // the owner pays for the program accounts it creates
+        test.accounts.insert(
+            test.owner,
+            TestAccount {
+                owner: system_program::id(),
+                lamports: 1_000_000_000,
+                data: vec![],
+            },
+        );
+        test
+    }
+
+    pub fn add(&mut self, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let lamports = Rent::default().minimum_balance(data.len()).max(1);
+        self.accounts.insert(
+            key,
+            TestAccount {
+                owner,
+                lamports,
+                data,
+            },
+        );
+    }
+
+    /// A system account holding `lamports`, to pay for new accounts
+    pub fn add_payer(&mut self, lamports: u64) -> Pubkey {
+        let payer = Pubkey::new_unique();
+        self.accounts.insert(
+            payer,
+            TestAccount {
+                owner: system_program::id(),
+                lamports,
+                data: vec![],
+            },
+        );
+        payer
+    }
+
+    pub fn add_mint(&mut self, mint_authority: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(*mint_authority),
+                decimals,
+                is_initialized: true,
+                ..Mint::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add(mint, spl_token::id(), data);
+        mint
+    }
+
+    /// A token account of `mint` holding `amount`, the mint supply growing
+    /// by as much
+    pub fn add_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        let mut data = vec![0; Account::LEN];
+        Account::pack(
+            Account {
+                mint: *mint,
+                owner: *owner,
+                amount,
+                state: AccountState::Initialized,
+                ..Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add(key, spl_token::id(), data);
+        let mut mint_state = self.mint(mint);
+        mint_state.supply += amount;
+        Mint::pack(mint_state, &mut self.accounts.get_mut(mint).unwrap().data).unwrap();
+        key
+    }
+
+    pub fn token_account(&self, key: &Pubkey) -> Account {
+        Account::unpack(&self.accounts[key].data).unwrap()
+    }
+
+    pub fn token_amount(&self, key: &Pubkey) -> u64 {
+        self.token_account(key).amount
+    }
+
+    /// Rewrites the token account at `key`
+    pub fn set_token_account(&mut self, key: &Pubkey, update: impl FnOnce(&mut Account)) {
+        let mut account = self.token_account(key);
+        update(&mut account);
+        Account::pack(account, &mut self.accounts.get_mut(key).unwrap().data).unwrap();
+    }
+
+    pub fn mint(&self, key: &Pubkey) -> Mint {
+        Mint::unpack(&self.accounts[key].data).unwrap()
+    }
+
+    /// Rewrites the mint at `key`
+    pub fn set_mint(&mut self, key: &Pubkey, update: impl FnOnce(&mut Mint)) {
+        let mut mint = self.mint(key);
+        update(&mut mint);
+        Mint::pack(mint, &mut self.accounts.get_mut(key).unwrap().data).unwrap();
+    }
+
+    pub fn global_state(&self) -> GlobalState {
+        GlobalStateVersion::unpack(&self.accounts[&self.state].data).unwrap()
+    }
+
+    pub fn set_global_state(&mut self, update: impl FnOnce(&mut GlobalState)) {
+        let mut state = self.global_state();
+        update(&mut state);
+        GlobalStateVersion::pack(
+            &state,
+            &mut self.accounts.get_mut(&self.state).unwrap().data,
+        )
+        .unwrap();
+    }
+
+    pub fn swap_v2(&self, swap: &Pubkey) -> SwapV2 {
+        match SwapVersion::unpack_version(&self.accounts[swap].data).unwrap() {
+            SwapVersion::SwapV2(swap_v2) => swap_v2,
+            _ => panic!("not a SwapV2 pool"),
+        }
+    }
+
+    /// Rewrites the pool at `swap`, for settings with no instruction of
+    /// their own under test
+    pub fn set_swap_v2(&mut self, swap: &Pubkey, update: impl FnOnce(&mut SwapV2)) {
+        let mut swap_v2 = self.swap_v2(swap);
+        update(&mut swap_v2);
+        SwapVersion::pack(
+            SwapVersion::SwapV2(swap_v2),
+            &mut self.accounts.get_mut(swap).unwrap().data,
+        )
+        .unwrap();
+    }
+
+    /// Runs an instruction of the swap program, the accounts it writes
+    /// being kept only when it succeeds. Accounts missing from the test are
+    /// empty system accounts, such as the program ids.
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        self.process_with(instruction, Processor::process)
+    }
+
+    /// Runs an instruction through `process_instruction`, as `process` does
+    pub fn process_with(
+        &mut self,
+        instruction: &Instruction,
+        process_instruction: fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult,
+    ) -> ProgramResult {
+        test_syscall_stubs();
+        RETURN_DATA.with(|return_data| *return_data.borrow_mut() = None);
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert(TestAccount {
+                owner: system_program::id(),
+                lamports: 0,
+                data: vec![],
+            });
+        }
+        let mut input = self.serialize(instruction);
+        let (program_id, account_infos, data) =
+            unsafe { entrypoint::deserialize(input.as_mut_ptr() as *mut u8) };
+        let result = process_instruction(program_id, &account_infos, data);
+        if result.is_ok() {
+            for account_info in account_infos.iter() {
+                let account = self.accounts.get_mut(account_info.key).unwrap();
+                account.owner = *account_info.owner;
+                account.lamports = account_info.lamports();
+                account.data = account_info.data.borrow().to_vec();
+            }
+        }
+        result
+    }
+
+    /// The input of the program entrypoint for `instruction`, in words so
+    /// its fields are aligned
+    fn serialize(&self, instruction: &Instruction) -> Vec<u64> {
+        let mut input = vec![];
+        input.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+        for (index, meta) in instruction.accounts.iter().enumerate() {
+            if let Some(position) = instruction.accounts[..index]
+                .iter()
+                .position(|other| other.pubkey == meta.pubkey)
+            {
+                input.push(position as u8);
+                input.extend_from_slice(&[0; 7]);
+                continue;
+            }
+            let account = &self.accounts[&meta.pubkey];
+            input.push(u8::MAX);
+            input.push(meta.is_signer as u8);
+            input.push(meta.is_writable as u8);
+            input.push(0);
+            input.extend_from_slice(&[0; 4]);
+            input.extend_from_slice(meta.pubkey.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().div_ceil(8) * 8, 0);
+            input.extend_from_slice(&0u64.to_le_bytes());
+        }
+        input.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        input.extend_from_slice(&instruction.data);
+        input.extend_from_slice(instruction.program_id.as_ref());
+
+        let mut words = vec![0u64; input.len().div_ceil(8)];
+        for (word, bytes) in words.iter_mut().zip(input.chunks(size_of::<u64>())) {
+            let mut le_bytes = [0; 8];
+            le_bytes[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(le_bytes);
+        }
+        words
+    }
+}
+
+/// A pool initialized through the program, the wallet of its first
+/// liquidity provider and the account locking MIN_LP_SUPPLY
+pub struct TestPool {
+    pub swap: Pubkey,
+    pub authority: Pubkey,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub pool_mint: Pubkey,
+    pub provider: Pubkey,
+    pub provider_pool_tokens: Pubkey,
+    pub dead_shares: Pubkey,
+}
+
+/// A trader holding `amount_a` and `amount_b` of the pool tokens, no pool
+/// tokens, and lamports to pay for the accounts its trades create
+pub struct TestUser {
+    pub wallet: Pubkey,
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub pool_tokens: Pubkey,
+}
+
+impl ProgramTest {
+    /// Initializes a pool of `swap_curve` over reserves of `reserve_a` and
+    /// `reserve_b`, tokens of 6 decimals
+    pub fn create_pool(
+        &mut self,
+        swap_curve: SwapCurve,
+        reserve_a: u64,
+        reserve_b: u64,
+    ) -> TestPool {
+        let token_a_mint = self.add_mint(&Pubkey::new_unique(), 6);
+        let token_b_mint = self.add_mint(&Pubkey::new_unique(), 6);
+        self.create_pool_of(
+            swap_curve,
+            &token_a_mint,
+            &token_b_mint,
+            reserve_a,
+            reserve_b,
+        )
+    }
+
+    /// Initializes a pool of `swap_curve` over reserves of `reserve_a` of
+    /// `token_a_mint` and `reserve_b` of `token_b_mint`
+    pub fn create_pool_of(
+        &mut self,
+        swap_curve: SwapCurve,
+        token_a_mint: &Pubkey,
+        token_b_mint: &Pubkey,
+        reserve_a: u64,
+        reserve_b: u64,
+    ) -> TestPool {
+        let program_id = atlas_swap::id();
+        let swap = Pubkey::new_unique();
+        let authority = Pubkey::find_program_address(&[swap.as_ref()], &program_id).0;
+        let provider = Pubkey::new_unique();
+        let (token_a_mint, token_b_mint) = (*token_a_mint, *token_b_mint);
+        let pool_mint = self.add_mint(&authority, 6);
+        let token_a = self.add_token_account(&token_a_mint, &authority, reserve_a);
+        let token_b = self.add_token_account(&token_b_mint, &authority, reserve_b);
+        let provider_pool_tokens = self.add_token_account(&pool_mint, &provider, 0);
+        let dead_shares = self.add_token_account(&pool_mint, &authority, 0);
+        self.add(swap, program_id, vec![0; SwapVersion::LATEST_LEN]);
+        self.process(
+            &initialize(
+                &program_id,
+                &spl_token::id(),
+                &swap,
+                &authority,
+                &self.state,
+                &token_a,
+                &token_b,
+                &pool_mint,
+                &provider_pool_tokens,
+                &token_a_mint,
+                &token_b_mint,
+                &dead_shares,
+                swap_curve,
+                None,
+                None,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        TestPool {
+            swap,
+            authority,
+            token_a,
+            token_b,
+            token_a_mint,
+            token_b_mint,
+            pool_mint,
+            provider,
+            provider_pool_tokens,
+            dead_shares,
+        }
+    }
+
+    /// A program charging no fees, and a constant product pool over
+    /// `reserve` of each token
+    pub fn constant_product_pool(reserve: u64) -> (Self, TestPool) {
+        let mut test = Self::new(Fees {
+            fee_denominator: 10_000,
+            ..Fees::default()
+        });
+        let pool = test.create_pool(
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            reserve,
+            reserve,
+        );
+        (test, pool)
+    }
+
+    pub fn add_user(&mut self, pool: &TestPool, amount_a: u64, amount_b: u64) -> TestUser {
+        let wallet = self.add_payer(1_000_000_000);
+        TestUser {
+            wallet,
+            token_a: self.add_token_account(&pool.token_a_mint, &wallet, amount_a),
+            token_b: self.add_token_account(&pool.token_b_mint, &wallet, amount_b),
+            pool_tokens: self.add_token_account(&pool.pool_mint, &wallet, 0),
+        }
+    }
+
+    /// A swap of `amount_in` from token A to token B, or the other way,
+    /// paying the owner fee to `fee_account`
+    pub fn swap_instruction(
+        &self,
+        pool: &TestPool,
+        user: &TestUser,
+        a_to_b: bool,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        fee_account: Option<&Pubkey>,
+    ) -> Instruction {
+        let (source, swap_source, swap_destination, destination) = if a_to_b {
+            (&user.token_a, &pool.token_a, &pool.token_b, &user.token_b)
+        } else {
+            (&user.token_b, &pool.token_b, &pool.token_a, &user.token_a)
+        };
+        swap(
+            &atlas_swap::id(),
+            SwapArgs {
+                token_program_id: spl_token::id(),
+                swap_pubkey: pool.swap,
+                authority_pubkey: pool.authority,
+                user_transfer_authority_pubkey: user.wallet,
+                global_state_pubkey: self.state,
+                source_pubkey: *source,
+                swap_source_pubkey: *swap_source,
+                swap_destination_pubkey: *swap_destination,
+                destination_pubkey: *destination,
+                pool_mint_pubkey: pool.pool_mint,
+                pool_fee_pubkey: fee_account.copied(),
+                ..SwapArgs::default()
+            },
+            Swap {
+                amount_in,
+                minimum_amount_out,
+                max_price_impact_bps: None,
+                tip: 0,
+                memo: Vec::new(),
+                trade_direction: None,
+                fee_discount: false,
+            },
+        )
+        .unwrap()
+    }
+
+    /// A deposit of both tokens for `pool_token_amount`, without a gate or
+    /// deposit record
+    pub fn deposit_instruction(
+        &self,
+        pool: &TestPool,
+        user: &TestUser,
+        pool_token_amount: u64,
+        maximum_token_a_amount: u64,
+        maximum_token_b_amount: u64,
+    ) -> Instruction {
+        deposit_all_token_types(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &self.state,
+            &user.wallet,
+            &user.token_a,
+            &user.token_b,
+            &pool.token_a,
+            &pool.token_b,
+            &pool.pool_mint,
+            &user.pool_tokens,
+            None,
+            None,
+            DepositAllTokenTypes {
+                pool_token_amount,
+                maximum_token_a_amount,
+                maximum_token_b_amount,
+                max_ratio_deviation_bps: None,
+            },
+        )
+        .unwrap()
+    }
+}
@@ -0,0 +1,237 @@
+//! Recurring orders swap one tick at most per interval under the rules of a
+//! regular swap, paying the keeper of each tick, and their owner closes them
+//! for what is left of the escrow.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{close_dca, create_dca, execute_dca_tick, CreateDca},
+    pda::find_dca_order,
+};
+use common::{set_clock, ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE_A: u64 = 1_000_000_000;
+const RESERVE_B: u64 = 1_000_000;
+const AMOUNT_PER_TICK: u64 = 1_500_000;
+const KEEPER_FEE: u64 = 10;
+const INTERVAL: i64 = 60;
+
+/// A recurring order escrowing `ticks` ticks of token A, its owner,
+/// source, destination, position and vault
+fn create_position(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    ticks: u64,
+    minimum_amount_out_per_tick: u64,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey, Pubkey) {
+    let amount = ticks * (AMOUNT_PER_TICK + KEEPER_FEE);
+    let owner = test.add_payer(1_000_000_000);
+    let source = test.add_token_account(&pool.token_a_mint, &owner, amount);
+    let destination = test.add_token_account(&pool.token_b_mint, &owner, 0);
+    let position = find_dca_order(&atlas_swap::id(), &pool.swap, &owner, 0).0;
+    let position_vault = test.add_token_account(&pool.token_a_mint, &position, 0);
+    test.process(
+        &create_dca(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &position,
+            &position_vault,
+            &owner,
+            &source,
+            &destination,
+            CreateDca {
+                dca_id: 0,
+                amount,
+                amount_per_tick: AMOUNT_PER_TICK,
+                minimum_amount_out_per_tick,
+                keeper_fee: KEEPER_FEE,
+                interval: INTERVAL,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    (owner, source, destination, position, position_vault)
+}
+
+fn tick_instruction(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    position: &Pubkey,
+    position_vault: &Pubkey,
+    destination: &Pubkey,
+    keeper: &Pubkey,
+) -> Instruction {
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    execute_dca_tick(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        position,
+        position_vault,
+        &pool.token_a,
+        &pool.token_b,
+        destination,
+        &pool.pool_mint,
+        &fee_account,
+        keeper,
+        None,
+        None,
+        &[],
+    )
+    .unwrap()
+}
+
+#[test]
+fn ticks_wait_for_the_interval() {
+    let fees = Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(swap_curve.clone(), RESERVE_A, RESERVE_B);
+    set_clock(0, 1_000);
+    let (owner, source, destination, position, position_vault) =
+        create_position(&mut test, &pool, 2, 1);
+    assert_eq!(test.token_amount(&source), 0);
+
+    let keeper = test.add_token_account(&pool.token_a_mint, &Pubkey::new_unique(), 0);
+    let tick = tick_instruction(
+        &mut test,
+        &pool,
+        &position,
+        &position_vault,
+        &destination,
+        &keeper,
+    );
+    test.process(&tick).unwrap();
+    let quote = get_swap_quote(
+        RESERVE_A,
+        RESERVE_B,
+        AMOUNT_PER_TICK,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    assert_eq!(
+        test.token_amount(&destination),
+        quote.destination_amount_swapped as u64
+    );
+    assert_eq!(test.token_amount(&keeper), KEEPER_FEE);
+    assert_eq!(
+        test.token_amount(&position_vault),
+        AMOUNT_PER_TICK + KEEPER_FEE + AMOUNT_PER_TICK - quote.source_amount_swapped as u64
+    );
+
+    set_clock(0, 1_000 + INTERVAL - 1);
+    assert_eq!(test.process(&tick), Err(SwapError::DcaTickTooEarly.into()));
+    set_clock(0, 1_000 + INTERVAL);
+    test.process(&tick).unwrap();
+    assert!(test.token_amount(&destination) > quote.destination_amount_swapped as u64);
+    assert_eq!(test.token_amount(&keeper), 2 * KEEPER_FEE);
+
+    // closing the position refunds the input the ticks rounded away
+    let remaining = test.token_amount(&position_vault);
+    test.process(
+        &close_dca(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &position,
+            &position_vault,
+            &owner,
+            &source,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(test.token_amount(&source), remaining);
+    assert_eq!(test.accounts[&position].lamports, 0);
+    assert_eq!(test.accounts[&position_vault].lamports, 0);
+}
+
+#[test]
+fn ticks_respect_the_minimum_amount_out() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE_A,
+        RESERVE_B,
+    );
+    set_clock(0, 1_000);
+    let (owner, source, destination, position, position_vault) =
+        create_position(&mut test, &pool, 1, RESERVE_B);
+
+    let keeper = test.add_token_account(&pool.token_a_mint, &Pubkey::new_unique(), 0);
+    let tick = tick_instruction(
+        &mut test,
+        &pool,
+        &position,
+        &position_vault,
+        &destination,
+        &keeper,
+    );
+    assert_eq!(test.process(&tick), Err(SwapError::ExceededSlippage.into()));
+    assert_eq!(
+        test.token_amount(&position_vault),
+        AMOUNT_PER_TICK + KEEPER_FEE
+    );
+
+    // only the owner closes the position
+    let intruder = test.add_payer(0);
+    assert_eq!(
+        test.process(
+            &close_dca(
+                &atlas_swap::id(),
+                &spl_token::id(),
+                &pool.swap,
+                &position,
+                &position_vault,
+                &intruder,
+                &source,
+                None,
+            )
+            .unwrap()
+        ),
+        Err(SwapError::InvalidOwner.into())
+    );
+    test.process(
+        &close_dca(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &position,
+            &position_vault,
+            &owner,
+            &source,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(test.token_amount(&source), AMOUNT_PER_TICK + KEEPER_FEE);
+}
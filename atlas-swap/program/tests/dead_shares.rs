@@ -0,0 +1,253 @@
+//! Initialize, and a deposit into a pool emptied before Initialize did so,
+//! lock MIN_LP_SUPPLY of the first pool tokens with the swap authority, and
+//! deposits are only taken at the price of the reserves.
+
+mod common;
+
+use atlas_swap::{
+    constraints::MIN_LP_SUPPLY,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        deposit_all_token_types, initialize, withdraw_all_token_types, DepositAllTokenTypes,
+        WithdrawAllTokenTypes,
+    },
+    state::SwapVersion,
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{
+    instruction::AccountMeta, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use spl_token::state::Mint;
+
+const RESERVE_A: u64 = 4_000_000_000;
+const RESERVE_B: u64 = 1_000_000_000;
+
+fn pool(test: &mut ProgramTest) -> TestPool {
+    test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE_A,
+        RESERVE_B,
+    )
+}
+
+#[test]
+fn initialize_locks_dead_shares_with_the_swap_authority() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = pool(&mut test);
+    assert_eq!(test.mint(&pool.pool_mint).supply, 2_000_000_000);
+    assert_eq!(test.token_amount(&pool.dead_shares), MIN_LP_SUPPLY as u64);
+    assert_eq!(
+        test.token_amount(&pool.provider_pool_tokens),
+        2_000_000_000 - MIN_LP_SUPPLY as u64
+    );
+
+    // the locked pool tokens must stay out of reach of the provider
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &atlas_swap::id()).0;
+    let token_a = test.add_token_account(&pool.token_a_mint, &authority, RESERVE_A);
+    let token_b = test.add_token_account(&pool.token_b_mint, &authority, RESERVE_B);
+    let pool_mint = test.add_mint(&authority, 6);
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &pool.provider, 0);
+    test.add(swap, atlas_swap::id(), vec![0; SwapVersion::LATEST_LEN]);
+    let init = |dead_shares: &Pubkey| {
+        initialize(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &swap,
+            &authority,
+            &test.state,
+            &token_a,
+            &token_b,
+            &pool_mint,
+            &provider_pool_tokens,
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            dead_shares,
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            None,
+            None,
+        )
+        .unwrap()
+    };
+    let provider_owned = init(&provider_pool_tokens);
+    let other_mint = init(&pool.dead_shares);
+    assert_eq!(
+        test.process(&provider_owned),
+        Err(SwapError::IncorrectPoolMint.into())
+    );
+    assert_eq!(
+        test.process(&other_mint),
+        Err(SwapError::IncorrectPoolMint.into())
+    );
+}
+
+#[test]
+fn the_last_withdrawal_leaves_the_dead_shares_backed() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    // reserves too small for their mean, minting the legacy initial supply
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        15_000,
+        25_000,
+    );
+    let provider_pool_tokens = test.token_amount(&pool.provider_pool_tokens);
+    let user = test.add_user(&pool, 0, 0);
+    // all of the pool tokens of the provider, which leaves 2 of token A,
+    // swept but for one unit, and 3 of token B, kept
+    let withdraw = withdraw_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        &pool.provider,
+        &pool.pool_mint,
+        &pool.provider_pool_tokens,
+        &pool.token_a,
+        &pool.token_b,
+        &user.token_a,
+        &user.token_b,
+        None,
+        None,
+        WithdrawAllTokenTypes {
+            pool_token_amount: provider_pool_tokens,
+            minimum_token_a_amount: 0,
+            minimum_token_b_amount: 0,
+        },
+    )
+    .unwrap();
+    test.process(&withdraw).unwrap();
+    assert_eq!(test.mint(&pool.pool_mint).supply, MIN_LP_SUPPLY as u64);
+    assert_eq!(test.token_amount(&user.token_a), 14_999);
+    assert_eq!(test.token_amount(&user.token_b), 24_997);
+    assert_eq!(test.token_amount(&pool.token_a), 1);
+    assert_eq!(test.token_amount(&pool.token_b), 3);
+
+    // the pool still takes deposits at the price of what is left
+    let depositor = test.add_user(&pool, 1, 3);
+    let deposit = test.deposit_instruction(&pool, &depositor, MIN_LP_SUPPLY as u64, 1, 3);
+    test.process(&deposit).unwrap();
+    assert_eq!(
+        test.token_amount(&depositor.pool_tokens),
+        MIN_LP_SUPPLY as u64
+    );
+    assert_eq!(test.token_amount(&pool.token_a), 2);
+    assert_eq!(test.token_amount(&pool.token_b), 6);
+}
+
+#[test]
+fn deposit_into_an_emptied_pool_locks_dead_shares() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = pool(&mut test);
+    // a pool initialized before Initialize locked the minimum supply,
+    // emptied by its provider burning its pool tokens through the token
+    // program, its vaults holding a donation
+    test.set_token_account(&pool.dead_shares, |account| account.amount = 0);
+    let mut pool_mint = test.mint(&pool.pool_mint);
+    pool_mint.supply = 0;
+    Mint::pack(
+        pool_mint,
+        &mut test.accounts.get_mut(&pool.pool_mint).unwrap().data,
+    )
+    .unwrap();
+    test.set_token_account(&pool.provider_pool_tokens, |account| account.amount = 0);
+
+    let user = test.add_user(&pool, RESERVE_A, RESERVE_B);
+    let deposit = test.deposit_instruction(&pool, &user, 1, RESERVE_A, RESERVE_B);
+    assert_eq!(
+        test.process(&deposit),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    let stranger = test.add_token_account(&pool.pool_mint, &user.wallet, 0);
+    let mut deposit_to_stranger = deposit.clone();
+    deposit_to_stranger
+        .accounts
+        .push(AccountMeta::new(stranger, false));
+    assert_eq!(
+        test.process(&deposit_to_stranger),
+        Err(SwapError::IncorrectPoolMint.into())
+    );
+
+    let dead_shares = test.add_token_account(&pool.pool_mint, &pool.authority, 0);
+    let mut deposit = deposit;
+    deposit.accounts.push(AccountMeta::new(dead_shares, false));
+    test.process(&deposit).unwrap();
+    // the geometric mean of the reserves, whatever amount was asked for
+    assert_eq!(test.mint(&pool.pool_mint).supply, 2_000_000_000);
+    assert_eq!(test.token_amount(&dead_shares), MIN_LP_SUPPLY as u64);
+    assert_eq!(
+        test.token_amount(&user.pool_tokens),
+        2_000_000_000 - MIN_LP_SUPPLY as u64
+    );
+    assert_eq!(test.token_amount(&user.token_a), 0);
+    assert_eq!(test.token_amount(&user.token_b), 0);
+}
+
+#[test]
+fn deposits_are_priced_like_the_reserves() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = pool(&mut test);
+    let user = test.add_user(&pool, RESERVE_A, RESERVE_B);
+    let deposit = |max_ratio_deviation_bps, maximum_token_a_amount| {
+        deposit_all_token_types(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &test.state,
+            &user.wallet,
+            &user.token_a,
+            &user.token_b,
+            &pool.token_a,
+            &pool.token_b,
+            &pool.pool_mint,
+            &user.pool_tokens,
+            None,
+            None,
+            DepositAllTokenTypes {
+                pool_token_amount: 1_000_000,
+                maximum_token_a_amount,
+                maximum_token_b_amount: 1_000_000,
+                max_ratio_deviation_bps,
+            },
+        )
+        .unwrap()
+    };
+    // maximums pricing token A 2% below the reserves
+    let off_price = deposit(Some(100), 4_080_000);
+    let within = deposit(Some(300), 4_080_000);
+    let unchecked = deposit(None, 4_080_000);
+    assert_eq!(
+        test.process(&off_price),
+        Err(SwapError::DepositRatioMismatch.into())
+    );
+    test.process(&within).unwrap();
+    test.process(&unchecked).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), 2_000_000);
+}
@@ -0,0 +1,138 @@
+//! Custodial wallets fund swaps through a delegate: the user transfer
+//! authority may be the delegate of the source account rather than its
+//! owner. Only the pool's own vaults reject delegates.
+
+mod common;
+
+use atlas_swap::{
+    curve::calculator::TradeDirection,
+    error::SwapError,
+    instruction::{swap, Swap, SwapArgs},
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{
+    entrypoint::ProgramResult, program_error::ProgramError, program_option::COption, pubkey::Pubkey,
+};
+use spl_token::state::Account;
+
+const RESERVE: u64 = 1_000_000;
+
+/// A constant product pool of two tokens with empty fees, and a wallet whose
+/// token A account has a delegate
+struct Pool {
+    test: ProgramTest,
+    pool: TestPool,
+    user: TestUser,
+}
+
+impl Pool {
+    fn new(source_delegate: Option<(Pubkey, u64)>) -> Self {
+        let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+        let user = test.add_user(&pool, RESERVE, 0);
+        if let Some((delegate, delegated_amount)) = source_delegate {
+            test.set_token_account(&user.token_a, |account| {
+                account.delegate = COption::Some(delegate);
+                account.delegated_amount = delegated_amount;
+            });
+        }
+        Self { test, pool, user }
+    }
+
+    fn source(&self) -> Account {
+        self.test.token_account(&self.user.token_a)
+    }
+
+    /// Swaps token A for token B, the given authority signing
+    fn swap(&mut self, user_transfer_authority: &Pubkey, amount_in: u64) -> ProgramResult {
+        self.swap_in_direction(user_transfer_authority, amount_in, None)
+    }
+
+    /// Swaps token A for token B, the payload naming `trade_direction`
+    fn swap_in_direction(
+        &mut self,
+        user_transfer_authority: &Pubkey,
+        amount_in: u64,
+        trade_direction: Option<TradeDirection>,
+    ) -> ProgramResult {
+        let instruction = swap(
+            &atlas_swap::id(),
+            SwapArgs {
+                token_program_id: spl_token::id(),
+                swap_pubkey: self.pool.swap,
+                authority_pubkey: self.pool.authority,
+                user_transfer_authority_pubkey: *user_transfer_authority,
+                global_state_pubkey: self.test.state,
+                source_pubkey: self.user.token_a,
+                swap_source_pubkey: self.pool.token_a,
+                swap_destination_pubkey: self.pool.token_b,
+                destination_pubkey: self.user.token_b,
+                pool_mint_pubkey: self.pool.pool_mint,
+                ..SwapArgs::default()
+            },
+            Swap {
+                amount_in,
+                minimum_amount_out: 1,
+                max_price_impact_bps: None,
+                tip: 0,
+                memo: Vec::new(),
+                trade_direction,
+                fee_discount: false,
+            },
+        )
+        .unwrap();
+        self.test.process(&instruction)
+    }
+}
+
+#[test]
+fn delegate_funds_swap_from_custodial_account() {
+    let custodian = Pubkey::new_unique();
+    let mut pool = Pool::new(Some((custodian, 1_000)));
+    pool.swap(&custodian, 600).unwrap();
+
+    let source = pool.source();
+    assert_eq!(source.amount, RESERVE - 600);
+    assert_eq!(source.delegate, COption::Some(custodian));
+    assert_eq!(source.delegated_amount, 400);
+    assert_eq!(pool.test.token_amount(&pool.pool.token_a), RESERVE + 600);
+    assert!(pool.test.token_amount(&pool.user.token_b) > 0);
+}
+
+#[test]
+fn delegate_cannot_swap_more_than_its_allowance() {
+    let custodian = Pubkey::new_unique();
+    let mut pool = Pool::new(Some((custodian, 1_000)));
+    assert_eq!(
+        pool.swap(&custodian, 1_001),
+        Err(ProgramError::Custom(
+            spl_token::error::TokenError::InsufficientFunds as u32
+        ))
+    );
+    assert_eq!(pool.source().amount, RESERVE);
+}
+
+#[test]
+fn signer_without_delegation_cannot_fund_swap() {
+    let mut pool = Pool::new(None);
+    assert_eq!(
+        pool.swap(&Pubkey::new_unique(), 600),
+        Err(ProgramError::Custom(
+            spl_token::error::TokenError::OwnerMismatch as u32
+        ))
+    );
+}
+
+#[test]
+fn swap_direction_must_match_its_vaults() {
+    let custodian = Pubkey::new_unique();
+    let mut pool = Pool::new(Some((custodian, 1_000)));
+    assert_eq!(
+        pool.swap_in_direction(&custodian, 600, Some(TradeDirection::BtoA)),
+        Err(SwapError::TradeDirectionMismatch.into())
+    );
+    assert_eq!(pool.source().amount, RESERVE);
+
+    pool.swap_in_direction(&custodian, 600, Some(TradeDirection::AtoB))
+        .unwrap();
+    assert_eq!(pool.source().amount, RESERVE - 600);
+}
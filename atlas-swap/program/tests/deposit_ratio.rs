@@ -0,0 +1,41 @@
+//! Deposits of both tokens are priced against the pool reserves, tokens
+//! donated to the vaults neither pricing them nor blocking them.
+
+mod common;
+
+use atlas_swap::curve::{
+    base::{CurveType, SwapCurve},
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+};
+use common::ProgramTest;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn donation_to_a_vault_leaves_deposits_priced_on_the_reserves() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+    test.set_token_account(&pool.token_a, |account| account.amount += RESERVE / 10);
+
+    let supply = test.mint(&pool.pool_mint).supply;
+    let deposit = test.deposit_instruction(&pool, &user, supply / 100, RESERVE, RESERVE);
+    test.process(&deposit).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), supply / 100);
+    // both tokens deposited in the ratio of the reserves, not the vaults
+    let token_a_amount = RESERVE - test.token_amount(&user.token_a);
+    let token_b_amount = RESERVE - test.token_amount(&user.token_b);
+    assert_eq!(token_a_amount, token_b_amount);
+    assert_eq!(token_a_amount, RESERVE / 100);
+}
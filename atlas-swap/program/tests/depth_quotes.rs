@@ -0,0 +1,65 @@
+//! Depth ladders quote every input size exactly as a swap would.
+
+use atlas_swap::curve::{
+    base::{get_swap_quote, CurveType, SwapCurve},
+    calculator::TradeDirection,
+    constant_product::ConstantProductCurve,
+    depth::{get_depth_quotes, log_spaced_amounts, DEPTH_LADDER_POINTS},
+    fees::Fees,
+};
+
+#[test]
+fn log_spaced_amounts_span_the_range() {
+    let amounts = log_spaced_amounts(1_000, 1_000_000_000, DEPTH_LADDER_POINTS);
+    assert_eq!(amounts.len(), DEPTH_LADDER_POINTS);
+    assert_eq!(amounts.first(), Some(&1_000));
+    assert_eq!(amounts.last(), Some(&1_000_000_000));
+    for pair in amounts.windows(2) {
+        // a ratio of 10^(6/9), about 4.64
+        assert!(pair[1] / pair[0] == 4 || pair[1] / pair[0] == 5, "{:?}", amounts);
+    }
+
+    assert_eq!(log_spaced_amounts(1, 4, 10), vec![1, 2, 3, 4]);
+    assert_eq!(log_spaced_amounts(0, 7, 1), vec![7]);
+    assert!(log_spaced_amounts(8, 7, 10).is_empty());
+}
+
+#[test]
+fn depth_quotes_match_single_swap_quotes() {
+    let curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let fees = Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let amounts = log_spaced_amounts(10_000, 10_000_000_000, DEPTH_LADDER_POINTS);
+    let quotes = get_depth_quotes(
+        1_000_000_000,
+        2_000_000_000,
+        &amounts,
+        TradeDirection::AtoB,
+        &fees,
+        &curve,
+    );
+    assert_eq!(quotes.len(), DEPTH_LADDER_POINTS);
+    for quote in quotes.iter() {
+        let result = get_swap_quote(
+            1_000_000_000,
+            2_000_000_000,
+            quote.amount_in,
+            TradeDirection::AtoB,
+            &fees,
+            &curve,
+        )
+        .unwrap();
+        assert_eq!(u128::from(quote.amount_out), result.destination_amount_swapped);
+    }
+    // larger sizes fill for more
+    for pair in quotes.windows(2) {
+        assert!(pair[1].amount_out > pair[0].amount_out);
+    }
+}
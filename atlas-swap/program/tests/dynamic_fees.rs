@@ -0,0 +1,107 @@
+//! Dynamic fees scale the fixed fee of every curve with the realized
+//! volatility of a pool, from the dynamic minimum at none to the dynamic
+//! maximum at DYNAMIC_FEE_VOLATILITY_CEILING and above.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::{Fees, DYNAMIC_FEE_VOLATILITY_CEILING},
+    },
+    error::SwapError,
+};
+
+fn dynamic_fees(min_numerator: u64, max_numerator: u64) -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 5,
+        stable_return_fee_numerator: 5,
+        constant_product_fixed_fee_numerator: 20,
+        stable_fixed_fee_numerator: 4,
+        fee_denominator: 10_000,
+        dynamic_fee_min_numerator: min_numerator,
+        dynamic_fee_max_numerator: max_numerator,
+        ..Fees::default()
+    }
+}
+
+/// Fixed fee numerators of the constant product and stable curves at
+/// `volatility`
+fn numerators(fees: &Fees, volatility: u64) -> (u64, u64) {
+    let fees = fees.with_volatility(volatility).unwrap();
+    (
+        fees.constant_product_fixed_fee_numerator,
+        fees.stable_fixed_fee_numerator,
+    )
+}
+
+#[test]
+fn fixed_fee_scales_between_the_dynamic_bounds() {
+    let fees = dynamic_fees(10, 110);
+    assert!(fees.is_dynamic());
+    assert_eq!(numerators(&fees, 0), (10, 10));
+    assert_eq!(
+        numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING / 4),
+        (35, 35)
+    );
+    assert_eq!(
+        numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING / 2),
+        (60, 60)
+    );
+    // rounded down between the bounds
+    assert_eq!(numerators(&fees, 1), (10, 10));
+    assert_eq!(numerators(&fees, 999), (109, 109));
+    assert_eq!(
+        numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING),
+        (110, 110)
+    );
+    // capped at the ceiling
+    assert_eq!(
+        numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING + 1),
+        (110, 110)
+    );
+    assert_eq!(numerators(&fees, u64::MAX), (110, 110));
+
+    // the scaled numerator is the one charged
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let fees = fees
+        .with_volatility(DYNAMIC_FEE_VOLATILITY_CEILING / 2)
+        .unwrap();
+    assert_eq!(
+        fees.fixed_fee(1_000_000, u128::MAX, &swap_curve),
+        Some(6_000)
+    );
+}
+
+#[test]
+fn equal_dynamic_bounds_charge_a_flat_fee() {
+    let fees = dynamic_fees(50, 50);
+    assert_eq!(numerators(&fees, 0), (50, 50));
+    assert_eq!(numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING), (50, 50));
+}
+
+#[test]
+fn static_fees_ignore_volatility() {
+    let fees = dynamic_fees(0, 0);
+    assert!(!fees.is_dynamic());
+    assert_eq!(numerators(&fees, 0), (20, 4));
+    assert_eq!(numerators(&fees, DYNAMIC_FEE_VOLATILITY_CEILING), (20, 4));
+}
+
+#[test]
+fn dynamic_bounds_are_validated() {
+    assert_eq!(dynamic_fees(10, 110).validate(), Ok(()));
+    assert_eq!(dynamic_fees(0, 9_994).validate(), Ok(()));
+    // the minimum above the maximum
+    assert_eq!(
+        dynamic_fees(111, 110).validate(),
+        Err(SwapError::InvalidFee)
+    );
+    // no room left for the return fee
+    assert_eq!(
+        dynamic_fees(10, 9_995).validate(),
+        Err(SwapError::InvalidFee)
+    );
+}
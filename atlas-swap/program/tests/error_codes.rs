@@ -0,0 +1,63 @@
+//! Custom error codes of the program sit in Anchor's range for program
+//! errors, one per variant in order, and decode back to their variant, and
+//! the error catalog describes every one of them.
+
+use atlas_swap::error::{error_catalog, SwapError, ERROR_CODE_OFFSET};
+use std::collections::HashSet;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+
+#[test]
+fn error_codes_are_offset_variant_positions() {
+    assert_eq!(ERROR_CODE_OFFSET, 6000);
+    assert_eq!(SwapError::AlreadyInUse.code(), 6000);
+    assert_eq!(SwapError::InvalidProgramAddress.code(), 6001);
+    assert_eq!(SwapError::ExpectedAccount.code(), 6005);
+    assert_eq!(u32::from(SwapError::InvalidOwner), 6002);
+    assert_eq!(
+        ProgramError::from(SwapError::InvalidOwner),
+        ProgramError::Custom(6002)
+    );
+}
+
+#[test]
+fn error_codes_decode_back_to_their_variant() {
+    assert_eq!(SwapError::from_code(0), None);
+    assert_eq!(SwapError::from_code(ERROR_CODE_OFFSET - 1), None);
+
+    let mut code = ERROR_CODE_OFFSET;
+    while let Some(error) = SwapError::from_code(code) {
+        assert_eq!(error.code(), code);
+        assert_eq!(
+            <SwapError as DecodeError<SwapError>>::decode_custom_error_to_enum(code),
+            Some(error)
+        );
+        code += 1;
+    }
+    // every variant up to the last one appended has a code
+    assert!(code > SwapError::PendingActionRequired.code());
+    assert_eq!(
+        <SwapError as DecodeError<SwapError>>::decode_custom_error_to_enum(code),
+        None
+    );
+}
+
+#[test]
+fn error_catalog_describes_every_error() {
+    let catalog = error_catalog();
+    assert_eq!(
+        catalog.len() as u32,
+        SwapError::PendingActionRequired.code() - ERROR_CODE_OFFSET + 1
+    );
+    let mut names = HashSet::new();
+    for (position, description) in catalog.iter().enumerate() {
+        assert_eq!(description.code, ERROR_CODE_OFFSET + position as u32);
+        let error = SwapError::from_code(description.code).unwrap();
+        assert_eq!(description.message, error.to_string());
+        assert!(!description.message.is_empty());
+        assert!(names.insert(description.name.clone()));
+    }
+
+    let description = SwapError::ZeroTradingTokens.description();
+    assert_eq!(description.name, "ZeroTradingTokens");
+    assert_eq!(catalog[(description.code - ERROR_CODE_OFFSET) as usize], description);
+}
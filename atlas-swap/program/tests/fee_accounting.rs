@@ -0,0 +1,78 @@
+//! Swap events read back from transaction logs fold into fee and PnL
+//! summaries per wallet and per pool.
+
+use atlas_swap::{
+    accounting::{parse_swap_event, FeeReport},
+    curve::calculator::TradeDirection,
+    events::{Event, SwapEvent},
+};
+use solana_program::pubkey::Pubkey;
+
+/// Log line of the first event of `swap_event`, as `sol_log_data` logs it
+const SWAP_LOG: &str = "Program data: AQ== AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQECAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgDoAwAAAAAAAN4DAAAAAAAAAwAAAAAAAAABAAAAAAAAAAcAAAAAAAAA";
+
+fn swap_event(trade_direction: TradeDirection, amount_in: u64, amount_out: u64) -> SwapEvent {
+    SwapEvent {
+        swap: Pubkey::new_from_array([1; 32]),
+        owner: Pubkey::new_from_array([2; 32]),
+        trade_direction,
+        amount_in,
+        amount_out,
+        trade_fee: 3,
+        owner_fee: 1,
+        slot: 7,
+    }
+}
+
+#[test]
+fn swap_events_parse_from_program_data_logs() {
+    let event = swap_event(TradeDirection::AtoB, 1_000, 990);
+    assert_eq!(SwapEvent::unpack(&event.data()), Some(event.clone()));
+    assert_eq!(parse_swap_event(SWAP_LOG), Some(event));
+
+    assert_eq!(parse_swap_event("Program log: Instruction: Swap"), None);
+    // another event kind
+    assert_eq!(parse_swap_event(&SWAP_LOG.replace("AQ==", "AA==")), None);
+    // truncated data
+    assert_eq!(parse_swap_event(&SWAP_LOG[..SWAP_LOG.len() - 4]), None);
+}
+
+#[test]
+fn reports_fold_fees_and_pnl_per_wallet_and_pool() {
+    let mut report = FeeReport::default();
+    report.add_logs(&[
+        "Program EZrRrk4cDBwNMnJekmn7JmV6hcFaPAmFDksDePHJZR6Q invoke [1]",
+        "Program log: Instruction: Swap",
+        SWAP_LOG,
+    ]);
+    report.add_swap(&swap_event(TradeDirection::BtoA, 985, 995));
+    let other_wallet = SwapEvent {
+        owner: Pubkey::new_from_array([3; 32]),
+        ..swap_event(TradeDirection::AtoB, 500, 495)
+    };
+    report.add_swap(&other_wallet);
+
+    let swap = Pubkey::new_from_array([1; 32]);
+    let owner = Pubkey::new_from_array([2; 32]);
+    let wallet = &report.wallets[&(swap, owner)];
+    assert_eq!(wallet.swaps, 2);
+    assert_eq!(wallet.trade_fee_a, 3);
+    assert_eq!(wallet.trade_fee_b, 3);
+    assert_eq!(wallet.owner_fee_a + wallet.owner_fee_b, 2);
+    assert_eq!(wallet.token_a_pnl(), -5);
+    assert_eq!(wallet.token_b_pnl(), 5);
+
+    let pool = &report.pools[&swap];
+    assert_eq!(pool.swaps, 3);
+    assert_eq!(pool.token_a_in, 1_500);
+    assert_eq!(pool.trade_fee_a, 6);
+
+    let csv = report.wallets_csv();
+    assert_eq!(csv.lines().count(), 3);
+    assert!(csv.starts_with("pool,wallet,swaps,"));
+    assert!(csv.contains(&format!("{},{},2,1000,995,985,990,3,3,1,1,-5,5", swap, owner)));
+    assert_eq!(report.pools_csv().lines().count(), 2);
+    assert!(report
+        .to_json()
+        .contains(&format!("{{\"pool\":\"{}\",\"swaps\":3,", swap)));
+}
@@ -0,0 +1,201 @@
+//! Holders of the discount token of the program state claiming the discount
+//! trade at a reduced fixed fee, and a holder account that does not qualify
+//! fails the swap.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::{FeeDiscount, Fees},
+    },
+    error::SwapError,
+    instruction::{swap as swap_instruction, Swap, SwapArgs},
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+const MINIMUM_BALANCE: u64 = 100;
+
+/// A pool charging a 1% fixed fee, and the mint of the discount token
+/// granting half of it off
+fn discounted_pool(test: &mut ProgramTest) -> (TestPool, Pubkey) {
+    let discount_mint = test.add_mint(&Pubkey::new_unique(), 0);
+    test.set_global_state(|state| {
+        state.fee_discount = FeeDiscount {
+            mint: discount_mint,
+            minimum_balance: MINIMUM_BALANCE,
+            discount_bps: 5_000,
+        }
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    (pool, discount_mint)
+}
+
+/// A swap of `AMOUNT_IN` token A paying its fixed fee into `fee_account`,
+/// claiming the discount with `holder`
+fn swap(
+    test: &ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    fee_account: &Pubkey,
+    holder: Option<&Pubkey>,
+) -> Instruction {
+    swap_instruction(
+        &atlas_swap::id(),
+        SwapArgs {
+            token_program_id: spl_token::id(),
+            swap_pubkey: pool.swap,
+            authority_pubkey: pool.authority,
+            user_transfer_authority_pubkey: user.wallet,
+            global_state_pubkey: test.state,
+            source_pubkey: user.token_a,
+            swap_source_pubkey: pool.token_a,
+            swap_destination_pubkey: pool.token_b,
+            destination_pubkey: user.token_b,
+            pool_mint_pubkey: pool.pool_mint,
+            pool_fee_pubkey: Some(*fee_account),
+            discount_holder_pubkey: holder.copied(),
+            ..SwapArgs::default()
+        },
+        Swap {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out: 1,
+            max_price_impact_bps: None,
+            tip: 0,
+            memo: Vec::new(),
+            trade_direction: None,
+            fee_discount: holder.is_some(),
+        },
+    )
+    .unwrap()
+}
+
+fn fees() -> Fees {
+    Fees {
+        constant_product_fixed_fee_numerator: 100,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+#[test]
+fn holders_pay_the_discounted_fixed_fee() {
+    let mut test = ProgramTest::new(fees());
+    let (pool, discount_mint) = discounted_pool(&mut test);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    test.process(&swap(&test, &pool, &user, &fee_account, None))
+        .unwrap();
+    assert_eq!(test.token_amount(&fee_account), AMOUNT_IN / 100);
+
+    let holder = test.add_user(&pool, AMOUNT_IN, 0);
+    let holder_account = test.add_token_account(&discount_mint, &holder.wallet, MINIMUM_BALANCE);
+    test.process(&swap(
+        &test,
+        &pool,
+        &holder,
+        &fee_account,
+        Some(&holder_account),
+    ))
+    .unwrap();
+    assert_eq!(
+        test.token_amount(&fee_account),
+        AMOUNT_IN / 100 + AMOUNT_IN / 200
+    );
+}
+
+#[test]
+fn holder_accounts_that_do_not_qualify_are_rejected() {
+    let mut test = ProgramTest::new(fees());
+    let (pool, discount_mint) = discounted_pool(&mut test);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+
+    let other_mint = test.add_mint(&Pubkey::new_unique(), 0);
+    let wrong_mint = test.add_token_account(&other_mint, &user.wallet, MINIMUM_BALANCE);
+    let wrong_owner =
+        test.add_token_account(&discount_mint, &Pubkey::new_unique(), MINIMUM_BALANCE);
+    let below_minimum = test.add_token_account(&discount_mint, &user.wallet, MINIMUM_BALANCE - 1);
+    let empty = test.add_token_account(&discount_mint, &user.wallet, 0);
+    for holder in [wrong_mint, wrong_owner, below_minimum, empty].iter() {
+        assert_eq!(
+            test.process(&swap(&test, &pool, &user, &fee_account, Some(holder))),
+            Err(SwapError::InvalidFeeDiscountAccount.into())
+        );
+    }
+
+    // a disabled discount takes no holder account
+    let holder_account = test.add_token_account(&discount_mint, &user.wallet, MINIMUM_BALANCE);
+    test.set_global_state(|state| state.fee_discount.discount_bps = 0);
+    assert_eq!(
+        test.process(&swap(
+            &test,
+            &pool,
+            &user,
+            &fee_account,
+            Some(&holder_account)
+        )),
+        Err(SwapError::InvalidFeeDiscountAccount.into())
+    );
+    // and an empty account holds nothing, whatever the minimum balance
+    test.set_global_state(|state| {
+        state.fee_discount.discount_bps = 5_000;
+        state.fee_discount.minimum_balance = 0;
+    });
+    assert_eq!(
+        test.process(&swap(&test, &pool, &user, &fee_account, Some(&empty))),
+        Err(SwapError::InvalidFeeDiscountAccount.into())
+    );
+    assert_eq!(test.token_amount(&fee_account), 0);
+}
+
+#[test]
+fn only_swaps_claiming_the_discount_pass_a_holder() {
+    let mut test = ProgramTest::new(fees());
+    let (pool, discount_mint) = discounted_pool(&mut test);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let user = test.add_user(&pool, 2 * AMOUNT_IN, 0);
+
+    // an account passed after the others, not claiming the discount, is
+    // not read as a holder
+    let unrelated = test.add_token_account(&pool.token_b_mint, &user.wallet, 0);
+    let mut unclaimed = swap(&test, &pool, &user, &fee_account, None);
+    unclaimed
+        .accounts
+        .push(AccountMeta::new_readonly(unrelated, false));
+    test.process(&unclaimed).unwrap();
+    let holder_account = test.add_token_account(&discount_mint, &user.wallet, MINIMUM_BALANCE);
+    let mut unclaimed = swap(&test, &pool, &user, &fee_account, None);
+    unclaimed
+        .accounts
+        .push(AccountMeta::new_readonly(holder_account, false));
+    test.process(&unclaimed).unwrap();
+    assert_eq!(test.token_amount(&fee_account), 2 * AMOUNT_IN / 100);
+
+    // a swap claiming the discount without the holder
+    let mut claimed = swap(&test, &pool, &user, &fee_account, Some(&holder_account));
+    claimed.accounts.pop();
+    assert_eq!(
+        test.process(&claimed),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+}
@@ -0,0 +1,266 @@
+//! Once handed to an spl-governance governance, the fees of the program are
+//! only changed by the governance signing SetGovernedFees, as the
+//! instructions of its executed proposals are, until it hands them back.
+
+use atlas_swap::{
+    curve::fees::{FeeDiscount, Fees},
+    error::SwapError,
+    instruction::{
+        set_fee_governance, set_global_state, set_governed_fees, SetFeeGovernance,
+        SetGovernedFees,
+    },
+    pda::{find_fee_governance, find_global_state, find_spl_governance},
+    processor::Processor,
+    state::{FeeGovernance, GlobalState, GlobalStateVersion},
+};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+struct TestAccount {
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+/// A program state owned by `owner`, its fee governance account already
+/// allocated, and the realm of a governance program
+struct TestProgram {
+    owner: Pubkey,
+    payer: Pubkey,
+    state: Pubkey,
+    fee_governance: Pubkey,
+    governance_program_id: Pubkey,
+    realm: Pubkey,
+    governance_seed: Pubkey,
+    accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl TestProgram {
+    fn new() -> Self {
+        let program_id = atlas_swap::id();
+        let mut program = Self {
+            owner: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            state: find_global_state(&program_id).0,
+            fee_governance: find_fee_governance(&program_id).0,
+            governance_program_id: Pubkey::new_unique(),
+            realm: Pubkey::new_unique(),
+            governance_seed: Pubkey::new_unique(),
+            accounts: HashMap::new(),
+        };
+        let mut state_data = vec![0; GlobalStateVersion::LATEST_LEN];
+        GlobalStateVersion::pack(
+            &GlobalState {
+                is_initialized: true,
+                owner: program.owner,
+                fee_owner: Pubkey::new_unique(),
+                initial_supply: 1_000_000,
+                fees: fees(10),
+                ..GlobalState::default()
+            },
+            &mut state_data,
+        )
+        .unwrap();
+        program.add(program.state, program_id, state_data);
+        program.add(program.fee_governance, program_id, vec![0; FeeGovernance::LEN]);
+        program.add(program.realm, program.governance_program_id, vec![1]);
+        program
+    }
+
+    fn add(&mut self, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        self.accounts.insert(
+            key,
+            TestAccount {
+                owner,
+                lamports: 1,
+                data,
+            },
+        );
+    }
+
+    fn governance(&self) -> Pubkey {
+        find_spl_governance(&self.governance_program_id, &self.realm, &self.governance_seed).0
+    }
+
+    fn state(&self) -> GlobalState {
+        GlobalStateVersion::unpack(&self.accounts[&self.state].data).unwrap()
+    }
+
+    /// Runs an instruction, accounts missing from the program being empty
+    /// accounts such as the program ids
+    fn process(&mut self, instruction: Instruction) -> ProgramResult {
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert(TestAccount {
+                owner: Pubkey::default(),
+                lamports: 1,
+                data: vec![],
+            });
+        }
+        let mut account_infos = vec![];
+        for (key, account) in self.accounts.iter_mut() {
+            if let Some(meta) = instruction.accounts.iter().find(|meta| meta.pubkey == *key) {
+                account_infos.push(AccountInfo::new(
+                    key,
+                    meta.is_signer,
+                    meta.is_writable,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    false,
+                    0,
+                ));
+            }
+        }
+        let account_infos = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .unwrap()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    fn set_fee_governance(
+        &mut self,
+        authority: Pubkey,
+        governance_program_id: Pubkey,
+    ) -> ProgramResult {
+        let instruction = set_fee_governance(
+            &atlas_swap::id(),
+            &self.state,
+            &authority,
+            &self.fee_governance,
+            &self.payer,
+            SetFeeGovernance {
+                governance_program_id,
+                realm: self.realm,
+                governance_seed: self.governance_seed,
+            },
+        )
+        .unwrap();
+        self.process(instruction)
+    }
+
+    fn set_governed_fees(&mut self, governance: Pubkey, fees: Fees) -> ProgramResult {
+        let instruction = set_governed_fees(
+            &atlas_swap::id(),
+            &self.state,
+            &self.fee_governance,
+            &governance,
+            SetGovernedFees {
+                fees,
+                fee_discount: FeeDiscount::default(),
+            },
+        )
+        .unwrap();
+        self.process(instruction)
+    }
+
+    fn set_global_state(&mut self, fees: Fees) -> ProgramResult {
+        let state = self.state();
+        let instruction = set_global_state(
+            &atlas_swap::id(),
+            &self.state,
+            &self.owner,
+            &state.owner,
+            &state.fee_owner,
+            state.initial_supply,
+            state.lp_decimals,
+            fees,
+            state.fee_discount,
+            &state.quoter,
+        )
+        .unwrap();
+        self.process(instruction)
+    }
+}
+
+fn fees(trade_fee_numerator: u64) -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: trade_fee_numerator,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+#[test]
+fn governed_fees_are_only_set_by_the_governance() {
+    let mut program = TestProgram::new();
+    let (owner, governance) = (program.owner, program.governance());
+    let governance_program_id = program.governance_program_id;
+
+    program.set_fee_governance(owner, governance_program_id).unwrap();
+    assert!(program.state().fee_governed);
+    let fee_governance =
+        FeeGovernance::unpack(&program.accounts[&program.fee_governance].data).unwrap();
+    assert_eq!(fee_governance.governance, governance);
+    assert_eq!(fee_governance.realm, program.realm);
+
+    // the owner keeps the rest of the program state, but not the fees
+    program.set_global_state(fees(10)).unwrap();
+    assert_eq!(
+        program.set_global_state(fees(20)),
+        Err(SwapError::FeesGoverned.into())
+    );
+    assert_eq!(
+        program.set_governed_fees(owner, fees(20)),
+        Err(SwapError::InvalidFeeGovernance.into())
+    );
+    assert_eq!(
+        program.set_fee_governance(owner, Pubkey::default()),
+        Err(SwapError::InvalidFeeGovernance.into())
+    );
+
+    program.set_governed_fees(governance, fees(20)).unwrap();
+    assert_eq!(program.state().fees, fees(20));
+    assert_eq!(
+        program.set_governed_fees(governance, fees(10_000)),
+        Err(SwapError::InvalidFee.into())
+    );
+
+    // handing the fees back to the owner
+    program.set_fee_governance(governance, Pubkey::default()).unwrap();
+    assert!(!program.state().fee_governed);
+    assert_eq!(
+        program.set_governed_fees(governance, fees(30)),
+        Err(ProgramError::UninitializedAccount)
+    );
+    // which changes them through the scheduler again
+    assert_eq!(
+        program.set_global_state(fees(30)),
+        Err(SwapError::PendingActionRequired.into())
+    );
+    assert_eq!(program.state().fees, fees(20));
+}
+
+#[test]
+fn fee_governance_needs_a_realm_of_the_governance_program() {
+    let mut program = TestProgram::new();
+    let owner = program.owner;
+
+    assert_eq!(
+        program.set_fee_governance(Pubkey::new_unique(), program.governance_program_id),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert_eq!(
+        program.set_fee_governance(owner, Pubkey::new_unique()),
+        Err(SwapError::InvalidFeeGovernance.into())
+    );
+    assert!(!program.state().fee_governed);
+    assert_eq!(
+        program.set_governed_fees(program.governance(), fees(20)),
+        Err(ProgramError::UninitializedAccount)
+    );
+}
@@ -0,0 +1,113 @@
+//! Trades reaching a tier of the fee schedule, in basis points of the source
+//! reserve, pay the highest fixed fee of the tiers they reach when it is
+//! above the base fixed fee of their curve.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::{FeeTier, Fees},
+        stable::StableCurve,
+    },
+    error::SwapError,
+};
+
+const RESERVE: u128 = 1_000_000;
+
+fn tiered_fees(base_numerator: u64, fee_tiers: [(u64, u64); 2]) -> Fees {
+    let tier = |(threshold_bps, fixed_fee_numerator)| FeeTier {
+        threshold_bps,
+        fixed_fee_numerator,
+    };
+    Fees {
+        constant_product_return_fee_numerator: 5,
+        stable_return_fee_numerator: 5,
+        constant_product_fixed_fee_numerator: base_numerator,
+        stable_fixed_fee_numerator: base_numerator / 2,
+        fee_denominator: 10_000,
+        fee_tiers: [tier(fee_tiers[0]), tier(fee_tiers[1])],
+        ..Fees::default()
+    }
+}
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+fn fee(fees: &Fees, trading_tokens: u128, swap_source_amount: u128) -> u128 {
+    fees.fixed_fee(trading_tokens, swap_source_amount, &constant_product())
+        .unwrap()
+}
+
+#[test]
+fn trades_pay_the_highest_tier_they_reach() {
+    // 1% and 5% of the reserve, 10_000 and 50_000 tokens
+    let fees = tiered_fees(20, [(100, 30), (500, 50)]);
+    assert_eq!(fees.validate(), Ok(()));
+    // below the first tier, the base fee
+    assert_eq!(fee(&fees, 9_999, RESERVE), 19);
+    // from the threshold on, inclusive
+    assert_eq!(fee(&fees, 10_000, RESERVE), 30);
+    assert_eq!(fee(&fees, 49_999, RESERVE), 149);
+    assert_eq!(fee(&fees, 50_000, RESERVE), 250);
+    assert_eq!(fee(&fees, RESERVE * 2, RESERVE), 10_000);
+    // thresholds are rounded down, 1% of 1_000_099 is 10_000
+    assert_eq!(fee(&fees, 10_000, RESERVE + 99), 30);
+    assert_eq!(fee(&fees, 9_999, RESERVE + 99), 19);
+    // an empty reserve puts every trade in every tier
+    assert_eq!(fee(&fees, 1_000, 0), 5);
+}
+
+#[test]
+fn tiers_never_lower_the_base_fee() {
+    let fees = tiered_fees(40, [(100, 30), (500, 50)]);
+    assert_eq!(fee(&fees, 9_999, RESERVE), 39);
+    assert_eq!(fee(&fees, 10_000, RESERVE), 40);
+    assert_eq!(fee(&fees, 50_000, RESERVE), 250);
+
+    // the stable curve tiers up from its own base fee
+    let stable = SwapCurve {
+        curve_type: CurveType::Stable,
+        calculator: StableCurve {
+            amp: 100,
+            ..StableCurve::default()
+        }
+        .into(),
+    };
+    assert_eq!(fees.fixed_fee(9_999, RESERVE, &stable), Some(19));
+    assert_eq!(fees.fixed_fee(10_000, RESERVE, &stable), Some(30));
+}
+
+#[test]
+fn disabled_tiers_are_skipped() {
+    let fees = tiered_fees(20, [(0, 90), (500, 50)]);
+    assert_eq!(fees.validate(), Ok(()));
+    assert_eq!(fee(&fees, 10_000, RESERVE), 20);
+    assert_eq!(fee(&fees, 50_000, RESERVE), 250);
+    assert_eq!(fee(&fees, RESERVE, 0), 5_000);
+}
+
+#[test]
+fn tiers_are_validated() {
+    // thresholds must ascend
+    assert_eq!(
+        tiered_fees(20, [(500, 30), (100, 50)]).validate(),
+        Err(SwapError::InvalidFee)
+    );
+    assert_eq!(
+        tiered_fees(20, [(100, 30), (100, 50)]).validate(),
+        Err(SwapError::InvalidFee)
+    );
+    // and leave room for the return fee
+    assert_eq!(
+        tiered_fees(20, [(100, 30), (500, 9_994)]).validate(),
+        Ok(())
+    );
+    assert_eq!(
+        tiered_fees(20, [(100, 30), (500, 9_995)]).validate(),
+        Err(SwapError::InvalidFee)
+    );
+}
@@ -0,0 +1,86 @@
+//! The program owner may sweep tokens of other mints sent to the swap
+//! authority, but never the vaults or pool tokens it holds.
+
+mod common;
+
+use atlas_swap::{error::SwapError, instruction::recover_foreign_tokens};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const FOREIGN: u64 = 5_000;
+
+fn recover(
+    test: &ProgramTest,
+    pool: &TestPool,
+    owner: &Pubkey,
+    foreign: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    recover_foreign_tokens(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        owner,
+        &test.state,
+        foreign,
+        destination,
+    )
+    .unwrap()
+}
+
+#[test]
+fn the_owner_recovers_foreign_tokens() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let owner = test.owner;
+    let foreign_mint = test.add_mint(&owner, 6);
+    let foreign = test.add_token_account(&foreign_mint, &pool.authority, FOREIGN);
+    let destination = test.add_token_account(&foreign_mint, &owner, 0);
+
+    let other = recover(&test, &pool, &Pubkey::new_unique(), &foreign, &destination);
+    assert_eq!(
+        test.process(&other),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    test.process(&recover(&test, &pool, &owner, &foreign, &destination))
+        .unwrap();
+    assert_eq!(test.token_amount(&foreign), 0);
+    assert_eq!(test.token_amount(&destination), FOREIGN);
+
+    // tokens the authority does not own
+    let not_held = test.add_token_account(&foreign_mint, &Pubkey::new_unique(), FOREIGN);
+    let instruction = recover(&test, &pool, &owner, &not_held, &destination);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidOwner.into())
+    );
+}
+
+#[test]
+fn the_pool_tokens_are_never_recovered() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let owner = test.owner;
+    // the vaults, an extra account of each pool token held by the
+    // authority, and the locked pool tokens
+    let extra_a = test.add_token_account(&pool.token_a_mint, &pool.authority, FOREIGN);
+    let extra_b = test.add_token_account(&pool.token_b_mint, &pool.authority, FOREIGN);
+    for (held, mint) in [
+        (pool.token_a, pool.token_a_mint),
+        (pool.token_b, pool.token_b_mint),
+        (extra_a, pool.token_a_mint),
+        (extra_b, pool.token_b_mint),
+        (pool.dead_shares, pool.pool_mint),
+    ]
+    .iter()
+    {
+        let amount = test.token_amount(held);
+        let destination = test.add_token_account(mint, &owner, 0);
+        let instruction = recover(&test, &pool, &owner, held, &destination);
+        assert_eq!(
+            test.process(&instruction),
+            Err(SwapError::InvalidInput.into())
+        );
+        assert_eq!(test.token_amount(held), amount);
+    }
+}
@@ -0,0 +1,91 @@
+//! The program state migrates from the legacy layout to the versioned one
+//! in place, keeping its address, with the owner topping up the rent of
+//! the grown account.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::migrate_global_state,
+    pda::find_global_state,
+    state::{GlobalState, GlobalStateVersion, GLOBAL_STATE_V1_LEN},
+};
+use common::ProgramTest;
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+
+const LAMPORTS: u64 = 1_000_000_000;
+
+/// Replaces the program state with `state` in the legacy layout, and
+/// returns the owner paying for the migration
+fn legacy_state(test: &mut ProgramTest) -> (GlobalState, Pubkey) {
+    let owner = test.add_payer(LAMPORTS);
+    let state = GlobalState {
+        owner,
+        ..test.global_state()
+    };
+    let mut data = vec![0; GlobalState::LEN];
+    state.pack_into_slice(&mut data);
+    data.truncate(GLOBAL_STATE_V1_LEN);
+    let account = test.accounts.get_mut(&test.state).unwrap();
+    account.lamports = Rent::default().minimum_balance(GLOBAL_STATE_V1_LEN);
+    account.data = data;
+    (state, owner)
+}
+
+#[test]
+fn migrate_grows_the_legacy_state_in_place() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let (state, owner) = legacy_state(&mut test);
+    assert_eq!(test.state, find_global_state(&atlas_swap::id()).0);
+
+    let migrate = migrate_global_state(&atlas_swap::id(), &test.state, &owner).unwrap();
+    test.process(&migrate).unwrap();
+
+    let account = &test.accounts[&test.state];
+    assert_eq!(account.data.len(), GlobalStateVersion::LATEST_LEN);
+    let rent = Rent::default().minimum_balance(GlobalStateVersion::LATEST_LEN);
+    assert_eq!(account.lamports, rent);
+    assert_eq!(
+        test.accounts[&owner].lamports,
+        LAMPORTS - (rent - Rent::default().minimum_balance(GLOBAL_STATE_V1_LEN))
+    );
+    assert_eq!(test.global_state(), state);
+
+    // handlers read the migrated state at the same address
+    test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        LAMPORTS,
+        LAMPORTS,
+    );
+
+    assert_eq!(
+        test.process(&migrate),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
+
+#[test]
+fn only_the_owner_migrates_the_state() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    legacy_state(&mut test);
+    let other = test.add_payer(LAMPORTS);
+    assert_eq!(
+        test.process(&migrate_global_state(&atlas_swap::id(), &test.state, &other).unwrap()),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert_eq!(test.accounts[&test.state].data.len(), GLOBAL_STATE_V1_LEN);
+}
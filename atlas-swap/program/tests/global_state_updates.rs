@@ -0,0 +1,265 @@
+//! SetGlobalState keeps the program state fit to create pools from, and
+//! consistent with the pools already created from it.
+
+use atlas_swap::{
+    constraints::{MAX_LP_DECIMALS, MIN_LP_SUPPLY},
+    curve::fees::Fees,
+    error::SwapError,
+    instruction::{set_global_state, set_pool_count, SetPoolCount},
+    pda::find_global_state,
+    processor::Processor,
+    state::{GlobalState, GlobalStateVersion},
+};
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::Instruction,
+    pubkey::Pubkey,
+};
+use std::collections::HashMap;
+
+struct TestAccount {
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+/// A program state owned by `owner`
+struct TestProgram {
+    owner: Pubkey,
+    state: Pubkey,
+    accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl TestProgram {
+    fn new(state: GlobalState) -> Self {
+        let program_id = atlas_swap::id();
+        let owner = Pubkey::new_unique();
+        let mut state_data = vec![0; GlobalStateVersion::LATEST_LEN];
+        GlobalStateVersion::pack(
+            &GlobalState {
+                is_initialized: true,
+                owner,
+                fee_owner: Pubkey::new_unique(),
+                initial_supply: 1_000_000,
+                lp_decimals: 6,
+                fees: Fees {
+                    fee_denominator: 10_000,
+                    ..Fees::default()
+                },
+                ..state
+            },
+            &mut state_data,
+        )
+        .unwrap();
+        let mut program = Self {
+            owner,
+            state: find_global_state(&program_id).0,
+            accounts: HashMap::new(),
+        };
+        program.accounts.insert(
+            program.state,
+            TestAccount {
+                owner: program_id,
+                lamports: 1,
+                data: state_data,
+            },
+        );
+        program
+    }
+
+    fn state(&self) -> GlobalState {
+        GlobalStateVersion::unpack(&self.accounts[&self.state].data).unwrap()
+    }
+
+    /// Runs an instruction, accounts missing from the program being empty
+    /// accounts such as the program ids
+    fn process(&mut self, instruction: Instruction) -> ProgramResult {
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert(TestAccount {
+                owner: Pubkey::default(),
+                lamports: 1,
+                data: vec![],
+            });
+        }
+        let mut account_infos = vec![];
+        for (key, account) in self.accounts.iter_mut() {
+            if let Some(meta) = instruction.accounts.iter().find(|meta| meta.pubkey == *key) {
+                account_infos.push(AccountInfo::new(
+                    key,
+                    meta.is_signer,
+                    meta.is_writable,
+                    &mut account.lamports,
+                    &mut account.data,
+                    &account.owner,
+                    false,
+                    0,
+                ));
+            }
+        }
+        let account_infos = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .unwrap()
+                    .clone()
+            })
+            .collect::<Vec<_>>();
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    /// SetGlobalState keeping every field but those of `update`
+    fn set_global_state(&mut self, update: GlobalState) -> ProgramResult {
+        let instruction = set_global_state(
+            &atlas_swap::id(),
+            &self.state,
+            &self.owner,
+            &update.owner,
+            &update.fee_owner,
+            update.initial_supply,
+            update.lp_decimals,
+            update.fees,
+            update.fee_discount,
+            &update.quoter,
+        )
+        .unwrap();
+        self.process(instruction)
+    }
+}
+
+#[test]
+fn pool_parameters_are_fixed_once_pools_exist() {
+    let mut program = TestProgram::new(GlobalState {
+        pool_count: 1,
+        ..GlobalState::default()
+    });
+    let state = program.state();
+
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            initial_supply: 2_000_000,
+            ..program.state()
+        }),
+        Err(SwapError::PoolParametersFixed.into())
+    );
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            lp_decimals: 9,
+            ..program.state()
+        }),
+        Err(SwapError::PoolParametersFixed.into())
+    );
+    assert_eq!(program.state(), state);
+
+    // the other fields still change
+    let fee_owner = Pubkey::new_unique();
+    program
+        .set_global_state(GlobalState {
+            fee_owner,
+            ..program.state()
+        })
+        .unwrap();
+    assert_eq!(program.state().fee_owner, fee_owner);
+    assert_eq!(program.state().pool_count, 1);
+}
+
+#[test]
+fn pool_parameters_change_before_any_pool() {
+    let mut program = TestProgram::new(GlobalState::default());
+
+    program
+        .set_global_state(GlobalState {
+            initial_supply: 2_000_000,
+            lp_decimals: 9,
+            ..program.state()
+        })
+        .unwrap();
+    let mut state = program.state();
+    assert_eq!((state.initial_supply, state.lp_decimals), (2_000_000, 9));
+
+    state.record_pool().unwrap();
+    assert_eq!(state.pool_count, 1);
+    state.pool_count = u64::MAX;
+    assert_eq!(state.record_pool(), Err(SwapError::CalculationFailure));
+}
+
+#[test]
+fn pool_count_is_seeded_once_by_the_owner() {
+    let mut program = TestProgram::new(GlobalState::default());
+    let seed = |program: &TestProgram, owner: &Pubkey, pool_count: u64| {
+        set_pool_count(
+            &atlas_swap::id(),
+            &program.state,
+            owner,
+            SetPoolCount { pool_count },
+        )
+        .unwrap()
+    };
+
+    let instruction = seed(&program, &Pubkey::new_unique(), 3);
+    assert_eq!(
+        program.process(instruction),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let instruction = seed(&program, &program.owner, 0);
+    assert_eq!(
+        program.process(instruction),
+        Err(SwapError::PoolCountSet.into())
+    );
+    let instruction = seed(&program, &program.owner, 3);
+    program.process(instruction).unwrap();
+    assert_eq!(program.state().pool_count, 3);
+
+    // the pools seeded fix the pool parameters
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            initial_supply: 2_000_000,
+            ..program.state()
+        }),
+        Err(SwapError::PoolParametersFixed.into())
+    );
+    let instruction = seed(&program, &program.owner, 5);
+    assert_eq!(
+        program.process(instruction),
+        Err(SwapError::PoolCountSet.into())
+    );
+    assert_eq!(program.state().pool_count, 3);
+}
+
+#[test]
+fn set_global_state_rejects_values_that_break_pool_creation() {
+    let mut program = TestProgram::new(GlobalState::default());
+    let state = program.state();
+
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            fee_owner: Pubkey::default(),
+            ..program.state()
+        }),
+        Err(SwapError::InvalidFeeOwner.into())
+    );
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            initial_supply: MIN_LP_SUPPLY as u64,
+            ..program.state()
+        }),
+        Err(SwapError::InitialSupplyTooLow.into())
+    );
+    assert_eq!(
+        program.set_global_state(GlobalState {
+            lp_decimals: MAX_LP_DECIMALS + 1,
+            ..program.state()
+        }),
+        Err(SwapError::InvalidLpDecimals.into())
+    );
+    assert_eq!(program.state(), state);
+
+    program
+        .set_global_state(GlobalState {
+            initial_supply: MIN_LP_SUPPLY as u64 + 1,
+            lp_decimals: MAX_LP_DECIMALS,
+            ..program.state()
+        })
+        .unwrap();
+}
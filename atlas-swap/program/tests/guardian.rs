@@ -0,0 +1,164 @@
+//! The guardian may pause swaps and deposits, in every pool or in one of
+//! them, and only the program owner may resume them. Withdrawals stay open.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        set_guardian, set_paused, set_pool_paused, withdraw_all_token_types, SetGuardian,
+        SetPaused, WithdrawAllTokenTypes,
+    },
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+fn curve() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+fn pause(test: &ProgramTest, signer: &Pubkey, paused: bool) -> Instruction {
+    set_paused(&atlas_swap::id(), &test.state, signer, SetPaused { paused }).unwrap()
+}
+
+fn pause_pool(test: &ProgramTest, pool: &TestPool, signer: &Pubkey, paused: bool) -> Instruction {
+    set_pool_paused(
+        &atlas_swap::id(),
+        &pool.swap,
+        signer,
+        &test.state,
+        SetPaused { paused },
+    )
+    .unwrap()
+}
+
+fn guard(test: &mut ProgramTest, guardian: Pubkey) {
+    let owner = test.owner;
+    test.process(
+        &set_guardian(
+            &atlas_swap::id(),
+            &test.state,
+            &owner,
+            SetGuardian { guardian },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+}
+
+/// Result of a swap, then of a deposit, into `pool`
+fn trade(test: &mut ProgramTest, pool: &TestPool) -> (ProgramResult, ProgramResult) {
+    let user = test.add_user(pool, 2 * AMOUNT, AMOUNT);
+    let swap = test.swap_instruction(pool, &user, true, AMOUNT, 1, None);
+    let deposit = test.deposit_instruction(pool, &user, AMOUNT / 2, AMOUNT, AMOUNT);
+    (test.process(&swap), test.process(&deposit))
+}
+
+fn rejected_as_paused() -> (ProgramResult, ProgramResult) {
+    (Err(SwapError::Paused.into()), Err(SwapError::Paused.into()))
+}
+
+#[test]
+fn guardian_pauses_and_only_the_owner_resumes() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = test.create_pool(curve(), RESERVE, RESERVE);
+    let guardian = Pubkey::new_unique();
+    let intruder_guard = set_guardian(
+        &atlas_swap::id(),
+        &test.state,
+        &guardian,
+        SetGuardian { guardian },
+    )
+    .unwrap();
+    assert_eq!(
+        test.process(&intruder_guard),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    // without a guardian, only the owner may pause
+    assert_eq!(
+        test.process(&pause(&test, &guardian, true)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    guard(&mut test, guardian);
+
+    test.process(&pause(&test, &guardian, true)).unwrap();
+    assert!(test.global_state().paused);
+    assert_eq!(trade(&mut test, &pool), rejected_as_paused());
+    // withdrawals stay open
+    let destination_a = test.add_token_account(&pool.token_a_mint, &pool.provider, 0);
+    let destination_b = test.add_token_account(&pool.token_b_mint, &pool.provider, 0);
+    let withdraw = withdraw_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        &pool.provider,
+        &pool.pool_mint,
+        &pool.provider_pool_tokens,
+        &pool.token_a,
+        &pool.token_b,
+        &destination_a,
+        &destination_b,
+        None,
+        None,
+        WithdrawAllTokenTypes {
+            pool_token_amount: RESERVE / 10,
+            minimum_token_a_amount: 1,
+            minimum_token_b_amount: 1,
+        },
+    )
+    .unwrap();
+    test.process(&withdraw).unwrap();
+    assert!(test.token_amount(&destination_a) > 0);
+
+    assert_eq!(
+        test.process(&pause(&test, &guardian, false)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let owner = test.owner;
+    test.process(&pause(&test, &owner, false)).unwrap();
+    assert_eq!(trade(&mut test, &pool), (Ok(()), Ok(())));
+
+    // once removed, the guardian may not pause anymore
+    guard(&mut test, Pubkey::default());
+    assert_eq!(
+        test.process(&pause(&test, &guardian, true)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+}
+
+#[test]
+fn pool_pause_leaves_other_pools_open() {
+    let mut test = ProgramTest::new(Fees::default());
+    let paused = test.create_pool(curve(), RESERVE, RESERVE);
+    let open = test.create_pool(curve(), RESERVE, RESERVE);
+    let guardian = Pubkey::new_unique();
+    guard(&mut test, guardian);
+
+    test.process(&pause_pool(&test, &paused, &guardian, true))
+        .unwrap();
+    assert!(test.swap_v2(&paused.swap).paused);
+    assert_eq!(trade(&mut test, &paused), rejected_as_paused());
+    assert_eq!(trade(&mut test, &open), (Ok(()), Ok(())));
+
+    assert_eq!(
+        test.process(&pause_pool(&test, &paused, &guardian, false)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let owner = test.owner;
+    test.process(&pause_pool(&test, &paused, &owner, false))
+        .unwrap();
+    assert_eq!(trade(&mut test, &paused), (Ok(()), Ok(())));
+}
@@ -0,0 +1,82 @@
+//! Deposits into a pool with a holding period restart the holding period
+//! of the depositor only, so dust minted to another owner cannot hold back
+//! its withdrawals.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{init_deposit_record, set_min_hold_period, SetMinHoldPeriod},
+    pda::find_deposit_record,
+    state::DepositRecord,
+};
+use common::{set_clock, ProgramTest};
+use solana_program::{instruction::AccountMeta, program_pack::Pack};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn deposit_restarts_the_holding_period_of_the_depositor_only() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    test.process(
+        &set_min_hold_period(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            SetMinHoldPeriod { min_hold_slots: 100 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let holder = test.add_user(&pool, RESERVE, RESERVE);
+    let griefer = test.add_user(&pool, RESERVE, RESERVE);
+    let deposit_record = find_deposit_record(&atlas_swap::id(), &pool.swap, &holder.wallet).0;
+    let payer = test.add_payer(1_000_000_000);
+    test.process(
+        &init_deposit_record(
+            &atlas_swap::id(),
+            &pool.swap,
+            &payer,
+            &deposit_record,
+            &holder.wallet,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    set_clock(50, 0);
+
+    // dust minted by another depositor to the holder's account
+    let mut dust = test.deposit_instruction(&pool, &griefer, 10, RESERVE, RESERVE);
+    dust.accounts[9].pubkey = holder.pool_tokens;
+    dust.accounts.push(AccountMeta::new(deposit_record, false));
+    assert_eq!(
+        test.process(&dust),
+        Err(SwapError::HoldingPeriodOwnerMismatch.into())
+    );
+    let record = DepositRecord::unpack(&test.accounts[&deposit_record].data).unwrap();
+    assert_eq!(record.last_deposit_slot, 0);
+
+    // the holder's own deposit restarts it
+    let mut deposit = test.deposit_instruction(&pool, &holder, 10, RESERVE, RESERVE);
+    deposit.accounts.push(AccountMeta::new(deposit_record, false));
+    test.process(&deposit).unwrap();
+    let record = DepositRecord::unpack(&test.accounts[&deposit_record].data).unwrap();
+    assert_eq!(record.last_deposit_slot, 50);
+}
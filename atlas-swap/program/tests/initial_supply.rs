@@ -0,0 +1,131 @@
+//! The first deposit of a pool or basket pool mints the geometric mean of
+//! its amounts as pool tokens, and only deposits too small for the locked
+//! minimum supply fall back to the initial supply of the program state.
+
+mod common;
+
+use atlas_swap::{
+    constraints::MIN_LP_SUPPLY,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    instruction::initialize_basket,
+    state::BasketPool,
+};
+use common::ProgramTest;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+#[test]
+fn initial_supply_is_the_geometric_mean_of_the_reserves() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(constant_product(), 4_000_000_000, 1_000_000_000);
+    assert_eq!(test.mint(&pool.pool_mint).supply, 2_000_000_000);
+    // MIN_LP_SUPPLY of it is locked with the swap authority
+    assert_eq!(
+        test.token_amount(&pool.provider_pool_tokens),
+        2_000_000_000 - MIN_LP_SUPPLY as u64
+    );
+    assert_eq!(test.token_amount(&pool.dead_shares), MIN_LP_SUPPLY as u64);
+
+    // the mean is rounded down
+    let pool = test.create_pool(constant_product(), 3_000_000, 5_000_000);
+    assert_eq!(test.mint(&pool.pool_mint).supply, 3_872_983);
+}
+
+#[test]
+fn small_first_deposits_mint_the_legacy_initial_supply() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let initial_supply = test.global_state().initial_supply;
+    let reserve = MIN_LP_SUPPLY as u64 - 1;
+    let pool = test.create_pool(constant_product(), reserve, reserve);
+    assert_eq!(test.mint(&pool.pool_mint).supply, initial_supply);
+
+    // a mean of MIN_LP_SUPPLY would leave nothing to the provider once
+    // locked
+    let pool = test.create_pool(
+        constant_product(),
+        MIN_LP_SUPPLY as u64,
+        MIN_LP_SUPPLY as u64,
+    );
+    assert_eq!(test.mint(&pool.pool_mint).supply, initial_supply);
+    let reserve = MIN_LP_SUPPLY as u64 + 1;
+    let pool = test.create_pool(constant_product(), reserve, reserve);
+    assert_eq!(test.mint(&pool.pool_mint).supply, reserve);
+    assert_eq!(test.token_amount(&pool.provider_pool_tokens), 1);
+}
+
+#[test]
+fn basket_pools_mint_the_geometric_mean_of_their_reserves() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let initial_supply = test.global_state().initial_supply;
+    let mut basket = |reserves: &[u64]| {
+        let basket = Pubkey::new_unique();
+        let authority = Pubkey::find_program_address(&[basket.as_ref()], &atlas_swap::id()).0;
+        let pool_mint = test.add_mint(&authority, 6);
+        let destination = test.add_token_account(&pool_mint, &Pubkey::new_unique(), 0);
+        let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+        let vaults: Vec<Pubkey> = reserves
+            .iter()
+            .map(|reserve| {
+                let mint = test.add_mint(&Pubkey::new_unique(), 6);
+                test.add_token_account(&mint, &authority, *reserve)
+            })
+            .collect();
+        test.add(basket, atlas_swap::id(), vec![0; BasketPool::LEN]);
+        test.process(
+            &initialize_basket(
+                &atlas_swap::id(),
+                &spl_token::id(),
+                &basket,
+                &authority,
+                &test.state,
+                &pool_mint,
+                &destination,
+                &dead_shares,
+                &vaults,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        (
+            test.mint(&pool_mint).supply,
+            test.token_amount(&destination),
+            test.token_amount(&dead_shares),
+        )
+    };
+
+    assert_eq!(
+        basket(&[1_000_000_000, 8_000_000_000, 125_000_000]),
+        (
+            1_000_000_000,
+            1_000_000_000 - MIN_LP_SUPPLY as u64,
+            MIN_LP_SUPPLY as u64
+        )
+    );
+    assert_eq!(
+        basket(&[1_000, 1_000, 1_000]),
+        (
+            initial_supply,
+            initial_supply - MIN_LP_SUPPLY as u64,
+            MIN_LP_SUPPLY as u64
+        )
+    );
+}
@@ -0,0 +1,87 @@
+//! Strict parsing of instruction data: truncated data is rejected without
+//! panicking, data after the last field is rejected and every payload reads
+//! back what it packs.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::{FeeDiscount, Fees},
+    },
+    instruction::{Initialize, SetGlobalState, Stake, SwapInstruction},
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+
+#[test]
+fn truncated_data_of_every_tag_is_rejected_without_panicking() {
+    for tag in 0..=u8::MAX {
+        for len in 0..128 {
+            let mut data = vec![tag];
+            data.extend((0..len).map(|i| i as u8));
+            let instruction = std::panic::catch_unwind(|| SwapInstruction::unpack(&data))
+                .unwrap_or_else(|_| panic!("tag {} with {} bytes panicked", tag, len));
+            if let Ok(instruction) = instruction {
+                assert_eq!(
+                    SwapInstruction::unpack(&instruction.pack().unwrap()).unwrap(),
+                    instruction
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn initialize_payload_with_fee_owner_round_trips() {
+    let instruction = SwapInstruction::Initialize(Initialize {
+        swap_curve: SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        fee_owner: Some(Pubkey::new_unique()),
+    });
+    let packed = instruction.pack().unwrap();
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+    assert!(SwapInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+}
+
+#[test]
+fn trailing_data_is_rejected() {
+    let instructions = [
+        SwapInstruction::SetGlobalStateInstruction(SetGlobalState {
+            owner: Pubkey::new_unique(),
+            fee_owner: Pubkey::new_unique(),
+            initial_supply: 1_000_000_000,
+            lp_decimals: 6,
+            fees: Fees::default(),
+            fee_discount: FeeDiscount::default(),
+            quoter: Pubkey::new_unique(),
+        }),
+        SwapInstruction::Stake(Stake { amount: 10 }),
+        SwapInstruction::UnlockLiquidity,
+        SwapInstruction::Initialize(Initialize {
+            swap_curve: SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            fee_owner: Some(Pubkey::new_unique()),
+        }),
+    ];
+    for instruction in instructions.iter() {
+        let mut packed = instruction.pack().unwrap();
+        assert_eq!(&SwapInstruction::unpack(&packed).unwrap(), instruction);
+        packed.push(0);
+        assert!(SwapInstruction::unpack(&packed).is_err());
+    }
+}
+
+#[test]
+fn fees_reject_slices_of_another_length() {
+    let mut packed = vec![0u8; Fees::LEN + 1];
+    packed[..Fees::LEN].copy_from_slice(&{
+        let mut fees = [0u8; Fees::LEN];
+        Fees::default().pack_into_slice(&mut fees);
+        fees
+    });
+    assert!(Fees::unpack_from_slice(&packed).is_err());
+    assert!(Fees::unpack_from_slice(&packed[..Fees::LEN]).is_ok());
+}
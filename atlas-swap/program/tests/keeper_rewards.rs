@@ -0,0 +1,113 @@
+//! Permissionless cranks of a pool pay their keeper the keeper reward out
+//! of the pool's protocol-owned liquidity, at most once per keeper interval.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{set_keeper_reward, sync, KeeperRewardAccounts, SetKeeperReward},
+    pda::find_protocol_liquidity_authority,
+};
+use common::{set_clock, ProgramTest};
+use solana_program::pubkey::Pubkey;
+
+const RESERVE: u64 = 1_000_000_000;
+const PROTOCOL_LIQUIDITY: u64 = 250;
+const REWARD: u64 = 100;
+const INTERVAL: u64 = 10;
+
+#[test]
+fn cranks_pay_the_keeper_once_per_interval() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let protocol_liquidity_authority = find_protocol_liquidity_authority(&atlas_swap::id()).0;
+    let protocol_liquidity = test.add_token_account(
+        &pool.pool_mint,
+        &protocol_liquidity_authority,
+        PROTOCOL_LIQUIDITY,
+    );
+    let keeper = test.add_token_account(&pool.pool_mint, &Pubkey::new_unique(), 0);
+    let keeper_accounts = KeeperRewardAccounts {
+        global_state_pubkey: test.state,
+        protocol_liquidity_authority_pubkey: protocol_liquidity_authority,
+        protocol_liquidity_pubkey: protocol_liquidity,
+        keeper_pubkey: keeper,
+        token_program_id: spl_token::id(),
+    };
+    let crank = |keeper_accounts: Option<&KeeperRewardAccounts>| {
+        sync(
+            &atlas_swap::id(),
+            &pool.swap,
+            &pool.token_a,
+            &pool.token_b,
+            keeper_accounts,
+        )
+        .unwrap()
+    };
+
+    let set_reward = |owner: &Pubkey| {
+        set_keeper_reward(
+            &atlas_swap::id(),
+            &test.state,
+            owner,
+            SetKeeperReward {
+                keeper_reward: REWARD,
+                keeper_interval_slots: INTERVAL,
+            },
+        )
+        .unwrap()
+    };
+    let intruder_set = set_reward(&Pubkey::new_unique());
+    let owner_set = set_reward(&test.owner);
+    assert_eq!(
+        test.process(&intruder_set),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    // no reward before it is set
+    set_clock(100, 0);
+    test.process(&crank(Some(&keeper_accounts))).unwrap();
+    assert_eq!(test.token_amount(&keeper), 0);
+    test.process(&owner_set).unwrap();
+
+    test.process(&crank(Some(&keeper_accounts))).unwrap();
+    assert_eq!(test.token_amount(&keeper), REWARD);
+    assert_eq!(test.swap_v2(&pool.swap).keeper_slot, 100);
+    // a crank within the interval goes through, unrewarded
+    set_clock(100 + INTERVAL - 1, 0);
+    test.process(&crank(Some(&keeper_accounts))).unwrap();
+    assert_eq!(test.token_amount(&keeper), REWARD);
+    set_clock(100 + INTERVAL, 0);
+    test.process(&crank(Some(&keeper_accounts))).unwrap();
+    assert_eq!(test.token_amount(&keeper), 2 * REWARD);
+    // and so does a crank without the reward accounts
+    set_clock(100 + 2 * INTERVAL, 0);
+    test.process(&crank(None)).unwrap();
+    assert_eq!(test.token_amount(&keeper), 2 * REWARD);
+
+    // the reward is paid out of the protocol-owned liquidity only
+    let foreign = test.add_token_account(&pool.pool_mint, &Pubkey::new_unique(), REWARD);
+    let foreign_accounts = KeeperRewardAccounts {
+        protocol_liquidity_pubkey: foreign,
+        ..keeper_accounts.clone()
+    };
+    assert_eq!(
+        test.process(&crank(Some(&foreign_accounts))),
+        Err(SwapError::InvalidOwner.into())
+    );
+    // up to what is left of it
+    test.process(&crank(Some(&keeper_accounts))).unwrap();
+    assert_eq!(test.token_amount(&keeper), PROTOCOL_LIQUIDITY);
+    assert_eq!(test.token_amount(&protocol_liquidity), 0);
+}
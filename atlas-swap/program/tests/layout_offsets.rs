@@ -0,0 +1,110 @@
+//! The exported byte offsets point at their fields in packed accounts, so
+//! getProgramAccounts filters built from them match.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_price::ConstantPriceCurve,
+    },
+    state::*,
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use std::convert::TryInto;
+
+fn field(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+fn swap_curve() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantPrice,
+        calculator: ConstantPriceCurve { token_b_price: 1 }.into(),
+    }
+}
+
+#[test]
+fn swap_offsets_hold_for_both_versions() {
+    let (token_program_id, token_a, token_b) =
+        (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (pool_mint, token_a_mint, token_b_mint) =
+        (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let (gate_mint, fee_owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut v1 = vec![0u8; SWAP_V1_ACCOUNT_LEN];
+    SwapVersion::pack(
+        SwapVersion::SwapV1(SwapV1 {
+            is_initialized: true,
+            nonce: 255,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            swap_curve: swap_curve(),
+        }),
+        &mut v1,
+    )
+    .unwrap();
+    let mut v2 = vec![0u8; SWAP_V2_ACCOUNT_LEN];
+    SwapVersion::pack(
+        SwapVersion::SwapV2(SwapV2 {
+            is_initialized: true,
+            nonce: 255,
+            token_program_id,
+            token_a,
+            token_b,
+            pool_mint,
+            token_a_mint,
+            token_b_mint,
+            swap_curve: swap_curve(),
+            deprecated: true,
+            gate_mint,
+            paused: true,
+            fee_owner,
+            ..SwapV2::default()
+        }),
+        &mut v2,
+    )
+    .unwrap();
+    assert_eq!(SWAP_V2_ACCOUNT_LEN, SwapVersion::LATEST_LEN);
+
+    for (data, version) in [(&v1, SWAP_V1_VERSION), (&v2, SWAP_V2_VERSION)] {
+        assert_eq!(data[SWAP_VERSION_OFFSET], version);
+        assert_eq!(field(data, SWAP_V1_TOKEN_PROGRAM_ID_OFFSET), token_program_id);
+        assert_eq!(field(data, SWAP_V1_TOKEN_A_OFFSET), token_a);
+        assert_eq!(field(data, SWAP_V1_TOKEN_B_OFFSET), token_b);
+        assert_eq!(field(data, SWAP_V1_POOL_MINT_OFFSET), pool_mint);
+        assert_eq!(field(data, SWAP_V1_TOKEN_A_MINT_OFFSET), token_a_mint);
+        assert_eq!(field(data, SWAP_V1_TOKEN_B_MINT_OFFSET), token_b_mint);
+        assert_eq!(data[SWAP_V1_CURVE_TYPE_OFFSET], CurveType::ConstantPrice as u8);
+    }
+    assert_eq!(v2[SWAP_V2_DEPRECATED_OFFSET], 1);
+    assert_eq!(field(&v2, SWAP_V2_GATE_MINT_OFFSET), gate_mint);
+    assert_eq!(v2[SWAP_V2_PAUSED_OFFSET], 1);
+    assert_eq!(field(&v2, SWAP_V2_FEE_OWNER_OFFSET), fee_owner);
+}
+
+#[test]
+fn global_state_offsets_hold_for_the_latest_layout() {
+    let state = GlobalState {
+        is_initialized: true,
+        owner: Pubkey::new_unique(),
+        fee_owner: Pubkey::new_unique(),
+        quoter: Pubkey::new_unique(),
+        buyback_mint: Pubkey::new_unique(),
+        ..GlobalState::default()
+    };
+    let mut data = vec![0u8; GlobalStateVersion::LATEST_LEN];
+    GlobalStateVersion::pack(&state, &mut data).unwrap();
+
+    assert_eq!(data[0], GLOBAL_STATE_V2_VERSION);
+    assert_eq!(field(&data, GLOBAL_STATE_OWNER_OFFSET), state.owner);
+    assert_eq!(field(&data, GLOBAL_STATE_FEE_OWNER_OFFSET), state.fee_owner);
+    assert_eq!(field(&data, GLOBAL_STATE_QUOTER_OFFSET), state.quoter);
+    assert_eq!(field(&data, GLOBAL_STATE_BUYBACK_MINT_OFFSET), state.buyback_mint);
+    // the legacy layout is the same without the version byte
+    let mut legacy = vec![0u8; GlobalState::LEN];
+    state.pack_into_slice(&mut legacy);
+    assert_eq!(field(&legacy, GLOBAL_STATE_OWNER_OFFSET - 1), state.owner);
+}
@@ -0,0 +1,288 @@
+//! Limit orders are filled under the rules of a regular swap, the owner
+//! fee shared with the fee recipients, and the input the swap rounds away
+//! refunded to the order owner. Orders out of the money fail to fill, and
+//! their owner cancels them for the whole escrow.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::{FeeRecipient, Fees},
+    },
+    error::SwapError,
+    instruction::{cancel_limit_order, fill_limit_order, place_limit_order, PlaceLimitOrder},
+    pda::find_limit_order,
+};
+use common::{ProgramTest, TestPool};
+use solana_program::pubkey::Pubkey;
+
+const RESERVE_A: u64 = 1_000_000_000;
+const RESERVE_B: u64 = 1_000_000;
+const AMOUNT_IN: u64 = 1_500_000;
+const KEEPER_FEE: u64 = 10;
+
+#[test]
+fn fill_pays_the_fees_of_a_swap_and_refunds_the_owner() {
+    let fees = Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(swap_curve.clone(), RESERVE_A, RESERVE_B);
+    let fee_recipient = FeeRecipient {
+        owner: Pubkey::new_unique(),
+        weight_bps: 5_000,
+    };
+    test.set_global_state(|state| state.fee_recipients[0] = fee_recipient);
+
+    let owner = test.add_payer(1_000_000_000);
+    let source = test.add_token_account(&pool.token_a_mint, &owner, AMOUNT_IN + KEEPER_FEE);
+    let destination = test.add_token_account(&pool.token_b_mint, &owner, 0);
+    let order = find_limit_order(&atlas_swap::id(), &pool.swap, &owner, 0).0;
+    let order_vault = test.add_token_account(&pool.token_a_mint, &order, 0);
+    test.process(
+        &place_limit_order(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &order,
+            &order_vault,
+            &owner,
+            &source,
+            &destination,
+            PlaceLimitOrder {
+                order_id: 0,
+                amount_in: AMOUNT_IN,
+                minimum_amount_out: 1,
+                keeper_fee: KEEPER_FEE,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(test.token_amount(&source), 0);
+
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let recipient_account = test.add_token_account(&pool.token_a_mint, &fee_recipient.owner, 0);
+    let keeper = test.add_token_account(&pool.token_a_mint, &Pubkey::new_unique(), 0);
+    test.process(
+        &fill_limit_order(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &test.state,
+            &order,
+            &order_vault,
+            &owner,
+            &pool.token_a,
+            &pool.token_b,
+            &destination,
+            &pool.pool_mint,
+            &fee_account,
+            &keeper,
+            &source,
+            None,
+            None,
+            &[recipient_account],
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let quote = get_swap_quote(
+        RESERVE_A,
+        RESERVE_B,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    let owner_fee = quote.owner_fee as u64;
+    assert!(owner_fee > 0);
+    assert!(quote.source_amount_swapped < u128::from(AMOUNT_IN));
+    assert_eq!(test.token_amount(&recipient_account), owner_fee / 2);
+    assert_eq!(test.token_amount(&fee_account), owner_fee - owner_fee / 2);
+    assert_eq!(test.token_amount(&keeper), KEEPER_FEE);
+    assert_eq!(
+        test.token_amount(&source),
+        AMOUNT_IN - quote.source_amount_swapped as u64
+    );
+    assert_eq!(
+        test.token_amount(&destination),
+        quote.destination_amount_swapped as u64
+    );
+    assert_eq!(test.accounts[&order].lamports, 0);
+}
+
+/// An order of `AMOUNT_IN` token A for at least `minimum_amount_out` token
+/// B, its owner, source, destination, order and vault
+fn place_order(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    minimum_amount_out: u64,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey, Pubkey) {
+    let owner = test.add_payer(1_000_000_000);
+    let source = test.add_token_account(&pool.token_a_mint, &owner, AMOUNT_IN + KEEPER_FEE);
+    let destination = test.add_token_account(&pool.token_b_mint, &owner, 0);
+    let order = find_limit_order(&atlas_swap::id(), &pool.swap, &owner, 0).0;
+    let order_vault = test.add_token_account(&pool.token_a_mint, &order, 0);
+    test.process(
+        &place_limit_order(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &order,
+            &order_vault,
+            &owner,
+            &source,
+            &destination,
+            PlaceLimitOrder {
+                order_id: 0,
+                amount_in: AMOUNT_IN,
+                minimum_amount_out,
+                keeper_fee: KEEPER_FEE,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    (owner, source, destination, order, order_vault)
+}
+
+#[test]
+fn fill_fails_until_the_limit_price_is_reached() {
+    let fees = Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(swap_curve.clone(), RESERVE_A, RESERVE_B);
+    let quote = get_swap_quote(
+        RESERVE_A,
+        RESERVE_B,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    let (owner, source, destination, order, order_vault) = place_order(
+        &mut test,
+        &pool,
+        quote.destination_amount_swapped as u64 + 1,
+    );
+
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let keeper = test.add_token_account(&pool.token_a_mint, &Pubkey::new_unique(), 0);
+    let fill = fill_limit_order(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        &order,
+        &order_vault,
+        &owner,
+        &pool.token_a,
+        &pool.token_b,
+        &destination,
+        &pool.pool_mint,
+        &fee_account,
+        &keeper,
+        &source,
+        None,
+        None,
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        test.process(&fill),
+        Err(SwapError::LimitPriceNotReached.into())
+    );
+    assert_eq!(test.token_amount(&order_vault), AMOUNT_IN + KEEPER_FEE);
+    assert_eq!(test.token_amount(&destination), 0);
+
+    // a swap the other way moves the price past the limit
+    let trader = test.add_user(&pool, 0, RESERVE_B / 10);
+    let swap = test.swap_instruction(&pool, &trader, false, RESERVE_B / 10, 1, None);
+    test.process(&swap).unwrap();
+    test.process(&fill).unwrap();
+    assert!(test.token_amount(&destination) > quote.destination_amount_swapped as u64);
+    assert_eq!(test.token_amount(&keeper), KEEPER_FEE);
+}
+
+#[test]
+fn cancel_refunds_the_escrow_to_the_owner() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE_A,
+        RESERVE_B,
+    );
+    let (owner, source, _destination, order, order_vault) =
+        place_order(&mut test, &pool, RESERVE_B);
+    assert_eq!(test.token_amount(&source), 0);
+
+    let intruder = test.add_payer(0);
+    let intruder_account = test.add_token_account(&pool.token_a_mint, &intruder, 0);
+    assert_eq!(
+        test.process(
+            &cancel_limit_order(
+                &atlas_swap::id(),
+                &spl_token::id(),
+                &pool.swap,
+                &order,
+                &order_vault,
+                &intruder,
+                &intruder_account,
+                None,
+            )
+            .unwrap()
+        ),
+        Err(SwapError::InvalidOwner.into())
+    );
+
+    let lamports = test.accounts[&owner].lamports;
+    let rent = test.accounts[&order].lamports + test.accounts[&order_vault].lamports;
+    test.process(
+        &cancel_limit_order(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &order,
+            &order_vault,
+            &owner,
+            &source,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(test.token_amount(&source), AMOUNT_IN + KEEPER_FEE);
+    assert_eq!(test.accounts[&order].lamports, 0);
+    assert_eq!(test.accounts[&order_vault].lamports, 0);
+    assert_eq!(test.accounts[&owner].lamports, lamports + rent);
+}
@@ -0,0 +1,154 @@
+//! Pool tokens locked in the escrow of their owner stay there until the
+//! unlock timestamp, which a top-up may extend but never bring forward.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{lock_liquidity, unlock_liquidity, LockLiquidity},
+    pda::find_liquidity_lock,
+    state::LiquidityLock,
+};
+use common::{set_clock, ProgramTest};
+use solana_program::program_pack::Pack;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn locked_pool_tokens_are_released_at_the_unlock_timestamp() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let owner = test.add_payer(1_000_000_000);
+    let source = test.add_token_account(&pool.pool_mint, &owner, 1_000);
+    let destination = test.add_token_account(&pool.pool_mint, &owner, 0);
+    let lock = find_liquidity_lock(&atlas_swap::id(), &pool.swap, &owner).0;
+    let lock_vault = test.add_token_account(&pool.pool_mint, &lock, 0);
+    let lock_instruction = |amount, unlock_timestamp| {
+        lock_liquidity(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &lock,
+            &lock_vault,
+            &owner,
+            &source,
+            LockLiquidity {
+                amount,
+                unlock_timestamp,
+            },
+        )
+        .unwrap()
+    };
+    let unlock = unlock_liquidity(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &lock,
+        &lock_vault,
+        &owner,
+        &destination,
+    )
+    .unwrap();
+
+    set_clock(0, 100);
+    assert_eq!(
+        test.process(&lock_instruction(600, 100)),
+        Err(SwapError::InvalidUnlockTimestamp.into())
+    );
+    test.process(&lock_instruction(600, 200)).unwrap();
+    assert_eq!(test.accounts[&lock].owner, atlas_swap::id());
+    assert_eq!(test.token_amount(&lock_vault), 600);
+    assert_eq!(test.token_amount(&source), 400);
+
+    // a top-up may only push the unlock timestamp back
+    assert_eq!(
+        test.process(&lock_instruction(100, 150)),
+        Err(SwapError::InvalidUnlockTimestamp.into())
+    );
+    test.process(&lock_instruction(100, 300)).unwrap();
+    let state = LiquidityLock::unpack_from_slice(&test.accounts[&lock].data).unwrap();
+    assert_eq!(state.owner, owner);
+    assert_eq!(state.amount, 700);
+    assert_eq!(state.unlock_timestamp, 300);
+
+    set_clock(0, 299);
+    assert_eq!(
+        test.process(&unlock),
+        Err(SwapError::LiquidityLocked.into())
+    );
+    set_clock(0, 300);
+    test.process(&unlock).unwrap();
+    assert_eq!(test.token_amount(&destination), 700);
+    assert_eq!(test.token_amount(&lock_vault), 0);
+    let state = LiquidityLock::unpack_from_slice(&test.accounts[&lock].data).unwrap();
+    assert_eq!(state.amount, 0);
+}
+
+#[test]
+fn only_the_lock_owner_unlocks() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let owner = test.add_payer(1_000_000_000);
+    let source = test.add_token_account(&pool.pool_mint, &owner, 1_000);
+    let lock = find_liquidity_lock(&atlas_swap::id(), &pool.swap, &owner).0;
+    let lock_vault = test.add_token_account(&pool.pool_mint, &lock, 0);
+    set_clock(0, 100);
+    test.process(
+        &lock_liquidity(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &lock,
+            &lock_vault,
+            &owner,
+            &source,
+            LockLiquidity {
+                amount: 1_000,
+                unlock_timestamp: 200,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    set_clock(0, 200);
+    let thief = test.add_payer(0);
+    let thief_account = test.add_token_account(&pool.pool_mint, &thief, 0);
+    let unlock = unlock_liquidity(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &lock,
+        &lock_vault,
+        &thief,
+        &thief_account,
+    )
+    .unwrap();
+    assert_eq!(test.process(&unlock), Err(SwapError::InvalidOwner.into()));
+    assert_eq!(test.token_amount(&lock_vault), 1_000);
+}
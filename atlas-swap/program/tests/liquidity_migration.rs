@@ -0,0 +1,167 @@
+//! MigrateLiquidity moves liquidity between two pools of the same tokens in
+//! one instruction, the slippage limit bounding the pool tokens minted.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        migrate_liquidity, MigrateLiquidity, MigrateLiquidityDestination, MigrateLiquiditySwap,
+    },
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{entrypoint::ProgramResult, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000;
+const POOL_SUPPLY: u64 = 1_000_000;
+
+/// Two pools of the same tokens without fees, each of POOL_SUPPLY pool
+/// tokens, the second pricing token A at twice the first, and a user
+/// holding pool tokens of the first
+struct TestMigration {
+    test: ProgramTest,
+    user: Pubkey,
+    user_token_a: Pubkey,
+    user_token_b: Pubkey,
+    pool_fee: Pubkey,
+    from: TestPool,
+    to: TestPool,
+    to_pool_tokens: Pubkey,
+}
+
+impl TestMigration {
+    fn new() -> Self {
+        let mut test = ProgramTest::new(Fees {
+            fee_denominator: 10_000,
+            ..Fees::default()
+        });
+        let token_a_mint = test.add_mint(&Pubkey::new_unique(), 6);
+        let token_b_mint = test.add_mint(&Pubkey::new_unique(), 6);
+        let curve = || SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        };
+        let from = test.create_pool_of(curve(), &token_a_mint, &token_b_mint, RESERVE, RESERVE);
+        let to = test.create_pool_of(curve(), &token_a_mint, &token_b_mint, RESERVE, 2 * RESERVE);
+        test.set_mint(&to.pool_mint, |mint| mint.supply = POOL_SUPPLY);
+        // the provider of the first pool migrates
+        let user = from.provider;
+        test.set_token_account(&from.provider_pool_tokens, |account| {
+            account.amount = POOL_SUPPLY / 2
+        });
+        // the fee account of token A, in excess of the second pool's ratio
+        let fee_owner = test.fee_owner;
+        let pool_fee = test.add_token_account(&token_a_mint, &fee_owner, 0);
+        Self {
+            user,
+            user_token_a: test.add_token_account(&token_a_mint, &user, 0),
+            user_token_b: test.add_token_account(&token_b_mint, &user, 0),
+            to_pool_tokens: test.add_token_account(&to.pool_mint, &user, 0),
+            pool_fee,
+            from,
+            to,
+            test,
+        }
+    }
+
+    fn balance(&self, key: &Pubkey) -> u64 {
+        self.test.token_amount(key)
+    }
+
+    /// Migrates pool tokens of the first pool into the second
+    fn migrate(
+        &mut self,
+        pool_token_amount: u64,
+        minimum_pool_token_amount: u64,
+        swap_imbalance: bool,
+    ) -> ProgramResult {
+        let swap = MigrateLiquiditySwap {
+            pool_fee_pubkey: self.pool_fee,
+            oracle_pubkey: None,
+            protocol_liquidity_pubkey: None,
+            fee_recipient_pubkeys: vec![],
+        };
+        let instruction = migrate_liquidity(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &self.from.swap,
+            &self.from.authority,
+            &self.user,
+            &self.test.state,
+            &self.from.pool_mint,
+            &self.from.provider_pool_tokens,
+            &self.from.token_a,
+            &self.from.token_b,
+            &self.user_token_a,
+            &self.user_token_b,
+            None,
+            None,
+            &MigrateLiquidityDestination {
+                swap_pubkey: self.to.swap,
+                authority_pubkey: self.to.authority,
+                swap_token_a_pubkey: self.to.token_a,
+                swap_token_b_pubkey: self.to.token_b,
+                pool_mint_pubkey: self.to.pool_mint,
+                destination_pubkey: self.to_pool_tokens,
+            },
+            if swap_imbalance { Some(&swap) } else { None },
+            None,
+            None,
+            MigrateLiquidity {
+                pool_token_amount,
+                minimum_pool_token_amount,
+                swap_imbalance,
+            },
+        )
+        .unwrap();
+        self.test.process(&instruction)
+    }
+}
+
+#[test]
+fn migration_without_swap_leaves_the_imbalance_over() {
+    let mut migration = TestMigration::new();
+    migration.migrate(100_000, 50_000, false).unwrap();
+
+    assert_eq!(
+        migration.balance(&migration.from.provider_pool_tokens),
+        POOL_SUPPLY / 2 - 100_000
+    );
+    assert_eq!(migration.balance(&migration.to_pool_tokens), 50_000);
+    assert_eq!(migration.balance(&migration.to.token_a), RESERVE + 50_000);
+    assert_eq!(
+        migration.balance(&migration.to.token_b),
+        2 * RESERVE + 100_000
+    );
+    assert_eq!(migration.balance(&migration.user_token_a), 50_000);
+    assert_eq!(migration.balance(&migration.user_token_b), 0);
+}
+
+#[test]
+fn migration_swapping_the_imbalance_mints_more_pool_tokens() {
+    let mut migration = TestMigration::new();
+    migration.migrate(100_000, 74_000, true).unwrap();
+
+    // the swap is priced linearly, leaving a little of one token over
+    assert_eq!(
+        migration.balance(&migration.from.provider_pool_tokens),
+        POOL_SUPPLY / 2 - 100_000
+    );
+    assert!(migration.balance(&migration.to_pool_tokens) >= 74_000);
+    assert!(migration.balance(&migration.user_token_a) < 100);
+    assert!(migration.balance(&migration.user_token_b) < 100);
+}
+
+#[test]
+fn migration_below_the_minimum_pool_tokens_fails() {
+    let mut migration = TestMigration::new();
+    assert_eq!(
+        migration.migrate(100_000, 50_001, false),
+        Err(SwapError::ExceededSlippage.into())
+    );
+}
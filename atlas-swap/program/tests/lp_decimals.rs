@@ -0,0 +1,92 @@
+//! Each pool chooses the decimals of its pool mint, up to MAX_LP_DECIMALS,
+//! whatever the lp_decimals of the program state.
+
+mod common;
+
+use atlas_swap::{
+    constraints::{default_lp_decimals, MAX_LP_DECIMALS},
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::initialize,
+    state::SwapVersion,
+};
+use common::ProgramTest;
+use solana_program::{entrypoint::ProgramResult, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+
+/// Initializes a pool of a 6 and an 8 decimals token whose pool mint has
+/// `lp_decimals`, and returns the pool mint with the result
+fn initialize_pool(test: &mut ProgramTest, lp_decimals: u8) -> (Pubkey, ProgramResult) {
+    let program_id = atlas_swap::id();
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &program_id).0;
+    let token_a_mint = test.add_mint(&Pubkey::new_unique(), 6);
+    let token_b_mint = test.add_mint(&Pubkey::new_unique(), 8);
+    let pool_mint = test.add_mint(&authority, lp_decimals);
+    let token_a = test.add_token_account(&token_a_mint, &authority, RESERVE);
+    let token_b = test.add_token_account(&token_b_mint, &authority, RESERVE);
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &Pubkey::new_unique(), 0);
+    let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+    test.add(swap, program_id, vec![0; SwapVersion::LATEST_LEN]);
+    let result = test.process(
+        &initialize(
+            &program_id,
+            &spl_token::id(),
+            &swap,
+            &authority,
+            &test.state,
+            &token_a,
+            &token_b,
+            &pool_mint,
+            &provider_pool_tokens,
+            &token_a_mint,
+            &token_b_mint,
+            &dead_shares,
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            None,
+            None,
+        )
+        .unwrap(),
+    );
+    (pool_mint, result)
+}
+
+#[test]
+fn pools_choose_their_lp_decimals() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    assert_eq!(test.global_state().lp_decimals, 6);
+
+    let lp_decimals = default_lp_decimals(6, 8);
+    assert_eq!(lp_decimals, 8);
+    let (pool_mint, result) = initialize_pool(&mut test, lp_decimals);
+    result.unwrap();
+    assert_eq!(test.mint(&pool_mint).decimals, 8);
+    assert!(test.mint(&pool_mint).supply > 0);
+
+    let (_, result) = initialize_pool(&mut test, 0);
+    result.unwrap();
+    let (_, result) = initialize_pool(&mut test, MAX_LP_DECIMALS);
+    result.unwrap();
+}
+
+#[test]
+fn lp_decimals_are_capped() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    assert_eq!(default_lp_decimals(12, 18), MAX_LP_DECIMALS);
+    let (_, result) = initialize_pool(&mut test, MAX_LP_DECIMALS + 1);
+    assert_eq!(result, Err(SwapError::MismatchDecimalValidation.into()));
+}
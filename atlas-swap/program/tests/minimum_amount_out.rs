@@ -0,0 +1,65 @@
+//! Slippage limits derived from pool state hold for a swap at the quoted
+//! reserves and are never left at zero.
+
+use atlas_swap::curve::{
+    base::{get_minimum_amount_out, get_swap_quote, CurveType, SwapCurve},
+    calculator::TradeDirection,
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+};
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+fn fees() -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        minimum_fee: 3,
+        ..Fees::default()
+    }
+}
+
+#[test]
+fn minimum_amount_out_takes_the_tolerance_off_the_quote() {
+    let curve = constant_product();
+    let quote = get_swap_quote(1_000_000, 3_000_000, 10_000, TradeDirection::AtoB, &fees(), &curve)
+        .unwrap()
+        .destination_amount_swapped as u64;
+
+    let exact = |slippage_bps| {
+        get_minimum_amount_out(
+            1_000_000,
+            3_000_000,
+            10_000,
+            TradeDirection::AtoB,
+            &fees(),
+            &curve,
+            slippage_bps,
+        )
+    };
+    assert_eq!(exact(0), Some(quote));
+    // rounded up, the trader's way
+    assert_eq!(exact(50), Some((quote * 9_950).div_ceil(10_000)));
+    assert!(exact(50).unwrap() < quote);
+    assert_eq!(exact(10_001), None);
+}
+
+#[test]
+fn minimum_amount_out_is_never_zero() {
+    let curve = constant_product();
+    assert_eq!(
+        get_minimum_amount_out(1_000_000, 1_000_000, 10_000, TradeDirection::BtoA, &fees(), &curve, 10_000),
+        Some(1)
+    );
+    // the minimum fee takes all of a dust swap
+    assert_eq!(
+        get_minimum_amount_out(1_000_000, 1_000_000, 3, TradeDirection::AtoB, &fees(), &curve, 100),
+        None
+    );
+}
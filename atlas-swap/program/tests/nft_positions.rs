@@ -0,0 +1,255 @@
+//! Deposits into a pool giving out position NFTs open an LP position owned
+//! by a new NFT, and whoever holds the NFT owns the position.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        claim_fees, decrease_position, deposit_all_token_types, set_claimable_fees,
+        set_nft_positions, DecreasePosition, DepositAllTokenTypes, SetClaimableFees,
+        SetNftPositions,
+    },
+    pda::find_lp_position,
+    state::LpPosition,
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const POOL_TOKENS: u64 = 100_000_000;
+
+/// A deposit of `POOL_TOKENS` minted into `destination`, opening the position
+/// of `nft_mint`
+fn deposit(
+    test: &ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    destination: &Pubkey,
+    nft_mint: &Pubkey,
+    nft_destination: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let mut deposit = deposit_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &test.state,
+        &user.wallet,
+        &user.token_a,
+        &user.token_b,
+        &pool.token_a,
+        &pool.token_b,
+        &pool.pool_mint,
+        destination,
+        None,
+        None,
+        DepositAllTokenTypes {
+            pool_token_amount: POOL_TOKENS,
+            maximum_token_a_amount: u64::MAX,
+            maximum_token_b_amount: u64::MAX,
+            max_ratio_deviation_bps: None,
+        },
+    )
+    .unwrap();
+    let position = find_lp_position(&atlas_swap::id(), &pool.swap, nft_mint).0;
+    deposit.accounts.extend([
+        AccountMeta::new(position, false),
+        AccountMeta::new(*nft_mint, false),
+        AccountMeta::new(*nft_destination, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ]);
+    deposit
+}
+
+#[test]
+fn deposits_open_positions_owned_by_their_nft() {
+    let fees = Fees {
+        constant_product_return_fee_numerator: 30,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees);
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let position_vault = test.add_token_account(&pool.pool_mint, &pool.authority, 0);
+    let set_nft = |owner: &Pubkey| {
+        set_nft_positions(
+            &atlas_swap::id(),
+            &pool.swap,
+            owner,
+            &test.state,
+            SetNftPositions {
+                nft_positions: true,
+            },
+        )
+        .unwrap()
+    };
+    let intruder_set = set_nft(&Pubkey::new_unique());
+    let owner_set = set_nft(&test.owner);
+    // the pool tokens of NFT positions are held in the position vault
+    assert_eq!(
+        test.process(&owner_set),
+        Err(SwapError::ClaimableFeesDisabled.into())
+    );
+    test.process(
+        &set_claimable_fees(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            &position_vault,
+            SetClaimableFees {
+                claimable_fees: true,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        test.process(&intruder_set),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    test.process(&owner_set).unwrap();
+
+    let user = test.add_user(&pool, 2 * POOL_TOKENS, 2 * POOL_TOKENS);
+    let payer = test.add_payer(1_000_000_000);
+    let nft_mint = test.add_mint(&pool.authority, 0);
+    let nft_account = test.add_token_account(&nft_mint, &user.wallet, 0);
+    // the pool tokens are not minted to the depositor
+    let to_user = deposit(
+        &test,
+        &pool,
+        &user,
+        &user.pool_tokens,
+        &nft_mint,
+        &nft_account,
+        &payer,
+    );
+    assert_eq!(
+        test.process(&to_user),
+        Err(SwapError::IncorrectSwapAccount.into())
+    );
+    let foreign_mint = test.add_mint(&Pubkey::new_unique(), 0);
+    let foreign_account = test.add_token_account(&foreign_mint, &user.wallet, 0);
+    let foreign = deposit(
+        &test,
+        &pool,
+        &user,
+        &position_vault,
+        &foreign_mint,
+        &foreign_account,
+        &payer,
+    );
+    assert_eq!(
+        test.process(&foreign),
+        Err(SwapError::InvalidPositionMint.into())
+    );
+
+    let to_position = deposit(
+        &test,
+        &pool,
+        &user,
+        &position_vault,
+        &nft_mint,
+        &nft_account,
+        &payer,
+    );
+    test.process(&to_position).unwrap();
+    assert_eq!(test.token_amount(&position_vault), POOL_TOKENS);
+    assert_eq!(test.token_amount(&user.pool_tokens), 0);
+    assert_eq!(test.token_amount(&nft_account), 1);
+    let position = find_lp_position(&atlas_swap::id(), &pool.swap, &nft_mint).0;
+    let lp_position = LpPosition::unpack(&test.accounts[&position].data).unwrap();
+    assert!(lp_position.nft);
+    assert_eq!(lp_position.owner, nft_mint);
+    assert_eq!(lp_position.pool_tokens, POOL_TOKENS);
+    assert_eq!(test.swap_v2(&pool.swap).position_pool_tokens, POOL_TOKENS);
+    // a second deposit cannot mint the NFT again
+    assert_eq!(
+        test.process(&to_position),
+        Err(SwapError::InvalidPositionMint.into())
+    );
+
+    let trader = test.add_user(&pool, 1_000_000, 0);
+    let swap = test.swap_instruction(&pool, &trader, true, 1_000_000, 1, None);
+    test.process(&swap).unwrap();
+    let fee_a = test.swap_v2(&pool.swap).unclaimed_fee_a;
+    assert!(fee_a > 0);
+
+    // the NFT changes hands, and so does the position
+    let holder = test.add_user(&pool, 0, 0);
+    test.set_token_account(&nft_account, |account| account.owner = holder.wallet);
+    let claim = |owner: &TestUser| {
+        let mut claim = claim_fees(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &position,
+            &pool.token_a,
+            &pool.token_b,
+            &owner.wallet,
+            &owner.token_a,
+            &owner.token_b,
+        )
+        .unwrap();
+        claim
+            .accounts
+            .push(AccountMeta::new_readonly(nft_account, false));
+        claim
+    };
+    assert_eq!(
+        test.process(&claim(&user)),
+        Err(SwapError::InvalidOwner.into())
+    );
+    let mut without_nft = claim(&holder);
+    without_nft.accounts.pop();
+    assert_eq!(
+        test.process(&without_nft),
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    test.process(&claim(&holder)).unwrap();
+    assert_eq!(test.token_amount(&holder.token_a), fee_a);
+
+    let mut decrease = decrease_position(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &position,
+        &position_vault,
+        &holder.wallet,
+        &holder.pool_tokens,
+        DecreasePosition {
+            pool_token_amount: POOL_TOKENS,
+        },
+    )
+    .unwrap();
+    decrease
+        .accounts
+        .push(AccountMeta::new_readonly(nft_account, false));
+    test.process(&decrease).unwrap();
+    assert_eq!(test.token_amount(&holder.pool_tokens), POOL_TOKENS);
+    assert_eq!(test.token_amount(&position_vault), 0);
+}
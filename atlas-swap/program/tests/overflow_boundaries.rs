@@ -0,0 +1,269 @@
+//! Swaps, deposits and withdrawals at the edges of the integer ranges: u64
+//! reserves and amounts, u128 pool supplies and fee numerators up to the
+//! denominator. The math the processor runs must return None, which it
+//! turns into CalculationFailure, or a result its u64 conversions check,
+//! never wrap around or panic.
+
+use atlas_swap::curve::{
+    base::{
+        get_deposit_quote, get_swap_quote, get_withdraw_quote, CurveType, SwapCurve,
+    },
+    calculator::{CurveCalculator, RoundDirection, TradeDirection},
+    constant_price::ConstantPriceCurve,
+    constant_product::ConstantProductCurve,
+    fees::{FeeTier, Fees},
+    offset::OffsetCurve,
+    stable::StableCurve,
+};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const AMOUNTS: &[u64] = &[
+    0,
+    1,
+    2,
+    u32::MAX as u64,
+    u64::MAX / 2,
+    u64::MAX - 1,
+    u64::MAX,
+];
+
+const SUPPLIES: &[u128] = &[
+    1,
+    u64::MAX as u128,
+    u64::MAX as u128 + 1,
+    u128::MAX / 2,
+    u128::MAX - 1,
+    u128::MAX,
+];
+
+fn curves() -> Vec<SwapCurve> {
+    vec![
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        SwapCurve {
+            curve_type: CurveType::ConstantPrice,
+            calculator: ConstantPriceCurve {
+                token_b_price: u64::MAX,
+            }
+            .into(),
+        },
+        SwapCurve {
+            curve_type: CurveType::Stable,
+            calculator: StableCurve {
+                amp: 1_000_000,
+                token_a_decimals: 0,
+                token_b_decimals: 9,
+            }
+            .into(),
+        },
+        SwapCurve {
+            curve_type: CurveType::Offset,
+            calculator: OffsetCurve {
+                token_b_offset: u64::MAX,
+            }
+            .into(),
+        },
+    ]
+}
+
+/// Fees as the program takes them, then numerators at and past the
+/// denominator, which validation rejects but the math must still survive
+fn fees() -> Vec<Fees> {
+    let at_most = |numerator: u64, fee_denominator: u64| Fees {
+        constant_product_return_fee_numerator: numerator,
+        constant_product_fixed_fee_numerator: numerator,
+        stable_return_fee_numerator: numerator,
+        stable_fixed_fee_numerator: numerator,
+        fee_denominator,
+        dynamic_fee_min_numerator: numerator,
+        dynamic_fee_max_numerator: numerator,
+        fee_tiers: [FeeTier {
+            threshold_bps: 1,
+            fixed_fee_numerator: numerator,
+        }; 2],
+        minimum_fee: numerator,
+    };
+    vec![
+        Fees {
+            constant_product_return_fee_numerator: 25,
+            constant_product_fixed_fee_numerator: 5,
+            stable_return_fee_numerator: 4,
+            stable_fixed_fee_numerator: 1,
+            fee_denominator: 10_000,
+            ..Fees::default()
+        },
+        at_most(u64::MAX - 1, u64::MAX),
+        at_most(u64::MAX, u64::MAX),
+        at_most(u64::MAX, 1),
+        at_most(1, 0),
+    ]
+}
+
+fn directions() -> [TradeDirection; 2] {
+    [TradeDirection::AtoB, TradeDirection::BtoA]
+}
+
+/// Run `f`, failing with `case` on a panic
+fn no_panic<T>(case: String, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| panic!("panicked on {}", case))
+}
+
+#[test]
+fn swaps_at_u64_bounds_fail_gracefully() {
+    for (c, curve) in curves().iter().enumerate() {
+        for (f, fees) in fees().iter().enumerate() {
+            for &reserves_a in AMOUNTS {
+                for &reserves_b in AMOUNTS {
+                    for &amount_in in AMOUNTS {
+                        for direction in directions() {
+                            let case = format!(
+                                "curve {} fees {} swap {} {:?} at {}/{}",
+                                c, f, amount_in, direction, reserves_a, reserves_b
+                            );
+                            let quote = no_panic(case.clone(), || {
+                                get_swap_quote(
+                                    reserves_a, reserves_b, amount_in, direction, fees, curve,
+                                )
+                            });
+                            if let Some(quote) = quote {
+                                let reserve_out = match direction {
+                                    TradeDirection::AtoB => reserves_b,
+                                    TradeDirection::BtoA => reserves_a,
+                                };
+                                assert!(
+                                    quote.destination_amount_swapped <= u128::from(reserve_out),
+                                    "{}",
+                                    case
+                                );
+                                assert!(
+                                    quote.source_amount_swapped <= u128::from(amount_in),
+                                    "{}",
+                                    case
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn deposits_and_withdrawals_at_u64_bounds_fail_gracefully() {
+    for (c, curve) in curves().iter().enumerate() {
+        for &reserves_a in AMOUNTS {
+            for &reserves_b in AMOUNTS {
+                for &pool_supply in AMOUNTS {
+                    for &pool_token_amount in AMOUNTS {
+                        let case = format!(
+                            "curve {} {} of {} pool tokens at {}/{}",
+                            c, pool_token_amount, pool_supply, reserves_a, reserves_b
+                        );
+                        no_panic(case.clone(), || {
+                            get_deposit_quote(
+                                reserves_a,
+                                reserves_b,
+                                pool_supply,
+                                pool_token_amount,
+                                curve,
+                            )
+                        });
+                        for fees in fees().iter() {
+                            let quote = no_panic(case.clone(), || {
+                                get_withdraw_quote(
+                                    reserves_a,
+                                    reserves_b,
+                                    pool_supply,
+                                    pool_token_amount,
+                                    fees.constant_product_fixed_fee_numerator,
+                                    fees,
+                                    curve,
+                                )
+                            });
+                            if let Some(quote) = quote {
+                                assert!(quote.token_a_amount <= u128::from(reserves_a), "{}", case);
+                                assert!(quote.token_b_amount <= u128::from(reserves_b), "{}", case);
+                            }
+                        }
+                        for direction in directions() {
+                            let amount = u128::from(pool_token_amount);
+                            no_panic(case.clone(), || {
+                                curve.deposit_single_token_type(
+                                    amount,
+                                    u128::from(reserves_a),
+                                    u128::from(reserves_b),
+                                    u128::from(pool_supply),
+                                    direction,
+                                    &Fees::default(),
+                                )
+                            });
+                            no_panic(case.clone(), || {
+                                curve.withdraw_single_token_type_exact_out(
+                                    amount,
+                                    u128::from(reserves_a),
+                                    u128::from(reserves_b),
+                                    u128::from(pool_supply),
+                                    direction,
+                                    &Fees::default(),
+                                )
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pool_supplies_at_u128_bounds_fail_gracefully() {
+    for (c, curve) in curves().iter().enumerate() {
+        for &pool_supply in SUPPLIES {
+            for &pool_tokens in SUPPLIES {
+                for &reserve in AMOUNTS {
+                    let reserve = u128::from(reserve);
+                    let case = format!(
+                        "curve {} {} of {} pool tokens at {}",
+                        c, pool_tokens, pool_supply, reserve
+                    );
+                    for round_direction in [RoundDirection::Floor, RoundDirection::Ceiling] {
+                        no_panic(case.clone(), || {
+                            curve.calculator.pool_tokens_to_trading_tokens(
+                                pool_tokens,
+                                pool_supply,
+                                reserve,
+                                reserve,
+                                round_direction,
+                            )
+                        });
+                    }
+                    for direction in directions() {
+                        no_panic(case.clone(), || {
+                            curve.deposit_single_token_type(
+                                reserve,
+                                reserve,
+                                reserve,
+                                pool_supply,
+                                direction,
+                                &Fees::default(),
+                            )
+                        });
+                        no_panic(case.clone(), || {
+                            curve.withdraw_single_token_type_exact_out(
+                                reserve,
+                                reserve,
+                                reserve,
+                                pool_supply,
+                                direction,
+                                &Fees::default(),
+                            )
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
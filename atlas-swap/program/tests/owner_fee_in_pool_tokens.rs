@@ -0,0 +1,136 @@
+//! A pool minting the owner fee as pool tokens leaves the fee owner's cut in
+//! its reserves and mints the fee owner the pool tokens of a single-sided
+//! deposit of it.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+        offset::OffsetCurve,
+    },
+    error::SwapError,
+    instruction::{set_owner_fee_in_pool_tokens, SetOwnerFeeInPoolTokens},
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+
+fn in_pool_tokens(test: &ProgramTest, pool: &TestPool, signer: &Pubkey) -> Instruction {
+    set_owner_fee_in_pool_tokens(
+        &atlas_swap::id(),
+        &pool.swap,
+        signer,
+        &test.state,
+        SetOwnerFeeInPoolTokens {
+            owner_fee_in_pool_tokens: true,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn owner_fee_is_minted_as_pool_tokens() {
+    let fees = Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let fee_owner = test.fee_owner;
+    let fee_a = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let fee_pool_tokens = test.add_token_account(&pool.pool_mint, &fee_owner, 0);
+    let user = test.add_user(&pool, 2 * AMOUNT_IN, 0);
+    assert_eq!(
+        test.process(&test.swap_instruction(
+            &pool,
+            &user,
+            true,
+            AMOUNT_IN,
+            1,
+            Some(&fee_pool_tokens)
+        )),
+        Err(SwapError::IncorrectFeeAccountMint.into())
+    );
+
+    assert_eq!(
+        test.process(&in_pool_tokens(&test, &pool, &Pubkey::new_unique())),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let owner = test.owner;
+    test.process(&in_pool_tokens(&test, &pool, &owner)).unwrap();
+    assert!(test.swap_v2(&pool.swap).owner_fee_in_pool_tokens);
+    assert_eq!(
+        test.process(&test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, Some(&fee_a))),
+        Err(SwapError::IncorrectFeeAccountMint.into())
+    );
+
+    let quote = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &test.swap_v2(&pool.swap).swap_curve,
+    )
+    .unwrap();
+    assert!(quote.owner_fee > 0);
+    let supply = test.mint(&pool.pool_mint).supply;
+    test.process(&test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, Some(&fee_pool_tokens)))
+        .unwrap();
+
+    // the whole input stays in the pool
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(
+        swap_v2.token_a_reserve as u128,
+        RESERVE as u128 + quote.source_amount_swapped
+    );
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+    // less than the pool tokens of a balanced deposit of twice the fee
+    let minted = test.token_amount(&fee_pool_tokens);
+    assert!(minted > 0);
+    assert!((minted as u128) < quote.owner_fee * supply as u128 / RESERVE as u128);
+    assert_eq!(test.mint(&pool.pool_mint).supply, supply + minted);
+}
+
+#[test]
+fn curves_without_deposits_keep_the_owner_fee_in_the_input_token() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    test.set_swap_v2(&pool.swap, |swap_v2| {
+        swap_v2.swap_curve = SwapCurve {
+            curve_type: CurveType::Offset,
+            calculator: OffsetCurve {
+                token_b_offset: RESERVE,
+            }
+            .into(),
+        }
+    });
+    let owner = test.owner;
+    assert_eq!(
+        test.process(&in_pool_tokens(&test, &pool, &owner)),
+        Err(SwapError::UnsupportedCurveOperation.into())
+    );
+}
@@ -0,0 +1,194 @@
+//! Every exported program address is derived from the seeds the processor
+//! checks, with a bump that recreates it.
+
+use atlas_swap::pda::*;
+use solana_program::pubkey::Pubkey;
+
+fn assert_derived(found: (Pubkey, u8), seeds: &[&[u8]], program_id: &Pubkey) {
+    let (address, bump) = found;
+    assert_eq!(Pubkey::find_program_address(seeds, program_id), found);
+    let mut signer_seeds = seeds.to_vec();
+    let bump_seed = [bump];
+    signer_seeds.push(&bump_seed);
+    assert_eq!(
+        Pubkey::create_program_address(&signer_seeds, program_id),
+        Ok(address)
+    );
+    assert!(!address.is_on_curve());
+}
+
+#[test]
+fn program_addresses_use_the_processor_seeds() {
+    let program_id = atlas_swap::id();
+    let swap = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let program = program_id.as_ref();
+
+    assert_derived(
+        find_pool_authority(&program_id, &swap),
+        &[swap.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_global_state(&program_id),
+        &[b"atlas-swap", program],
+        &program_id,
+    );
+    assert_derived(
+        find_protocol_liquidity_authority(&program_id),
+        &[b"atlas-swap-pol", program],
+        &program_id,
+    );
+    assert_derived(
+        find_allowlist_entry(&program_id, &swap, &owner),
+        &[b"atlas-swap-allowlist", swap.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_deposit_record(&program_id, &swap, &owner),
+        &[b"atlas-swap-deposit", swap.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_quote_nonce(&program_id, &swap, &owner),
+        &[b"atlas-swap-quote-nonce", swap.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_liquidity_lock(&program_id, &swap, &owner),
+        &[b"atlas-swap-lock", swap.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_swap_commitment(&program_id, &swap, &owner),
+        &[b"atlas-swap-commit", swap.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_batch_auction(&program_id, &swap),
+        &[b"atlas-swap-batch", swap.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_limit_order(&program_id, &swap, &owner, 7),
+        &[
+            b"atlas-swap-order",
+            swap.as_ref(),
+            owner.as_ref(),
+            &7u64.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    assert_derived(
+        find_dca_order(&program_id, &swap, &owner, 7),
+        &[
+            b"atlas-swap-dca",
+            swap.as_ref(),
+            owner.as_ref(),
+            &7u64.to_le_bytes(),
+        ],
+        &program_id,
+    );
+    let (stake_pool, _) = find_stake_pool(&program_id, &swap);
+    assert_derived(
+        find_stake_pool(&program_id, &swap),
+        &[b"atlas-swap-stake", swap.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_stake_position(&program_id, &stake_pool, &owner),
+        &[b"atlas-swap-stake", stake_pool.as_ref(), owner.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_lp_position(&program_id, &swap, &mint),
+        &[b"atlas-swap-position", swap.as_ref(), mint.as_ref()],
+        &program_id,
+    );
+    assert_derived(
+        find_fee_governance(&program_id),
+        &[b"atlas-swap-fee-governance", program],
+        &program_id,
+    );
+    assert_derived(
+        find_scheduler(&program_id),
+        &[b"atlas-swap-scheduler", program],
+        &program_id,
+    );
+    let governance_program_id = Pubkey::new_unique();
+    assert_derived(
+        find_spl_governance(&governance_program_id, &owner, &mint),
+        &[b"account-governance", owner.as_ref(), mint.as_ref()],
+        &governance_program_id,
+    );
+}
+
+#[test]
+fn program_addresses_differ_by_program_and_input() {
+    let program_id = atlas_swap::id();
+    let other_program_id = Pubkey::new_unique();
+    let swap = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    assert_ne!(
+        find_global_state(&program_id),
+        find_global_state(&other_program_id)
+    );
+    assert_ne!(
+        find_pool_authority(&program_id, &swap),
+        find_pool_authority(&other_program_id, &swap)
+    );
+    assert_ne!(
+        find_limit_order(&program_id, &swap, &owner, 0),
+        find_limit_order(&program_id, &swap, &owner, 1)
+    );
+    assert_ne!(
+        find_limit_order(&program_id, &swap, &owner, 0).0,
+        find_dca_order(&program_id, &swap, &owner, 0).0
+    );
+    assert_ne!(
+        find_deposit_record(&program_id, &swap, &owner),
+        find_deposit_record(&program_id, &swap, &Pubkey::new_unique())
+    );
+}
+
+#[test]
+fn vault_atas_are_owned_by_the_pool_authority() {
+    let program_id = atlas_swap::id();
+    let swap = Pubkey::new_unique();
+    let token_a_mint = Pubkey::new_unique();
+    let token_b_mint = Pubkey::new_unique();
+    let token_2022 = atlas_swap::token_2022::TOKEN_2022_PROGRAM_ID;
+    let (authority, _) = find_pool_authority(&program_id, &swap);
+
+    let (token_a, token_b) = derive_vault_atas(
+        &program_id,
+        &swap,
+        &token_a_mint,
+        &spl_token::id(),
+        &token_b_mint,
+        &token_2022,
+    );
+    assert_eq!(
+        token_a,
+        Pubkey::find_program_address(
+            &[
+                authority.as_ref(),
+                spl_token::id().as_ref(),
+                token_a_mint.as_ref()
+            ],
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+        )
+        .0
+    );
+    assert_eq!(
+        token_b,
+        get_associated_token_address(&authority, &token_2022, &token_b_mint)
+    );
+    // the token program of the mint is part of the address
+    assert_ne!(
+        token_b,
+        get_associated_token_address(&authority, &spl_token::id(), &token_b_mint)
+    );
+}
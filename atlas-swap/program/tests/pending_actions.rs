@@ -0,0 +1,334 @@
+//! Admin actions staged in the scheduler wait for their own ETA, run in
+//! any order once it passes and may be cancelled by the owner until then.
+//! They are the only way to change the owner, the fees and the amp of a
+//! pool, the last by at most MAX_AMP_CHANGE_FACTOR at a time.
+
+mod common;
+
+use atlas_swap::{
+    constraints::{MAX_AMP_CHANGE_FACTOR, MIN_PENDING_ACTION_DELAY},
+    curve::{
+        base::{Calculator, CurveType, SwapCurve},
+        fees::{FeeDiscount, Fees},
+        stable::StableCurve,
+    },
+    error::SwapError,
+    instruction::{
+        cancel_pending_action, execute_pending_action, schedule_pending_action, set_global_state,
+        update_curve_parameters, CancelPendingAction, ExecutePendingAction, SchedulePendingAction,
+        SwapInstruction, UpdateCurveParameters,
+    },
+    pda::find_scheduler,
+    state::{AdminAction, GlobalState, Scheduler, MAX_PENDING_ACTIONS},
+};
+use common::{set_clock, ProgramTest, TestPool};
+use solana_program::{
+    entrypoint::ProgramResult, instruction::Instruction, program_pack::Pack, pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMP: u64 = 100;
+
+/// A program and a stable pool, and the scheduler of the program
+struct PendingActionTest {
+    test: ProgramTest,
+    pool: TestPool,
+    scheduler: Pubkey,
+}
+
+impl PendingActionTest {
+    fn new() -> Self {
+        let mut test = ProgramTest::new(fees(10));
+        let pool = test.create_pool(
+            SwapCurve {
+                curve_type: CurveType::Stable,
+                calculator: StableCurve {
+                    amp: AMP,
+                    ..StableCurve::default()
+                }
+                .into(),
+            },
+            RESERVE,
+            RESERVE,
+        );
+        Self {
+            test,
+            pool,
+            scheduler: find_scheduler(&atlas_swap::id()).0,
+        }
+    }
+
+    fn scheduler(&self) -> Scheduler {
+        Scheduler::unpack(&self.test.accounts[&self.scheduler].data).unwrap()
+    }
+
+    fn amp(&self) -> u64 {
+        match self.test.swap_v2(&self.pool.swap).swap_curve.calculator {
+            Calculator::Stable(stable_curve) => stable_curve.amp,
+            _ => panic!("not a stable pool"),
+        }
+    }
+
+    fn schedule(&mut self, owner: Pubkey, eta: i64, action: AdminAction) -> ProgramResult {
+        let instruction = schedule_pending_action(
+            &atlas_swap::id(),
+            &self.test.state,
+            &owner,
+            &self.scheduler,
+            SchedulePendingAction { eta, action },
+        )
+        .unwrap();
+        set_clock(0, 0);
+        self.test.process(&instruction)
+    }
+
+    fn execute(&mut self, action_id: u64, unix_timestamp: i64) -> ProgramResult {
+        let instruction = execute_pending_action(
+            &atlas_swap::id(),
+            &self.test.state,
+            &self.scheduler,
+            Some(&self.pool.swap),
+            ExecutePendingAction { action_id },
+        )
+        .unwrap();
+        set_clock(0, unix_timestamp);
+        self.test.process(&instruction)
+    }
+
+    fn cancel(&mut self, owner: Pubkey, action_id: u64) -> ProgramResult {
+        let instruction = cancel_pending_action(
+            &atlas_swap::id(),
+            &self.test.state,
+            &owner,
+            &self.scheduler,
+            CancelPendingAction { action_id },
+        )
+        .unwrap();
+        self.test.process(&instruction)
+    }
+
+    /// Schedules an amp change of the pool and runs it once due
+    fn set_amp(&mut self, amp: u64) -> ProgramResult {
+        let owner = self.test.owner;
+        let day = MIN_PENDING_ACTION_DELAY;
+        let swap = self.pool.swap;
+        self.schedule(owner, day, AdminAction::SetAmp { swap, amp })?;
+        let action_id = self.scheduler().actions.last().unwrap().action_id;
+        self.execute(action_id, day)
+    }
+
+    /// SetGlobalState of the owner keeping every field but those of `update`
+    fn set_global_state(&self, update: GlobalState) -> Instruction {
+        set_global_state(
+            &atlas_swap::id(),
+            &self.test.state,
+            &self.test.owner,
+            &update.owner,
+            &update.fee_owner,
+            update.initial_supply,
+            update.lp_decimals,
+            update.fees,
+            update.fee_discount,
+            &update.quoter,
+        )
+        .unwrap()
+    }
+}
+
+fn fees(trade_fee_numerator: u64) -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: trade_fee_numerator,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+#[test]
+fn pending_actions_run_from_their_own_eta() {
+    let mut program = PendingActionTest::new();
+    let owner = program.test.owner;
+    let new_owner = Pubkey::new_unique();
+    let day = MIN_PENDING_ACTION_DELAY;
+
+    let set_fees = AdminAction::SetFees {
+        fees: fees(20),
+        fee_discount: FeeDiscount::default(),
+    };
+    let set_amp = AdminAction::SetAmp {
+        swap: program.pool.swap,
+        amp: 500,
+    };
+    assert_eq!(
+        program.schedule(owner, day - 1, set_fees.clone()),
+        Err(SwapError::PendingActionDelayTooShort.into())
+    );
+    assert_eq!(
+        program.schedule(new_owner, day, set_fees.clone()),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    program.schedule(owner, 2 * day, set_fees).unwrap();
+    program.schedule(owner, day, set_amp).unwrap();
+    program
+        .schedule(owner, 3 * day, AdminAction::SetOwner { owner: new_owner })
+        .unwrap();
+    let scheduler = program.scheduler();
+    assert_eq!(scheduler.next_action_id, 3);
+    assert_eq!(scheduler.actions.len(), 3);
+
+    assert_eq!(
+        program.execute(0, 2 * day - 1),
+        Err(SwapError::PendingActionNotReady.into())
+    );
+    program.execute(1, day).unwrap();
+    assert_eq!(program.amp(), 500);
+    program.execute(0, 2 * day).unwrap();
+    assert_eq!(program.test.global_state().fees, fees(20));
+    assert_eq!(
+        program.execute(0, 2 * day),
+        Err(SwapError::PendingActionNotFound.into())
+    );
+
+    // the owner may call off a change until it runs
+    assert_eq!(
+        program.cancel(new_owner, 2),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    program.cancel(owner, 2).unwrap();
+    assert_eq!(
+        program.execute(2, 3 * day),
+        Err(SwapError::PendingActionNotFound.into())
+    );
+    assert_eq!(program.test.global_state().owner, owner);
+    assert!(program.scheduler().actions.is_empty());
+}
+
+#[test]
+fn scheduler_holds_a_bounded_number_of_actions() {
+    let mut program = PendingActionTest::new();
+    let owner = program.test.owner;
+    let day = MIN_PENDING_ACTION_DELAY;
+
+    for _ in 0..MAX_PENDING_ACTIONS {
+        program
+            .schedule(owner, day, AdminAction::SetOwner { owner })
+            .unwrap();
+    }
+    assert_eq!(
+        program.schedule(owner, day, AdminAction::SetOwner { owner }),
+        Err(SwapError::TooManyPendingActions.into())
+    );
+
+    // an amp change past MAX_AMP_CHANGE_FACTOR fails when it runs
+    program.cancel(owner, 0).unwrap();
+    let set_amp = AdminAction::SetAmp {
+        swap: program.pool.swap,
+        amp: 100_000,
+    };
+    program.schedule(owner, day, set_amp.clone()).unwrap();
+    let action_id = program.scheduler().actions.last().unwrap().action_id;
+    assert_eq!(
+        program.execute(action_id, day),
+        Err(SwapError::AmpChangeTooLarge.into())
+    );
+    assert_eq!(program.amp(), AMP);
+
+    let instruction = SwapInstruction::SchedulePendingAction(SchedulePendingAction {
+        eta: day,
+        action: set_amp,
+    });
+    assert_eq!(
+        SwapInstruction::unpack(&instruction.pack().unwrap()),
+        Ok(instruction)
+    );
+}
+
+#[test]
+fn owner_fees_and_amp_do_not_change_directly() {
+    let mut program = PendingActionTest::new();
+    let state = program.test.global_state();
+
+    let updates: [fn(&mut GlobalState); 3] = [
+        |state| state.owner = Pubkey::new_unique(),
+        |state| state.fees = fees(20),
+        |state| state.fee_discount.discount_bps = 1_000,
+    ];
+    for update in updates.iter() {
+        let mut changed = program.test.global_state();
+        update(&mut changed);
+        let instruction = program.set_global_state(changed);
+        assert_eq!(
+            program.test.process(&instruction),
+            Err(SwapError::PendingActionRequired.into())
+        );
+    }
+    assert_eq!(program.test.global_state(), state);
+    // the fields the scheduler does not delay still change at once
+    let fee_owner = Pubkey::new_unique();
+    let instruction = program.set_global_state(GlobalState { fee_owner, ..state });
+    program.test.process(&instruction).unwrap();
+    assert_eq!(program.test.global_state().fee_owner, fee_owner);
+
+    let update_curve = |owner: &Pubkey| {
+        update_curve_parameters(
+            &atlas_swap::id(),
+            &program.pool.swap,
+            owner,
+            &program.test.state,
+            UpdateCurveParameters {
+                swap_curve: SwapCurve {
+                    curve_type: CurveType::Stable,
+                    calculator: StableCurve {
+                        amp: 2 * AMP,
+                        ..StableCurve::default()
+                    }
+                    .into(),
+                },
+            },
+        )
+        .unwrap()
+    };
+    let other = update_curve(&Pubkey::new_unique());
+    let owners = update_curve(&program.test.owner);
+    assert_eq!(
+        program.test.process(&other),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert_eq!(
+        program.test.process(&owners),
+        Err(SwapError::PendingActionRequired.into())
+    );
+    assert_eq!(program.amp(), AMP);
+}
+
+#[test]
+fn scheduled_amp_changes_stay_within_the_change_factor() {
+    let mut program = PendingActionTest::new();
+
+    program.set_amp(AMP * MAX_AMP_CHANGE_FACTOR).unwrap();
+    // the decimals are those of the pool's mints
+    assert_eq!(
+        program
+            .test
+            .swap_v2(&program.pool.swap)
+            .swap_curve
+            .calculator,
+        Calculator::Stable(StableCurve {
+            amp: AMP * MAX_AMP_CHANGE_FACTOR,
+            token_a_decimals: 6,
+            token_b_decimals: 6,
+        })
+    );
+    program.set_amp(AMP).unwrap();
+    assert_eq!(program.amp(), AMP);
+
+    assert_eq!(
+        program.set_amp(AMP * MAX_AMP_CHANGE_FACTOR + 1),
+        Err(SwapError::AmpChangeTooLarge.into())
+    );
+    assert_eq!(
+        program.set_amp(AMP / MAX_AMP_CHANGE_FACTOR - 1),
+        Err(SwapError::AmpChangeTooLarge.into())
+    );
+    assert_eq!(program.set_amp(0), Err(SwapError::InvalidCurve.into()));
+    assert_eq!(program.amp(), AMP);
+}
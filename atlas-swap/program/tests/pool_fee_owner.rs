@@ -0,0 +1,202 @@
+//! A pool created with its own fee owner, which only the program owner may
+//! set, has the owner fee of its swaps and its withdrawal fee paid to that
+//! fee owner in place of the one of the global state.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{initialize, withdraw_all_token_types, WithdrawAllTokenTypes},
+    state::{SwapState, SwapVersion},
+};
+use common::{ProgramTest, TestPool};
+use solana_program::pubkey::Pubkey;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn pool_fee_owner_round_trips_through_the_swap_state() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    assert_eq!(test.swap_v2(&pool.swap).fee_owner(), None);
+
+    let fee_owner = Pubkey::new_unique();
+    test.set_swap_v2(&pool.swap, |swap_v2| swap_v2.fee_owner = fee_owner);
+    let data = &test.accounts[&pool.swap].data;
+    let swap_v2 = match SwapVersion::unpack_version(data).unwrap() {
+        SwapVersion::SwapV2(swap_v2) => swap_v2,
+        _ => panic!("not a SwapV2 pool"),
+    };
+    assert_eq!(swap_v2.fee_owner(), Some(&fee_owner));
+    let mut packed = vec![0; SwapVersion::LATEST_LEN];
+    SwapVersion::pack(SwapVersion::SwapV2(swap_v2), &mut packed).unwrap();
+    assert_eq!(&packed, data);
+    assert_eq!(
+        SwapVersion::unpack(&packed).unwrap().fee_owner(),
+        Some(&fee_owner)
+    );
+}
+
+#[test]
+fn initialize_with_a_fee_owner_signed_by_the_program_owner() {
+    let mut test = ProgramTest::new(Fees {
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let program_owner = test.owner;
+    // a pool to borrow the accounts of, reinitialized with a fee owner
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &atlas_swap::id()).0;
+    let token_a = test.add_token_account(&pool.token_a_mint, &authority, RESERVE);
+    let token_b = test.add_token_account(&pool.token_b_mint, &authority, RESERVE);
+    let pool_mint = test.add_mint(&authority, 6);
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &pool.provider, 0);
+    let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+    test.add(swap, atlas_swap::id(), vec![0; SwapVersion::LATEST_LEN]);
+    let fee_owner = Pubkey::new_unique();
+    let init = |program_owner: &Pubkey| {
+        initialize(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &swap,
+            &authority,
+            &test.state,
+            &token_a,
+            &token_b,
+            &pool_mint,
+            &provider_pool_tokens,
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &dead_shares,
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            Some(&fee_owner),
+            Some(program_owner),
+        )
+        .unwrap()
+    };
+    let mut unsigned = init(&program_owner);
+    unsigned.accounts[11].is_signer = false;
+    let other = init(&Pubkey::new_unique());
+    let init = init(&program_owner);
+    assert_eq!(
+        test.process(&unsigned),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert_eq!(
+        test.process(&other),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    test.process(&init).unwrap();
+    assert_eq!(test.swap_v2(&swap).fee_owner(), Some(&fee_owner));
+
+    // swaps pay the owner fee to the fee owner of the pool
+    let pool = TestPool {
+        swap,
+        authority,
+        token_a,
+        token_b,
+        pool_mint,
+        provider_pool_tokens,
+        ..pool
+    };
+    let user = test.add_user(&pool, RESERVE, 0);
+    let global_fee_owner = test.fee_owner;
+    let global_fee_account = test.add_token_account(&pool.token_a_mint, &global_fee_owner, 0);
+    let swap = test.swap_instruction(
+        &pool,
+        &user,
+        true,
+        RESERVE / 1_000,
+        1,
+        Some(&global_fee_account),
+    );
+    assert_eq!(test.process(&swap), Err(SwapError::InvalidOwner.into()));
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let swap = test.swap_instruction(&pool, &user, true, RESERVE / 1_000, 1, Some(&fee_account));
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&fee_account) > 0);
+}
+
+#[test]
+fn withdrawal_fees_go_to_the_fee_owner_of_the_pool() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let fee_owner = Pubkey::new_unique();
+    test.set_swap_v2(&pool.swap, |swap_v2| {
+        swap_v2.fee_owner = fee_owner;
+        swap_v2.withdraw_fee_numerator = 30;
+    });
+    let user = test.add_user(&pool, 0, 0);
+    let global_fee_owner = test.fee_owner;
+    let global_fee_account = test.add_token_account(&pool.pool_mint, &global_fee_owner, 0);
+    let fee_account = test.add_token_account(&pool.pool_mint, &fee_owner, 0);
+    let withdraw = |pool_fee: &Pubkey| {
+        withdraw_all_token_types(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &test.state,
+            &pool.provider,
+            &pool.pool_mint,
+            &pool.provider_pool_tokens,
+            &pool.token_a,
+            &pool.token_b,
+            &user.token_a,
+            &user.token_b,
+            Some(pool_fee),
+            None,
+            WithdrawAllTokenTypes {
+                pool_token_amount: RESERVE / 10,
+                minimum_token_a_amount: 0,
+                minimum_token_b_amount: 0,
+            },
+        )
+        .unwrap()
+    };
+    let withdraw_to_global = withdraw(&global_fee_account);
+    let withdraw_to_pool = withdraw(&fee_account);
+    assert_eq!(
+        test.process(&withdraw_to_global),
+        Err(SwapError::InvalidOwner.into())
+    );
+    test.process(&withdraw_to_pool).unwrap();
+    assert_eq!(test.token_amount(&fee_account), RESERVE / 10 * 30 / 10_000);
+}
@@ -0,0 +1,145 @@
+//! Swaps and deposits of a gated pool pass only for wallets holding the gate
+//! token, or an allowlist entry of the pool that the program owner created.
+
+mod common;
+
+use atlas_swap::{
+    error::SwapError,
+    instruction::{set_allowlisted, set_pool_gate, SetAllowlisted, SetPoolGate},
+    pda::find_allowlist_entry,
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+fn new_pool() -> (ProgramTest, TestPool, TestUser) {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let user = test.add_user(&pool, 2 * AMOUNT_IN, AMOUNT_IN);
+    (test, pool, user)
+}
+
+fn set_gate(test: &ProgramTest, pool: &TestPool, owner: &Pubkey, gate_mint: Pubkey) -> Instruction {
+    set_pool_gate(
+        &atlas_swap::id(),
+        &pool.swap,
+        owner,
+        &test.state,
+        SetPoolGate {
+            gated: true,
+            gate_mint,
+        },
+    )
+    .unwrap()
+}
+
+fn allowlist(test: &ProgramTest, pool: &TestPool, wallet: &Pubkey, allowed: bool) -> Instruction {
+    set_allowlisted(
+        &atlas_swap::id(),
+        &pool.swap,
+        &test.owner,
+        &test.state,
+        &find_allowlist_entry(&atlas_swap::id(), &pool.swap, wallet).0,
+        wallet,
+        if allowed { None } else { Some(&test.owner) },
+        SetAllowlisted { allowed },
+    )
+    .unwrap()
+}
+
+/// A swap of AMOUNT_IN token A by `user`, passing `gate`
+fn gated_swap(test: &ProgramTest, pool: &TestPool, user: &TestUser, gate: &Pubkey) -> Instruction {
+    let mut swap = test.swap_instruction(pool, user, true, AMOUNT_IN, 1, None);
+    swap.accounts.push(AccountMeta::new_readonly(*gate, false));
+    swap
+}
+
+#[test]
+fn gate_token_holders_pass() {
+    let (mut test, pool, user) = new_pool();
+    let owner = test.owner;
+    let gate_mint = test.add_mint(&owner, 0);
+    test.process(&set_gate(&test, &pool, &owner, gate_mint))
+        .unwrap();
+
+    // no gate account, an empty one and one of another wallet
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    assert!(test.process(&swap).is_err());
+    let empty = test.add_token_account(&gate_mint, &user.wallet, 0);
+    let swap = gated_swap(&test, &pool, &user, &empty);
+    assert_eq!(test.process(&swap), Err(SwapError::GateCheckFailed.into()));
+    let others = test.add_token_account(&gate_mint, &Pubkey::new_unique(), 1);
+    let swap = gated_swap(&test, &pool, &user, &others);
+    assert_eq!(test.process(&swap), Err(SwapError::GateCheckFailed.into()));
+    // a token of another mint
+    let other_mint = test.add_mint(&owner, 0);
+    let other_token = test.add_token_account(&other_mint, &user.wallet, 1);
+    let swap = gated_swap(&test, &pool, &user, &other_token);
+    assert_eq!(test.process(&swap), Err(SwapError::GateCheckFailed.into()));
+    assert_eq!(test.token_amount(&user.token_a), 2 * AMOUNT_IN);
+
+    let holding = test.add_token_account(&gate_mint, &user.wallet, 1);
+    let swap = gated_swap(&test, &pool, &user, &holding);
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&user.token_b) > AMOUNT_IN);
+}
+
+#[test]
+fn allowlisted_wallets_pass() {
+    let (mut test, pool, user) = new_pool();
+    let owner = test.owner;
+    test.process(&set_gate(&test, &pool, &owner, Pubkey::default()))
+        .unwrap();
+    let entry = find_allowlist_entry(&atlas_swap::id(), &pool.swap, &user.wallet).0;
+
+    // the entry of another wallet
+    let other_wallet = Pubkey::new_unique();
+    test.process(&allowlist(&test, &pool, &other_wallet, true))
+        .unwrap();
+    let other_entry = find_allowlist_entry(&atlas_swap::id(), &pool.swap, &other_wallet).0;
+    let swap = gated_swap(&test, &pool, &user, &other_entry);
+    assert_eq!(test.process(&swap), Err(SwapError::GateCheckFailed.into()));
+    // an entry of the wallet not created yet
+    let swap = gated_swap(&test, &pool, &user, &entry);
+    assert_eq!(test.process(&swap), Err(SwapError::GateCheckFailed.into()));
+
+    test.process(&allowlist(&test, &pool, &user.wallet, true))
+        .unwrap();
+    let swap = gated_swap(&test, &pool, &user, &entry);
+    test.process(&swap).unwrap();
+    let mut gated_deposit = test.deposit_instruction(&pool, &user, 1_000, AMOUNT_IN, AMOUNT_IN);
+    gated_deposit
+        .accounts
+        .push(AccountMeta::new_readonly(entry, false));
+    test.process(&gated_deposit).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), 1_000);
+
+    // removed from the allowlist, the entry closed
+    test.process(&allowlist(&test, &pool, &user.wallet, false))
+        .unwrap();
+    assert!(test.process(&gated_deposit).is_err());
+    assert_eq!(test.token_amount(&user.pool_tokens), 1_000);
+}
+
+#[test]
+fn only_the_program_owner_gates_pools() {
+    let (mut test, pool, user) = new_pool();
+    let other = set_gate(&test, &pool, &Pubkey::new_unique(), Pubkey::default());
+    assert_eq!(
+        test.process(&other),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let mut other_allowlist = allowlist(&test, &pool, &user.wallet, true);
+    let other_owner = test.add_payer(1_000_000_000);
+    other_allowlist.accounts[1].pubkey = other_owner;
+    assert_eq!(
+        test.process(&other_allowlist),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+}
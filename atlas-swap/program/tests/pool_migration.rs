@@ -0,0 +1,109 @@
+//! SwapV1 pools migrate to SwapV2 in place, keeping their authority and
+//! balances, with the payer topping up the rent of the grown account.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::migrate_pool_state,
+    state::{SwapState, SwapV1, SwapVersion, SWAP_V1_ACCOUNT_LEN},
+};
+use common::ProgramTest;
+use solana_program::{rent::Rent, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn migrate_grows_a_v1_pool_into_a_v2_pool() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    // the pool as created before SwapV2, in an account sized for SwapV1
+    let swap_v2 = test.swap_v2(&pool.swap);
+    let mut data = vec![0; SWAP_V1_ACCOUNT_LEN];
+    SwapVersion::pack(
+        SwapVersion::SwapV1(SwapV1 {
+            is_initialized: true,
+            nonce: swap_v2.nonce,
+            token_program_id: swap_v2.token_program_id,
+            token_a: swap_v2.token_a,
+            token_b: swap_v2.token_b,
+            pool_mint: swap_v2.pool_mint,
+            token_a_mint: swap_v2.token_a_mint,
+            token_b_mint: swap_v2.token_b_mint,
+            swap_curve: swap_v2.swap_curve.clone(),
+        }),
+        &mut data,
+    )
+    .unwrap();
+    let account = test.accounts.get_mut(&pool.swap).unwrap();
+    account.lamports = Rent::default().minimum_balance(SWAP_V1_ACCOUNT_LEN);
+    account.data = data;
+    let user = test.add_user(&pool, RESERVE, 0);
+    test.process(&test.swap_instruction(&pool, &user, true, 1_000, 1, None))
+        .unwrap();
+    let token_a_balance = test.token_amount(&pool.token_a);
+    let token_b_balance = test.token_amount(&pool.token_b);
+
+    let payer = test.add_payer(1_000_000_000);
+    let migrate = migrate_pool_state(
+        &atlas_swap::id(),
+        &pool.swap,
+        &pool.token_a,
+        &pool.token_b,
+        &payer,
+    )
+    .unwrap();
+    test.process(&migrate).unwrap();
+
+    let account = &test.accounts[&pool.swap];
+    assert_eq!(account.data.len(), SwapVersion::LATEST_LEN);
+    let rent = Rent::default().minimum_balance(SwapVersion::LATEST_LEN);
+    assert_eq!(account.lamports, rent);
+    assert_eq!(
+        test.accounts[&payer].lamports,
+        1_000_000_000 - (rent - Rent::default().minimum_balance(SWAP_V1_ACCOUNT_LEN))
+    );
+    assert_eq!(test.accounts[&payer].owner, system_program::id());
+    let migrated = test.swap_v2(&pool.swap);
+    assert_eq!(migrated.nonce, swap_v2.nonce);
+    assert_eq!(migrated.token_a, pool.token_a);
+    assert_eq!(migrated.token_b, pool.token_b);
+    assert_eq!(migrated.pool_mint, pool.pool_mint);
+    assert_eq!(
+        migrated.reserves(),
+        Some((token_a_balance, token_b_balance))
+    );
+    assert_eq!(test.token_amount(&pool.token_a), token_a_balance);
+    assert_eq!(test.token_amount(&pool.token_b), token_b_balance);
+
+    // the pool authority still signs for the vaults
+    test.process(&test.swap_instruction(&pool, &user, true, 1_000, 1, None))
+        .unwrap();
+    assert_eq!(
+        test.swap_v2(&pool.swap).reserves(),
+        Some((
+            test.token_amount(&pool.token_a),
+            test.token_amount(&pool.token_b)
+        ))
+    );
+
+    assert_eq!(
+        test.process(&migrate),
+        Err(SwapError::PoolStateMigrated.into())
+    );
+}
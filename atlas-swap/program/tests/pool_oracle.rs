@@ -0,0 +1,139 @@
+//! Swaps of a pool with a Pyth price oracle fail when their price strays
+//! from the oracle's by more than the pool's maximum deviation, or when the
+//! oracle is stale, not trading or another account.
+
+mod common;
+
+use atlas_swap::{
+    error::SwapError,
+    instruction::{set_pool_oracle, SetPoolOracle},
+    oracle::{
+        MAX_ORACLE_STALENESS_SLOTS, PYTH_MAGIC, PYTH_PRICE_ACCOUNT_TYPE, PYTH_STATUS_TRADING,
+    },
+};
+use common::{set_clock, ProgramTest, TestPool, TestUser};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+const MAX_DEVIATION_BPS: u64 = 100;
+const PUB_SLOT: u64 = 100;
+
+/// A Pyth price account pricing token A at `price` 10^-8 token B
+fn pyth_price(price: i64, status: u32) -> Vec<u8> {
+    let mut data = vec![0; 240];
+    data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&2u32.to_le_bytes());
+    data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT_TYPE.to_le_bytes());
+    data[20..24].copy_from_slice(&(-8i32).to_le_bytes());
+    data[208..216].copy_from_slice(&price.to_le_bytes());
+    data[224..228].copy_from_slice(&status.to_le_bytes());
+    data[232..240].copy_from_slice(&PUB_SLOT.to_le_bytes());
+    data
+}
+
+/// A one-to-one pool checked against an oracle of `price`, and a trader
+/// holding token A
+fn new_pool(price: i64) -> (ProgramTest, TestPool, TestUser, Pubkey) {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let oracle = Pubkey::new_unique();
+    test.add(
+        oracle,
+        Pubkey::new_unique(),
+        pyth_price(price, PYTH_STATUS_TRADING),
+    );
+    set_clock(PUB_SLOT, 0);
+    let owner = test.owner;
+    let set_oracle = set_oracle(&test, &pool, &owner, &oracle, MAX_DEVIATION_BPS);
+    test.process(&set_oracle).unwrap();
+    (test, pool, user, oracle)
+}
+
+fn set_oracle(
+    test: &ProgramTest,
+    pool: &TestPool,
+    owner: &Pubkey,
+    oracle: &Pubkey,
+    max_deviation_bps: u64,
+) -> Instruction {
+    set_pool_oracle(
+        &atlas_swap::id(),
+        &pool.swap,
+        owner,
+        &test.state,
+        oracle,
+        &pool.token_a_mint,
+        &pool.token_b_mint,
+        SetPoolOracle { max_deviation_bps },
+    )
+    .unwrap()
+}
+
+/// A swap of AMOUNT_IN token A, reading `oracle`
+fn swap(test: &ProgramTest, pool: &TestPool, user: &TestUser, oracle: &Pubkey) -> Instruction {
+    let mut swap = test.swap_instruction(pool, user, true, AMOUNT_IN, 1, None);
+    swap.accounts
+        .push(AccountMeta::new_readonly(*oracle, false));
+    swap
+}
+
+#[test]
+fn swaps_near_the_oracle_price_pass() {
+    let (mut test, pool, user, oracle) = new_pool(100_000_000);
+    let swap = swap(&test, &pool, &user, &oracle);
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&user.token_b) > 0);
+}
+
+#[test]
+fn swaps_away_from_the_oracle_price_fail() {
+    let (mut test, pool, user, oracle) = new_pool(102_000_000);
+    let swap = swap(&test, &pool, &user, &oracle);
+    assert_eq!(
+        test.process(&swap),
+        Err(SwapError::OraclePriceDeviation.into())
+    );
+    assert_eq!(test.token_amount(&user.token_a), AMOUNT_IN);
+}
+
+#[test]
+fn swaps_need_a_fresh_trading_oracle_of_the_pool() {
+    let (mut test, pool, user, oracle) = new_pool(100_000_000);
+
+    // another account of the same price
+    let other_oracle = Pubkey::new_unique();
+    let oracle_account = &test.accounts[&oracle];
+    let (owner, data) = (oracle_account.owner, oracle_account.data.clone());
+    test.add(other_oracle, owner, data);
+    let other = swap(&test, &pool, &user, &other_oracle);
+    assert_eq!(test.process(&other), Err(SwapError::InvalidOracle.into()));
+
+    let swap = swap(&test, &pool, &user, &oracle);
+    set_clock(PUB_SLOT + MAX_ORACLE_STALENESS_SLOTS + 1, 0);
+    assert_eq!(test.process(&swap), Err(SwapError::InvalidOracle.into()));
+    set_clock(PUB_SLOT, 0);
+    test.add(oracle, owner, pyth_price(100_000_000, 0));
+    assert_eq!(test.process(&swap), Err(SwapError::InvalidOracle.into()));
+    assert_eq!(test.token_amount(&user.token_a), AMOUNT_IN);
+}
+
+#[test]
+fn only_the_program_owner_sets_the_oracle() {
+    let (mut test, pool, user, oracle) = new_pool(102_000_000);
+    let other = set_oracle(&test, &pool, &Pubkey::new_unique(), &oracle, 0);
+    assert_eq!(
+        test.process(&other),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+
+    // a pool without its oracle swaps at any price
+    let owner = test.owner;
+    let remove = set_oracle(&test, &pool, &owner, &oracle, 0);
+    test.process(&remove).unwrap();
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+}
@@ -0,0 +1,219 @@
+//! Price impact of swaps against the spot price, and the optional fields of
+//! the Swap payload, with or without a version header.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+    },
+    error::SwapError,
+    instruction::{Swap, SwapInstruction, INSTRUCTION_VERSION, MAX_MEMO_LEN},
+};
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+#[test]
+fn small_trade_has_no_price_impact() {
+    let curve = constant_product();
+    let result = curve
+        .swap(
+            10_000,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::AtoB,
+            &Default::default(),
+        )
+        .unwrap();
+    let price_impact = curve
+        .price_impact(
+            result.source_amount_less_fees().unwrap(),
+            result.destination_amount_swapped,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::AtoB,
+        )
+        .unwrap();
+    assert!(price_impact <= 1);
+}
+
+#[test]
+fn tenth_of_reserve_trade_moves_price_by_about_nine_percent() {
+    // x * y = k: selling 10% of the source reserve executes at 1 / 1.1 of spot
+    let curve = constant_product();
+    let result = curve
+        .swap(
+            100_000_000,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::BtoA,
+            &Default::default(),
+        )
+        .unwrap();
+    let price_impact = curve
+        .price_impact(
+            result.source_amount_less_fees().unwrap(),
+            result.destination_amount_swapped,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::BtoA,
+        )
+        .unwrap();
+    assert!((908..=910).contains(&price_impact), "{}", price_impact);
+}
+
+#[test]
+fn swap_payload_without_price_impact_guard_round_trips() {
+    let instruction = SwapInstruction::Swap(Swap {
+        amount_in: 10,
+        minimum_amount_out: 5,
+        max_price_impact_bps: None,
+        tip: 0,
+        memo: Vec::new(),
+        trade_direction: None,
+        fee_discount: false,
+    });
+    let packed = instruction.pack().unwrap();
+    assert_eq!(packed.len(), 17);
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+}
+
+#[test]
+fn swap_payload_with_price_impact_guard_round_trips() {
+    let instruction = SwapInstruction::Swap(Swap {
+        amount_in: 10,
+        minimum_amount_out: 5,
+        max_price_impact_bps: Some(100),
+        tip: 0,
+        memo: Vec::new(),
+        trade_direction: None,
+        fee_discount: false,
+    });
+    let packed = instruction.pack().unwrap();
+    assert_eq!(packed.len(), 25);
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+}
+
+#[test]
+fn versioned_swap_payload_round_trips() {
+    let instruction = SwapInstruction::Swap(Swap {
+        amount_in: 10,
+        minimum_amount_out: 5,
+        max_price_impact_bps: Some(100),
+        tip: 0,
+        memo: Vec::new(),
+        trade_direction: None,
+        fee_discount: false,
+    });
+    let packed = instruction.pack_versioned().unwrap();
+    assert_eq!(packed.len(), 27);
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+    let mut newer = packed.clone();
+    newer[1] = INSTRUCTION_VERSION + 1;
+    assert!(SwapInstruction::unpack(&newer).is_err());
+    let mut unversioned = packed;
+    unversioned[1] = 0;
+    assert!(SwapInstruction::unpack(&unversioned).is_err());
+}
+
+#[test]
+fn swap_payload_with_memo_round_trips() {
+    let instruction = SwapInstruction::Swap(Swap {
+        amount_in: 10,
+        minimum_amount_out: 5,
+        max_price_impact_bps: None,
+        tip: 0,
+        memo: b"order-42".to_vec(),
+        trade_direction: None,
+        fee_discount: false,
+    });
+    let packed = instruction.pack().unwrap();
+    assert_eq!(packed.len(), 42);
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+    assert!(SwapInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+
+    let mut too_long = packed[..33].to_vec();
+    too_long.push(MAX_MEMO_LEN as u8 + 1);
+    too_long.extend_from_slice(&[0; MAX_MEMO_LEN + 1]);
+    assert!(SwapInstruction::unpack(&too_long).is_err());
+
+    // nor is a memo packed past the length its length byte allows
+    let memo = |len| {
+        SwapInstruction::Swap(Swap {
+            amount_in: 10,
+            minimum_amount_out: 5,
+            max_price_impact_bps: None,
+            tip: 0,
+            memo: vec![0; len],
+            trade_direction: None,
+            fee_discount: false,
+        })
+    };
+    let longest = memo(MAX_MEMO_LEN);
+    assert_eq!(
+        SwapInstruction::unpack(&longest.pack().unwrap()).unwrap(),
+        longest
+    );
+    assert_eq!(
+        memo(MAX_MEMO_LEN + 1).pack(),
+        Err(SwapError::InvalidInstruction.into())
+    );
+    assert_eq!(memo(256).pack(), Err(SwapError::InvalidInstruction.into()));
+}
+
+#[test]
+fn swap_payload_with_trade_direction_round_trips() {
+    let instruction = SwapInstruction::Swap(Swap {
+        amount_in: 10,
+        minimum_amount_out: 5,
+        max_price_impact_bps: None,
+        tip: 0,
+        memo: Vec::new(),
+        trade_direction: Some(TradeDirection::BtoA),
+        fee_discount: false,
+    });
+    let packed = instruction.pack().unwrap();
+    assert_eq!(packed.len(), 35);
+    assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+
+    let mut unknown = packed.clone();
+    unknown[34] = 2;
+    assert!(SwapInstruction::unpack(&unknown).is_err());
+    let mut trailing = packed;
+    trailing.push(0);
+    assert!(SwapInstruction::unpack(&trailing).is_err());
+}
+
+#[test]
+fn swap_payload_claiming_the_fee_discount_round_trips() {
+    let claim = |trade_direction| {
+        SwapInstruction::Swap(Swap {
+            amount_in: 10,
+            minimum_amount_out: 5,
+            max_price_impact_bps: None,
+            tip: 0,
+            memo: Vec::new(),
+            trade_direction,
+            fee_discount: true,
+        })
+    };
+    for trade_direction in [None, Some(TradeDirection::AtoB), Some(TradeDirection::BtoA)].iter() {
+        let instruction = claim(*trade_direction);
+        let packed = instruction.pack().unwrap();
+        assert_eq!(packed.len(), 36);
+        assert_eq!(SwapInstruction::unpack(&packed).unwrap(), instruction);
+    }
+
+    // the flag is only ever set
+    let mut unset = claim(None).pack().unwrap();
+    unset[35] = 0;
+    assert!(SwapInstruction::unpack(&unset).is_err());
+    let mut trailing = claim(None).pack().unwrap();
+    trailing.push(1);
+    assert!(SwapInstruction::unpack(&trailing).is_err());
+}
@@ -0,0 +1,145 @@
+//! Rated stable pools value token A at the exchange rate of their stake
+//! pool, read when the program owner sets the stake pool and refreshed by
+//! anyone once the stake pool is updated for the epoch.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{Calculator, CurveType, SwapCurve},
+        fees::Fees,
+        rated_stable::RatedStableCurve,
+    },
+    error::SwapError,
+    instruction::{set_rate_account, update_rate},
+    oracle::STAKE_POOL_ACCOUNT_TYPE,
+    state::PRICE_PRECISION,
+};
+use common::{ProgramTest, TestPool};
+use solana_program::pubkey::Pubkey;
+
+const RESERVE_A: u64 = 1_000_000_000;
+const RESERVE_B: u64 = 1_100_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+/// An SPL stake pool account managing `total_lamports` for
+/// `pool_token_supply`, last updated in `last_update_epoch`
+fn stake_pool_data(total_lamports: u64, pool_token_supply: u64, last_update_epoch: u64) -> Vec<u8> {
+    let mut data = vec![0; 282];
+    data[0] = STAKE_POOL_ACCOUNT_TYPE;
+    data[258..266].copy_from_slice(&total_lamports.to_le_bytes());
+    data[266..274].copy_from_slice(&pool_token_supply.to_le_bytes());
+    data[274..282].copy_from_slice(&last_update_epoch.to_le_bytes());
+    data
+}
+
+/// A rated stable pool, its rate set from a stake pool worth 1.1 lamports
+/// per token, and the stake pool
+fn rated_pool(test: &mut ProgramTest) -> (TestPool, Pubkey) {
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::RatedStable,
+            calculator: RatedStableCurve {
+                amp: 100,
+                rate: PRICE_PRECISION,
+            }
+            .into(),
+        },
+        RESERVE_A,
+        RESERVE_B,
+    );
+    let stake_pool = Pubkey::new_unique();
+    test.add(
+        stake_pool,
+        Pubkey::new_unique(),
+        stake_pool_data(1_100, 1_000, 0),
+    );
+    test.process(
+        &set_rate_account(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.owner,
+            &test.state,
+            &stake_pool,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    (pool, stake_pool)
+}
+
+fn rate(test: &ProgramTest, pool: &TestPool) -> u128 {
+    match test.swap_v2(&pool.swap).swap_curve.calculator {
+        Calculator::RatedStable(calculator) => calculator.rate,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn swaps_value_token_a_at_the_stake_pool_rate() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let (pool, stake_pool) = rated_pool(&mut test);
+    assert_eq!(test.swap_v2(&pool.swap).rate_account, stake_pool);
+    assert_eq!(rate(&test, &pool), PRICE_PRECISION * 11 / 10);
+
+    // reserves balanced at the rate trade at the rate
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+    let amount_out = test.token_amount(&user.token_b);
+    assert!(amount_out < AMOUNT_IN * 11 / 10);
+    assert!(amount_out > AMOUNT_IN * 11 / 10 - AMOUNT_IN / 1_000);
+}
+
+#[test]
+fn rate_is_refreshed_from_an_up_to_date_stake_pool_only() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let (pool, stake_pool) = rated_pool(&mut test);
+
+    // the stake pool is not updated for the current epoch
+    test.accounts.get_mut(&stake_pool).unwrap().data = stake_pool_data(1_200, 1_000, 1);
+    let refresh = update_rate(&atlas_swap::id(), &pool.swap, &stake_pool, None, None).unwrap();
+    assert_eq!(
+        test.process(&refresh),
+        Err(SwapError::InvalidRateAccount.into())
+    );
+    test.accounts.get_mut(&stake_pool).unwrap().data = stake_pool_data(1_200, 1_000, 0);
+    test.process(&refresh).unwrap();
+    assert_eq!(rate(&test, &pool), PRICE_PRECISION * 12 / 10);
+
+    // only the stake pool of the swap refreshes its rate
+    let other = Pubkey::new_unique();
+    test.add(
+        other,
+        Pubkey::new_unique(),
+        stake_pool_data(2_000, 1_000, 0),
+    );
+    assert_eq!(
+        test.process(&update_rate(&atlas_swap::id(), &pool.swap, &other, None, None).unwrap()),
+        Err(SwapError::InvalidRateAccount.into())
+    );
+    // and only the program owner sets another one
+    let intruder = Pubkey::new_unique();
+    assert_eq!(
+        test.process(
+            &set_rate_account(
+                &atlas_swap::id(),
+                &pool.swap,
+                &intruder,
+                &test.state,
+                &other,
+                None,
+            )
+            .unwrap()
+        ),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert_eq!(rate(&test, &pool), PRICE_PRECISION * 12 / 10);
+}
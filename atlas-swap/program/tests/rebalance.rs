@@ -0,0 +1,187 @@
+//! The fee owner may swap at no fee into the short side of a stable pool
+//! off its peg, up to the peg and up to MAX_REBALANCE_BPS_PER_EPOCH of the
+//! reserves per epoch.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+        stable::StableCurve,
+    },
+    error::SwapError,
+    instruction::{rebalance, Rebalance},
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+// twelve percent of the reserves off the peg, a tenth of them rebalanced per
+// epoch at most
+const RESERVE_A: u64 = 440_000_000;
+const RESERVE_B: u64 = 560_000_000;
+const AMOUNT_IN: u64 = 20_000_000;
+
+fn stable_curve() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::Stable,
+        calculator: StableCurve {
+            amp: 100,
+            ..StableCurve::default()
+        }
+        .into(),
+    }
+}
+
+fn new_test() -> ProgramTest {
+    ProgramTest::new(Fees {
+        constant_product_return_fee_numerator: 25,
+        stable_return_fee_numerator: 4,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    })
+}
+
+/// The fee owner's token accounts, holding enough of both tokens
+fn fee_owner_accounts(test: &mut ProgramTest, pool: &TestPool) -> (Pubkey, Pubkey) {
+    let fee_owner = test.fee_owner;
+    (
+        test.add_token_account(&pool.token_a_mint, &fee_owner, RESERVE_A),
+        test.add_token_account(&pool.token_b_mint, &fee_owner, RESERVE_B),
+    )
+}
+
+/// A rebalance of `amount_in` of token A into `pool`, signed by `signer`
+fn rebalance_a(
+    test: &ProgramTest,
+    pool: &TestPool,
+    signer: &Pubkey,
+    accounts: (Pubkey, Pubkey),
+    amount_in: u64,
+) -> Instruction {
+    rebalance(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        signer,
+        &test.state,
+        &accounts.0,
+        &pool.token_a,
+        &pool.token_b,
+        &accounts.1,
+        Rebalance {
+            amount_in,
+            minimum_amount_out: 1,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn the_fee_owner_tops_up_the_short_side_at_no_fee() {
+    let mut test = new_test();
+    let pool = test.create_pool(stable_curve(), RESERVE_A, RESERVE_B);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let fee_owner = test.fee_owner;
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    test.process(&instruction).unwrap();
+
+    let expected = test
+        .swap_v2(&pool.swap)
+        .swap_curve
+        .swap(
+            AMOUNT_IN as u128,
+            RESERVE_A as u128,
+            RESERVE_B as u128,
+            TradeDirection::AtoB,
+            &Fees::default(),
+        )
+        .unwrap()
+        .destination_amount_swapped as u64;
+    assert_eq!(test.token_amount(&accounts.0), RESERVE_A - AMOUNT_IN);
+    assert_eq!(test.token_amount(&accounts.1), RESERVE_B + expected);
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, RESERVE_A + AMOUNT_IN);
+    assert_eq!(swap_v2.token_b_reserve, RESERVE_B - expected);
+    assert_eq!(swap_v2.rebalance_amount, AMOUNT_IN);
+}
+
+#[test]
+fn only_the_signed_fee_owner_rebalances() {
+    let mut test = new_test();
+    let pool = test.create_pool(stable_curve(), RESERVE_A, RESERVE_B);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let fee_owner = test.fee_owner;
+
+    let other = rebalance_a(&test, &pool, &Pubkey::new_unique(), accounts, AMOUNT_IN);
+    assert_eq!(test.process(&other), Err(SwapError::InvalidOwner.into()));
+    let mut unsigned = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    unsigned.accounts[2].is_signer = false;
+    assert_eq!(
+        test.process(&unsigned),
+        Err(SwapError::InvalidSigner.into())
+    );
+    assert_eq!(test.token_amount(&accounts.0), RESERVE_A);
+}
+
+#[test]
+fn rebalances_restore_the_peg_of_stable_pools_only() {
+    let mut test = new_test();
+    let fee_owner = test.fee_owner;
+
+    // a pool on its peg
+    let pool = test.create_pool(stable_curve(), RESERVE_B, RESERVE_B);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidRebalance.into())
+    );
+    // the long side of a pool off its peg
+    let pool = test.create_pool(stable_curve(), RESERVE_B, RESERVE_A);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidRebalance.into())
+    );
+    // a constant product pool
+    let constant_product = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(constant_product, RESERVE_A, RESERVE_B);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::UnsupportedCurveOperation.into())
+    );
+}
+
+#[test]
+fn rebalances_stop_at_the_peg_and_the_epoch_limit() {
+    let mut test = new_test();
+    let pool = test.create_pool(stable_curve(), RESERVE_A, RESERVE_B);
+    let accounts = fee_owner_accounts(&mut test, &pool);
+    let fee_owner = test.fee_owner;
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, AMOUNT_IN);
+    test.process(&instruction).unwrap();
+
+    // past a tenth of the reserves this epoch
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, 80_000_001);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::RebalanceLimitExceeded.into())
+    );
+    // within the limit, but past the peg
+    let instruction = rebalance_a(&test, &pool, &fee_owner, accounts, 70_000_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidRebalance.into())
+    );
+    assert_eq!(test.token_amount(&accounts.0), RESERVE_A - AMOUNT_IN);
+}
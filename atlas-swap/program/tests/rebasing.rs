@@ -0,0 +1,135 @@
+//! Rebasing pools trade against their vault balances, so balance changes
+//! outside of trades accrue to the liquidity providers, while other pools
+//! keep trading against their tracked reserves.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{set_rebasing, SetRebasing},
+};
+use common::{ProgramTest, TestPool};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const REBASED: u64 = 1_100_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+fn set_rebasing_instruction(
+    test: &ProgramTest,
+    pool: &TestPool,
+    owner: &Pubkey,
+    rebasing: bool,
+) -> Instruction {
+    set_rebasing(
+        &atlas_swap::id(),
+        &pool.swap,
+        owner,
+        &test.state,
+        &pool.token_a,
+        &pool.token_b,
+        SetRebasing { rebasing },
+    )
+    .unwrap()
+}
+
+/// Output of a swap of `AMOUNT_IN` token A after the token B vault of a
+/// pool rebased up, the pool marked rebasing or not
+fn swap_after_rebase(rebasing: bool) -> (u64, ProgramTest, TestPool) {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let owner = test.owner;
+    test.process(&set_rebasing_instruction(&test, &pool, &owner, rebasing))
+        .unwrap();
+    assert_eq!(test.swap_v2(&pool.swap).rebasing, rebasing);
+
+    test.set_token_account(&pool.token_b, |vault| vault.amount = REBASED);
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+    (test.token_amount(&user.token_b), test, pool)
+}
+
+fn quote(token_b_reserve: u64) -> u64 {
+    get_swap_quote(
+        RESERVE,
+        token_b_reserve,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &Fees {
+            fee_denominator: 10_000,
+            ..Fees::default()
+        },
+        &SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+    )
+    .unwrap()
+    .destination_amount_swapped as u64
+}
+
+#[test]
+fn rebasing_pool_trades_against_its_vault_balances() {
+    let (amount_out, test, pool) = swap_after_rebase(true);
+    assert_eq!(amount_out, quote(REBASED));
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_b_reserve, REBASED - amount_out);
+}
+
+#[test]
+fn other_pools_trade_against_their_tracked_reserves() {
+    let (amount_out, mut test, pool) = swap_after_rebase(false);
+    assert_eq!(amount_out, quote(RESERVE));
+    assert_eq!(
+        test.swap_v2(&pool.swap).token_b_reserve,
+        RESERVE - amount_out
+    );
+
+    // marking the pool rebasing takes the drift into its reserves
+    let owner = test.owner;
+    test.process(&set_rebasing_instruction(&test, &pool, &owner, true))
+        .unwrap();
+    assert_eq!(
+        test.swap_v2(&pool.swap).token_b_reserve,
+        REBASED - amount_out
+    );
+}
+
+#[test]
+fn only_the_program_owner_sets_the_rebasing_mode() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let intruder = Pubkey::new_unique();
+    assert_eq!(
+        test.process(&set_rebasing_instruction(&test, &pool, &intruder, true)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert!(!test.swap_v2(&pool.swap).rebasing);
+}
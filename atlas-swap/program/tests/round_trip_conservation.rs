@@ -0,0 +1,165 @@
+//! Economic safety of round trips: a deposit followed by a withdrawal of the
+//! same pool tokens never returns more than was deposited, and a swap
+//! followed by the reverse swap of its output never returns more than was
+//! swapped, whatever the curve, fees and reserve ratio.
+
+use atlas_swap::curve::{
+    base::{get_deposit_quote, get_swap_quote, get_withdraw_quote, CurveType, SwapCurve},
+    calculator::TradeDirection,
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+    stable::StableCurve,
+};
+use std::convert::TryFrom;
+
+const RESERVES: &[u64] = &[
+    1_000,
+    1_000_000,
+    1_000_000_000,
+    1_000_000_000_000_000,
+    u64::MAX / 4,
+];
+
+/// Trade fee and owner fee numerators over 10_000
+const FEES: &[(u64, u64)] = &[(0, 0), (1, 0), (0, 1), (25, 5), (30, 0), (100, 100), (9_000, 999)];
+
+fn curves() -> Vec<SwapCurve> {
+    let mut curves = vec![SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }];
+    for &amp in &[1, 100, 10_000] {
+        curves.push(SwapCurve {
+            curve_type: CurveType::Stable,
+            calculator: StableCurve {
+                amp,
+                ..StableCurve::default()
+            }
+            .into(),
+        });
+    }
+    curves
+}
+
+fn fees(trade_fee_numerator: u64, owner_fee_numerator: u64) -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: trade_fee_numerator,
+        constant_product_fixed_fee_numerator: owner_fee_numerator,
+        stable_return_fee_numerator: trade_fee_numerator,
+        stable_fixed_fee_numerator: owner_fee_numerator,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+/// Amounts from a single base unit up to a multiple of the reserve
+fn amounts(reserve: u64) -> impl Iterator<Item = u64> {
+    vec![1, 2, 7, reserve / 1_000, reserve / 3, reserve, reserve.saturating_mul(3)]
+        .into_iter()
+        .filter(|amount| *amount > 0)
+}
+
+#[test]
+fn swap_then_reverse_swap_never_profits() {
+    for curve in curves() {
+        for &(trade_fee_numerator, owner_fee_numerator) in FEES {
+            let fees = fees(trade_fee_numerator, owner_fee_numerator);
+            for &reserve_a in RESERVES {
+                for &reserve_b in RESERVES {
+                    for amount_in in amounts(reserve_a) {
+                        let out = match get_swap_quote(
+                            reserve_a,
+                            reserve_b,
+                            amount_in,
+                            TradeDirection::AtoB,
+                            &fees,
+                            &curve,
+                        ) {
+                            Some(out) if out.destination_amount_swapped > 0 => out,
+                            _ => continue,
+                        };
+                        // the owner fee leaves the pool
+                        let reserve_a = u64::try_from(
+                            u128::from(reserve_a) + out.pool_source_amount().unwrap(),
+                        )
+                        .unwrap();
+                        let reserve_b =
+                            u64::try_from(out.new_swap_destination_amount).unwrap();
+                        let amount_out = u64::try_from(out.destination_amount_swapped).unwrap();
+                        if let Some(back) = get_swap_quote(
+                            reserve_a,
+                            reserve_b,
+                            amount_out,
+                            TradeDirection::BtoA,
+                            &fees,
+                            &curve,
+                        ) {
+                            assert!(
+                                back.destination_amount_swapped <= u128::from(amount_in),
+                                "{:?} fees {:?} reserves {} {}: {} in, {} back",
+                                curve.curve_type,
+                                (trade_fee_numerator, owner_fee_numerator),
+                                reserve_a,
+                                reserve_b,
+                                amount_in,
+                                back.destination_amount_swapped,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn deposit_then_withdraw_never_profits() {
+    for curve in curves() {
+        for &withdraw_fee_numerator in &[0, 1, 30] {
+            let fees = fees(0, 0);
+            for &reserve_a in RESERVES {
+                for &reserve_b in RESERVES {
+                    for &supply in RESERVES {
+                        for pool_tokens in amounts(supply) {
+                            let deposit = match get_deposit_quote(
+                                reserve_a,
+                                reserve_b,
+                                supply,
+                                pool_tokens,
+                                &curve,
+                            ) {
+                                Some(deposit) => deposit,
+                                None => continue,
+                            };
+                            let reserves = (
+                                u64::try_from(u128::from(reserve_a) + deposit.token_a_amount),
+                                u64::try_from(u128::from(reserve_b) + deposit.token_b_amount),
+                                supply.checked_add(pool_tokens),
+                            );
+                            let (reserve_a, reserve_b, supply) = match reserves {
+                                (Ok(reserve_a), Ok(reserve_b), Some(supply)) => {
+                                    (reserve_a, reserve_b, supply)
+                                }
+                                _ => continue,
+                            };
+                            let withdrawal = match get_withdraw_quote(
+                                reserve_a,
+                                reserve_b,
+                                supply,
+                                pool_tokens,
+                                withdraw_fee_numerator,
+                                &fees,
+                                &curve,
+                            ) {
+                                Some(withdrawal) => withdrawal,
+                                None => continue,
+                            };
+                            assert!(withdrawal.token_a_amount <= deposit.token_a_amount);
+                            assert!(withdrawal.token_b_amount <= deposit.token_b_amount);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
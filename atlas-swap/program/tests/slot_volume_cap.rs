@@ -0,0 +1,87 @@
+//! A pool with a slot volume cap fails the swaps that take the token A
+//! volume of the current slot above the cap.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{set_slot_volume_cap, SetSlotVolumeCap},
+};
+use common::{set_clock, ProgramTest, TestPool};
+use solana_program::{entrypoint::ProgramResult, instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const CAP: u64 = 3_000_000;
+
+fn set_cap(test: &ProgramTest, pool: &TestPool, signer: &Pubkey, cap: u64) -> Instruction {
+    set_slot_volume_cap(
+        &atlas_swap::id(),
+        &pool.swap,
+        signer,
+        &test.state,
+        SetSlotVolumeCap {
+            max_slot_volume: cap,
+        },
+    )
+    .unwrap()
+}
+
+fn swap(test: &mut ProgramTest, pool: &TestPool, a_to_b: bool, amount: u64) -> ProgramResult {
+    let user = if a_to_b {
+        test.add_user(pool, amount, 0)
+    } else {
+        test.add_user(pool, 0, amount)
+    };
+    let swap = test.swap_instruction(pool, &user, a_to_b, amount, 1, None);
+    test.process(&swap)
+}
+
+#[test]
+fn swaps_of_a_slot_stay_under_the_cap() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    assert_eq!(
+        test.process(&set_cap(&test, &pool, &Pubkey::new_unique(), CAP)),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    let owner = test.owner;
+    test.process(&set_cap(&test, &pool, &owner, CAP)).unwrap();
+    assert_eq!(test.swap_v2(&pool.swap).max_slot_volume, CAP);
+
+    set_clock(10, 0);
+    swap(&mut test, &pool, true, 2_000_000).unwrap();
+    assert_eq!(
+        swap(&mut test, &pool, true, 2_000_000),
+        Err(SwapError::SlotVolumeExceeded.into())
+    );
+    // a B to A swap counts the token A it pays out
+    assert_eq!(
+        swap(&mut test, &pool, false, 2_000_000),
+        Err(SwapError::SlotVolumeExceeded.into())
+    );
+    swap(&mut test, &pool, false, 500_000).unwrap();
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.volume_slot, 10);
+    assert!(swap_v2.slot_volume > 2_000_000 && swap_v2.slot_volume <= CAP);
+
+    // the volume starts over in the next slot
+    set_clock(11, 0);
+    swap(&mut test, &pool, true, 2_000_000).unwrap();
+    assert_eq!(test.swap_v2(&pool.swap).volume_slot, 11);
+
+    // zero removes the cap
+    test.process(&set_cap(&test, &pool, &owner, 0)).unwrap();
+    swap(&mut test, &pool, true, 2 * CAP).unwrap();
+}
@@ -0,0 +1,215 @@
+//! Instructions built in the layout of upstream spl-token-swap, with the
+//! global state account appended, replay against pools of this program.
+
+#![cfg(feature = "spl-compat")]
+
+mod common;
+
+use atlas_swap::{
+    constraints::MIN_LP_SUPPLY,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    state::{SwapState, SwapVersion},
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+/// Data of an upstream instruction: its tag, then its u64 fields
+fn upstream_data(tag: u8, fields: &[u64]) -> Vec<u8> {
+    let mut data = vec![tag];
+    for field in fields {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data
+}
+
+fn upstream_swap(
+    test: &ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    fee_account: &Pubkey,
+    amount_in: u64,
+) -> Instruction {
+    Instruction {
+        program_id: atlas_swap::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(pool.swap, false),
+            AccountMeta::new_readonly(pool.authority, false),
+            AccountMeta::new_readonly(user.wallet, true),
+            AccountMeta::new(user.token_a, false),
+            AccountMeta::new(pool.token_a, false),
+            AccountMeta::new(pool.token_b, false),
+            AccountMeta::new(user.token_b, false),
+            AccountMeta::new(pool.pool_mint, false),
+            AccountMeta::new(*fee_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(test.state, false),
+        ],
+        data: upstream_data(1, &[amount_in, 1]),
+    }
+}
+
+#[test]
+fn upstream_initialize_creates_a_pool() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &atlas_swap::id()).0;
+    let token_a_mint = test.add_mint(&Pubkey::new_unique(), 6);
+    let token_b_mint = test.add_mint(&Pubkey::new_unique(), 6);
+    let pool_mint = test.add_mint(&authority, 6);
+    let token_a = test.add_token_account(&token_a_mint, &authority, RESERVE);
+    let token_b = test.add_token_account(&token_b_mint, &authority, RESERVE);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool_mint, &fee_owner, 0);
+    let provider = Pubkey::new_unique();
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &provider, 0);
+    let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+    test.add(swap, atlas_swap::id(), vec![0; SwapVersion::LATEST_LEN]);
+
+    // fees, then the swap curve
+    let mut data = upstream_data(0, &[25, 10_000, 5, 10_000, 0, 0, 20, 100]);
+    let mut swap_curve = vec![0; SwapCurve::LEN];
+    constant_product().pack_into_slice(&mut swap_curve);
+    data.extend_from_slice(&swap_curve);
+    let initialize = Instruction {
+        program_id: atlas_swap::id(),
+        accounts: vec![
+            AccountMeta::new(swap, true),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(token_a, false),
+            AccountMeta::new_readonly(token_b, false),
+            AccountMeta::new(pool_mint, false),
+            AccountMeta::new_readonly(fee_account, false),
+            AccountMeta::new(provider_pool_tokens, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(test.state, false),
+            AccountMeta::new_readonly(token_a_mint, false),
+            AccountMeta::new_readonly(token_b_mint, false),
+            AccountMeta::new(dead_shares, false),
+        ],
+        data,
+    };
+    test.process(&initialize).unwrap();
+
+    let swap_v2 = test.swap_v2(&swap);
+    assert!(swap_v2.is_initialized);
+    assert_eq!(swap_v2.token_a, token_a);
+    assert_eq!(swap_v2.token_b, token_b);
+    assert_eq!(swap_v2.pool_mint, pool_mint);
+    assert_eq!(swap_v2.swap_curve.curve_type, CurveType::ConstantProduct);
+    assert_eq!(swap_v2.fee_owner(), None);
+    assert_eq!(swap_v2.reserves(), Some((RESERVE, RESERVE)));
+    assert!(test.token_amount(&provider_pool_tokens) > 0);
+    assert_eq!(test.token_amount(&dead_shares), MIN_LP_SUPPLY as u64);
+    assert_eq!(test.token_amount(&fee_account), 0);
+    assert_eq!(test.global_state().pool_count, 1);
+}
+
+#[test]
+fn upstream_swap_deposit_and_withdraw_replay() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(constant_product(), RESERVE, RESERVE);
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.pool_mint, &fee_owner, 0);
+
+    // the pool charges no owner fee, its upstream fee account is left out
+    // and so is the host fee account
+    let mut swap = upstream_swap(&test, &pool, &user, &fee_account, RESERVE / 1_000);
+    let host_fee_account = test.add_token_account(&pool.pool_mint, &user.wallet, 0);
+    swap.accounts
+        .insert(10, AccountMeta::new(host_fee_account, false));
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&user.token_a) < RESERVE);
+    assert!(test.token_amount(&user.token_b) > RESERVE);
+    assert_eq!(test.token_amount(&fee_account), 0);
+    assert_eq!(test.token_amount(&host_fee_account), 0);
+
+    let deposit = Instruction {
+        program_id: atlas_swap::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(pool.swap, false),
+            AccountMeta::new_readonly(pool.authority, false),
+            AccountMeta::new_readonly(user.wallet, true),
+            AccountMeta::new(user.token_a, false),
+            AccountMeta::new(user.token_b, false),
+            AccountMeta::new(pool.token_a, false),
+            AccountMeta::new(pool.token_b, false),
+            AccountMeta::new(pool.pool_mint, false),
+            AccountMeta::new(user.pool_tokens, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(test.state, false),
+        ],
+        data: upstream_data(2, &[1_000_000, RESERVE, RESERVE]),
+    };
+    test.process(&deposit).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), 1_000_000);
+
+    let token_a_before = test.token_amount(&user.token_a);
+    let withdraw = Instruction {
+        program_id: atlas_swap::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(pool.swap, false),
+            AccountMeta::new_readonly(pool.authority, false),
+            AccountMeta::new_readonly(user.wallet, true),
+            AccountMeta::new(pool.pool_mint, false),
+            AccountMeta::new(user.pool_tokens, false),
+            AccountMeta::new(pool.token_a, false),
+            AccountMeta::new(pool.token_b, false),
+            AccountMeta::new(user.token_a, false),
+            AccountMeta::new(user.token_b, false),
+            AccountMeta::new(fee_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(test.state, false),
+        ],
+        data: upstream_data(3, &[1_000_000, 1, 1]),
+    };
+    test.process(&withdraw).unwrap();
+    assert_eq!(test.token_amount(&user.pool_tokens), 0);
+    assert!(test.token_amount(&user.token_a) > token_a_before);
+}
+
+#[test]
+fn upstream_swap_pays_the_owner_fee_in_pool_tokens_only() {
+    let mut test = ProgramTest::new(Fees {
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(constant_product(), RESERVE, RESERVE);
+    let user = test.add_user(&pool, RESERVE, 0);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.pool_mint, &fee_owner, 0);
+
+    // an owner fee in the SOURCE token has no upstream fee account to go to
+    let swap = upstream_swap(&test, &pool, &user, &fee_account, RESERVE / 1_000);
+    assert!(test.process(&swap).is_err());
+
+    test.set_swap_v2(&pool.swap, |swap_v2| {
+        swap_v2.owner_fee_in_pool_tokens = true
+    });
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&fee_account) > 0);
+}
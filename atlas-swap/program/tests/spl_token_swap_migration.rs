@@ -0,0 +1,183 @@
+//! MigrateFromSplTokenSwap withdraws liquidity from an spl-token-swap pool
+//! and deposits it into a pool of this program in one instruction, what the
+//! deposit cannot use staying in the user's accounts.
+
+mod common;
+
+use atlas_swap::{
+    constraints::SPL_TOKEN_SWAP_PROGRAM_ID,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{migrate_from_spl_token_swap, MigrateFromSplTokenSwap, SplTokenSwapPool},
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const UPSTREAM_SUPPLY: u64 = 1_000_000;
+const UPSTREAM_RESERVE: u64 = 2_000_000;
+
+/// Upstream pool of the tokens of `pool` over reserves of `reserve_a` and
+/// `reserve_b`, all of its pool tokens held by `user`, returned with the
+/// account of them
+fn upstream_pool(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    reserve_a: u64,
+    reserve_b: u64,
+) -> (SplTokenSwapPool, Pubkey) {
+    let authority = Pubkey::new_unique();
+    let pool_mint = test.add_mint(&authority, 6);
+    let pool_tokens = test.add_token_account(&pool_mint, &user.wallet, UPSTREAM_SUPPLY);
+    let upstream = SplTokenSwapPool {
+        program_id: SPL_TOKEN_SWAP_PROGRAM_ID,
+        swap_pubkey: Pubkey::new_unique(),
+        authority_pubkey: authority,
+        pool_mint_pubkey: pool_mint,
+        swap_token_a_pubkey: test.add_token_account(&pool.token_a_mint, &authority, reserve_a),
+        swap_token_b_pubkey: test.add_token_account(&pool.token_b_mint, &authority, reserve_b),
+        pool_fee_pubkey: test.add_token_account(&pool_mint, &Pubkey::new_unique(), 0),
+    };
+    (upstream, pool_tokens)
+}
+
+fn migrate(
+    test: &ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    upstream: &SplTokenSwapPool,
+    upstream_pool_tokens: &Pubkey,
+    minimum_pool_token_amount: u64,
+) -> Instruction {
+    migrate_from_spl_token_swap(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        upstream,
+        upstream_pool_tokens,
+        &pool.swap,
+        &pool.authority,
+        &user.wallet,
+        &test.state,
+        &user.token_a,
+        &user.token_b,
+        &pool.token_a,
+        &pool.token_b,
+        &pool.pool_mint,
+        &user.pool_tokens,
+        None,
+        None,
+        MigrateFromSplTokenSwap {
+            pool_token_amount: UPSTREAM_SUPPLY / 2,
+            minimum_pool_token_amount,
+        },
+    )
+    .unwrap()
+}
+
+fn pool(test: &mut ProgramTest) -> TestPool {
+    test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    )
+}
+
+#[test]
+fn migration_moves_liquidity_from_spl_token_swap() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = pool(&mut test);
+    let user = test.add_user(&pool, 0, 0);
+    let (upstream, upstream_pool_tokens) =
+        upstream_pool(&mut test, &pool, &user, UPSTREAM_RESERVE, UPSTREAM_RESERVE);
+    // half the upstream pool, at the reserves of this one
+    let expected = UPSTREAM_RESERVE / 2;
+
+    let foreign = SplTokenSwapPool {
+        program_id: Pubkey::new_unique(),
+        ..upstream.clone()
+    };
+    assert_eq!(
+        test.process(&migrate(
+            &test,
+            &pool,
+            &user,
+            &foreign,
+            &upstream_pool_tokens,
+            1
+        )),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    assert_eq!(
+        test.process(&migrate(
+            &test,
+            &pool,
+            &user,
+            &upstream,
+            &upstream_pool_tokens,
+            expected + 1
+        )),
+        Err(SwapError::ExceededSlippage.into())
+    );
+    test.process(&migrate(
+        &test,
+        &pool,
+        &user,
+        &upstream,
+        &upstream_pool_tokens,
+        expected * 99 / 100,
+    ))
+    .unwrap();
+
+    assert_eq!(
+        test.token_amount(&upstream_pool_tokens),
+        UPSTREAM_SUPPLY / 2
+    );
+    assert_eq!(
+        test.token_amount(&upstream.swap_token_a_pubkey),
+        UPSTREAM_RESERVE / 2
+    );
+    let minted = test.token_amount(&user.pool_tokens);
+    assert!(minted >= expected * 99 / 100 && minted <= expected);
+    assert!(test.token_amount(&user.token_a) <= 1);
+    assert!(test.token_amount(&user.token_b) <= 1);
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+}
+
+#[test]
+fn migration_leaves_the_imbalance_with_the_user() {
+    let mut test = ProgramTest::new(Fees::default());
+    let pool = pool(&mut test);
+    let user = test.add_user(&pool, 0, 0);
+    let (upstream, upstream_pool_tokens) = upstream_pool(
+        &mut test,
+        &pool,
+        &user,
+        UPSTREAM_RESERVE,
+        UPSTREAM_RESERVE / 2,
+    );
+    test.process(&migrate(
+        &test,
+        &pool,
+        &user,
+        &upstream,
+        &upstream_pool_tokens,
+        1,
+    ))
+    .unwrap();
+
+    // the token B withdrawn bounds the deposit
+    let minted = test.token_amount(&user.pool_tokens);
+    assert!((UPSTREAM_RESERVE / 4 * 99 / 100..=UPSTREAM_RESERVE / 4).contains(&minted));
+    assert!(test.token_amount(&user.token_b) <= 1);
+    assert!(test.token_amount(&user.token_a) >= UPSTREAM_RESERVE / 4);
+}
@@ -0,0 +1,272 @@
+//! SplitSwap routes a trade through two pools of the same pair, each pool
+//! taking its share of the input and paying at least its share of the
+//! minimum output.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{initialize, split_swap, SplitSwap, SplitSwapPool},
+    state::SwapVersion,
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+
+fn curve() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+/// A constant product pool of the tokens of `pool`, over reserves of
+/// `reserve_a` and `reserve_b`
+fn pool_of_same_pair(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    reserve_a: u64,
+    reserve_b: u64,
+) -> TestPool {
+    let program_id = atlas_swap::id();
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &program_id).0;
+    let pool_mint = test.add_mint(&authority, 6);
+    let token_a = test.add_token_account(&pool.token_a_mint, &authority, reserve_a);
+    let token_b = test.add_token_account(&pool.token_b_mint, &authority, reserve_b);
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &pool.provider, 0);
+    let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+    test.add(swap, program_id, vec![0; SwapVersion::LATEST_LEN]);
+    test.process(
+        &initialize(
+            &program_id,
+            &spl_token::id(),
+            &swap,
+            &authority,
+            &test.state,
+            &token_a,
+            &token_b,
+            &pool_mint,
+            &provider_pool_tokens,
+            &pool.token_a_mint,
+            &pool.token_b_mint,
+            &dead_shares,
+            curve(),
+            None,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    TestPool {
+        swap,
+        authority,
+        token_a,
+        token_b,
+        pool_mint,
+        provider_pool_tokens,
+        dead_shares,
+        ..*pool
+    }
+}
+
+/// The accounts of `pool` to swap token A into, the fee account held by
+/// the fee owner
+fn split_pool(test: &mut ProgramTest, pool: &TestPool) -> SplitSwapPool {
+    let fee_owner = test.fee_owner;
+    SplitSwapPool {
+        swap_pubkey: pool.swap,
+        authority_pubkey: pool.authority,
+        swap_source_pubkey: pool.token_a,
+        swap_destination_pubkey: pool.token_b,
+        pool_mint_pubkey: pool.pool_mint,
+        pool_fee_pubkey: test.add_token_account(&pool.token_a_mint, &fee_owner, 0),
+        oracle_pubkey: None,
+        gate_pubkey: None,
+        protocol_liquidity_pubkey: None,
+    }
+}
+
+fn split(
+    test: &ProgramTest,
+    user: &TestUser,
+    first_pool: &SplitSwapPool,
+    second_pool: &SplitSwapPool,
+    minimum_amount_out: u64,
+    first_pool_bps: u64,
+) -> Instruction {
+    split_swap(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &user.wallet,
+        &test.state,
+        &user.token_a,
+        &user.token_b,
+        first_pool,
+        second_pool,
+        &[],
+        SplitSwap {
+            amount_in: AMOUNT_IN,
+            minimum_amount_out,
+            first_pool_bps,
+        },
+    )
+    .unwrap()
+}
+
+/// Output of a fee-free constant product trade
+fn amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+    (reserve_out as u128 * amount_in as u128 / (reserve_in as u128 + amount_in as u128)) as u64
+}
+
+fn new_test() -> ProgramTest {
+    ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    })
+}
+
+#[test]
+fn split_swap_routes_the_input_in_proportion() {
+    let mut test = new_test();
+    let first = test.create_pool(curve(), RESERVE, RESERVE);
+    let second = pool_of_same_pair(&mut test, &first, RESERVE * 2, RESERVE * 2);
+    let user = test.add_user(&first, AMOUNT_IN, 0);
+    let first_pool = split_pool(&mut test, &first);
+    let second_pool = split_pool(&mut test, &second);
+
+    let expected = amount_out(RESERVE, RESERVE, AMOUNT_IN * 3 / 10)
+        + amount_out(RESERVE * 2, RESERVE * 2, AMOUNT_IN * 7 / 10);
+    // one percent of slippage, room for the smaller pool's larger impact
+    let minimum = expected * 99 / 100;
+    let instruction = split(&test, &user, &first_pool, &second_pool, minimum, 3_000);
+    test.process(&instruction).unwrap();
+    assert_eq!(
+        test.token_amount(&first.token_a),
+        RESERVE + AMOUNT_IN * 3 / 10
+    );
+    assert_eq!(
+        test.token_amount(&second.token_a),
+        RESERVE * 2 + AMOUNT_IN * 7 / 10
+    );
+    assert_eq!(test.token_amount(&user.token_a), 0);
+    assert_eq!(test.token_amount(&user.token_b), expected);
+}
+
+#[test]
+fn split_swap_fails_below_the_combined_minimum() {
+    let mut test = new_test();
+    let first = test.create_pool(curve(), RESERVE, RESERVE);
+    let second = pool_of_same_pair(&mut test, &first, RESERVE, RESERVE);
+    let user = test.add_user(&first, AMOUNT_IN, 0);
+    let first_pool = split_pool(&mut test, &first);
+    let second_pool = split_pool(&mut test, &second);
+
+    let expected = amount_out(RESERVE, RESERVE, AMOUNT_IN / 2) * 2;
+    let instruction = split(&test, &user, &first_pool, &second_pool, expected + 1, 5_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::ExceededSlippage.into())
+    );
+    let instruction = split(&test, &user, &first_pool, &second_pool, expected, 5_000);
+    test.process(&instruction).unwrap();
+    assert_eq!(test.token_amount(&user.token_b), expected);
+}
+
+#[test]
+fn each_pool_pays_its_share_of_the_minimum() {
+    let mut test = new_test();
+    // the first pool pays half the price of the second
+    let first = test.create_pool(curve(), RESERVE, RESERVE / 2);
+    let second = pool_of_same_pair(&mut test, &first, RESERVE, RESERVE * 3);
+    let user = test.add_user(&first, AMOUNT_IN, 0);
+    let first_pool = split_pool(&mut test, &first);
+    let second_pool = split_pool(&mut test, &second);
+
+    // the second pool alone covers the total, but the first pays short of
+    // half of it
+    let total = amount_out(RESERVE, RESERVE / 2, AMOUNT_IN / 2)
+        + amount_out(RESERVE, RESERVE * 3, AMOUNT_IN / 2);
+    let instruction = split(&test, &user, &first_pool, &second_pool, total, 5_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::ExceededSlippage.into())
+    );
+    // a minimum each pool covers its share of
+    let minimum = amount_out(RESERVE, RESERVE / 2, AMOUNT_IN / 2) * 2;
+    let instruction = split(&test, &user, &first_pool, &second_pool, minimum, 5_000);
+    test.process(&instruction).unwrap();
+    assert_eq!(test.token_amount(&user.token_b), total);
+}
+
+#[test]
+fn split_swap_needs_two_pools_of_the_same_pair() {
+    let mut test = new_test();
+    let first = test.create_pool(curve(), RESERVE, RESERVE);
+    let second = pool_of_same_pair(&mut test, &first, RESERVE, RESERVE);
+    let other = test.create_pool(curve(), RESERVE, RESERVE);
+    let user = test.add_user(&first, AMOUNT_IN, 0);
+    let first_pool = split_pool(&mut test, &first);
+    let second_pool = split_pool(&mut test, &second);
+    let other_pool = split_pool(&mut test, &other);
+
+    // the same pool twice
+    let instruction = split(&test, &user, &first_pool, &first_pool, 1, 5_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidSplitRoute.into())
+    );
+    // a pool of other tokens
+    let instruction = split(&test, &user, &first_pool, &other_pool, 1, 5_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidSplitRoute.into())
+    );
+    // the second pool swapping the other way
+    let reversed = SplitSwapPool {
+        swap_source_pubkey: second.token_b,
+        swap_destination_pubkey: second.token_a,
+        ..second_pool
+    };
+    let instruction = split(&test, &user, &first_pool, &reversed, 1, 5_000);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidSplitRoute.into())
+    );
+    // more than the whole input through the first pool
+    let instruction = split(&test, &user, &first_pool, &second_pool, 1, 10_001);
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidSplitRoute.into())
+    );
+    assert_eq!(test.token_amount(&user.token_a), AMOUNT_IN);
+}
+
+#[test]
+fn split_swap_checks_the_global_state() {
+    let mut test = new_test();
+    let first = test.create_pool(curve(), RESERVE, RESERVE);
+    let second = pool_of_same_pair(&mut test, &first, RESERVE, RESERVE);
+    let user = test.add_user(&first, AMOUNT_IN, 0);
+    let first_pool = split_pool(&mut test, &first);
+    let second_pool = split_pool(&mut test, &second);
+
+    // a copy of the global state at another address
+    let fake_state = Pubkey::new_unique();
+    let state_data = test.accounts[&test.state].data.clone();
+    test.add(fake_state, atlas_swap::id(), state_data);
+    let mut instruction = split(&test, &user, &first_pool, &second_pool, 1, 5_000);
+    instruction.accounts[1].pubkey = fake_state;
+    assert_eq!(
+        test.process(&instruction),
+        Err(SwapError::InvalidProgramAddress.into())
+    );
+    assert_eq!(test.token_amount(&user.token_a), AMOUNT_IN);
+}
@@ -0,0 +1,82 @@
+//! Worst-case inputs for the Newton solvers of the stable curve: extreme
+//! amps, reserves and imbalances must settle within the iteration cap and
+//! quote sensible amounts.
+
+use atlas_swap::curve::{
+    calculator::{CurveCalculator, TradeDirection, VIRTUAL_PRICE_PRECISION},
+    stable::StableCurve,
+};
+
+const AMPS: &[u64] = &[1, 100, 10_000, 1_000_000];
+
+const RESERVES: &[u128] = &[
+    1,
+    3,
+    1_000,
+    1_000_000_000,
+    1_000_000_000_000_000_000,
+    u64::MAX as u128,
+];
+
+fn stable(amp: u64) -> StableCurve {
+    StableCurve {
+        amp,
+        ..StableCurve::default()
+    }
+}
+
+#[test]
+fn swap_never_returns_more_than_the_destination_reserve() {
+    for &amp in AMPS {
+        for &swap_source_amount in RESERVES {
+            for &swap_destination_amount in RESERVES {
+                for &source_amount in RESERVES {
+                    if let Some(result) = stable(amp).swap_without_fees(
+                        source_amount,
+                        swap_source_amount,
+                        swap_destination_amount,
+                        TradeDirection::AtoB,
+                    ) {
+                        assert!(result.destination_amount_swapped <= swap_destination_amount);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn swap_output_grows_with_the_amount_in() {
+    for &amp in AMPS {
+        let curve = stable(amp);
+        let mut previous = 0;
+        for source_amount in (1..=9).map(|i| i * 100_000_000_000u128) {
+            let result = curve
+                .swap_without_fees(
+                    source_amount,
+                    1_000_000_000_000,
+                    1_000,
+                    TradeDirection::BtoA,
+                )
+                .unwrap();
+            assert!(result.destination_amount_swapped >= previous);
+            previous = result.destination_amount_swapped;
+        }
+    }
+}
+
+#[test]
+fn invariant_of_extreme_reserves_is_bounded_by_their_sum() {
+    for &amp in AMPS {
+        for &swap_token_a_amount in RESERVES {
+            for &swap_token_b_amount in RESERVES {
+                let invariant = stable(amp)
+                    .virtual_price(swap_token_a_amount, swap_token_b_amount, 1)
+                    .map(|virtual_price| virtual_price / VIRTUAL_PRICE_PRECISION);
+                if let Some(invariant) = invariant {
+                    assert!(invariant <= swap_token_a_amount + swap_token_b_amount);
+                }
+            }
+        }
+    }
+}
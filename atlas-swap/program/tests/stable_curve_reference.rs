@@ -0,0 +1,139 @@
+//! Differential tests of the stable curve against an exact reference: the
+//! invariant and the swap amount of random states are solved in big
+//! integers by bisection, and the Newton solvers of the curve must land
+//! within the error of their rounding.
+//!
+//! The invariant of the curve takes 2x + 1 for n * x to never divide by
+//! zero, which lifts it above the exact one by at most D / (3 min(x, y)),
+//! and Newton's method adds a unit.
+//! Given that invariant, the destination reserve is solved to the unit, and
+//! the swap keeps one more back for the pool.
+
+use atlas_swap::curve::{
+    calculator::{CurveCalculator, TradeDirection, VIRTUAL_PRICE_PRECISION},
+    stable::StableCurve,
+};
+use num_bigint::BigUint;
+use proptest::prelude::*;
+use std::convert::TryInto;
+
+fn big(value: u128) -> BigUint {
+    BigUint::from(value)
+}
+
+/// Largest `value` in `[low, high]` for which `below` holds, `below` holding
+/// for `low` and turning false at most once
+fn bisect(mut low: BigUint, mut high: BigUint, below: impl Fn(&BigUint) -> bool) -> BigUint {
+    while low < high {
+        let middle = (&low + &high + 1u32) / 2u32;
+        if below(&middle) {
+            low = middle;
+        } else {
+            high = middle - 1u32;
+        }
+    }
+    low
+}
+
+/// Exact invariant rounded down, the root of
+/// D^3 + 4xy(Ann - 1)D = 4xy * Ann(x + y), Ann being twice the amp
+fn reference_d(amp: u64, x: u128, y: u128) -> u128 {
+    let ann = big(u128::from(amp) * 2);
+    let four_xy = big(x) * big(y) * 4u32;
+    let sum = big(x) + big(y);
+    let target = &four_xy * &ann * &sum;
+    let d = bisect(BigUint::from(0u32), sum.clone(), |d| {
+        d * d * d + &four_xy * (&ann - 1u32) * d <= target
+    });
+    d.try_into().unwrap()
+}
+
+/// Exact destination reserve rounded up keeping the invariant `d` with a
+/// source reserve of `x`, the positive root of
+/// 4x * Ann * y^2 + 4x(Ann * x + D - Ann * D)y = D^3
+fn reference_y(amp: u64, x: u128, d: u128) -> u128 {
+    let ann = big(u128::from(amp) * 2);
+    let (x, d) = (big(x), big(d));
+    let d_cubed = &d * &d * &d;
+    let lhs = |y: &BigUint| {
+        let y_squared = y * y;
+        let positive = &x * 4u32 * &ann * &y_squared + &x * 4u32 * (&ann * &x + &d) * y;
+        let negative = &x * 4u32 * &ann * &d * y;
+        (positive, negative)
+    };
+    // the root lies below the first power of two past it
+    let mut high = BigUint::from(1u32);
+    loop {
+        let (positive, negative) = lhs(&high);
+        if positive >= negative + &d_cubed {
+            break;
+        }
+        high *= 2u32;
+    }
+    let y = bisect(BigUint::from(0u32), high, |y| {
+        let (positive, negative) = lhs(y);
+        positive < negative + &d_cubed
+    }) + 1u32;
+    y.try_into().unwrap()
+}
+
+fn stable(amp: u64) -> StableCurve {
+    StableCurve {
+        amp,
+        ..StableCurve::default()
+    }
+}
+
+/// Invariant of the curve, exact as long as D * VIRTUAL_PRICE_PRECISION
+/// fits in a u128
+fn curve_d(amp: u64, x: u128, y: u128) -> u128 {
+    stable(amp)
+        .virtual_price(x, y, VIRTUAL_PRICE_PRECISION)
+        .unwrap()
+}
+
+/// Amounts from about a thousand up to about a billion tokens of 9
+/// decimals, spread over their orders of magnitude
+fn amount() -> impl Strategy<Value = u128> {
+    (1u128..1_000, 3u32..16).prop_map(|(mantissa, exponent)| mantissa * 10u128.pow(exponent))
+}
+
+fn amp() -> impl Strategy<Value = u64> {
+    prop_oneof![1u64..=10, 10u64..=1_000, 1_000u64..=1_000_000]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(4_000))]
+
+    #[test]
+    fn invariant_matches_the_reference(amp in amp(), x in amount(), y in amount()) {
+        let d = curve_d(amp, x, y);
+        let reference = reference_d(amp, x, y);
+        prop_assert!(d >= reference);
+        prop_assert!(d - reference <= 1 + reference / (3 * x.min(y)));
+    }
+
+    #[test]
+    fn swap_matches_the_reference(
+        amp in amp(),
+        x in amount(),
+        y in amount(),
+        source_amount in amount(),
+    ) {
+        let amount_swapped = stable(amp)
+            .swap_without_fees(source_amount, x, y, TradeDirection::AtoB)
+            .unwrap()
+            .destination_amount_swapped;
+
+        // against the exact destination reserve at the invariant of the curve
+        let new_y = reference_y(amp, x + source_amount, curve_d(amp, x, y));
+        let reference = y.saturating_sub(new_y);
+        prop_assert!(amount_swapped <= reference);
+        prop_assert!(amount_swapped + 1 >= reference);
+
+        // a larger invariant leaves more in the pool, so the swap never
+        // pays out more than at the exact invariant
+        let exact_y = reference_y(amp, x + source_amount, reference_d(amp, x, y));
+        prop_assert!(amount_swapped <= y.saturating_sub(exact_y));
+    }
+}
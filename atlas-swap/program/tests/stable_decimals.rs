@@ -0,0 +1,116 @@
+//! Stable pools of tokens with different decimals take the decimals of
+//! their mints at init and trade one whole token for about one whole token.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{Calculator, CurveType, SwapCurve},
+        fees::Fees,
+        stable::StableCurve,
+    },
+    instruction::initialize,
+    state::SwapVersion,
+};
+use common::{ProgramTest, TestPool};
+use solana_program::pubkey::Pubkey;
+
+/// One million whole tokens of 6 and 8 decimals
+const RESERVE_A: u64 = 1_000_000_000_000;
+const RESERVE_B: u64 = 100_000_000_000_000;
+
+/// A stable pool of a 6 decimals token A and an 8 decimals token B
+fn stable_pool(test: &mut ProgramTest) -> TestPool {
+    let program_id = atlas_swap::id();
+    let swap = Pubkey::new_unique();
+    let authority = Pubkey::find_program_address(&[swap.as_ref()], &program_id).0;
+    let provider = Pubkey::new_unique();
+    let token_a_mint = test.add_mint(&Pubkey::new_unique(), 6);
+    let token_b_mint = test.add_mint(&Pubkey::new_unique(), 8);
+    let pool_mint = test.add_mint(&authority, 8);
+    let token_a = test.add_token_account(&token_a_mint, &authority, RESERVE_A);
+    let token_b = test.add_token_account(&token_b_mint, &authority, RESERVE_B);
+    let provider_pool_tokens = test.add_token_account(&pool_mint, &provider, 0);
+    let dead_shares = test.add_token_account(&pool_mint, &authority, 0);
+    test.add(swap, program_id, vec![0; SwapVersion::LATEST_LEN]);
+    test.process(
+        &initialize(
+            &program_id,
+            &spl_token::id(),
+            &swap,
+            &authority,
+            &test.state,
+            &token_a,
+            &token_b,
+            &pool_mint,
+            &provider_pool_tokens,
+            &token_a_mint,
+            &token_b_mint,
+            &dead_shares,
+            SwapCurve {
+                curve_type: CurveType::Stable,
+                calculator: StableCurve {
+                    amp: 100,
+                    ..StableCurve::default()
+                }
+                .into(),
+            },
+            None,
+            None,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    TestPool {
+        swap,
+        authority,
+        token_a,
+        token_b,
+        token_a_mint,
+        token_b_mint,
+        pool_mint,
+        provider,
+        provider_pool_tokens,
+        dead_shares,
+    }
+}
+
+#[test]
+fn initialize_takes_the_decimals_of_the_mints() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = stable_pool(&mut test);
+    assert_eq!(
+        test.swap_v2(&pool.swap).swap_curve.calculator,
+        Calculator::Stable(StableCurve {
+            amp: 100,
+            token_a_decimals: 6,
+            token_b_decimals: 8,
+        })
+    );
+}
+
+#[test]
+fn swaps_trade_whole_tokens_at_par() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = stable_pool(&mut test);
+
+    // a thousand whole tokens each way
+    let user = test.add_user(&pool, 1_000_000_000, 100_000_000_000);
+    let a_to_b = test.swap_instruction(&pool, &user, true, 1_000_000_000, 1, None);
+    test.process(&a_to_b).unwrap();
+    let amount_out = test.token_amount(&user.token_b) - 100_000_000_000;
+    assert!(amount_out <= 100_000_000_000);
+    assert!(amount_out > 99_990_000_000);
+
+    let b_to_a = test.swap_instruction(&pool, &user, false, 100_000_000_000, 1, None);
+    test.process(&b_to_a).unwrap();
+    // back at par, within the drift the first swap left
+    let amount_out = test.token_amount(&user.token_a);
+    assert!((amount_out as i64 - 1_000_000_000).abs() < 100_000);
+}
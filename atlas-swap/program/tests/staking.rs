@@ -0,0 +1,264 @@
+//! Stakers of pool tokens share the rewards of each slot in proportion to
+//! their stake, and claim them as far as the reward vault is funded.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{
+        claim_rewards, initialize_stake_pool, stake, unstake, InitializeStakePool, Stake, Unstake,
+    },
+    pda::{find_stake_pool, find_stake_position},
+    state::{StakePool, StakePosition},
+};
+use common::{set_clock, ProgramTest, TestAccount, TestPool};
+use solana_program::{program_pack::Pack, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+
+/// A stake pool of `pool` paying 100 reward tokens per slot out of a vault
+/// holding `rewards`, and its reward mint
+fn setup(rewards: u64) -> (ProgramTest, TestPool, Pubkey, Pubkey) {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    test.accounts.insert(
+        test.owner,
+        TestAccount {
+            owner: system_program::id(),
+            lamports: 1_000_000_000,
+            data: vec![],
+        },
+    );
+    let stake_pool = find_stake_pool(&atlas_swap::id(), &pool.swap).0;
+    let stake_vault = test.add_token_account(&pool.pool_mint, &stake_pool, 0);
+    let reward_mint = test.add_mint(&Pubkey::new_unique(), 6);
+    let reward_vault = test.add_token_account(&reward_mint, &stake_pool, rewards);
+    set_clock(10, 0);
+    test.process(
+        &initialize_stake_pool(
+            &atlas_swap::id(),
+            &pool.swap,
+            &test.state,
+            &stake_pool,
+            &stake_vault,
+            &reward_vault,
+            &test.owner,
+            InitializeStakePool {
+                reward_per_slot: 100,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    (test, pool, stake_pool, reward_mint)
+}
+
+/// A staker holding `amount` pool tokens, and the accounts of its position
+struct Staker {
+    wallet: Pubkey,
+    pool_tokens: Pubkey,
+    rewards: Pubkey,
+    position: Pubkey,
+}
+
+fn add_staker(
+    test: &mut ProgramTest,
+    pool: &TestPool,
+    stake_pool: &Pubkey,
+    reward_mint: &Pubkey,
+    amount: u64,
+) -> Staker {
+    let wallet = test.add_payer(1_000_000_000);
+    Staker {
+        wallet,
+        pool_tokens: test.add_token_account(&pool.pool_mint, &wallet, amount),
+        rewards: test.add_token_account(reward_mint, &wallet, 0),
+        position: find_stake_position(&atlas_swap::id(), stake_pool, &wallet).0,
+    }
+}
+
+fn stake_instruction(
+    test: &ProgramTest,
+    pool: &TestPool,
+    stake_pool: &Pubkey,
+    staker: &Staker,
+    amount: u64,
+) -> solana_program::instruction::Instruction {
+    let state = StakePool::unpack_from_slice(&test.accounts[stake_pool].data).unwrap();
+    stake(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        stake_pool,
+        &staker.position,
+        &state.stake_vault,
+        &staker.wallet,
+        &staker.pool_tokens,
+        Stake { amount },
+    )
+    .unwrap()
+}
+
+fn unstake_instruction(
+    test: &ProgramTest,
+    pool: &TestPool,
+    stake_pool: &Pubkey,
+    staker: &Staker,
+    amount: u64,
+) -> solana_program::instruction::Instruction {
+    let state = StakePool::unpack_from_slice(&test.accounts[stake_pool].data).unwrap();
+    unstake(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        stake_pool,
+        &staker.position,
+        &state.stake_vault,
+        &staker.wallet,
+        &staker.pool_tokens,
+        Unstake { amount },
+    )
+    .unwrap()
+}
+
+fn claim_instruction(
+    test: &ProgramTest,
+    pool: &TestPool,
+    stake_pool: &Pubkey,
+    staker: &Staker,
+) -> solana_program::instruction::Instruction {
+    let state = StakePool::unpack_from_slice(&test.accounts[stake_pool].data).unwrap();
+    claim_rewards(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        stake_pool,
+        &staker.position,
+        &state.reward_vault,
+        &staker.wallet,
+        &staker.rewards,
+    )
+    .unwrap()
+}
+
+#[test]
+fn rewards_are_shared_in_proportion_to_the_stake() {
+    let (mut test, pool, stake_pool, reward_mint) = setup(1_000_000);
+    let first = add_staker(&mut test, &pool, &stake_pool, &reward_mint, 400);
+    let second = add_staker(&mut test, &pool, &stake_pool, &reward_mint, 100);
+
+    test.process(&stake_instruction(&test, &pool, &stake_pool, &first, 400))
+        .unwrap();
+    let position = StakePosition::unpack_from_slice(&test.accounts[&first.position].data).unwrap();
+    assert_eq!(position.owner, first.wallet);
+    assert_eq!(position.amount, 400);
+    assert_eq!(test.token_amount(&first.pool_tokens), 0);
+
+    // the first staker earns all 1000 rewards of slots 10 to 20, then 4/5 of
+    // the 1000 of slots 20 to 30
+    set_clock(20, 0);
+    test.process(&stake_instruction(&test, &pool, &stake_pool, &second, 100))
+        .unwrap();
+    set_clock(30, 0);
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &first))
+        .unwrap();
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &second))
+        .unwrap();
+    assert_eq!(test.token_amount(&first.rewards), 1_800);
+    assert_eq!(test.token_amount(&second.rewards), 200);
+
+    assert_eq!(
+        test.process(&unstake_instruction(&test, &pool, &stake_pool, &first, 401)),
+        Err(SwapError::InsufficientStake.into())
+    );
+    test.process(&unstake_instruction(&test, &pool, &stake_pool, &first, 400))
+        .unwrap();
+    assert_eq!(test.token_amount(&first.pool_tokens), 400);
+    let state = StakePool::unpack_from_slice(&test.accounts[&stake_pool].data).unwrap();
+    assert_eq!(state.total_staked, 100);
+
+    // the second staker alone earns the rewards after the first left
+    set_clock(40, 0);
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &first))
+        .unwrap();
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &second))
+        .unwrap();
+    assert_eq!(test.token_amount(&first.rewards), 1_800);
+    assert_eq!(test.token_amount(&second.rewards), 1_200);
+}
+
+#[test]
+fn claims_stop_at_the_funded_rewards() {
+    let (mut test, pool, stake_pool, reward_mint) = setup(300);
+    let staker = add_staker(&mut test, &pool, &stake_pool, &reward_mint, 100);
+    test.process(&stake_instruction(&test, &pool, &stake_pool, &staker, 100))
+        .unwrap();
+
+    set_clock(20, 0);
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &staker))
+        .unwrap();
+    assert_eq!(test.token_amount(&staker.rewards), 300);
+    let position = StakePosition::unpack_from_slice(&test.accounts[&staker.position].data).unwrap();
+    assert_eq!(position.pending_rewards, 700);
+
+    // the rest is paid out once the vault is topped up
+    let state = StakePool::unpack_from_slice(&test.accounts[&stake_pool].data).unwrap();
+    test.set_token_account(&state.reward_vault, |vault| vault.amount = 700);
+    test.process(&claim_instruction(&test, &pool, &stake_pool, &staker))
+        .unwrap();
+    assert_eq!(test.token_amount(&staker.rewards), 1_000);
+}
+
+#[test]
+fn only_the_program_owner_initializes_a_stake_pool() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let stake_pool = find_stake_pool(&atlas_swap::id(), &pool.swap).0;
+    let stake_vault = test.add_token_account(&pool.pool_mint, &stake_pool, 0);
+    let reward_vault = test.add_token_account(&pool.token_a_mint, &stake_pool, 0);
+    let intruder = test.add_payer(1_000_000_000);
+    assert_eq!(
+        test.process(
+            &initialize_stake_pool(
+                &atlas_swap::id(),
+                &pool.swap,
+                &test.state,
+                &stake_pool,
+                &stake_vault,
+                &reward_vault,
+                &intruder,
+                InitializeStakePool {
+                    reward_per_slot: 100,
+                },
+            )
+            .unwrap(),
+        ),
+        Err(SwapError::InvalidProgramOwner.into())
+    );
+    assert!(test.accounts[&stake_pool].data.is_empty());
+}
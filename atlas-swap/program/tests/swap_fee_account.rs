@@ -0,0 +1,90 @@
+//! The fee account of a swap is left out of its accounts when the swap
+//! charges no owner fee, and required otherwise, holding the source token.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+};
+use common::ProgramTest;
+
+const RESERVE: u64 = 1_000_000_000;
+
+#[test]
+fn fee_account_is_required_only_for_an_owner_fee() {
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(swap_curve.clone(), RESERVE, RESERVE);
+    let user = test.add_user(&pool, RESERVE, 0);
+    let swap = test.swap_instruction(&pool, &user, true, RESERVE / 1_000, 1, None);
+    assert_eq!(swap.accounts[9].pubkey, spl_token::id());
+    test.process(&swap).unwrap();
+
+    let mut test = ProgramTest::new(Fees {
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(swap_curve, RESERVE, RESERVE);
+    let user = test.add_user(&pool, RESERVE, 0);
+    let fee_owner = test.fee_owner;
+    let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let swap = test.swap_instruction(&pool, &user, true, RESERVE / 1_000, 1, None);
+    assert_eq!(
+        test.process(&swap),
+        Err(SwapError::IncorrectFeeAccount.into())
+    );
+    let swap = test.swap_instruction(&pool, &user, true, RESERVE / 1_000, 1, Some(&fee_account));
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&fee_account) > 0);
+}
+
+#[test]
+fn fee_account_must_hold_the_source_token() {
+    let mut test = ProgramTest::new(Fees {
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    );
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+    let fee_owner = test.fee_owner;
+    let fee_a = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let fee_b = test.add_token_account(&pool.token_b_mint, &fee_owner, 0);
+
+    // a fee account of the destination token, either way
+    let swap = test.swap_instruction(&pool, &user, true, RESERVE / 1_000, 1, Some(&fee_b));
+    assert_eq!(
+        test.process(&swap),
+        Err(SwapError::IncorrectFeeAccountMint.into())
+    );
+    let swap = test.swap_instruction(&pool, &user, false, RESERVE / 1_000, 1, Some(&fee_a));
+    assert_eq!(
+        test.process(&swap),
+        Err(SwapError::IncorrectFeeAccountMint.into())
+    );
+    assert_eq!(test.token_amount(&user.token_a), RESERVE);
+    assert_eq!(test.token_amount(&user.token_b), RESERVE);
+
+    let swap = test.swap_instruction(&pool, &user, false, RESERVE / 1_000, 1, Some(&fee_b));
+    test.process(&swap).unwrap();
+    assert!(test.token_amount(&fee_b) > 0);
+}
@@ -0,0 +1,97 @@
+//! Boundary values for the owner fee split of a swap: the amount moved into
+//! the pool must never underflow, and overflowing inputs must fail cleanly.
+
+use atlas_swap::curve::{
+    base::{CurveType, SwapCurve, SwapResult},
+    calculator::TradeDirection,
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+};
+
+fn constant_product() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+fn fees(return_fee_numerator: u64, fixed_fee_numerator: u64) -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: return_fee_numerator,
+        constant_product_fixed_fee_numerator: fixed_fee_numerator,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+fn assert_split(result: &SwapResult) {
+    let pool_source_amount = result.pool_source_amount().unwrap();
+    assert_eq!(pool_source_amount + result.owner_fee, result.source_amount_swapped);
+}
+
+#[test]
+fn owner_fee_split_with_minimum_owner_fee() {
+    // the fee rounds down to zero, so the minimum fee of one token applies
+    let fees = Fees {
+        minimum_fee: 1,
+        ..fees(0, 30)
+    };
+    let result = constant_product()
+        .swap(100, 1_000_000, 1_000_000, TradeDirection::AtoB, &fees)
+        .unwrap();
+    assert_eq!(result.owner_fee, 1);
+    assert_eq!(result.pool_source_amount(), Some(99));
+}
+
+#[test]
+fn owner_fee_split_without_minimum_owner_fee() {
+    // the fee rounds down to zero and no minimum applies
+    let result = constant_product()
+        .swap(100, 1_000_000, 1_000_000, TradeDirection::AtoB, &fees(0, 30))
+        .unwrap();
+    assert_eq!(result.owner_fee, 0);
+    assert_eq!(result.pool_source_amount(), Some(100));
+}
+
+#[test]
+fn owner_fee_split_with_max_fixed_fee() {
+    let result = constant_product()
+        .swap(
+            1_000_000,
+            1_000_000_000,
+            1_000_000_000,
+            TradeDirection::AtoB,
+            &fees(0, 9_999),
+        )
+        .unwrap();
+    assert_split(&result);
+}
+
+#[test]
+fn owner_fee_split_with_u64_max_amounts() {
+    let max = u128::from(u64::MAX);
+    let result = constant_product()
+        .swap(max, max, max, TradeDirection::BtoA, &fees(25, 5))
+        .unwrap();
+    assert_split(&result);
+}
+
+#[test]
+fn owner_fee_split_rejects_owner_fee_above_swapped_amount() {
+    let result = SwapResult {
+        new_swap_source_amount: 0,
+        new_swap_destination_amount: 0,
+        source_amount_swapped: 10,
+        destination_amount_swapped: 0,
+        trade_fee: 0,
+        owner_fee: 11,
+    };
+    assert_eq!(result.pool_source_amount(), None);
+}
+
+#[test]
+fn swap_overflow_fails_without_panicking() {
+    assert!(constant_product()
+        .swap(u128::MAX, u128::MAX, u128::MAX, TradeDirection::AtoB, &fees(25, 5))
+        .is_none());
+}
@@ -0,0 +1,60 @@
+//! A swap takes from the user only the input the curve trades, the input it
+//! rounds away going back to the user rather than into the pool.
+
+mod common;
+
+use atlas_swap::curve::{
+    base::{CurveType, SwapCurve},
+    constant_product::{self, ConstantProductCurve},
+    fees::Fees,
+};
+use common::ProgramTest;
+
+const RESERVE_A: u64 = 1_000_000_000;
+const RESERVE_B: u64 = 1_000_000;
+
+#[test]
+fn input_rounded_away_by_the_curve_is_refunded() {
+    let mut test = ProgramTest::new(Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE_A,
+        RESERVE_B,
+    );
+    let user = test.add_user(&pool, RESERVE_A, 0);
+
+    let amount_in = 1_500;
+    let swapped = constant_product::swap(
+        u128::from(amount_in),
+        u128::from(RESERVE_A),
+        u128::from(RESERVE_B),
+    )
+    .unwrap();
+    let source_amount_swapped = swapped.source_amount_swapped as u64;
+    assert!(source_amount_swapped < amount_in);
+
+    test.process(&test.swap_instruction(&pool, &user, true, amount_in, 1, None))
+        .unwrap();
+    assert_eq!(
+        test.token_amount(&user.token_a),
+        RESERVE_A - source_amount_swapped
+    );
+    assert_eq!(
+        test.token_amount(&user.token_b),
+        swapped.destination_amount_swapped as u64
+    );
+    assert_eq!(
+        test.token_amount(&pool.token_a),
+        RESERVE_A + source_amount_swapped
+    );
+    assert_eq!(
+        test.swap_v2(&pool.swap).token_a_reserve,
+        RESERVE_A + source_amount_swapped
+    );
+}
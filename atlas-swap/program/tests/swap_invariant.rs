@@ -0,0 +1,103 @@
+//! Swaps that pass the post-trade checks move the vaults by exactly the
+//! reserve changes recorded in the pool, and never lower the value of the
+//! reserves under the curve.
+
+mod common;
+
+use atlas_swap::curve::{
+    base::{CurveType, SwapCurve},
+    calculator::CurveCalculator,
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+    stable::StableCurve,
+};
+use common::ProgramTest;
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNTS: &[u64] = &[10, 17, 1_000, 123_456, 10_000_000, 250_000_000, 13];
+
+fn check_swaps(swap_curve: SwapCurve) {
+    let mut test = ProgramTest::new(Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        stable_return_fee_numerator: 4,
+        stable_fixed_fee_numerator: 1,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    });
+    let pool = test.create_pool(swap_curve.clone(), RESERVE, RESERVE);
+    let fee_owner = test.fee_owner;
+    let token_a_fee = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+    let token_b_fee = test.add_token_account(&pool.token_b_mint, &fee_owner, 0);
+    let user = test.add_user(&pool, RESERVE, RESERVE);
+
+    let mut value = swap_curve
+        .calculator
+        .normalized_value(u128::from(RESERVE), u128::from(RESERVE))
+        .unwrap();
+    for (i, &amount_in) in AMOUNTS.iter().enumerate() {
+        let a_to_b = i % 2 == 0;
+        let fee_account = if a_to_b { &token_a_fee } else { &token_b_fee };
+        let swap = test.swap_instruction(&pool, &user, a_to_b, amount_in, 0, Some(fee_account));
+        let fee_balance = test.token_amount(fee_account);
+        let (vault_a, vault_b) = (
+            test.token_amount(&pool.token_a),
+            test.token_amount(&pool.token_b),
+        );
+        let (user_a, user_b) = (
+            test.token_amount(&user.token_a),
+            test.token_amount(&user.token_b),
+        );
+        test.process(&swap).unwrap();
+
+        let swap_v2 = test.swap_v2(&pool.swap);
+        assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+        assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+        // the tokens the user parted with went to the vault and fee account
+        let fee = test.token_amount(fee_account) - fee_balance;
+        if a_to_b {
+            let spent = user_a - test.token_amount(&user.token_a);
+            assert_eq!(swap_v2.token_a_reserve - vault_a + fee, spent);
+            assert_eq!(
+                vault_b - swap_v2.token_b_reserve,
+                test.token_amount(&user.token_b) - user_b
+            );
+        } else {
+            let spent = user_b - test.token_amount(&user.token_b);
+            assert_eq!(swap_v2.token_b_reserve - vault_b + fee, spent);
+            assert_eq!(
+                vault_a - swap_v2.token_a_reserve,
+                test.token_amount(&user.token_a) - user_a
+            );
+        }
+        let new_value = swap_curve
+            .calculator
+            .normalized_value(
+                u128::from(swap_v2.token_a_reserve),
+                u128::from(swap_v2.token_b_reserve),
+            )
+            .unwrap();
+        assert!(new_value.greater_than_or_equal(&value));
+        value = new_value;
+    }
+}
+
+#[test]
+fn constant_product_swaps_keep_the_invariant() {
+    check_swaps(SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    });
+}
+
+#[test]
+fn stable_swaps_keep_the_invariant() {
+    check_swaps(SwapCurve {
+        curve_type: CurveType::Stable,
+        calculator: StableCurve {
+            amp: 100,
+            ..StableCurve::default()
+        }
+        .into(),
+    });
+}
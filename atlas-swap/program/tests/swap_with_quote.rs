@@ -0,0 +1,623 @@
+//! SwapWithQuote fills a quote signed by the fee owner or the quoter, whose
+//! signature an ed25519 program instruction right before it verifies. The
+//! fill follows the rules of a swap, books what the vault received and may
+//! not beat the curve by more than MAX_QUOTE_DEVIATION_BPS. Each taker's
+//! quotes use their own rising nonces.
+
+mod common;
+
+use atlas_swap::{
+    constraints::MAX_QUOTE_DEVIATION_BPS,
+    curve::{
+        base::{get_swap_quote, CurveType, SwapCurve},
+        calculator::TradeDirection,
+        constant_product::ConstantProductCurve,
+        fees::{FeeRecipient, Fees},
+    },
+    error::SwapError,
+    instruction::{swap_with_quote, SwapArgs, SwapWithQuote},
+    pda::find_quote_nonce,
+    quote::Quote,
+    state::QuoteNonce,
+};
+use common::{set_clock, set_transfer_fee, ProgramTest, TestPool, TestUser};
+use solana_program::{
+    ed25519_program,
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{
+        self,
+        instructions::{construct_instructions_data, store_current_index, BorrowedInstruction},
+    },
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+const EXPIRY: i64 = 1_000;
+
+fn fees() -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+fn curve() -> SwapCurve {
+    SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    }
+}
+
+/// Pool fees whose trade fee covers a quote beating the curve by
+/// MAX_QUOTE_DEVIATION_BPS
+fn covering_fees() -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 100,
+        ..fees()
+    }
+}
+
+/// Output of the curve for `amount_in` of token A, `fees` taken, and the
+/// owner fee
+fn curve_quote(fees: &Fees, amount_in: u64) -> (u64, u64) {
+    let result = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        amount_in,
+        TradeDirection::AtoB,
+        fees,
+        &curve(),
+    )
+    .unwrap();
+    (
+        result.destination_amount_swapped as u64,
+        result.owner_fee as u64,
+    )
+}
+
+/// A pool quoted by `quoter`, a trader holding AMOUNT_IN of token A and the
+/// fee account of the pool's token A
+struct QuoteTest {
+    test: ProgramTest,
+    pool: TestPool,
+    user: TestUser,
+    fee_account: Pubkey,
+    quoter: Keypair,
+}
+
+impl QuoteTest {
+    fn new() -> Self {
+        let mut test = ProgramTest::new(fees());
+        let quoter = Keypair::new();
+        test.set_global_state(|state| state.quoter = quoter.pubkey());
+        let pool = test.create_pool(curve(), RESERVE, RESERVE);
+        let user = test.add_user(&pool, AMOUNT_IN, 0);
+        let fee_owner = test.fee_owner;
+        let fee_account = test.add_token_account(&pool.token_a_mint, &fee_owner, 0);
+        set_clock(0, 0);
+        Self {
+            test,
+            pool,
+            user,
+            fee_account,
+            quoter,
+        }
+    }
+
+    /// The quote of `amount_out` token B for AMOUNT_IN token A
+    fn quote(&self, amount_out: u64, nonce: u64) -> Quote {
+        self.quote_by(&self.user, amount_out, nonce)
+    }
+
+    fn quote_by(&self, taker: &TestUser, amount_out: u64, nonce: u64) -> Quote {
+        Quote {
+            swap: self.pool.swap,
+            taker: taker.wallet,
+            swap_source: self.pool.token_a,
+            amount_in: AMOUNT_IN,
+            amount_out,
+            expiry: EXPIRY,
+            nonce,
+        }
+    }
+
+    fn fill(&self, quote: &Quote) -> Instruction {
+        self.fill_by(&self.user, quote)
+    }
+
+    fn fill_by(&self, taker: &TestUser, quote: &Quote) -> Instruction {
+        swap_with_quote(
+            &atlas_swap::id(),
+            SwapArgs {
+                token_program_id: spl_token::id(),
+                swap_pubkey: self.pool.swap,
+                authority_pubkey: self.pool.authority,
+                user_transfer_authority_pubkey: taker.wallet,
+                global_state_pubkey: self.test.state,
+                source_pubkey: taker.token_a,
+                swap_source_pubkey: self.pool.token_a,
+                swap_destination_pubkey: self.pool.token_b,
+                destination_pubkey: taker.token_b,
+                pool_mint_pubkey: self.pool.pool_mint,
+                pool_fee_pubkey: Some(self.fee_account),
+                ..SwapArgs::default()
+            },
+            &find_quote_nonce(&atlas_swap::id(), &self.pool.swap, &taker.wallet).0,
+            SwapWithQuote {
+                amount_in: quote.amount_in,
+                amount_out: quote.amount_out,
+                expiry: quote.expiry,
+                nonce: quote.nonce,
+            },
+        )
+        .unwrap()
+    }
+
+    /// Nonce of the last quote `taker` filled
+    fn last_nonce(&self, taker: &TestUser) -> u64 {
+        let quote_nonce = find_quote_nonce(&atlas_swap::id(), &self.pool.swap, &taker.wallet).0;
+        QuoteNonce::unpack(&self.test.accounts[&quote_nonce].data)
+            .unwrap()
+            .nonce
+    }
+
+    /// Runs the last of `instructions`, the instructions sysvar holding
+    /// them all
+    fn process(&mut self, instructions: &[Instruction]) -> Result<(), SwapError> {
+        let borrowed = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: vec![],
+                data: &instruction.data,
+            })
+            .collect::<Vec<_>>();
+        let mut data = construct_instructions_data(&borrowed);
+        store_current_index(&mut data, (instructions.len() - 1) as u16);
+        self.test
+            .add(sysvar::instructions::id(), sysvar::id(), data);
+        self.test
+            .process(instructions.last().unwrap())
+            .map_err(|error| match error {
+                solana_program::program_error::ProgramError::Custom(code) => {
+                    SwapError::from_code(code).unwrap()
+                }
+                error => panic!("unexpected error {:?}", error),
+            })
+    }
+}
+
+/// Data of an ed25519 program instruction verifying one signature of
+/// `message` by `signer`, laid out as the ed25519 program reads it
+fn ed25519_data(signer: &Keypair, message: &[u8]) -> Vec<u8> {
+    const DATA_START: u16 = 16;
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_data_offset = signature_offset + 64;
+    let mut data = vec![1, 0];
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_data_offset,
+        message.len() as u16,
+        u16::MAX,
+    ]
+    .iter()
+    {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    data
+}
+
+fn verify_quote(signer: &Keypair, message: &[u8]) -> Instruction {
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data: ed25519_data(signer, message),
+    }
+}
+
+#[test]
+fn a_signed_quote_fills_and_pays_the_owner_fee() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, owner_fee) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    quote_test.process(&[verify, fill]).unwrap();
+
+    let QuoteTest {
+        test,
+        pool,
+        user,
+        fee_account,
+        ..
+    } = &quote_test;
+    assert_eq!(test.token_amount(&user.token_a), 0);
+    assert_eq!(test.token_amount(&user.token_b), amount_out);
+    assert_eq!(test.token_amount(fee_account), owner_fee);
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, RESERVE + AMOUNT_IN - owner_fee);
+    assert_eq!(swap_v2.token_b_reserve, RESERVE - amount_out);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+    assert_eq!(quote_test.last_nonce(user), 1);
+}
+
+#[test]
+fn the_fee_owner_may_sign_quotes() {
+    let mut quote_test = QuoteTest::new();
+    let fee_owner = Keypair::new();
+    quote_test
+        .test
+        .set_global_state(|state| state.fee_owner = fee_owner.pubkey());
+    let fee_account =
+        quote_test
+            .test
+            .add_token_account(&quote_test.pool.token_a_mint, &fee_owner.pubkey(), 0);
+    quote_test.fee_account = fee_account;
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&fee_owner, &quote.message());
+    let fill = quote_test.fill(&quote);
+    quote_test.process(&[verify, fill]).unwrap();
+    assert_eq!(
+        quote_test.test.token_amount(&quote_test.user.token_b),
+        amount_out
+    );
+}
+
+#[test]
+fn quotes_of_other_signers_fail() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&Keypair::new(), &quote.message());
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::InvalidQuote)
+    );
+}
+
+#[test]
+fn fills_of_another_message_than_the_signed_one_fail() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let signed = quote_test.quote(amount_out / 2, 1);
+    let verify = verify_quote(&quote_test.quoter, &signed.message());
+    let fill = quote_test.fill(&quote_test.quote(amount_out, 1));
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::InvalidQuote)
+    );
+
+    // the message offsets point past the message
+    let quote = quote_test.quote(amount_out, 1);
+    let mut verify = verify_quote(&quote_test.quoter, &quote.message());
+    verify.data.pop();
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::InvalidQuote)
+    );
+}
+
+#[test]
+fn expired_quotes_fail() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    set_clock(0, EXPIRY + 1);
+    assert_eq!(
+        quote_test.process(&[verify.clone(), fill.clone()]),
+        Err(SwapError::QuoteExpired)
+    );
+    set_clock(0, EXPIRY);
+    quote_test.process(&[verify, fill]).unwrap();
+}
+
+#[test]
+fn quote_nonces_only_rise() {
+    let mut quote_test = QuoteTest::new();
+    quote_test.test.set_global_state(|state| {
+        state.fees = Fees {
+            fee_denominator: 10_000,
+            ..Fees::default()
+        }
+    });
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN / 4);
+    let fill = |quote_test: &mut QuoteTest, nonce: u64| {
+        let quote = Quote {
+            amount_in: AMOUNT_IN / 4,
+            ..quote_test.quote(amount_out / 2, nonce)
+        };
+        let verify = verify_quote(&quote_test.quoter, &quote.message());
+        let fill = quote_test.fill(&quote);
+        quote_test.process(&[verify, fill])
+    };
+    fill(&mut quote_test, 5).unwrap();
+    assert_eq!(fill(&mut quote_test, 5), Err(SwapError::InvalidQuote));
+    assert_eq!(fill(&mut quote_test, 4), Err(SwapError::InvalidQuote));
+    fill(&mut quote_test, 6).unwrap();
+    assert_eq!(quote_test.last_nonce(&quote_test.user), 6);
+}
+
+#[test]
+fn the_verify_instruction_must_directly_precede_the_fill() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+
+    // nothing before the fill
+    assert_eq!(
+        quote_test.process(std::slice::from_ref(&fill)),
+        Err(SwapError::InvalidQuote)
+    );
+    // another instruction between the verification and the fill
+    let other = Instruction {
+        program_id: Pubkey::new_unique(),
+        accounts: vec![],
+        data: vec![],
+    };
+    assert_eq!(
+        quote_test.process(&[verify.clone(), other, fill.clone()]),
+        Err(SwapError::InvalidQuote)
+    );
+    // the verification after the fill
+    let borrowed = [&verify, &fill, &verify]
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: vec![],
+            data: &instruction.data,
+        })
+        .collect::<Vec<_>>();
+    let mut data = construct_instructions_data(&borrowed);
+    store_current_index(&mut data, 0);
+    quote_test
+        .test
+        .add(sysvar::instructions::id(), sysvar::id(), data);
+    assert_eq!(
+        quote_test.test.process(&fill),
+        Err(SwapError::InvalidQuote.into())
+    );
+    // the verification data read from another instruction
+    let mut verify_elsewhere = verify.clone();
+    verify_elsewhere.data[4..6].copy_from_slice(&1u16.to_le_bytes());
+    assert_eq!(
+        quote_test.process(&[verify_elsewhere, fill.clone()]),
+        Err(SwapError::InvalidQuote)
+    );
+    quote_test.process(&[verify, fill]).unwrap();
+}
+
+#[test]
+fn verify_instructions_of_several_signatures_fail() {
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let mut verify = verify_quote(&quote_test.quoter, &quote.message());
+    verify.data[0] = 2;
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill.clone()]),
+        Err(SwapError::InvalidQuote)
+    );
+    // a verification by another program
+    let not_ed25519 = Instruction {
+        program_id: Pubkey::new_unique(),
+        ..verify_quote(&quote_test.quoter, &quote.message())
+    };
+    assert_eq!(
+        quote_test.process(&[not_ed25519, fill]),
+        Err(SwapError::InvalidQuote)
+    );
+}
+
+#[test]
+fn quotes_may_beat_the_curve_by_the_maximum_deviation_only() {
+    let mut quote_test = QuoteTest::new();
+    quote_test
+        .test
+        .set_global_state(|state| state.fees = covering_fees());
+    let (amount_out, _) = curve_quote(&covering_fees(), AMOUNT_IN);
+    let max_amount_out =
+        (amount_out as u128 * (10_000 + MAX_QUOTE_DEVIATION_BPS as u128) / 10_000) as u64;
+    let quote = quote_test.quote(max_amount_out + 1, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::QuoteAboveCurve)
+    );
+    // a quote draining the vault
+    let quote = quote_test.quote(RESERVE - 1, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::QuoteAboveCurve)
+    );
+
+    let quote = quote_test.quote(max_amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    quote_test.process(&[verify, fill]).unwrap();
+    assert_eq!(
+        quote_test.test.token_amount(&quote_test.user.token_b),
+        max_amount_out
+    );
+}
+
+#[test]
+fn quotes_may_not_take_value_from_the_pool() {
+    // the trade fee of the pool falls short of the maximum deviation
+    let mut quote_test = QuoteTest::new();
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let max_amount_out =
+        (amount_out as u128 * (10_000 + MAX_QUOTE_DEVIATION_BPS as u128) / 10_000) as u64;
+    let quote = quote_test.quote(max_amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::InvariantViolated)
+    );
+    assert_eq!(
+        quote_test.test.token_amount(&quote_test.user.token_a),
+        AMOUNT_IN
+    );
+}
+
+#[test]
+fn fills_leave_the_quotes_of_other_takers_open() {
+    let mut quote_test = QuoteTest::new();
+    let other = quote_test.test.add_user(&quote_test.pool, AMOUNT_IN, 0);
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let other_quote = quote_test.quote_by(&other, amount_out / 2, 1);
+    let other_verify = verify_quote(&quote_test.quoter, &other_quote.message());
+    let other_fill = quote_test.fill_by(&other, &other_quote);
+
+    let quote = quote_test.quote(amount_out, 5);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    quote_test.process(&[verify, fill]).unwrap();
+    quote_test
+        .process(&[other_verify.clone(), other_fill.clone()])
+        .unwrap();
+    assert_eq!(quote_test.test.token_amount(&other.token_b), amount_out / 2);
+    assert_eq!(quote_test.last_nonce(&quote_test.user), 5);
+    assert_eq!(quote_test.last_nonce(&other), 1);
+    // each quote fills once
+    assert_eq!(
+        quote_test.process(&[other_verify, other_fill]),
+        Err(SwapError::InvalidQuote)
+    );
+}
+
+#[test]
+fn fills_pay_the_fee_recipients() {
+    let mut quote_test = QuoteTest::new();
+    let fee_recipient = FeeRecipient {
+        owner: Pubkey::new_unique(),
+        weight_bps: 5_000,
+    };
+    quote_test
+        .test
+        .set_global_state(|state| state.fee_recipients[0] = fee_recipient);
+    let recipient_account =
+        quote_test
+            .test
+            .add_token_account(&quote_test.pool.token_a_mint, &fee_recipient.owner, 0);
+    let (amount_out, owner_fee) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let mut fill = quote_test.fill(&quote);
+    fill.accounts
+        .push(AccountMeta::new(recipient_account, false));
+    quote_test.process(&[verify, fill]).unwrap();
+    let recipient_amount = owner_fee / 2;
+    assert!(recipient_amount > 0);
+    let QuoteTest {
+        test,
+        pool,
+        fee_account,
+        ..
+    } = &quote_test;
+    assert_eq!(test.token_amount(&recipient_account), recipient_amount);
+    assert_eq!(test.token_amount(fee_account), owner_fee - recipient_amount);
+    assert_eq!(
+        test.swap_v2(&pool.swap).token_a_reserve,
+        test.token_amount(&pool.token_a)
+    );
+}
+
+#[test]
+fn fills_mint_the_owner_fee_in_pool_tokens() {
+    let mut quote_test = QuoteTest::new();
+    let swap = quote_test.pool.swap;
+    quote_test
+        .test
+        .set_swap_v2(&swap, |swap_v2| swap_v2.owner_fee_in_pool_tokens = true);
+    let fee_owner = quote_test.test.fee_owner;
+    quote_test.fee_account =
+        quote_test
+            .test
+            .add_token_account(&quote_test.pool.pool_mint, &fee_owner, 0);
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    let supply = quote_test.test.mint(&quote_test.pool.pool_mint).supply;
+    quote_test.process(&[verify, fill]).unwrap();
+
+    // the whole input stays in the pool
+    let QuoteTest {
+        test,
+        pool,
+        fee_account,
+        ..
+    } = &quote_test;
+    assert_eq!(test.token_amount(&pool.token_a), RESERVE + AMOUNT_IN);
+    assert_eq!(
+        test.swap_v2(&pool.swap).token_a_reserve,
+        RESERVE + AMOUNT_IN
+    );
+    let minted = test.token_amount(fee_account);
+    assert!(minted > 0);
+    assert_eq!(test.mint(&pool.pool_mint).supply, supply + minted);
+}
+
+#[test]
+fn fills_book_what_the_vault_received() {
+    let mut quote_test = QuoteTest::new();
+    set_transfer_fee(&quote_test.pool.token_a_mint, 100);
+    let amount_received = AMOUNT_IN - AMOUNT_IN / 100;
+    let (amount_out, owner_fee) = curve_quote(&fees(), amount_received);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    quote_test.process(&[verify, fill]).unwrap();
+
+    let QuoteTest { test, pool, .. } = &quote_test;
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(
+        swap_v2.token_a_reserve,
+        RESERVE + amount_received - owner_fee
+    );
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+}
+
+#[test]
+fn the_owner_fee_goes_to_the_fee_owner() {
+    let mut quote_test = QuoteTest::new();
+    let wallet = quote_test.user.wallet;
+    quote_test.fee_account =
+        quote_test
+            .test
+            .add_token_account(&quote_test.pool.token_a_mint, &wallet, 0);
+    let (amount_out, _) = curve_quote(&fees(), AMOUNT_IN);
+    let quote = quote_test.quote(amount_out, 1);
+    let verify = verify_quote(&quote_test.quoter, &quote.message());
+    let fill = quote_test.fill(&quote);
+    assert_eq!(
+        quote_test.process(&[verify, fill]),
+        Err(SwapError::InvalidOwner)
+    );
+}
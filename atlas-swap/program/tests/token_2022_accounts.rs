@@ -0,0 +1,119 @@
+//! Token-2022 pools read extension-bearing accounts, trade public balances
+//! and reject accounts configured for confidential transfers.
+
+use atlas_swap::{
+    error::SwapError,
+    token_2022::{
+        is_token_program, unpack_account, unpack_mint, with_token_program, TOKEN_2022_PROGRAM_ID,
+    },
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use spl_token::state::{Account, AccountState, Mint};
+
+/// Extension type and length, followed by zeroed data
+fn extension(extension_type: u16, length: u16) -> Vec<u8> {
+    let mut tlv = extension_type.to_le_bytes().to_vec();
+    tlv.extend_from_slice(&length.to_le_bytes());
+    tlv.extend(vec![0; usize::from(length)]);
+    tlv
+}
+
+fn account_with_extensions(extensions: &[(u16, u16)]) -> Vec<u8> {
+    let mut data = vec![0; Account::LEN];
+    Account::pack(
+        Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 42,
+            state: AccountState::Initialized,
+            ..Account::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    if !extensions.is_empty() {
+        data.push(2);
+        for &(extension_type, length) in extensions {
+            data.extend(extension(extension_type, length));
+        }
+    }
+    data
+}
+
+fn mint_with_extensions(extensions: &[(u16, u16)]) -> Vec<u8> {
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    if !extensions.is_empty() {
+        data.resize(Account::LEN, 0);
+        data.push(1);
+        for &(extension_type, length) in extensions {
+            data.extend(extension(extension_type, length));
+        }
+    }
+    data
+}
+
+#[test]
+fn accounts_with_extensions_unpack_to_their_base_state() {
+    assert_eq!(unpack_account(&account_with_extensions(&[])).unwrap().amount, 42);
+    // ImmutableOwner
+    assert_eq!(unpack_account(&account_with_extensions(&[(7, 0)])).unwrap().amount, 42);
+    // extensions of a mint
+    let mut mint_type = account_with_extensions(&[(7, 0)]);
+    mint_type[Account::LEN] = 1;
+    assert_eq!(unpack_account(&mint_type), Err(SwapError::InvalidAccountExtensions));
+    let mut truncated = account_with_extensions(&[(7, 0), (8, 1)]);
+    truncated.pop();
+    assert_eq!(unpack_account(&truncated), Err(SwapError::InvalidAccountExtensions));
+}
+
+#[test]
+fn confidential_transfer_accounts_are_rejected() {
+    assert_eq!(
+        unpack_account(&account_with_extensions(&[(7, 0), (5, 295)])),
+        Err(SwapError::ConfidentialTransferAccount)
+    );
+}
+
+#[test]
+fn confidential_transfer_mints_trade_public_balances() {
+    // ConfidentialTransferMint
+    assert_eq!(unpack_mint(&mint_with_extensions(&[(4, 65)])).unwrap().decimals, 6);
+    assert_eq!(unpack_mint(&mint_with_extensions(&[])).unwrap().decimals, 6);
+    // TransferFeeConfig, PermanentDelegate and TransferHook
+    for &extension_type in &[1, 12, 14] {
+        assert_eq!(
+            unpack_mint(&mint_with_extensions(&[(4, 65), (extension_type, 64)])),
+            Err(SwapError::UnsupportedMintExtension)
+        );
+    }
+}
+
+#[test]
+fn token_instructions_target_either_token_program() {
+    let instruction = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &[],
+        1,
+    )
+    .unwrap();
+    assert!(is_token_program(&TOKEN_2022_PROGRAM_ID));
+    assert_eq!(
+        with_token_program(&TOKEN_2022_PROGRAM_ID, instruction.clone())
+            .unwrap()
+            .program_id,
+        TOKEN_2022_PROGRAM_ID
+    );
+    assert!(with_token_program(&Pubkey::new_unique(), instruction).is_err());
+}
@@ -0,0 +1,91 @@
+//! Pools price trades off their tracked reserves, so tokens sent straight
+//! to a vault move no price until Sync folds them in or Skim sends them
+//! out.
+
+mod common;
+
+use atlas_swap::instruction::{skim, sync};
+use common::{ProgramTest, TestPool};
+
+const RESERVE: u64 = 1_000_000_000;
+const DONATION: u64 = 500_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+
+/// Sends DONATION of token B straight to the vault of the pool
+fn donate(test: &mut ProgramTest, pool: &TestPool) {
+    test.set_token_account(&pool.token_b, |account| account.amount += DONATION);
+}
+
+/// Output of a fee-free constant product trade
+fn amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> u64 {
+    (reserve_out as u128 * amount_in as u128 / (reserve_in as u128 + amount_in as u128)) as u64
+}
+
+#[test]
+fn donations_move_no_price() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    donate(&mut test, &pool);
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+
+    let expected = amount_out(RESERVE, RESERVE, AMOUNT_IN);
+    assert_eq!(test.token_amount(&user.token_b), expected);
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, RESERVE + AMOUNT_IN);
+    assert_eq!(swap_v2.token_b_reserve, RESERVE - expected);
+    assert_eq!(
+        test.token_amount(&pool.token_b),
+        RESERVE - expected + DONATION
+    );
+}
+
+#[test]
+fn skim_sends_out_what_is_above_the_reserves() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    donate(&mut test, &pool);
+    let user = test.add_user(&pool, 0, 0);
+    let skim = skim(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &pool.token_a,
+        &pool.token_b,
+        &user.token_a,
+        &user.token_b,
+    )
+    .unwrap();
+    test.process(&skim).unwrap();
+
+    assert_eq!(test.token_amount(&user.token_a), 0);
+    assert_eq!(test.token_amount(&user.token_b), DONATION);
+    assert_eq!(test.token_amount(&pool.token_b), RESERVE);
+    assert_eq!(test.swap_v2(&pool.swap).token_b_reserve, RESERVE);
+}
+
+#[test]
+fn sync_folds_donations_into_the_reserves() {
+    let (mut test, pool) = ProgramTest::constant_product_pool(RESERVE);
+    donate(&mut test, &pool);
+    let sync = sync(
+        &atlas_swap::id(),
+        &pool.swap,
+        &pool.token_a,
+        &pool.token_b,
+        None,
+    )
+    .unwrap();
+    test.process(&sync).unwrap();
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, RESERVE);
+    assert_eq!(swap_v2.token_b_reserve, RESERVE + DONATION);
+
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+    assert_eq!(
+        test.token_amount(&user.token_b),
+        amount_out(RESERVE, RESERVE + DONATION, AMOUNT_IN)
+    );
+}
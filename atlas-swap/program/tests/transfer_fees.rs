@@ -0,0 +1,69 @@
+//! Swaps are quoted on the amount the source vault actually receives, so a
+//! token taking a cut of each transfer cannot be used to drain the pool.
+
+mod common;
+
+use atlas_swap::curve::{
+    base::{get_swap_quote, CurveType, SwapCurve},
+    calculator::TradeDirection,
+    constant_product::ConstantProductCurve,
+    fees::Fees,
+};
+use common::{set_transfer_fee, ProgramTest};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 10_000_000;
+const TRANSFER_FEE_BPS: u64 = 100;
+
+#[test]
+fn swaps_are_quoted_on_the_amount_received() {
+    let fees = Fees {
+        fee_denominator: 10_000,
+        ..Fees::default()
+    };
+    let mut test = ProgramTest::new(fees.clone());
+    let swap_curve = SwapCurve {
+        curve_type: CurveType::ConstantProduct,
+        calculator: ConstantProductCurve.into(),
+    };
+    let pool = test.create_pool(swap_curve.clone(), RESERVE, RESERVE);
+    set_transfer_fee(&pool.token_a_mint, TRANSFER_FEE_BPS);
+
+    let user = test.add_user(&pool, AMOUNT_IN, 0);
+    let swap = test.swap_instruction(&pool, &user, true, AMOUNT_IN, 1, None);
+    test.process(&swap).unwrap();
+
+    let amount_received = AMOUNT_IN - AMOUNT_IN * TRANSFER_FEE_BPS / 10_000;
+    let quote = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        amount_received,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    assert_eq!(
+        test.token_amount(&user.token_b),
+        quote.destination_amount_swapped as u64
+    );
+    let full_quote = get_swap_quote(
+        RESERVE,
+        RESERVE,
+        AMOUNT_IN,
+        TradeDirection::AtoB,
+        &fees,
+        &swap_curve,
+    )
+    .unwrap();
+    assert!(quote.destination_amount_swapped < full_quote.destination_amount_swapped);
+
+    // the reserves follow the vaults
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+    assert_eq!(
+        swap_v2.token_a_reserve,
+        RESERVE + quote.source_amount_swapped as u64
+    );
+}
@@ -0,0 +1,137 @@
+//! Swap, deposit and withdraw must reject a user transfer authority that did
+//! not sign, before touching any token account.
+
+use atlas_swap::{
+    error::SwapError,
+    instruction::{
+        deposit_all_token_types, swap, withdraw_all_token_types, DepositAllTokenTypes, Swap,
+        SwapArgs, WithdrawAllTokenTypes,
+    },
+    processor::Processor,
+};
+use solana_program::{
+    account_info::AccountInfo, instruction::Instruction, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Runs the instruction with empty accounts, dropping every signature
+fn process_unsigned(instruction: &Instruction) -> Result<(), ProgramError> {
+    let owner = Pubkey::new_unique();
+    let mut lamports = vec![0u64; instruction.accounts.len()];
+    let mut data = vec![Vec::<u8>::new(); instruction.accounts.len()];
+    let accounts: Vec<AccountInfo> = instruction
+        .accounts
+        .iter()
+        .zip(lamports.iter_mut())
+        .zip(data.iter_mut())
+        .map(|((meta, lamports), data)| {
+            AccountInfo::new(
+                &meta.pubkey,
+                false,
+                meta.is_writable,
+                lamports,
+                data,
+                &owner,
+                false,
+                0,
+            )
+        })
+        .collect();
+    Processor::process(&instruction.program_id, &accounts, &instruction.data)
+}
+
+#[test]
+fn swap_requires_user_transfer_authority_signature() {
+    let instruction = swap(
+        &atlas_swap::id(),
+        SwapArgs {
+            token_program_id: spl_token::id(),
+            swap_pubkey: Pubkey::new_unique(),
+            authority_pubkey: Pubkey::new_unique(),
+            user_transfer_authority_pubkey: Pubkey::new_unique(),
+            global_state_pubkey: Pubkey::new_unique(),
+            source_pubkey: Pubkey::new_unique(),
+            swap_source_pubkey: Pubkey::new_unique(),
+            swap_destination_pubkey: Pubkey::new_unique(),
+            destination_pubkey: Pubkey::new_unique(),
+            pool_mint_pubkey: Pubkey::new_unique(),
+            pool_fee_pubkey: Some(Pubkey::new_unique()),
+            ..SwapArgs::default()
+        },
+        Swap {
+            amount_in: 1_000,
+            minimum_amount_out: 0,
+            max_price_impact_bps: None,
+            tip: 0,
+            memo: Vec::new(),
+            trade_direction: None,
+            fee_discount: false,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        process_unsigned(&instruction),
+        Err(SwapError::InvalidSigner.into())
+    );
+}
+
+#[test]
+fn deposit_requires_user_transfer_authority_signature() {
+    let instruction = deposit_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        None,
+        DepositAllTokenTypes {
+            pool_token_amount: 1_000,
+            maximum_token_a_amount: u64::MAX,
+            maximum_token_b_amount: u64::MAX,
+            max_ratio_deviation_bps: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        process_unsigned(&instruction),
+        Err(SwapError::InvalidSigner.into())
+    );
+}
+
+#[test]
+fn withdraw_requires_user_transfer_authority_signature() {
+    let instruction = withdraw_all_token_types(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        None,
+        None,
+        WithdrawAllTokenTypes {
+            pool_token_amount: 1_000,
+            minimum_token_a_amount: 0,
+            minimum_token_b_amount: 0,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        process_unsigned(&instruction),
+        Err(SwapError::InvalidSigner.into())
+    );
+}
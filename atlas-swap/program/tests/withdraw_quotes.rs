@@ -0,0 +1,128 @@
+//! QuoteWithdraw returns what a WithdrawAllTokenTypes of the same pool
+//! tokens transfers out, through the MIN_LP_SUPPLY cap and the dust sweep.
+
+mod common;
+
+use atlas_swap::{
+    constraints::MIN_LP_SUPPLY,
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    instruction::{quote_withdraw, withdraw_all_token_types, QuoteWithdraw, WithdrawAllTokenTypes},
+};
+use common::{return_data, ProgramTest, TestPool};
+use solana_program::pubkey::Pubkey;
+use std::convert::TryInto;
+
+const POOL_SUPPLY: u64 = 1_000_000;
+
+/// A constant product pool without fees, and the accounts its provider
+/// withdraws to, the provider holding all but the locked MIN_LP_SUPPLY
+struct WithdrawTest {
+    test: ProgramTest,
+    pool: TestPool,
+    destination_a: Pubkey,
+    destination_b: Pubkey,
+}
+
+impl WithdrawTest {
+    fn new(reserve_a: u64, reserve_b: u64) -> Self {
+        let mut test = ProgramTest::new(Fees {
+            fee_denominator: 10_000,
+            ..Fees::default()
+        });
+        let pool = test.create_pool(
+            SwapCurve {
+                curve_type: CurveType::ConstantProduct,
+                calculator: ConstantProductCurve.into(),
+            },
+            reserve_a,
+            reserve_b,
+        );
+        // the pool tokens of a pool created with a supply of POOL_SUPPLY
+        test.set_mint(&pool.pool_mint, |mint| mint.supply = POOL_SUPPLY);
+        test.set_token_account(&pool.provider_pool_tokens, |account| {
+            account.amount = POOL_SUPPLY - MIN_LP_SUPPLY as u64
+        });
+        let destination_a = test.add_token_account(&pool.token_a_mint, &pool.provider, 0);
+        let destination_b = test.add_token_account(&pool.token_b_mint, &pool.provider, 0);
+        Self {
+            test,
+            pool,
+            destination_a,
+            destination_b,
+        }
+    }
+
+    /// Token A and B amounts quoted for a withdrawal of `pool_token_amount`
+    fn quote(&mut self, pool_token_amount: u64) -> (u64, u64) {
+        let instruction = quote_withdraw(
+            &atlas_swap::id(),
+            &self.pool.swap,
+            &self.test.state,
+            &self.pool.pool_mint,
+            &self.pool.token_a,
+            &self.pool.token_b,
+            QuoteWithdraw { pool_token_amount },
+        )
+        .unwrap();
+        self.test.process(&instruction).unwrap();
+        let data = return_data().unwrap();
+        assert_eq!(data.len(), 16);
+        (
+            u64::from_le_bytes(data[..8].try_into().unwrap()),
+            u64::from_le_bytes(data[8..].try_into().unwrap()),
+        )
+    }
+
+    /// Token A and B amounts a withdrawal of `pool_token_amount` transfers
+    fn withdraw(&mut self, pool_token_amount: u64) -> (u64, u64) {
+        let instruction = withdraw_all_token_types(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &self.pool.swap,
+            &self.pool.authority,
+            &self.test.state,
+            &self.pool.provider,
+            &self.pool.pool_mint,
+            &self.pool.provider_pool_tokens,
+            &self.pool.token_a,
+            &self.pool.token_b,
+            &self.destination_a,
+            &self.destination_b,
+            None,
+            None,
+            WithdrawAllTokenTypes {
+                pool_token_amount,
+                minimum_token_a_amount: 0,
+                minimum_token_b_amount: 0,
+            },
+        )
+        .unwrap();
+        self.test.process(&instruction).unwrap();
+        (
+            self.test.token_amount(&self.destination_a),
+            self.test.token_amount(&self.destination_b),
+        )
+    }
+}
+
+#[test]
+fn quote_matches_partial_withdrawal() {
+    let mut pool = WithdrawTest::new(1_000_003, 2_000_007);
+    let quote = pool.quote(123_457);
+    assert_eq!(quote, (123_457, 246_914));
+    assert_eq!(pool.withdraw(123_457), quote);
+}
+
+#[test]
+fn quote_caps_pool_tokens_and_sweeps_dust() {
+    let mut pool = WithdrawTest::new(15, 25);
+    // capped to the pool supply less MIN_LP_SUPPLY, which leaves 2 of
+    // token A, swept but for one unit, and 3 of token B, kept
+    let quote = pool.quote(POOL_SUPPLY);
+    assert_eq!(quote, (14, 22));
+    assert_eq!(pool.withdraw(POOL_SUPPLY - MIN_LP_SUPPLY as u64), quote);
+}
@@ -0,0 +1,208 @@
+//! Zaps deposit or withdraw a single token, swapping the share of it that
+//! balances the other token at the pool's reserves.
+
+mod common;
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    error::SwapError,
+    instruction::{zap_in, zap_out, ZapIn, ZapOut},
+};
+use common::{ProgramTest, TestPool, TestUser};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 10_000_000;
+
+fn fees() -> Fees {
+    Fees {
+        constant_product_return_fee_numerator: 25,
+        constant_product_fixed_fee_numerator: 5,
+        fee_denominator: 10_000,
+        ..Fees::default()
+    }
+}
+
+fn pool(test: &mut ProgramTest) -> TestPool {
+    test.create_pool(
+        SwapCurve {
+            curve_type: CurveType::ConstantProduct,
+            calculator: ConstantProductCurve.into(),
+        },
+        RESERVE,
+        RESERVE,
+    )
+}
+
+/// Checks that the reserves tracked by the pool are its vault balances
+fn assert_reserves(test: &ProgramTest, pool: &TestPool) {
+    let swap_v2 = test.swap_v2(&pool.swap);
+    assert_eq!(swap_v2.token_a_reserve, test.token_amount(&pool.token_a));
+    assert_eq!(swap_v2.token_b_reserve, test.token_amount(&pool.token_b));
+}
+
+fn zap_in_instruction(
+    test: &ProgramTest,
+    pool: &TestPool,
+    user: &TestUser,
+    source: &Pubkey,
+    other: &Pubkey,
+    pool_fee: &Pubkey,
+    minimum_pool_token_amount: u64,
+) -> Instruction {
+    zap_in(
+        &atlas_swap::id(),
+        &spl_token::id(),
+        &pool.swap,
+        &pool.authority,
+        &user.wallet,
+        &test.state,
+        source,
+        other,
+        &pool.token_a,
+        &pool.token_b,
+        &pool.pool_mint,
+        pool_fee,
+        &user.pool_tokens,
+        None,
+        None,
+        None,
+        &[],
+        None,
+        ZapIn {
+            amount_in: AMOUNT,
+            minimum_pool_token_amount,
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn zap_in_deposits_a_single_token() {
+    let mut test = ProgramTest::new(fees());
+    let pool = pool(&mut test);
+    let fee_a = test.add_token_account(&pool.token_a_mint, &test.fee_owner.clone(), 0);
+    let fee_b = test.add_token_account(&pool.token_b_mint, &test.fee_owner.clone(), 0);
+    // the pool tokens of half the amount, less the fees of the swap
+    let minimum = AMOUNT / 2 * 99 / 100;
+
+    for a_to_b in [true, false] {
+        let user = if a_to_b {
+            test.add_user(&pool, AMOUNT, 0)
+        } else {
+            test.add_user(&pool, 0, AMOUNT)
+        };
+        let (source, other, pool_fee) = if a_to_b {
+            (user.token_a, user.token_b, fee_a)
+        } else {
+            (user.token_b, user.token_a, fee_b)
+        };
+        let too_many = zap_in_instruction(&test, &pool, &user, &source, &other, &pool_fee, AMOUNT);
+        assert_eq!(
+            test.process(&too_many),
+            Err(SwapError::ExceededSlippage.into())
+        );
+        let zap = zap_in_instruction(&test, &pool, &user, &source, &other, &pool_fee, minimum);
+        test.process(&zap).unwrap();
+
+        let minted = test.token_amount(&user.pool_tokens);
+        assert!(minted >= minimum && minted < AMOUNT / 2);
+        // the swap leaves out the share of its fees, the deposit uses all
+        // the rest
+        assert!(test.token_amount(&source) < AMOUNT / 100);
+        assert!(test.token_amount(&other) < AMOUNT / 1_000);
+        assert!(test.token_amount(&pool_fee) > 0);
+        assert_reserves(&test, &pool);
+    }
+
+    // pool tokens are neither token of the pool
+    let user = test.add_user(&pool, 0, 0);
+    let pool_tokens = test.add_token_account(&pool.pool_mint, &user.wallet, AMOUNT);
+    let zap = zap_in_instruction(&test, &pool, &user, &pool_tokens, &user.token_b, &fee_a, 1);
+    assert_eq!(
+        test.process(&zap),
+        Err(SwapError::IncorrectSwapAccount.into())
+    );
+}
+
+#[test]
+fn zap_out_withdraws_a_single_token() {
+    let mut test = ProgramTest::new(fees());
+    let pool = pool(&mut test);
+    let fee_a = test.add_token_account(&pool.token_a_mint, &test.fee_owner.clone(), 0);
+    let fee_b = test.add_token_account(&pool.token_b_mint, &test.fee_owner.clone(), 0);
+    let token_a = test.add_token_account(&pool.token_a_mint, &pool.provider, 0);
+    let token_b = test.add_token_account(&pool.token_b_mint, &pool.provider, 0);
+    let zap = |test: &ProgramTest, a_to_b: bool, minimum_amount_out: u64| {
+        // the other token is swapped, paying the fee in it
+        let (destination, other, pool_fee) = if a_to_b {
+            (token_b, token_a, fee_a)
+        } else {
+            (token_a, token_b, fee_b)
+        };
+        zap_out(
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &pool.swap,
+            &pool.authority,
+            &pool.provider,
+            &test.state,
+            &pool.pool_mint,
+            &pool.provider_pool_tokens,
+            &pool.token_a,
+            &pool.token_b,
+            &destination,
+            &other,
+            &pool_fee,
+            None,
+            None,
+            None,
+            None,
+            None,
+            &[],
+            ZapOut {
+                pool_token_amount: AMOUNT,
+                minimum_amount_out,
+            },
+        )
+        .unwrap()
+    };
+    for a_to_b in [false, true] {
+        let (destination, other) = if a_to_b {
+            (token_b, token_a)
+        } else {
+            (token_a, token_b)
+        };
+        let before = test.token_amount(&destination);
+        let other_before = test.token_amount(&other);
+        let pool_tokens = test.token_amount(&pool.provider_pool_tokens);
+        // both tokens of the pool tokens at the spot price, less the fees
+        // and the price impact of the swap
+        let destination_reserve = if a_to_b {
+            test.swap_v2(&pool.swap).token_b_reserve
+        } else {
+            test.swap_v2(&pool.swap).token_a_reserve
+        };
+        let value = 2 * destination_reserve * AMOUNT / test.mint(&pool.pool_mint).supply;
+        let minimum = value * 99 / 100;
+        assert_eq!(
+            test.process(&zap(&test, a_to_b, value)),
+            Err(SwapError::ExceededSlippage.into())
+        );
+        test.process(&zap(&test, a_to_b, minimum)).unwrap();
+
+        let amount_out = test.token_amount(&destination) - before;
+        assert!(amount_out >= minimum && amount_out < value);
+        // all of the other token withdrawn is swapped
+        assert_eq!(test.token_amount(&other), other_before);
+        assert_eq!(
+            test.token_amount(&pool.provider_pool_tokens),
+            pool_tokens - AMOUNT
+        );
+        assert_reserves(&test, &pool);
+    }
+}
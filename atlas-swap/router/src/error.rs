@@ -0,0 +1,51 @@
+//! Error types
+
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+use thiserror::Error;
+
+/// Errors that may be returned by the router program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum RouterError {
+    // 0.
+    /// Invalid instruction number passed in.
+    #[error("Invalid instruction")]
+    InvalidInstruction,
+    /// The route has no hops or more than the router allows.
+    #[error("Invalid route")]
+    InvalidRoute,
+    /// The route authority is not the program address of the router.
+    #[error("Invalid route authority")]
+    InvalidRouteAuthority,
+    /// An intermediate token account is not owned by the route authority.
+    #[error("Intermediate token account not owned by the route authority")]
+    InvalidIntermediateAccount,
+    /// The route returns less than the minimum amount out.
+    #[error("Route exceeds desired slippage limit")]
+    ExceededSlippage,
+
+    // 5.
+    /// The token account to wrap or unwrap does not hold native SOL.
+    #[error("Token account is not a native SOL account")]
+    ExpectedNativeAccount,
+    /// The deserialization of the account returned something besides State::Account.
+    #[error("Deserialized account is not an SPL Token account")]
+    ExpectedAccount,
+    /// General calculation failure due to overflow or underflow
+    #[error("General calculation failure due to overflow or underflow")]
+    CalculationFailure,
+    /// DESTINATION is not the associated token account of the user for the
+    /// destination mint.
+    #[error("Destination is not the user's associated token account")]
+    InvalidDestinationAccount,
+}
+impl From<RouterError> for ProgramError {
+    fn from(e: RouterError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for RouterError {
+    fn type_of() -> &'static str {
+        "Router Error"
+    }
+}
@@ -0,0 +1,234 @@
+//! Instruction types
+
+#![allow(clippy::too_many_arguments)]
+
+use crate::error::RouterError;
+use atlas_swap::{constraints::SWAP_ROUTE_TAG, instruction::SplitSwapPool};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use std::convert::TryInto;
+use std::mem::size_of;
+
+/// Maximum number of pools a route may go through
+pub const MAX_HOPS: u8 = 4;
+
+// Derived as the swap program derives them, for callers of either program
+pub use atlas_swap::pda::{get_associated_token_address, ASSOCIATED_TOKEN_PROGRAM_ID};
+
+/// Route instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    /// SOURCE amount to swap through the first pool
+    pub amount_in: u64,
+    /// Minimum amount of DESTINATION token to output from the last pool
+    pub minimum_amount_out: u64,
+    /// Number of pools the route goes through
+    pub hop_count: u8,
+    /// Whether to wrap `amount_in` lamports of the user into the native SOL
+    /// SOURCE account first
+    pub wrap_source: bool,
+    /// Whether to close the native SOL DESTINATION account to the user at the
+    /// end, unwrapping its whole balance
+    pub unwrap_destination: bool,
+    /// Whether to create DESTINATION as the user's associated token account
+    /// of the destination mint when it does not exist yet, funded by the user
+    pub create_destination: bool,
+}
+
+/// Instructions supported by the router program.
+#[repr(C)]
+#[derive(Debug, PartialEq)]
+pub enum RouterInstruction {
+    ///   Swap through a route of atlas-swap pools. The first pool is paid by
+    ///   the user, the following ones by the route authority out of the
+    ///   intermediate accounts, and only the amount credited to DESTINATION
+    ///   is checked against the slippage limit.
+    ///
+    ///   0. `[writable, signer]` User transfer authority, owner of SOURCE and DESTINATION
+    ///   1. `[]` Route authority, program address of SWAP_ROUTE_TAG
+    ///   2. `[]` atlas-swap program id
+    ///   3. `[]` atlas-swap global state account
+    ///   4. `[writable]` SOURCE token account
+    ///   5. `[writable]` DESTINATION token account
+    ///   6. `[]` Token program id
+    ///   7. `[]` System program id
+    ///   8. `[]` DESTINATION token mint, when creating DESTINATION
+    ///   9. `[]` Associated token account program id, when creating DESTINATION
+    ///   10. `[writable]` Intermediate token account of each hop but the last, owned by the route authority
+    ///   11. `[writable]` Token-swap, swap authority, pool account to swap INTO, pool account to swap FROM, pool token mint and fee account of each hop, each followed by the hop's price oracle, gate and protocol pool token accounts when the pool has them, and its SOURCE fee recipient accounts
+    Route(Route),
+}
+
+impl RouterInstruction {
+    /// Unpacks a byte buffer into a [RouterInstruction](enum.RouterInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input.split_first().ok_or(RouterError::InvalidInstruction)?;
+        Ok(match tag {
+            0 => {
+                let (amount_in, rest) = Self::unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = Self::unpack_u64(rest)?;
+                let (&hop_count, rest) = rest.split_first().ok_or(RouterError::InvalidInstruction)?;
+                let (&wrap_source, rest) = rest.split_first().ok_or(RouterError::InvalidInstruction)?;
+                let (&unwrap_destination, rest) =
+                    rest.split_first().ok_or(RouterError::InvalidInstruction)?;
+                let (&create_destination, _rest) =
+                    rest.split_first().ok_or(RouterError::InvalidInstruction)?;
+                Self::Route(Route {
+                    amount_in,
+                    minimum_amount_out,
+                    hop_count,
+                    wrap_source: wrap_source != 0,
+                    unwrap_destination: unwrap_destination != 0,
+                    create_destination: create_destination != 0,
+                })
+            }
+            _ => return Err(RouterError::InvalidInstruction.into()),
+        })
+    }
+
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() >= 8 {
+            let (amount, rest) = input.split_at(8);
+            let amount = amount
+                .get(..8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(RouterError::InvalidInstruction)?;
+            Ok((amount, rest))
+        } else {
+            Err(RouterError::InvalidInstruction.into())
+        }
+    }
+
+    /// Packs a [RouterInstruction](enum.RouterInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            Self::Route(Route {
+                amount_in,
+                minimum_amount_out,
+                hop_count,
+                wrap_source,
+                unwrap_destination,
+                create_destination,
+            }) => {
+                buf.push(0);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.push(*hop_count);
+                buf.push(*wrap_source as u8);
+                buf.push(*unwrap_destination as u8);
+                buf.push(*create_destination as u8);
+            }
+        }
+        buf
+    }
+}
+
+/// Address of the route authority of the router program
+pub fn find_route_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SWAP_ROUTE_TAG.as_bytes()], program_id)
+}
+
+/// Creates an associated token program 'create' instruction, the funding
+/// account paying the rent of the new account
+pub fn create_associated_token_account(
+    funding_pubkey: &Pubkey,
+    wallet_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let associated_token_pubkey =
+        get_associated_token_address(wallet_pubkey, token_program_id, mint_pubkey);
+    Instruction {
+        program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*funding_pubkey, true),
+            AccountMeta::new(associated_token_pubkey, false),
+            AccountMeta::new_readonly(*wallet_pubkey, false),
+            AccountMeta::new_readonly(*mint_pubkey, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+        data: vec![],
+    }
+}
+
+/// Creates a 'route' instruction. `hops` holds the pool accounts of each hop
+/// in order, and `fee_recipient_pubkeys` the fee recipient accounts of the
+/// SOURCE token of each hop. `destination_mint_pubkey` is only required when
+/// the route creates DESTINATION.
+pub fn route(
+    program_id: &Pubkey,
+    swap_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    user_transfer_authority_pubkey: &Pubkey,
+    global_state_pubkey: &Pubkey,
+    source_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    destination_mint_pubkey: Option<&Pubkey>,
+    intermediate_pubkeys: &[Pubkey],
+    hops: &[SplitSwapPool],
+    fee_recipient_pubkeys: &[Vec<Pubkey>],
+    instruction: Route,
+) -> Result<Instruction, ProgramError> {
+    let create_destination = instruction.create_destination;
+    let user_transfer_authority_meta = if instruction.wrap_source || create_destination {
+        AccountMeta::new(*user_transfer_authority_pubkey, true)
+    } else {
+        AccountMeta::new_readonly(*user_transfer_authority_pubkey, true)
+    };
+    let data = RouterInstruction::Route(instruction).pack();
+    let (route_authority_pubkey, _) = find_route_authority(program_id);
+
+    let mut accounts = vec![
+        user_transfer_authority_meta,
+        AccountMeta::new_readonly(route_authority_pubkey, false),
+        AccountMeta::new_readonly(*swap_program_id, false),
+        AccountMeta::new_readonly(*global_state_pubkey, false),
+        AccountMeta::new(*source_pubkey, false),
+        AccountMeta::new(*destination_pubkey, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if create_destination {
+        let destination_mint_pubkey =
+            destination_mint_pubkey.ok_or(RouterError::InvalidInstruction)?;
+        accounts.push(AccountMeta::new_readonly(*destination_mint_pubkey, false));
+        accounts.push(AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false));
+    }
+    for intermediate_pubkey in intermediate_pubkeys.iter() {
+        accounts.push(AccountMeta::new(*intermediate_pubkey, false));
+    }
+    for (index, hop) in hops.iter().enumerate() {
+        accounts.push(AccountMeta::new(hop.swap_pubkey, false));
+        accounts.push(AccountMeta::new_readonly(hop.authority_pubkey, false));
+        accounts.push(AccountMeta::new(hop.swap_source_pubkey, false));
+        accounts.push(AccountMeta::new(hop.swap_destination_pubkey, false));
+        accounts.push(AccountMeta::new(hop.pool_mint_pubkey, false));
+        accounts.push(AccountMeta::new(hop.pool_fee_pubkey, false));
+        if let Some(oracle_pubkey) = hop.oracle_pubkey {
+            accounts.push(AccountMeta::new_readonly(oracle_pubkey, false));
+        }
+        if let Some(gate_pubkey) = hop.gate_pubkey {
+            accounts.push(AccountMeta::new_readonly(gate_pubkey, false));
+        }
+        if let Some(protocol_liquidity_pubkey) = hop.protocol_liquidity_pubkey {
+            accounts.push(AccountMeta::new(protocol_liquidity_pubkey, false));
+        }
+        for fee_recipient_pubkey in fee_recipient_pubkeys.get(index).into_iter().flatten() {
+            accounts.push(AccountMeta::new(*fee_recipient_pubkey, false));
+        }
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
@@ -0,0 +1,16 @@
+#![deny(missing_docs)]
+
+//! Router composing atlas-swap pools into multi-hop swaps with a single
+//! slippage check.
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
+
+// Export the swap program so downstream users share its types
+pub use atlas_swap;
+
+solana_program::declare_id!("hMqchPQDN77fjBW86PPZ97Vu8JJnTzWSu6Hze7RwWFH");
@@ -0,0 +1,338 @@
+//! Program state processor
+
+use crate::{
+    error::RouterError,
+    instruction::{
+        create_associated_token_account, find_route_authority, get_associated_token_address,
+        Route, RouterInstruction, ASSOCIATED_TOKEN_PROGRAM_ID, MAX_HOPS,
+    },
+};
+use atlas_swap::{
+    constraints::SWAP_ROUTE_TAG,
+    cpi::{self, CpiContext, SwapAccounts},
+    instruction::Swap,
+    state::{GlobalStateVersion, SwapVersion},
+};
+use num_traits::FromPrimitive;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    decode_error::DecodeError,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::{PrintProgramError, ProgramError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+/// Program state handler.
+pub struct Processor {}
+impl Processor {
+    /// Unpacks a spl_token `Account`.
+    pub fn unpack_token_account(
+        account_info: &AccountInfo,
+        token_program_id: &Pubkey,
+    ) -> Result<spl_token::state::Account, RouterError> {
+        if account_info.owner != token_program_id {
+            Err(RouterError::ExpectedAccount)
+        } else {
+            spl_token::state::Account::unpack(&account_info.data.borrow())
+                .map_err(|_| RouterError::ExpectedAccount)
+        }
+    }
+
+    /// Processes a [Route](enum.Instruction.html).
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_route(
+        program_id: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        hop_count: u8,
+        wrap_source: bool,
+        unwrap_destination: bool,
+        create_destination: bool,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_transfer_authority_info = next_account_info(account_info_iter)?;
+        let route_authority_info = next_account_info(account_info_iter)?;
+        let swap_program_info = next_account_info(account_info_iter)?;
+        let state_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let destination_mint_info = if create_destination {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+        let associated_token_program_info = if create_destination {
+            Some(next_account_info(account_info_iter)?)
+        } else {
+            None
+        };
+
+        if hop_count == 0 || hop_count > MAX_HOPS {
+            return Err(RouterError::InvalidRoute.into());
+        }
+        let (route_authority, bump) = find_route_authority(program_id);
+        if *route_authority_info.key != route_authority {
+            return Err(RouterError::InvalidRouteAuthority.into());
+        }
+        if *swap_program_info.key != atlas_swap::id() || *token_program_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let intermediate_infos = (1..hop_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+        for intermediate_info in intermediate_infos.iter() {
+            let intermediate_account =
+                Self::unpack_token_account(intermediate_info, token_program_info.key)?;
+            if intermediate_account.owner != route_authority {
+                return Err(RouterError::InvalidIntermediateAccount.into());
+            }
+        }
+        let fee_recipient_count = GlobalStateVersion::unpack(&state_info.data.borrow())?
+            .fee_recipients()
+            .count();
+
+        if wrap_source {
+            if Self::unpack_token_account(source_info, token_program_info.key)?.mint
+                != spl_token::native_mint::id()
+            {
+                return Err(RouterError::ExpectedNativeAccount.into());
+            }
+            if *system_program_info.key != system_program::id() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            invoke(
+                &system_instruction::transfer(
+                    user_transfer_authority_info.key,
+                    source_info.key,
+                    amount_in,
+                ),
+                &[
+                    user_transfer_authority_info.clone(),
+                    source_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+            invoke(
+                &spl_token::instruction::sync_native(token_program_info.key, source_info.key)?,
+                &[source_info.clone(), token_program_info.clone()],
+            )?;
+        }
+
+        if let (Some(destination_mint_info), Some(associated_token_program_info)) =
+            (destination_mint_info, associated_token_program_info)
+        {
+            if *associated_token_program_info.key != ASSOCIATED_TOKEN_PROGRAM_ID
+                || *system_program_info.key != system_program::id()
+            {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            if *destination_info.key
+                != get_associated_token_address(
+                    user_transfer_authority_info.key,
+                    token_program_info.key,
+                    destination_mint_info.key,
+                )
+            {
+                return Err(RouterError::InvalidDestinationAccount.into());
+            }
+            // an existing account is used as is, and checked by the swaps
+            if destination_info.data_is_empty() {
+                invoke(
+                    &create_associated_token_account(
+                        user_transfer_authority_info.key,
+                        user_transfer_authority_info.key,
+                        destination_mint_info.key,
+                        token_program_info.key,
+                    ),
+                    &[
+                        user_transfer_authority_info.clone(),
+                        destination_info.clone(),
+                        destination_mint_info.clone(),
+                        system_program_info.clone(),
+                        token_program_info.clone(),
+                        associated_token_program_info.clone(),
+                    ],
+                )?;
+            }
+        }
+
+        let destination_before =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        let mut hop_amount_in = amount_in;
+        for hop in 0..usize::from(hop_count) {
+            // the first hop spends the user's tokens, the others what the
+            // previous hop left in the route authority's account
+            let (authority_info, hop_source_info) = if hop == 0 {
+                (user_transfer_authority_info, source_info)
+            } else {
+                (route_authority_info, intermediate_infos[hop - 1])
+            };
+            let hop_destination_info = intermediate_infos.get(hop).copied().unwrap_or(destination_info);
+            let hop_destination_before =
+                Self::unpack_token_account(hop_destination_info, token_program_info.key)?.amount;
+
+            let swap_info = next_account_info(account_info_iter)?;
+            let swap_authority_info = next_account_info(account_info_iter)?;
+            let swap_source_info = next_account_info(account_info_iter)?;
+            let swap_destination_info = next_account_info(account_info_iter)?;
+            let pool_mint_info = next_account_info(account_info_iter)?;
+            let pool_fee_info = next_account_info(account_info_iter)?;
+            if swap_info.owner != swap_program_info.key {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            // the pool's optional swap accounts follow its pool accounts
+            let token_swap = SwapVersion::unpack(&swap_info.data.borrow())?;
+            let oracle_info = if token_swap.external_oracle().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            let gate_info = if token_swap.gate().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            let protocol_liquidity_info = if token_swap.protocol_liquidity_bps().is_some() {
+                Some(next_account_info(account_info_iter)?)
+            } else {
+                None
+            };
+            let fee_recipient_infos = (0..fee_recipient_count)
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, ProgramError>>()?;
+
+            let swap_accounts = SwapAccounts {
+                swap: swap_info.clone(),
+                authority: swap_authority_info.clone(),
+                user_transfer_authority: authority_info.clone(),
+                global_state: state_info.clone(),
+                source: hop_source_info.clone(),
+                swap_source: swap_source_info.clone(),
+                swap_destination: swap_destination_info.clone(),
+                destination: hop_destination_info.clone(),
+                pool_mint: pool_mint_info.clone(),
+                pool_fee: Some(pool_fee_info.clone()),
+                token_program: token_program_info.clone(),
+                oracle: oracle_info.cloned(),
+                gate: gate_info.cloned(),
+                protocol_liquidity: protocol_liquidity_info.cloned(),
+                fee_recipients: fee_recipient_infos.into_iter().cloned().collect(),
+            };
+            let route_authority_seeds: &[&[u8]] = &[SWAP_ROUTE_TAG.as_bytes(), &[bump]];
+            let signer_seeds: &[&[&[u8]]] = if hop == 0 { &[] } else { &[route_authority_seeds] };
+            cpi::swap(
+                CpiContext::with_signer(swap_program_info.clone(), swap_accounts, signer_seeds),
+                Swap {
+                    amount_in: hop_amount_in,
+                    minimum_amount_out: 0,
+                    max_price_impact_bps: None,
+                    tip: 0,
+                    memo: Vec::new(),
+                    trade_direction: None,
+                    fee_discount: false,
+                },
+            )?;
+
+            let hop_destination_after =
+                Self::unpack_token_account(hop_destination_info, token_program_info.key)?.amount;
+            hop_amount_in = hop_destination_after
+                .checked_sub(hop_destination_before)
+                .ok_or(RouterError::CalculationFailure)?;
+        }
+
+        let destination_after =
+            Self::unpack_token_account(destination_info, token_program_info.key)?.amount;
+        let amount_out = destination_after
+            .checked_sub(destination_before)
+            .ok_or(RouterError::CalculationFailure)?;
+        if amount_out < minimum_amount_out {
+            return Err(RouterError::ExceededSlippage.into());
+        }
+
+        if unwrap_destination {
+            if Self::unpack_token_account(destination_info, token_program_info.key)?.mint
+                != spl_token::native_mint::id()
+            {
+                return Err(RouterError::ExpectedNativeAccount.into());
+            }
+            invoke(
+                &spl_token::instruction::close_account(
+                    token_program_info.key,
+                    destination_info.key,
+                    user_transfer_authority_info.key,
+                    user_transfer_authority_info.key,
+                    &[],
+                )?,
+                &[
+                    destination_info.clone(),
+                    user_transfer_authority_info.clone(),
+                    token_program_info.clone(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Processes an [Instruction](enum.Instruction.html).
+    pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+        let instruction = RouterInstruction::unpack(input)?;
+        match instruction {
+            RouterInstruction::Route(Route {
+                amount_in,
+                minimum_amount_out,
+                hop_count,
+                wrap_source,
+                unwrap_destination,
+                create_destination,
+            }) => {
+                msg!("Instruction: Route");
+                Self::process_route(
+                    program_id,
+                    amount_in,
+                    minimum_amount_out,
+                    hop_count,
+                    wrap_source,
+                    unwrap_destination,
+                    create_destination,
+                    accounts,
+                )
+            }
+        }
+    }
+}
+
+impl PrintProgramError for RouterError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        match self {
+            RouterError::InvalidInstruction => msg!("Error: InvalidInstruction"),
+            RouterError::InvalidRoute => msg!("Error: Invalid route"),
+            RouterError::InvalidRouteAuthority => msg!("Error: Invalid route authority"),
+            RouterError::InvalidIntermediateAccount => {
+                msg!("Error: Intermediate token account not owned by the route authority")
+            }
+            RouterError::ExceededSlippage => {
+                msg!("Error: Route exceeds desired slippage limit")
+            }
+            RouterError::ExpectedNativeAccount => {
+                msg!("Error: Token account is not a native SOL account")
+            }
+            RouterError::ExpectedAccount => {
+                msg!("Error: Deserialized account is not an SPL Token account")
+            }
+            RouterError::CalculationFailure => msg!("Error: CalculationFailure"),
+            RouterError::InvalidDestinationAccount => {
+                msg!("Error: Destination is not the user's associated token account")
+            }
+        }
+    }
+}
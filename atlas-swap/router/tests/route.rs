@@ -0,0 +1,574 @@
+//! Routes run natively through the router, atlas-swap and the token program,
+//! with the accounts laid out as the runtime serializes them so the
+//! destination account the router creates can grow in place.
+
+use atlas_swap::{
+    curve::{
+        base::{CurveType, SwapCurve},
+        constant_product::ConstantProductCurve,
+        fees::Fees,
+    },
+    instruction::SplitSwapPool,
+    pda::find_global_state,
+    state::{GlobalState, GlobalStateVersion, SwapV2, SwapVersion},
+};
+use atlas_swap_router::{
+    error::RouterError,
+    instruction::{
+        find_route_authority, get_associated_token_address, route, Route,
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+    },
+    processor::Processor,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{self, ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use spl_token::state::{Account, AccountState, Mint};
+use std::{collections::HashMap, convert::TryInto, mem::size_of};
+
+const RESERVE: u64 = 1_000_000;
+const AMOUNT_IN: u64 = 10_000;
+
+struct TestSyscallStubs {}
+impl program_stubs::SyscallStubs for TestSyscallStubs {
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let signers = signers_seeds
+            .iter()
+            .flat_map(|seeds| {
+                [atlas_swap_router::id(), atlas_swap::id()]
+                    .iter()
+                    .filter_map(|program_id| Pubkey::create_program_address(seeds, program_id).ok())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut new_account_infos = vec![];
+        for meta in instruction.accounts.iter() {
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| *account_info.key == meta.pubkey)
+                .expect("account meta missing from the account infos");
+            let mut new_account_info = account_info.clone();
+            if signers.contains(account_info.key) {
+                new_account_info.is_signer = true;
+            }
+            assert!(
+                !meta.is_signer || new_account_info.is_signer,
+                "{} did not sign",
+                meta.pubkey
+            );
+            new_account_infos.push(new_account_info);
+        }
+
+        if instruction.program_id == atlas_swap::id() {
+            atlas_swap::processor::Processor::process(
+                &instruction.program_id,
+                &new_account_infos,
+                &instruction.data,
+            )
+        } else if instruction.program_id == system_program::id() {
+            process_system_instruction(&new_account_infos, &instruction.data)
+        } else if instruction.program_id == ASSOCIATED_TOKEN_PROGRAM_ID {
+            create_associated_token_account(&new_account_infos)
+        } else {
+            spl_token::processor::Processor::process(
+                &instruction.program_id,
+                &new_account_infos,
+                &instruction.data,
+            )
+        }
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Clock) = Clock::default();
+        }
+        SUCCESS
+    }
+}
+
+/// The lamport transfers the router asks of the system program
+fn process_system_instruction(account_infos: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match u32::from_le_bytes(data[..4].try_into().unwrap()) {
+        2 => {
+            let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+            let mut from = account_infos[0].lamports.borrow_mut();
+            **from = from
+                .checked_sub(lamports)
+                .ok_or(ProgramError::InsufficientFunds)?;
+            **account_infos[1].lamports.borrow_mut() += lamports;
+            Ok(())
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// The associated token program creating the account of a wallet for a
+/// mint, the funding account paying its rent
+fn create_associated_token_account(account_infos: &[AccountInfo]) -> ProgramResult {
+    let funding_info = &account_infos[0];
+    let associated_token_info = &account_infos[1];
+    let wallet_info = &account_infos[2];
+    let mint_info = &account_infos[3];
+    let token_program_info = &account_infos[5];
+    assert_eq!(
+        *associated_token_info.key,
+        get_associated_token_address(wallet_info.key, token_program_info.key, mint_info.key)
+    );
+    assert!(associated_token_info.data_is_empty());
+
+    let lamports = Rent::default().minimum_balance(Account::LEN);
+    let mut funding_lamports = funding_info.lamports.borrow_mut();
+    **funding_lamports = funding_lamports
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    **associated_token_info.lamports.borrow_mut() += lamports;
+    associated_token_info.realloc(Account::LEN, true)?;
+    associated_token_info.assign(token_program_info.key);
+    Account::pack(
+        Account {
+            mint: *mint_info.key,
+            owner: *wallet_info.key,
+            state: AccountState::Initialized,
+            ..Account::default()
+        },
+        &mut associated_token_info.data.borrow_mut(),
+    )
+}
+
+fn test_syscall_stubs() {
+    use std::sync::Once;
+    static ONCE: Once = Once::new();
+
+    ONCE.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(TestSyscallStubs {}));
+    });
+}
+
+struct TestAccount {
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+/// A pool of two of the route's tokens and the fee account of each of them
+struct TestPool {
+    swap: Pubkey,
+    authority: Pubkey,
+    pool_mint: Pubkey,
+    vaults: HashMap<Pubkey, Pubkey>,
+    fee_accounts: HashMap<Pubkey, Pubkey>,
+}
+
+impl TestPool {
+    fn hop(&self, source_mint: &Pubkey, destination_mint: &Pubkey) -> SplitSwapPool {
+        SplitSwapPool {
+            swap_pubkey: self.swap,
+            authority_pubkey: self.authority,
+            swap_source_pubkey: self.vaults[source_mint],
+            swap_destination_pubkey: self.vaults[destination_mint],
+            pool_mint_pubkey: self.pool_mint,
+            pool_fee_pubkey: self.fee_accounts[source_mint],
+            oracle_pubkey: None,
+            gate_pubkey: None,
+            protocol_liquidity_pubkey: None,
+        }
+    }
+}
+
+/// Two constant product pools without fees chaining mints A, B and C, and
+/// a user holding token A
+struct TestRoute {
+    accounts: HashMap<Pubkey, TestAccount>,
+    global_state: Pubkey,
+    user: Pubkey,
+    source: Pubkey,
+    mints: [Pubkey; 3],
+    pools: [TestPool; 2],
+}
+
+impl TestRoute {
+    fn new() -> Self {
+        let fee_owner = Pubkey::new_unique();
+        let mut test = Self {
+            accounts: HashMap::new(),
+            global_state: find_global_state(&atlas_swap::id()).0,
+            user: Pubkey::new_unique(),
+            source: Pubkey::new_unique(),
+            mints: [
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            ],
+            pools: [Self::pool(), Self::pool()],
+        };
+
+        let mut state_data = vec![0; GlobalStateVersion::LATEST_LEN];
+        GlobalStateVersion::pack(
+            &GlobalState {
+                is_initialized: true,
+                fee_owner,
+                fees: Fees {
+                    fee_denominator: 10_000,
+                    ..Fees::default()
+                },
+                ..GlobalState::default()
+            },
+            &mut state_data,
+        )
+        .unwrap();
+        test.add(test.global_state, atlas_swap::id(), state_data);
+        for mint in test.mints.clone().iter() {
+            test.add_mint(*mint, Pubkey::new_unique(), 0);
+        }
+        let (user, source, token_a_mint) = (test.user, test.source, test.mints[0]);
+        test.accounts.insert(
+            user,
+            TestAccount {
+                owner: system_program::id(),
+                lamports: 1_000_000_000,
+                data: vec![],
+            },
+        );
+        test.add_token_account(source, user, token_a_mint, AMOUNT_IN);
+
+        for (index, pool) in test.pools.iter_mut().enumerate() {
+            let (authority, nonce) =
+                Pubkey::find_program_address(&[pool.swap.as_ref()], &atlas_swap::id());
+            pool.authority = authority;
+            let (token_a_mint, token_b_mint) = (test.mints[index], test.mints[index + 1]);
+            for mint in [token_a_mint, token_b_mint].iter() {
+                pool.vaults.insert(*mint, Pubkey::new_unique());
+                pool.fee_accounts.insert(*mint, Pubkey::new_unique());
+            }
+            let mut swap_data = vec![0; SwapVersion::LATEST_LEN];
+            SwapVersion::pack(
+                SwapVersion::SwapV2(SwapV2 {
+                    is_initialized: true,
+                    nonce,
+                    token_program_id: spl_token::id(),
+                    token_a: pool.vaults[&token_a_mint],
+                    token_b: pool.vaults[&token_b_mint],
+                    pool_mint: pool.pool_mint,
+                    token_a_mint,
+                    token_b_mint,
+                    swap_curve: SwapCurve {
+                        curve_type: CurveType::ConstantProduct,
+                        calculator: ConstantProductCurve.into(),
+                    },
+                    token_a_reserve: RESERVE,
+                    token_b_reserve: RESERVE,
+                    ..SwapV2::default()
+                }),
+                &mut swap_data,
+            )
+            .unwrap();
+            test.accounts.insert(
+                pool.swap,
+                TestAccount {
+                    owner: atlas_swap::id(),
+                    lamports: 1,
+                    data: swap_data,
+                },
+            );
+        }
+        for index in 0..2 {
+            let pool_mint = test.pools[index].pool_mint;
+            let authority = test.pools[index].authority;
+            test.add_mint(pool_mint, authority, RESERVE);
+            for mint in test.mints[index..index + 2].to_vec().iter() {
+                let vault = test.pools[index].vaults[mint];
+                let fee_account = test.pools[index].fee_accounts[mint];
+                test.add_token_account(vault, authority, *mint, RESERVE);
+                test.add_token_account(fee_account, fee_owner, *mint, 0);
+            }
+        }
+        test
+    }
+
+    fn pool() -> TestPool {
+        TestPool {
+            swap: Pubkey::new_unique(),
+            authority: Pubkey::default(),
+            pool_mint: Pubkey::new_unique(),
+            vaults: HashMap::new(),
+            fee_accounts: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, key: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        self.accounts.insert(
+            key,
+            TestAccount {
+                owner,
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+            },
+        );
+    }
+
+    fn add_mint(&mut self, key: Pubkey, mint_authority: Pubkey, supply: u64) {
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::Some(mint_authority),
+                supply,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add(key, spl_token::id(), data);
+    }
+
+    fn add_token_account(&mut self, key: Pubkey, owner: Pubkey, mint: Pubkey, amount: u64) {
+        let mut data = vec![0; Account::LEN];
+        Account::pack(
+            Account {
+                mint,
+                owner,
+                amount,
+                state: AccountState::Initialized,
+                ..Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add(key, spl_token::id(), data);
+    }
+
+    fn token_account(&self, key: &Pubkey) -> Account {
+        Account::unpack(&self.accounts[key].data).unwrap()
+    }
+
+    fn balance(&self, key: &Pubkey) -> u64 {
+        self.token_account(key).amount
+    }
+
+    /// A route of `AMOUNT_IN` token A from the user's SOURCE account to
+    /// token C, through the intermediate token B account of the route
+    /// authority
+    fn route(
+        &self,
+        destination: &Pubkey,
+        intermediate: &Pubkey,
+        minimum_amount_out: u64,
+        create_destination: bool,
+    ) -> Instruction {
+        let [token_a_mint, token_b_mint, token_c_mint] = self.mints;
+        route(
+            &atlas_swap_router::id(),
+            &atlas_swap::id(),
+            &spl_token::id(),
+            &self.user,
+            &self.global_state,
+            &self.source,
+            destination,
+            Some(&token_c_mint),
+            &[*intermediate],
+            &[
+                self.pools[0].hop(&token_a_mint, &token_b_mint),
+                self.pools[1].hop(&token_b_mint, &token_c_mint),
+            ],
+            &[],
+            Route {
+                amount_in: AMOUNT_IN,
+                minimum_amount_out,
+                hop_count: 2,
+                wrap_source: false,
+                unwrap_destination: false,
+                create_destination,
+            },
+        )
+        .unwrap()
+    }
+
+    /// An empty token B account of the route authority
+    fn add_intermediate(&mut self) -> Pubkey {
+        let intermediate = Pubkey::new_unique();
+        let route_authority = find_route_authority(&atlas_swap_router::id()).0;
+        let token_b_mint = self.mints[1];
+        self.add_token_account(intermediate, route_authority, token_b_mint, 0);
+        intermediate
+    }
+
+    /// Runs a router instruction, committing its accounts when it succeeds,
+    /// accounts missing from the tree being empty system accounts such as
+    /// the program ids
+    fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        test_syscall_stubs();
+        for meta in instruction.accounts.iter() {
+            self.accounts.entry(meta.pubkey).or_insert(TestAccount {
+                owner: system_program::id(),
+                lamports: 0,
+                data: vec![],
+            });
+        }
+        let mut input = self.serialize(instruction);
+        let (program_id, account_infos, data) =
+            unsafe { entrypoint::deserialize(input.as_mut_ptr() as *mut u8) };
+        let result = Processor::process(program_id, &account_infos, data);
+        if result.is_ok() {
+            for account_info in account_infos.iter() {
+                let account = self.accounts.get_mut(account_info.key).unwrap();
+                account.owner = *account_info.owner;
+                account.lamports = account_info.lamports();
+                account.data = account_info.data.borrow().to_vec();
+            }
+        }
+        result
+    }
+
+    /// The input of the program entrypoint for `instruction`, in words so
+    /// its fields are aligned
+    fn serialize(&self, instruction: &Instruction) -> Vec<u64> {
+        let mut input = vec![];
+        input.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+        for (index, meta) in instruction.accounts.iter().enumerate() {
+            if let Some(position) = instruction.accounts[..index]
+                .iter()
+                .position(|other| other.pubkey == meta.pubkey)
+            {
+                input.push(position as u8);
+                input.extend_from_slice(&[0; 7]);
+                continue;
+            }
+            let account = &self.accounts[&meta.pubkey];
+            input.push(u8::MAX);
+            input.push(meta.is_signer as u8);
+            input.push(meta.is_writable as u8);
+            input.push(0);
+            input.extend_from_slice(&[0; 4]);
+            input.extend_from_slice(meta.pubkey.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len() + (8 - input.len() % 8) % 8, 0);
+            input.extend_from_slice(&0u64.to_le_bytes());
+        }
+        input.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        input.extend_from_slice(&instruction.data);
+        input.extend_from_slice(instruction.program_id.as_ref());
+
+        let mut words = vec![0u64; input.chunks(size_of::<u64>()).len()];
+        for (word, bytes) in words.iter_mut().zip(input.chunks(size_of::<u64>())) {
+            let mut le_bytes = [0; 8];
+            le_bytes[..bytes.len()].copy_from_slice(bytes);
+            *word = u64::from_le_bytes(le_bytes);
+        }
+        words
+    }
+}
+
+#[test]
+fn route_swaps_through_every_hop() {
+    let mut test = TestRoute::new();
+    let destination = Pubkey::new_unique();
+    let (user, token_c_mint) = (test.user, test.mints[2]);
+    test.add_token_account(destination, user, token_c_mint, 0);
+    let intermediate = test.add_intermediate();
+    test.process(&test.route(&destination, &intermediate, 1, false))
+        .unwrap();
+
+    let [token_a_mint, token_b_mint, token_c_mint] = test.mints;
+    let amount_out = test.balance(&destination);
+    assert!(amount_out > 0);
+    // the input the curves round away goes back to the account of each hop
+    let first_pool = &test.pools[0];
+    let second_pool = &test.pools[1];
+    let token_a_in = AMOUNT_IN - test.balance(&test.source);
+    let token_b_out = RESERVE - test.balance(&first_pool.vaults[&token_b_mint]);
+    let token_b_in = token_b_out - test.balance(&intermediate);
+    assert!(AMOUNT_IN - token_a_in <= 1);
+    assert!(token_b_out - token_b_in <= 1);
+    assert_eq!(
+        test.balance(&first_pool.vaults[&token_a_mint]),
+        RESERVE + token_a_in
+    );
+    assert_eq!(
+        test.balance(&second_pool.vaults[&token_b_mint]),
+        RESERVE + token_b_in
+    );
+    assert_eq!(
+        test.balance(&second_pool.vaults[&token_c_mint]),
+        RESERVE - amount_out
+    );
+}
+
+#[test]
+fn route_checks_slippage_on_the_final_output() {
+    let mut test = TestRoute::new();
+    let destination = Pubkey::new_unique();
+    let (user, token_c_mint) = (test.user, test.mints[2]);
+    test.add_token_account(destination, user, token_c_mint, 0);
+    let intermediate = test.add_intermediate();
+    test.process(&test.route(&destination, &intermediate, 0, false))
+        .unwrap();
+    let amount_out = test.balance(&destination);
+
+    // the same route against the same pools, one token short of a limit
+    // no hop checks on its own
+    let mut test = TestRoute::new();
+    let (user, token_c_mint) = (test.user, test.mints[2]);
+    test.add_token_account(destination, user, token_c_mint, 0);
+    let intermediate = test.add_intermediate();
+    assert_eq!(
+        test.process(&test.route(&destination, &intermediate, amount_out + 1, false)),
+        Err(RouterError::ExceededSlippage.into())
+    );
+    assert_eq!(test.balance(&test.source), AMOUNT_IN);
+    test.process(&test.route(&destination, &intermediate, amount_out, false))
+        .unwrap();
+    assert_eq!(test.balance(&destination), amount_out);
+}
+
+#[test]
+fn route_creates_the_destination_associated_token_account() {
+    let mut test = TestRoute::new();
+    let (user, token_c_mint) = (test.user, test.mints[2]);
+    let destination = get_associated_token_address(&user, &spl_token::id(), &token_c_mint);
+    let intermediate = test.add_intermediate();
+    let other_destination = Pubkey::new_unique();
+    assert_eq!(
+        test.process(&test.route(&other_destination, &intermediate, 1, true)),
+        Err(RouterError::InvalidDestinationAccount.into())
+    );
+
+    let lamports_before = test.accounts[&user].lamports;
+    test.process(&test.route(&destination, &intermediate, 1, true))
+        .unwrap();
+    let rent = Rent::default().minimum_balance(Account::LEN);
+    assert_eq!(test.accounts[&user].lamports, lamports_before - rent);
+    assert_eq!(test.accounts[&destination].owner, spl_token::id());
+    let destination_account = test.token_account(&destination);
+    assert_eq!(destination_account.owner, user);
+    assert_eq!(destination_account.mint, token_c_mint);
+    assert!(destination_account.amount > 0);
+
+    // an existing account is swapped into as is
+    let amount_out = destination_account.amount;
+    let source = test.source;
+    test.add_token_account(source, user, test.mints[0], AMOUNT_IN);
+    test.process(&test.route(&destination, &intermediate, 1, true))
+        .unwrap();
+    assert_eq!(test.accounts[&user].lamports, lamports_before - rent);
+    assert!(test.balance(&destination) > amount_out);
+}